@@ -3,7 +3,10 @@
 //! This module handles argument parsing and user interface only.
 //! NO logic or database operations are performed here.
 
-use clap::Parser;
+pub mod profile;
+
+use clap::{CommandFactory, Parser};
+use schemars::JsonSchema;
 use serde::Serialize;
 use serde_json::Value;
 
@@ -20,6 +23,49 @@ pub struct Cli {
     /// Enable verbose logging.
     #[arg(short, long, global = true)]
     pub verbose: bool,
+
+    /// Print nothing on success; the exit code still reports the outcome.
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Describe the steps the command would take, without running it.
+    ///
+    /// Unlike `--dry-run`, nothing is resolved or read; the description is
+    /// derived from the arguments alone.
+    #[arg(long, global = true)]
+    pub explain: bool,
+
+    /// Skip (or refuse to patch) source files larger than this many bytes.
+    #[arg(
+        long,
+        global = true,
+        value_name = "BYTES",
+        default_value_t = crate::ingest::DEFAULT_MAX_FILE_SIZE
+    )]
+    pub max_file_size: u64,
+
+    /// Refuse source files whose syntax tree nests deeper than this many levels.
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        default_value_t = crate::ingest::DEFAULT_MAX_AST_DEPTH
+    )]
+    pub max_ast_depth: usize,
+
+    /// Use this `Cargo.toml` instead of searching upward from the target file.
+    ///
+    /// Validation runs in its directory and the cross-file reference scan is
+    /// bounded to the files below it, for layouts where the manifest does not
+    /// sit above the sources.
+    #[arg(long, global = true, value_name = "FILE")]
+    pub manifest_path: Option<std::path::PathBuf>,
+
+    /// Fill in flags from `[profile.<NAME>]` in the nearest `splice.toml`.
+    ///
+    /// Flags given on the command line take precedence over the profile's.
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
 }
 
 /// Available Splice commands.
@@ -32,8 +78,17 @@ pub enum Commands {
         file: std::path::PathBuf,
 
         /// Symbol name to delete.
-        #[arg(short, long)]
-        symbol: String,
+        #[arg(short, long, required_unless_present = "symbol_regex")]
+        symbol: Option<String>,
+
+        /// Delete every symbol in the file whose name matches this regex, with all
+        /// of their references, as a single validated batch.
+        #[arg(
+            long,
+            value_name = "PATTERN",
+            conflicts_with_all = ["symbol", "context_symbol"]
+        )]
+        symbol_regex: Option<String>,
 
         /// Optional symbol kind filter.
         #[arg(short, long)]
@@ -51,6 +106,34 @@ pub enum Commands {
         #[arg(long)]
         create_backup: bool,
 
+        /// Report only aggregate counts, omitting the per-file and per-span arrays.
+        #[arg(long)]
+        summary_only: bool,
+
+        /// Report the spans that would be deleted without modifying any file.
+        ///
+        /// The reference scan is cached so an immediately following delete can reuse it.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Delete even if the symbol is re-exported at the crate root with `pub use`.
+        #[arg(long)]
+        force: bool,
+
+        /// Refuse to delete when more than N references would be removed.
+        #[arg(long, value_name = "N")]
+        max_references: Option<usize>,
+
+        /// Only remove references inside this enclosing symbol, keeping the definition
+        /// and every reference elsewhere.
+        #[arg(long, value_name = "NAME")]
+        context_symbol: Option<String>,
+
+        /// After deleting, collapse runs of more than N blank lines (default 2) in the
+        /// modified files.
+        #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "2")]
+        trim_blank_lines: Option<usize>,
+
         /// Optional operation ID for auditing (auto-generated UUID if not provided).
         #[arg(long)]
         operation_id: Option<String>,
@@ -58,6 +141,14 @@ pub enum Commands {
         /// Optional JSON metadata to attach to this operation.
         #[arg(long)]
         metadata: Option<String>,
+
+        /// Cargo feature selection for the cargo check gate.
+        #[command(flatten)]
+        cargo_features: CargoFeatureArgs,
+
+        /// Validation gates to run.
+        #[command(flatten)]
+        gate_args: GateArgs,
     },
 
     /// Apply a patch to a symbol's span.
@@ -67,13 +158,38 @@ pub enum Commands {
         file: Option<std::path::PathBuf>,
 
         /// Symbol name to patch.
-        #[arg(short = 's', long, required_unless_present = "batch")]
+        #[arg(
+            short = 's',
+            long,
+            required_unless_present_any = ["batch", "insert_after", "insert_before"],
+            conflicts_with_all = ["insert_after", "insert_before"]
+        )]
         symbol: Option<String>,
 
+        /// Insert the `--with` content after this symbol instead of replacing it.
+        #[arg(long, value_name = "SYMBOL", conflicts_with_all = ["batch", "insert_before"])]
+        insert_after: Option<String>,
+
+        /// Insert the `--with` content before this symbol instead of replacing it.
+        #[arg(long, value_name = "SYMBOL", conflicts_with = "batch")]
+        insert_before: Option<String>,
+
         /// Optional symbol kind filter.
         #[arg(short, long, conflicts_with = "batch")]
         kind: Option<SymbolKind>,
 
+        /// Select an overloaded Java/C++ callable by parameter types, e.g. "(int, String)".
+        #[arg(long, value_name = "TYPES", conflicts_with = "batch")]
+        signature: Option<String>,
+
+        /// Only match test-only symbols (inside `#[cfg(test)]` modules or `#[test]` functions).
+        #[arg(long, conflicts_with_all = ["batch", "no_test"])]
+        test: bool,
+
+        /// Never match test-only symbols.
+        #[arg(long, conflicts_with = "batch")]
+        no_test: bool,
+
         /// Optional validation mode (off, os, path).
         #[arg(long, value_name = "MODE")]
         analyzer: Option<AnalyzerMode>,
@@ -99,7 +215,15 @@ pub enum Commands {
         #[arg(long, conflicts_with = "batch")]
         preview: bool,
 
+        /// Persistent cargo target directory for preview builds, reused across previews.
+        ///
+        /// Must not be the workspace's own `target/`.
+        #[arg(long, value_name = "DIR", requires = "preview")]
+        preview_target_dir: Option<std::path::PathBuf>,
+
         /// Create a backup before patching.
+        ///
+        /// Ignored with `--preview`, which never writes to the real workspace.
         #[arg(long)]
         create_backup: bool,
 
@@ -110,6 +234,125 @@ pub enum Commands {
         /// Optional JSON metadata to attach to this operation.
         #[arg(long)]
         metadata: Option<String>,
+
+        /// Validate C/C++ batches per translation unit, with the workspace and
+        /// patched header directories on the include path.
+        #[arg(long, requires = "batch")]
+        include_aware: bool,
+
+        /// Run the per-file gates of a batch concurrently; cargo check still
+        /// runs once after them.
+        #[arg(long, requires = "batch")]
+        parallel_validate: bool,
+
+        /// Number of threads for `--parallel-validate` (default: one per CPU).
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 0,
+            requires = "parallel_validate"
+        )]
+        parallel: usize,
+
+        /// Patch even if a span starts or ends inside a string literal or comment.
+        #[arg(long)]
+        force: bool,
+
+        /// Refuse to patch unless the file's current SHA-256 equals this hash.
+        #[arg(long, value_name = "SHA256", conflicts_with = "batch")]
+        verify_hash: Option<String>,
+
+        /// Run the language's formatter (rustfmt, black, prettier) on patched
+        /// files before validating; skipped if the formatter is not installed.
+        #[arg(long)]
+        format_after: bool,
+
+        /// Skip the patch, gates included, when the replacement matches the span
+        /// token for token and differs only in whitespace.
+        #[arg(long, conflicts_with = "batch")]
+        normalize_whitespace: bool,
+
+        /// Cargo feature selection for the cargo check gate.
+        #[command(flatten)]
+        cargo_features: CargoFeatureArgs,
+
+        /// Validation gates to run.
+        #[command(flatten)]
+        gate_args: GateArgs,
+    },
+
+    /// Replace a whole file's contents, with the same validation and rollback as a patch.
+    ReplaceFile {
+        /// Path to the file to replace.
+        #[arg(short = 'f', long)]
+        file: std::path::PathBuf,
+
+        /// File holding the new contents.
+        #[arg(long = "with", value_name = "FILE")]
+        with_: std::path::PathBuf,
+
+        /// Optional language (auto-detect from extension by default).
+        #[arg(long, value_name = "LANG")]
+        language: Option<Language>,
+
+        /// Optional validation mode (off, os, path).
+        #[arg(long, value_name = "MODE")]
+        analyzer: Option<AnalyzerMode>,
+
+        /// Create a backup before replacing.
+        #[arg(long)]
+        create_backup: bool,
+
+        /// Optional operation ID for auditing (auto-generated UUID if not provided).
+        #[arg(long)]
+        operation_id: Option<String>,
+
+        /// Optional JSON metadata to attach to this operation.
+        #[arg(long)]
+        metadata: Option<String>,
+
+        /// Cargo feature selection for the cargo check gate.
+        #[command(flatten)]
+        cargo_features: CargoFeatureArgs,
+
+        /// Validation gates to run.
+        #[command(flatten)]
+        gate_args: GateArgs,
+    },
+
+    /// Remove one imported name from a Rust file's `use` declarations, validated.
+    RemoveImport {
+        /// Path to the Rust file holding the import.
+        #[arg(short = 'f', long)]
+        file: std::path::PathBuf,
+
+        /// Imported name as bound in the file, or its full path (e.g. `crate::a::b`).
+        #[arg(short, long)]
+        name: String,
+
+        /// Optional validation mode (off, os, path).
+        #[arg(long, value_name = "MODE")]
+        analyzer: Option<AnalyzerMode>,
+
+        /// Create a backup before removing.
+        #[arg(long)]
+        create_backup: bool,
+
+        /// Optional operation ID for auditing (auto-generated UUID if not provided).
+        #[arg(long)]
+        operation_id: Option<String>,
+
+        /// Optional JSON metadata to attach to this operation.
+        #[arg(long)]
+        metadata: Option<String>,
+
+        /// Cargo feature selection for the cargo check gate.
+        #[command(flatten)]
+        cargo_features: CargoFeatureArgs,
+
+        /// Validation gates to run.
+        #[command(flatten)]
+        gate_args: GateArgs,
     },
 
     /// Execute a multi-step refactoring plan.
@@ -117,6 +360,14 @@ pub enum Commands {
         /// Path to the plan.json file.
         #[arg(short, long)]
         file: std::path::PathBuf,
+
+        /// Resolve every step and check replacement files without applying anything.
+        #[arg(long)]
+        validate_only: bool,
+
+        /// Continue past failing steps and report every step's outcome at the end.
+        #[arg(long, conflicts_with = "validate_only")]
+        keep_going: bool,
     },
 
     /// Undo a previous operation by restoring from a backup manifest.
@@ -124,6 +375,10 @@ pub enum Commands {
         /// Path to the backup manifest file.
         #[arg(short, long)]
         manifest: std::path::PathBuf,
+
+        /// Restore even if files were edited after the operation, discarding those edits.
+        #[arg(long)]
+        force: bool,
     },
 
     /// Apply a pattern replacement to multiple files.
@@ -132,6 +387,10 @@ pub enum Commands {
         #[arg(short, long)]
         glob: String,
 
+        /// Skip files matching this glob, even when they match `--glob`; repeatable.
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+
         /// Text pattern to find.
         #[arg(short, long)]
         find: String,
@@ -148,10 +407,187 @@ pub enum Commands {
         #[arg(long)]
         no_validate: bool,
 
+        /// Only replace whole identifier tokens, skipping substrings, comments, and strings.
+        #[arg(long)]
+        identifier_only: bool,
+
         /// Create a backup before applying.
         #[arg(long)]
         create_backup: bool,
 
+        /// Report only aggregate counts, omitting the list of patched files.
+        #[arg(long)]
+        summary_only: bool,
+
+        /// Optional operation ID for auditing (auto-generated UUID if not provided).
+        #[arg(long)]
+        operation_id: Option<String>,
+
+        /// Optional JSON metadata to attach to this operation.
+        #[arg(long)]
+        metadata: Option<String>,
+    },
+
+    /// Resolve a symbol and print its span as JSON, without patching.
+    Resolve {
+        /// Path to the source file containing the symbol.
+        #[arg(short = 'f', long)]
+        file: std::path::PathBuf,
+
+        /// Symbol name to resolve.
+        #[arg(
+            short = 's',
+            long,
+            required_unless_present_any = ["at", "qualified"],
+            conflicts_with_all = ["at", "qualified"]
+        )]
+        symbol: Option<String>,
+
+        /// Resolve the innermost symbol enclosing LINE:COL (1-based line, 0-based column).
+        #[arg(long, value_name = "LINE:COL", value_parser = parse_position)]
+        at: Option<(usize, usize)>,
+
+        /// Resolve a symbol by the enclosing classes or functions and its name,
+        /// e.g. `Calculator.add` for a Python method (`::` also separates).
+        #[arg(long, value_name = "PATH", conflicts_with = "at")]
+        qualified: Option<String>,
+
+        /// Unit in which the `--at` column is counted.
+        #[arg(long, value_name = "ENCODING", default_value = "utf8")]
+        position_encoding: PositionEncoding,
+
+        /// With `--at`, report the symbol straight from the extracted symbols,
+        /// without storing them in or resolving through the code graph.
+        #[arg(
+            long,
+            alias = "emit-spans-only",
+            requires = "at",
+            conflicts_with_all = ["test", "no_test"]
+        )]
+        no_graph: bool,
+
+        /// Optional symbol kind filter.
+        #[arg(short, long)]
+        kind: Option<SymbolKind>,
+
+        /// Select an overloaded Java/C++ callable by parameter types, e.g. "(int, String)".
+        #[arg(long, value_name = "TYPES", conflicts_with = "at")]
+        signature: Option<String>,
+
+        /// Only match test-only symbols (inside `#[cfg(test)]` modules or `#[test]` functions).
+        #[arg(long, conflicts_with = "no_test")]
+        test: bool,
+
+        /// Never match test-only symbols.
+        #[arg(long)]
+        no_test: bool,
+
+        /// Optional language (auto-detect from extension by default).
+        #[arg(long, value_name = "LANG")]
+        language: Option<Language>,
+    },
+
+    /// List references to a symbol with a source snippet for each (Rust only).
+    References {
+        /// Path to the source file containing the symbol definition (with
+        /// `--report-unreferenced`, a Rust file or directory to check).
+        #[arg(short = 'f', long)]
+        file: std::path::PathBuf,
+
+        /// Symbol name to find references for.
+        #[arg(short = 's', long, required_unless_present = "report_unreferenced")]
+        symbol: Option<String>,
+
+        /// Instead of one symbol, list every private or `pub(...)` item in
+        /// `--file` that has no references, as dead-code candidates.
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "symbol", "follow_renames", "context_symbol", "graph", "stream", "since_mtime"
+            ]
+        )]
+        report_unreferenced: bool,
+
+        /// How to format each reference snippet.
+        #[arg(long, value_name = "FORMAT", default_value = "plain")]
+        context_format: ContextFormat,
+
+        /// Lines of source to include above and below each reference snippet.
+        #[arg(short = 'C', long, value_name = "N", default_value_t = 0)]
+        context: usize,
+
+        /// Lines above each reference snippet (defaults to `--context`).
+        #[arg(short = 'B', long, value_name = "N")]
+        before_context: Option<usize>,
+
+        /// Lines below each reference snippet (defaults to `--context`).
+        #[arg(short = 'A', long, value_name = "N")]
+        after_context: Option<usize>,
+
+        /// Also find usages of names the symbol was renamed from in git history.
+        #[arg(long)]
+        follow_renames: bool,
+
+        /// Only report references inside this enclosing symbol (e.g. a function).
+        #[arg(long, value_name = "NAME")]
+        context_symbol: Option<String>,
+
+        /// Tab width used to compute each reference's `column_display`.
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = crate::resolve::references::DEFAULT_TAB_WIDTH
+        )]
+        tab_width: usize,
+
+        /// Also report the references as a GraphViz DOT digraph in `graph`:
+        /// the definition, each referencing file, and each reference in it.
+        #[arg(long, conflicts_with = "stream")]
+        graph: bool,
+
+        /// Print only a JSON array of the references on stdout, one element
+        /// per line as each is rendered, instead of the success payload.
+        #[arg(long)]
+        stream: bool,
+
+        /// Leave out references in files matching this glob; repeatable.
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+
+        /// Only scan other files modified after this RFC 3339 time for
+        /// cross-file references (e.g. `2024-05-01T00:00:00Z`).
+        #[arg(long, value_name = "RFC3339", value_parser = parse_rfc3339)]
+        since_mtime: Option<std::time::SystemTime>,
+    },
+
+    /// Rename a Rust symbol at its definition and every reference, validated.
+    ///
+    /// With `--dry-run` the rewrites are listed per file and nothing is written.
+    Rename {
+        /// Path to the source file containing the symbol definition.
+        #[arg(short = 'f', long)]
+        file: std::path::PathBuf,
+
+        /// Symbol name to rename.
+        #[arg(short = 's', long)]
+        symbol: String,
+
+        /// New name for the symbol.
+        #[arg(long, value_name = "NAME")]
+        to: String,
+
+        /// List the rewrites without applying them.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Optional validation mode (off, os, path).
+        #[arg(long, value_name = "MODE")]
+        analyzer: Option<AnalyzerMode>,
+
+        /// Create a backup before renaming.
+        #[arg(long)]
+        create_backup: bool,
+
         /// Optional operation ID for auditing (auto-generated UUID if not provided).
         #[arg(long)]
         operation_id: Option<String>,
@@ -159,6 +595,31 @@ pub enum Commands {
         /// Optional JSON metadata to attach to this operation.
         #[arg(long)]
         metadata: Option<String>,
+
+        /// Cargo feature selection for the cargo check gate.
+        #[command(flatten)]
+        cargo_features: CargoFeatureArgs,
+
+        /// Validation gates to run.
+        #[command(flatten)]
+        gate_args: GateArgs,
+    },
+
+    /// Report symbols added, removed, or moved between two snapshots.
+    ///
+    /// Each snapshot is a directory, or a git revision of the repository at `--repo`.
+    Diff {
+        /// Old snapshot (directory path or git revision).
+        #[arg(long, value_name = "DIR|REV")]
+        before: String,
+
+        /// New snapshot (directory path or git revision).
+        #[arg(long, value_name = "DIR|REV")]
+        after: String,
+
+        /// Repository used to resolve git revisions (default: current directory).
+        #[arg(long, value_name = "DIR")]
+        repo: Option<std::path::PathBuf>,
     },
 
     /// Query symbols by labels (uses Magellan integration).
@@ -185,6 +646,65 @@ pub enum Commands {
         show_code: bool,
     },
 
+    /// Parse every supported file under a directory into a new code graph.
+    ///
+    /// Files are parsed in parallel; the graph can then be searched with
+    /// `graph-query`.
+    Ingest {
+        /// Directory to scan recursively.
+        #[arg(value_name = "DIR")]
+        dir: std::path::PathBuf,
+
+        /// Code graph database to create; must not exist yet.
+        #[arg(long, value_name = "PATH")]
+        graph: std::path::PathBuf,
+
+        /// Number of parser threads (default: one per CPU).
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        jobs: usize,
+
+        /// Skip files matching this glob; repeatable.
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+    },
+
+    /// List symbols stored in a code graph that match a selector.
+    ///
+    /// Selector terms are `kind:<KIND>`, `name:<GLOB>`, `file:<PATH>` and
+    /// `lang:<LANG>`, all of which must match, e.g.
+    /// `kind:function file:src/lib.rs name:foo*`.
+    GraphQuery {
+        /// Code graph database to query (e.g. one built by `ingest` or kept by `serve --graph`).
+        #[arg(long, value_name = "PATH")]
+        graph: std::path::PathBuf,
+
+        /// Selector; an empty selector lists every symbol.
+        #[arg(value_name = "SELECTOR", default_value = "")]
+        selector: String,
+    },
+
+    /// Serve resolve/references/preview/applyPatch as JSON-RPC over stdin/stdout.
+    ///
+    /// Reads one request per line and writes one response per line, keeping
+    /// the code graph open between requests.
+    Serve {
+        /// Unit in which position columns are counted, in requests and responses.
+        #[arg(long, value_name = "ENCODING", default_value = "utf8")]
+        position_encoding: PositionEncoding,
+
+        /// Code graph database to keep open (default: a temporary database).
+        #[arg(long, value_name = "PATH")]
+        graph: Option<std::path::PathBuf>,
+
+        /// Cargo feature selection for the cargo check gate.
+        #[command(flatten)]
+        cargo_features: CargoFeatureArgs,
+
+        /// Validation gates to run for `preview` and `applyPatch`.
+        #[command(flatten)]
+        gate_args: GateArgs,
+    },
+
     /// Get code chunks from the database (uses Magellan integration).
     Get {
         /// Path to the Magellan database.
@@ -203,6 +723,63 @@ pub enum Commands {
         #[arg(long)]
         end: usize,
     },
+
+    /// List the supported languages, their file extensions, and the tool each
+    /// is validated with.
+    #[command(alias = "list-languages")]
+    Languages {
+        /// Print a plain-text table, or the usual JSON payload.
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Print the JSON Schema of a format Splice reads or writes.
+    ///
+    /// Without an argument, prints all of them keyed by name.
+    JsonSchema {
+        /// Format to describe.
+        #[arg(value_enum)]
+        format: Option<SchemaFormat>,
+    },
+
+    /// Print a file's tree-sitter parse tree, for debugging symbol extraction.
+    #[command(hide = true)]
+    DumpAst {
+        /// Path to the source file to parse.
+        #[arg(short, long)]
+        file: std::path::PathBuf,
+
+        /// Optional language (auto-detect from extension by default).
+        #[arg(long, value_name = "LANG")]
+        language: Option<Language>,
+
+        /// Also list the extracted symbols with their spans.
+        #[arg(long)]
+        symbols: bool,
+    },
+
+    /// Print the scopes the Rust shadowing analysis computes for a file.
+    #[command(hide = true)]
+    DumpScopes {
+        /// Path to the Rust file to analyze.
+        #[arg(short, long)]
+        file: std::path::PathBuf,
+    },
+}
+
+impl Commands {
+    /// The `--operation-id` supplied for this command, if it accepts one.
+    pub fn operation_id(&self) -> Option<&str> {
+        match self {
+            Commands::Delete { operation_id, .. }
+            | Commands::Patch { operation_id, .. }
+            | Commands::ReplaceFile { operation_id, .. }
+            | Commands::RemoveImport { operation_id, .. }
+            | Commands::Rename { operation_id, .. }
+            | Commands::ApplyFiles { operation_id, .. } => operation_id.as_deref(),
+            _ => None,
+        }
+    }
 }
 
 /// Symbol kind for filtering.
@@ -210,7 +787,7 @@ pub enum Commands {
 /// These are common symbol types across languages. Not all types are
 /// available in all languages - the CLI will validate based on the
 /// detected or specified language.
-#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SymbolKind {
     /// Function symbol.
     Function,
@@ -236,6 +813,47 @@ pub enum SymbolKind {
     Constructor,
     /// Type alias (TypeScript, Rust, Python).
     TypeAlias,
+    /// Re-export (`pub use`, Rust).
+    Reexport,
+    /// Macro (`macro_rules!`, Rust; `defmacro`, Elixir).
+    Macro,
+}
+
+impl From<SymbolKind> for &'static str {
+    /// The kind string stored in the graph and accepted in plan files.
+    fn from(kind: SymbolKind) -> Self {
+        match kind {
+            SymbolKind::Function => "function",
+            SymbolKind::Method => "method",
+            SymbolKind::Class => "class",
+            SymbolKind::Struct => "struct",
+            SymbolKind::Interface => "interface",
+            SymbolKind::Enum => "enum",
+            SymbolKind::Trait => "trait",
+            SymbolKind::Impl => "impl",
+            SymbolKind::Module => "module",
+            SymbolKind::Variable => "variable",
+            SymbolKind::Constructor => "constructor",
+            SymbolKind::TypeAlias => "type_alias",
+            SymbolKind::Reexport => "reexport",
+            SymbolKind::Macro => "macro",
+        }
+    }
+}
+
+impl TryFrom<&str> for SymbolKind {
+    type Error = crate::SpliceError;
+
+    /// Parse a kind string as produced by `From<SymbolKind> for &str`.
+    fn try_from(kind: &str) -> Result<Self, Self::Error> {
+        use clap::ValueEnum;
+
+        SymbolKind::value_variants()
+            .iter()
+            .copied()
+            .find(|variant| <&str>::from(*variant) == kind)
+            .ok_or_else(|| crate::SpliceError::Other(format!("Invalid symbol kind: {}", kind)))
+    }
 }
 
 /// Programming language.
@@ -255,6 +873,10 @@ pub enum Language {
     JavaScript,
     /// TypeScript (.ts, .tsx)
     TypeScript,
+    /// Haskell (.hs)
+    Haskell,
+    /// Elixir (.ex, .exs)
+    Elixir,
 }
 
 impl Language {
@@ -268,6 +890,8 @@ impl Language {
             Language::Java => "java",
             Language::JavaScript => "javascript",
             Language::TypeScript => "typescript",
+            Language::Haskell => "haskell",
+            Language::Elixir => "elixir",
         }
     }
 
@@ -281,6 +905,8 @@ impl Language {
             Language::Java => crate::symbol::Language::Java,
             Language::JavaScript => crate::symbol::Language::JavaScript,
             Language::TypeScript => crate::symbol::Language::TypeScript,
+            Language::Haskell => crate::symbol::Language::Haskell,
+            Language::Elixir => crate::symbol::Language::Elixir,
         }
     }
 }
@@ -298,19 +924,228 @@ pub enum AnalyzerMode {
     Path,
 }
 
+/// Column unit for editor positions passed with `--at`.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PositionEncoding {
+    /// UTF-8 bytes (default).
+    #[default]
+    Utf8,
+
+    /// UTF-16 code units, as sent by LSP clients such as VS Code.
+    Utf16,
+
+    /// Unicode scalar values.
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Convert to the offset module's encoding.
+    pub fn to_offset_encoding(self) -> crate::offset::PositionEncoding {
+        match self {
+            PositionEncoding::Utf8 => crate::offset::PositionEncoding::Utf8,
+            PositionEncoding::Utf16 => crate::offset::PositionEncoding::Utf16,
+            PositionEncoding::Utf32 => crate::offset::PositionEncoding::Utf32,
+        }
+    }
+}
+
+/// Parse an RFC 3339 timestamp argument.
+fn parse_rfc3339(value: &str) -> Result<std::time::SystemTime, String> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(std::time::SystemTime::from)
+        .map_err(|e| format!("expected an RFC 3339 time, got '{}': {}", value, e))
+}
+
+/// Parse a `LINE:COL` position argument.
+fn parse_position(value: &str) -> Result<(usize, usize), String> {
+    let (line, column) = value
+        .split_once(':')
+        .ok_or_else(|| format!("expected LINE:COL, got '{}'", value))?;
+    let line = line
+        .trim()
+        .parse::<usize>()
+        .map_err(|e| format!("invalid line '{}': {}", line, e))?;
+    let column = column
+        .trim()
+        .parse::<usize>()
+        .map_err(|e| format!("invalid column '{}': {}", column, e))?;
+    if line == 0 {
+        return Err("line numbers start at 1".to_string());
+    }
+    Ok((line, column))
+}
+
+/// Output format for listings that have a human-readable form.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Plain text, one entry per line (default).
+    #[default]
+    Text,
+
+    /// JSON success payload.
+    Json,
+}
+
+/// Output format for reference snippets.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContextFormat {
+    /// Raw source text (default).
+    #[default]
+    Plain,
+
+    /// Fenced markdown code block with a `file:line` caption.
+    Markdown,
+}
+
+impl ContextFormat {
+    /// Render a snippet taken from `file` at `line` (1-based).
+    ///
+    /// `language` is used as the fence tag in markdown mode.
+    pub fn render(&self, file: &str, line: usize, language: &str, snippet: &str) -> String {
+        match self {
+            ContextFormat::Plain => snippet.to_string(),
+            ContextFormat::Markdown => format!(
+                "`{}:{}`\n```{}\n{}\n```",
+                file,
+                line,
+                language,
+                snippet.trim_end_matches('\n')
+            ),
+        }
+    }
+}
+
+/// Cargo feature flags forwarded to the cargo check gate (Rust only).
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct CargoFeatureArgs {
+    /// Comma-separated list of features to activate during cargo check.
+    #[arg(long, value_name = "LIST", value_delimiter = ',')]
+    pub features: Vec<String>,
+
+    /// Activate all available features during cargo check.
+    #[arg(long)]
+    pub all_features: bool,
+
+    /// Do not activate the `default` feature during cargo check.
+    #[arg(long)]
+    pub no_default_features: bool,
+}
+
+impl CargoFeatureArgs {
+    /// Convert to the feature selection used by the validation gates.
+    pub fn to_cargo_features(&self) -> crate::validate::CargoFeatures {
+        crate::validate::CargoFeatures {
+            features: self.features.clone(),
+            all_features: self.all_features,
+            no_default_features: self.no_default_features,
+        }
+    }
+}
+
+/// Validation gate selectable with `--gates`.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gate {
+    /// Tree-sitter reparse of each patched file.
+    TreeSitter,
+    /// Language compiler (`cargo check` for Rust).
+    #[value(alias = "cargo")]
+    Compiler,
+    /// rust-analyzer (Rust only, requires `--analyzer`).
+    Analyzer,
+}
+
+/// Selection of validation gates to run after patching.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct GateArgs {
+    /// Comma-separated list of gates to run (default: all).
+    #[arg(long, value_name = "LIST", value_delimiter = ',')]
+    pub gates: Vec<Gate>,
+
+    /// Limit cargo check to the package and lib/bin target owning the patched
+    /// files. Faster, but dependents outside that target are not rechecked.
+    #[arg(long)]
+    pub fast_check: bool,
+
+    /// Run rust-analyzer in the package owning the patched files instead of
+    /// across the whole workspace. Faster, but other members are not rechecked.
+    #[arg(long)]
+    pub scoped_analyzer: bool,
+}
+
+impl GateArgs {
+    /// Convert to the gate set used by the patch pipeline.
+    pub fn to_gate_set(&self) -> crate::patch::GateSet {
+        if self.gates.is_empty() {
+            return crate::patch::GateSet::default();
+        }
+        crate::patch::GateSet {
+            tree_sitter: self.gates.contains(&Gate::TreeSitter),
+            compiler: self.gates.contains(&Gate::Compiler),
+            analyzer: self.gates.contains(&Gate::Analyzer),
+        }
+    }
+}
+
 /// Parse command-line arguments.
 ///
 /// This function is the entry point for CLI argument parsing.
-/// It returns the parsed Cli struct or exits on error.
+/// It returns the parsed Cli struct or exits on error. A `--profile` is
+/// expanded into flags first, see [`profile`].
 pub fn parse_args() -> Cli {
-    Cli::parse()
+    let args: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    let cwd = std::env::current_dir().unwrap_or_default();
+    match profile::apply_profile(&Cli::command(), args, &cwd) {
+        Ok(args) => Cli::parse_from(args),
+        Err(message) => Cli::command()
+            .error(clap::error::ErrorKind::InvalidValue, message)
+            .exit(),
+    }
+}
+
+/// Formats whose JSON Schema `splice json-schema` can print.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaFormat {
+    /// Plan file read by `splice plan`.
+    Plan,
+    /// Batch manifest read by `splice patch --batch`.
+    Batch,
+    /// Success payload printed on stdout.
+    Success,
+    /// Error payload printed on stderr.
+    Error,
+}
+
+impl SchemaFormat {
+    /// Name used on the command line and as the key in the combined output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SchemaFormat::Plan => "plan",
+            SchemaFormat::Batch => "batch",
+            SchemaFormat::Success => "success",
+            SchemaFormat::Error => "error",
+        }
+    }
+
+    /// JSON Schema derived from the serde types behind this format.
+    pub fn schema(&self) -> Value {
+        let schema = match self {
+            SchemaFormat::Plan => schemars::schema_for!(crate::plan::Plan),
+            SchemaFormat::Batch => crate::patch::batch_schema(),
+            SchemaFormat::Success => schemars::schema_for!(CliSuccessPayload),
+            SchemaFormat::Error => schemars::schema_for!(CliErrorPayload),
+        };
+        serde_json::to_value(schema).expect("JSON Schema serializes to JSON")
+    }
 }
 
 /// JSON success payload for CLI responses.
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 pub struct CliSuccessPayload {
     /// Status indicator ("ok").
     pub status: &'static str,
+    /// Operation ID correlating this response with its log lines.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
     /// Human-readable message.
     pub message: String,
     /// Optional structured data.
@@ -323,6 +1158,7 @@ impl CliSuccessPayload {
     pub fn message_only(message: String) -> Self {
         Self {
             status: "ok",
+            operation_id: None,
             message,
             data: None,
         }
@@ -332,23 +1168,33 @@ impl CliSuccessPayload {
     pub fn with_data(message: String, data: Value) -> Self {
         Self {
             status: "ok",
+            operation_id: None,
             message,
             data: Some(data),
         }
     }
+
+    /// Attach the operation ID of the invocation that produced this payload.
+    pub fn with_operation_id(mut self, operation_id: impl Into<String>) -> Self {
+        self.operation_id = Some(operation_id.into());
+        self
+    }
 }
 
 /// JSON error payload for CLI responses.
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 pub struct CliErrorPayload {
     /// Status indicator ("error").
     pub status: &'static str,
+    /// Operation ID correlating this response with its log lines.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
     /// Structured error details.
     pub error: ErrorDetails,
 }
 
 /// Details for a CLI error payload.
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 pub struct ErrorDetails {
     /// Error kind identifier (SymbolNotFound, etc.).
     pub kind: &'static str,
@@ -366,10 +1212,30 @@ pub struct ErrorDetails {
     /// Optional diagnostics emitted by validation gates.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub diagnostics: Option<Vec<DiagnosticPayload>>,
+    /// Each failure of an `Aggregate` error, with its own kind and context.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<ErrorDetails>>,
 }
 
 impl CliErrorPayload {
     /// Build payload from a SpliceError instance.
+    pub fn from_error(error: &crate::SpliceError) -> Self {
+        CliErrorPayload {
+            status: "error",
+            operation_id: None,
+            error: ErrorDetails::from_error(error),
+        }
+    }
+
+    /// Attach the operation ID of the invocation that produced this payload.
+    pub fn with_operation_id(mut self, operation_id: impl Into<String>) -> Self {
+        self.operation_id = Some(operation_id.into());
+        self
+    }
+}
+
+impl ErrorDetails {
+    /// Describe a SpliceError, recursing into the failures of an aggregate.
     pub fn from_error(error: &crate::SpliceError) -> Self {
         let symbol = error.symbol().map(|s| s.to_string());
         let file = error
@@ -389,23 +1255,27 @@ impl CliErrorPayload {
                 )
             }
         };
+        let errors = match error {
+            crate::SpliceError::Aggregate(errors) => {
+                Some(errors.iter().map(ErrorDetails::from_error).collect())
+            }
+            _ => None,
+        };
 
-        CliErrorPayload {
-            status: "error",
-            error: ErrorDetails {
-                kind: error.kind(),
-                message: error.to_string(),
-                symbol,
-                file,
-                hint,
-                diagnostics,
-            },
+        ErrorDetails {
+            kind: error.kind(),
+            message: error.to_string(),
+            symbol,
+            file,
+            hint,
+            diagnostics,
+            errors,
         }
     }
 }
 
 /// JSON representation of a diagnostic.
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 pub struct DiagnosticPayload {
     /// Tool emitting the diagnostic.
     pub tool: String,
@@ -422,6 +1292,9 @@ pub struct DiagnosticPayload {
     /// Optional column (0-based).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub column: Option<usize>,
+    /// Optional byte offset of the reported position within `file`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub byte_offset: Option<usize>,
     /// Optional compiler/analyzer error code.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub code: Option<String>,
@@ -448,6 +1321,7 @@ impl From<crate::error::Diagnostic> for DiagnosticPayload {
             file: diag.file.map(|p| p.to_string_lossy().to_string()),
             line: diag.line,
             column: diag.column,
+            byte_offset: diag.byte_offset,
             code: diag.code,
             note: diag.note,
             tool_path: diag.tool_path.map(|p| p.to_string_lossy().to_string()),
@@ -0,0 +1,347 @@
+//! Named flag bundles read from `splice.toml`.
+//!
+//! A profile is a table of long flag names and values:
+//!
+//! ```toml
+//! [profile.ci]
+//! analyzer = "path"
+//! gates = ["tree-sitter", "compiler"]
+//! fast-check = true
+//! ```
+//!
+//! `--profile ci` expands the table into command-line flags before clap parses
+//! the arguments, so a profile only supplies defaults: a flag given explicitly
+//! wins, and flags the chosen subcommand does not accept are left out.
+
+use clap::{Arg, ArgAction, Command};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+/// Name of the configuration file holding the profiles.
+pub const CONFIG_FILE: &str = "splice.toml";
+
+/// Value of one flag in a profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileValue {
+    /// Switch: `true` passes the flag, `false` leaves it out.
+    Bool(bool),
+    /// Flag value, passed as `--flag=value`. Integers are kept as their text.
+    Value(String),
+    /// Repeated flag value, passed once per element.
+    List(Vec<String>),
+}
+
+/// Flags of one profile, in the order they appear in the file.
+pub type Profile = Vec<(String, ProfileValue)>;
+
+/// Find `splice.toml` in `start` or the nearest directory above it.
+pub fn find_config(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Top level of `splice.toml`. Tables other than `[profile.*]` are ignored.
+#[derive(Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    profile: BTreeMap<String, toml::Table>,
+}
+
+/// Parse the `[profile.<name>]` tables of a `splice.toml`.
+///
+/// Flag values may be strings, integers, booleans, or arrays of strings and
+/// integers. Other tables are skipped.
+pub fn parse_profiles(source: &str) -> Result<BTreeMap<String, Profile>, String> {
+    let config: ConfigFile =
+        toml::from_str(source).map_err(|err| format!("{}: {}", CONFIG_FILE, err))?;
+    config
+        .profile
+        .into_iter()
+        .map(|(name, table)| {
+            let profile = table
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = profile_value(value).map_err(|message| {
+                        format!(
+                            "{}: profile `{}`, key `{}`: {}",
+                            CONFIG_FILE, name, key, message
+                        )
+                    })?;
+                    Ok((key, value))
+                })
+                .collect::<Result<Profile, String>>()?;
+            Ok((name, profile))
+        })
+        .collect()
+}
+
+fn profile_value(value: toml::Value) -> Result<ProfileValue, String> {
+    match value {
+        toml::Value::Boolean(flag) => Ok(ProfileValue::Bool(flag)),
+        toml::Value::Array(items) => items
+            .into_iter()
+            .map(scalar_text)
+            .collect::<Result<_, _>>()
+            .map(ProfileValue::List),
+        scalar => scalar_text(scalar).map(ProfileValue::Value),
+    }
+}
+
+/// Text of a string or integer flag value.
+fn scalar_text(value: toml::Value) -> Result<String, String> {
+    match value {
+        toml::Value::String(text) => Ok(text),
+        toml::Value::Integer(number) => Ok(number.to_string()),
+        other => Err(format!(
+            "expected a string, integer, boolean, or array, got {} `{}`",
+            other.type_str(),
+            other
+        )),
+    }
+}
+
+/// Select the profile named by `--profile` in `args`, if any, and expand it.
+///
+/// `args` is the full command line, program name first. The profile is read
+/// from the `splice.toml` nearest to `cwd`. Returns the arguments unchanged
+/// when no profile is requested.
+pub fn apply_profile(
+    command: &Command,
+    args: Vec<OsString>,
+    cwd: &Path,
+) -> Result<Vec<OsString>, String> {
+    let Some(name) = requested_profile(&args) else {
+        return Ok(args);
+    };
+    let config = find_config(cwd).ok_or_else(|| {
+        format!(
+            "--profile {} needs a {} in {} or a parent directory",
+            name,
+            CONFIG_FILE,
+            cwd.display()
+        )
+    })?;
+    let source = std::fs::read_to_string(&config)
+        .map_err(|err| format!("cannot read {}: {}", config.display(), err))?;
+    let profiles =
+        parse_profiles(&source).map_err(|err| format!("{} ({})", err, config.display()))?;
+    let profile = profiles.get(&name).ok_or_else(|| {
+        let known: Vec<&str> = profiles.keys().map(String::as_str).collect();
+        format!(
+            "no profile `{}` in {} (defined: {})",
+            name,
+            config.display(),
+            if known.is_empty() {
+                "none".to_string()
+            } else {
+                known.join(", ")
+            }
+        )
+    })?;
+    expand_profile(command, args, profile)
+}
+
+/// Value of `--profile NAME` or `--profile=NAME`, ignoring anything after `--`.
+fn requested_profile(args: &[OsString]) -> Option<String> {
+    let mut iter = args.iter().skip(1).map(|arg| arg.to_string_lossy());
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--profile" {
+            return iter.next().map(|name| name.into_owned());
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+/// Insert the profile's flags right after the subcommand name.
+///
+/// A flag is skipped if the command line already sets it (by long or short
+/// name) or if neither the subcommand nor the global options accept it. A key
+/// that no subcommand accepts is an error, so typos do not pass silently.
+pub fn expand_profile(
+    command: &Command,
+    args: Vec<OsString>,
+    profile: &Profile,
+) -> Result<Vec<OsString>, String> {
+    let known = |key: &str| {
+        command
+            .get_arguments()
+            .chain(command.get_subcommands().flat_map(Command::get_arguments))
+            .any(|arg| arg.get_long() == Some(key))
+    };
+    if let Some((key, _)) = profile.iter().find(|(key, _)| !known(key)) {
+        return Err(format!("profile sets unknown flag `--{}`", key));
+    }
+
+    let Some((position, subcommand)) = find_subcommand(command, &args) else {
+        return Ok(args);
+    };
+    let explicit = &args[position + 1..];
+    let explicit = match explicit.iter().position(|arg| arg == "--") {
+        Some(end) => &explicit[..end],
+        None => explicit,
+    };
+    let given = |arg: &Arg| {
+        args[1..position].iter().chain(explicit).any(|token| {
+            let token = token.to_string_lossy();
+            let long = arg.get_long().is_some_and(|long| {
+                token.strip_prefix("--").is_some_and(|rest| {
+                    rest == long || rest.strip_prefix(long).is_some_and(|v| v.starts_with('='))
+                })
+            });
+            let short = arg.get_short().is_some_and(|short| {
+                token
+                    .strip_prefix('-')
+                    .is_some_and(|rest| !rest.starts_with('-') && rest.starts_with(short))
+            });
+            long || short
+        })
+    };
+
+    let mut expanded = Vec::new();
+    for (key, value) in profile {
+        let Some(arg) = command
+            .get_arguments()
+            .chain(subcommand.get_arguments())
+            .find(|arg| arg.get_long() == Some(key.as_str()))
+        else {
+            continue;
+        };
+        if given(arg) {
+            continue;
+        }
+        match value {
+            ProfileValue::Bool(true) if matches!(arg.get_action(), ArgAction::SetTrue) => {
+                expanded.push(OsString::from(format!("--{}", key)));
+            }
+            ProfileValue::Bool(false) if matches!(arg.get_action(), ArgAction::SetTrue) => {}
+            ProfileValue::Bool(flag) => {
+                expanded.push(OsString::from(format!("--{}={}", key, flag)))
+            }
+            ProfileValue::Value(value) => {
+                expanded.push(OsString::from(format!("--{}={}", key, value)))
+            }
+            ProfileValue::List(values) => expanded.extend(
+                values
+                    .iter()
+                    .map(|value| OsString::from(format!("--{}={}", key, value))),
+            ),
+        }
+    }
+
+    let mut args = args;
+    args.splice(position + 1..position + 1, expanded);
+    Ok(args)
+}
+
+/// Index and definition of the subcommand named on the command line.
+fn find_subcommand<'a>(command: &'a Command, args: &[OsString]) -> Option<(usize, &'a Command)> {
+    let mut index = 1;
+    while index < args.len() {
+        let token = args[index].to_string_lossy();
+        if token == "--" {
+            return None;
+        }
+        if let Some(long) = token.strip_prefix("--") {
+            let takes_value = command
+                .get_arguments()
+                .find(|arg| arg.get_long() == Some(long))
+                .is_some_and(|arg| arg.get_action().takes_values());
+            index += if takes_value { 2 } else { 1 };
+            continue;
+        }
+        if token.starts_with('-') {
+            index += 1;
+            continue;
+        }
+        return command
+            .find_subcommand(OsStr::new(token.as_ref()))
+            .map(|subcommand| (index, subcommand));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_parse_profiles_reads_only_profile_tables() {
+        let source = "[workspace]\nignored = 1\n\n# CI settings\n[profile.ci]\nanalyzer = \"path\" # inline\ngates = ['tree-sitter', \"compiler\"]\nfast-check = true\nmax-file-size = 1_000\n";
+        let profiles = parse_profiles(source).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(
+            profiles["ci"],
+            vec![
+                (
+                    "analyzer".to_string(),
+                    ProfileValue::Value("path".to_string())
+                ),
+                (
+                    "gates".to_string(),
+                    ProfileValue::List(vec!["tree-sitter".to_string(), "compiler".to_string()])
+                ),
+                ("fast-check".to_string(), ProfileValue::Bool(true)),
+                (
+                    "max-file-size".to_string(),
+                    ProfileValue::Value("1000".to_string())
+                ),
+            ]
+        );
+
+        let error = parse_profiles("[profile.ci]\nanalyzer = path\n").unwrap_err();
+        assert!(error.contains("line 2"), "error: {}", error);
+
+        let error = parse_profiles("[profile.ci]\nanalyzer = 1.5\n").unwrap_err();
+        assert!(error.contains("key `analyzer`"), "error: {}", error);
+    }
+
+    #[test]
+    fn test_expand_profile_yields_to_explicit_flags() {
+        let profile = parse_profiles(
+            "[profile.ci]\nanalyzer = \"path\"\nfast-check = true\ncontext = 2\nmax-file-size = 10\n",
+        )
+        .unwrap()
+        .remove("ci")
+        .unwrap();
+        let args = |line: &str| line.split(' ').map(OsString::from).collect::<Vec<_>>();
+
+        // `context` is not a patch flag and `--analyzer` is given explicitly
+        let expanded = expand_profile(
+            &Cli::command(),
+            args("splice --profile ci patch --analyzer off --file a.rs"),
+            &profile,
+        )
+        .unwrap();
+        assert_eq!(
+            expanded,
+            args("splice --profile ci patch --fast-check --max-file-size=10 --analyzer off --file a.rs")
+        );
+
+        // Short flags count as explicit too, and global flags may precede the subcommand
+        let expanded = expand_profile(
+            &Cli::command(),
+            args("splice --max-file-size 5 references -C1 --file a.rs"),
+            &profile,
+        )
+        .unwrap();
+        assert_eq!(
+            expanded,
+            args("splice --max-file-size 5 references -C1 --file a.rs")
+        );
+
+        let typo = vec![("fast-chek".to_string(), ProfileValue::Bool(true))];
+        assert!(expand_profile(&Cli::command(), args("splice patch"), &typo).is_err());
+    }
+}
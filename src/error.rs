@@ -45,13 +45,36 @@ pub enum SpliceError {
         hint: String,
     },
 
-    /// Symbol name is ambiguous without file context.
-    #[error("Ambiguous symbol '{name}': found in multiple files: {files:?}")]
+    /// Symbol is only defined inside a macro invocation (e.g. `lazy_static!`).
+    #[error(
+        "Symbol '{name}' in {} is generated by the `{macro_name}!` macro; splice cannot address macro-generated items",
+        file.display()
+    )]
+    MacroGeneratedSymbol {
+        /// Symbol name that was requested.
+        name: String,
+        /// File containing the macro invocation.
+        file: PathBuf,
+        /// Name of the macro defining the symbol.
+        macro_name: String,
+    },
+
+    /// Symbol name is ambiguous without file context, or names several
+    /// overloads or kinds of symbol.
+    #[error(
+        "Ambiguous symbol '{name}': {}",
+        ambiguity_detail(files, signatures, kinds)
+    )]
     AmbiguousSymbol {
         /// The ambiguous symbol name.
         name: String,
         /// List of files where this symbol was found.
         files: Vec<String>,
+        /// Signatures of the overloads the name matched (e.g., `add(int)`), if any.
+        signatures: Vec<String>,
+        /// Kind and location of each candidate when the name matched symbols
+        /// of different kinds (e.g., `struct at src/lib.rs:3`), if any.
+        kinds: Vec<String>,
     },
 
     /// Reference finding failed.
@@ -91,6 +114,37 @@ pub enum SpliceError {
         end: usize,
     },
 
+    /// File is larger than the configured `--max-file-size` limit.
+    #[error("File '{file}' is {size} bytes, exceeding the {limit} byte limit")]
+    FileTooLarge {
+        /// The oversized file.
+        file: PathBuf,
+        /// Actual file size in bytes.
+        size: u64,
+        /// Configured limit in bytes.
+        limit: u64,
+    },
+
+    /// Syntax tree is nested deeper than the configured `--max-ast-depth` limit.
+    #[error("Syntax tree of '{file}' is nested deeper than the {limit} level limit")]
+    AstTooDeep {
+        /// The file whose tree is too deep.
+        file: PathBuf,
+        /// Configured depth limit.
+        limit: usize,
+    },
+
+    /// Patch span starts or ends inside a string literal or comment.
+    #[error("Span boundary at byte {offset} in '{file}' falls inside a {node_kind} node")]
+    SpanInsideLiteral {
+        /// The file being patched.
+        file: PathBuf,
+        /// The offending span boundary.
+        offset: usize,
+        /// Tree-sitter kind of the enclosing literal or comment.
+        node_kind: String,
+    },
+
     /// Compiler validation failed.
     #[error("Compiler error: {0}")]
     CompilerError(String),
@@ -165,15 +219,150 @@ pub enum SpliceError {
         error: String,
     },
 
+    /// Plan validation found steps that would fail.
+    #[error("Plan validation failed at step(s) {failed_steps:?}")]
+    PlanValidationFailed {
+        /// 1-based numbers of the failing steps.
+        failed_steps: Vec<usize>,
+        /// One diagnostic per failing step.
+        diagnostics: Vec<Diagnostic>,
+    },
+
+    /// Plan ran with `--keep-going` and some steps failed.
+    #[error("Plan steps {failed_steps:?} failed; steps {applied_steps:?} were applied")]
+    PlanStepsFailed {
+        /// 1-based numbers of the failing steps.
+        failed_steps: Vec<usize>,
+        /// 1-based numbers of the steps that were applied.
+        applied_steps: Vec<usize>,
+        /// One diagnostic per step, in plan order.
+        diagnostics: Vec<Diagnostic>,
+    },
+
+    /// Undo refused because files changed after the operation completed.
+    #[error("Undo refused: {} file(s) changed since the operation: {files:?}", files.len())]
+    UndoDiverged {
+        /// Workspace-relative paths whose current contents differ from the recorded result.
+        files: Vec<PathBuf>,
+    },
+
+    /// File contents differ from the hash the caller expected (`--verify-hash`).
+    #[error("Hash mismatch for '{file}': expected {expected}, found {actual}")]
+    HashMismatch {
+        /// The file that was checked.
+        file: PathBuf,
+        /// SHA-256 the caller expected.
+        expected: String,
+        /// SHA-256 of the file's current contents.
+        actual: String,
+    },
+
+    /// Rolling back a failed operation did not restore every file's original bytes.
+    #[error(
+        "Rollback left {} file(s) differing from their original contents: {files:?} (rolled back because: {cause})",
+        files.len()
+    )]
+    RollbackIncomplete {
+        /// Files that could not be restored or whose hash differs from before the operation.
+        files: Vec<PathBuf>,
+        /// The failure that triggered the rollback.
+        #[source]
+        cause: Box<SpliceError>,
+    },
+
+    /// Deleting the symbol would remove it from the crate's public API.
+    #[error(
+        "'{symbol}' is re-exported at the crate root ({}); deleting it changes the public API",
+        locations.join(", ")
+    )]
+    PublicApiChange {
+        /// The symbol being deleted.
+        symbol: String,
+        /// `file:line` of each crate-root `pub use` exposing the symbol.
+        locations: Vec<String>,
+    },
+
+    /// A delete would remove more references than the caller allowed.
+    #[error("Deleting '{symbol}' would remove {count} references, more than the limit of {limit}")]
+    TooManyReferences {
+        /// The symbol (or symbol pattern) being deleted.
+        symbol: String,
+        /// References the delete would remove.
+        count: usize,
+        /// The `--max-references` limit.
+        limit: usize,
+    },
+
+    /// A bundled tree-sitter grammar was generated for an ABI the linked
+    /// tree-sitter library cannot load.
+    #[error(
+        "{language} grammar uses tree-sitter ABI {grammar_abi}, but this build supports ABI {}-{}",
+        supported_abi.start(),
+        supported_abi.end()
+    )]
+    GrammarIncompatible {
+        /// Grammar that failed to load.
+        language: String,
+        /// ABI version the grammar was generated for.
+        grammar_abi: usize,
+        /// ABI versions the linked tree-sitter library accepts.
+        supported_abi: std::ops::RangeInclusive<usize>,
+    },
+
+    /// Patched content defines a symbol that already exists in the same scope.
+    #[error("'{name}' is already defined as a {kind} at {}:{line}", file.display())]
+    DuplicateSymbol {
+        /// Name of the duplicated symbol.
+        name: String,
+        /// Symbol kind shared by both definitions.
+        kind: String,
+        /// File holding the existing definition.
+        file: PathBuf,
+        /// 1-based line of the existing definition.
+        line: usize,
+    },
+
+    /// A path that has to be reported or passed on as text is not valid UTF-8.
+    #[error("Path is not valid UTF-8: {}", path.display())]
+    NonUtf8Path {
+        /// The offending path, shown lossily.
+        path: PathBuf,
+    },
+
     /// UTF-8 validation error.
     #[error("UTF-8 error: {0}")]
     Utf8(#[from] std::str::Utf8Error),
 
+    /// Several independent failures reported together, e.g. by a multi-symbol
+    /// operation that checks every target before giving up.
+    #[error("{} failures: {}", .0.len(), aggregate_detail(.0))]
+    Aggregate(Vec<SpliceError>),
+
     /// Generic error with context.
     #[error("{0}")]
     Other(String),
 }
 
+/// Describe where an ambiguous symbol was found, listing overloads when known.
+fn ambiguity_detail(files: &[String], signatures: &[String], kinds: &[String]) -> String {
+    if !kinds.is_empty() {
+        format!("matches several kinds: {}", kinds.join(", "))
+    } else if signatures.is_empty() {
+        format!("found in multiple files: {:?}", files)
+    } else {
+        format!("matches overloads {}", signatures.join(", "))
+    }
+}
+
+/// Join the messages of an aggregate's failures.
+fn aggregate_detail(errors: &[SpliceError]) -> String {
+    errors
+        .iter()
+        .map(|error| error.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 impl From<std::io::Error> for SpliceError {
     fn from(err: std::io::Error) -> Self {
         SpliceError::Io {
@@ -186,6 +375,13 @@ impl From<std::io::Error> for SpliceError {
 /// Result type alias for Splice operations.
 pub type Result<T> = std::result::Result<T, SpliceError>;
 
+/// Borrow `path` as UTF-8, failing with `NonUtf8Path` rather than dropping it.
+pub fn utf8_path(path: &Path) -> Result<&str> {
+    path.to_str().ok_or_else(|| SpliceError::NonUtf8Path {
+        path: path.to_path_buf(),
+    })
+}
+
 /// Severity level for diagnostics emitted by validation gates.
 #[derive(Debug, Clone, Copy)]
 pub enum DiagnosticLevel {
@@ -226,6 +422,9 @@ pub struct Diagnostic {
     pub line: Option<usize>,
     /// Optional 0-based column number.
     pub column: Option<usize>,
+    /// Optional byte offset into `file` of `line`/`column`, recomputed from the
+    /// file contents so it does not depend on how the tool counts tabs.
+    pub byte_offset: Option<usize>,
     /// Optional compiler/analyzer error code.
     pub code: Option<String>,
     /// Optional hint/help text provided by the tool.
@@ -252,6 +451,7 @@ impl Diagnostic {
             file: None,
             line: None,
             column: None,
+            byte_offset: None,
             code: None,
             note: None,
             tool_path: None,
@@ -273,6 +473,12 @@ impl Diagnostic {
         self
     }
 
+    /// Attach the normalized byte offset of the reported position.
+    pub fn with_byte_offset(mut self, byte_offset: Option<usize>) -> Self {
+        self.byte_offset = byte_offset;
+        self
+    }
+
     /// Attach optional diagnostic code.
     pub fn with_code(mut self, code: Option<String>) -> Self {
         self.code = code;
@@ -331,6 +537,24 @@ impl SpliceError {
         }
     }
 
+    /// Combine independent failures into one error.
+    ///
+    /// Nested aggregates are flattened, and a single failure is returned as is.
+    pub fn aggregate(errors: Vec<SpliceError>) -> Self {
+        let mut flat = Vec::with_capacity(errors.len());
+        for error in errors {
+            match error {
+                SpliceError::Aggregate(inner) => flat.extend(inner),
+                error => flat.push(error),
+            }
+        }
+        if flat.len() == 1 {
+            flat.remove(0)
+        } else {
+            SpliceError::Aggregate(flat)
+        }
+    }
+
     /// Kind identifier for structured logging / CLI output.
     pub fn kind(&self) -> &'static str {
         match self {
@@ -338,10 +562,14 @@ impl SpliceError {
             SpliceError::Graph(_) => "Graph",
             SpliceError::Parse { .. } => "Parse",
             SpliceError::SymbolNotFound { .. } => "SymbolNotFound",
+            SpliceError::MacroGeneratedSymbol { .. } => "MacroGeneratedSymbol",
             SpliceError::AmbiguousSymbol { .. } => "AmbiguousSymbol",
             SpliceError::ReferenceFailed { .. } => "ReferenceFailed",
             SpliceError::AmbiguousReference { .. } => "AmbiguousReference",
             SpliceError::InvalidSpan { .. } => "InvalidSpan",
+            SpliceError::FileTooLarge { .. } => "FileTooLarge",
+            SpliceError::AstTooDeep { .. } => "AstTooDeep",
+            SpliceError::SpanInsideLiteral { .. } => "SpanInsideLiteral",
             SpliceError::CompilerError(_) => "CompilerError",
             SpliceError::ParseValidationFailed { .. } => "ParseValidationFailed",
             SpliceError::CargoCheckFailed { .. } => "CargoCheckFailed",
@@ -351,18 +579,67 @@ impl SpliceError {
             SpliceError::InvalidPlanSchema { .. } => "InvalidPlanSchema",
             SpliceError::InvalidBatchSchema { .. } => "InvalidBatchSchema",
             SpliceError::PlanExecutionFailed { .. } => "PlanExecutionFailed",
+            SpliceError::PlanValidationFailed { .. } => "PlanValidationFailed",
+            SpliceError::PlanStepsFailed { .. } => "PlanStepsFailed",
+            SpliceError::UndoDiverged { .. } => "UndoDiverged",
+            SpliceError::HashMismatch { .. } => "HashMismatch",
+            SpliceError::RollbackIncomplete { .. } => "RollbackIncomplete",
+            SpliceError::PublicApiChange { .. } => "PublicApiChange",
+            SpliceError::TooManyReferences { .. } => "TooManyReferences",
+            SpliceError::GrammarIncompatible { .. } => "GrammarIncompatible",
+            SpliceError::DuplicateSymbol { .. } => "DuplicateSymbol",
+            SpliceError::NonUtf8Path { .. } => "NonUtf8Path",
             SpliceError::Utf8(_) => "Utf8",
+            SpliceError::Aggregate(_) => "Aggregate",
             SpliceError::Other(_) => "Other",
         }
     }
 
+    /// Process exit code for this error class, so scripts can branch on it.
+    ///
+    /// - `3`: symbol not found, or only generated by a macro
+    /// - `4`: ambiguous symbol or reference
+    /// - `5`: validation failed (duplicate definition, parse, compiler, or analyzer gate)
+    /// - `6`: I/O error
+    /// - `1`: any other error
+    ///
+    /// `2` is left to clap, which exits with it on a command-line usage error.
+    ///
+    /// An aggregate takes its failures' code when they all agree, otherwise `1`.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            SpliceError::Aggregate(errors) => {
+                let mut codes = errors.iter().map(SpliceError::exit_code);
+                match codes.next() {
+                    Some(first) if codes.all(|code| code == first) => first,
+                    _ => 1,
+                }
+            }
+            SpliceError::SymbolNotFound { .. } | SpliceError::MacroGeneratedSymbol { .. } => 3,
+            SpliceError::AmbiguousSymbol { .. } | SpliceError::AmbiguousReference { .. } => 4,
+            SpliceError::ParseValidationFailed { .. }
+            | SpliceError::CargoCheckFailed { .. }
+            | SpliceError::CompilerValidationFailed { .. }
+            | SpliceError::CompilerError(_)
+            | SpliceError::AnalyzerFailed { .. }
+            | SpliceError::PlanValidationFailed { .. }
+            | SpliceError::DuplicateSymbol { .. } => 5,
+            SpliceError::Io { .. } => 6,
+            _ => 1,
+        }
+    }
+
     /// Optional symbol context for structured output.
     pub fn symbol(&self) -> Option<&str> {
         match self {
             SpliceError::SymbolNotFound { symbol, .. } => Some(symbol.as_str()),
+            SpliceError::MacroGeneratedSymbol { name, .. } => Some(name.as_str()),
             SpliceError::AmbiguousSymbol { name, .. } => Some(name.as_str()),
             SpliceError::ReferenceFailed { name, .. } => Some(name.as_str()),
             SpliceError::AmbiguousReference { name, .. } => Some(name.as_str()),
+            SpliceError::PublicApiChange { symbol, .. } => Some(symbol.as_str()),
+            SpliceError::TooManyReferences { symbol, .. } => Some(symbol.as_str()),
+            SpliceError::DuplicateSymbol { name, .. } => Some(name.as_str()),
             _ => None,
         }
     }
@@ -372,9 +649,16 @@ impl SpliceError {
         match self {
             SpliceError::Parse { file, .. } => Some(file.as_path()),
             SpliceError::InvalidSpan { file, .. } => Some(file.as_path()),
+            SpliceError::FileTooLarge { file, .. } => Some(file.as_path()),
+            SpliceError::AstTooDeep { file, .. } => Some(file.as_path()),
+            SpliceError::SpanInsideLiteral { file, .. } => Some(file.as_path()),
             SpliceError::ParseValidationFailed { file, .. } => Some(file.as_path()),
             SpliceError::CargoCheckFailed { workspace, .. } => Some(workspace.as_path()),
             SpliceError::CompilerValidationFailed { file, .. } => Some(file.as_path()),
+            SpliceError::HashMismatch { file, .. } => Some(file.as_path()),
+            SpliceError::DuplicateSymbol { file, .. } => Some(file.as_path()),
+            SpliceError::NonUtf8Path { path } => Some(path.as_path()),
+            SpliceError::MacroGeneratedSymbol { file, .. } => Some(file.as_path()),
             SpliceError::SymbolNotFound {
                 file: Some(file), ..
             } => Some(file.as_path()),
@@ -387,6 +671,15 @@ impl SpliceError {
     pub fn hint(&self) -> Option<&str> {
         match self {
             SpliceError::SymbolNotFound { hint, .. } => Some(hint.as_str()),
+            SpliceError::MacroGeneratedSymbol { .. } => {
+                Some("Edit the macro invocation that defines it instead")
+            }
+            SpliceError::AmbiguousSymbol { kinds, .. } if !kinds.is_empty() => {
+                Some("Pass --kind with one of the listed kinds")
+            }
+            SpliceError::AmbiguousSymbol { signatures, .. } if !signatures.is_empty() => {
+                Some("Pass --signature with one of the listed parameter lists, e.g. --signature \"(int)\"")
+            }
             SpliceError::AmbiguousSymbol { .. } => {
                 Some("Pass --file to disambiguate symbols defined in multiple files")
             }
@@ -396,6 +689,38 @@ impl SpliceError {
             SpliceError::ReferenceFailed { .. } => {
                 Some("Check ingest logs; reference resolver could not complete")
             }
+            SpliceError::FileTooLarge { .. } => Some("Raise the limit with --max-file-size <BYTES>"),
+            SpliceError::AstTooDeep { .. } => Some("Raise the limit with --max-ast-depth <N>"),
+            SpliceError::SpanInsideLiteral { .. } => {
+                Some("Re-index the file if the span is stale, or pass --force to patch anyway")
+            }
+            SpliceError::UndoDiverged { .. } => {
+                Some("Pass --force to restore anyway and discard the later edits")
+            }
+            SpliceError::HashMismatch { .. } => {
+                Some("The file changed since the hash was taken; re-plan against its current contents")
+            }
+            SpliceError::RollbackIncomplete { .. } => {
+                Some("Restore the listed files from version control or a backup before retrying")
+            }
+            SpliceError::PublicApiChange { .. } => {
+                Some("Downstream crates may import it from there; pass --force to delete anyway")
+            }
+            SpliceError::GrammarIncompatible { .. } => {
+                Some("Pin the grammar crate to a release built for the tree-sitter version splice uses")
+            }
+            SpliceError::TooManyReferences { .. } => {
+                Some("Check the spans with --dry-run, then raise --max-references or narrow the target")
+            }
+            SpliceError::DuplicateSymbol { .. } => {
+                Some("Rename the new definition, or patch the existing one instead of inserting")
+            }
+            SpliceError::NonUtf8Path { .. } => {
+                Some("Rename the file or directory so its path is valid UTF-8")
+            }
+            SpliceError::PlanStepsFailed { .. } => {
+                Some("Applied steps are kept; fix the failed steps and run them in a new plan")
+            }
             _ => None,
         }
     }
@@ -410,6 +735,19 @@ impl SpliceError {
                 ]
             }
             SpliceError::CompilerValidationFailed { diagnostics, .. } => diagnostics.clone(),
+            SpliceError::PlanValidationFailed { diagnostics, .. }
+            | SpliceError::PlanStepsFailed { diagnostics, .. } => diagnostics.clone(),
+            SpliceError::UndoDiverged { files } => files
+                .iter()
+                .map(|file| {
+                    Diagnostic::new(
+                        "undo",
+                        DiagnosticLevel::Error,
+                        "File was modified after the operation",
+                    )
+                    .with_file(file.clone())
+                })
+                .collect(),
             SpliceError::CargoCheckFailed {
                 workspace,
                 output,
@@ -437,6 +775,20 @@ impl SpliceError {
                     diagnostics.clone()
                 }
             }
+            SpliceError::DuplicateSymbol {
+                name,
+                kind,
+                file,
+                line,
+            } => {
+                vec![Diagnostic::new(
+                    "splice",
+                    DiagnosticLevel::Error,
+                    format!("{} '{}' is already defined here", kind, name),
+                )
+                .with_file(file.clone())
+                .with_position(Some(*line), None)]
+            }
             SpliceError::CompilerError(message) => {
                 vec![Diagnostic::new(
                     "compiler",
@@ -444,6 +796,9 @@ impl SpliceError {
                     message.clone(),
                 )]
             }
+            SpliceError::Aggregate(errors) => {
+                errors.iter().flat_map(SpliceError::diagnostics).collect()
+            }
             _ => Vec::new(),
         }
     }
@@ -5,7 +5,7 @@
 //! - Label-based symbol queries
 //! - Code chunk retrieval (no file re-reading)
 
-use crate::error::{Result, SpliceError};
+use crate::error::{utf8_path, Result, SpliceError};
 use magellan::{CodeGraph as MagellanGraph, SymbolQueryResult};
 use std::path::Path;
 
@@ -17,9 +17,7 @@ pub struct MagellanIntegration {
 impl MagellanIntegration {
     /// Open or create a Magellan code graph at the given path.
     pub fn open(db_path: &Path) -> Result<Self> {
-        let db_path_str = db_path
-            .to_str()
-            .ok_or_else(|| SpliceError::Other(format!("Invalid UTF-8 in path: {:?}", db_path)))?;
+        let db_path_str = utf8_path(db_path)?;
 
         // Convert anyhow::Error to SpliceError
         let inner = MagellanGraph::open(db_path_str).map_err(|e| {
@@ -36,9 +34,7 @@ impl MagellanIntegration {
     ///
     /// Returns the number of symbols indexed.
     pub fn index_file(&mut self, file_path: &Path) -> Result<usize> {
-        let file_path_str = file_path
-            .to_str()
-            .ok_or_else(|| SpliceError::Other(format!("Invalid UTF-8 in path: {:?}", file_path)))?;
+        let file_path_str = utf8_path(file_path)?;
 
         let source = std::fs::read(file_path).map_err(|e| {
             SpliceError::Other(format!("Failed to read file {:?}: {}", file_path, e))
@@ -84,9 +80,7 @@ impl MagellanIntegration {
     ///
     /// Returns None if no code chunk exists at the given span.
     pub fn get_code_chunk(&self, file_path: &Path, start: usize, end: usize) -> Result<Option<String>> {
-        let file_path_str = file_path
-            .to_str()
-            .ok_or_else(|| SpliceError::Other(format!("Invalid UTF-8 in path: {:?}", file_path)))?;
+        let file_path_str = utf8_path(file_path)?;
 
         self.inner.get_code_chunk_by_span(file_path_str, start, end).map_err(|e| {
             SpliceError::Other(format!("Failed to get code chunk: {}", e))
@@ -99,9 +93,7 @@ impl MagellanIntegration {
     /// have the same name (e.g., struct + impl), you'll get all of them.
     /// Use `get_code_chunk` with exact spans for precision.
     pub fn get_code_chunks_for_symbol(&self, file_path: &Path, symbol_name: &str) -> Result<Vec<CodeChunk>> {
-        let file_path_str = file_path
-            .to_str()
-            .ok_or_else(|| SpliceError::Other(format!("Invalid UTF-8 in path: {:?}", file_path)))?;
+        let file_path_str = utf8_path(file_path)?;
 
         self.inner.get_code_chunks_for_symbol(file_path_str, symbol_name).map_err(|e| {
             SpliceError::Other(format!("Failed to get code chunks for symbol {}: {}", symbol_name, e))
@@ -5,21 +5,82 @@
 //! relationship management for multi-language code analysis.
 
 pub mod magellan_integration;
+pub mod query;
 pub mod schema;
 
-use crate::error::{Result, SpliceError};
-use crate::symbol::Language;
+use crate::error::{utf8_path, Result, SpliceError};
+use crate::symbol::{Language, Symbol};
 use serde_json::json;
-use sqlitegraph::{EdgeSpec, GraphBackend, NodeId, NodeSpec};
+use sqlitegraph::pattern::{entity_ids_with_constraint, NodeConstraint};
+use sqlitegraph::{
+    bulk_insert_edges, bulk_insert_entities, BackendDirection, EdgeSpec, GraphBackend,
+    GraphEdgeCreate, GraphEntityCreate, NeighborQuery, NodeId, NodeSpec, SqliteGraphBackend,
+};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// A symbol to store, with every property the graph records for it.
+#[derive(Debug, Clone, Copy)]
+pub struct SymbolRecord<'a> {
+    /// Symbol name.
+    pub name: &'a str,
+    /// Symbol kind (e.g., `function`, `struct`).
+    pub kind: &'a str,
+    /// Language the symbol is written in.
+    pub language: Language,
+    /// Visibility modifier (e.g., `pub`, `pub(crate)`, `private`).
+    pub visibility: Option<&'a str>,
+    /// Parameter types of an overloadable callable (e.g., `(int, String)`).
+    pub signature: Option<&'a str>,
+    /// Whether the symbol is test-only code.
+    pub in_test: bool,
+    /// Byte span as (byte_start, byte_end).
+    pub span: (usize, usize),
+}
+
+impl<'a> SymbolRecord<'a> {
+    /// A record with no visibility or signature, outside test code.
+    pub fn new(
+        name: &'a str,
+        kind: &'a str,
+        language: Language,
+        byte_start: usize,
+        byte_end: usize,
+    ) -> Self {
+        Self {
+            name,
+            kind,
+            language,
+            visibility: None,
+            signature: None,
+            in_test: false,
+            span: (byte_start, byte_end),
+        }
+    }
+
+    /// A record of an extracted symbol, except its signature, which the
+    /// symbol returns owned.
+    pub fn of<S: Symbol>(symbol: &'a S) -> Self {
+        Self {
+            visibility: symbol.visibility(),
+            in_test: symbol.in_test(),
+            ..Self::new(
+                symbol.name(),
+                symbol.kind(),
+                symbol.language(),
+                symbol.byte_start(),
+                symbol.byte_end(),
+            )
+        }
+    }
+}
 
 /// Graph database handle.
 ///
 /// Wraps SQLiteGraph and provides Splice-specific operations.
 pub struct CodeGraph {
-    /// The underlying graph backend.
-    backend: Box<dyn GraphBackend>,
+    /// The underlying graph backend, kept concrete for its bulk inserts.
+    backend: SqliteGraphBackend,
 
     /// Cache for symbol name → Vec<NodeId> mapping (multiple files can have same name).
     symbol_cache: HashMap<String, Vec<NodeId>>,
@@ -31,8 +92,7 @@ pub struct CodeGraph {
 impl CodeGraph {
     /// Open or create a code graph at the given path.
     pub fn open(path: &std::path::Path) -> Result<Self> {
-        let cfg = sqlitegraph::GraphConfig::sqlite();
-        let backend = sqlitegraph::open_graph(path, &cfg)?;
+        let backend = SqliteGraphBackend::from_graph(sqlitegraph::SqliteGraph::open(path)?);
         Ok(Self {
             backend,
             symbol_cache: HashMap::new(),
@@ -103,24 +163,43 @@ impl CodeGraph {
         language: Language,
         byte_start: usize,
         byte_end: usize,
+    ) -> Result<NodeId> {
+        self.store_symbol_record(
+            file_path,
+            &SymbolRecord::new(name, kind, language, byte_start, byte_end),
+        )
+    }
+
+    /// Store a symbol with every property the graph records for it.
+    ///
+    /// Creates the File node if it doesn't exist, the Symbol node, and a
+    /// DEFINES edge from File to Symbol.
+    ///
+    /// Returns the NodeId of the created Symbol node.
+    pub fn store_symbol_record(
+        &mut self,
+        file_path: &Path,
+        record: &SymbolRecord<'_>,
     ) -> Result<NodeId> {
         // Get or create File node
-        let file_path_str = file_path
-            .to_str()
-            .ok_or_else(|| SpliceError::Other(format!("Invalid UTF-8 in path: {:?}", file_path)))?;
+        let file_path_str = utf8_path(file_path)?;
         let file_node_id = self.get_or_create_file_node(file_path_str)?;
 
         // Determine label based on kind (language-agnostic)
-        let label = schema::kind_to_label(kind);
+        let label = schema::kind_to_label(record.kind);
 
         // Create symbol node with file_path and language in spec
+        let (byte_start, byte_end) = record.span;
         let node_spec = NodeSpec {
             kind: label.0,
-            name: name.to_string(),
+            name: record.name.to_string(),
             file_path: Some(file_path_str.to_string()),
             data: json!({
-                "kind": kind,
-                "language": language.as_str(),
+                "kind": record.kind,
+                "language": record.language.as_str(),
+                "visibility": record.visibility,
+                "signature": record.signature,
+                "in_test": record.in_test,
                 "byte_start": byte_start,
                 "byte_end": byte_end,
                 "file_path": file_path_str,
@@ -141,7 +220,7 @@ impl CodeGraph {
         self.backend.insert_edge(edge_spec)?;
 
         // Cache the symbol name → NodeId mapping (by file)
-        let cache_key = format!("{}::{}", file_path_str, name);
+        let cache_key = format!("{}::{}", file_path_str, record.name);
         self.symbol_cache
             .entry(cache_key)
             .or_default()
@@ -150,6 +229,104 @@ impl CodeGraph {
         Ok(symbol_id)
     }
 
+    /// Store every symbol extracted from one file, returning how many were stored.
+    ///
+    /// Each symbol keeps its visibility, overload signature, and whether it
+    /// is test-only code, as with `store_symbol_record`.
+    pub fn store_file_symbols<S: Symbol>(
+        &mut self,
+        file_path: &Path,
+        symbols: &[S],
+    ) -> Result<usize> {
+        for symbol in symbols {
+            let signature = symbol.signature();
+            let record = SymbolRecord {
+                signature: signature.as_deref(),
+                ..SymbolRecord::of(symbol)
+            };
+            self.store_symbol_record(file_path, &record)?;
+        }
+        Ok(symbols.len())
+    }
+
+    /// Store the symbols of many files at once, returning how many were stored.
+    ///
+    /// Stores the same nodes and edges as calling `store_file_symbols` per
+    /// file, but inserts them in batched transactions instead of one
+    /// statement each, which is what makes ingesting a large tree fast.
+    pub fn store_files_symbols<S: Symbol>(&mut self, files: &[(PathBuf, Vec<S>)]) -> Result<usize> {
+        let mut files_with_symbols = Vec::new();
+        for (file_path, symbols) in files {
+            if !symbols.is_empty() {
+                files_with_symbols.push((utf8_path(file_path)?, symbols));
+            }
+        }
+
+        // File nodes first, so the DEFINES edges have both endpoints
+        let new_files: Vec<&str> = files_with_symbols
+            .iter()
+            .map(|(path, _)| *path)
+            .filter(|path| !self.file_cache.contains_key(*path))
+            .collect();
+        let file_entities: Vec<GraphEntityCreate> = new_files
+            .iter()
+            .map(|path| GraphEntityCreate {
+                kind: schema::label_file().0,
+                name: path.to_string(),
+                file_path: Some(path.to_string()),
+                data: json!({ "path": path }),
+            })
+            .collect();
+        let file_ids = bulk_insert_entities(self.backend.graph(), &file_entities)?;
+        for (path, id) in new_files.into_iter().zip(file_ids) {
+            self.file_cache.insert(path.to_string(), NodeId::from(id));
+        }
+
+        let mut entities = Vec::new();
+        let mut owners = Vec::new();
+        for (path, symbols) in &files_with_symbols {
+            for symbol in symbols.iter() {
+                entities.push(GraphEntityCreate {
+                    kind: schema::kind_to_label(symbol.kind()).0,
+                    name: symbol.name().to_string(),
+                    file_path: Some(path.to_string()),
+                    data: json!({
+                        "kind": symbol.kind(),
+                        "language": symbol.language().as_str(),
+                        "visibility": symbol.visibility(),
+                        "signature": symbol.signature(),
+                        "in_test": symbol.in_test(),
+                        "byte_start": symbol.byte_start(),
+                        "byte_end": symbol.byte_end(),
+                        "file_path": path,
+                    }),
+                });
+                owners.push((*path, symbol.name()));
+            }
+        }
+        let symbol_ids = bulk_insert_entities(self.backend.graph(), &entities)?;
+
+        let edges: Vec<GraphEdgeCreate> = owners
+            .iter()
+            .zip(&symbol_ids)
+            .map(|((path, _), &id)| GraphEdgeCreate {
+                from_id: self.file_cache[*path].as_i64(),
+                to_id: id,
+                edge_type: schema::EDGE_DEFINES.to_string(),
+                data: json!({}),
+            })
+            .collect();
+        bulk_insert_edges(self.backend.graph(), &edges)?;
+
+        for ((path, name), id) in owners.into_iter().zip(symbol_ids) {
+            self.symbol_cache
+                .entry(format!("{}::{}", path, name))
+                .or_default()
+                .push(NodeId::from(id));
+        }
+        Ok(entities.len())
+    }
+
     /// Store a symbol with file association using Rust symbol kind.
     ///
     /// This is a backward-compatible method that internally converts
@@ -217,7 +394,8 @@ impl CodeGraph {
 
     /// Get all symbol nodes with a given name across all files.
     ///
-    /// Returns a Vec of (node_id, file_path) tuples for all symbols with the given name.
+    /// Returns a Vec of (node_id, file_path) tuples for all symbols with the given name,
+    /// sorted by file path and then byte offset so the order is stable across runs.
     pub fn find_symbols_by_name(&self, name: &str) -> Vec<(NodeId, Option<String>)> {
         let mut results = Vec::new();
 
@@ -228,22 +406,101 @@ impl CodeGraph {
                     // Try to get file_path from the node
                     if let Ok(node) = self.backend.get_node(node_id.as_i64()) {
                         let file_path = node.data.get("file_path").and_then(|v| v.as_str());
-                        results.push((node_id, file_path.map(|s| s.to_string())));
+                        let byte_start = node.data.get("byte_start").and_then(|v| v.as_u64());
+                        results.push((node_id, file_path.map(|s| s.to_string()), byte_start));
                     }
                 }
             }
         }
 
+        // The cache is a HashMap, so impose a deterministic order
+        results.sort_by(|a, b| (&a.1, a.2).cmp(&(&b.1, b.2)));
+
         results
+            .into_iter()
+            .map(|(node_id, file_path, _)| (node_id, file_path))
+            .collect()
     }
 
     /// Get symbol by file and name from cache.
     pub fn find_symbol_in_file(&self, file_path: &str, name: &str) -> Option<NodeId> {
+        self.find_symbols_in_file(file_path, name).first().copied()
+    }
+
+    /// Get every symbol with this name in a file, in storage order.
+    ///
+    /// Overloads and same-named items in different scopes share a name.
+    pub fn find_symbols_in_file(&self, file_path: &str, name: &str) -> &[NodeId] {
         let cache_key = format!("{}::{}", file_path, name);
-        self.symbol_cache
-            .get(&cache_key)
-            .and_then(|ids| ids.first())
-            .copied()
+        self.symbol_cache.get(&cache_key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Remove a symbol node and its edges (such as the DEFINES edge from its file).
+    ///
+    /// Name lookups stop returning the symbol as well.
+    pub fn remove_symbol(&mut self, node_id: NodeId) -> Result<()> {
+        self.backend.graph().delete_entity(node_id.as_i64())?;
+        self.uncache_symbol(node_id);
+        Ok(())
+    }
+
+    /// Remove the File node for `file_path`, every symbol stored for it, and
+    /// their DEFINES edges, returning how many symbols were removed.
+    ///
+    /// Nodes stored by earlier runs of a persistent graph are removed too.
+    pub fn remove_file(&mut self, file_path: &Path) -> Result<usize> {
+        let file_path_str = utf8_path(file_path)?;
+        let graph = self.backend.graph();
+
+        // Each earlier run stored a File node of its own
+        let mut file_ids: Vec<i64> = self
+            .file_cache
+            .remove(file_path_str)
+            .into_iter()
+            .map(NodeId::as_i64)
+            .collect();
+        let files = NodeConstraint {
+            kind: Some(schema::label_file().0),
+            name_prefix: Some(file_path_str.to_string()),
+        };
+        for id in entity_ids_with_constraint(graph, &files)? {
+            if !file_ids.contains(&id) && graph.get_entity(id)?.name == file_path_str {
+                file_ids.push(id);
+            }
+        }
+
+        let mut removed = Vec::new();
+        for file_id in file_ids {
+            let defines = NeighborQuery {
+                direction: BackendDirection::Outgoing,
+                edge_type: Some(schema::EDGE_DEFINES.to_string()),
+            };
+            for id in self.backend.neighbors(file_id, defines)? {
+                graph.delete_entity(id)?;
+                removed.push(NodeId::from(id));
+            }
+            graph.delete_entity(file_id)?;
+        }
+        for &node_id in &removed {
+            self.uncache_symbol(node_id);
+        }
+        Ok(removed.len())
+    }
+
+    /// Drop a removed symbol from the name lookups.
+    fn uncache_symbol(&mut self, node_id: NodeId) {
+        for ids in self.symbol_cache.values_mut() {
+            ids.retain(|&id| id != node_id);
+        }
+        self.symbol_cache.retain(|_, ids| !ids.is_empty());
+    }
+
+    /// Ids of every node in the database, including ones stored by earlier runs.
+    ///
+    /// Unlike the name lookups above, this does not depend on the in-memory
+    /// cache, so it sees everything a persistent graph holds.
+    pub fn node_ids(&self) -> Result<Vec<NodeId>> {
+        Ok(self.backend.entity_ids()?.into_iter().map(NodeId::from).collect())
     }
 
     /// Get the byte span for a NodeId.
@@ -273,11 +530,11 @@ impl CodeGraph {
 
     /// Access the underlying graph backend for advanced operations.
     pub fn inner(&self) -> &dyn GraphBackend {
-        self.backend.as_ref()
+        &self.backend
     }
 
     /// Access the underlying graph backend mutably for advanced operations.
     pub fn inner_mut(&mut self) -> &mut dyn GraphBackend {
-        self.backend.as_mut()
+        &mut self.backend
     }
 }
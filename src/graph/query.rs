@@ -0,0 +1,157 @@
+//! Selector queries over the symbols stored in a code graph.
+//!
+//! A selector is a whitespace-separated list of `key:value` terms, all of
+//! which must match:
+//!
+//! - `kind:function` — symbol kind as stored (`function`, `struct`, `type_alias`, ...)
+//! - `name:foo*` — symbol name, as a glob (`*`, `?`, `[...]`)
+//! - `file:src/lib.rs` — defining file; matches whole trailing path components
+//! - `lang:rust` — language (`language:` is accepted too)
+//!
+//! For example, `kind:function file:src/lib.rs name:parse_*` lists the
+//! `parse_` functions defined in `src/lib.rs`.
+
+use crate::error::{Result, SpliceError};
+use crate::graph::CodeGraph;
+use serde::Serialize;
+use std::path::Path;
+
+/// Parsed selector; each `None` field matches anything.
+#[derive(Debug, Clone, Default)]
+pub struct Selector {
+    /// Required symbol kind.
+    pub kind: Option<String>,
+    /// Glob the symbol name must match.
+    pub name: Option<glob::Pattern>,
+    /// Trailing path the defining file must end with.
+    pub file: Option<String>,
+    /// Required language.
+    pub language: Option<String>,
+}
+
+impl Selector {
+    /// Parse a selector such as `kind:function name:foo*`.
+    ///
+    /// An empty selector matches every symbol. Unknown keys, terms without a
+    /// `:`, empty values, and invalid globs are errors.
+    pub fn parse(selector: &str) -> Result<Self> {
+        let mut parsed = Selector::default();
+        for term in selector.split_whitespace() {
+            let invalid = |reason: &str| {
+                SpliceError::Other(format!("Invalid selector term '{}': {}", term, reason))
+            };
+            let (key, value) = term
+                .split_once(':')
+                .ok_or_else(|| invalid("expected key:value"))?;
+            if value.is_empty() {
+                return Err(invalid("empty value"));
+            }
+            match key {
+                "kind" => parsed.kind = Some(value.to_string()),
+                "name" => {
+                    let pattern = glob::Pattern::new(value).map_err(|e| invalid(&e.to_string()))?;
+                    parsed.name = Some(pattern);
+                }
+                "file" => parsed.file = Some(value.to_string()),
+                "lang" | "language" => parsed.language = Some(value.to_string()),
+                _ => return Err(invalid("unknown key (expected kind, name, file, or lang)")),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Whether a stored symbol satisfies every term.
+    fn matches(&self, symbol: &SymbolMatch) -> bool {
+        self.kind.as_ref().is_none_or(|kind| &symbol.kind == kind)
+            && self
+                .name
+                .as_ref()
+                .is_none_or(|pattern| pattern.matches(&symbol.name))
+            && self
+                .file
+                .as_ref()
+                .is_none_or(|file| Path::new(&symbol.file_path).ends_with(file))
+            && self
+                .language
+                .as_ref()
+                .is_none_or(|language| symbol.language.as_deref() == Some(language.as_str()))
+    }
+}
+
+/// A symbol node returned by [`query_symbols`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SymbolMatch {
+    /// Graph node id.
+    pub node_id: i64,
+    /// Symbol name.
+    pub name: String,
+    /// Symbol kind as stored (`function`, `struct`, ...).
+    pub kind: String,
+    /// Language, when recorded.
+    pub language: Option<String>,
+    /// Defining file.
+    pub file_path: String,
+    /// Start byte offset of the symbol.
+    pub byte_start: usize,
+    /// End byte offset of the symbol.
+    pub byte_end: usize,
+}
+
+/// Symbols in `graph` matching `selector`, ordered by file, then position.
+///
+/// Scans every stored node, so symbols written by earlier runs against a
+/// persistent graph are included. File nodes are never returned.
+pub fn query_symbols(graph: &CodeGraph, selector: &Selector) -> Result<Vec<SymbolMatch>> {
+    let mut matches = Vec::new();
+    for node_id in graph.node_ids()? {
+        let node = graph.inner().get_node(node_id.as_i64())?;
+        let str_prop = |key: &str| node.data.get(key).and_then(|v| v.as_str());
+        let u64_prop = |key: &str| node.data.get(key).and_then(|v| v.as_u64());
+
+        // Only symbol nodes carry a kind and span
+        let (Some(kind), Some(byte_start), Some(byte_end)) = (
+            str_prop("kind"),
+            u64_prop("byte_start"),
+            u64_prop("byte_end"),
+        ) else {
+            continue;
+        };
+        let symbol = SymbolMatch {
+            node_id: node.id,
+            name: node.name.clone(),
+            kind: kind.to_string(),
+            language: str_prop("language").map(str::to_string),
+            file_path: node.file_path.clone().unwrap_or_default(),
+            byte_start: byte_start as usize,
+            byte_end: byte_end as usize,
+        };
+        if selector.matches(&symbol) {
+            matches.push(symbol);
+        }
+    }
+
+    matches.sort_by(|a, b| {
+        (&a.file_path, a.byte_start, a.node_id).cmp(&(&b.file_path, b.byte_start, b.node_id))
+    });
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_selector_terms() {
+        let selector = Selector::parse("kind:function  file:src/lib.rs name:foo*").unwrap();
+        assert_eq!(selector.kind.as_deref(), Some("function"));
+        assert_eq!(selector.file.as_deref(), Some("src/lib.rs"));
+        assert!(selector.name.unwrap().matches("foo_bar"));
+        assert!(selector.language.is_none());
+
+        assert!(Selector::parse("").unwrap().kind.is_none());
+        assert!(Selector::parse("function").is_err());
+        assert!(Selector::parse("colour:red").is_err());
+        assert!(Selector::parse("name:").is_err());
+        assert!(Selector::parse("name:[").is_err());
+    }
+}
@@ -61,6 +61,11 @@ pub fn label_type_alias() -> Label {
     Label("symbol_type_alias".into())
 }
 
+/// Label for macro symbols.
+pub fn label_macro() -> Label {
+    Label("symbol_macro".into())
+}
+
 /// Label for File nodes.
 pub fn label_file() -> Label {
     Label("file".into())
@@ -116,7 +121,7 @@ pub fn kind_to_label(kind: &str) -> Label {
     match kind {
         "function" => label_function(),
         "method" => label_method(),
-        "class" | "struct" => label_class(),
+        "class" | "struct" | "data" => label_class(),
         "interface" => label_interface(),
         "enum" => label_enum(),
         "impl" => label_impl(),
@@ -125,6 +130,7 @@ pub fn kind_to_label(kind: &str) -> Label {
         "variable" | "field" | "const" | "static" => label_variable(),
         "constructor" => label_constructor(),
         "type_alias" => label_type_alias(),
+        "macro" => label_macro(),
         _ => label_function(), // Default fallback
     }
 }
@@ -4,6 +4,8 @@
 //! functions, classes, structs, namespaces, enums, and other C/C++ constructs with byte spans.
 
 use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
+use crate::ingest::{ensure_ast_depth, ParseOptions};
 use ropey::Rope;
 use std::path::Path;
 
@@ -37,6 +39,9 @@ pub struct CppSymbol {
     /// Function parameters (for functions and methods).
     pub parameters: Vec<String>,
 
+    /// Function parameter types, in order (e.g., `int`, `const std::string&`).
+    pub parameter_types: Vec<String>,
+
     /// Namespace path (e.g., `std::vector` or `Outer::Inner`).
     pub namespace_path: String,
 
@@ -102,14 +107,18 @@ impl CppSymbolKind {
 ///
 /// Returns a list of symbol entries ready for graph insertion.
 pub fn extract_cpp_symbols(path: &Path, source: &[u8]) -> Result<Vec<CppSymbol>> {
+    extract_cpp_symbols_with_options(path, source, &ParseOptions::default())
+}
+
+/// Extract C/C++ symbols like [`extract_cpp_symbols`], with `options` bounding
+/// the syntax tree accepted.
+pub fn extract_cpp_symbols_with_options(
+    path: &Path,
+    source: &[u8],
+    options: &ParseOptions,
+) -> Result<Vec<CppSymbol>> {
     // Create tree-sitter parser for C/C++
-    let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&tree_sitter_cpp::language())
-        .map_err(|e| SpliceError::Parse {
-            file: path.to_path_buf(),
-            message: format!("Failed to set C++ language: {:?}", e),
-        })?;
+    let mut parser = parser_for("C++", &tree_sitter_cpp::language())?;
 
     // Parse the source code
     let tree = parser
@@ -118,6 +127,7 @@ pub fn extract_cpp_symbols(path: &Path, source: &[u8]) -> Result<Vec<CppSymbol>>
             file: path.to_path_buf(),
             message: "Parse failed - no tree returned".to_string(),
         })?;
+    ensure_ast_depth(&tree, path, options.max_ast_depth)?;
 
     // Create Rope for line/col conversion
     let rope = Rope::from_str(std::str::from_utf8(source)?);
@@ -377,6 +387,7 @@ fn extract_symbol(
 
     // Extract parameters for functions
     let parameters = extract_parameters(node, source);
+    let parameter_types = extract_parameter_types(node, source);
 
     // Build fully qualified name
     let fully_qualified = if namespace_path.is_empty() {
@@ -410,6 +421,7 @@ fn extract_symbol(
         col_start,
         col_end,
         parameters,
+        parameter_types,
         namespace_path: namespace_path.to_string(),
         fully_qualified,
         is_template,
@@ -495,6 +507,67 @@ fn extract_parameters(node: tree_sitter::Node, source: &[u8]) -> Vec<String> {
     parameters
 }
 
+/// Extract parameter types from a function definition.
+///
+/// A type is the parameter's text without its name and default value, with
+/// whitespace collapsed, so `const std::string &s = ""` becomes
+/// `const std::string &`. A trailing `...` is kept as its own entry.
+fn extract_parameter_types(node: tree_sitter::Node, source: &[u8]) -> Vec<String> {
+    let mut types = Vec::new();
+
+    let mut cursor = node.walk();
+    let Some(declarator) = node
+        .children(&mut cursor)
+        .find(|child| child.kind() == "function_declarator")
+    else {
+        return types;
+    };
+    let Some(list) = declarator.child_by_field_name("parameters") else {
+        return types;
+    };
+
+    let mut list_cursor = list.walk();
+    for param in list.children(&mut list_cursor) {
+        match param.kind() {
+            "parameter_declaration"
+            | "optional_parameter_declaration"
+            | "variadic_parameter_declaration" => {}
+            "..." => {
+                types.push("...".to_string());
+                continue;
+            }
+            _ => continue,
+        }
+        let declarator = param.child_by_field_name("declarator");
+        let end = declarator.map_or(param.end_byte(), |d| d.end_byte());
+        let mut text = Vec::new();
+        match declarator.and_then(declared_identifier) {
+            Some(name) => {
+                text.extend_from_slice(&source[param.start_byte()..name.start_byte()]);
+                text.push(b' ');
+                text.extend_from_slice(&source[name.end_byte()..end]);
+            }
+            None => text.extend_from_slice(&source[param.start_byte()..end]),
+        }
+        let text = String::from_utf8_lossy(&text);
+        types.push(text.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+
+    types
+}
+
+/// The identifier a (possibly pointer, reference, or array) declarator declares.
+fn declared_identifier(declarator: tree_sitter::Node) -> Option<tree_sitter::Node> {
+    if declarator.kind() == "identifier" {
+        return Some(declarator);
+    }
+    let mut cursor = declarator.walk();
+    let found = declarator
+        .named_children(&mut cursor)
+        .find_map(declared_identifier);
+    found
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -22,9 +22,41 @@ pub enum Language {
     JavaScript,
     /// TypeScript (.ts, .tsx)
     TypeScript,
+    /// Haskell (.hs)
+    Haskell,
+    /// Elixir (.ex, .exs)
+    Elixir,
 }
 
 impl Language {
+    /// Every supported language, in the order they are listed to users.
+    pub const ALL: [Language; 9] = [
+        Language::Rust,
+        Language::Python,
+        Language::C,
+        Language::Cpp,
+        Language::Java,
+        Language::JavaScript,
+        Language::TypeScript,
+        Language::Haskell,
+        Language::Elixir,
+    ];
+
+    /// File extensions (without the dot) mapped to this language.
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Language::Rust => &["rs"],
+            Language::Python => &["py"],
+            Language::C => &["c", "h"],
+            Language::Cpp => &["cpp", "hpp", "cc", "cxx"],
+            Language::Java => &["java"],
+            Language::JavaScript => &["js", "mjs", "cjs"],
+            Language::TypeScript => &["ts", "tsx"],
+            Language::Haskell => &["hs"],
+            Language::Elixir => &["ex", "exs"],
+        }
+    }
+
     /// Convert language to string identifier.
     pub fn as_str(&self) -> &'static str {
         match self {
@@ -35,6 +67,8 @@ impl Language {
             Language::Java => "java",
             Language::JavaScript => "javascript",
             Language::TypeScript => "typescript",
+            Language::Haskell => "haskell",
+            Language::Elixir => "elixir",
         }
     }
 }
@@ -42,7 +76,9 @@ impl Language {
 /// Detect programming language from file path.
 ///
 /// Uses table-driven extension mapping. Returns None for unknown extensions.
-/// Never guesses or infers from file content.
+/// Never guesses or infers from file content. Only the last extension counts,
+/// so compound names like `app.test.tsx`, `index.d.ts` and `vendor.min.js`
+/// map to the language of their final component.
 ///
 /// # Examples
 ///
@@ -58,33 +94,30 @@ pub fn detect_language(path: &Path) -> Option<Language> {
     let extension = path.extension()?.to_str()?;
 
     // Table-driven mapping (case-sensitive)
-    let language = match extension {
-        // Rust
-        "rs" => Language::Rust,
-
-        // Python
-        "py" => Language::Python,
-
-        // C
-        "c" | "h" => Language::C,
-
-        // C++
-        "cpp" | "hpp" | "cc" | "cxx" => Language::Cpp,
-
-        // Java
-        "java" => Language::Java,
-
-        // JavaScript
-        "js" | "mjs" | "cjs" => Language::JavaScript,
-
-        // TypeScript
-        "ts" | "tsx" => Language::TypeScript,
-
-        // Unknown extension
-        _ => return None,
-    };
+    Language::ALL
+        .into_iter()
+        .find(|language| language.extensions().contains(&extension))
+}
 
-    Some(language)
+/// Whether `path` is a TypeScript declaration file (`*.d.ts`).
+///
+/// Declaration files carry types only and produce no JavaScript, so callers
+/// may want to treat them differently from ordinary TypeScript sources even
+/// though `detect_language` reports both as TypeScript.
+///
+/// # Examples
+///
+/// ```
+/// # use splice::ingest::detect::is_declaration_file;
+/// # use std::path::Path;
+/// assert!(is_declaration_file(Path::new("types/component.d.ts")));
+/// assert!(!is_declaration_file(Path::new("component.ts")));
+/// ```
+pub fn is_declaration_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(".d.ts"))
+        .is_some_and(|stem| !stem.is_empty())
 }
 
 #[cfg(test)]
@@ -158,6 +191,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_detect_elixir() {
+        assert_eq!(
+            detect_language(Path::new("lib/app.ex")),
+            Some(Language::Elixir)
+        );
+        assert_eq!(
+            detect_language(Path::new("mix.exs")),
+            Some(Language::Elixir)
+        );
+    }
+
     #[test]
     fn test_unknown_extension_returns_none() {
         assert_eq!(detect_language(Path::new("file.unknown")), None);
@@ -213,5 +258,17 @@ mod tests {
         assert_eq!(Language::Java.as_str(), "java");
         assert_eq!(Language::JavaScript.as_str(), "javascript");
         assert_eq!(Language::TypeScript.as_str(), "typescript");
+        assert_eq!(Language::Haskell.as_str(), "haskell");
+        assert_eq!(Language::Elixir.as_str(), "elixir");
+    }
+
+    #[test]
+    fn test_every_listed_extension_detects_its_language() {
+        for language in Language::ALL {
+            for extension in language.extensions() {
+                let path = format!("file.{}", extension);
+                assert_eq!(detect_language(Path::new(&path)), Some(language));
+            }
+        }
     }
 }
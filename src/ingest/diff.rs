@@ -0,0 +1,218 @@
+//! Symbol-level diff between two source snapshots.
+//!
+//! A snapshot is either a directory on disk or a git revision. Each snapshot is
+//! run through the normal extraction dispatcher and the resulting symbol sets are
+//! compared by name, kind, and fully-qualified name.
+
+use crate::error::{Result, SpliceError};
+use crate::ingest::dispatch::extract_symbols;
+use crate::symbol::{Language, Symbol};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A symbol captured from a snapshot, keyed by its snapshot-relative file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SnapshotSymbol {
+    /// File path relative to the snapshot root.
+    pub file: PathBuf,
+    /// Local symbol name.
+    pub name: String,
+    /// Symbol kind (e.g., "function", "class").
+    pub kind: String,
+    /// Fully qualified name.
+    pub fully_qualified: String,
+    /// Language identifier.
+    pub language: &'static str,
+}
+
+/// A symbol present in both snapshots but defined in a different file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MovedSymbol {
+    /// Symbol as it appears in the new snapshot.
+    pub symbol: SnapshotSymbol,
+    /// File that defined the symbol in the old snapshot.
+    pub from_file: PathBuf,
+}
+
+/// Differences between two symbol snapshots.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SymbolDiff {
+    /// Symbols only present in the new snapshot.
+    pub added: Vec<SnapshotSymbol>,
+    /// Symbols only present in the old snapshot.
+    pub removed: Vec<SnapshotSymbol>,
+    /// Symbols whose defining file changed.
+    pub moved: Vec<MovedSymbol>,
+}
+
+impl SymbolDiff {
+    /// Returns true when both snapshots define the same symbols in the same files.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty()
+    }
+}
+
+/// Extract symbols from every supported source file under a directory.
+///
+/// Skips `target/`, `.git/`, and hidden directories. Files that fail to parse
+/// are skipped so a single broken file does not hide the rest of the snapshot.
+pub fn collect_dir_symbols(root: &Path) -> Result<Vec<SnapshotSymbol>> {
+    let mut files = Vec::new();
+    collect_source_files(root, &mut files)?;
+    files.sort();
+
+    let mut symbols = Vec::new();
+    for path in files {
+        let source = std::fs::read(&path).map_err(|e| SpliceError::Io {
+            path: path.clone(),
+            source: e,
+        })?;
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        push_file_symbols(relative, &source, &mut symbols);
+    }
+
+    Ok(symbols)
+}
+
+/// Extract symbols from every supported source file at a git revision.
+///
+/// Files are read with `git show <rev>:<path>`, so the working tree is never touched.
+pub fn collect_git_symbols(repo: &Path, revision: &str) -> Result<Vec<SnapshotSymbol>> {
+    let listing = run_git(repo, &["ls-tree", "-r", "--name-only", revision])?;
+    let listing = String::from_utf8_lossy(&listing).into_owned();
+
+    let mut symbols = Vec::new();
+    for file in listing.lines() {
+        let relative = Path::new(file);
+        if Language::from_path(relative).is_none() {
+            continue;
+        }
+        let source = run_git(repo, &["show", &format!("{}:{}", revision, file)])?;
+        push_file_symbols(relative, &source, &mut symbols);
+    }
+
+    Ok(symbols)
+}
+
+/// Compare two symbol snapshots.
+///
+/// Symbols are matched on (name, kind, fully-qualified name). A match defined in
+/// the same file in both snapshots is unchanged; a match whose file changed is
+/// reported as moved. Unmatched symbols are added or removed.
+pub fn diff_symbols(before: &[SnapshotSymbol], after: &[SnapshotSymbol]) -> SymbolDiff {
+    type Key<'a> = (&'a str, &'a str, &'a str);
+
+    let mut before_map: BTreeMap<Key, Vec<&SnapshotSymbol>> = BTreeMap::new();
+    for symbol in before {
+        before_map.entry(key_of(symbol)).or_default().push(symbol);
+    }
+    let mut after_map: BTreeMap<Key, Vec<&SnapshotSymbol>> = BTreeMap::new();
+    for symbol in after {
+        after_map.entry(key_of(symbol)).or_default().push(symbol);
+    }
+
+    let mut diff = SymbolDiff::default();
+
+    for (key, mut old) in before_map {
+        let mut new = after_map.remove(&key).unwrap_or_default();
+
+        // Same file in both snapshots: unchanged
+        old.retain(|o| match new.iter().position(|n| n.file == o.file) {
+            Some(idx) => {
+                new.remove(idx);
+                false
+            }
+            None => true,
+        });
+
+        let paired = old.len().min(new.len());
+        for (o, n) in old.iter().zip(new.iter()) {
+            diff.moved.push(MovedSymbol {
+                symbol: (*n).clone(),
+                from_file: o.file.clone(),
+            });
+        }
+        diff.removed.extend(old[paired..].iter().map(|s| (*s).clone()));
+        diff.added.extend(new[paired..].iter().map(|s| (*s).clone()));
+    }
+
+    for (_, new) in after_map {
+        diff.added.extend(new.into_iter().cloned());
+    }
+
+    diff.added
+        .sort_by(|a, b| (&a.file, &a.fully_qualified).cmp(&(&b.file, &b.fully_qualified)));
+    diff.removed
+        .sort_by(|a, b| (&a.file, &a.fully_qualified).cmp(&(&b.file, &b.fully_qualified)));
+
+    diff
+}
+
+fn key_of(symbol: &SnapshotSymbol) -> (&str, &str, &str) {
+    (&symbol.name, &symbol.kind, &symbol.fully_qualified)
+}
+
+fn push_file_symbols(relative: &Path, source: &[u8], symbols: &mut Vec<SnapshotSymbol>) {
+    let extracted = match extract_symbols(relative, source) {
+        Ok(s) => s,
+        Err(e) => {
+            log::warn!("Skipping {} in symbol diff: {}", relative.display(), e);
+            return;
+        }
+    };
+
+    symbols.extend(extracted.iter().map(|symbol| SnapshotSymbol {
+        file: relative.to_path_buf(),
+        name: symbol.name().to_string(),
+        kind: symbol.kind().to_string(),
+        fully_qualified: symbol.fully_qualified().to_string(),
+        language: symbol.language().as_str(),
+    }));
+}
+
+fn collect_source_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = std::fs::read_dir(dir).map_err(|e| SpliceError::Io {
+        path: dir.to_path_buf(),
+        source: e,
+    })?;
+
+    for entry in entries {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+        if path.is_dir() {
+            if name == "target" || name.starts_with('.') {
+                continue;
+            }
+            collect_source_files(&path, files)?;
+        } else if Language::from_path(&path).is_some() {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Run `git` in `repo` and return its stdout, failing on a non-zero exit.
+pub(crate) fn run_git(repo: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .output()
+        .map_err(|e| SpliceError::Io {
+            path: repo.to_path_buf(),
+            source: e,
+        })?;
+
+    if !output.status.success() {
+        return Err(SpliceError::Other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(output.stdout)
+}
@@ -5,11 +5,18 @@
 //! extension and routes to the appropriate parser.
 
 use crate::error::{Result, SpliceError};
+use crate::ingest::{
+    cpp::extract_cpp_symbols_with_options, elixir::extract_elixir_symbols_with_options,
+    haskell::extract_haskell_symbols_with_options, java::extract_java_symbols_with_options,
+    javascript::extract_javascript_symbols_with_options,
+    python::extract_python_symbols_with_options, rust::extract_rust_symbols_with_options,
+    typescript::extract_typescript_symbols_with_options, ParseOptions,
+};
 use crate::ingest::{
     detect::detect_language,
     detect::Language as DetectLanguage,
     {
-        extract_cpp_symbols, extract_java_symbols, extract_javascript_symbols,
+        extract_cpp_symbols, extract_elixir_symbols, extract_haskell_symbols, extract_java_symbols, extract_javascript_symbols,
         extract_python_symbols, extract_rust_symbols, extract_typescript_symbols,
     },
 };
@@ -72,6 +79,14 @@ pub fn extract_symbols(path: &Path, source: &[u8]) -> Result<Vec<AnySymbol>> {
             let symbols = extract_typescript_symbols(path, source)?;
             Ok(symbols.into_iter().map(AnySymbol::TypeScript).collect())
         }
+        DetectLanguage::Haskell => {
+            let symbols = extract_haskell_symbols(path, source)?;
+            Ok(symbols.into_iter().map(AnySymbol::Haskell).collect())
+        }
+        DetectLanguage::Elixir => {
+            let symbols = extract_elixir_symbols(path, source)?;
+            Ok(symbols.into_iter().map(AnySymbol::Elixir).collect())
+        }
     }
 }
 
@@ -101,35 +116,121 @@ pub fn extract_symbols_with_language(
     path: &Path,
     source: &[u8],
     language: Language,
+) -> Result<Vec<AnySymbol>> {
+    extract_symbols_with_options(path, source, language, &ParseOptions::default())
+}
+
+/// Extract symbols in `language`, with `options` bounding the syntax tree
+/// accepted.
+///
+/// # Errors
+///
+/// Returns `SpliceError::Parse` if parsing fails, or `SpliceError::AstTooDeep`
+/// if the tree nests deeper than `options.max_ast_depth`.
+pub fn extract_symbols_with_options(
+    path: &Path,
+    source: &[u8],
+    language: Language,
+    options: &ParseOptions,
 ) -> Result<Vec<AnySymbol>> {
     match language {
         Language::Rust => {
-            let symbols = extract_rust_symbols(path, source)?;
+            let symbols = extract_rust_symbols_with_options(path, source, options)?;
             Ok(symbols.into_iter().map(AnySymbol::Rust).collect())
         }
         Language::Python => {
-            let symbols = extract_python_symbols(path, source)?;
+            let symbols = extract_python_symbols_with_options(path, source, options)?;
             Ok(symbols.into_iter().map(AnySymbol::Python).collect())
         }
         Language::C | Language::Cpp => {
-            let symbols = extract_cpp_symbols(path, source)?;
+            let symbols = extract_cpp_symbols_with_options(path, source, options)?;
             Ok(symbols.into_iter().map(AnySymbol::Cpp).collect())
         }
         Language::Java => {
-            let symbols = extract_java_symbols(path, source)?;
+            let symbols = extract_java_symbols_with_options(path, source, options)?;
             Ok(symbols.into_iter().map(AnySymbol::Java).collect())
         }
         Language::JavaScript => {
-            let symbols = extract_javascript_symbols(path, source)?;
+            let symbols = extract_javascript_symbols_with_options(path, source, options)?;
             Ok(symbols.into_iter().map(AnySymbol::JavaScript).collect())
         }
         Language::TypeScript => {
-            let symbols = extract_typescript_symbols(path, source)?;
+            let symbols = extract_typescript_symbols_with_options(path, source, options)?;
             Ok(symbols.into_iter().map(AnySymbol::TypeScript).collect())
         }
+        Language::Haskell => {
+            let symbols = extract_haskell_symbols_with_options(path, source, options)?;
+            Ok(symbols.into_iter().map(AnySymbol::Haskell).collect())
+        }
+        Language::Elixir => {
+            let symbols = extract_elixir_symbols_with_options(path, source, options)?;
+            Ok(symbols.into_iter().map(AnySymbol::Elixir).collect())
+        }
     }
 }
 
+/// Parse a source file and render its tree-sitter parse tree as an s-expression.
+///
+/// Uses the same grammar the extractor for `language` does (C is parsed with
+/// the C++ grammar, `.tsx` files with the TSX grammar), so the dump shows
+/// exactly the nodes extraction walks. Meant for diagnosing missed symbols.
+///
+/// # Example
+///
+/// ```
+/// use splice::ingest::dispatch::parse_tree_sexp;
+/// use splice::ingest::ParseOptions;
+/// use splice::symbol::Language;
+/// use std::path::Path;
+///
+/// let sexp = parse_tree_sexp(
+///     Path::new("lib.rs"),
+///     b"fn main() {}",
+///     Language::Rust,
+///     &ParseOptions::default(),
+/// )?;
+/// assert!(sexp.starts_with("(source_file (function_item"));
+/// # Ok::<(), splice::SpliceError>(())
+/// ```
+pub fn parse_tree_sexp(
+    path: &Path,
+    source: &[u8],
+    language: Language,
+    options: &ParseOptions,
+) -> Result<String> {
+    use crate::ingest::ensure_ast_depth;
+    use crate::ingest::grammar::parser_for;
+
+    let (name, grammar) = match language {
+        Language::Rust => ("Rust", tree_sitter_rust::language()),
+        Language::Python => ("Python", tree_sitter_python::language()),
+        Language::C | Language::Cpp => ("C++", tree_sitter_cpp::language()),
+        Language::Java => ("Java", tree_sitter_java::language()),
+        Language::JavaScript => ("JavaScript", tree_sitter_javascript::language()),
+        Language::TypeScript if path.extension().and_then(|e| e.to_str()) == Some("tsx") => {
+            ("TSX", tree_sitter_typescript::language_tsx())
+        }
+        Language::TypeScript => ("TypeScript", tree_sitter_typescript::language_typescript()),
+        Language::Haskell => ("Haskell", tree_sitter_haskell::language()),
+        Language::Elixir => ("Elixir", tree_sitter_elixir::language()),
+    };
+    let mut parser = parser_for(name, &grammar)?;
+
+    let source = match language {
+        Language::Rust => crate::ingest::rust::mask_shebang(source),
+        _ => std::borrow::Cow::Borrowed(source),
+    };
+    let tree = parser
+        .parse(&*source, None)
+        .ok_or_else(|| SpliceError::Parse {
+            file: path.to_path_buf(),
+            message: "Parse failed - no tree returned".to_string(),
+        })?;
+    ensure_ast_depth(&tree, path, options.max_ast_depth)?;
+
+    Ok(tree.root_node().to_sexp())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
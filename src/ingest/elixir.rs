@@ -0,0 +1,331 @@
+//! Elixir-specific tree-sitter parsing logic.
+//!
+//! This module contains tree-sitter-elixir integration for extracting
+//! modules, functions, and macros with byte spans.
+
+use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
+use crate::ingest::{ensure_ast_depth, ParseOptions};
+use ropey::Rope;
+use std::path::Path;
+
+/// Represents an Elixir symbol with its byte and line/col spans.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElixirSymbol {
+    /// Local symbol name (e.g., `greet`, or `Greeter` for a module).
+    pub name: String,
+
+    /// Symbol kind (module, function, macro).
+    pub kind: ElixirSymbolKind,
+
+    /// Start byte offset.
+    pub byte_start: usize,
+
+    /// End byte offset.
+    pub byte_end: usize,
+
+    /// Start line (1-based).
+    pub line_start: usize,
+
+    /// End line (1-based).
+    pub line_end: usize,
+
+    /// Start column (0-based, in bytes).
+    pub col_start: usize,
+
+    /// End column (0-based, in bytes).
+    pub col_end: usize,
+
+    /// Enclosing module (e.g., `MyApp.Greeter`; empty at top level).
+    pub module_name: String,
+
+    /// Fully qualified name (e.g., `MyApp.Greeter.greet`).
+    pub fully_qualified: String,
+
+    /// Whether the definition is private (`defp`/`defmacrop`).
+    pub is_private: bool,
+}
+
+/// Kinds of Elixir symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElixirSymbolKind {
+    /// `defmodule` block.
+    Module,
+    /// `def` or `defp` function.
+    Function,
+    /// `defmacro` or `defmacrop` macro.
+    Macro,
+}
+
+impl ElixirSymbolKind {
+    /// Convert to string for storage.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ElixirSymbolKind::Module => "module",
+            ElixirSymbolKind::Function => "function",
+            ElixirSymbolKind::Macro => "macro",
+        }
+    }
+}
+
+/// Extract symbols and spans from an Elixir source file.
+///
+/// Uses tree-sitter-elixir to parse the file and extract:
+/// - `defmodule` blocks (nested modules are qualified by their parent)
+/// - `def`/`defp` functions
+/// - `defmacro`/`defmacrop` macros
+///
+/// A function defined by several consecutive clauses is reported once, with a
+/// span covering every clause, so replacing the symbol replaces all of them.
+pub fn extract_elixir_symbols(path: &Path, source: &[u8]) -> Result<Vec<ElixirSymbol>> {
+    extract_elixir_symbols_with_options(path, source, &ParseOptions::default())
+}
+
+/// Extract Elixir symbols like [`extract_elixir_symbols`], with `options` bounding
+/// the syntax tree accepted.
+pub fn extract_elixir_symbols_with_options(
+    path: &Path,
+    source: &[u8],
+    options: &ParseOptions,
+) -> Result<Vec<ElixirSymbol>> {
+    let mut parser = parser_for("Elixir", &tree_sitter_elixir::language())?;
+
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| SpliceError::Parse {
+            file: path.to_path_buf(),
+            message: "Parse failed - no tree returned".to_string(),
+        })?;
+    ensure_ast_depth(&tree, path, options.max_ast_depth)?;
+
+    let rope = Rope::from_str(std::str::from_utf8(source)?);
+    let mut symbols = Vec::new();
+    extract_from_block(tree.root_node(), source, &rope, "", &mut symbols);
+    Ok(symbols)
+}
+
+/// Extract definitions among the direct children of `block`.
+fn extract_from_block(
+    block: tree_sitter::Node,
+    source: &[u8],
+    rope: &Rope,
+    module_name: &str,
+    symbols: &mut Vec<ElixirSymbol>,
+) {
+    // Index of the function/macro symbol that the previous sibling defined
+    let mut previous_def: Option<usize> = None;
+
+    let mut cursor = block.walk();
+    for node in block.named_children(&mut cursor) {
+        let Some((keyword, arguments)) = definition_call(node, source) else {
+            previous_def = None;
+            continue;
+        };
+
+        match keyword {
+            "defmodule" => {
+                previous_def = None;
+                let Some(alias) = arguments.named_child(0).filter(|n| n.kind() == "alias")
+                else {
+                    continue;
+                };
+                let name = alias.utf8_text(source).unwrap_or_default().to_string();
+                let qualified = qualify(module_name, &name);
+                symbols.push(make_symbol(
+                    name,
+                    ElixirSymbolKind::Module,
+                    node,
+                    rope,
+                    module_name,
+                    false,
+                ));
+
+                if let Some(body) = child_of_kind(node, "do_block") {
+                    extract_from_block(body, source, rope, &qualified, symbols);
+                }
+            }
+            "def" | "defp" | "defmacro" | "defmacrop" => {
+                let Some(name) = definition_name(arguments, source) else {
+                    previous_def = None;
+                    continue;
+                };
+                let kind = if keyword.starts_with("defmacro") {
+                    ElixirSymbolKind::Macro
+                } else {
+                    ElixirSymbolKind::Function
+                };
+
+                // Additional clause of the previous definition: extend its span
+                if let Some(index) = previous_def {
+                    let last = &mut symbols[index];
+                    if last.kind == kind && last.name == name {
+                        extend_span(last, node.end_byte(), rope);
+                        continue;
+                    }
+                }
+
+                symbols.push(make_symbol(
+                    name,
+                    kind,
+                    node,
+                    rope,
+                    module_name,
+                    keyword.ends_with('p'),
+                ));
+                previous_def = Some(symbols.len() - 1);
+            }
+            _ => previous_def = None,
+        }
+    }
+}
+
+/// If `node` is a call such as `def ...` or `defmodule ...`, return the
+/// keyword and its `arguments` node.
+fn definition_call<'a>(
+    node: tree_sitter::Node<'a>,
+    source: &'a [u8],
+) -> Option<(&'a str, tree_sitter::Node<'a>)> {
+    if node.kind() != "call" {
+        return None;
+    }
+    let target = node.child_by_field_name("target")?;
+    if target.kind() != "identifier" {
+        return None;
+    }
+    let keyword = target.utf8_text(source).ok()?;
+    let arguments = child_of_kind(node, "arguments")?;
+    Some((keyword, arguments))
+}
+
+/// Name of a `def`-style definition from its first argument.
+///
+/// Handles `def name(args)`, `def name(args) when guard`, and `def name`.
+fn definition_name(arguments: tree_sitter::Node, source: &[u8]) -> Option<String> {
+    let mut head = arguments.named_child(0)?;
+    if head.kind() == "binary_operator" {
+        head = head.child_by_field_name("left")?;
+    }
+    let name_node = match head.kind() {
+        "call" => head.child_by_field_name("target")?,
+        "identifier" => head,
+        _ => return None,
+    };
+    name_node.utf8_text(source).ok().map(|s| s.to_string())
+}
+
+fn child_of_kind<'a>(node: tree_sitter::Node<'a>, kind: &str) -> Option<tree_sitter::Node<'a>> {
+    let mut cursor = node.walk();
+    let found = node.named_children(&mut cursor).find(|c| c.kind() == kind);
+    found
+}
+
+fn qualify(module_name: &str, name: &str) -> String {
+    if module_name.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", module_name, name)
+    }
+}
+
+/// Build a symbol covering `node`.
+fn make_symbol(
+    name: String,
+    kind: ElixirSymbolKind,
+    node: tree_sitter::Node,
+    rope: &Rope,
+    module_name: &str,
+    is_private: bool,
+) -> ElixirSymbol {
+    let byte_start = node.start_byte();
+    let line_start = rope.byte_to_line(byte_start);
+    let col_start = byte_start - rope.line_to_byte(line_start);
+
+    let mut symbol = ElixirSymbol {
+        fully_qualified: qualify(module_name, &name),
+        name,
+        kind,
+        byte_start,
+        byte_end: byte_start,
+        line_start: line_start + 1,
+        line_end: line_start + 1,
+        col_start,
+        col_end: col_start,
+        module_name: module_name.to_string(),
+        is_private,
+    };
+    extend_span(&mut symbol, node.end_byte(), rope);
+    symbol
+}
+
+/// Move the end of a symbol's span to `byte_end`.
+fn extend_span(symbol: &mut ElixirSymbol, byte_end: usize, rope: &Rope) {
+    let line_end = rope.byte_to_line(byte_end);
+    symbol.byte_end = byte_end;
+    symbol.line_end = line_end + 1;
+    symbol.col_end = byte_end - rope.line_to_byte(line_end);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract(source: &str) -> Vec<ElixirSymbol> {
+        extract_elixir_symbols(Path::new("test.ex"), source.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_extract_module_functions_and_macros() {
+        let source = r#"defmodule MyApp.Greeter do
+  def hello(name), do: "Hello " <> name
+
+  def add(a, b) when is_integer(a) do
+    a + b
+  end
+
+  defp secret, do: 42
+
+  defmacro unless(cond, do: block) do
+    quote do: if(!unquote(cond), do: unquote(block))
+  end
+end
+"#;
+        let symbols = extract(source);
+        let summary: Vec<(&str, &str, &str)> = symbols
+            .iter()
+            .map(|s| (s.fully_qualified.as_str(), s.kind.as_str(), s.module_name.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("MyApp.Greeter", "module", ""),
+                ("MyApp.Greeter.hello", "function", "MyApp.Greeter"),
+                ("MyApp.Greeter.add", "function", "MyApp.Greeter"),
+                ("MyApp.Greeter.secret", "function", "MyApp.Greeter"),
+                ("MyApp.Greeter.unless", "macro", "MyApp.Greeter"),
+            ]
+        );
+        assert!(symbols[3].is_private);
+        assert!(!symbols[1].is_private);
+        assert_eq!(symbols[0].byte_start, 0);
+        assert_eq!(symbols[0].byte_end, source.trim_end().len());
+    }
+
+    #[test]
+    fn test_multiple_clauses_merge_into_one_symbol() {
+        let source = "defmodule Math do\n  def fact(0), do: 1\n  def fact(n), do: n * fact(n - 1)\n\n  def double(x), do: x * 2\nend\n";
+        let symbols = extract(source);
+        assert_eq!(symbols.len(), 3);
+        assert_eq!(symbols[1].name, "fact");
+        assert_eq!(symbols[1].line_start, 2);
+        assert_eq!(symbols[1].line_end, 3);
+        assert_eq!(symbols[2].name, "double");
+    }
+
+    #[test]
+    fn test_nested_module_is_qualified_by_parent() {
+        let source = "defmodule Outer do\n  defmodule Inner do\n    def x, do: 1\n  end\nend\n";
+        let symbols = extract(source);
+        let names: Vec<&str> = symbols.iter().map(|s| s.fully_qualified.as_str()).collect();
+        assert_eq!(names, vec!["Outer", "Outer.Inner", "Outer.Inner.x"]);
+    }
+}
@@ -0,0 +1,103 @@
+//! Tree-sitter grammar compatibility.
+//!
+//! Each bundled grammar is generated for a tree-sitter ABI version, and the
+//! linked `tree-sitter` library only loads grammars within the range it
+//! supports. A grammar crate bumped out of step with the library would
+//! otherwise fail every parse with an opaque `LanguageError`.
+
+use crate::error::{Result, SpliceError};
+use std::ops::RangeInclusive;
+
+/// Grammar ABI versions the linked tree-sitter library can load.
+pub fn supported_abi() -> RangeInclusive<usize> {
+    tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION
+}
+
+/// Check that a grammar generated for `grammar_abi` can be loaded.
+///
+/// `language` names the grammar in the error.
+pub fn check_grammar_abi(language: &str, grammar_abi: usize) -> Result<()> {
+    let supported_abi = supported_abi();
+    if supported_abi.contains(&grammar_abi) {
+        return Ok(());
+    }
+    Err(SpliceError::GrammarIncompatible {
+        language: language.to_string(),
+        grammar_abi,
+        supported_abi,
+    })
+}
+
+/// Create a parser for `grammar`.
+///
+/// An incompatible grammar is reported as `GrammarIncompatible` naming
+/// `language`, before any source is parsed.
+pub fn parser_for(language: &str, grammar: &tree_sitter::Language) -> Result<tree_sitter::Parser> {
+    check_grammar_abi(language, grammar.version())?;
+
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(grammar)
+        .map_err(|_| SpliceError::GrammarIncompatible {
+            language: language.to_string(),
+            grammar_abi: grammar.version(),
+            supported_abi: supported_abi(),
+        })?;
+    Ok(parser)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_grammars_are_compatible() {
+        let grammars = [
+            ("Rust", tree_sitter_rust::language()),
+            ("Python", tree_sitter_python::language()),
+            ("C", tree_sitter_c::language()),
+            ("C++", tree_sitter_cpp::language()),
+            ("Java", tree_sitter_java::language()),
+            ("JavaScript", tree_sitter_javascript::language()),
+            ("TypeScript", tree_sitter_typescript::language_typescript()),
+            ("TSX", tree_sitter_typescript::language_tsx()),
+            ("Haskell", tree_sitter_haskell::language()),
+            ("Elixir", tree_sitter_elixir::language()),
+        ];
+        for (name, grammar) in grammars {
+            assert!(
+                parser_for(name, &grammar).is_ok(),
+                "{} grammar should load",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_abi_is_grammar_incompatible() {
+        // An ABI outside the supported range is what set_language rejects
+        let newer = tree_sitter::LANGUAGE_VERSION + 1;
+        let error = check_grammar_abi("Rust", newer).unwrap_err();
+        assert_eq!(error.kind(), "GrammarIncompatible");
+        match &error {
+            SpliceError::GrammarIncompatible {
+                language,
+                grammar_abi,
+                supported_abi: supported,
+            } => {
+                assert_eq!(language, "Rust");
+                assert_eq!(*grammar_abi, newer);
+                assert_eq!(*supported, supported_abi());
+            }
+            other => panic!("expected GrammarIncompatible, got {:?}", other),
+        }
+        assert!(error
+            .to_string()
+            .starts_with(&format!("Rust grammar uses tree-sitter ABI {}", newer)));
+        assert!(error.hint().is_some());
+
+        let older = tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION - 1;
+        assert!(check_grammar_abi("Python", older).is_err());
+        assert!(check_grammar_abi("Python", tree_sitter::LANGUAGE_VERSION).is_ok());
+    }
+}
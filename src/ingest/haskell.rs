@@ -0,0 +1,284 @@
+//! Haskell-specific tree-sitter parsing logic.
+//!
+//! This module contains tree-sitter-haskell integration for extracting
+//! top-level function bindings, data types, and type classes with byte spans.
+
+use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
+use crate::ingest::{ensure_ast_depth, ParseOptions};
+use ropey::Rope;
+use std::path::Path;
+
+/// Represents a Haskell symbol with its byte and line/col spans.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HaskellSymbol {
+    /// Local symbol name (e.g., `fact`).
+    pub name: String,
+
+    /// Symbol kind (function, data type, type class).
+    pub kind: HaskellSymbolKind,
+
+    /// Start byte offset.
+    pub byte_start: usize,
+
+    /// End byte offset.
+    pub byte_end: usize,
+
+    /// Start line (1-based).
+    pub line_start: usize,
+
+    /// End line (1-based).
+    pub line_end: usize,
+
+    /// Start column (0-based, in bytes).
+    pub col_start: usize,
+
+    /// End column (0-based, in bytes).
+    pub col_end: usize,
+
+    /// Module name from the `module ... where` header (empty if absent).
+    pub module_name: String,
+
+    /// Fully qualified name (e.g., `Data.Shapes.area`).
+    pub fully_qualified: String,
+}
+
+/// Kinds of Haskell symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaskellSymbolKind {
+    /// Top-level function or value binding.
+    Function,
+    /// `data` or `newtype` declaration.
+    DataType,
+    /// `class` declaration.
+    TypeClass,
+}
+
+impl HaskellSymbolKind {
+    /// Convert to string for storage.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HaskellSymbolKind::Function => "function",
+            HaskellSymbolKind::DataType => "data",
+            HaskellSymbolKind::TypeClass => "class",
+        }
+    }
+}
+
+/// Extract symbols and spans from a Haskell source file.
+///
+/// Uses tree-sitter-haskell to parse the file and extract top-level:
+/// - Function and value bindings
+/// - `data` and `newtype` declarations
+/// - Type class declarations
+///
+/// A function defined by several equations is reported once. Its span starts
+/// at the type signature directly above it (if any) and ends after the last
+/// consecutive equation, so replacing the symbol replaces the whole definition.
+pub fn extract_haskell_symbols(path: &Path, source: &[u8]) -> Result<Vec<HaskellSymbol>> {
+    extract_haskell_symbols_with_options(path, source, &ParseOptions::default())
+}
+
+/// Extract Haskell symbols like [`extract_haskell_symbols`], with `options` bounding
+/// the syntax tree accepted.
+pub fn extract_haskell_symbols_with_options(
+    path: &Path,
+    source: &[u8],
+    options: &ParseOptions,
+) -> Result<Vec<HaskellSymbol>> {
+    let mut parser = parser_for("Haskell", &tree_sitter_haskell::language())?;
+
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| SpliceError::Parse {
+            file: path.to_path_buf(),
+            message: "Parse failed - no tree returned".to_string(),
+        })?;
+    ensure_ast_depth(&tree, path, options.max_ast_depth)?;
+
+    let rope = Rope::from_str(std::str::from_utf8(source)?);
+    let root = tree.root_node();
+    let module_name = extract_module_name(root, source).unwrap_or_default();
+
+    let mut symbols = Vec::new();
+    let Some(declarations) = root.child_by_field_name("declarations") else {
+        return Ok(symbols);
+    };
+
+    // Signature seen directly before the current declaration: (name, start byte)
+    let mut pending_signature: Option<(String, usize)> = None;
+
+    let mut cursor = declarations.walk();
+    for decl in declarations.named_children(&mut cursor) {
+        let name = match decl.child_by_field_name("name") {
+            Some(n) => n.utf8_text(source).unwrap_or_default().to_string(),
+            None => {
+                pending_signature = None;
+                continue;
+            }
+        };
+
+        match decl.kind() {
+            "signature" => {
+                pending_signature = Some((name, decl.start_byte()));
+                continue;
+            }
+            "function" | "bind" => {
+                // Additional equation of the previous function: extend its span
+                if let Some(last) = symbols.last_mut() {
+                    if last.kind == HaskellSymbolKind::Function
+                        && last.name == name
+                        && pending_signature.is_none()
+                    {
+                        extend_span(last, decl.end_byte(), &rope);
+                        continue;
+                    }
+                }
+
+                let start = match pending_signature.take() {
+                    Some((sig_name, sig_start)) if sig_name == name => sig_start,
+                    _ => decl.start_byte(),
+                };
+                symbols.push(make_symbol(
+                    name,
+                    HaskellSymbolKind::Function,
+                    start,
+                    decl.end_byte(),
+                    &rope,
+                    &module_name,
+                ));
+            }
+            "data_type" | "newtype" => {
+                symbols.push(make_symbol(
+                    name,
+                    HaskellSymbolKind::DataType,
+                    decl.start_byte(),
+                    decl.end_byte(),
+                    &rope,
+                    &module_name,
+                ));
+            }
+            "class" => {
+                symbols.push(make_symbol(
+                    name,
+                    HaskellSymbolKind::TypeClass,
+                    decl.start_byte(),
+                    decl.end_byte(),
+                    &rope,
+                    &module_name,
+                ));
+            }
+            _ => {}
+        }
+
+        pending_signature = None;
+    }
+
+    Ok(symbols)
+}
+
+/// Extract the dotted module name from the `module X.Y where` header.
+fn extract_module_name(root: tree_sitter::Node, source: &[u8]) -> Option<String> {
+    let header = root
+        .children(&mut root.walk())
+        .find(|child| child.kind() == "header")?;
+    let module = header.child_by_field_name("module")?;
+    module.utf8_text(source).ok().map(|s| s.to_string())
+}
+
+/// Build a symbol covering `byte_start..byte_end`.
+fn make_symbol(
+    name: String,
+    kind: HaskellSymbolKind,
+    byte_start: usize,
+    byte_end: usize,
+    rope: &Rope,
+    module_name: &str,
+) -> HaskellSymbol {
+    let line_start = rope.byte_to_line(byte_start);
+    let col_start = byte_start - rope.line_to_byte(line_start);
+
+    let fully_qualified = if module_name.is_empty() {
+        name.clone()
+    } else {
+        format!("{}.{}", module_name, name)
+    };
+
+    let mut symbol = HaskellSymbol {
+        name,
+        kind,
+        byte_start,
+        byte_end: byte_start,
+        line_start: line_start + 1,
+        line_end: line_start + 1,
+        col_start,
+        col_end: col_start,
+        module_name: module_name.to_string(),
+        fully_qualified,
+    };
+    extend_span(&mut symbol, byte_end, rope);
+    symbol
+}
+
+/// Move the end of a symbol's span to `byte_end`.
+fn extend_span(symbol: &mut HaskellSymbol, byte_end: usize, rope: &Rope) {
+    let line_end = rope.byte_to_line(byte_end);
+    symbol.byte_end = byte_end;
+    symbol.line_end = line_end + 1;
+    symbol.col_end = byte_end - rope.line_to_byte(line_end);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract(source: &str) -> Vec<HaskellSymbol> {
+        extract_haskell_symbols(Path::new("Test.hs"), source.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_extract_function_with_signature() {
+        let source = "add :: Int -> Int -> Int\nadd x y = x + y\n";
+        let symbols = extract(source);
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "add");
+        assert_eq!(symbols[0].kind.as_str(), "function");
+        assert_eq!(symbols[0].byte_start, 0);
+        assert_eq!(&source[symbols[0].byte_start..symbols[0].byte_end], source.trim_end());
+    }
+
+    #[test]
+    fn test_multiple_equations_merge_into_one_symbol() {
+        let source = "fact 0 = 1\nfact n = n * fact (n - 1)\n\nval = 3\n";
+        let symbols = extract(source);
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].name, "fact");
+        assert_eq!(symbols[0].line_start, 1);
+        assert_eq!(symbols[0].line_end, 2);
+        assert_eq!(symbols[1].name, "val");
+        assert_eq!(symbols[1].kind.as_str(), "function");
+    }
+
+    #[test]
+    fn test_extract_data_newtype_and_class() {
+        let source = "data Shape = Circle Double | Square Double\n\nnewtype Age = Age Int\n\nclass Pretty a where\n  pretty :: a -> String\n";
+        let symbols = extract(source);
+        let summary: Vec<(&str, &str)> = symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("Shape", "data"), ("Age", "data"), ("Pretty", "class")]
+        );
+    }
+
+    #[test]
+    fn test_module_header_qualifies_names() {
+        let source = "module Data.Shapes where\n\narea :: Double -> Double\narea r = r * r\n";
+        let symbols = extract(source);
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].module_name, "Data.Shapes");
+        assert_eq!(symbols[0].fully_qualified, "Data.Shapes.area");
+    }
+}
@@ -3,6 +3,7 @@
 //! Uses tree-sitter-cpp to parse and extract `#include` directives.
 
 use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
 use crate::ingest::imports::ImportKind;
 use std::path::Path;
 
@@ -22,13 +23,7 @@ use std::path::Path;
 /// ```
 pub fn extract_cpp_imports(path: &Path, source: &[u8]) -> Result<Vec<super::ImportFact>> {
     // Create tree-sitter parser for C/C++
-    let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&tree_sitter_cpp::language())
-        .map_err(|e| SpliceError::Parse {
-            file: path.to_path_buf(),
-            message: format!("Failed to set C++ language: {:?}", e),
-        })?;
+    let mut parser = parser_for("C++", &tree_sitter_cpp::language())?;
 
     // Parse the source code
     let tree = parser
@@ -122,6 +117,7 @@ fn extract_preproc_include(node: tree_sitter::Node, source: &[u8]) -> Option<sup
         imported_names: vec![path.clone()],
         is_glob: false,
         is_reexport: false,
+        aliases: Vec::new(),
         byte_span: (byte_start, byte_end),
     })
 }
@@ -3,6 +3,7 @@
 //! Uses tree-sitter-java to parse and extract `import` statements.
 
 use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
 use crate::ingest::imports::ImportKind;
 use std::path::Path;
 
@@ -23,13 +24,7 @@ use std::path::Path;
 /// ```
 pub fn extract_java_imports(path: &Path, source: &[u8]) -> Result<Vec<super::ImportFact>> {
     // Create tree-sitter parser for Java
-    let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&tree_sitter_java::language())
-        .map_err(|e| SpliceError::Parse {
-            file: path.to_path_buf(),
-            message: format!("Failed to set Java language: {:?}", e),
-        })?;
+    let mut parser = parser_for("Java", &tree_sitter_java::language())?;
 
     // Parse the source code
     let tree = parser
@@ -108,6 +103,7 @@ fn extract_import_declaration(node: tree_sitter::Node, source: &[u8]) -> Option<
         imported_names: Vec::new(), // Java imports don't specify local names
         is_glob,
         is_reexport: false,
+        aliases: Vec::new(),
         byte_span: (byte_start, byte_end),
     })
 }
@@ -3,6 +3,7 @@
 //! Uses tree-sitter-javascript to parse and extract ES6 `import` statements and CommonJS `require()` calls.
 
 use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
 use crate::ingest::imports::ImportKind;
 use std::path::Path;
 
@@ -24,13 +25,7 @@ use std::path::Path;
 /// ```
 pub fn extract_javascript_imports(path: &Path, source: &[u8]) -> Result<Vec<super::ImportFact>> {
     // Create tree-sitter parser for JavaScript
-    let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&tree_sitter_javascript::language())
-        .map_err(|e| SpliceError::Parse {
-            file: path.to_path_buf(),
-            message: format!("Failed to set JavaScript language: {:?}", e),
-        })?;
+    let mut parser = parser_for("JavaScript", &tree_sitter_javascript::language())?;
 
     // Parse the source code
     let tree = parser
@@ -179,6 +174,7 @@ fn extract_import_statement(node: tree_sitter::Node, source: &[u8]) -> Option<su
         imported_names,
         is_glob,
         is_reexport: false,
+        aliases: Vec::new(),
         byte_span: (byte_start, byte_end),
     })
 }
@@ -238,6 +234,7 @@ fn extract_require_call(node: tree_sitter::Node, source: &[u8]) -> Option<super:
         imported_names: vec![variable_name],
         is_glob: false,
         is_reexport: false,
+        aliases: Vec::new(),
         byte_span: (byte_start, byte_end),
     })
 }
@@ -36,6 +36,11 @@ pub struct ImportFact {
     /// Re-exports make the imported symbol available to other modules.
     pub is_reexport: bool,
 
+    /// Names imported under a local alias, as `(original, alias)` pairs
+    /// (e.g., `use crate::foo::bar as baz` → `("bar", "baz")`).
+    /// `imported_names` holds the alias, since that is the name used locally.
+    pub aliases: Vec<(String, String)>,
+
     /// Byte span of the import statement in source.
     pub byte_span: (usize, usize),
 }
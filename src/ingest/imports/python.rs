@@ -3,6 +3,7 @@
 //! Uses tree-sitter-python to parse and extract `import` and `from ... import` statements.
 
 use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
 use crate::ingest::imports::ImportKind;
 use std::path::Path;
 
@@ -22,13 +23,7 @@ use std::path::Path;
 /// ```
 pub fn extract_python_imports(path: &Path, source: &[u8]) -> Result<Vec<super::ImportFact>> {
     // Create tree-sitter parser for Python
-    let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&tree_sitter_python::language())
-        .map_err(|e| SpliceError::Parse {
-            file: path.to_path_buf(),
-            message: format!("Failed to set Python language: {:?}", e),
-        })?;
+    let mut parser = parser_for("Python", &tree_sitter_python::language())?;
 
     // Parse the source code
     let tree = parser
@@ -99,6 +94,7 @@ fn extract_import_statement(
                         imported_names: vec![imported_name],
                         is_glob: false,
                         is_reexport: false,
+                        aliases: Vec::new(),
                         byte_span: (byte_start, byte_end),
                     });
                 }
@@ -199,11 +195,9 @@ fn extract_import_from_statement(
                     imported_names.push(name_path.last().unwrap().clone());
                 }
             }
-            "aliased_import" => {
-                if stage == 2 {
-                    if let Some(alias) = extract_alias_name(child, source) {
-                        imported_names.push(alias);
-                    }
+            "aliased_import" if stage == 2 => {
+                if let Some(alias) = extract_alias_name(child, source) {
+                    imported_names.push(alias);
                 }
             }
             "wildcard_import" => {
@@ -221,6 +215,7 @@ fn extract_import_from_statement(
         imported_names,
         is_glob,
         is_reexport: false,
+        aliases: Vec::new(),
         byte_span: (byte_start, byte_end),
     })
 }
@@ -291,6 +286,7 @@ fn extract_aliased_import(
         imported_names: vec![imported_name],
         is_glob: false,
         is_reexport: false,
+        aliases: Vec::new(),
         byte_span: (byte_start, byte_end),
     })
 }
@@ -3,7 +3,9 @@
 //! Uses tree-sitter-rust to parse and extract `use` statements.
 
 use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
 use crate::ingest::imports::ImportKind;
+use crate::ingest::rust::mask_shebang;
 use std::path::Path;
 
 /// Extract import statements from a Rust source file.
@@ -22,17 +24,11 @@ use std::path::Path;
 /// ```
 pub fn extract_rust_imports(path: &Path, source: &[u8]) -> Result<Vec<super::ImportFact>> {
     // Create tree-sitter parser for Rust
-    let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&tree_sitter_rust::language())
-        .map_err(|e| SpliceError::Parse {
-            file: path.to_path_buf(),
-            message: format!("Failed to set Rust language: {:?}", e),
-        })?;
+    let mut parser = parser_for("Rust", &tree_sitter_rust::language())?;
 
     // Parse the source code
     let tree = parser
-        .parse(source, None)
+        .parse(&*mask_shebang(source), None)
         .ok_or_else(|| SpliceError::Parse {
             file: path.to_path_buf(),
             message: "Parse failed - no tree returned".to_string(),
@@ -93,6 +89,91 @@ fn extract_use_declaration(node: tree_sitter::Node, source: &[u8]) -> Option<sup
     }
 }
 
+/// Build an `ImportFact` for a single `use_declaration` node.
+///
+/// Used by symbol extraction to address `pub use` re-exports in place; the
+/// returned fact has an empty `file_path`.
+pub(crate) fn use_declaration_fact(
+    node: tree_sitter::Node,
+    source: &[u8],
+) -> Option<super::ImportFact> {
+    extract_use_declaration(node, source)
+}
+
+/// One name listed in the braced group of a `use` declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UseListEntry {
+    /// Name as exported by the group's path (`c` in `c as d`).
+    pub original: String,
+
+    /// Name bound locally (`d` in `c as d`, otherwise the same as `original`).
+    pub local: String,
+
+    /// Byte span of the entry inside the braces, without separators.
+    pub byte_span: (usize, usize),
+}
+
+/// List the entries of the braced group in the `use` declaration starting at `use_start`.
+///
+/// Returns an empty list when the declaration has no top-level group (for
+/// example `use a::b;`). Nested groups and globs inside the braces are skipped.
+pub fn use_list_entries(path: &Path, source: &[u8], use_start: usize) -> Result<Vec<UseListEntry>> {
+    let mut parser = parser_for("Rust", &tree_sitter_rust::language())?;
+    let tree = parser
+        .parse(&*mask_shebang(source), None)
+        .ok_or_else(|| SpliceError::Parse {
+            file: path.to_path_buf(),
+            message: "Parse failed - no tree returned".to_string(),
+        })?;
+
+    let mut node = tree
+        .root_node()
+        .descendant_for_byte_range(use_start, use_start);
+    while let Some(n) = node {
+        if n.kind() == "use_declaration" && n.start_byte() == use_start {
+            break;
+        }
+        node = n.parent();
+    }
+    let list = node
+        .and_then(|n| n.child_by_field_name("argument"))
+        .filter(|argument| argument.kind() == "scoped_use_list")
+        .and_then(|argument| argument.child_by_field_name("list"));
+    let Some(list) = list else {
+        return Ok(Vec::new());
+    };
+
+    let mut entries = Vec::new();
+    let mut cursor = list.walk();
+    for child in list.named_children(&mut cursor) {
+        let (original, local) = match child.kind() {
+            "identifier" | "self" | "scoped_identifier" => {
+                let name = last_path_segment(child, source);
+                (name.clone(), name)
+            }
+            "use_as_clause" => (
+                child
+                    .child_by_field_name("path")
+                    .and_then(|path| last_path_segment(path, source)),
+                child
+                    .child_by_field_name("alias")
+                    .and_then(|alias| alias.utf8_text(source).ok())
+                    .map(str::to_string),
+            ),
+            _ => continue,
+        };
+        if let (Some(original), Some(local)) = (original, local) {
+            entries.push(UseListEntry {
+                original,
+                local,
+                byte_span: (child.start_byte(), child.end_byte()),
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
 /// Check if a use_declaration is a re-export (pub use).
 fn check_is_reexport(node: tree_sitter::Node, source: &[u8]) -> bool {
     // Walk through children to find a visibility modifier
@@ -172,6 +253,15 @@ fn import_kind_from_path(path: &[String]) -> ImportKind {
     }
 }
 
+/// Last segment of a use path (`bar` in `inner::bar`, or a bare `bar`).
+fn last_path_segment(node: tree_sitter::Node, source: &[u8]) -> Option<String> {
+    let name = match node.kind() {
+        "scoped_identifier" => node.child_by_field_name("name")?,
+        _ => node,
+    };
+    name.utf8_text(source).ok().map(str::to_string)
+}
+
 /// Extract from scoped_identifier: use crate::foo::bar;
 fn extract_scoped_identifier(
     node: tree_sitter::Node,
@@ -190,6 +280,7 @@ fn extract_scoped_identifier(
         imported_names: vec![imported_name],
         is_glob: false,
         is_reexport,
+        aliases: Vec::new(),
         byte_span: (byte_start, byte_end),
     })
 }
@@ -221,6 +312,7 @@ fn extract_use_wildcard(
         imported_names: vec!["*".to_string()],
         is_glob: true,
         is_reexport,
+        aliases: Vec::new(),
         byte_span: (byte_start, byte_end),
     })
 }
@@ -244,6 +336,7 @@ fn extract_scoped_use_list(
 
     // Extract imported names from use_list
     let mut imported_names = Vec::new();
+    let mut aliases = Vec::new();
     let mut cursor = list_node.walk();
     for child in list_node.children(&mut cursor) {
         match child.kind() {
@@ -256,6 +349,12 @@ fn extract_scoped_use_list(
                 if let Some(alias) = child.child_by_field_name("alias") {
                     if let Ok(text) = alias.utf8_text(source) {
                         imported_names.push(text.to_string());
+                        if let Some(original) = child
+                            .child_by_field_name("path")
+                            .and_then(|path| last_path_segment(path, source))
+                        {
+                            aliases.push((original, text.to_string()));
+                        }
                     }
                 }
             }
@@ -271,6 +370,7 @@ fn extract_scoped_use_list(
         imported_names,
         is_glob: false,
         is_reexport,
+        aliases,
         byte_span: (byte_start, byte_end),
     })
 }
@@ -288,16 +388,17 @@ fn extract_use_as_clause(
     let alias = alias_node.utf8_text(source).ok()?.to_string();
 
     // For renamed imports, path doesn't include the original name
-    let (path, _imported_name) = extract_scoped_path(path_node, source)?;
+    let (path, imported_name) = extract_scoped_path(path_node, source)?;
     let import_kind = import_kind_from_path(&path);
 
     Some(super::ImportFact {
         file_path: std::path::PathBuf::new(),
         import_kind,
         path,
-        imported_names: vec![alias],
+        imported_names: vec![alias.clone()],
         is_glob: false,
         is_reexport,
+        aliases: vec![(imported_name, alias)],
         byte_span: (byte_start, byte_end),
     })
 }
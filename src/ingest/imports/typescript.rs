@@ -4,6 +4,7 @@
 //! TypeScript-specific type-only imports.
 
 use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
 use crate::ingest::imports::ImportKind;
 use std::path::Path;
 
@@ -29,27 +30,15 @@ use std::path::Path;
 /// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 pub fn extract_typescript_imports(path: &Path, source: &[u8]) -> Result<Vec<super::ImportFact>> {
-    let mut parser = tree_sitter::Parser::new();
-
     // Choose parser based on file extension
     let extension = path.extension().and_then(|e| e.to_str());
     let is_tsx = extension == Some("tsx");
 
-    if is_tsx {
-        parser
-            .set_language(&tree_sitter_typescript::language_tsx())
-            .map_err(|e| SpliceError::Parse {
-                file: path.to_path_buf(),
-                message: format!("Failed to set TSX language: {:?}", e),
-            })?;
+    let mut parser = if is_tsx {
+        parser_for("TSX", &tree_sitter_typescript::language_tsx())?
     } else {
-        parser
-            .set_language(&tree_sitter_typescript::language_typescript())
-            .map_err(|e| SpliceError::Parse {
-                file: path.to_path_buf(),
-                message: format!("Failed to set TypeScript language: {:?}", e),
-            })?;
-    }
+        parser_for("TypeScript", &tree_sitter_typescript::language_typescript())?
+    };
 
     // Parse the source code
     let tree = parser
@@ -227,6 +216,7 @@ fn extract_import_statement(node: tree_sitter::Node, source: &[u8]) -> Option<su
         imported_names,
         is_glob,
         is_reexport: false,
+        aliases: Vec::new(),
         byte_span: (byte_start, byte_end),
     })
 }
@@ -286,6 +276,7 @@ fn extract_require_call(node: tree_sitter::Node, source: &[u8]) -> Option<super:
         imported_names: vec![variable_name],
         is_glob: false,
         is_reexport: false,
+        aliases: Vec::new(),
         byte_span: (byte_start, byte_end),
     })
 }
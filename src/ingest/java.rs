@@ -4,6 +4,8 @@
 //! classes, interfaces, enums, methods, constructors, fields, and other Java constructs with byte spans.
 
 use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
+use crate::ingest::{ensure_ast_depth, ParseOptions};
 use ropey::Rope;
 use std::path::Path;
 
@@ -37,6 +39,9 @@ pub struct JavaSymbol {
     /// Method/constructor parameters.
     pub parameters: Vec<String>,
 
+    /// Method/constructor parameter types, in order (e.g., `int`, `String...`).
+    pub parameter_types: Vec<String>,
+
     /// Class/interface path (e.g., `Outer.Inner`).
     pub container_path: String,
 
@@ -93,13 +98,17 @@ impl JavaSymbolKind {
 ///
 /// Returns a list of symbol entries ready for graph insertion.
 pub fn extract_java_symbols(path: &Path, source: &[u8]) -> Result<Vec<JavaSymbol>> {
-    let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&tree_sitter_java::language())
-        .map_err(|e| SpliceError::Parse {
-            file: path.to_path_buf(),
-            message: format!("Failed to set Java language: {:?}", e),
-        })?;
+    extract_java_symbols_with_options(path, source, &ParseOptions::default())
+}
+
+/// Extract Java symbols like [`extract_java_symbols`], with `options` bounding
+/// the syntax tree accepted.
+pub fn extract_java_symbols_with_options(
+    path: &Path,
+    source: &[u8],
+    options: &ParseOptions,
+) -> Result<Vec<JavaSymbol>> {
+    let mut parser = parser_for("Java", &tree_sitter_java::language())?;
 
     let tree = parser
         .parse(source, None)
@@ -107,6 +116,7 @@ pub fn extract_java_symbols(path: &Path, source: &[u8]) -> Result<Vec<JavaSymbol
             file: path.to_path_buf(),
             message: "Parse failed - no tree returned".to_string(),
         })?;
+    ensure_ast_depth(&tree, path, options.max_ast_depth)?;
 
     let rope = Rope::from_str(std::str::from_utf8(source)?);
 
@@ -238,6 +248,7 @@ fn extract_symbol(
     let col_end = byte_end - line_end_byte;
 
     let parameters = extract_parameters(node, source);
+    let parameter_types = extract_parameter_types(node, source);
 
     let fully_qualified = if container_path.is_empty() {
         name.clone()
@@ -255,6 +266,7 @@ fn extract_symbol(
         col_start,
         col_end,
         parameters,
+        parameter_types,
         container_path: container_path.to_string(),
         fully_qualified,
         is_public,
@@ -309,6 +321,40 @@ fn extract_parameters(node: tree_sitter::Node, source: &[u8]) -> Vec<String> {
     parameters
 }
 
+/// Extract parameter types from a method/constructor, with whitespace collapsed.
+///
+/// Varargs keep their `...` suffix (`String...`).
+fn extract_parameter_types(node: tree_sitter::Node, source: &[u8]) -> Vec<String> {
+    let mut types = Vec::new();
+
+    if let Some(params) = node.child_by_field_name("parameters") {
+        for param in params.children(&mut params.walk()) {
+            let suffix = match param.kind() {
+                "formal_parameter" => "",
+                "spread_parameter" => "...",
+                _ => continue,
+            };
+            // Spread parameters have no `type` field; the type is the first named child
+            let type_node = param.child_by_field_name("type").or_else(|| {
+                param.children(&mut param.walk()).find(|child| {
+                    child.is_named() && !matches!(child.kind(), "modifiers" | "variable_declarator")
+                })
+            });
+            if let Some(Ok(text)) = type_node.map(|n| n.utf8_text(source)) {
+                let mut ty = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                // `int[] a` and `int a[]` declare the same type
+                if let Some(dims) = param.child_by_field_name("dimensions") {
+                    ty.push_str(dims.utf8_text(source).unwrap_or_default());
+                }
+                ty.push_str(suffix);
+                types.push(ty);
+            }
+        }
+    }
+
+    types
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
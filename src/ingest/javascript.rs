@@ -4,6 +4,8 @@
 //! functions, classes, variables, interfaces, types, and other JS/TS constructs with byte spans.
 
 use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
+use crate::ingest::{ensure_ast_depth, ParseOptions};
 use ropey::Rope;
 use std::path::Path;
 
@@ -102,13 +104,17 @@ impl JavaScriptSymbolKind {
 ///
 /// Returns a list of symbol entries ready for graph insertion.
 pub fn extract_javascript_symbols(path: &Path, source: &[u8]) -> Result<Vec<JavaScriptSymbol>> {
-    let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&tree_sitter_javascript::language())
-        .map_err(|e| SpliceError::Parse {
-            file: path.to_path_buf(),
-            message: format!("Failed to set JavaScript language: {:?}", e),
-        })?;
+    extract_javascript_symbols_with_options(path, source, &ParseOptions::default())
+}
+
+/// Extract JavaScript symbols like [`extract_javascript_symbols`], with `options` bounding
+/// the syntax tree accepted.
+pub fn extract_javascript_symbols_with_options(
+    path: &Path,
+    source: &[u8],
+    options: &ParseOptions,
+) -> Result<Vec<JavaScriptSymbol>> {
+    let mut parser = parser_for("JavaScript", &tree_sitter_javascript::language())?;
 
     let tree = parser
         .parse(source, None)
@@ -116,6 +122,7 @@ pub fn extract_javascript_symbols(path: &Path, source: &[u8]) -> Result<Vec<Java
             file: path.to_path_buf(),
             message: "Parse failed - no tree returned".to_string(),
         })?;
+    ensure_ast_depth(&tree, path, options.max_ast_depth)?;
 
     let rope = Rope::from_str(std::str::from_utf8(source)?);
 
@@ -1,27 +1,38 @@
 //! Filesystem → AST → SQLiteGraph ingestion pipeline.
 //!
-//! This module handles reading Rust/Python/C/C++/Java/JavaScript/TypeScript source files, parsing them with
+//! This module handles reading Rust/Python/C/C++/Java/JavaScript/TypeScript/Haskell/Elixir source files, parsing them with
 //! tree-sitter, and storing symbols and spans in the code graph.
 
 pub mod cpp;
 pub mod detect;
+pub mod diff;
 pub mod dispatch;
+pub mod elixir;
+pub mod grammar;
+pub mod haskell;
 pub mod imports;
 pub mod java;
 pub mod javascript;
 pub mod magellan;
 pub mod python;
 pub mod rust;
+pub mod source;
 pub mod typescript;
 
-use crate::error::Result;
+use crate::error::{Result, SpliceError};
 use crate::graph::CodeGraph;
-use std::path::Path;
+use crate::symbol::AnySymbol;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
 
 /// Re-export common types for convenience.
 pub use cpp::{extract_cpp_symbols, CppSymbol, CppSymbolKind};
 pub use detect::{detect_language, Language};
-pub use dispatch::{extract_symbols, extract_symbols_with_language};
+pub use diff::{diff_symbols, SymbolDiff};
+pub use dispatch::{extract_symbols, extract_symbols_with_language, extract_symbols_with_options};
+pub use elixir::{extract_elixir_symbols, ElixirSymbol, ElixirSymbolKind};
+pub use haskell::{extract_haskell_symbols, HaskellSymbol, HaskellSymbolKind};
 pub use imports::{
     extract_cpp_imports, extract_java_imports, extract_javascript_imports, extract_python_imports,
     extract_rust_imports, ImportFact, ImportKind,
@@ -31,36 +42,282 @@ pub use javascript::{extract_javascript_symbols, JavaScriptSymbol, JavaScriptSym
 pub use magellan::{ingest_file_with_magellan, MagellanIngestor};
 pub use python::{extract_python_symbols, PythonSymbol, PythonSymbolKind};
 pub use rust::{extract_rust_symbols, RustSymbol, RustSymbolKind, Visibility};
+pub use source::SourceFile;
 pub use typescript::{extract_typescript_symbols, TypeScriptSymbol, TypeScriptSymbolKind};
 
+/// Default upper bound on source file size (2 MiB).
+///
+/// Larger files are usually minified or vendored code that is slow to parse and
+/// rarely a refactoring target.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Default upper bound on syntax tree depth.
+///
+/// Symbol extraction and scope building still recurse once per tree level, so
+/// a generated expression with thousands of nested parentheses would otherwise
+/// overflow the stack. Method chains of a few hundred calls stay well below it.
+pub const DEFAULT_MAX_AST_DEPTH: usize = 2048;
+
+/// Stack size of the parser and validation threads.
+///
+/// Matches a typical main thread so that trees up to `DEFAULT_MAX_AST_DEPTH`
+/// extract on workers as well; rayon's default of 2 MiB is not enough.
+pub(crate) const PARSER_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Limits applied when parsing a source file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Refuse syntax trees nested deeper than this many levels.
+    pub max_ast_depth: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_ast_depth: DEFAULT_MAX_AST_DEPTH,
+        }
+    }
+}
+
+/// Fail with `AstTooDeep` if `tree` nests deeper than `limit` levels.
+///
+/// The tree is walked with a cursor rather than recursively, stopping at the
+/// first node past the limit.
+pub fn ensure_ast_depth(tree: &tree_sitter::Tree, file: &Path, limit: usize) -> Result<()> {
+    let mut cursor = tree.walk();
+    let mut depth = 0;
+    loop {
+        if cursor.goto_first_child() {
+            depth += 1;
+            if depth > limit {
+                return Err(SpliceError::AstTooDeep {
+                    file: file.to_path_buf(),
+                    limit,
+                });
+            }
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                return Ok(());
+            }
+            depth -= 1;
+        }
+    }
+}
+
+/// Return the file's size if it exceeds `max_file_size` bytes.
+///
+/// Files whose metadata cannot be read are not considered oversized; the
+/// subsequent read reports the real error.
+pub fn oversized_file(path: &Path, max_file_size: u64) -> Option<u64> {
+    let size = std::fs::metadata(path).ok()?.len();
+    (size > max_file_size).then_some(size)
+}
+
+/// Compile `--exclude` glob patterns into one matcher.
+///
+/// ```
+/// use splice::ingest::{exclude_set, is_excluded};
+/// use std::path::Path;
+///
+/// let excludes = exclude_set(&["src/generated/**".to_string()]).unwrap();
+/// assert!(is_excluded(&excludes, Path::new("src/generated/api.rs")));
+/// assert!(!is_excluded(&excludes, Path::new("src/lib.rs")));
+/// ```
+pub fn exclude_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| {
+            SpliceError::Other(format!("Invalid exclude pattern '{}': {}", pattern, e))
+        })?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| SpliceError::Other(format!("Invalid exclude patterns: {}", e)))
+}
+
+/// Whether `path` matches an exclude glob.
+///
+/// Absolute paths are also tried relative to the current directory, so
+/// `src/generated/**` excludes files a workspace scan reports as absolute.
+pub fn is_excluded(excludes: &GlobSet, path: &Path) -> bool {
+    if excludes.is_empty() {
+        return false;
+    }
+    excludes.is_match(path)
+        || std::env::current_dir()
+            .ok()
+            .and_then(|cwd| path.strip_prefix(cwd).ok().map(|p| excludes.is_match(p)))
+            .unwrap_or(false)
+}
+
 /// Main ingest orchestrator.
 ///
-/// Reads Rust files from the filesystem, parses them with tree-sitter,
-/// and stores symbols/spans in the SQLiteGraph database.
+/// Reads source files in any supported language from the filesystem, parses
+/// them with tree-sitter, and stores symbols/spans in the SQLiteGraph database.
 pub struct Ingestor {
-    /// Graph database handle (not yet used, pending implementation).
-    _graph: CodeGraph,
+    graph: CodeGraph,
+    jobs: usize,
+    max_file_size: u64,
+    parse: ParseOptions,
+    excludes: GlobSet,
+}
+
+/// Outcome of `Ingestor::ingest_dir`.
+#[derive(Debug, Clone, Default)]
+pub struct IngestSummary {
+    /// Files whose symbols were stored.
+    pub files: usize,
+    /// Symbols stored across all files.
+    pub symbols: usize,
+    /// Files that were found but not stored, with the reason.
+    pub skipped_files: Vec<(PathBuf, String)>,
 }
 
 impl Ingestor {
     /// Create a new ingestor with the given graph database.
+    ///
+    /// Parsing uses one thread per CPU unless `with_jobs` says otherwise.
     pub fn new(graph: CodeGraph) -> Self {
-        Self { _graph: graph }
+        Self {
+            graph,
+            jobs: 0,
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            parse: ParseOptions::default(),
+            excludes: GlobSet::empty(),
+        }
     }
 
-    /// Ingest a single Rust source file.
-    pub fn ingest_file(&mut self, _path: &Path) -> Result<()> {
-        // TODO: Implement in Task 1
-        Err(crate::error::SpliceError::Other(
-            "Not implemented yet".to_string(),
-        ))
+    /// Parse with at most `jobs` threads; `0` means one per CPU.
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs;
+        self
     }
 
-    /// Ingest a directory of Rust source files recursively.
-    pub fn ingest_dir(&mut self, _path: &Path) -> Result<()> {
-        // TODO: Implement in Task 1
-        Err(crate::error::SpliceError::Other(
-            "Not implemented yet".to_string(),
-        ))
+    /// Skip files larger than `max_file_size` bytes.
+    pub fn with_max_file_size(mut self, max_file_size: u64) -> Self {
+        self.max_file_size = max_file_size;
+        self
+    }
+
+    /// Parse files with `options` instead of the defaults.
+    pub fn with_parse_options(mut self, options: ParseOptions) -> Self {
+        self.parse = options;
+        self
+    }
+
+    /// Skip files matching any of `excludes` (see `exclude_set`).
+    pub fn with_excludes(mut self, excludes: GlobSet) -> Self {
+        self.excludes = excludes;
+        self
+    }
+
+    /// The graph symbols are stored in.
+    pub fn graph(&self) -> &CodeGraph {
+        &self.graph
+    }
+
+    /// Give back the graph, e.g. to resolve against what was ingested.
+    pub fn into_graph(self) -> CodeGraph {
+        self.graph
+    }
+
+    /// Ingest a single source file, returning the number of symbols stored.
+    pub fn ingest_file(&mut self, path: &Path) -> Result<usize> {
+        let symbols = parse_file(path, &self.parse)?;
+        self.graph.store_file_symbols(path, &symbols)
+    }
+
+    /// Ingest every supported source file under `path`, recursively.
+    ///
+    /// Hidden directories, `target/` and `node_modules/` are not entered, and
+    /// files matching an exclude glob are left out.
+    /// Files are parsed in parallel, then stored from this thread in one
+    /// pass, since the graph handle is not shared between threads. Files
+    /// that cannot be read or parsed are reported in `skipped_files` rather
+    /// than failing the whole run.
+    pub fn ingest_dir(&mut self, path: &Path) -> Result<IngestSummary> {
+        let mut summary = IngestSummary::default();
+        let mut files = Vec::new();
+        collect_source_files(
+            path,
+            self.max_file_size,
+            &self.excludes,
+            &mut files,
+            &mut summary.skipped_files,
+        )?;
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.jobs)
+            .stack_size(PARSER_STACK_SIZE)
+            .build()
+            .map_err(|err| {
+                SpliceError::Other(format!("Failed to start parser threads: {}", err))
+            })?;
+        let parse = self.parse;
+        let parsed: Vec<(PathBuf, Result<Vec<AnySymbol>>)> = pool.install(|| {
+            files
+                .into_par_iter()
+                .map(|file| {
+                    let symbols = parse_file(&file, &parse);
+                    (file, symbols)
+                })
+                .collect()
+        });
+
+        let mut stored = Vec::new();
+        for (file, symbols) in parsed {
+            match symbols {
+                Ok(symbols) => stored.push((file, symbols)),
+                Err(err) => summary.skipped_files.push((file, err.to_string())),
+            }
+        }
+        summary.symbols = self.graph.store_files_symbols(&stored)?;
+        summary.files = stored.len();
+
+        Ok(summary)
+    }
+}
+
+fn parse_file(path: &Path, options: &ParseOptions) -> Result<Vec<AnySymbol>> {
+    let language = crate::symbol::Language::from_path(path).ok_or_else(|| SpliceError::Parse {
+        file: path.to_path_buf(),
+        message: "Cannot detect language - unknown file extension".to_string(),
+    })?;
+    let source = std::fs::read(path)?;
+    extract_symbols_with_options(path, &source, language, options)
+}
+
+/// Collect files with a known language under `dir`, in sorted order.
+fn collect_source_files(
+    dir: &Path,
+    max_file_size: u64,
+    excludes: &GlobSet,
+    files: &mut Vec<PathBuf>,
+    skipped: &mut Vec<(PathBuf, String)>,
+) -> Result<()> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "target" && name != "node_modules" {
+                collect_source_files(&path, max_file_size, excludes, files, skipped)?;
+            }
+        } else if detect_language(&path).is_some() && !is_excluded(excludes, &path) {
+            match oversized_file(&path, max_file_size) {
+                Some(size) => skipped.push((
+                    path,
+                    format!("{} bytes exceeds the {} byte limit", size, max_file_size),
+                )),
+                None => files.push(path),
+            }
+        }
     }
+    Ok(())
 }
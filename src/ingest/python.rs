@@ -4,10 +4,14 @@
 //! functions, classes, and other Python constructs with byte spans.
 
 use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
+use crate::ingest::{ensure_ast_depth, ParseOptions};
 use ropey::Rope;
 use std::path::Path;
 
 /// Represents a Python symbol with its byte and line/col spans.
+///
+/// Spans of decorated functions and classes start at the first decorator.
 #[derive(Debug, Clone, PartialEq)]
 pub struct PythonSymbol {
     /// Local symbol name (e.g., `foo`).
@@ -79,16 +83,24 @@ impl PythonSymbolKind {
 /// - Class definitions with methods
 /// - Async functions
 ///
+/// Methods and nested functions are qualified by what encloses them: the
+/// `fully_qualified` name of `inner` in `def outer(): def inner(): ...` is
+/// `module::outer::inner`.
+///
 /// Returns a list of symbol entries ready for graph insertion.
 pub fn extract_python_symbols(path: &Path, source: &[u8]) -> Result<Vec<PythonSymbol>> {
+    extract_python_symbols_with_options(path, source, &ParseOptions::default())
+}
+
+/// Extract Python symbols like [`extract_python_symbols`], with `options` bounding
+/// the syntax tree accepted.
+pub fn extract_python_symbols_with_options(
+    path: &Path,
+    source: &[u8],
+    options: &ParseOptions,
+) -> Result<Vec<PythonSymbol>> {
     // Create tree-sitter parser for Python
-    let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&tree_sitter_python::language())
-        .map_err(|e| SpliceError::Parse {
-            file: path.to_path_buf(),
-            message: format!("Failed to set Python language: {:?}", e),
-        })?;
+    let mut parser = parser_for("Python", &tree_sitter_python::language())?;
 
     // Parse the source code
     let tree = parser
@@ -97,6 +109,7 @@ pub fn extract_python_symbols(path: &Path, source: &[u8]) -> Result<Vec<PythonSy
             file: path.to_path_buf(),
             message: "Parse failed - no tree returned".to_string(),
         })?;
+    ensure_ast_depth(&tree, path, options.max_ast_depth)?;
 
     // Create Rope for line/col conversion
     let rope = Rope::from_str(std::str::from_utf8(source)?);
@@ -133,28 +146,23 @@ fn extract_symbols(
 
             symbols.push(symbol);
 
-            // For classes, update the module path for children (nested classes/methods)
-            if kind == PythonSymbolKind::Class {
-                let new_module_path = format!("{}::{}", module_path, name);
-
-                // Extract symbols from the class body (block)
-                if let Some(block) = node.child_by_field_name("body") {
-                    extract_symbols(block, source, rope, symbols, &new_module_path);
-                }
+            // Classes and functions scope what they contain, so methods are
+            // `Class::method` and nested functions `outer::inner`
+            let new_module_path = format!("{}::{}", module_path, name);
 
-                // Don't recurse into children again since we handled the block
-                return;
+            // Extract symbols from the body (block)
+            if let Some(block) = node.child_by_field_name("body") {
+                extract_symbols(block, source, rope, symbols, &new_module_path);
             }
+
+            // Don't recurse into children again since we handled the block
+            return;
         }
     }
 
-    // Recursively process children (only for non-class bodies, which are handled above)
+    // Recursively process children (bodies of symbols are handled above)
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        // Skip class bodies since we handle them above
-        if kind == "class_definition" && child.kind() == "block" {
-            continue;
-        }
         extract_symbols(child, source, rope, symbols, module_path);
     }
 }
@@ -176,9 +184,14 @@ fn extract_symbol(
         .ok()?
         .to_string();
 
-    // Compute byte spans
-    let byte_start = node.start_byte();
-    let byte_end = node.end_byte();
+    // Compute byte spans; decorators belong to the definition they wrap, so
+    // deleting or replacing the span never leaves an orphaned `@decorator`
+    let span_node = match node.parent() {
+        Some(parent) if parent.kind() == "decorated_definition" => parent,
+        _ => node,
+    };
+    let byte_start = span_node.start_byte();
+    let byte_end = span_node.end_byte();
 
     // Convert bytes to line/col using ropey
     let start_char = rope.byte_to_char(byte_start);
@@ -4,7 +4,12 @@
 //! functions, impls, structs, and other Rust constructs with byte spans.
 
 use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
+use crate::ingest::imports::rust::use_declaration_fact;
+use crate::ingest::{ensure_ast_depth, ParseOptions, DEFAULT_MAX_AST_DEPTH};
 use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::path::Path;
 
 /// Visibility modifier for Rust symbols.
@@ -20,24 +25,102 @@ pub enum Visibility {
     Private,
 }
 
+impl Visibility {
+    /// Convert to string for storage (`pub`, `pub(crate)`, ..., or `private`).
+    pub fn as_str(&self) -> &str {
+        match self {
+            Visibility::Public => "pub",
+            Visibility::Restricted(modifier) => modifier.as_str(),
+            Visibility::Private => "private",
+        }
+    }
+
+    /// Module subtree a restricted item is visible in, as an absolute path.
+    ///
+    /// `module` is the absolute path of the module declaring the item.
+    /// `pub(crate)` gives `crate`, `pub(self)` and `pub(super)` are resolved
+    /// against `module`, and `pub(in path)` gives `path`, resolving any `self`
+    /// or `super` in it. Returns `None` for `pub` and private items.
+    ///
+    /// ```
+    /// use splice::ingest::rust::Visibility;
+    ///
+    /// let within_a = Visibility::Restricted("pub(in crate::a)".to_string());
+    /// assert_eq!(within_a.restricted_scope("crate::a::b").as_deref(), Some("crate::a"));
+    /// let parent = Visibility::Restricted("pub(super)".to_string());
+    /// assert_eq!(parent.restricted_scope("crate::a::b").as_deref(), Some("crate::a"));
+    /// assert_eq!(Visibility::Public.restricted_scope("crate::a"), None);
+    /// ```
+    pub fn restricted_scope(&self, module: &str) -> Option<String> {
+        let Visibility::Restricted(modifier) = self else {
+            return None;
+        };
+        let inner = modifier.strip_prefix("pub(")?.strip_suffix(')')?.trim();
+        let path = match inner.strip_prefix("in") {
+            Some(rest) if rest.starts_with(char::is_whitespace) => rest,
+            _ => inner,
+        };
+        let path: String = path.chars().filter(|c| !c.is_whitespace()).collect();
+
+        let mut segments = path.split("::");
+        let mut scope: Vec<&str> = match segments.next()? {
+            "crate" => vec!["crate"],
+            "self" => module.split("::").collect(),
+            "super" => {
+                let mut parent: Vec<&str> = module.split("::").collect();
+                if parent.len() > 1 {
+                    parent.pop();
+                }
+                parent
+            }
+            _ => return None,
+        };
+        for segment in segments {
+            match segment {
+                "super" if scope.len() > 1 => {
+                    scope.pop();
+                }
+                "super" | "self" => {}
+                name => scope.push(name),
+            }
+        }
+        Some(scope.join("::"))
+    }
+}
+
 /// Extract symbols and spans from a Rust source file.
 ///
 /// Uses tree-sitter-rust to parse the file and extract:
 /// - Functions with signatures and bodies
 /// - Struct and enum definitions
+/// - Named struct fields (qualified as `Struct::field`)
 /// - Impl blocks with methods
+/// - Associated types and consts of traits and impls (kinds `type_alias` and
+///   `const`, qualified as `Owner::Item`)
 /// - Module declarations
+/// - Single-name `pub use` re-exports (kind `reexport`)
+/// - `macro_rules!` definitions (kind `macro`; `#[macro_export]` ones are public
+///   and qualified at the crate root)
+///
+/// Symbols inside a `#[cfg(test)]` module, the module itself, and `#[test]`
+/// functions are marked `in_test`.
 ///
 /// Returns a list of symbol entries ready for graph insertion.
 pub fn extract_rust_symbols(path: &Path, source: &[u8]) -> Result<Vec<RustSymbol>> {
+    extract_rust_symbols_with_options(path, source, &ParseOptions::default())
+}
+
+/// Extract Rust symbols like [`extract_rust_symbols`], with `options` bounding
+/// the syntax tree accepted.
+pub fn extract_rust_symbols_with_options(
+    path: &Path,
+    source: &[u8],
+    options: &ParseOptions,
+) -> Result<Vec<RustSymbol>> {
+    let source: &[u8] = &mask_shebang(source);
+
     // Create tree-sitter parser for Rust
-    let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&tree_sitter_rust::language())
-        .map_err(|e| SpliceError::Parse {
-            file: path.to_path_buf(),
-            message: format!("Failed to set Rust language: {:?}", e),
-        })?;
+    let mut parser = parser_for("Rust", &tree_sitter_rust::language())?;
 
     // Parse the source code
     let tree = parser
@@ -46,17 +129,47 @@ pub fn extract_rust_symbols(path: &Path, source: &[u8]) -> Result<Vec<RustSymbol
             file: path.to_path_buf(),
             message: "Parse failed - no tree returned".to_string(),
         })?;
+    ensure_ast_depth(&tree, path, options.max_ast_depth)?;
 
     // Create Rope for line/col conversion
     let rope = Rope::from_str(std::str::from_utf8(source)?);
 
     // Extract symbols from the AST
     let mut symbols = Vec::new();
-    extract_symbols(tree.root_node(), source, &rope, &mut symbols, "crate");
+    extract_symbols(
+        tree.root_node(),
+        source,
+        &rope,
+        &mut symbols,
+        "crate",
+        false,
+    );
 
     Ok(symbols)
 }
 
+/// Blank out a leading `#!` interpreter line so tree-sitter-rust can parse the file.
+///
+/// The grammar's shebang token stops at the first space, so
+/// `#!/usr/bin/env rust-script` leaves an error node that swallows the items
+/// after it. The line is replaced with spaces, keeping every byte offset and
+/// line number unchanged. Inner attributes (`#![...]`) are left alone.
+pub(crate) fn mask_shebang(source: &[u8]) -> Cow<'_, [u8]> {
+    let Some(rest) = source.strip_prefix(b"#!") else {
+        return Cow::Borrowed(source);
+    };
+    if rest.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'[') {
+        return Cow::Borrowed(source);
+    }
+    let end = source
+        .iter()
+        .position(|&b| b == b'\n')
+        .unwrap_or(source.len());
+    let mut masked = source.to_vec();
+    masked[..end].fill(b' ');
+    Cow::Owned(masked)
+}
+
 /// Extract symbols from AST nodes.
 fn extract_symbols(
     node: tree_sitter::Node,
@@ -64,6 +177,7 @@ fn extract_symbols(
     rope: &Rope,
     symbols: &mut Vec<RustSymbol>,
     module_path: &str,
+    in_test: bool,
 ) {
     // Check if this node is a symbol we care about
     let kind = node.kind();
@@ -74,14 +188,52 @@ fn extract_symbols(
         "trait_item" => Some(RustSymbolKind::Trait),
         "impl_item" => Some(RustSymbolKind::Impl),
         "mod_item" => Some(RustSymbolKind::Module),
+        "macro_definition" => Some(RustSymbolKind::Macro),
         _ => None,
     };
 
+    if kind == "use_declaration" {
+        if let Some(mut symbol) = extract_reexport(node, source, rope, module_path) {
+            symbol.in_test = in_test;
+            symbols.push(symbol);
+        }
+        return;
+    }
+
+    // A `#[cfg(test)]` module and everything in it, or a `#[test]` function, is test-only
+    let in_test = in_test
+        || match kind {
+            "mod_item" => has_outer_attribute(node, source, is_cfg_test),
+            "function_item" => has_outer_attribute(node, source, is_test_attribute),
+            _ => false,
+        };
+    let first_new = symbols.len();
+
     if let Some(kind) = symbol_kind {
-        if let Some(symbol) = extract_symbol(node, source, rope, kind, module_path) {
+        if let Some(mut symbol) = extract_symbol(node, source, rope, kind, module_path) {
+            if kind == RustSymbolKind::Macro && is_macro_exported(node, source) {
+                export_macro(&mut symbol);
+            }
+            let struct_name = (kind == RustSymbolKind::Struct).then(|| symbol.name.clone());
+            let assoc_owner = match kind {
+                RustSymbolKind::Trait => Some(symbol.name.clone()),
+                RustSymbolKind::Impl => Some(impl_item_owner(node, source, &symbol.name)),
+                _ => None,
+            };
             symbols.push(symbol);
+
+            // Named fields follow their struct so renames can target `Struct::field`
+            if let Some(struct_name) = struct_name {
+                extract_struct_fields(node, source, rope, symbols, module_path, &struct_name);
+            }
+            if let Some(owner) = assoc_owner {
+                extract_associated_items(node, source, rope, symbols, module_path, &owner);
+            }
         }
     }
+    for symbol in &mut symbols[first_new..] {
+        symbol.in_test = in_test;
+    }
 
     // Recursively process children
     let mut cursor = node.walk();
@@ -106,8 +258,235 @@ fn extract_symbols(
             Some(s) => s.as_str(),
             None => module_path,
         };
-        extract_symbols(child, source, rope, symbols, path_for_children);
+        extract_symbols(child, source, rope, symbols, path_for_children, in_test);
+    }
+}
+
+/// Name of the macro whose invocation appears to define `name`, such as
+/// `lazy_static` for `lazy_static! { static ref NAME: T = ...; }`.
+///
+/// A macro's input is an unparsed token tree, so items written inside one are
+/// never extracted as symbols. They are recognised by the keyword before the
+/// name (`static`, `ref`, `fn`, `struct`, ...); plain uses of the name inside
+/// a macro call do not count.
+pub fn macro_defining(path: &Path, source: &[u8], name: &str) -> Result<Option<String>> {
+    let source: &[u8] = &mask_shebang(source);
+    let mut parser = parser_for("Rust", &tree_sitter_rust::language())?;
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| SpliceError::Parse {
+            file: path.to_path_buf(),
+            message: "Parse failed - no tree returned".to_string(),
+        })?;
+    ensure_ast_depth(&tree, path, DEFAULT_MAX_AST_DEPTH)?;
+    Ok(find_macro_defining(tree.root_node(), source, name))
+}
+
+fn find_macro_defining(node: tree_sitter::Node, source: &[u8], name: &str) -> Option<String> {
+    if node.kind() == "macro_invocation" {
+        let macro_name = node.child_by_field_name("macro").and_then(|m| {
+            let last = m.child_by_field_name("name").unwrap_or(m);
+            last.utf8_text(source).ok()
+        });
+        let tokens = node.named_children(&mut node.walk()).last();
+        if let (Some(macro_name), Some(tokens)) = (macro_name, tokens) {
+            if token_tree_defines(tokens, source, name) {
+                return Some(macro_name.to_string());
+            }
+        }
+        return None;
+    }
+
+    let mut cursor = node.walk();
+    let found = node
+        .children(&mut cursor)
+        .find_map(|child| find_macro_defining(child, source, name));
+    found
+}
+
+/// Whether `name` follows an item keyword anywhere in a macro's token tree.
+fn token_tree_defines(node: tree_sitter::Node, source: &[u8], name: &str) -> bool {
+    const ITEM_KEYWORDS: &[&str] = &[
+        "const", "enum", "fn", "mod", "ref", "static", "struct", "trait", "type", "union",
+    ];
+    if node.kind() == "identifier" && node.utf8_text(source).is_ok_and(|t| t == name) {
+        return node
+            .prev_sibling()
+            .and_then(|prev| prev.utf8_text(source).ok())
+            .is_some_and(|prev| ITEM_KEYWORDS.contains(&prev));
+    }
+    let mut cursor = node.walk();
+    let defines = node
+        .children(&mut cursor)
+        .any(|child| token_tree_defines(child, source, name));
+    defines
+}
+
+/// Whether a `macro_rules!` definition carries `#[macro_export]`.
+fn is_macro_exported(node: tree_sitter::Node, source: &[u8]) -> bool {
+    has_outer_attribute(node, source, |text| text.contains("macro_export"))
+}
+
+/// Whether any outer attribute of an item (e.g. `#[cfg(test)]`) satisfies `matches`.
+///
+/// Attributes are the `attribute_item` siblings directly before the item,
+/// possibly interleaved with comments.
+fn has_outer_attribute(
+    node: tree_sitter::Node,
+    source: &[u8],
+    matches: impl Fn(&str) -> bool,
+) -> bool {
+    let mut sibling = node.prev_named_sibling();
+    while let Some(attr) = sibling {
+        match attr.kind() {
+            "attribute_item" => {
+                if attr.utf8_text(source).is_ok_and(&matches) {
+                    return true;
+                }
+            }
+            "line_comment" | "block_comment" => {}
+            _ => break,
+        }
+        sibling = attr.prev_named_sibling();
+    }
+    false
+}
+
+/// `#[cfg(test)]`, ignoring whitespace.
+fn is_cfg_test(attribute: &str) -> bool {
+    let compact: String = attribute.chars().filter(|c| !c.is_whitespace()).collect();
+    compact == "#[cfg(test)]"
+}
+
+/// `#[test]` or a runtime's test macro such as `#[tokio::test]`.
+fn is_test_attribute(attribute: &str) -> bool {
+    let inner = attribute.trim_start_matches("#[").trim_end_matches(']');
+    let path = inner.split('(').next().unwrap_or(inner).trim();
+    path == "test" || path.ends_with("::test")
+}
+
+/// `#[macro_export]` places a macro at the crate root, visible to every module.
+fn export_macro(symbol: &mut RustSymbol) {
+    symbol.visibility = Visibility::Public;
+    symbol.module_path = "crate".to_string();
+    symbol.fully_qualified = format!("crate::{}", symbol.name);
+}
+
+/// Extract named fields from a struct_item node.
+///
+/// Tuple structs and unit structs have no named fields and yield nothing.
+/// Fields keep the struct's module path; only the qualified name includes
+/// the struct (`crate::module::Struct::field`).
+fn extract_struct_fields(
+    node: tree_sitter::Node,
+    source: &[u8],
+    rope: &Rope,
+    symbols: &mut Vec<RustSymbol>,
+    module_path: &str,
+    struct_name: &str,
+) {
+    let Some(body) = node.child_by_field_name("body") else {
+        return;
+    };
+    if body.kind() != "field_declaration_list" {
+        return;
+    }
+
+    let mut cursor = body.walk();
+    for child in body.children(&mut cursor) {
+        if child.kind() != "field_declaration" {
+            continue;
+        }
+        if let Some(mut field) = extract_symbol(child, source, rope, RustSymbolKind::Field, module_path)
+        {
+            field.fully_qualified = format!("{}::{}::{}", module_path, struct_name, field.name);
+            symbols.push(field);
+        }
+    }
+}
+
+/// Name that qualifies the associated items of an impl block.
+///
+/// Items of `impl Trait for Type` implement the trait's items, so they are
+/// qualified by the trait; items of an inherent impl by the type. Paths and
+/// generic arguments are dropped (`a::Iter<T>` -> `Iter`).
+fn impl_item_owner(node: tree_sitter::Node, source: &[u8], impl_name: &str) -> String {
+    let text = node
+        .child_by_field_name("trait")
+        .and_then(|t| t.utf8_text(source).ok())
+        .unwrap_or(impl_name);
+    let path = text.split('<').next().unwrap_or(text);
+    path.rsplit("::").next().unwrap_or(path).trim().to_string()
+}
+
+/// Extract associated types and consts from a trait_item or impl_item node.
+///
+/// Trait declarations (`type Item;`, `const MAX: usize;`) and impl definitions
+/// (`type Item = u8;`) become `type_alias` and `const` symbols qualified by
+/// their owner (`crate::module::Owner::Item`).
+fn extract_associated_items(
+    node: tree_sitter::Node,
+    source: &[u8],
+    rope: &Rope,
+    symbols: &mut Vec<RustSymbol>,
+    module_path: &str,
+    owner: &str,
+) {
+    let Some(body) = node.child_by_field_name("body") else {
+        return;
+    };
+
+    let mut cursor = body.walk();
+    for child in body.children(&mut cursor) {
+        let kind = match child.kind() {
+            "associated_type" | "type_item" => RustSymbolKind::TypeAlias,
+            "const_item" => RustSymbolKind::Const,
+            _ => continue,
+        };
+        if let Some(mut item) = extract_symbol(child, source, rope, kind, module_path) {
+            item.fully_qualified = format!("{}::{}::{}", module_path, owner, item.name);
+            symbols.push(item);
+        }
+    }
+}
+
+/// Extract a `pub use` re-export as an addressable symbol.
+///
+/// Only re-exports of a single name are addressable: `pub use a::Bar;` is named
+/// `Bar` and `pub use a::Bar as Baz;` is named `Baz`. Globs and multi-name lists
+/// are skipped, since their span cannot be removed for one name alone. The span
+/// covers the whole `use` declaration.
+fn extract_reexport(
+    node: tree_sitter::Node,
+    source: &[u8],
+    rope: &Rope,
+    module_path: &str,
+) -> Option<RustSymbol> {
+    let fact = use_declaration_fact(node, source)?;
+    if !fact.is_reexport || fact.is_glob || fact.imported_names.len() != 1 {
+        return None;
     }
+    let name = fact.imported_names.into_iter().next()?;
+
+    let (byte_start, byte_end) = fact.byte_span;
+    let line_start = rope.byte_to_line(byte_start);
+    let line_end = rope.byte_to_line(byte_end);
+
+    Some(RustSymbol {
+        fully_qualified: format!("{}::{}", module_path, name),
+        name,
+        kind: RustSymbolKind::Reexport,
+        byte_start,
+        byte_end,
+        line_start: line_start + 1,
+        line_end: line_end + 1,
+        col_start: byte_start - rope.line_to_byte(line_start),
+        col_end: byte_end - rope.line_to_byte(line_end),
+        children: Vec::new(),
+        module_path: module_path.to_string(),
+        visibility: extract_visibility(node, source),
+        in_test: false,
+    })
 }
 
 /// Extract the struct name from an impl_item node.
@@ -119,12 +498,13 @@ fn extract_symbols(
 /// In tree-sitter Rust grammar:
 /// - Inherent impl: `impl StructName` -> has `type:` field pointing to StructName
 /// - Trait impl: `impl Trait for StructName` -> has `trait:` field (Trait) and `type:` field (StructName)
+///
 /// The `type:` field ALWAYS contains the struct name being implemented.
 fn extract_impl_name(node: &tree_sitter::Node, source: &[u8]) -> Option<String> {
     // Access the 'type' field which always contains the struct name
     let type_node = node.child_by_field_name("type")?;
 
-    let name_bytes = &source[type_node.start_byte() as usize..type_node.end_byte() as usize];
+    let name_bytes = &source[type_node.start_byte()..type_node.end_byte()];
     std::str::from_utf8(name_bytes).ok().map(|s| s.to_string())
 }
 
@@ -182,6 +562,7 @@ fn extract_symbol(
         module_path: module_path.to_string(),
         fully_qualified,
         visibility,
+        in_test: false,
     })
 }
 
@@ -249,10 +630,14 @@ pub struct RustSymbol {
 
     /// Visibility modifier.
     pub visibility: Visibility,
+
+    /// Whether the symbol is test-only code: inside a `#[cfg(test)]` module,
+    /// the module itself, or a `#[test]` function.
+    pub in_test: bool,
 }
 
 /// Kinds of Rust symbols.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RustSymbolKind {
     /// Function symbol.
     Function,
@@ -272,6 +657,12 @@ pub enum RustSymbolKind {
     Const,
     /// Static item.
     Static,
+    /// Named struct field.
+    Field,
+    /// `pub use` re-export of a single name.
+    Reexport,
+    /// `macro_rules!` definition.
+    Macro,
 }
 
 impl RustSymbolKind {
@@ -287,6 +678,32 @@ impl RustSymbolKind {
             RustSymbolKind::TypeAlias => "type_alias",
             RustSymbolKind::Const => "const",
             RustSymbolKind::Static => "static",
+            RustSymbolKind::Field => "field",
+            RustSymbolKind::Reexport => "reexport",
+            RustSymbolKind::Macro => "macro",
         }
     }
 }
+
+impl TryFrom<&str> for RustSymbolKind {
+    type Error = SpliceError;
+
+    /// Parse a kind string as produced by [`RustSymbolKind::as_str`].
+    fn try_from(kind: &str) -> Result<Self> {
+        Ok(match kind {
+            "function" => RustSymbolKind::Function,
+            "struct" => RustSymbolKind::Struct,
+            "enum" => RustSymbolKind::Enum,
+            "impl" => RustSymbolKind::Impl,
+            "module" => RustSymbolKind::Module,
+            "trait" => RustSymbolKind::Trait,
+            "type_alias" => RustSymbolKind::TypeAlias,
+            "const" => RustSymbolKind::Const,
+            "static" => RustSymbolKind::Static,
+            "field" => RustSymbolKind::Field,
+            "reexport" => RustSymbolKind::Reexport,
+            "macro" => RustSymbolKind::Macro,
+            _ => return Err(SpliceError::Other(format!("Invalid symbol kind: {}", kind))),
+        })
+    }
+}
@@ -0,0 +1,167 @@
+//! A source file with its text, rope, and syntax tree.
+//!
+//! Reference finding and the validation gates each need some of the same
+//! views of a file. `SourceFile` reads the bytes once and builds the rope and
+//! tree on first use, so one file is decoded and parsed at most once however
+//! many passes look at it.
+
+use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
+use crate::ingest::{ensure_ast_depth, ParseOptions};
+use crate::symbol::Language;
+use ropey::Rope;
+use std::cell::OnceCell;
+use std::path::{Path, PathBuf};
+use tree_sitter::Tree;
+
+/// A file's bytes, with its rope and tree built lazily.
+#[derive(Debug)]
+pub struct SourceFile {
+    path: PathBuf,
+    language: Language,
+    bytes: Vec<u8>,
+    options: ParseOptions,
+    rope: OnceCell<Rope>,
+    tree: OnceCell<Tree>,
+}
+
+impl SourceFile {
+    /// Read `path` as a `language` source file.
+    pub fn read(path: &Path, language: Language) -> Result<Self> {
+        Ok(Self::from_bytes(path, language, std::fs::read(path)?))
+    }
+
+    /// Wrap source already in memory; `path` is used only in errors and spans.
+    pub fn from_bytes(path: &Path, language: Language, bytes: Vec<u8>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            language,
+            bytes,
+            options: ParseOptions::default(),
+            rope: OnceCell::new(),
+            tree: OnceCell::new(),
+        }
+    }
+
+    /// Parse with `options` instead of the defaults.
+    pub fn with_parse_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Path the source was read from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Language the source is parsed as.
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Raw source bytes.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Source as UTF-8 text.
+    pub fn text(&self) -> Result<&str> {
+        Ok(std::str::from_utf8(&self.bytes)?)
+    }
+
+    /// Rope over the source text, built on first call.
+    ///
+    /// Fails with `Utf8` if the source is not valid UTF-8.
+    pub fn rope(&self) -> Result<&Rope> {
+        let text = self.text()?;
+        Ok(self.rope.get_or_init(|| Rope::from_str(text)))
+    }
+
+    /// Syntax tree of the source, parsed on first call.
+    ///
+    /// A Rust shebang line is masked before parsing, so byte offsets in the
+    /// tree still line up with [`bytes`](Self::bytes). The tree may contain
+    /// error nodes; only a parser that returns no tree at all, or a tree
+    /// nested deeper than the parse options' `max_ast_depth`, is an error.
+    /// A `.tsx` file is parsed with the TSX grammar, so its JSX is part of the
+    /// tree.
+    pub fn tree(&self) -> Result<&Tree> {
+        if let Some(tree) = self.tree.get() {
+            return Ok(tree);
+        }
+        let grammar = match self.path.extension().and_then(|e| e.to_str()) {
+            Some("tsx") if self.language == Language::TypeScript => {
+                tree_sitter_typescript::language_tsx()
+            }
+            _ => grammar_for(self.language),
+        };
+        let mut parser = parser_for(self.language.as_str(), &grammar)?;
+        let parsed = if self.language == Language::Rust {
+            parser.parse(&*crate::ingest::rust::mask_shebang(&self.bytes), None)
+        } else {
+            parser.parse(&self.bytes, None)
+        };
+        let tree = parsed.ok_or_else(|| SpliceError::Parse {
+            file: self.path.clone(),
+            message: "Parse failed - no tree returned".to_string(),
+        })?;
+        ensure_ast_depth(&tree, &self.path, self.options.max_ast_depth)?;
+        Ok(self.tree.get_or_init(|| tree))
+    }
+}
+
+/// Tree-sitter grammar used to parse `language`.
+pub fn grammar_for(language: Language) -> tree_sitter::Language {
+    match language {
+        Language::Rust => tree_sitter_rust::language(),
+        Language::Python => tree_sitter_python::language(),
+        Language::C => tree_sitter_c::language(),
+        Language::Cpp => tree_sitter_cpp::language(),
+        Language::Java => tree_sitter_java::language(),
+        Language::JavaScript => tree_sitter_javascript::language(),
+        Language::TypeScript => tree_sitter_typescript::language_typescript(),
+        Language::Haskell => tree_sitter_haskell::language(),
+        Language::Elixir => tree_sitter_elixir::language(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_and_rope_agree_on_node_positions() {
+        let source = "#!/usr/bin/env rust-script\nfn main() {\n    let héllo = 1;\n}\n";
+        let file = SourceFile::from_bytes(
+            Path::new("script.rs"),
+            Language::Rust,
+            source.as_bytes().to_vec(),
+        );
+
+        let tree = file.tree().unwrap();
+        let rope = file.rope().unwrap();
+        assert!(!tree.root_node().has_error());
+        assert_eq!(rope.len_bytes(), file.bytes().len());
+        assert_eq!(tree.root_node().end_byte(), file.bytes().len());
+
+        let function = tree.root_node().named_child(0).unwrap();
+        assert_eq!(function.kind(), "function_item");
+        let start = function.start_position();
+        assert_eq!(rope.byte_to_line(function.start_byte()), start.row);
+        assert_eq!(
+            rope.byte_slice(function.start_byte()..function.end_byte())
+                .to_string(),
+            &source[function.start_byte()..function.end_byte()]
+        );
+
+        // The tree is parsed once and reused
+        assert!(std::ptr::eq(tree, file.tree().unwrap()));
+    }
+
+    #[test]
+    fn test_rope_rejects_invalid_utf8() {
+        let file = SourceFile::from_bytes(Path::new("bad.rs"), Language::Rust, vec![0xff, b'\n']);
+        assert!(file.rope().is_err());
+        assert!(file.tree().is_ok());
+    }
+}
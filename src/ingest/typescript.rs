@@ -5,6 +5,8 @@
 //! constructs with byte spans.
 
 use crate::error::{Result, SpliceError};
+use crate::ingest::grammar::parser_for;
+use crate::ingest::{ensure_ast_depth, ParseOptions};
 use ropey::Rope;
 use std::path::Path;
 
@@ -109,27 +111,25 @@ impl TypeScriptSymbolKind {
 ///
 /// Returns a list of symbol entries ready for graph insertion.
 pub fn extract_typescript_symbols(path: &Path, source: &[u8]) -> Result<Vec<TypeScriptSymbol>> {
-    let mut parser = tree_sitter::Parser::new();
+    extract_typescript_symbols_with_options(path, source, &ParseOptions::default())
+}
 
+/// Extract TypeScript symbols like [`extract_typescript_symbols`], with `options` bounding
+/// the syntax tree accepted.
+pub fn extract_typescript_symbols_with_options(
+    path: &Path,
+    source: &[u8],
+    options: &ParseOptions,
+) -> Result<Vec<TypeScriptSymbol>> {
     // Choose parser based on file extension
     let extension = path.extension().and_then(|e| e.to_str());
     let is_tsx = extension == Some("tsx");
 
-    if is_tsx {
-        parser
-            .set_language(&tree_sitter_typescript::language_tsx())
-            .map_err(|e| SpliceError::Parse {
-                file: path.to_path_buf(),
-                message: format!("Failed to set TSX language: {:?}", e),
-            })?;
+    let mut parser = if is_tsx {
+        parser_for("TSX", &tree_sitter_typescript::language_tsx())?
     } else {
-        parser
-            .set_language(&tree_sitter_typescript::language_typescript())
-            .map_err(|e| SpliceError::Parse {
-                file: path.to_path_buf(),
-                message: format!("Failed to set TypeScript language: {:?}", e),
-            })?;
-    }
+        parser_for("TypeScript", &tree_sitter_typescript::language_typescript())?
+    };
 
     let tree = parser
         .parse(source, None)
@@ -137,6 +137,7 @@ pub fn extract_typescript_symbols(path: &Path, source: &[u8]) -> Result<Vec<Type
             file: path.to_path_buf(),
             message: "Parse failed - no tree returned".to_string(),
         })?;
+    ensure_ast_depth(&tree, path, options.max_ast_depth)?;
 
     let rope = Rope::from_str(std::str::from_utf8(source)?);
 
@@ -11,9 +11,11 @@ pub mod cli;
 pub mod error;
 pub mod graph;
 pub mod ingest;
+pub mod offset;
 pub mod patch;
 pub mod plan;
 pub mod resolve;
+pub mod serve;
 pub mod symbol;
 pub mod validate;
 
@@ -4,6 +4,7 @@
 //! The CLI is a thin adapter over existing APIs - NO logic is implemented here.
 
 use serde_json::{json, Map, Value};
+use splice::resolve::references::{ReferenceContext, ReferenceScanOptions};
 use std::collections::HashMap;
 use std::env;
 use std::path::{Path, PathBuf};
@@ -13,9 +14,45 @@ fn main() -> ExitCode {
     // Parse CLI arguments
     let cli = splice::cli::parse_args();
 
+    // One ID per invocation, echoed in the JSON payload and on every log line
+    let operation_id = cli
+        .command
+        .operation_id()
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
     // Initialize logger if verbose
     if cli.verbose {
-        env_logger::init();
+        init_logger(&operation_id);
+    }
+
+    let max_file_size = cli.max_file_size;
+    let parse = splice::ingest::ParseOptions {
+        max_ast_depth: cli.max_ast_depth,
+    };
+    let manifest_path = cli
+        .manifest_path
+        .as_deref()
+        .map(|path| std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()));
+    let manifest_path = manifest_path.as_deref();
+
+    // Warnings from the gates that passed, reported with the success payload
+    let gate_warnings = splice::patch::GateWarnings::default();
+
+    // A streamed references array, or the plain language table, is the whole of stdout
+    let streamed = matches!(
+        cli.command,
+        splice::cli::Commands::References { stream: true, .. }
+            | splice::cli::Commands::Languages {
+                format: splice::cli::OutputFormat::Text
+            }
+    );
+
+    if cli.explain {
+        if !cli.quiet {
+            emit_success_payload(&explain_command(&cli.command).with_operation_id(operation_id));
+        }
+        return ExitCode::SUCCESS;
     }
 
     // Execute command
@@ -23,56 +60,364 @@ fn main() -> ExitCode {
         splice::cli::Commands::Delete {
             file,
             symbol,
+            symbol_regex,
             kind,
             analyzer,
             language,
             create_backup,
-            operation_id,
+            summary_only,
+            dry_run,
+            force,
+            max_references,
+            context_symbol,
+            trim_blank_lines,
+            operation_id: _,
             metadata,
-        } => execute_delete(&file, &symbol, kind, analyzer, language, create_backup, operation_id, metadata),
+            cargo_features,
+            gate_args,
+        } => {
+            let options = validation_options(
+                &cargo_features,
+                &gate_args,
+                max_file_size,
+                parse,
+                manifest_path,
+                &gate_warnings,
+            );
+            let delete = DeleteOptions {
+                kind,
+                language,
+                summary_only,
+                dry_run,
+                force,
+                max_references,
+                context_symbol: context_symbol.as_deref(),
+                trim_blank_lines,
+            };
+            let write = WriteOptions {
+                create_backup,
+                operation_id: Some(operation_id.clone()),
+                metadata,
+            };
+            match symbol_regex {
+                Some(pattern) => {
+                    execute_delete_matching(&file, &pattern, analyzer, &delete, write, &options)
+                }
+                // clap requires --symbol whenever --symbol-regex is absent
+                None => execute_delete(
+                    &file,
+                    &symbol.unwrap_or_default(),
+                    analyzer,
+                    &delete,
+                    write,
+                    &options,
+                ),
+            }
+        }
 
         splice::cli::Commands::Patch {
             file,
             symbol,
+            insert_after,
+            insert_before,
             kind,
+            signature,
+            test,
+            no_test,
             analyzer,
             with_: replacement_file,
             language,
             batch,
             preview,
+            preview_target_dir,
             create_backup,
-            operation_id,
+            operation_id: _,
             metadata,
-        } => match batch {
-            Some(batch_path) => execute_patch_batch(&batch_path, analyzer, language, create_backup, operation_id, metadata),
-            None => execute_single_patch(
-                file,
-                symbol,
-                kind,
-                analyzer,
-                replacement_file,
-                language,
-                preview,
+            include_aware,
+            parallel_validate,
+            parallel,
+            force,
+            verify_hash,
+            format_after,
+            normalize_whitespace,
+            cargo_features,
+            gate_args,
+        } => {
+            let options = splice::patch::ValidationOptions {
+                include_aware,
+                parallel_validate: parallel_validate.then_some(parallel),
+                allow_literal_spans: force,
+                // cargo runs in the workspace, so resolve against our cwd first
+                cargo_target_dir: preview_target_dir
+                    .map(|dir| std::path::absolute(&dir).unwrap_or(dir)),
+                format_after,
+                normalize_whitespace,
+                ..validation_options(
+                    &cargo_features,
+                    &gate_args,
+                    max_file_size,
+                    parse,
+                    manifest_path,
+                    &gate_warnings,
+                )
+            };
+            let write = WriteOptions {
                 create_backup,
-                operation_id,
+                operation_id: Some(operation_id.clone()),
+                metadata,
+            };
+            match batch {
+                Some(batch_path) => {
+                    execute_patch_batch(&batch_path, analyzer, language, write, &options)
+                }
+                None => execute_single_patch(
+                    PatchArgs {
+                        file,
+                        symbol,
+                        insert_after,
+                        insert_before,
+                        replacement_file,
+                        verify_hash,
+                        preview,
+                    },
+                    SymbolFilter {
+                        kind,
+                        signature: signature.as_deref(),
+                        in_test: test_scope(test, no_test),
+                        language,
+                    },
+                    analyzer,
+                    write,
+                    &options,
+                ),
+            }
+        }
+
+        splice::cli::Commands::ReplaceFile {
+            file,
+            with_,
+            language,
+            analyzer,
+            create_backup,
+            operation_id: _,
+            metadata,
+            cargo_features,
+            gate_args,
+        } => execute_replace_file(
+            &file,
+            &with_,
+            language,
+            analyzer,
+            WriteOptions {
+                create_backup,
+                operation_id: Some(operation_id.clone()),
                 metadata,
+            },
+            &validation_options(
+                &cargo_features,
+                &gate_args,
+                max_file_size,
+                parse,
+                manifest_path,
+                &gate_warnings,
             ),
-        },
+        ),
+
+        splice::cli::Commands::RemoveImport {
+            file,
+            name,
+            analyzer,
+            create_backup,
+            operation_id: _,
+            metadata,
+            cargo_features,
+            gate_args,
+        } => execute_remove_import(
+            &file,
+            &name,
+            analyzer,
+            WriteOptions {
+                create_backup,
+                operation_id: Some(operation_id.clone()),
+                metadata,
+            },
+            &validation_options(
+                &cargo_features,
+                &gate_args,
+                max_file_size,
+                parse,
+                manifest_path,
+                &gate_warnings,
+            ),
+        ),
 
-        splice::cli::Commands::Plan { file } => execute_plan(&file),
+        splice::cli::Commands::Plan {
+            file,
+            validate_only,
+            keep_going,
+        } => {
+            if validate_only {
+                execute_plan_validation(&file)
+            } else if keep_going {
+                execute_plan_keep_going(&file)
+            } else {
+                execute_plan(&file)
+            }
+        }
 
-        splice::cli::Commands::Undo { manifest } => execute_undo(&manifest),
+        splice::cli::Commands::Undo { manifest, force } => execute_undo(&manifest, force),
 
         splice::cli::Commands::ApplyFiles {
             glob,
+            exclude,
             find,
             replace,
             language,
             no_validate,
+            identifier_only,
             create_backup,
-            operation_id,
+            summary_only,
+            operation_id: _,
+            metadata,
+        } => execute_apply_files(
+            &glob,
+            &find,
+            &replace,
+            &ApplyFilesOptions {
+                exclude_patterns: &exclude,
+                language,
+                validate: !no_validate,
+                identifier_only,
+                summary_only,
+                max_file_size,
+            },
+            WriteOptions {
+                create_backup,
+                operation_id: Some(operation_id.clone()),
+                metadata,
+            },
+        ),
+
+        splice::cli::Commands::Resolve {
+            file,
+            symbol,
+            at,
+            qualified,
+            position_encoding,
+            no_graph,
+            kind,
+            signature,
+            test,
+            no_test,
+            language,
+        } => match at {
+            // clap only allows --no-graph together with --at
+            Some((line, column)) if no_graph => execute_hover(
+                &file,
+                line,
+                column,
+                position_encoding,
+                kind,
+                language,
+                &parse,
+            ),
+            _ => execute_resolve(
+                &file,
+                symbol.as_deref(),
+                at,
+                qualified.as_deref(),
+                position_encoding,
+                SymbolFilter {
+                    kind,
+                    signature: signature.as_deref(),
+                    in_test: test_scope(test, no_test),
+                    language,
+                },
+                &parse,
+            ),
+        },
+
+        splice::cli::Commands::References {
+            file,
+            symbol,
+            report_unreferenced,
+            context_format,
+            context,
+            before_context,
+            after_context,
+            follow_renames,
+            context_symbol,
+            tab_width,
+            graph,
+            stream,
+            exclude,
+            since_mtime,
+        } => reference_scan_options(max_file_size, parse, manifest_path).and_then(|scan| {
+            if report_unreferenced {
+                execute_report_unreferenced(&file, &exclude, &scan)
+            } else {
+                let output = ReferenceOutput {
+                    context_format,
+                    before_context: before_context.unwrap_or(context),
+                    after_context: after_context.unwrap_or(context),
+                    tab_width,
+                    graph,
+                    stream,
+                };
+                // clap requires --symbol whenever --report-unreferenced is absent
+                execute_references(
+                    &file,
+                    &symbol.unwrap_or_default(),
+                    follow_renames,
+                    context_symbol.as_deref(),
+                    &output,
+                    &ReferenceScanOptions {
+                        exclude,
+                        since: since_mtime,
+                        ..scan
+                    },
+                )
+            }
+        }),
+
+        splice::cli::Commands::Rename {
+            file,
+            symbol,
+            to,
+            dry_run,
+            analyzer,
+            create_backup,
+            operation_id: _,
             metadata,
-        } => execute_apply_files(&glob, &find, &replace, language, !no_validate, create_backup, operation_id, metadata),
+            cargo_features,
+            gate_args,
+        } => {
+            let validation = validation_options(
+                &cargo_features,
+                &gate_args,
+                max_file_size,
+                parse,
+                manifest_path,
+                &gate_warnings,
+            );
+            let apply = (!dry_run).then(|| RenameApply {
+                analyzer,
+                write: WriteOptions {
+                    create_backup,
+                    operation_id: Some(operation_id.clone()),
+                    metadata,
+                },
+                validation: &validation,
+            });
+            reference_scan_options(max_file_size, parse, manifest_path)
+                .and_then(|scan| execute_rename(&file, &symbol, &to, &scan, apply))
+        }
+
+        splice::cli::Commands::Diff {
+            before,
+            after,
+            repo,
+        } => execute_diff(&before, &after, repo.as_deref()),
 
         splice::cli::Commands::Query {
             db,
@@ -88,20 +433,742 @@ fn main() -> ExitCode {
             start,
             end,
         } => execute_get(&db, &file, start, end),
+
+        splice::cli::Commands::Languages { format } => Ok(execute_languages(format)),
+
+        splice::cli::Commands::JsonSchema { format } => Ok(execute_json_schema(format)),
+
+        splice::cli::Commands::DumpAst {
+            file,
+            language,
+            symbols,
+        } => execute_dump_ast(&file, language, symbols, &parse),
+
+        splice::cli::Commands::DumpScopes { file } => execute_dump_scopes(&file),
+
+        splice::cli::Commands::Ingest {
+            dir,
+            graph,
+            jobs,
+            exclude,
+        } => execute_ingest(&dir, &graph, jobs, &exclude, max_file_size, parse),
+
+        splice::cli::Commands::GraphQuery { graph, selector } => {
+            execute_graph_query(&graph, &selector)
+        }
+
+        splice::cli::Commands::Serve {
+            position_encoding,
+            graph,
+            cargo_features,
+            gate_args,
+        } => {
+            let options = validation_options(
+                &cargo_features,
+                &gate_args,
+                max_file_size,
+                parse,
+                manifest_path,
+                &Default::default(),
+            );
+            return match execute_serve(graph.as_deref(), position_encoding, options) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    log::error!("{}", e);
+                    let payload = splice::cli::CliErrorPayload::from_error(&e)
+                        .with_operation_id(operation_id);
+                    emit_error_payload(&payload);
+                    ExitCode::from(e.exit_code())
+                }
+            };
+        }
     };
 
     // Handle result
     match result {
         Ok(payload) => {
-            emit_success_payload(&payload);
+            let payload = with_gate_warnings(payload, gate_warnings.take());
+            if !cli.quiet && !streamed {
+                emit_success_payload(&payload.with_operation_id(operation_id));
+            }
             ExitCode::SUCCESS
         }
         Err(e) => {
-            let payload = splice::cli::CliErrorPayload::from_error(&e);
+            log::error!("{}", e);
+            let payload =
+                splice::cli::CliErrorPayload::from_error(&e).with_operation_id(operation_id);
             emit_error_payload(&payload);
-            ExitCode::from(1)
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+/// Initialize `env_logger` with the operation ID in every record's prefix.
+fn init_logger(operation_id: &str) {
+    use std::io::Write;
+
+    let operation_id = operation_id.to_string();
+    env_logger::Builder::from_default_env()
+        .format(move |buf, record| {
+            writeln!(
+                buf,
+                "[{} {} {} op={}] {}",
+                buf.timestamp(),
+                record.level(),
+                record.target(),
+                operation_id,
+                record.args()
+            )
+        })
+        .init();
+}
+
+/// Describe what `command` would do, for `--explain`.
+///
+/// Built from the parsed arguments only: no file is read, so a step such as
+/// "resolve symbol" may still fail when the command actually runs.
+fn explain_command(command: &splice::cli::Commands) -> splice::cli::CliSuccessPayload {
+    use splice::cli::Commands;
+    use splice::symbol::Language as SymbolLanguage;
+
+    let language_of = |language: &Option<splice::cli::Language>, file: Option<&Path>| {
+        language
+            .map(|l| l.to_symbol_language())
+            .or_else(|| file.and_then(SymbolLanguage::from_path))
+    };
+    let symbol_desc = |symbol: &str, kind: &Option<splice::cli::SymbolKind>, file: &Path| match kind
+    {
+        Some(kind) => format!(
+            "Resolve {} '{}' in {}",
+            <&str>::from(*kind),
+            symbol,
+            file.display()
+        ),
+        None => format!("Resolve symbol '{}' in {}", symbol, file.display()),
+    };
+
+    let mut steps: Vec<String> = Vec::new();
+    let mut gates: Vec<String> = Vec::new();
+    let name = match command {
+        Commands::Delete {
+            file,
+            symbol,
+            symbol_regex,
+            kind,
+            analyzer,
+            language,
+            create_backup,
+            dry_run,
+            force,
+            max_references,
+            context_symbol,
+            trim_blank_lines,
+            cargo_features,
+            gate_args,
+            ..
+        } => {
+            let lang = language_of(language, Some(file));
+            let target = match symbol_regex {
+                Some(pattern) => {
+                    steps.push(match kind {
+                        Some(kind) => format!(
+                            "Resolve every {} in {} whose name matches /{}/",
+                            <&str>::from(*kind),
+                            file.display(),
+                            pattern
+                        ),
+                        None => format!(
+                            "Resolve every symbol in {} whose name matches /{}/",
+                            file.display(),
+                            pattern
+                        ),
+                    });
+                    format!("each symbol matching /{}/", pattern)
+                }
+                None => {
+                    let symbol = symbol.as_deref().unwrap_or_default();
+                    steps.push(symbol_desc(symbol, kind, file));
+                    format!("'{}'", symbol)
+                }
+            };
+            if lang == Some(SymbolLanguage::Rust) {
+                steps.push(format!(
+                    "Find references to {} across the Cargo workspace",
+                    target
+                ));
+                if let Some(context) = context_symbol {
+                    steps.push(format!(
+                        "Keep only references inside '{}'; the definition is not deleted",
+                        context
+                    ));
+                } else if !force {
+                    steps
+                        .push("Refuse if a crate-root `pub use` re-exports the symbol".to_string());
+                }
+                if let Some(limit) = max_references.filter(|_| !dry_run) {
+                    steps.push(format!(
+                        "Refuse if more than {} references would be removed",
+                        limit
+                    ));
+                }
+            }
+            if *dry_run {
+                steps.push(
+                    "Report the spans that would be deleted; no file is modified".to_string(),
+                );
+            } else {
+                if *create_backup {
+                    steps
+                        .push("Back up the definition file and every referencing file".to_string());
+                }
+                gates = explain_gates(gate_args, cargo_features, lang, *analyzer);
+                if symbol_regex.is_some() {
+                    steps.push(
+                        "Delete every definition and reference in one batch, dropping spans \
+                         nested in another deleted span"
+                            .to_string(),
+                    );
+                    steps.push(format!("Run gates once on the batch: {}", gates.join(", ")));
+                } else {
+                    if context_symbol.is_some() {
+                        steps.push("Delete each kept reference (highest offset first)".to_string());
+                    } else {
+                        steps.push(
+                            "Delete each reference (highest offset first), then the definition"
+                                .to_string(),
+                        );
+                    }
+                    steps.push(format!(
+                        "After each deletion, run gates: {}",
+                        gates.join(", ")
+                    ));
+                }
+                if let Some(max) = trim_blank_lines {
+                    steps.push(format!(
+                        "Collapse runs of more than {} blank lines in the modified files",
+                        max
+                    ));
+                }
+            }
+            "delete"
+        }
+        Commands::Patch {
+            file,
+            symbol,
+            insert_after,
+            insert_before,
+            kind,
+            analyzer,
+            with_,
+            language,
+            batch,
+            preview,
+            create_backup,
+            include_aware,
+            parallel_validate,
+            force,
+            verify_hash,
+            format_after,
+            normalize_whitespace,
+            cargo_features,
+            gate_args,
+            ..
+        } => {
+            let lang = language_of(language, file.as_deref());
+            gates = explain_gates(gate_args, cargo_features, lang, *analyzer);
+            let format_step =
+                "Reformat the patched file(s) with the language's formatter, if installed";
+            if let Some(batch) = batch {
+                steps.push(format!("Load span replacements from {}", batch.display()));
+                if *create_backup {
+                    steps.push("Back up every file the batch edits".to_string());
+                }
+                steps.push("Write all replacements and new files".to_string());
+                if *format_after {
+                    steps.push(format_step.to_string());
+                }
+                steps.push(format!("Run gates: {}", gates.join(", ")));
+                if *parallel_validate {
+                    steps.push("Run the per-file gates concurrently".to_string());
+                }
+                if *include_aware {
+                    steps.push(
+                        "Compile C/C++ sources per translation unit with patched header directories on the include path"
+                            .to_string(),
+                    );
+                }
+                steps.push("Roll back every file if any gate fails".to_string());
+            } else {
+                let file = file.as_deref().unwrap_or(Path::new("<file>"));
+                let with = with_
+                    .as_deref()
+                    .map_or("<with>".to_string(), |p| p.display().to_string());
+                let (target, action) = match (symbol, insert_after, insert_before) {
+                    (_, Some(anchor), _) => {
+                        (anchor, format!("Insert the contents of {} after it", with))
+                    }
+                    (_, _, Some(anchor)) => {
+                        (anchor, format!("Insert the contents of {} before it", with))
+                    }
+                    (Some(symbol), _, _) => (
+                        symbol,
+                        format!("Replace its span with the contents of {}", with),
+                    ),
+                    (None, None, None) => unreachable!("clap requires a symbol"),
+                };
+                steps.push(symbol_desc(target, kind, file));
+                if let Some(hash) = verify_hash {
+                    steps.push(format!("Refuse unless the file's SHA-256 is {}", hash));
+                }
+                if !force {
+                    steps.push(
+                        "Refuse if the span starts or ends inside a string or comment".to_string(),
+                    );
+                }
+                steps.push(action);
+                steps.push(
+                    "Refuse if the new content duplicates an existing symbol in the same scope"
+                        .to_string(),
+                );
+                if *normalize_whitespace {
+                    steps.push(
+                        "Stop without writing if the new content differs only in whitespace"
+                            .to_string(),
+                    );
+                }
+                if *preview {
+                    steps.push("Apply the change in a temporary copy of the workspace".to_string());
+                } else if *create_backup {
+                    steps.push(format!("Back up {}", file.display()));
+                }
+                if *format_after {
+                    steps.push(format_step.to_string());
+                }
+                steps.push(format!("Run gates: {}", gates.join(", ")));
+                if !preview {
+                    steps.push(format!("Restore {} if any gate fails", file.display()));
+                }
+            }
+            "patch"
+        }
+        Commands::ReplaceFile {
+            file,
+            with_,
+            language,
+            analyzer,
+            create_backup,
+            cargo_features,
+            gate_args,
+            ..
+        } => {
+            let lang = language_of(language, Some(file));
+            gates = explain_gates(gate_args, cargo_features, lang, *analyzer);
+            if *create_backup {
+                steps.push(format!("Back up {}", file.display()));
+            }
+            steps.push(format!(
+                "Replace the whole of {} with the contents of {}",
+                file.display(),
+                with_.display()
+            ));
+            steps.push(format!("Run gates: {}", gates.join(", ")));
+            steps.push(format!("Restore {} if any gate fails", file.display()));
+            "replace-file"
+        }
+        Commands::RemoveImport {
+            file,
+            name,
+            analyzer,
+            create_backup,
+            cargo_features,
+            gate_args,
+            ..
+        } => {
+            gates = explain_gates(
+                gate_args,
+                cargo_features,
+                Some(SymbolLanguage::Rust),
+                *analyzer,
+            );
+            if *create_backup {
+                steps.push(format!("Back up {}", file.display()));
+            }
+            steps.push(format!(
+                "Find the use declaration in {} that imports {}",
+                file.display(),
+                name
+            ));
+            steps.push(
+                "Remove just that entry from a braced group, or the whole declaration".to_string(),
+            );
+            steps.push(format!("Run gates: {}", gates.join(", ")));
+            steps.push(format!("Restore {} if any gate fails", file.display()));
+            "remove-import"
+        }
+        Commands::Plan {
+            file,
+            validate_only,
+            keep_going,
+        } => {
+            if *validate_only {
+                steps.push(format!(
+                    "Resolve every step in {} and check its replacement file, without patching",
+                    file.display()
+                ));
+            } else {
+                steps.push(format!(
+                    "Apply each step in {} in order, with validation gates",
+                    file.display()
+                ));
+                if *keep_going {
+                    steps.push("Record failing steps and continue with the rest".to_string());
+                    steps.push("Report every step's outcome; fail if any step failed".to_string());
+                } else {
+                    steps.push("Stop at the first failing step".to_string());
+                }
+            }
+            "plan"
+        }
+        Commands::Undo { manifest, force } => {
+            if !force {
+                steps.push("Refuse if a file was edited after the operation".to_string());
+            }
+            steps.push(format!(
+                "Restore the files recorded in {}",
+                manifest.display()
+            ));
+            "undo"
+        }
+        Commands::ApplyFiles {
+            glob,
+            exclude,
+            find,
+            replace,
+            no_validate,
+            identifier_only,
+            create_backup,
+            ..
+        } => {
+            let matching = if *identifier_only {
+                "identifier"
+            } else {
+                "occurrence"
+            };
+            steps.push(format!(
+                "Find each {} of '{}' in files matching {}",
+                matching, find, glob
+            ));
+            if !exclude.is_empty() {
+                steps.push(format!("Skip files matching {}", exclude.join(", ")));
+            }
+            if *create_backup {
+                steps.push("Back up every matching file".to_string());
+            }
+            steps.push(format!("Replace it with '{}'", replace));
+            if !no_validate {
+                steps.push("Run the validation gates on each patched file".to_string());
+            }
+            "apply-files"
+        }
+        Commands::Resolve {
+            file,
+            symbol,
+            at,
+            qualified,
+            no_graph,
+            kind,
+            ..
+        } => {
+            match (symbol, at, qualified) {
+                (Some(symbol), _, _) => steps.push(symbol_desc(symbol, kind, file)),
+                (None, Some((line, col)), _) => steps.push(format!(
+                    "Resolve the innermost symbol at {}:{} in {}",
+                    line,
+                    col,
+                    file.display()
+                )),
+                (None, None, Some(path)) => steps.push(format!(
+                    "Resolve the symbol qualified as '{}' in {}",
+                    path,
+                    file.display()
+                )),
+                (None, None, None) => steps.push(format!("Resolve a symbol in {}", file.display())),
+            }
+            if *no_graph {
+                steps.push("Take it from the extracted symbols, not the code graph".to_string());
+            }
+            steps.push("Print its span; no file is modified".to_string());
+            "resolve"
+        }
+        Commands::References {
+            file,
+            report_unreferenced: true,
+            exclude,
+            ..
+        } => {
+            steps.push(format!("Extract the Rust items in {}", file.display()));
+            steps.push("Find references to each private or pub(...) non-method item".to_string());
+            if !exclude.is_empty() {
+                steps.push(format!(
+                    "Leave out items in files matching {}",
+                    exclude.join(", ")
+                ));
+            }
+            steps.push("List the items with no references".to_string());
+            "references"
+        }
+        Commands::References {
+            file,
+            symbol,
+            follow_renames,
+            context_symbol,
+            graph,
+            stream,
+            exclude,
+            since_mtime,
+            ..
+        } => {
+            let symbol = symbol.as_deref().unwrap_or_default();
+            steps.push(format!("Resolve symbol '{}' in {}", symbol, file.display()));
+            steps.push(format!(
+                "Find references to '{}' across the Cargo workspace",
+                symbol
+            ));
+            if *follow_renames {
+                steps.push("Also search for names the symbol had in git history".to_string());
+            }
+            if let Some(context) = context_symbol {
+                steps.push(format!("Keep only references inside '{}'", context));
+            }
+            if *graph {
+                steps.push("Render the references as a DOT graph".to_string());
+            }
+            if !exclude.is_empty() {
+                steps.push(format!(
+                    "Leave out references in files matching {}",
+                    exclude.join(", ")
+                ));
+            }
+            if let Some(since) = since_mtime {
+                let since = chrono::DateTime::<chrono::Utc>::from(*since);
+                steps.push(format!(
+                    "Scan only other files modified after {}",
+                    since.to_rfc3339()
+                ));
+            }
+            if *stream {
+                steps.push("Print the references as a JSON array, one per line".to_string());
+            }
+            "references"
+        }
+        Commands::Rename {
+            file,
+            symbol,
+            to,
+            dry_run,
+            analyzer,
+            create_backup,
+            cargo_features,
+            gate_args,
+            ..
+        } => {
+            steps.push(format!("Resolve symbol '{}' in {}", symbol, file.display()));
+            steps.push(format!(
+                "Find references to '{}' across the Cargo workspace",
+                symbol
+            ));
+            if *dry_run {
+                steps.push(format!(
+                    "List the rewrites of its definition and references to '{}' without applying them",
+                    to
+                ));
+            } else {
+                gates = explain_gates(
+                    gate_args,
+                    cargo_features,
+                    Some(SymbolLanguage::Rust),
+                    *analyzer,
+                );
+                if *create_backup {
+                    steps.push("Back up every file to be rewritten".to_string());
+                }
+                steps.push(format!(
+                    "Rewrite its definition and references to '{}' as one batch",
+                    to
+                ));
+                steps.push(format!("Run gates: {}", gates.join(", ")));
+                steps.push("Restore every rewritten file if any gate fails".to_string());
+            }
+            "rename"
+        }
+        Commands::Diff { before, after, .. } => {
+            steps.push(format!(
+                "Compare the symbols defined at {} and {}",
+                before, after
+            ));
+            "diff"
+        }
+        Commands::Query { db, label, .. } => {
+            steps.push(format!(
+                "Look up nodes labelled {:?} in {}",
+                label,
+                db.display()
+            ));
+            "query"
+        }
+        Commands::Get {
+            db,
+            file,
+            start,
+            end,
+        } => {
+            steps.push(format!(
+                "Read bytes {}..{} of {} via {}",
+                start,
+                end,
+                file.display(),
+                db.display()
+            ));
+            "get"
+        }
+        Commands::Languages { .. } => {
+            steps.push(
+                "List each supported language with its extensions, grammar, and validation tool"
+                    .to_string(),
+            );
+            "languages"
+        }
+        Commands::JsonSchema { format } => {
+            let formats = format.map_or_else(
+                || "plan, batch, success, and error".to_string(),
+                |format| format.as_str().to_string(),
+            );
+            steps.push(format!(
+                "Print the JSON Schema for the {} format(s)",
+                formats
+            ));
+            "json-schema"
+        }
+        Commands::DumpAst {
+            file,
+            language,
+            symbols,
+        } => {
+            let lang = language_of(language, Some(file));
+            steps.push(format!(
+                "Parse {} as {}",
+                file.display(),
+                lang.map_or("an undetected language", |lang| lang.as_str())
+            ));
+            steps.push("Print the parse tree as an s-expression".to_string());
+            if *symbols {
+                steps.push("List the extracted symbols with their spans".to_string());
+            }
+            "dump-ast"
+        }
+        Commands::DumpScopes { file } => {
+            steps.push(format!("Parse {} as Rust", file.display()));
+            steps.push("Print each scope's byte range, parent, and declared names".to_string());
+            "dump-scopes"
+        }
+        Commands::Ingest {
+            dir,
+            graph,
+            jobs,
+            exclude,
+        } => {
+            let threads = match jobs {
+                0 => "one thread per CPU".to_string(),
+                n => format!("{} thread(s)", n),
+            };
+            steps.push(format!(
+                "Parse every supported file under {} using {}",
+                dir.display(),
+                threads
+            ));
+            if !exclude.is_empty() {
+                steps.push(format!("Skip files matching {}", exclude.join(", ")));
+            }
+            steps.push(format!(
+                "Store their symbols in a new graph at {}",
+                graph.display()
+            ));
+            "ingest"
+        }
+        Commands::GraphQuery { graph, selector } => {
+            steps.push(format!(
+                "Select stored symbols matching '{}' in {}",
+                selector,
+                graph.display()
+            ));
+            "graph-query"
+        }
+        Commands::Serve { graph, .. } => {
+            steps.push("Read JSON requests from stdin and answer each on stdout".to_string());
+            if let Some(graph) = graph {
+                steps.push(format!("Store ingested symbols in {}", graph.display()));
+            }
+            "serve"
+        }
+    };
+
+    let mut data = json!({
+        "command": name,
+        "steps": steps,
+    });
+    if !gates.is_empty() {
+        data["gates"] = json!(gates);
+    }
+    splice::cli::CliSuccessPayload::with_data(
+        format!(
+            "Plan for '{}': {} step(s); nothing was run.",
+            name,
+            steps.len()
+        ),
+        data,
+    )
+}
+
+/// Name the validation gates a patch or delete would run, in order.
+fn explain_gates(
+    gate_args: &splice::cli::GateArgs,
+    cargo_features: &splice::cli::CargoFeatureArgs,
+    language: Option<splice::symbol::Language>,
+    analyzer: Option<splice::cli::AnalyzerMode>,
+) -> Vec<String> {
+    use splice::symbol::Language as SymbolLanguage;
+
+    let gate_set = gate_args.to_gate_set();
+    let mut gates = Vec::new();
+    if gate_set.tree_sitter {
+        gates.push("tree-sitter reparse".to_string());
+    }
+    if gate_set.compiler {
+        match language {
+            Some(SymbolLanguage::Rust) => {
+                let mut check = vec!["cargo check".to_string()];
+                check.extend(cargo_features.to_cargo_features().to_args());
+                if gate_args.fast_check {
+                    check.push("(owning target only)".to_string());
+                }
+                gates.push(check.join(" "));
+            }
+            Some(language) => gates.push(format!("{} compiler check", language.as_str())),
+            None => gates.push("compiler check".to_string()),
         }
     }
+    let analyzer_on = matches!(
+        analyzer,
+        Some(splice::cli::AnalyzerMode::Os | splice::cli::AnalyzerMode::Path)
+    );
+    if gate_set.analyzer && analyzer_on && language == Some(SymbolLanguage::Rust) {
+        if gate_args.scoped_analyzer {
+            gates.push("rust-analyzer (owning package only)".to_string());
+        } else {
+            gates.push("rust-analyzer".to_string());
+        }
+    }
+    gates
 }
 
 /// Execute the delete command.
@@ -113,27 +1180,45 @@ fn main() -> ExitCode {
 /// 4. Deletes all references first (in reverse byte order per file)
 /// 5. Deletes the definition last
 /// 6. Applies each deletion with validation gates
+/// 7. Optionally collapses the blank lines the deletions left behind
 ///
 /// All logic is delegated to existing APIs.
 fn execute_delete(
     file_path: &Path,
     symbol_name: &str,
-    kind: Option<splice::cli::SymbolKind>,
     analyzer: Option<splice::cli::AnalyzerMode>,
-    language: Option<splice::cli::Language>,
-    create_backup: bool,
-    operation_id: Option<String>,
-    metadata: Option<String>,
+    delete: &DeleteOptions,
+    write: WriteOptions,
+    validation: &splice::patch::ValidationOptions,
 ) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
-    use splice::graph::CodeGraph;
-    use splice::patch::apply_patch_with_validation;
-    use splice::resolve::references::find_references;
-    use splice::symbol::{Language as SymbolLanguage, Symbol};
+    use splice::graph::{CodeGraph, SymbolRecord};
+    use splice::patch::{
+        apply_batch_with_options, fold_blank_line_trim, SpanBatch, SpanReplacement,
+    };
+    use splice::resolve::references::cache::{load_cached_references, store_cached_references};
+    use splice::resolve::references::{find_references_with_options, retain_within_symbol};
+    use splice::symbol::Language as SymbolLanguage;
     use splice::validate::AnalyzerMode as ValidateAnalyzerMode;
 
-    // Determine language (from CLI flag or auto-detect from file extension)
-    let symbol_lang = language
-        .map(|l| l.to_symbol_language())
+    let DeleteOptions {
+        kind,
+        language,
+        summary_only,
+        dry_run,
+        force,
+        max_references,
+        context_symbol,
+        trim_blank_lines,
+    } = *delete;
+    let WriteOptions {
+        create_backup,
+        operation_id,
+        metadata,
+    } = write;
+
+    // Determine language (from CLI flag or auto-detect from file extension)
+    let symbol_lang = language
+        .map(|l| l.to_symbol_language())
         .or_else(|| SymbolLanguage::from_path(file_path));
 
     let symbol_lang = symbol_lang.ok_or_else(|| splice::SpliceError::Parse {
@@ -145,51 +1230,90 @@ fn execute_delete(
     let source = std::fs::read(file_path)?;
 
     // Step 2: Extract symbols using language-aware dispatcher
-    let symbols = extract_symbols_with_language(file_path, &source, symbol_lang)?;
+    let symbols =
+        extract_symbols_with_language(file_path, &source, symbol_lang, &validation.parse)?;
 
     // Step 3: Create in-memory graph (for reference finding API compatibility)
-    let graph_db_path = file_path.parent().unwrap().join(".splice_graph.db");
+    let graph_db_path = file_path
+        .parent()
+        .ok_or_else(|| {
+            splice::SpliceError::Other("Cannot determine workspace directory".to_string())
+        })?
+        .join(".splice_graph.db");
     let mut code_graph = CodeGraph::open(&graph_db_path)?;
 
-    // Step 4: Store symbols in graph with language metadata
+    // Step 4: Store symbols in graph with language metadata, replacing the
+    // nodes an earlier run stored for this file
+    code_graph.remove_file(file_path)?;
     for symbol in &symbols {
-        code_graph.store_symbol_with_file_and_language(
-            file_path,
-            symbol.name(),
-            symbol.kind(),
-            symbol.language(),
-            symbol.byte_start(),
-            symbol.byte_end(),
-        )?;
+        code_graph.store_symbol_record(file_path, &SymbolRecord::of(symbol))?;
     }
 
     // Step 5: Convert CLI kind to string for resolution
     // Note: This will be used in Phase 4 for multi-language reference finding
-    let _kind_str = kind.map(|k| match k {
-        splice::cli::SymbolKind::Function => "function",
-        splice::cli::SymbolKind::Method => "method",
-        splice::cli::SymbolKind::Class => "class",
-        splice::cli::SymbolKind::Struct => "struct",
-        splice::cli::SymbolKind::Interface => "interface",
-        splice::cli::SymbolKind::Enum => "enum",
-        splice::cli::SymbolKind::Trait => "trait",
-        splice::cli::SymbolKind::Impl => "impl",
-        splice::cli::SymbolKind::Module => "module",
-        splice::cli::SymbolKind::Variable => "variable",
-        splice::cli::SymbolKind::Constructor => "constructor",
-        splice::cli::SymbolKind::TypeAlias => "type_alias",
-    });
+    let _kind_str = kind.map(<&str>::from);
 
-    // Step 6: Find all references to the symbol
+    // Step 6: Find all references to the symbol, reusing a preceding dry run's scan
     // Note: Reference finding is still Rust-only (Phase 4 will add multi-language)
-    let ref_set = find_references(&code_graph, file_path, symbol_name, None)?;
+    // The cache is keyed on the upward-searched workspace, so an overridden
+    // scan root neither reads nor writes it
+    let scan = reference_scan_options(
+        validation.max_file_size,
+        validation.parse,
+        validation.manifest_path.as_deref(),
+    )?;
+    let cached = if dry_run || scan.scan_root.is_some() {
+        None
+    } else {
+        load_cached_references(file_path, symbol_name, None, validation.max_file_size)?
+    };
+    let mut ref_set = match cached {
+        Some(ref_set) => {
+            log::info!("Reusing cached references for '{}'", symbol_name);
+            ref_set
+        }
+        None => find_references_with_options(&code_graph, file_path, symbol_name, None, &scan)?,
+    };
+    if dry_run && scan.scan_root.is_none() {
+        store_cached_references(
+            file_path,
+            symbol_name,
+            None,
+            validation.max_file_size,
+            &ref_set,
+        )?;
+    }
+
+    // A context symbol narrows the deletion to the usages inside it; the
+    // definition stays, so the public API is unchanged
+    let delete_definition = context_symbol.is_none();
+    if let Some(context) = context_symbol {
+        retain_within_symbol(&mut ref_set.references, file_path, context)?;
+        ref_set.public_reexports.clear();
+    }
+
+    // Step 7: Refuse to silently remove a symbol from the crate's public API
+    let public_reexports: Vec<String> = ref_set
+        .public_reexports
+        .iter()
+        .map(|r| format!("{}:{}", r.file_path.display(), r.line))
+        .collect();
+    if !public_reexports.is_empty() && !dry_run && !force {
+        return Err(splice::SpliceError::PublicApiChange {
+            symbol: symbol_name.to_string(),
+            locations: public_reexports,
+        });
+    }
+    if let Some(limit) = max_references.filter(|_| !dry_run) {
+        ensure_reference_limit(symbol_name, ref_set.references.len(), limit)?;
+    }
 
-    // Step 7: Determine workspace directory (parent of source file)
+    // Step 8: Determine workspace directory (parent of source file)
     let workspace_dir = file_path.parent().ok_or_else(|| {
         splice::SpliceError::Other("Cannot determine workspace directory".to_string())
     })?;
 
-    // Step 8: Convert CLI analyzer mode to validate analyzer mode (default to Off)
+    // Step 9: Convert CLI analyzer mode to validate analyzer mode (default to Off)
     let analyzer_mode = match analyzer {
         Some(splice::cli::AnalyzerMode::Off) => ValidateAnalyzerMode::Off,
         Some(splice::cli::AnalyzerMode::Os) => ValidateAnalyzerMode::Path,
@@ -201,20 +1325,15 @@ fn execute_delete(
         None => ValidateAnalyzerMode::Off,
     };
 
-    // Step 9: Group references by file and sort by byte offset (descending for deletion)
+    // Step 10: Group references by file
     let mut refs_by_file: HashMap<String, Vec<&splice::resolve::references::Reference>> =
         HashMap::new();
     for r in &ref_set.references {
         refs_by_file.entry(r.file_path.clone()).or_default().push(r);
     }
 
-    // Sort each file's references by byte offset descending
-    for refs in refs_by_file.values_mut() {
-        refs.sort_by_key(|r| std::cmp::Reverse(r.byte_start));
-    }
-
-    // Step 10: Create backup if requested
-    let backup_manifest_path = if create_backup {
+    // Step 11: Create backup if requested (nothing is modified in a dry run)
+    let backup_manifest_path = if create_backup && !dry_run {
         use splice::patch::BackupWriter;
 
         let workspace_root = find_workspace_root(file_path)?;
@@ -236,68 +1355,119 @@ fn execute_delete(
         None
     };
 
-    // Step 11: Delete references from each file
-    let mut deleted_count = 0;
-    let mut files_modified = Vec::new();
-
-    for (file_path_str, refs) in refs_by_file {
-        let path = Path::new(&file_path_str);
-
-        // Detect language for this file
-        let file_lang = SymbolLanguage::from_path(path).unwrap_or(symbol_lang);
-
-        // Delete each reference in this file (highest byte offset first)
-        for r in refs {
-            apply_patch_with_validation(
-                path,
+    // Step 12: Delete the references and the definition in one batch, so the
+    // gates run once on the result and a failure rolls every file back
+    let def = &ref_set.definition;
+    let deleted_count = ref_set.references.len();
+    let mut files_modified: Vec<String> = refs_by_file.into_keys().collect();
+    let mut replacements: Vec<SpanReplacement> = ref_set
+        .references
+        .iter()
+        // A use inside the deleted definition goes with it
+        .filter(|r| {
+            !(delete_definition
+                && Path::new(&r.file_path) == file_path
+                && def.byte_start <= r.byte_start
+                && r.byte_end <= def.byte_end)
+        })
+        .map(|r| {
+            SpanReplacement::new(
+                PathBuf::from(&r.file_path),
                 r.byte_start,
                 r.byte_end,
-                "", // Delete = replace with empty
-                workspace_dir,
-                file_lang,
-                analyzer_mode,
-            )?;
-            deleted_count += 1;
+                String::new(),
+            )
+        })
+        .collect();
+    if delete_definition {
+        replacements.push(SpanReplacement::new(
+            file_path.to_path_buf(),
+            def.byte_start,
+            def.byte_end,
+            String::new(),
+        ));
+        // Track the definition file as modified
+        let def_file_path = splice::error::utf8_path(file_path)?.to_string();
+        if !files_modified.contains(&def_file_path) {
+            files_modified.push(def_file_path);
         }
-
-        files_modified.push(file_path_str);
     }
 
-    // Step 11: Delete the definition itself
-    let def = &ref_set.definition;
-    apply_patch_with_validation(
-        file_path,
-        def.byte_start,
-        def.byte_end,
-        "", // Delete = replace with empty
-        workspace_dir,
-        symbol_lang,
-        analyzer_mode,
-    )?;
-    deleted_count += 1;
+    // Step 13: Collapse leftover blank lines in the same batch, validated like
+    // the deletions
+    let mut blank_runs_trimmed = 0;
+    if !dry_run {
+        if let Some(max_blank_lines) = trim_blank_lines {
+            blank_runs_trimmed = fold_blank_line_trim(
+                &mut replacements,
+                symbol_lang,
+                validation.parse,
+                max_blank_lines,
+            )?;
+        }
+        apply_batch_with_options(
+            &[SpanBatch::new(replacements)],
+            workspace_dir,
+            symbol_lang,
+            analyzer_mode,
+            validation,
+        )?;
+    }
 
-    // Track the definition file as modified
-    let def_file_path = file_path.to_str().unwrap_or("").to_string();
-    if !files_modified.contains(&def_file_path) {
-        files_modified.push(def_file_path);
+    // Step 14: The graph must not resolve the deleted symbol to its old span
+    if delete_definition && !dry_run {
+        let file_str = splice::error::utf8_path(file_path)?;
+        let stored = Vec::from(code_graph.find_symbols_in_file(file_str, symbol_name));
+        for node_id in stored {
+            if code_graph.get_span(node_id)? == (def.byte_start, def.byte_end) {
+                code_graph.remove_symbol(node_id)?;
+            }
+        }
     }
 
-    // Step 12: Return success message
-    let base_message = if ref_set.has_glob_ambiguity {
+    // Step 15: Return success message
+    let verb = if dry_run { "Would delete" } else { "Deleted" };
+    let mut base_message = if let Some(context) = context_symbol {
+        format!(
+            "{} {} references to '{}' inside '{}' across {} file(s).",
+            verb,
+            deleted_count,
+            symbol_name,
+            context,
+            files_modified.len()
+        )
+    } else if ref_set.has_glob_ambiguity {
         format!(
-            "Deleted '{}' ({} references + definition) across {} file(s). WARNING: glob imports detected - some references may have been missed.",
+            "{} '{}' ({} references + definition) across {} file(s). WARNING: glob imports detected - some references may have been missed.",
+            verb,
             symbol_name,
-            deleted_count - 1,
+            deleted_count,
             files_modified.len()
         )
     } else {
         format!(
-            "Deleted '{}' ({} references + definition) across {} file(s).",
+            "{} '{}' ({} references + definition) across {} file(s).",
+            verb,
             symbol_name,
-            deleted_count - 1,
+            deleted_count,
             files_modified.len()
         )
     };
+    if !public_reexports.is_empty() {
+        base_message.push_str(&format!(
+            " WARNING: public API change - re-exported at the crate root ({}).",
+            public_reexports.join(", ")
+        ));
+    }
+
+    let mut bytes_changed: usize = ref_set
+        .references
+        .iter()
+        .map(|r| r.byte_end - r.byte_start)
+        .sum();
+    if delete_definition {
+        bytes_changed += def.byte_end - def.byte_start;
+    }
 
     // Collect span IDs (byte ranges) for all deleted spans
     let mut span_ids: Vec<serde_json::Value> = Vec::new();
@@ -309,11 +1479,17 @@ fn execute_delete(
         }));
     }
     // Add definition span
-    span_ids.push(json!({
-        "file": file_path.to_string_lossy(),
-        "byte_start": def.byte_start,
-        "byte_end": def.byte_end,
-    }));
+    if delete_definition {
+        span_ids.push(json!({
+            "file": file_path.to_string_lossy(),
+            "byte_start": def.byte_start,
+            "byte_end": def.byte_end,
+        }));
+    }
+
+    if let Some(manifest_path) = &backup_manifest_path {
+        record_backup_result(manifest_path)?;
+    }
 
     // Build response data
     let mut response_data = serde_json::Map::new();
@@ -331,130 +1507,201 @@ fn execute_delete(
             response_data.insert("metadata".to_string(), json!(meta));
         }
     }
-    response_data.insert("span_ids".to_string(), json!(span_ids));
-    response_data.insert("files_modified".to_string(), json!(files_modified));
+    response_data.insert("bytes_changed".to_string(), json!(bytes_changed));
+    response_data.insert(
+        "skipped_files".to_string(),
+        skipped_files_json(&ref_set.skipped_files),
+    );
+    if !public_reexports.is_empty() {
+        response_data.insert("public_reexports".to_string(), json!(public_reexports));
+    }
+    if dry_run {
+        response_data.insert("dry_run".to_string(), json!(true));
+    }
+    if trim_blank_lines.is_some() && !dry_run {
+        response_data.insert("blank_runs_trimmed".to_string(), json!(blank_runs_trimmed));
+    }
+    if summary_only {
+        response_data.insert("files_modified_count".to_string(), json!(files_modified.len()));
+        response_data.insert("references_removed".to_string(), json!(deleted_count));
+    } else {
+        response_data.insert("span_ids".to_string(), json!(span_ids));
+        response_data.insert("files_modified".to_string(), json!(files_modified));
+    }
 
     Ok(splice::cli::CliSuccessPayload::with_data(base_message, serde_json::Value::Object(response_data)))
 }
 
-/// Execute the patch command.
-///
-/// This function is a thin adapter that:
-/// 1. Extracts symbols from source file using language-aware dispatcher
-/// 2. Resolves the target symbol to its byte span
-/// 3. Reads replacement content from file
-/// 4. Applies patch with validation gates
+/// Refuse a delete that would remove more than `limit` references.
+fn ensure_reference_limit(
+    symbol: &str,
+    count: usize,
+    limit: usize,
+) -> Result<(), splice::SpliceError> {
+    if count > limit {
+        return Err(splice::SpliceError::TooManyReferences {
+            symbol: symbol.to_string(),
+            count,
+            limit,
+        });
+    }
+    Ok(())
+}
+
+/// Execute the delete command for every symbol whose name matches a regex.
 ///
-/// All logic is delegated to existing APIs.
-fn execute_single_patch(
-    file_path: Option<PathBuf>,
-    symbol_name: Option<String>,
-    kind: Option<splice::cli::SymbolKind>,
+/// Each matching symbol (narrowed by `kind`) is resolved in the file and its
+/// references are found as for a single delete. All definitions and
+/// references are then removed in one batch, so the gates run once on the
+/// result rather than after every span; references lying inside another
+/// deleted definition go with it.
+fn execute_delete_matching(
+    file_path: &Path,
+    pattern: &str,
     analyzer: Option<splice::cli::AnalyzerMode>,
-    replacement_file: Option<PathBuf>,
-    language: Option<splice::cli::Language>,
-    preview: bool,
-    create_backup: bool,
-    operation_id: Option<String>,
-    metadata: Option<String>,
+    delete: &DeleteOptions,
+    write: WriteOptions,
+    validation: &splice::patch::ValidationOptions,
 ) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
-    let file_path = require_patch_arg("--file", file_path)?;
-    let symbol_name = require_patch_arg("--symbol", symbol_name)?;
-    let replacement_file = require_patch_arg("--with", replacement_file)?;
+    use splice::graph::{CodeGraph, SymbolRecord};
+    use splice::ingest::rust::RustSymbolKind;
+    use splice::patch::{
+        apply_batch_with_options, fold_blank_line_trim, SpanBatch, SpanReplacement,
+    };
+    use splice::resolve::references::find_references_with_options;
+    use splice::symbol::{Language as SymbolLanguage, Symbol};
+    use splice::validate::AnalyzerMode as ValidateAnalyzerMode;
+    use std::collections::BTreeMap;
 
-    execute_patch(
-        &file_path,
-        &symbol_name,
+    let DeleteOptions {
         kind,
-        analyzer,
-        &replacement_file,
         language,
-        preview,
+        summary_only,
+        dry_run,
+        force,
+        max_references,
+        trim_blank_lines,
+        ..
+    } = *delete;
+    let WriteOptions {
         create_backup,
         operation_id,
         metadata,
-    )
-}
+    } = write;
 
-fn execute_patch(
-    file_path: &Path,
-    symbol_name: &str,
-    kind: Option<splice::cli::SymbolKind>,
-    analyzer: Option<splice::cli::AnalyzerMode>,
-    replacement_file: &Path,
-    language: Option<splice::cli::Language>,
-    preview: bool,
-    create_backup: bool,
-    operation_id: Option<String>,
-    metadata: Option<String>,
-) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
-    use splice::graph::CodeGraph;
-    use splice::patch::{apply_patch_with_validation, preview_patch, FilePatchSummary};
-    use splice::resolve::resolve_symbol;
-    use splice::symbol::{Language as SymbolLanguage, Symbol};
-    use splice::validate::AnalyzerMode as ValidateAnalyzerMode;
+    let regex = regex::Regex::new(pattern).map_err(|e| {
+        splice::SpliceError::Other(format!("Invalid --symbol-regex '{}': {}", pattern, e))
+    })?;
 
-    // Determine language (from CLI flag or auto-detect from file extension)
     let symbol_lang = language
         .map(|l| l.to_symbol_language())
-        .or_else(|| SymbolLanguage::from_path(file_path));
-
-    let symbol_lang = symbol_lang.ok_or_else(|| splice::SpliceError::Parse {
-        file: file_path.to_path_buf(),
-        message: "Cannot detect language - unknown file extension".to_string(),
-    })?;
+        .or_else(|| SymbolLanguage::from_path(file_path))
+        .ok_or_else(|| splice::SpliceError::Parse {
+            file: file_path.to_path_buf(),
+            message: "Cannot detect language - unknown file extension".to_string(),
+        })?;
 
-    // Step 1: Read source file
     let source = std::fs::read(file_path)?;
+    let symbols =
+        extract_symbols_with_language(file_path, &source, symbol_lang, &validation.parse)?;
 
-    // Step 2: Extract symbols using language-aware dispatcher
-    let symbols = extract_symbols_with_language(file_path, &source, symbol_lang)?;
-
-    // Step 3: Create in-memory graph
-    let graph_db_path = file_path.parent().unwrap().join(".splice_graph.db");
+    let graph_db_path = file_path
+        .parent()
+        .ok_or_else(|| {
+            splice::SpliceError::Other("Cannot determine workspace directory".to_string())
+        })?
+        .join(".splice_graph.db");
     let mut code_graph = CodeGraph::open(&graph_db_path)?;
+    code_graph.remove_file(file_path)?;
+    for symbol in &symbols {
+        code_graph.store_symbol_record(file_path, &SymbolRecord::of(symbol))?;
+    }
 
-    // Step 4: Store symbols in graph with language metadata
+    // Matching names in file order, each once
+    let kind_str = kind.map(<&str>::from);
+    let mut targets: Vec<(&str, Option<RustSymbolKind>)> = Vec::new();
     for symbol in &symbols {
-        code_graph.store_symbol_with_file_and_language(
-            file_path,
-            symbol.name(),
-            symbol.kind(),
-            symbol.language(),
-            symbol.byte_start(),
-            symbol.byte_end(),
-        )?;
+        if regex.is_match(symbol.name())
+            && kind_str.is_none_or(|k| symbol.kind() == k)
+            && !targets.iter().any(|(name, _)| *name == symbol.name())
+        {
+            let rust_kind = RustSymbolKind::try_from(symbol.kind()).ok();
+            targets.push((symbol.name(), rust_kind));
+        }
+    }
+    if targets.is_empty() {
+        return Err(splice::SpliceError::symbol_not_found(
+            pattern,
+            Some(file_path),
+        ));
     }
 
-    // Step 5: Convert CLI kind to string for resolution
-    let kind_str = kind.map(|k| match k {
-        splice::cli::SymbolKind::Function => "function",
-        splice::cli::SymbolKind::Method => "method",
-        splice::cli::SymbolKind::Class => "class",
-        splice::cli::SymbolKind::Struct => "struct",
-        splice::cli::SymbolKind::Interface => "interface",
-        splice::cli::SymbolKind::Enum => "enum",
-        splice::cli::SymbolKind::Trait => "trait",
-        splice::cli::SymbolKind::Impl => "impl",
-        splice::cli::SymbolKind::Module => "module",
-        splice::cli::SymbolKind::Variable => "variable",
-        splice::cli::SymbolKind::Constructor => "constructor",
-        splice::cli::SymbolKind::TypeAlias => "type_alias",
-    });
+    let scan = reference_scan_options(
+        validation.max_file_size,
+        validation.parse,
+        validation.manifest_path.as_deref(),
+    )?;
+    let mut spans: Vec<(PathBuf, usize, usize)> = Vec::new();
+    let mut definitions: Vec<(&str, usize, usize)> = Vec::new();
+    let mut reference_count = 0;
+    let mut has_glob_ambiguity = false;
+    let mut public_reexports: Vec<String> = Vec::new();
+    let mut skipped_files: BTreeMap<PathBuf, String> = BTreeMap::new();
+    for (name, rust_kind) in &targets {
+        let ref_set =
+            find_references_with_options(&code_graph, file_path, name, *rust_kind, &scan)?;
+        reference_count += ref_set.references.len();
+        has_glob_ambiguity |= ref_set.has_glob_ambiguity;
+        public_reexports.extend(
+            ref_set
+                .public_reexports
+                .iter()
+                .map(|r| format!("{}:{}", r.file_path.display(), r.line)),
+        );
+        skipped_files.extend(ref_set.skipped_files);
+        spans.extend(
+            ref_set
+                .references
+                .iter()
+                .map(|r| (PathBuf::from(&r.file_path), r.byte_start, r.byte_end)),
+        );
+        let def = &ref_set.definition;
+        spans.push((file_path.to_path_buf(), def.byte_start, def.byte_end));
+        definitions.push((name, def.byte_start, def.byte_end));
+    }
 
-    // Step 6: Resolve symbol to span
-    let resolved = resolve_symbol(&code_graph, Some(file_path), kind_str, symbol_name)?;
+    // Same safety checks as a single delete, over the whole selection
+    if !public_reexports.is_empty() && !dry_run && !force {
+        return Err(splice::SpliceError::PublicApiChange {
+            symbol: pattern.to_string(),
+            locations: public_reexports,
+        });
+    }
+    if let Some(limit) = max_references.filter(|_| !dry_run) {
+        ensure_reference_limit(pattern, reference_count, limit)?;
+    }
 
-    // Step 7: Read replacement content
-    let replacement_content = std::fs::read_to_string(replacement_file)?;
+    // Drop spans nested in another one, e.g. a call inside a deleted definition
+    spans.sort_by(|a, b| {
+        (&a.0, a.1, std::cmp::Reverse(a.2)).cmp(&(&b.0, b.1, std::cmp::Reverse(b.2)))
+    });
+    let mut kept: Vec<(PathBuf, usize, usize)> = Vec::new();
+    for span in spans {
+        if kept
+            .last()
+            .is_some_and(|last| last.0 == span.0 && span.2 <= last.2)
+        {
+            continue;
+        }
+        kept.push(span);
+    }
+    let mut files_modified: Vec<&Path> = kept.iter().map(|(file, _, _)| file.as_path()).collect();
+    files_modified.dedup();
 
-    // Step 8: Determine workspace directory (parent of source file)
     let workspace_dir = file_path.parent().ok_or_else(|| {
         splice::SpliceError::Other("Cannot determine workspace directory".to_string())
     })?;
-    let workspace_root = find_workspace_root(file_path)?;
 
-    // Step 9: Convert CLI analyzer mode to validate analyzer mode (default to Off)
     let analyzer_mode = match analyzer {
         Some(splice::cli::AnalyzerMode::Off) => ValidateAnalyzerMode::Off,
         Some(splice::cli::AnalyzerMode::Os) => ValidateAnalyzerMode::Path,
@@ -466,111 +1713,421 @@ fn execute_patch(
         None => ValidateAnalyzerMode::Off,
     };
 
-    // Step 10: Create backup if requested (skip for preview mode)
-    let backup_manifest_path = if create_backup && !preview {
+    let backup_manifest_path = if create_backup && !dry_run {
         use splice::patch::BackupWriter;
 
+        let workspace_root = find_workspace_root(file_path)?;
         let mut backup_writer = BackupWriter::new(&workspace_root, operation_id.clone())?;
-        backup_writer.backup_file(file_path)?;
+        for file in &files_modified {
+            backup_writer.backup_file(file)?;
+        }
         Some(backup_writer.finalize()?)
     } else {
         None
     };
 
-    if preview {
-        let (summary, report) = preview_patch(
-            file_path,
-            resolved.byte_start,
-            resolved.byte_end,
-            &replacement_content,
-            &workspace_root,
+    let mut blank_runs_trimmed = 0;
+    if !dry_run {
+        let mut replacements = kept
+            .iter()
+            .map(|(file, start, end)| {
+                SpanReplacement::new(file.clone(), *start, *end, String::new())
+            })
+            .collect();
+        // The trim goes in the same batch, so it is rolled back with the deletions
+        if let Some(max_blank_lines) = trim_blank_lines {
+            blank_runs_trimmed = fold_blank_line_trim(
+                &mut replacements,
+                symbol_lang,
+                validation.parse,
+                max_blank_lines,
+            )?;
+        }
+        apply_batch_with_options(
+            &[SpanBatch::new(replacements)],
+            workspace_dir,
             symbol_lang,
             analyzer_mode,
+            validation,
         )?;
-        let message = format!(
-            "Previewed patch '{}' at bytes {}..{} (hash: {} -> {})",
-            symbol_name,
-            resolved.byte_start,
-            resolved.byte_end,
-            summary.before_hash,
-            summary.after_hash
-        );
-        return Ok(build_success_payload(message, vec![summary], Some(report)));
-    }
 
-    let (before_hash, after_hash) = apply_patch_with_validation(
-        file_path,
-        resolved.byte_start,
-        resolved.byte_end,
-        &replacement_content,
-        workspace_dir,
-        symbol_lang,
-        analyzer_mode,
-    )?;
+        // The graph must not resolve the deleted symbols to their old spans
+        let file_str = splice::error::utf8_path(file_path)?;
+        for (name, start, end) in definitions {
+            let stored = Vec::from(code_graph.find_symbols_in_file(file_str, name));
+            for node_id in stored {
+                if code_graph.get_span(node_id)? == (start, end) {
+                    code_graph.remove_symbol(node_id)?;
+                }
+            }
+        }
+    }
 
-    let summary = FilePatchSummary {
-        file: file_path.to_path_buf(),
-        before_hash,
-        after_hash,
-    };
+    if let Some(manifest_path) = &backup_manifest_path {
+        record_backup_result(manifest_path)?;
+    }
 
-    let message = format!(
-        "Patched '{}' at bytes {}..{} (hash: {} -> {})",
-        symbol_name,
-        resolved.byte_start,
-        resolved.byte_end,
-        summary.before_hash,
-        summary.after_hash
+    let verb = if dry_run { "Would delete" } else { "Deleted" };
+    let mut message = format!(
+        "{} {} symbol(s) matching '{}' ({} references + definitions) across {} file(s).",
+        verb,
+        targets.len(),
+        pattern,
+        reference_count,
+        files_modified.len()
     );
+    if has_glob_ambiguity {
+        message.push_str(" WARNING: glob imports detected - some references may have been missed.");
+    }
+    if !public_reexports.is_empty() {
+        message.push_str(&format!(
+            " WARNING: public API change - re-exported at the crate root ({}).",
+            public_reexports.join(", ")
+        ));
+    }
 
-    // Build span ID
-    let span_id = json!({
-        "file": file_path.to_string_lossy(),
-        "byte_start": resolved.byte_start,
-        "byte_end": resolved.byte_end,
-    });
-
-    // Build response data
     let mut response_data = serde_json::Map::new();
-    response_data.insert(
-        "files".to_string(),
-        json!([{
-            "file": file_path.to_string_lossy(),
-            "before_hash": summary.before_hash,
-            "after_hash": summary.after_hash,
-        }]),
-    );
-    response_data.insert("span_ids".to_string(), json!([span_id]));
     if let Some(manifest_path) = backup_manifest_path {
-        response_data.insert("backup_manifest".to_string(), json!(manifest_path.to_string_lossy()));
+        response_data.insert(
+            "backup_manifest".to_string(),
+            json!(manifest_path.to_string_lossy()),
+        );
     }
     if let Some(op_id) = operation_id {
         response_data.insert("operation_id".to_string(), json!(op_id));
     }
     if let Some(meta) = metadata {
-        // Try to parse as JSON, if fails include as string
         if let Ok(parsed) = serde_json::from_str::<Value>(&meta) {
             response_data.insert("metadata".to_string(), parsed);
         } else {
             response_data.insert("metadata".to_string(), json!(meta));
         }
     }
+    let names: Vec<&str> = targets.iter().map(|(name, _)| *name).collect();
+    response_data.insert("symbols".to_string(), json!(names));
+    let bytes_changed: usize = kept.iter().map(|(_, start, end)| end - start).sum();
+    response_data.insert("bytes_changed".to_string(), json!(bytes_changed));
+    let skipped: Vec<Value> = skipped_files
+        .iter()
+        .map(|(file, reason)| json!({ "file": file.to_string_lossy(), "reason": reason }))
+        .collect();
+    response_data.insert("skipped_files".to_string(), json!(skipped));
+    if !public_reexports.is_empty() {
+        response_data.insert("public_reexports".to_string(), json!(public_reexports));
+    }
+    if dry_run {
+        response_data.insert("dry_run".to_string(), json!(true));
+    }
+    if trim_blank_lines.is_some() && !dry_run {
+        response_data.insert("blank_runs_trimmed".to_string(), json!(blank_runs_trimmed));
+    }
+    if summary_only {
+        response_data.insert(
+            "files_modified_count".to_string(),
+            json!(files_modified.len()),
+        );
+        response_data.insert("references_removed".to_string(), json!(reference_count));
+    } else {
+        let span_ids: Vec<Value> = kept
+            .iter()
+            .map(|(file, start, end)| {
+                json!({ "file": file.to_string_lossy(), "byte_start": start, "byte_end": end })
+            })
+            .collect();
+        response_data.insert("span_ids".to_string(), json!(span_ids));
+        response_data.insert("files_modified".to_string(), json!(files_modified));
+    }
 
-    Ok(splice::cli::CliSuccessPayload::with_data(message, serde_json::Value::Object(response_data)))
+    Ok(splice::cli::CliSuccessPayload::with_data(
+        message,
+        Value::Object(response_data),
+    ))
 }
 
-/// Execute a batch patch command driven by a JSON manifest.
-fn execute_patch_batch(
-    batch_path: &Path,
+/// Execute the patch command.
+///
+/// This function is a thin adapter that:
+/// 1. Extracts symbols from source file using language-aware dispatcher
+/// 2. Resolves the target symbol to its byte span
+/// 3. Reads replacement content from file
+/// 4. Applies patch with validation gates
+///
+/// All logic is delegated to existing APIs.
+fn execute_single_patch(
+    args: PatchArgs,
+    filter: SymbolFilter,
+    analyzer: Option<splice::cli::AnalyzerMode>,
+    write: WriteOptions,
+    validation: &splice::patch::ValidationOptions,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::patch::InsertPosition;
+
+    let file_path = require_patch_arg("--file", args.file)?;
+    if let Some(expected) = args.verify_hash {
+        splice::patch::verify_file_hash(&file_path, &expected)?;
+    }
+    let (symbol_name, insert) = match (args.insert_after, args.insert_before) {
+        (Some(name), _) => (name, Some(InsertPosition::After)),
+        (None, Some(name)) => (name, Some(InsertPosition::Before)),
+        (None, None) => (require_patch_arg("--symbol", args.symbol)?, None),
+    };
+    let replacement_file = require_patch_arg("--with", args.replacement_file)?;
+
+    let target = PatchTarget {
+        file_path: &file_path,
+        symbol_name: &symbol_name,
+        insert,
+        replacement_file: &replacement_file,
+        preview: args.preview,
+    };
+    execute_patch(&target, filter, analyzer, write, validation)
+}
+
+fn execute_patch(
+    target: &PatchTarget,
+    filter: SymbolFilter,
+    analyzer: Option<splice::cli::AnalyzerMode>,
+    write: WriteOptions,
+    validation: &splice::patch::ValidationOptions,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::graph::CodeGraph;
+    use splice::patch::{
+        apply_patch_with_options, ensure_no_duplicate_symbol, insertion_edit,
+        preview_patch_with_options, FilePatchSummary, InsertPosition,
+    };
+    use splice::resolve::{ensure_resolved_in_file, resolve_symbol_in_scope};
+    use splice::symbol::Language as SymbolLanguage;
+    use splice::validate::AnalyzerMode as ValidateAnalyzerMode;
+
+    let PatchTarget {
+        file_path,
+        symbol_name,
+        insert,
+        replacement_file,
+        preview,
+    } = *target;
+    let SymbolFilter {
+        kind,
+        signature,
+        in_test,
+        language,
+    } = filter;
+    let WriteOptions {
+        create_backup,
+        operation_id,
+        metadata,
+    } = write;
+
+    // Determine language (from CLI flag or auto-detect from file extension)
+    let symbol_lang = language
+        .map(|l| l.to_symbol_language())
+        .or_else(|| SymbolLanguage::from_path(file_path));
+
+    let symbol_lang = symbol_lang.ok_or_else(|| splice::SpliceError::Parse {
+        file: file_path.to_path_buf(),
+        message: "Cannot detect language - unknown file extension".to_string(),
+    })?;
+
+    // Step 1: Read source file
+    let source = std::fs::read(file_path)?;
+
+    // Step 2: Extract symbols using language-aware dispatcher
+    let symbols =
+        extract_symbols_with_language(file_path, &source, symbol_lang, &validation.parse)?;
+
+    // Step 3: Create in-memory graph
+    let graph_db_path = file_path.parent().unwrap().join(".splice_graph.db");
+    let mut code_graph = CodeGraph::open(&graph_db_path)?;
+
+    // Step 4: Store symbols in graph with language metadata
+    code_graph.store_file_symbols(file_path, &symbols)?;
+
+    // Step 5: Convert CLI kind to string for resolution
+    let kind_str = kind.map(<&str>::from);
+
+    // Step 6: Resolve symbol to span (overloads are told apart by --signature)
+    let resolved = resolve_symbol_in_scope(
+        &code_graph,
+        Some(file_path),
+        kind_str,
+        symbol_name,
+        signature,
+        in_test,
+    )?;
+    ensure_resolved_in_file(&resolved, file_path)?;
+
+    // Step 7: Read replacement content; insertions become a zero-width edit beside the symbol
+    let replacement_content = std::fs::read_to_string(replacement_file)?;
+    let (byte_start, byte_end, replacement_content) = match insert {
+        Some(position) => {
+            let (offset, text) = insertion_edit(
+                std::str::from_utf8(&source)?,
+                resolved.byte_start,
+                resolved.byte_end,
+                &replacement_content,
+                position,
+                symbol_lang,
+            );
+            (offset, offset, text)
+        }
+        None => (resolved.byte_start, resolved.byte_end, replacement_content),
+    };
+    let (action, preview_action) = match insert {
+        Some(InsertPosition::Before) => ("Inserted before", "insert before"),
+        Some(InsertPosition::After) => ("Inserted after", "insert after"),
+        None => ("Patched", "patch"),
+    };
+
+    // Step 8: Refuse content that redefines a symbol already in the file
+    ensure_no_duplicate_symbol(
+        file_path,
+        &source,
+        byte_start,
+        byte_end,
+        &replacement_content,
+        symbol_lang,
+    )?;
+
+    // Step 9: Determine workspace directory (parent of source file)
+    let workspace_dir = file_path.parent().ok_or_else(|| {
+        splice::SpliceError::Other("Cannot determine workspace directory".to_string())
+    })?;
+    let workspace_root = find_workspace_root(file_path)?;
+
+    // Step 10: Convert CLI analyzer mode to validate analyzer mode (default to Off)
+    let analyzer_mode = match analyzer {
+        Some(splice::cli::AnalyzerMode::Off) => ValidateAnalyzerMode::Off,
+        Some(splice::cli::AnalyzerMode::Os) => ValidateAnalyzerMode::Path,
+        Some(splice::cli::AnalyzerMode::Path) => {
+            return Err(splice::SpliceError::Other(
+                "Explicit analyzer path not yet supported".to_string(),
+            ));
+        }
+        None => ValidateAnalyzerMode::Off,
+    };
+
+    // Step 11: Preview patches a copy of the workspace, so nothing real needs a backup
+    if preview {
+        if create_backup {
+            log::info!("--create-backup is ignored with --preview; no backup is written");
+        }
+        let (summary, report) = preview_patch_with_options(
+            file_path,
+            byte_start,
+            byte_end,
+            &replacement_content,
+            &workspace_root,
+            symbol_lang,
+            analyzer_mode,
+            validation,
+        )?;
+        let message = format!(
+            "Previewed {} '{}' at bytes {}..{} (hash: {} -> {})",
+            preview_action,
+            symbol_name,
+            byte_start,
+            byte_end,
+            summary.before_hash,
+            summary.after_hash
+        );
+        return Ok(build_success_payload(message, vec![summary], Some(report)));
+    }
+
+    // Step 12: Create backup if requested
+    let backup_manifest_path = if create_backup {
+        use splice::patch::BackupWriter;
+
+        let mut backup_writer = BackupWriter::new(&workspace_root, operation_id.clone())?;
+        backup_writer.backup_file(file_path)?;
+        Some(backup_writer.finalize()?)
+    } else {
+        None
+    };
+
+    let (before_hash, after_hash) = apply_patch_with_options(
+        file_path,
+        byte_start,
+        byte_end,
+        &replacement_content,
+        workspace_dir,
+        symbol_lang,
+        analyzer_mode,
+        validation,
+    )?;
+
+    let summary = FilePatchSummary {
+        file: file_path.to_path_buf(),
+        before_hash,
+        after_hash,
+    };
+
+    if let Some(manifest_path) = &backup_manifest_path {
+        record_backup_result(manifest_path)?;
+    }
+
+    let message = format!(
+        "{} '{}' at bytes {}..{} (hash: {} -> {})",
+        action,
+        symbol_name,
+        byte_start,
+        byte_end,
+        summary.before_hash,
+        summary.after_hash
+    );
+
+    // Build span ID
+    let span_id = json!({
+        "file": file_path.to_string_lossy(),
+        "byte_start": byte_start,
+        "byte_end": byte_end,
+    });
+
+    // Build response data
+    let mut response_data = serde_json::Map::new();
+    response_data.insert(
+        "files".to_string(),
+        json!([{
+            "file": file_path.to_string_lossy(),
+            "before_hash": summary.before_hash,
+            "after_hash": summary.after_hash,
+        }]),
+    );
+    response_data.insert("span_ids".to_string(), json!([span_id]));
+    if let Some(manifest_path) = backup_manifest_path {
+        response_data.insert("backup_manifest".to_string(), json!(manifest_path.to_string_lossy()));
+    }
+    if let Some(op_id) = operation_id {
+        response_data.insert("operation_id".to_string(), json!(op_id));
+    }
+    if let Some(meta) = metadata {
+        // Try to parse as JSON, if fails include as string
+        if let Ok(parsed) = serde_json::from_str::<Value>(&meta) {
+            response_data.insert("metadata".to_string(), parsed);
+        } else {
+            response_data.insert("metadata".to_string(), json!(meta));
+        }
+    }
+
+    Ok(splice::cli::CliSuccessPayload::with_data(message, serde_json::Value::Object(response_data)))
+}
+
+/// Execute a batch patch command driven by a JSON manifest.
+fn execute_patch_batch(
+    batch_path: &Path,
     analyzer: Option<splice::cli::AnalyzerMode>,
     language: Option<splice::cli::Language>,
-    create_backup: bool,
-    operation_id: Option<String>,
-    metadata: Option<String>,
+    write: WriteOptions,
+    validation: &splice::patch::ValidationOptions,
 ) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
-    use splice::patch::{apply_batch_with_validation, load_batches_from_file};
+    use splice::patch::{apply_batch_with_options, load_batches_from_file};
     use splice::validate::AnalyzerMode as ValidateAnalyzerMode;
 
+    let WriteOptions {
+        create_backup,
+        operation_id,
+        metadata,
+    } = write;
+
     let absolute_batch = if batch_path.is_absolute() {
         batch_path.to_path_buf()
     } else {
@@ -628,13 +2185,28 @@ fn execute_patch_batch(
         for file in files_to_backup {
             backup_writer.backup_file(&file)?;
         }
+        // Created files have no original content; undo deletes them
+        for batch in &batches {
+            for new_file in batch.new_files() {
+                backup_writer.record_new_file(&new_file.path)?;
+            }
+        }
         Some(backup_writer.finalize()?)
     } else {
         None
     };
 
-    let summaries =
-        apply_batch_with_validation(&batches, &workspace_dir, symbol_language, analyzer_mode)?;
+    let summaries = apply_batch_with_options(
+        &batches,
+        &workspace_dir,
+        symbol_language,
+        analyzer_mode,
+        validation,
+    )?;
+
+    if let Some(manifest_path) = &backup_manifest_path {
+        record_backup_result(manifest_path)?;
+    }
 
     let files_data: Vec<_> = summaries
         .iter()
@@ -674,25 +2246,887 @@ fn execute_patch_batch(
         response_data["operation_id"] = json!(op_id);
     }
 
-    if let Some(meta) = metadata {
-        // Try to parse as JSON, if fails include as string
-        if let Ok(parsed) = serde_json::from_str::<Value>(&meta) {
-            response_data["metadata"] = parsed;
-        } else {
-            response_data["metadata"] = json!(meta);
+    if let Some(meta) = metadata {
+        // Try to parse as JSON, if fails include as string
+        if let Ok(parsed) = serde_json::from_str::<Value>(&meta) {
+            response_data["metadata"] = parsed;
+        } else {
+            response_data["metadata"] = json!(meta);
+        }
+    }
+
+    Ok(splice::cli::CliSuccessPayload::with_data(
+        format!(
+            "Patched {} file(s) across {} batch(es).",
+            summaries.len(),
+            batch_count
+        ),
+        response_data,
+    ))
+}
+
+/// Execute the replace-file command.
+///
+/// The new contents replace the span `0..len`, so the whole-file rewrite goes
+/// through the same atomic write, gates, and rollback as a symbol patch.
+fn execute_replace_file(
+    file_path: &Path,
+    replacement_file: &Path,
+    language: Option<splice::cli::Language>,
+    analyzer: Option<splice::cli::AnalyzerMode>,
+    write: WriteOptions,
+    validation: &splice::patch::ValidationOptions,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::patch::apply_patch_with_options;
+    use splice::symbol::Language as SymbolLanguage;
+    use splice::validate::AnalyzerMode as ValidateAnalyzerMode;
+
+    let WriteOptions {
+        create_backup,
+        operation_id,
+        metadata,
+    } = write;
+
+    let symbol_lang = language
+        .map(|l| l.to_symbol_language())
+        .or_else(|| SymbolLanguage::from_path(file_path))
+        .ok_or_else(|| splice::SpliceError::Parse {
+            file: file_path.to_path_buf(),
+            message: "Cannot detect language - unknown file extension".to_string(),
+        })?;
+
+    let source_len = std::fs::read(file_path)?.len();
+    let replacement_content = std::fs::read_to_string(replacement_file)?;
+
+    let workspace_dir = file_path.parent().ok_or_else(|| {
+        splice::SpliceError::Other("Cannot determine workspace directory".to_string())
+    })?;
+
+    let analyzer_mode = match analyzer {
+        Some(splice::cli::AnalyzerMode::Off) => ValidateAnalyzerMode::Off,
+        Some(splice::cli::AnalyzerMode::Os) => ValidateAnalyzerMode::Path,
+        Some(splice::cli::AnalyzerMode::Path) => {
+            return Err(splice::SpliceError::Other(
+                "Explicit analyzer path not yet supported".to_string(),
+            ));
+        }
+        None => ValidateAnalyzerMode::Off,
+    };
+
+    let backup_manifest_path = if create_backup {
+        use splice::patch::BackupWriter;
+
+        let workspace_root = find_workspace_root(file_path)?;
+        let mut backup_writer = BackupWriter::new(&workspace_root, operation_id.clone())?;
+        backup_writer.backup_file(file_path)?;
+        Some(backup_writer.finalize()?)
+    } else {
+        None
+    };
+
+    let (before_hash, after_hash) = apply_patch_with_options(
+        file_path,
+        0,
+        source_len,
+        &replacement_content,
+        workspace_dir,
+        symbol_lang,
+        analyzer_mode,
+        validation,
+    )?;
+
+    if let Some(manifest_path) = &backup_manifest_path {
+        record_backup_result(manifest_path)?;
+    }
+
+    let mut response_data = serde_json::Map::new();
+    response_data.insert(
+        "files".to_string(),
+        json!([{
+            "file": file_path.to_string_lossy(),
+            "before_hash": before_hash,
+            "after_hash": after_hash,
+        }]),
+    );
+    if let Some(manifest_path) = backup_manifest_path {
+        response_data.insert(
+            "backup_manifest".to_string(),
+            json!(manifest_path.to_string_lossy()),
+        );
+    }
+    if let Some(op_id) = operation_id {
+        response_data.insert("operation_id".to_string(), json!(op_id));
+    }
+    if let Some(meta) = metadata {
+        // Try to parse as JSON, if fails include as string
+        if let Ok(parsed) = serde_json::from_str::<Value>(&meta) {
+            response_data.insert("metadata".to_string(), parsed);
+        } else {
+            response_data.insert("metadata".to_string(), json!(meta));
+        }
+    }
+
+    Ok(splice::cli::CliSuccessPayload::with_data(
+        format!(
+            "Replaced {} ({} -> {} bytes, hash: {} -> {})",
+            file_path.display(),
+            source_len,
+            replacement_content.len(),
+            before_hash,
+            after_hash
+        ),
+        serde_json::Value::Object(response_data),
+    ))
+}
+
+/// Execute the remove-import command.
+///
+/// Finds the `use` declaration importing `name` and removes that name (or the
+/// whole declaration), going through the same gates and rollback as a patch.
+fn execute_remove_import(
+    file_path: &Path,
+    name: &str,
+    analyzer: Option<splice::cli::AnalyzerMode>,
+    write: WriteOptions,
+    validation: &splice::patch::ValidationOptions,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::patch::{apply_patch_with_options, remove_import};
+    use splice::symbol::Language as SymbolLanguage;
+    use splice::validate::AnalyzerMode as ValidateAnalyzerMode;
+
+    let WriteOptions {
+        create_backup,
+        operation_id,
+        metadata,
+    } = write;
+
+    if SymbolLanguage::from_path(file_path) != Some(SymbolLanguage::Rust) {
+        return Err(splice::SpliceError::Parse {
+            file: file_path.to_path_buf(),
+            message: "remove-import only handles Rust use declarations".to_string(),
+        });
+    }
+
+    let source = std::fs::read(file_path)?;
+    let edit = remove_import(file_path, &source, name)?;
+    let removed = String::from_utf8_lossy(&source[edit.start..edit.end]).into_owned();
+
+    let workspace_dir = file_path.parent().ok_or_else(|| {
+        splice::SpliceError::Other("Cannot determine workspace directory".to_string())
+    })?;
+
+    let analyzer_mode = match analyzer {
+        Some(splice::cli::AnalyzerMode::Off) => ValidateAnalyzerMode::Off,
+        Some(splice::cli::AnalyzerMode::Os) => ValidateAnalyzerMode::Path,
+        Some(splice::cli::AnalyzerMode::Path) => {
+            return Err(splice::SpliceError::Other(
+                "Explicit analyzer path not yet supported".to_string(),
+            ));
+        }
+        None => ValidateAnalyzerMode::Off,
+    };
+
+    let backup_manifest_path = if create_backup {
+        use splice::patch::BackupWriter;
+
+        let workspace_root = find_workspace_root(file_path)?;
+        let mut backup_writer = BackupWriter::new(&workspace_root, operation_id.clone())?;
+        backup_writer.backup_file(file_path)?;
+        Some(backup_writer.finalize()?)
+    } else {
+        None
+    };
+
+    let (before_hash, after_hash) = apply_patch_with_options(
+        file_path,
+        edit.start,
+        edit.end,
+        &edit.content,
+        workspace_dir,
+        SymbolLanguage::Rust,
+        analyzer_mode,
+        validation,
+    )?;
+
+    if let Some(manifest_path) = &backup_manifest_path {
+        record_backup_result(manifest_path)?;
+    }
+
+    let mut response_data = serde_json::Map::new();
+    response_data.insert(
+        "files".to_string(),
+        json!([{
+            "file": file_path.to_string_lossy(),
+            "before_hash": before_hash,
+            "after_hash": after_hash,
+        }]),
+    );
+    response_data.insert(
+        "removed".to_string(),
+        json!({ "byte_start": edit.start, "byte_end": edit.end, "text": removed }),
+    );
+    if let Some(manifest_path) = backup_manifest_path {
+        response_data.insert(
+            "backup_manifest".to_string(),
+            json!(manifest_path.to_string_lossy()),
+        );
+    }
+    if let Some(op_id) = operation_id {
+        response_data.insert("operation_id".to_string(), json!(op_id));
+    }
+    if let Some(meta) = metadata {
+        // Try to parse as JSON, if fails include as string
+        if let Ok(parsed) = serde_json::from_str::<Value>(&meta) {
+            response_data.insert("metadata".to_string(), parsed);
+        } else {
+            response_data.insert("metadata".to_string(), json!(meta));
+        }
+    }
+
+    Ok(splice::cli::CliSuccessPayload::with_data(
+        format!(
+            "Removed import {} from {} (hash: {} -> {})",
+            name,
+            file_path.display(),
+            before_hash,
+            after_hash
+        ),
+        serde_json::Value::Object(response_data),
+    ))
+}
+
+/// Execute the resolve command.
+///
+/// Resolves the symbol exactly as `patch` would and reports its span without
+/// touching the file.
+fn execute_resolve(
+    file_path: &Path,
+    symbol_name: Option<&str>,
+    at: Option<(usize, usize)>,
+    qualified: Option<&str>,
+    position_encoding: splice::cli::PositionEncoding,
+    filter: SymbolFilter,
+    parse: &splice::ingest::ParseOptions,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::graph::CodeGraph;
+    use splice::resolve::{resolve_symbol_at, resolve_symbol_in_scope};
+    use splice::symbol::{qualified_names_match, Language as SymbolLanguage, Symbol};
+
+    let SymbolFilter {
+        kind,
+        signature,
+        mut in_test,
+        language,
+    } = filter;
+
+    let symbol_lang = language
+        .map(|l| l.to_symbol_language())
+        .or_else(|| SymbolLanguage::from_path(file_path))
+        .ok_or_else(|| splice::SpliceError::Parse {
+            file: file_path.to_path_buf(),
+            message: "Cannot detect language - unknown file extension".to_string(),
+        })?;
+
+    let source = std::fs::read(file_path)?;
+    let symbols = extract_symbols_with_language(file_path, &source, symbol_lang, parse)?;
+
+    // With --at, resolve the innermost symbol (of the requested kind) enclosing the position
+    let mut kind_str = kind.map(<&str>::from);
+    let mut signature = signature.map(str::to_string);
+    // --qualified picks one definition, even among several sharing its name and kind
+    let mut qualified_start = None;
+    let symbol_name = match (symbol_name, at, qualified) {
+        (Some(name), _, _) => name.to_string(),
+        (None, Some((line, column)), _) => {
+            let text = std::str::from_utf8(&source)?;
+            let byte = splice::offset::position_to_byte(
+                text,
+                line,
+                column,
+                position_encoding.to_offset_encoding(),
+            )
+            .ok_or_else(|| {
+                splice::SpliceError::Other(format!(
+                    "Position {}:{} is not a valid {:?} position in {}",
+                    line,
+                    column,
+                    position_encoding,
+                    file_path.display()
+                ))
+            })?;
+            let enclosing = symbols
+                .iter()
+                .filter(|s| s.byte_start() <= byte && byte < s.byte_end())
+                .filter(|s| kind_str.is_none_or(|k| s.kind() == k))
+                .min_by_key(|s| s.byte_end() - s.byte_start())
+                .ok_or_else(|| {
+                    splice::SpliceError::symbol_not_found(
+                        format!("<symbol at {}:{}>", line, column),
+                        Some(file_path),
+                    )
+                })?;
+            kind_str = Some(enclosing.kind());
+            signature = enclosing.signature();
+            in_test = Some(enclosing.in_test());
+            enclosing.name().to_string()
+        }
+        // With --qualified, the first symbol (of the requested kind) with that qualified name
+        (None, None, Some(path)) => {
+            let symbol = symbols
+                .iter()
+                .filter(|s| kind_str.is_none_or(|k| s.kind() == k))
+                .find(|s| qualified_names_match(s.fully_qualified(), path))
+                .ok_or_else(|| splice::SpliceError::symbol_not_found(path, Some(file_path)))?;
+            qualified_start = Some(symbol.byte_start());
+            symbol.name().to_string()
+        }
+        (None, None, None) => {
+            return Err(splice::SpliceError::Other(
+                "Either --symbol, --at or --qualified is required".to_string(),
+            ))
+        }
+    };
+
+    let graph_db_path = file_path.parent().unwrap().join(".splice_graph.db");
+    let mut code_graph = CodeGraph::open(&graph_db_path)?;
+    code_graph.store_file_symbols(file_path, &symbols)?;
+
+    let mut resolved = match qualified_start {
+        Some(start) => resolve_symbol_at(&code_graph, file_path, &symbol_name, start)?,
+        None => resolve_symbol_in_scope(
+            &code_graph,
+            Some(file_path),
+            kind_str,
+            &symbol_name,
+            signature.as_deref(),
+            in_test,
+        )?,
+    };
+
+    // The graph only stores byte spans; take line/col from the extracted symbol
+    if let Some(symbol) = symbols.iter().find(|s| {
+        s.name() == resolved.name
+            && s.byte_start() == resolved.byte_start
+            && s.byte_end() == resolved.byte_end
+    }) {
+        resolved.line_start = symbol.line_start();
+        resolved.line_end = symbol.line_end();
+        resolved.col_start = symbol.col_start();
+        resolved.col_end = symbol.col_end();
+    }
+
+    let data = serde_json::to_value(&resolved)
+        .map_err(|e| splice::SpliceError::Other(format!("Failed to serialize span: {}", e)))?;
+
+    Ok(splice::cli::CliSuccessPayload::with_data(
+        format!(
+            "Resolved '{}' at bytes {}..{}",
+            resolved.name, resolved.byte_start, resolved.byte_end
+        ),
+        data,
+    ))
+}
+
+/// Execute `resolve --at --no-graph`: the innermost symbol at a position,
+/// taken from the extracted symbols alone.
+fn execute_hover(
+    file_path: &Path,
+    line: usize,
+    column: usize,
+    position_encoding: splice::cli::PositionEncoding,
+    kind: Option<splice::cli::SymbolKind>,
+    language: Option<splice::cli::Language>,
+    parse: &splice::ingest::ParseOptions,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    let hover = splice::resolve::hover::hover(
+        file_path,
+        line,
+        column,
+        language.map(|l| l.to_symbol_language()),
+        position_encoding.to_offset_encoding(),
+        kind.map(<&str>::from),
+        parse,
+    )?;
+    let data = serde_json::to_value(&hover)
+        .map_err(|e| splice::SpliceError::Other(format!("Failed to serialize span: {}", e)))?;
+
+    Ok(splice::cli::CliSuccessPayload::with_data(
+        format!(
+            "Resolved '{}' at bytes {}..{}",
+            hover.name, hover.byte_start, hover.byte_end
+        ),
+        data,
+    ))
+}
+
+/// Execute the serve command.
+///
+/// Responses go to stdout line by line, so unlike the other commands there
+/// is no success payload; only a startup or I/O failure is reported as one.
+fn execute_serve(
+    graph_path: Option<&Path>,
+    position_encoding: splice::cli::PositionEncoding,
+    options: splice::patch::ValidationOptions,
+) -> Result<(), splice::SpliceError> {
+    use splice::graph::CodeGraph;
+    use splice::serve::Server;
+
+    // Keep the temporary graph alive for the whole session
+    let temp_dir;
+    let graph_path = match graph_path {
+        Some(path) => path.to_path_buf(),
+        None => {
+            temp_dir = tempfile::TempDir::new()?;
+            temp_dir.path().join("graph.db")
+        }
+    };
+    let graph = CodeGraph::open(&graph_path)?;
+
+    let mut server = Server::new(graph, position_encoding.to_offset_encoding(), options);
+    let stdin = std::io::stdin();
+    server.run(stdin.lock(), std::io::stdout().lock())
+}
+
+/// Execute the references command.
+///
+/// Finds same-file and cross-file references like `delete` does, and reports
+/// each one with the source line it occurs on. With `follow_renames`, also
+/// reports usages of names the symbol was renamed from in git history.
+///
+/// With `stream`, each reference is written to stdout as soon as it is
+/// rendered, as an element of a JSON array, and only the current file's text
+/// is kept; the returned payload is not printed.
+fn execute_references(
+    file_path: &Path,
+    symbol_name: &str,
+    follow_renames: bool,
+    context_symbol: Option<&str>,
+    output: &ReferenceOutput,
+    scan: &ReferenceScanOptions,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::graph::{CodeGraph, SymbolRecord};
+    use splice::resolve::references::find_references_with_options;
+    use splice::resolve::references::history::former_names;
+    use splice::resolve::references::rust::find_rust_references_to_former_name;
+    use splice::resolve::references::{
+        context_snippet, display_column, references_dot, retain_within_symbol, Reference,
+    };
+    use splice::symbol::Language as SymbolLanguage;
+
+    let ReferenceOutput {
+        context_format,
+        before_context,
+        after_context,
+        tab_width,
+        graph,
+        stream,
+    } = *output;
+
+    let source = std::fs::read(file_path)?;
+    let language = SymbolLanguage::from_path(file_path).unwrap_or(SymbolLanguage::Rust);
+    let symbols = extract_symbols_with_language(file_path, &source, language, &scan.parse)?;
+
+    let graph_db_path = file_path.parent().unwrap().join(".splice_graph.db");
+    let mut code_graph = CodeGraph::open(&graph_db_path)?;
+    for symbol in &symbols {
+        code_graph.store_symbol_record(file_path, &SymbolRecord::of(symbol))?;
+    }
+
+    let mut ref_set =
+        find_references_with_options(&code_graph, file_path, symbol_name, None, scan)?;
+    if let Some(context) = context_symbol {
+        retain_within_symbol(&mut ref_set.references, file_path, context)?;
+    }
+
+    let former_names = if follow_renames {
+        former_names(file_path, symbol_name)?
+    } else {
+        Vec::new()
+    };
+    let mut former_refs = Vec::new();
+    for name in &former_names {
+        let mut refs =
+            find_rust_references_to_former_name(file_path, symbol_name, None, name, scan)?;
+        if let Some(context) = context_symbol {
+            retain_within_symbol(&mut refs, file_path, context)?;
+        }
+        for r in refs {
+            former_refs.push((Some(name.as_str()), r));
+        }
+    }
+
+    let all_refs = ref_set
+        .references
+        .iter()
+        .map(|r| (None, r))
+        .chain(former_refs.iter().map(|(name, r)| (*name, r)));
+    let render = |former_name: Option<&str>, r: &Reference, text: &str| {
+        let line_text = text.lines().nth(r.line.saturating_sub(1)).unwrap_or("");
+        let snippet = context_snippet(text, r.line, before_context, after_context);
+        let language = SymbolLanguage::from_path(Path::new(&r.file_path))
+            .unwrap_or(SymbolLanguage::Rust)
+            .as_str();
+
+        let mut reference = json!({
+            "file_path": r.file_path,
+            "line": r.line,
+            "column": r.column_byte,
+            "column_display": display_column(line_text, r.column_byte, tab_width),
+            "byte_start": r.byte_start,
+            "byte_end": r.byte_end,
+            "snippet": context_format.render(&r.file_path, r.line, language, &snippet),
+        });
+        if let Some(name) = former_name {
+            reference["former_name"] = json!(name);
+        }
+        if let Some(alias) = &r.alias {
+            reference["alias"] = json!(alias);
+        }
+        if let ReferenceContext::FieldAccess { is_verified: false } = r.context {
+            reference["unverified"] = json!(true);
+        }
+        reference
+    };
+
+    if stream {
+        use std::io::Write;
+
+        // Stdout is line-buffered, so each element reaches the consumer as it is written
+        let mut out = std::io::stdout().lock();
+        let mut current: Option<(&str, String)> = None;
+        let mut count = 0;
+        out.write_all(b"[")?;
+        for (former_name, r) in all_refs {
+            if !matches!(&current, Some((path, _)) if *path == r.file_path) {
+                current = Some((&r.file_path, std::fs::read_to_string(&r.file_path)?));
+            }
+            let (_, text) = current.as_ref().unwrap();
+            let separator: &[u8] = if count == 0 { b"\n" } else { b",\n" };
+            out.write_all(separator)?;
+            serde_json::to_writer(&mut out, &render(former_name, r, text)).map_err(|e| {
+                splice::SpliceError::Other(format!("Failed to write reference: {}", e))
+            })?;
+            count += 1;
+        }
+        out.write_all(b"\n]\n")?;
+        out.flush()?;
+
+        return Ok(splice::cli::CliSuccessPayload::with_data(
+            format!("Streamed {} references to '{}'", count, symbol_name),
+            json!({ "symbol": symbol_name, "count": count }),
+        ));
+    }
+
+    let mut sources: HashMap<&str, String> = HashMap::new();
+    let mut references = Vec::with_capacity(ref_set.references.len() + former_refs.len());
+    for (former_name, r) in all_refs {
+        let text = match sources.get(r.file_path.as_str()) {
+            Some(text) => text,
+            None => {
+                let text = std::fs::read_to_string(&r.file_path)?;
+                sources.entry(r.file_path.as_str()).or_insert(text)
+            }
+        };
+        references.push(render(former_name, r, text));
+    }
+
+    let mut data = json!({
+        "symbol": symbol_name,
+        "references": references,
+        "has_glob_ambiguity": ref_set.has_glob_ambiguity,
+        "skipped_files": skipped_files_json(&ref_set.skipped_files),
+        "former_names": former_names,
+    });
+    if graph {
+        let mut graph_refs = ref_set.references.clone();
+        graph_refs.extend(former_refs.into_iter().map(|(_, r)| r));
+        data["graph"] = json!(references_dot(&ref_set.definition, &graph_refs));
+    }
+
+    Ok(splice::cli::CliSuccessPayload::with_data(
+        format!("Found {} references to '{}'", references.len(), symbol_name),
+        data,
+    ))
+}
+
+/// Execute `references --report-unreferenced`.
+///
+/// Lists crate-internal items in `path` (a file or directory) that the
+/// reference engine finds no usages of. As with rename, the scan's graph
+/// lives in a temporary directory.
+fn execute_report_unreferenced(
+    path: &Path,
+    exclude_patterns: &[String],
+    scan: &ReferenceScanOptions,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::graph::CodeGraph;
+    use splice::ingest::{exclude_set, is_excluded};
+    use splice::resolve::references::unreferenced::find_unreferenced;
+
+    let graph_dir = tempfile::TempDir::new()?;
+    let code_graph = CodeGraph::open(&graph_dir.path().join("graph.db"))?;
+    let mut report = find_unreferenced(&code_graph, path, scan)?;
+
+    let excludes = exclude_set(exclude_patterns)?;
+    report
+        .symbols
+        .retain(|s| !is_excluded(&excludes, Path::new(&s.file_path)));
+    report
+        .skipped_files
+        .retain(|(file, _)| !is_excluded(&excludes, file));
+
+    let unreferenced: Vec<Value> = report
+        .symbols
+        .iter()
+        .map(|s| {
+            json!({
+                "file_path": s.file_path,
+                "name": s.name,
+                "kind": s.kind.as_str(),
+                "visibility": s.visibility,
+                "line": s.line,
+                "byte_start": s.byte_start,
+                "byte_end": s.byte_end,
+            })
+        })
+        .collect();
+
+    Ok(splice::cli::CliSuccessPayload::with_data(
+        format!(
+            "Found {} unreferenced item(s) in {} file(s)",
+            unreferenced.len(),
+            report.scanned_files
+        ),
+        json!({
+            "unreferenced": unreferenced,
+            "scanned_files": report.scanned_files,
+            "skipped_files": skipped_files_json(&report.skipped_files),
+        }),
+    ))
+}
+
+/// Execute the rename command (dry run only).
+///
+/// Lists the definition and reference rewrites grouped by file, plus the
+/// aliased and unverified references a rename leaves alone. Nothing in the workspace is
+/// written; the reference scan's graph lives in a temporary directory.
+fn execute_rename(
+    file_path: &Path,
+    symbol_name: &str,
+    new_name: &str,
+    scan: &ReferenceScanOptions,
+    apply: Option<RenameApply<'_>>,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::graph::CodeGraph;
+    use splice::patch::{apply_batch_with_options, SpanBatch, SpanReplacement};
+    use splice::resolve::references::rename::plan_rename;
+    use splice::symbol::Language as SymbolLanguage;
+    use splice::validate::AnalyzerMode as ValidateAnalyzerMode;
+
+    let graph_dir = tempfile::TempDir::new()?;
+    let code_graph = CodeGraph::open(&graph_dir.path().join("graph.db"))?;
+    let plan = plan_rename(&code_graph, file_path, symbol_name, None, new_name, scan)?;
+
+    let dry_run = apply.is_none();
+    let mut summaries = Vec::new();
+    let mut response_data = serde_json::Map::new();
+    if let Some(RenameApply {
+        analyzer,
+        write,
+        validation,
+    }) = apply
+    {
+        let WriteOptions {
+            create_backup,
+            operation_id,
+            metadata,
+        } = write;
+
+        let workspace_dir = file_path.parent().ok_or_else(|| {
+            splice::SpliceError::Other("Cannot determine workspace directory".to_string())
+        })?;
+
+        let analyzer_mode = match analyzer {
+            Some(splice::cli::AnalyzerMode::Off) => ValidateAnalyzerMode::Off,
+            Some(splice::cli::AnalyzerMode::Os) => ValidateAnalyzerMode::Path,
+            Some(splice::cli::AnalyzerMode::Path) => {
+                return Err(splice::SpliceError::Other(
+                    "Explicit analyzer path not yet supported".to_string(),
+                ));
+            }
+            None => ValidateAnalyzerMode::Off,
+        };
+
+        let replacements: Vec<SpanReplacement> = plan
+            .edits
+            .iter()
+            .map(|edit| {
+                SpanReplacement::new(
+                    PathBuf::from(&edit.file_path),
+                    edit.byte_start,
+                    edit.byte_end,
+                    edit.new.clone(),
+                )
+            })
+            .collect();
+
+        let backup_manifest_path = if create_backup {
+            use splice::patch::BackupWriter;
+
+            let workspace_root = find_workspace_root(file_path)?;
+            let mut backup_writer = BackupWriter::new(&workspace_root, operation_id.clone())?;
+            let mut backed_up = std::collections::HashSet::new();
+            for replacement in &replacements {
+                if backed_up.insert(&replacement.file) {
+                    backup_writer.backup_file(&replacement.file)?;
+                }
+            }
+            Some(backup_writer.finalize()?)
+        } else {
+            None
+        };
+
+        summaries = apply_batch_with_options(
+            &[SpanBatch::new(replacements)],
+            workspace_dir,
+            SymbolLanguage::Rust,
+            analyzer_mode,
+            validation,
+        )?;
+
+        if let Some(manifest_path) = &backup_manifest_path {
+            record_backup_result(manifest_path)?;
+            response_data.insert(
+                "backup_manifest".to_string(),
+                json!(manifest_path.to_string_lossy()),
+            );
+        }
+        if let Some(op_id) = operation_id {
+            response_data.insert("operation_id".to_string(), json!(op_id));
+        }
+        if let Some(meta) = metadata {
+            // Try to parse as JSON, if fails include as string
+            if let Ok(parsed) = serde_json::from_str::<Value>(&meta) {
+                response_data.insert("metadata".to_string(), parsed);
+            } else {
+                response_data.insert("metadata".to_string(), json!(meta));
+            }
+        }
+    }
+
+    // Edits come sorted by file, so each file's run is contiguous
+    let mut by_file: Vec<(&str, Vec<Value>)> = Vec::new();
+    for edit in &plan.edits {
+        if !matches!(by_file.last(), Some((file, _)) if *file == edit.file_path) {
+            by_file.push((&edit.file_path, Vec::new()));
+        }
+        if let Some((_, edits)) = by_file.last_mut() {
+            edits.push(json!({
+                "byte_start": edit.byte_start,
+                "byte_end": edit.byte_end,
+                "line": edit.line,
+                "column": edit.column,
+                "old": edit.old,
+                "new": edit.new,
+                "definition": edit.definition,
+            }));
         }
     }
+    let files: Vec<Value> = by_file
+        .into_iter()
+        .map(|(file_path, edits)| {
+            let mut file = json!({ "file_path": file_path, "edits": edits });
+            if let Some(summary) = summaries.iter().find(|s| s.file == Path::new(file_path)) {
+                file["before_hash"] = json!(summary.before_hash);
+                file["after_hash"] = json!(summary.after_hash);
+            }
+            file
+        })
+        .collect();
+    let aliased: Vec<Value> = plan
+        .aliased
+        .iter()
+        .map(|r| {
+            json!({
+                "file_path": r.file_path,
+                "line": r.line,
+                "column": r.column_byte,
+                "alias": r.alias,
+            })
+        })
+        .collect();
+    let unverified: Vec<Value> = plan
+        .unverified
+        .iter()
+        .map(|r| json!({ "file_path": r.file_path, "line": r.line, "column": r.column_byte }))
+        .collect();
+
+    let message = format!(
+        "Renaming '{}' to '{}' {} {} identifier(s) in {} file(s)",
+        symbol_name,
+        new_name,
+        if dry_run { "would rewrite" } else { "rewrote" },
+        plan.edits.len(),
+        files.len()
+    );
+    response_data.insert("symbol".to_string(), json!(symbol_name));
+    response_data.insert("new_name".to_string(), json!(new_name));
+    response_data.insert("dry_run".to_string(), json!(dry_run));
+    response_data.insert("files".to_string(), json!(files));
+    response_data.insert("aliased".to_string(), json!(aliased));
+    response_data.insert("unverified".to_string(), json!(unverified));
+    response_data.insert(
+        "skipped_files".to_string(),
+        skipped_files_json(&plan.skipped_files),
+    );
+    response_data.insert(
+        "has_glob_ambiguity".to_string(),
+        json!(plan.has_glob_ambiguity),
+    );
 
     Ok(splice::cli::CliSuccessPayload::with_data(
-        format!(
-            "Patched {} file(s) across {} batch(es).",
-            summaries.len(),
-            batch_count
-        ),
-        response_data,
+        message,
+        serde_json::Value::Object(response_data),
     ))
 }
 
+/// Execute the diff command.
+///
+/// Each side is treated as a directory when it exists on disk, otherwise as a
+/// git revision resolved in `repo`.
+fn execute_diff(
+    before: &str,
+    after: &str,
+    repo: Option<&Path>,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::ingest::diff::{collect_dir_symbols, collect_git_symbols, diff_symbols};
+
+    let repo = match repo {
+        Some(path) => path.to_path_buf(),
+        None => env::current_dir()?,
+    };
+    let collect = |snapshot: &str| {
+        let dir = Path::new(snapshot);
+        if dir.is_dir() {
+            collect_dir_symbols(dir)
+        } else {
+            collect_git_symbols(&repo, snapshot)
+        }
+    };
+
+    let diff = diff_symbols(&collect(before)?, &collect(after)?);
+    let message = format!(
+        "{} added, {} removed, {} moved",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.moved.len()
+    );
+    let data = serde_json::to_value(&diff)
+        .map_err(|e| splice::SpliceError::Other(format!("Failed to serialize diff: {}", e)))?;
+
+    Ok(splice::cli::CliSuccessPayload::with_data(message, data))
+}
+
 /// Execute the plan command.
 ///
 /// This function is a thin adapter that:
@@ -720,16 +3154,124 @@ fn execute_plan(plan_path: &Path) -> Result<splice::cli::CliSuccessPayload, spli
     )))
 }
 
+/// Execute the plan command in keep-going mode.
+///
+/// Every step is attempted. If all succeed the per-step report is returned as
+/// data; otherwise the run becomes a `PlanStepsFailed` error whose diagnostics
+/// note each applied step and describe each failed one.
+fn execute_plan_keep_going(
+    plan_path: &Path,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::error::{Diagnostic, DiagnosticLevel};
+    use splice::plan::execute_plan_keep_going;
+
+    let workspace_dir = plan_path.parent().ok_or_else(|| {
+        splice::SpliceError::Other(
+            "Cannot determine workspace directory from plan path".to_string(),
+        )
+    })?;
+
+    let outcomes = execute_plan_keep_going(plan_path, workspace_dir)?;
+
+    let (applied, failed): (Vec<_>, Vec<_>) = outcomes.iter().partition(|o| o.applied);
+    if !failed.is_empty() {
+        return Err(splice::SpliceError::PlanStepsFailed {
+            failed_steps: failed.iter().map(|o| o.step).collect(),
+            applied_steps: applied.iter().map(|o| o.step).collect(),
+            diagnostics: outcomes
+                .iter()
+                .map(|o| {
+                    let (level, detail) = match &o.error {
+                        Some(error) => (DiagnosticLevel::Error, error.as_str()),
+                        None => (
+                            DiagnosticLevel::Note,
+                            o.message.as_deref().unwrap_or("applied"),
+                        ),
+                    };
+                    Diagnostic::new("plan", level, format!("Step {}: {}", o.step, detail))
+                        .with_file(workspace_dir.join(&o.file))
+                })
+                .collect(),
+        });
+    }
+
+    Ok(splice::cli::CliSuccessPayload::with_data(
+        format!(
+            "Plan executed successfully: {} steps completed",
+            outcomes.len()
+        ),
+        json!({ "steps": outcomes }),
+    ))
+}
+
+/// Execute the plan command in validate-only mode.
+///
+/// Resolves each step and checks its replacement file without patching. Any
+/// failing step turns the whole run into a `PlanValidationFailed` error carrying
+/// one diagnostic per failing step.
+fn execute_plan_validation(
+    plan_path: &Path,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::error::{Diagnostic, DiagnosticLevel};
+    use splice::plan::validate_plan;
+
+    let workspace_dir = plan_path.parent().ok_or_else(|| {
+        splice::SpliceError::Other(
+            "Cannot determine workspace directory from plan path".to_string(),
+        )
+    })?;
+
+    let outcomes = validate_plan(plan_path, workspace_dir)?;
+
+    let failures: Vec<_> = outcomes.iter().filter(|o| !o.is_ok()).collect();
+    if !failures.is_empty() {
+        return Err(splice::SpliceError::PlanValidationFailed {
+            failed_steps: failures.iter().map(|o| o.step).collect(),
+            diagnostics: failures
+                .iter()
+                .map(|o| {
+                    Diagnostic::new(
+                        "plan",
+                        DiagnosticLevel::Error,
+                        format!("Step {}: {}", o.step, o.error.as_deref().unwrap_or("")),
+                    )
+                    .with_file(workspace_dir.join(&o.file))
+                })
+                .collect(),
+        });
+    }
+
+    Ok(splice::cli::CliSuccessPayload::with_data(
+        format!("Plan is valid: {} steps resolved", outcomes.len()),
+        json!({ "steps": outcomes }),
+    ))
+}
+
 /// Execute the undo command.
 ///
 /// This function restores files from a backup manifest created during
 /// a previous splice operation.
-fn execute_undo(manifest_path: &Path) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
-    use splice::patch::restore_from_manifest;
+fn execute_undo(
+    manifest_path: &Path,
+    force: bool,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::patch::restore_from_manifest_with_force;
+
+    let workspace_root = manifest_workspace_root(manifest_path)?;
+
+    // Restore from backup, refusing to clobber later edits unless forced
+    let restored_count = restore_from_manifest_with_force(manifest_path, workspace_root, force)?;
+
+    Ok(splice::cli::CliSuccessPayload::message_only(format!(
+        "Restored {} file(s) from backup.",
+        restored_count
+    )))
+}
 
-    // Determine workspace directory (parent of manifest's parent directory)
-    // The manifest is at .splice-backup/<operation_id>/manifest.json
-    // The workspace root is the parent of .splice-backup
+/// Workspace root owning a backup manifest.
+///
+/// The manifest is at `<workspace>/.splice-backup/<operation_id>/manifest.json`.
+fn manifest_workspace_root(manifest_path: &Path) -> Result<&Path, splice::SpliceError> {
     let backup_dir = manifest_path.parent().ok_or_else(|| {
         splice::SpliceError::Other("Manifest has no parent directory".to_string())
     })?;
@@ -740,19 +3282,17 @@ fn execute_undo(manifest_path: &Path) -> Result<splice::cli::CliSuccessPayload,
         )
     })?;
 
-    let workspace_root = splice_backup_dir.parent().ok_or_else(|| {
+    splice_backup_dir.parent().ok_or_else(|| {
         splice::SpliceError::Other(
             "Cannot determine workspace root from manifest path".to_string()
         )
-    })?;
-
-    // Restore from backup
-    let restored_count = restore_from_manifest(manifest_path, workspace_root)?;
+    })
+}
 
-    Ok(splice::cli::CliSuccessPayload::message_only(format!(
-        "Restored {} file(s) from backup.",
-        restored_count
-    )))
+/// Record the post-operation file hashes in a backup manifest, so undo can
+/// tell whether the files were edited afterwards.
+fn record_backup_result(manifest_path: &Path) -> Result<(), splice::SpliceError> {
+    splice::patch::record_after_hashes(manifest_path, manifest_workspace_root(manifest_path)?)
 }
 
 /// Execute the apply-files command.
@@ -764,14 +3304,25 @@ fn execute_apply_files(
     glob_pattern: &str,
     find_pattern: &str,
     replace_pattern: &str,
-    language: Option<splice::cli::Language>,
-    validate: bool,
-    create_backup: bool,
-    operation_id: Option<String>,
-    metadata: Option<String>,
+    options: &ApplyFilesOptions,
+    write: WriteOptions,
 ) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
     use splice::patch::{apply_pattern_replace, find_pattern_in_files, BackupWriter, PatternReplaceConfig};
 
+    let ApplyFilesOptions {
+        exclude_patterns,
+        language,
+        validate,
+        identifier_only,
+        summary_only,
+        max_file_size,
+    } = *options;
+    let WriteOptions {
+        create_backup,
+        operation_id,
+        metadata,
+    } = write;
+
     // Get current directory as workspace root
     let workspace_root = env::current_dir()
         .map_err(|err| {
@@ -788,10 +3339,13 @@ fn execute_apply_files(
         // First, find all matching files to back up
         let find_config = PatternReplaceConfig {
             glob_pattern: glob_pattern.to_string(),
+            exclude_patterns: exclude_patterns.to_vec(),
             find_pattern: find_pattern.to_string(),
             replace_pattern: replace_pattern.to_string(),
             language: symbol_language,
             validate: false,
+            max_file_size,
+            identifier_only,
         };
         let matches = find_pattern_in_files(&find_config)?;
 
@@ -808,19 +3362,31 @@ fn execute_apply_files(
     // Create configuration for pattern replacement
     let config = PatternReplaceConfig {
         glob_pattern: glob_pattern.to_string(),
+        exclude_patterns: exclude_patterns.to_vec(),
         find_pattern: find_pattern.to_string(),
         replace_pattern: replace_pattern.to_string(),
         language: symbol_language,
         validate,
+        max_file_size,
+        identifier_only,
     };
 
     // Apply the pattern replacement
     let result = apply_pattern_replace(&config, &workspace_root)?;
 
+    if let Some(manifest_path) = &backup_manifest_path {
+        record_backup_result(manifest_path)?;
+    }
+
     // Build response data
     let mut response_data = serde_json::Map::new();
-    response_data.insert("files_patched".to_string(), json!(result.files_patched));
+    if summary_only {
+        response_data.insert("files_patched_count".to_string(), json!(result.files_patched.len()));
+    } else {
+        response_data.insert("files_patched".to_string(), json!(result.files_patched));
+    }
     response_data.insert("replacements_count".to_string(), json!(result.replacements_count));
+    response_data.insert("bytes_changed".to_string(), json!(result.bytes_changed));
     if let Some(manifest_path) = backup_manifest_path {
         response_data.insert("backup_manifest".to_string(), json!(manifest_path.to_string_lossy()));
     }
@@ -845,6 +3411,84 @@ fn execute_apply_files(
     Ok(splice::cli::CliSuccessPayload::with_data(message, serde_json::Value::Object(response_data)))
 }
 
+/// Execute the ingest command: parse a directory into a fresh code graph.
+fn execute_ingest(
+    dir: &Path,
+    graph_path: &Path,
+    jobs: usize,
+    exclude_patterns: &[String],
+    max_file_size: u64,
+    parse: splice::ingest::ParseOptions,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::graph::CodeGraph;
+    use splice::ingest::{exclude_set, Ingestor};
+
+    // Nodes are only ever appended, so ingesting into a used graph would duplicate them
+    if graph_path.exists() {
+        return Err(splice::SpliceError::Other(format!(
+            "Graph {} already exists; remove it or choose another path",
+            graph_path.display()
+        )));
+    }
+    let excludes = exclude_set(exclude_patterns)?;
+    let graph = CodeGraph::open(graph_path)?;
+    let mut ingestor = Ingestor::new(graph)
+        .with_jobs(jobs)
+        .with_max_file_size(max_file_size)
+        .with_parse_options(parse)
+        .with_excludes(excludes);
+    let summary = ingestor.ingest_dir(dir)?;
+
+    let skipped: Vec<Value> = summary
+        .skipped_files
+        .iter()
+        .map(|(file, reason)| json!({ "file": file.to_string_lossy(), "reason": reason }))
+        .collect();
+    Ok(splice::cli::CliSuccessPayload::with_data(
+        format!(
+            "Ingested {} symbol(s) from {} file(s) into {}",
+            summary.symbols,
+            summary.files,
+            graph_path.display()
+        ),
+        json!({
+            "graph": graph_path.to_string_lossy(),
+            "files": summary.files,
+            "symbols": summary.symbols,
+            "skipped_files": skipped,
+        }),
+    ))
+}
+
+/// Execute the graph-query command: list stored symbols matching a selector.
+fn execute_graph_query(
+    graph_path: &Path,
+    selector: &str,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::graph::query::{query_symbols, Selector};
+    use splice::graph::CodeGraph;
+
+    let parsed = Selector::parse(selector)?;
+    // Opening would create an empty database; a typo should not look like "no matches"
+    if !graph_path.exists() {
+        return Err(splice::SpliceError::Io {
+            path: graph_path.to_path_buf(),
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "graph database not found"),
+        });
+    }
+    let graph = CodeGraph::open(graph_path)?;
+    let symbols = query_symbols(&graph, &parsed)?;
+
+    Ok(splice::cli::CliSuccessPayload::with_data(
+        format!("Found {} symbol(s) matching '{}'", symbols.len(), selector),
+        json!({
+            "selector": selector,
+            "count": symbols.len(),
+            "symbols": symbols,
+        }),
+    ))
+}
+
 /// Execute the query command.
 ///
 /// This function queries symbols by labels using Magellan integration.
@@ -964,6 +3608,91 @@ fn execute_query(
     ))
 }
 
+/// Execute the languages command.
+///
+/// With `--format text` the table is printed here and the payload is not.
+fn execute_languages(format: splice::cli::OutputFormat) -> splice::cli::CliSuccessPayload {
+    use splice::ingest::detect::Language as DetectLanguage;
+    use splice::ingest::grammar::parser_for;
+    use splice::ingest::source::grammar_for;
+    use splice::patch::validation_tool;
+    use splice::symbol::Language as SymbolLanguage;
+
+    let rows: Vec<_> = DetectLanguage::ALL
+        .into_iter()
+        .map(|language| {
+            let symbol_lang = SymbolLanguage::from(language);
+            let grammar = parser_for(language.as_str(), &grammar_for(symbol_lang)).is_ok();
+            (language, grammar, validation_tool(symbol_lang))
+        })
+        .collect();
+
+    if format == splice::cli::OutputFormat::Text {
+        println!(
+            "{:<12} {:<24} {:<9} VALIDATION",
+            "LANGUAGE", "EXTENSIONS", "GRAMMAR"
+        );
+        for (language, grammar, tool) in &rows {
+            let extensions: Vec<String> = language
+                .extensions()
+                .iter()
+                .map(|ext| format!(".{}", ext))
+                .collect();
+            println!(
+                "{:<12} {:<24} {:<9} {}",
+                language.as_str(),
+                extensions.join(", "),
+                if *grammar { "yes" } else { "no" },
+                tool
+            );
+        }
+    }
+
+    let languages: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|(language, grammar, tool)| {
+            json!({
+                "language": language.as_str(),
+                "extensions": language.extensions(),
+                "grammar": grammar,
+                "validation_tool": tool,
+            })
+        })
+        .collect();
+    splice::cli::CliSuccessPayload::with_data(
+        format!("{} supported languages", languages.len()),
+        json!({ "languages": languages }),
+    )
+}
+
+/// Execute the json-schema command.
+///
+/// One format yields its schema as `data`; none yields every schema keyed by name.
+fn execute_json_schema(
+    format: Option<splice::cli::SchemaFormat>,
+) -> splice::cli::CliSuccessPayload {
+    use clap::ValueEnum;
+    use splice::cli::SchemaFormat;
+
+    match format {
+        Some(format) => splice::cli::CliSuccessPayload::with_data(
+            format!("JSON Schema for the {} format", format.as_str()),
+            format.schema(),
+        ),
+        None => {
+            let schemas: serde_json::Map<String, serde_json::Value> =
+                SchemaFormat::value_variants()
+                    .iter()
+                    .map(|format| (format.as_str().to_string(), format.schema()))
+                    .collect();
+            splice::cli::CliSuccessPayload::with_data(
+                format!("JSON Schemas for {} formats", schemas.len()),
+                serde_json::Value::Object(schemas),
+            )
+        }
+    }
+}
+
 /// Execute the get command.
 ///
 /// This function retrieves code chunks from the database using Magellan integration.
@@ -1006,6 +3735,74 @@ fn execute_get(
     }
 }
 
+/// Execute the hidden dump-ast command: show the parse tree extraction walks.
+fn execute_dump_ast(
+    file_path: &Path,
+    language: Option<splice::cli::Language>,
+    with_symbols: bool,
+    parse: &splice::ingest::ParseOptions,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::ingest::dispatch::{extract_symbols_with_options, parse_tree_sexp};
+    use splice::symbol::{Language as SymbolLanguage, Symbol};
+
+    let symbol_lang = language
+        .map(|l| l.to_symbol_language())
+        .or_else(|| SymbolLanguage::from_path(file_path))
+        .ok_or_else(|| splice::SpliceError::Parse {
+            file: file_path.to_path_buf(),
+            message: "Cannot detect language - unknown file extension".to_string(),
+        })?;
+
+    let source = std::fs::read(file_path)?;
+    let ast = parse_tree_sexp(file_path, &source, symbol_lang, parse)?;
+
+    let mut data = json!({
+        "file": file_path.to_string_lossy(),
+        "language": symbol_lang.as_str(),
+        "ast": ast,
+    });
+    if with_symbols {
+        let symbols: Vec<Value> =
+            extract_symbols_with_options(file_path, &source, symbol_lang, parse)?
+                .iter()
+                .map(|symbol| {
+                    json!({
+                        "name": symbol.name(),
+                        "kind": symbol.kind(),
+                        "byte_start": symbol.byte_start(),
+                        "byte_end": symbol.byte_end(),
+                        "line_start": symbol.line_start(),
+                        "line_end": symbol.line_end(),
+                    })
+                })
+                .collect();
+        data["symbols"] = json!(symbols);
+    }
+
+    Ok(splice::cli::CliSuccessPayload::with_data(
+        format!("Parsed {} as {}", file_path.display(), symbol_lang.as_str()),
+        data,
+    ))
+}
+
+/// Execute the hidden dump-scopes command: show the shadowing analysis' scopes.
+fn execute_dump_scopes(
+    file_path: &Path,
+) -> Result<splice::cli::CliSuccessPayload, splice::SpliceError> {
+    use splice::resolve::references::rust::dump_scopes;
+
+    let source = std::fs::read(file_path)?;
+    let scopes = dump_scopes(&source)?;
+
+    Ok(splice::cli::CliSuccessPayload::with_data(
+        format!("Found {} scope(s) in {}", scopes.len(), file_path.display()),
+        json!({
+            "file": file_path.to_string_lossy(),
+            "scopes": scopes,
+        }),
+    ))
+}
+
 /// Emit JSON payload for successful CLI responses.
 fn emit_success_payload(payload: &splice::cli::CliSuccessPayload) {
     match serde_json::to_string(payload) {
@@ -1015,7 +3812,7 @@ fn emit_success_payload(payload: &splice::cli::CliSuccessPayload) {
                 "status": "ok",
                 "message": payload.message.clone(),
             });
-            println!("{}", fallback.to_string());
+            println!("{}", fallback);
             eprintln!("Serialization warning: {}", err);
         }
     }
@@ -1033,11 +3830,177 @@ fn emit_error_payload(payload: &splice::cli::CliErrorPayload) {
                     "message": err.to_string()
                 }
             });
-            eprintln!("{}", fallback.to_string());
+            eprintln!("{}", fallback);
         }
     }
 }
 
+/// Add the warnings reported by passing gates to a success payload as `data.warnings`.
+fn with_gate_warnings(
+    mut payload: splice::cli::CliSuccessPayload,
+    warnings: Vec<splice::error::Diagnostic>,
+) -> splice::cli::CliSuccessPayload {
+    if warnings.is_empty() {
+        return payload;
+    }
+    let warnings: Vec<splice::cli::DiagnosticPayload> =
+        warnings.into_iter().map(Into::into).collect();
+    let data = payload.data.get_or_insert_with(|| json!({}));
+    if let Some(data) = data.as_object_mut() {
+        data.insert("warnings".to_string(), json!(warnings));
+    }
+    payload
+}
+
+/// Flags shared by the commands that write source files.
+struct WriteOptions {
+    /// Back up the files before writing them.
+    create_backup: bool,
+    /// Identifier recorded in the backup manifest and the response.
+    operation_id: Option<String>,
+    /// Caller-supplied metadata echoed in the response.
+    metadata: Option<String>,
+}
+
+/// Settings for applying a rename; absent under `--dry-run`.
+struct RenameApply<'a> {
+    /// Optional analyzer gate mode.
+    analyzer: Option<splice::cli::AnalyzerMode>,
+    /// Backup and audit flags.
+    write: WriteOptions,
+    /// Gates run after the rewrite.
+    validation: &'a splice::patch::ValidationOptions,
+}
+
+/// How `patch` and `resolve` pick one symbol out of a file.
+#[derive(Clone, Copy)]
+struct SymbolFilter<'a> {
+    kind: Option<splice::cli::SymbolKind>,
+    signature: Option<&'a str>,
+    in_test: Option<bool>,
+    language: Option<splice::cli::Language>,
+}
+
+/// Flags of `delete`, shared by its `--symbol` and `--symbol-regex` forms.
+struct DeleteOptions<'a> {
+    kind: Option<splice::cli::SymbolKind>,
+    language: Option<splice::cli::Language>,
+    summary_only: bool,
+    dry_run: bool,
+    force: bool,
+    max_references: Option<usize>,
+    context_symbol: Option<&'a str>,
+    trim_blank_lines: Option<usize>,
+}
+
+/// A single `patch` as given on the command line, before the flags that
+/// `--batch` makes optional are checked.
+struct PatchArgs {
+    file: Option<PathBuf>,
+    symbol: Option<String>,
+    insert_after: Option<String>,
+    insert_before: Option<String>,
+    replacement_file: Option<PathBuf>,
+    verify_hash: Option<String>,
+    preview: bool,
+}
+
+/// What a single `patch` writes and where.
+struct PatchTarget<'a> {
+    file_path: &'a Path,
+    symbol_name: &'a str,
+    insert: Option<splice::patch::InsertPosition>,
+    replacement_file: &'a Path,
+    preview: bool,
+}
+
+/// How `references` renders each reference.
+struct ReferenceOutput {
+    context_format: splice::cli::ContextFormat,
+    before_context: usize,
+    after_context: usize,
+    tab_width: usize,
+    graph: bool,
+    stream: bool,
+}
+
+/// Flags of `apply-files` besides the glob and the pattern pair.
+struct ApplyFilesOptions<'a> {
+    exclude_patterns: &'a [String],
+    language: Option<splice::cli::Language>,
+    validate: bool,
+    identifier_only: bool,
+    summary_only: bool,
+    max_file_size: u64,
+}
+
+/// Build validation gate options from the shared CLI flags.
+fn validation_options(
+    cargo_features: &splice::cli::CargoFeatureArgs,
+    gate_args: &splice::cli::GateArgs,
+    max_file_size: u64,
+    parse: splice::ingest::ParseOptions,
+    manifest_path: Option<&Path>,
+    warnings: &splice::patch::GateWarnings,
+) -> splice::patch::ValidationOptions {
+    splice::patch::ValidationOptions {
+        cargo_features: cargo_features.to_cargo_features(),
+        max_file_size,
+        parse,
+        gates: gate_args.to_gate_set(),
+        fast_check: gate_args.fast_check,
+        scoped_analyzer: gate_args.scoped_analyzer,
+        manifest_path: manifest_path.map(Path::to_path_buf),
+        warnings: warnings.clone(),
+        ..Default::default()
+    }
+}
+
+/// Root of the cross-file reference scan when `--manifest-path` overrides
+/// the upward search: the manifest's directory.
+fn manifest_scan_root(manifest_path: Option<&Path>) -> Result<Option<&Path>, splice::SpliceError> {
+    match manifest_path {
+        None => Ok(None),
+        Some(manifest) if manifest.is_file() => Ok(manifest.parent()),
+        Some(manifest) => Err(splice::SpliceError::Other(format!(
+            "Manifest not found: {}",
+            manifest.display()
+        ))),
+    }
+}
+
+/// Reference scan bounds from `--max-file-size`, `--max-ast-depth` and
+/// `--manifest-path`.
+fn reference_scan_options(
+    max_file_size: u64,
+    parse: splice::ingest::ParseOptions,
+    manifest_path: Option<&Path>,
+) -> Result<ReferenceScanOptions, splice::SpliceError> {
+    Ok(ReferenceScanOptions {
+        max_file_size,
+        parse,
+        scan_root: manifest_scan_root(manifest_path)?.map(Path::to_path_buf),
+        ..ReferenceScanOptions::default()
+    })
+}
+
+/// Files the reference scan could not read or parse, as `[{file, reason}]`.
+fn skipped_files_json(skipped_files: &[(PathBuf, String)]) -> Value {
+    skipped_files
+        .iter()
+        .map(|(file, reason)| json!({ "file": file.to_string_lossy(), "reason": reason }))
+        .collect()
+}
+
+/// The `--test` / `--no-test` selector as a resolution filter (`None` when neither is given).
+fn test_scope(test: bool, no_test: bool) -> Option<bool> {
+    match (test, no_test) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
 fn require_patch_arg<T>(flag: &str, value: Option<T>) -> Result<T, splice::SpliceError> {
     value.ok_or_else(|| {
         splice::SpliceError::Other(format!(
@@ -1095,37 +4058,50 @@ fn extract_symbols_with_language(
     path: &Path,
     source: &[u8],
     language: splice::symbol::Language,
+    options: &splice::ingest::ParseOptions,
 ) -> Result<Vec<SymbolWrapper>, splice::SpliceError> {
-    use splice::ingest::{
-        extract_cpp_symbols, extract_java_symbols, extract_javascript_symbols,
-        extract_python_symbols, extract_rust_symbols, extract_typescript_symbols,
-    };
+    use splice::ingest::cpp::extract_cpp_symbols_with_options;
+    use splice::ingest::elixir::extract_elixir_symbols_with_options;
+    use splice::ingest::haskell::extract_haskell_symbols_with_options;
+    use splice::ingest::java::extract_java_symbols_with_options;
+    use splice::ingest::javascript::extract_javascript_symbols_with_options;
+    use splice::ingest::python::extract_python_symbols_with_options;
+    use splice::ingest::rust::extract_rust_symbols_with_options;
+    use splice::ingest::typescript::extract_typescript_symbols_with_options;
 
     match language {
         splice::symbol::Language::Rust => {
-            let symbols = extract_rust_symbols(path, source)?;
+            let symbols = extract_rust_symbols_with_options(path, source, options)?;
             Ok(symbols.into_iter().map(SymbolWrapper::Rust).collect())
         }
         splice::symbol::Language::Python => {
-            let symbols = extract_python_symbols(path, source)?;
+            let symbols = extract_python_symbols_with_options(path, source, options)?;
             Ok(symbols.into_iter().map(SymbolWrapper::Python).collect())
         }
         splice::symbol::Language::C | splice::symbol::Language::Cpp => {
-            let symbols = extract_cpp_symbols(path, source)?;
+            let symbols = extract_cpp_symbols_with_options(path, source, options)?;
             Ok(symbols.into_iter().map(SymbolWrapper::Cpp).collect())
         }
         splice::symbol::Language::Java => {
-            let symbols = extract_java_symbols(path, source)?;
+            let symbols = extract_java_symbols_with_options(path, source, options)?;
             Ok(symbols.into_iter().map(SymbolWrapper::Java).collect())
         }
         splice::symbol::Language::JavaScript => {
-            let symbols = extract_javascript_symbols(path, source)?;
+            let symbols = extract_javascript_symbols_with_options(path, source, options)?;
             Ok(symbols.into_iter().map(SymbolWrapper::JavaScript).collect())
         }
         splice::symbol::Language::TypeScript => {
-            let symbols = extract_typescript_symbols(path, source)?;
+            let symbols = extract_typescript_symbols_with_options(path, source, options)?;
             Ok(symbols.into_iter().map(SymbolWrapper::TypeScript).collect())
         }
+        splice::symbol::Language::Haskell => {
+            let symbols = extract_haskell_symbols_with_options(path, source, options)?;
+            Ok(symbols.into_iter().map(SymbolWrapper::Haskell).collect())
+        }
+        splice::symbol::Language::Elixir => {
+            let symbols = extract_elixir_symbols_with_options(path, source, options)?;
+            Ok(symbols.into_iter().map(SymbolWrapper::Elixir).collect())
+        }
     }
 }
 
@@ -1137,6 +4113,8 @@ enum SymbolWrapper {
     Java(splice::ingest::java::JavaSymbol),
     JavaScript(splice::ingest::javascript::JavaScriptSymbol),
     TypeScript(splice::ingest::typescript::TypeScriptSymbol),
+    Haskell(splice::ingest::haskell::HaskellSymbol),
+    Elixir(splice::ingest::elixir::ElixirSymbol),
 }
 
 impl splice::symbol::Symbol for SymbolWrapper {
@@ -1148,6 +4126,8 @@ impl splice::symbol::Symbol for SymbolWrapper {
             SymbolWrapper::Java(s) => s.name(),
             SymbolWrapper::JavaScript(s) => s.name(),
             SymbolWrapper::TypeScript(s) => s.name(),
+            SymbolWrapper::Haskell(s) => s.name(),
+            SymbolWrapper::Elixir(s) => s.name(),
         }
     }
 
@@ -1159,6 +4139,8 @@ impl splice::symbol::Symbol for SymbolWrapper {
             SymbolWrapper::Java(s) => s.kind(),
             SymbolWrapper::JavaScript(s) => s.kind(),
             SymbolWrapper::TypeScript(s) => s.kind(),
+            SymbolWrapper::Haskell(s) => s.kind(),
+            SymbolWrapper::Elixir(s) => s.kind(),
         }
     }
 
@@ -1170,6 +4152,8 @@ impl splice::symbol::Symbol for SymbolWrapper {
             SymbolWrapper::Java(s) => s.byte_start(),
             SymbolWrapper::JavaScript(s) => s.byte_start(),
             SymbolWrapper::TypeScript(s) => s.byte_start(),
+            SymbolWrapper::Haskell(s) => s.byte_start(),
+            SymbolWrapper::Elixir(s) => s.byte_start(),
         }
     }
 
@@ -1181,6 +4165,8 @@ impl splice::symbol::Symbol for SymbolWrapper {
             SymbolWrapper::Java(s) => s.byte_end(),
             SymbolWrapper::JavaScript(s) => s.byte_end(),
             SymbolWrapper::TypeScript(s) => s.byte_end(),
+            SymbolWrapper::Haskell(s) => s.byte_end(),
+            SymbolWrapper::Elixir(s) => s.byte_end(),
         }
     }
 
@@ -1192,6 +4178,8 @@ impl splice::symbol::Symbol for SymbolWrapper {
             SymbolWrapper::Java(s) => s.line_start(),
             SymbolWrapper::JavaScript(s) => s.line_start(),
             SymbolWrapper::TypeScript(s) => s.line_start(),
+            SymbolWrapper::Haskell(s) => s.line_start(),
+            SymbolWrapper::Elixir(s) => s.line_start(),
         }
     }
 
@@ -1203,6 +4191,8 @@ impl splice::symbol::Symbol for SymbolWrapper {
             SymbolWrapper::Java(s) => s.line_end(),
             SymbolWrapper::JavaScript(s) => s.line_end(),
             SymbolWrapper::TypeScript(s) => s.line_end(),
+            SymbolWrapper::Haskell(s) => s.line_end(),
+            SymbolWrapper::Elixir(s) => s.line_end(),
         }
     }
 
@@ -1214,6 +4204,8 @@ impl splice::symbol::Symbol for SymbolWrapper {
             SymbolWrapper::Java(s) => s.col_start(),
             SymbolWrapper::JavaScript(s) => s.col_start(),
             SymbolWrapper::TypeScript(s) => s.col_start(),
+            SymbolWrapper::Haskell(s) => s.col_start(),
+            SymbolWrapper::Elixir(s) => s.col_start(),
         }
     }
 
@@ -1225,6 +4217,8 @@ impl splice::symbol::Symbol for SymbolWrapper {
             SymbolWrapper::Java(s) => s.col_end(),
             SymbolWrapper::JavaScript(s) => s.col_end(),
             SymbolWrapper::TypeScript(s) => s.col_end(),
+            SymbolWrapper::Haskell(s) => s.col_end(),
+            SymbolWrapper::Elixir(s) => s.col_end(),
         }
     }
 
@@ -1236,6 +4230,8 @@ impl splice::symbol::Symbol for SymbolWrapper {
             SymbolWrapper::Java(s) => s.fully_qualified(),
             SymbolWrapper::JavaScript(s) => s.fully_qualified(),
             SymbolWrapper::TypeScript(s) => s.fully_qualified(),
+            SymbolWrapper::Haskell(s) => s.fully_qualified(),
+            SymbolWrapper::Elixir(s) => s.fully_qualified(),
         }
     }
 
@@ -1247,6 +4243,32 @@ impl splice::symbol::Symbol for SymbolWrapper {
             SymbolWrapper::Java(_) => splice::symbol::Language::Java,
             SymbolWrapper::JavaScript(_) => splice::symbol::Language::JavaScript,
             SymbolWrapper::TypeScript(_) => splice::symbol::Language::TypeScript,
+            SymbolWrapper::Haskell(_) => splice::symbol::Language::Haskell,
+            SymbolWrapper::Elixir(_) => splice::symbol::Language::Elixir,
+        }
+    }
+
+    fn visibility(&self) -> Option<&str> {
+        match self {
+            SymbolWrapper::Rust(s) => s.visibility(),
+            SymbolWrapper::Python(s) => s.visibility(),
+            SymbolWrapper::Cpp(s) => s.visibility(),
+            SymbolWrapper::Java(s) => s.visibility(),
+            SymbolWrapper::JavaScript(s) => s.visibility(),
+            SymbolWrapper::TypeScript(s) => s.visibility(),
+            SymbolWrapper::Haskell(s) => s.visibility(),
+            SymbolWrapper::Elixir(s) => s.visibility(),
+        }
+    }
+
+    fn signature(&self) -> Option<String> {
+        match self {
+            SymbolWrapper::Cpp(s) => s.signature(),
+            SymbolWrapper::Java(s) => s.signature(),
+            _ => None,
         }
     }
+    fn in_test(&self) -> bool {
+        matches!(self, SymbolWrapper::Rust(s) if s.in_test)
+    }
 }
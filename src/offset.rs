@@ -0,0 +1,152 @@
+//! Conversions between byte offsets and editor positions.
+//!
+//! Splice addresses source text by UTF-8 byte offset, while LSP-based
+//! editors count columns in UTF-16 code units (and some in UTF-32, i.e.
+//! Unicode scalar values). These helpers translate between the two.
+//!
+//! Lines are 1-based and columns 0-based, matching the spans reported by
+//! the ingest modules.
+
+/// Unit used to count columns within a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PositionEncoding {
+    /// UTF-8 bytes (Splice's native unit).
+    #[default]
+    Utf8,
+    /// UTF-16 code units (LSP default, VS Code).
+    Utf16,
+    /// Unicode scalar values.
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Length of `ch` in this encoding's units.
+    fn char_len(self, ch: char) -> usize {
+        match self {
+            PositionEncoding::Utf8 => ch.len_utf8(),
+            PositionEncoding::Utf16 => ch.len_utf16(),
+            PositionEncoding::Utf32 => 1,
+        }
+    }
+}
+
+/// Convert a 1-based `line` and 0-based `column` in `encoding` units to a byte offset.
+///
+/// Returns `None` if the line does not exist, the column runs past the end of
+/// the line, or the column falls inside a character (e.g. between the two
+/// halves of a UTF-16 surrogate pair).
+pub fn position_to_byte(
+    source: &str,
+    line: usize,
+    column: usize,
+    encoding: PositionEncoding,
+) -> Option<usize> {
+    let line_start = line_start_byte(source, line)?;
+    let line_text = line_text(&source[line_start..]);
+
+    let mut units = 0;
+    for (index, ch) in line_text.char_indices() {
+        if units == column {
+            return Some(line_start + index);
+        }
+        units += encoding.char_len(ch);
+        if units > column {
+            return None;
+        }
+    }
+    (units == column).then_some(line_start + line_text.len())
+}
+
+/// Convert a byte offset to a 1-based line and 0-based column in `encoding` units.
+///
+/// Returns `None` if `byte` is past the end of `source` or not on a char boundary.
+pub fn byte_to_position(
+    source: &str,
+    byte: usize,
+    encoding: PositionEncoding,
+) -> Option<(usize, usize)> {
+    if !source.is_char_boundary(byte) {
+        return None;
+    }
+    let before = &source[..byte];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..]
+        .chars()
+        .map(|ch| encoding.char_len(ch))
+        .sum();
+    Some((line, column))
+}
+
+/// Convert a 1-based `line` and 0-based UTF-16 column to a byte offset.
+pub fn utf16_to_byte(source: &str, line: usize, utf16_col: usize) -> Option<usize> {
+    position_to_byte(source, line, utf16_col, PositionEncoding::Utf16)
+}
+
+/// Convert a byte offset to a 1-based line and 0-based UTF-16 column.
+pub fn byte_to_utf16(source: &str, byte: usize) -> Option<(usize, usize)> {
+    byte_to_position(source, byte, PositionEncoding::Utf16)
+}
+
+fn line_start_byte(source: &str, line: usize) -> Option<usize> {
+    if line == 0 {
+        return None;
+    }
+    if line == 1 {
+        return Some(0);
+    }
+    source
+        .match_indices('\n')
+        .nth(line - 2)
+        .map(|(index, _)| index + 1)
+}
+
+/// Text of the line starting at the beginning of `rest`, without its terminator.
+fn line_text(rest: &str) -> &str {
+    let line = rest.split('\n').next().unwrap_or("");
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "🦀" is 4 bytes in UTF-8 and 2 code units in UTF-16
+    const SOURCE: &str = "fn main() {\n    let s = \"🦀\"; let x = 1;\n}\n";
+
+    #[test]
+    fn test_utf16_column_after_emoji_maps_to_byte() {
+        let line_start = SOURCE.find("    let").unwrap();
+        let x_byte = SOURCE.find("x =").unwrap();
+        let x_utf16 = SOURCE[line_start..x_byte].encode_utf16().count();
+        assert_eq!(x_utf16, x_byte - line_start - 2);
+
+        assert_eq!(utf16_to_byte(SOURCE, 2, x_utf16), Some(x_byte));
+        assert_eq!(byte_to_utf16(SOURCE, x_byte), Some((2, x_utf16)));
+    }
+
+    #[test]
+    fn test_column_inside_surrogate_pair_is_rejected() {
+        let emoji_col = "    let s = \"".len();
+        assert!(utf16_to_byte(SOURCE, 2, emoji_col).is_some());
+        assert_eq!(utf16_to_byte(SOURCE, 2, emoji_col + 1), None);
+    }
+
+    #[test]
+    fn test_encodings_agree_on_ascii_and_bounds() {
+        for encoding in [
+            PositionEncoding::Utf8,
+            PositionEncoding::Utf16,
+            PositionEncoding::Utf32,
+        ] {
+            assert_eq!(position_to_byte(SOURCE, 1, 3, encoding), Some(3));
+            assert_eq!(position_to_byte(SOURCE, 3, 1, encoding), Some(SOURCE.len() - 1));
+            assert_eq!(position_to_byte(SOURCE, 3, 2, encoding), None);
+            assert_eq!(position_to_byte(SOURCE, 9, 0, encoding), None);
+        }
+        let x_byte = SOURCE.find("x =").unwrap();
+        let utf32 = byte_to_position(SOURCE, x_byte, PositionEncoding::Utf32).unwrap();
+        let utf16 = byte_to_position(SOURCE, x_byte, PositionEncoding::Utf16).unwrap();
+        assert_eq!(utf16.1, utf32.1 + 1);
+    }
+}
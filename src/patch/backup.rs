@@ -1,14 +1,17 @@
 //! Backup and undo support for Splice operations.
 //!
 //! This module provides the ability to create backups before patching
-//! and restore from those backups later. Backups are stored in
-//! `.splice-backup/<operation_id>/` directories with a manifest
-//! tracking the original file locations and hashes.
+//! and restore from those backups later. Each operation gets a
+//! `.splice-backup/<operation_id>/` directory holding a manifest that
+//! tracks the original file locations and hashes. File contents live in a
+//! content-addressable store, `.splice-backup/objects/`, keyed by SHA-256,
+//! so a file backed up unchanged by many operations is stored once.
 
 use crate::error::{Result, SpliceError};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
 /// Metadata about a backed-up file.
@@ -16,10 +19,17 @@ use std::path::{Path, PathBuf};
 pub struct BackupEntry {
     /// Original path of the file (relative to workspace root).
     pub original_path: PathBuf,
-    /// SHA-256 hash of the original file content.
-    pub hash: String,
-    /// Byte count of the original file.
+    /// SHA-256 hash of the original file content, or `None` for a file the
+    /// operation created. Undo deletes such a file instead of restoring it.
+    pub hash: Option<String>,
+    /// Byte count of the original file (0 for a created file).
     pub size: u64,
+    /// SHA-256 hash of the file once the operation completed.
+    ///
+    /// Undo refuses to overwrite a file whose current hash no longer matches.
+    /// Absent for manifests written before this was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after_hash: Option<String>,
 }
 
 /// Manifest describing a backup operation.
@@ -52,11 +62,37 @@ impl BackupManifest {
     pub fn add_file(&mut self, original_path: PathBuf, hash: String, size: u64) {
         self.files.push(BackupEntry {
             original_path,
-            hash,
+            hash: Some(hash),
             size,
+            after_hash: None,
         });
     }
 
+    /// Add an entry for a file the operation is about to create.
+    pub fn add_created_file(&mut self, original_path: PathBuf) {
+        self.files.push(BackupEntry {
+            original_path,
+            hash: None,
+            size: 0,
+            after_hash: None,
+        });
+    }
+
+    /// Record each file's current hash as its post-operation `after_hash`.
+    ///
+    /// Call once the operation has written its changes, then `save` the manifest.
+    pub fn record_after_hashes(&mut self, workspace_root: &Path) -> Result<()> {
+        for entry in &mut self.files {
+            let path = workspace_root.join(&entry.original_path);
+            let content = fs::read(&path).map_err(|e| SpliceError::Io {
+                path: path.clone(),
+                source: e,
+            })?;
+            entry.after_hash = Some(compute_hash(&content));
+        }
+        Ok(())
+    }
+
     /// Save the manifest to a file in the backup directory.
     pub fn save(&self) -> Result<()> {
         let manifest_path = self.backup_dir.join("manifest.json");
@@ -134,9 +170,9 @@ impl BackupWriter {
 
     /// Backup a single file.
     ///
-    /// The file is copied to the backup directory with its original
-    /// filename preserved (to avoid collisions, files with the same
-    /// name from different directories are stored in subdirectories).
+    /// The contents are written to the shared object store under their hash,
+    /// unless a blob with that hash is already there, and the manifest
+    /// records the file's original path and hash.
     pub fn backup_file(&mut self, file_path: &Path) -> Result<()> {
         // Read original file
         let content = fs::read(file_path).map_err(|e| SpliceError::Io {
@@ -149,37 +185,39 @@ impl BackupWriter {
         let size = content.len() as u64;
 
         // Compute relative path from workspace root
-        let relative = file_path
-            .strip_prefix(&self.workspace_root)
-            .map_err(|_| SpliceError::Other(format!(
-                "File '{}' is not under workspace root '{}'",
-                file_path.display(),
-                self.workspace_root.display()
-            )))?;
+        let relative = self.relative_path(file_path)?;
 
-        // Create backup path preserving directory structure
-        let backup_path = self.manifest.backup_dir.join(relative);
-
-        // Create parent directories if needed
-        if let Some(parent) = backup_path.parent() {
-            fs::create_dir_all(parent).map_err(|e| SpliceError::Io {
-                path: parent.to_path_buf(),
-                source: e,
-            })?;
+        // Store the contents once per hash
+        let blob_path = blob_path(&self.manifest.backup_dir, &hash);
+        if !blob_path.exists() {
+            write_blob(&blob_path, &content)?;
         }
 
-        // Copy file to backup location
-        fs::write(&backup_path, &content).map_err(|e| SpliceError::Io {
-            path: backup_path.clone(),
-            source: e,
-        })?;
-
         // Add entry to manifest
         self.manifest.add_file(relative.to_path_buf(), hash, size);
 
         Ok(())
     }
 
+    /// Record a file the operation will create.
+    ///
+    /// There is nothing to store; undo deletes the file.
+    pub fn record_new_file(&mut self, file_path: &Path) -> Result<()> {
+        let relative = self.relative_path(file_path)?;
+        self.manifest.add_created_file(relative.to_path_buf());
+        Ok(())
+    }
+
+    fn relative_path<'a>(&self, file_path: &'a Path) -> Result<&'a Path> {
+        file_path.strip_prefix(&self.workspace_root).map_err(|_| {
+            SpliceError::Other(format!(
+                "File '{}' is not under workspace root '{}'",
+                file_path.display(),
+                self.workspace_root.display()
+            ))
+        })
+    }
+
     /// Finalize the backup by writing the manifest file.
     ///
     /// Returns the path to the manifest file.
@@ -194,15 +232,79 @@ impl BackupWriter {
     }
 }
 
+/// Record post-operation hashes in a saved manifest.
+///
+/// Used after a successful operation so that undo can detect later edits.
+pub fn record_after_hashes(manifest_path: &Path, workspace_root: &Path) -> Result<()> {
+    let mut manifest = BackupManifest::load(manifest_path)?;
+    manifest.record_after_hashes(workspace_root)?;
+    manifest.save()
+}
+
 /// Restore files from a backup manifest.
+///
+/// Refuses with `SpliceError::UndoDiverged` if any file changed after the
+/// operation; see `restore_from_manifest_with_force`.
 pub fn restore_from_manifest(manifest_path: &Path, workspace_root: &Path) -> Result<usize> {
+    restore_from_manifest_with_force(manifest_path, workspace_root, false)
+}
+
+/// Restore files from a backup manifest, optionally overwriting later edits.
+///
+/// Each file's current hash is compared with the `after_hash` recorded when
+/// the operation finished. Unless `force` is set, any mismatch aborts the
+/// restore before a single file is written and reports the diverged files.
+/// Files the operation created are deleted; they count as restored.
+pub fn restore_from_manifest_with_force(
+    manifest_path: &Path,
+    workspace_root: &Path,
+    force: bool,
+) -> Result<usize> {
     let manifest = BackupManifest::load(manifest_path)?;
 
+    if !force {
+        let diverged: Vec<PathBuf> = manifest
+            .files
+            .iter()
+            .filter(|entry| {
+                let Some(after_hash) = &entry.after_hash else {
+                    return false;
+                };
+                // A deleted file has diverged as well
+                let current = fs::read(workspace_root.join(&entry.original_path))
+                    .map(|content| compute_hash(&content));
+                current.map_or(true, |hash| &hash != after_hash)
+            })
+            .map(|entry| entry.original_path.clone())
+            .collect();
+
+        if !diverged.is_empty() {
+            return Err(SpliceError::UndoDiverged { files: diverged });
+        }
+    }
+
     let mut restored = 0;
 
     for entry in &manifest.files {
         let original_path = workspace_root.join(&entry.original_path);
-        let backup_path = manifest.backup_dir.join(&entry.original_path);
+        let Some(hash) = &entry.hash else {
+            match fs::remove_file(&original_path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(SpliceError::Io {
+                        path: original_path,
+                        source: e,
+                    })
+                }
+            }
+            restored += 1;
+            continue;
+        };
+        // Manifests written before the object store keep a copy per operation
+        let backup_path = Some(blob_path(&manifest.backup_dir, hash))
+            .filter(|path| path.exists())
+            .unwrap_or_else(|| manifest.backup_dir.join(&entry.original_path));
 
         // Verify backup file exists
         if !backup_path.exists() {
@@ -220,11 +322,11 @@ pub fn restore_from_manifest(manifest_path: &Path, workspace_root: &Path) -> Res
 
         // Verify hash matches
         let actual_hash = compute_hash(&content);
-        if actual_hash != entry.hash {
+        if &actual_hash != hash {
             return Err(SpliceError::Other(format!(
                 "Hash mismatch for {}: expected {}, got {}",
                 entry.original_path.display(),
-                entry.hash,
+                hash,
                 actual_hash
             )));
         }
@@ -249,6 +351,44 @@ pub fn restore_from_manifest(manifest_path: &Path, workspace_root: &Path) -> Res
     Ok(restored)
 }
 
+/// Path of the blob holding contents with `hash`, in the object store shared by
+/// every operation directory next to `backup_dir`.
+fn blob_path(backup_dir: &Path, hash: &str) -> PathBuf {
+    let (prefix, rest) = hash.split_at(2.min(hash.len()));
+    backup_dir
+        .parent()
+        .unwrap_or(backup_dir)
+        .join("objects")
+        .join(prefix)
+        .join(rest)
+}
+
+/// Write a blob through a temporary file, so a crash never leaves a partial
+/// blob under a valid hash.
+fn write_blob(blob_path: &Path, content: &[u8]) -> Result<()> {
+    let parent = blob_path
+        .parent()
+        .ok_or_else(|| SpliceError::Other("Blob path has no parent directory".to_string()))?;
+    fs::create_dir_all(parent).map_err(|e| SpliceError::Io {
+        path: parent.to_path_buf(),
+        source: e,
+    })?;
+
+    let mut temp = tempfile::NamedTempFile::new_in(parent).map_err(|e| SpliceError::Io {
+        path: parent.to_path_buf(),
+        source: e,
+    })?;
+    temp.write_all(content).map_err(|e| SpliceError::Io {
+        path: temp.path().to_path_buf(),
+        source: e,
+    })?;
+    temp.persist(blob_path).map_err(|e| SpliceError::Io {
+        path: blob_path.to_path_buf(),
+        source: e.error,
+    })?;
+    Ok(())
+}
+
 /// Compute SHA-256 hash of bytes.
 fn compute_hash(bytes: &[u8]) -> String {
     let mut hasher = Sha256::new();
@@ -284,9 +424,11 @@ mod tests {
         // Verify manifest exists
         assert!(manifest_path.exists(), "Manifest file should exist");
 
-        // Verify backup file exists
-        let backup_file = workspace_root
-            .join(".splice-backup/test-op-123/test.txt");
+        // Verify the contents were stored under their hash
+        let backup_file = blob_path(
+            &workspace_root.join(".splice-backup/test-op-123"),
+            &compute_hash(b"hello world"),
+        );
         assert!(backup_file.exists(), "Backup file should exist");
 
         // Verify content matches
@@ -348,7 +490,10 @@ mod tests {
         let manifest_path = writer.finalize().expect("Failed to finalize backup");
 
         // Tamper with the backup file
-        let backup_file = workspace_root.join(".splice-backup/hash-test/test.txt");
+        let backup_file = blob_path(
+            &workspace_root.join(".splice-backup/hash-test"),
+            &compute_hash(b"original"),
+        );
         fs::write(&backup_file, b"tampered").expect("Failed to tamper with backup");
 
         // Attempt to restore should fail due to hash mismatch
@@ -365,6 +510,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_restore_refuses_files_edited_after_operation() {
+        let workspace = TempDir::new().expect("Failed to create temp dir");
+        let workspace_root = workspace.path();
+
+        let test_file = workspace_root.join("test.txt");
+        fs::write(&test_file, b"original").expect("Failed to write test file");
+
+        let mut writer = BackupWriter::new(workspace_root, Some("diverged".to_string()))
+            .expect("Failed to create BackupWriter");
+        writer.backup_file(&test_file).expect("Failed to backup file");
+        let manifest_path = writer.finalize().expect("Failed to finalize backup");
+
+        // The operation writes its result, then the user keeps editing
+        fs::write(&test_file, b"patched").expect("Failed to patch file");
+        record_after_hashes(&manifest_path, workspace_root).expect("Failed to record hashes");
+        fs::write(&test_file, b"patched and edited").expect("Failed to edit file");
+
+        match restore_from_manifest(&manifest_path, workspace_root) {
+            Err(SpliceError::UndoDiverged { files }) => {
+                assert_eq!(files, vec![PathBuf::from("test.txt")]);
+            }
+            other => panic!("Expected UndoDiverged, got: {:?}", other),
+        }
+        assert_eq!(fs::read_to_string(&test_file).unwrap(), "patched and edited");
+
+        let restored = restore_from_manifest_with_force(&manifest_path, workspace_root, true)
+            .expect("Forced restore should succeed");
+        assert_eq!(restored, 1);
+        assert_eq!(fs::read_to_string(&test_file).unwrap(), "original");
+    }
+
+    #[test]
+    fn test_restore_deletes_created_files() {
+        let workspace = TempDir::new().expect("Failed to create temp dir");
+        let workspace_root = workspace.path();
+        let created = workspace_root.join("new.rs");
+
+        let mut writer = BackupWriter::new(workspace_root, Some("created".to_string()))
+            .expect("Failed to create BackupWriter");
+        writer
+            .record_new_file(&created)
+            .expect("Failed to record new file");
+        let manifest_path = writer.finalize().expect("Failed to finalize backup");
+
+        fs::write(&created, b"fn new() {}").expect("Failed to create file");
+        record_after_hashes(&manifest_path, workspace_root).expect("Failed to record hashes");
+
+        let manifest = BackupManifest::load(&manifest_path).expect("Failed to load manifest");
+        assert_eq!(manifest.files[0].hash, None);
+
+        let restored =
+            restore_from_manifest(&manifest_path, workspace_root).expect("Failed to restore");
+        assert_eq!(restored, 1);
+        assert!(!created.exists(), "Created file should be deleted");
+    }
+
     #[test]
     fn test_backup_with_subdirectories() {
         let workspace = TempDir::new().expect("Failed to create temp dir");
@@ -387,9 +589,9 @@ mod tests {
 
         let manifest_path = writer.finalize().expect("Failed to finalize backup");
 
-        // Verify backup preserves directory structure
-        let backup_file = workspace_root.join(".splice-backup/subdir-test/src/lib.rs");
-        assert!(backup_file.exists(), "Backup should preserve directory structure");
+        // The manifest keeps the directory structure; the blob store does not need to
+        let manifest = BackupManifest::load(&manifest_path).expect("Failed to load manifest");
+        assert_eq!(manifest.files[0].original_path, PathBuf::from("src/lib.rs"));
 
         // Verify can be restored
         fs::write(&test_file, b"modified").expect("Failed to modify");
@@ -401,6 +603,64 @@ mod tests {
         assert_eq!(content, "fn main() {}");
     }
 
+    #[test]
+    fn test_unchanged_file_is_stored_once_across_operations() {
+        let workspace = TempDir::new().expect("Failed to create temp dir");
+        let workspace_root = workspace.path();
+
+        let edited = workspace_root.join("lib.rs");
+        let neighbor = workspace_root.join("neighbor.rs");
+        fs::write(&edited, b"fn one() {}").expect("Failed to write lib.rs");
+        fs::write(&neighbor, b"fn neighbor() {}").expect("Failed to write neighbor.rs");
+
+        let mut manifests = Vec::new();
+        for (op_id, next) in [("op-1", "fn two() {}"), ("op-2", "fn three() {}")] {
+            let mut writer = BackupWriter::new(workspace_root, Some(op_id.to_string()))
+                .expect("Failed to create BackupWriter");
+            writer.backup_file(&edited).expect("Failed to backup lib.rs");
+            writer.backup_file(&neighbor).expect("Failed to backup neighbor.rs");
+            manifests.push(writer.finalize().expect("Failed to finalize backup"));
+            fs::write(&edited, next).expect("Failed to edit lib.rs");
+        }
+
+        // Two versions of lib.rs, one shared copy of neighbor.rs
+        let objects = workspace_root.join(".splice-backup/objects");
+        let blobs: usize = fs::read_dir(&objects)
+            .unwrap()
+            .map(|dir| fs::read_dir(dir.unwrap().path()).unwrap().count())
+            .sum();
+        assert_eq!(blobs, 3);
+        let neighbor_hash = compute_hash(b"fn neighbor() {}");
+        for manifest_path in &manifests {
+            let manifest = BackupManifest::load(manifest_path).unwrap();
+            assert_eq!(manifest.files[1].hash.as_ref(), Some(&neighbor_hash));
+        }
+
+        // Either operation restores from the shared blobs
+        fs::write(&neighbor, b"changed").expect("Failed to edit neighbor.rs");
+        restore_from_manifest(&manifests[0], workspace_root).expect("Failed to restore");
+        assert_eq!(fs::read_to_string(&edited).unwrap(), "fn one() {}");
+        assert_eq!(fs::read_to_string(&neighbor).unwrap(), "fn neighbor() {}");
+    }
+
+    #[test]
+    fn test_restore_reads_per_operation_copies_from_older_manifests() {
+        let workspace = TempDir::new().expect("Failed to create temp dir");
+        let workspace_root = workspace.path();
+        let backup_dir = workspace_root.join(".splice-backup/legacy");
+        fs::create_dir_all(backup_dir.join("src")).expect("Failed to create backup dir");
+        fs::write(backup_dir.join("src/lib.rs"), b"old").expect("Failed to write copy");
+
+        let mut manifest = BackupManifest::new("legacy".to_string(), backup_dir.clone());
+        manifest.add_file(PathBuf::from("src/lib.rs"), compute_hash(b"old"), 3);
+        manifest.save().expect("Failed to save manifest");
+
+        let restored = restore_from_manifest(&backup_dir.join("manifest.json"), workspace_root)
+            .expect("Failed to restore");
+        assert_eq!(restored, 1);
+        assert_eq!(fs::read_to_string(workspace_root.join("src/lib.rs")).unwrap(), "old");
+    }
+
     #[test]
     fn test_manifest_save_and_load() {
         let workspace = TempDir::new().expect("Failed to create temp dir");
@@ -430,7 +690,7 @@ mod tests {
         assert_eq!(loaded.operation_id, "test-manifest");
         assert_eq!(loaded.files.len(), 1);
         assert_eq!(loaded.files[0].original_path, PathBuf::from("src/lib.rs"));
-        assert_eq!(loaded.files[0].hash, "abc123");
+        assert_eq!(loaded.files[0].hash.as_deref(), Some("abc123"));
         assert_eq!(loaded.files[0].size, 1024);
     }
 }
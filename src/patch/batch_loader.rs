@@ -1,20 +1,24 @@
-use super::{SpanBatch, SpanReplacement};
+use super::{NewFile, SpanBatch, SpanReplacement};
 use crate::error::{Result, SpliceError};
+use schemars::JsonSchema;
 use serde::Deserialize;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 struct BatchSpec {
     batches: Vec<BatchEntry>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 struct BatchEntry {
+    #[serde(default)]
     replacements: Vec<ReplacementSpec>,
+    #[serde(default)]
+    new_files: Vec<NewFileSpec>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 struct ReplacementSpec {
     file: String,
     start: usize,
@@ -25,6 +29,20 @@ struct ReplacementSpec {
     with_file: Option<String>,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+struct NewFileSpec {
+    file: String,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(rename = "with", default)]
+    with_file: Option<String>,
+}
+
+/// JSON Schema of the manifest read by `load_batches_from_file`.
+pub fn batch_schema() -> schemars::schema::RootSchema {
+    schemars::schema_for!(BatchSpec)
+}
+
 /// Load span batches from a JSON manifest.
 pub fn load_batches_from_file(batch_path: &Path) -> Result<Vec<SpanBatch>> {
     let contents = fs::read_to_string(batch_path)?;
@@ -46,7 +64,7 @@ pub fn load_batches_from_file(batch_path: &Path) -> Result<Vec<SpanBatch>> {
 
     let mut batches = Vec::with_capacity(spec.batches.len());
     for (index, batch) in spec.batches.into_iter().enumerate() {
-        if batch.replacements.is_empty() {
+        if batch.replacements.is_empty() && batch.new_files.is_empty() {
             return Err(SpliceError::InvalidBatchSchema {
                 message: format!("Batch {} contains no replacements", index + 1),
             });
@@ -54,15 +72,18 @@ pub fn load_batches_from_file(batch_path: &Path) -> Result<Vec<SpanBatch>> {
 
         let mut replacements = Vec::with_capacity(batch.replacements.len());
         for (replacement_idx, replacement) in batch.replacements.into_iter().enumerate() {
-            let content = resolve_content(&base_dir, &replacement).map_err(|message| {
-                SpliceError::InvalidBatchSchema {
-                    message: format!(
-                        "Batch {} replacement {}: {}",
-                        index + 1,
-                        replacement_idx + 1,
-                        message
-                    ),
-                }
+            let content = resolve_content(
+                &base_dir,
+                replacement.content.as_deref(),
+                replacement.with_file.as_deref(),
+            )
+            .map_err(|message| SpliceError::InvalidBatchSchema {
+                message: format!(
+                    "Batch {} replacement {}: {}",
+                    index + 1,
+                    replacement_idx + 1,
+                    message
+                ),
             })?;
 
             let file_path = resolve_path(&base_dir, &replacement.file);
@@ -75,7 +96,28 @@ pub fn load_batches_from_file(batch_path: &Path) -> Result<Vec<SpanBatch>> {
             ));
         }
 
-        batches.push(SpanBatch::new(replacements));
+        let mut span_batch = SpanBatch::new(replacements);
+        for (new_file_idx, new_file) in batch.new_files.into_iter().enumerate() {
+            let content = resolve_content(
+                &base_dir,
+                new_file.content.as_deref(),
+                new_file.with_file.as_deref(),
+            )
+            .map_err(|message| SpliceError::InvalidBatchSchema {
+                message: format!(
+                    "Batch {} new file {}: {}",
+                    index + 1,
+                    new_file_idx + 1,
+                    message
+                ),
+            })?;
+            span_batch.push_new_file(NewFile::new(
+                resolve_path(&base_dir, &new_file.file),
+                content,
+            ));
+        }
+
+        batches.push(span_batch);
     }
 
     Ok(batches)
@@ -90,8 +132,12 @@ fn resolve_path(base_dir: &Path, value: &str) -> PathBuf {
     }
 }
 
-fn resolve_content(base_dir: &Path, spec: &ReplacementSpec) -> std::result::Result<String, String> {
-    match (&spec.content, &spec.with_file) {
+fn resolve_content(
+    base_dir: &Path,
+    content: Option<&str>,
+    with_file: Option<&str>,
+) -> std::result::Result<String, String> {
+    match (content, with_file) {
         (Some(inline), None) => Ok(inline.to_string()),
         (None, Some(with_file)) => {
             let path = resolve_path(base_dir, with_file);
@@ -12,21 +12,31 @@ mod backup;
 mod batch_loader;
 mod pattern;
 
-use crate::error::{Diagnostic, DiagnosticLevel, Result, SpliceError};
+use crate::error::{utf8_path, Diagnostic, DiagnosticLevel, Result, SpliceError};
+use crate::ingest::grammar::parser_for;
+use crate::ingest::source::{grammar_for, SourceFile};
+use crate::ingest::{oversized_file, ParseOptions, DEFAULT_MAX_FILE_SIZE};
 use crate::symbol::Language as SymbolLanguage;
-use crate::validate::{self, AnalyzerMode};
+use crate::validate::gates::ValidationOutcome;
+use crate::validate::{self, AnalyzerMode, AnalyzerScope, CargoFeatures};
+use rayon::prelude::*;
 use ropey::Rope;
 use serde::Serialize;
 use sha2::{Digest, Sha256};
-use std::collections::BTreeMap;
-use std::ffi::OsStr;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::{OsStr, OsString};
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use tempfile::TempDir;
 
-pub use backup::{restore_from_manifest, BackupWriter, BackupManifest};
-pub use batch_loader::load_batches_from_file;
+pub use backup::{
+    record_after_hashes, restore_from_manifest, restore_from_manifest_with_force, BackupManifest,
+    BackupWriter,
+};
+pub use batch_loader::{batch_schema, load_batches_from_file};
 pub use pattern::{find_pattern_in_files, apply_pattern_replace, PatternReplaceConfig, PatternReplaceResult};
 
 /// Replacement to apply within a specific file.
@@ -54,16 +64,47 @@ impl SpanReplacement {
     }
 }
 
+/// File created by a batch, alongside its span replacements.
+#[derive(Debug, Clone)]
+pub struct NewFile {
+    /// Path of the file to create. It must not exist yet.
+    pub path: PathBuf,
+    /// Full contents of the new file.
+    pub content: String,
+}
+
+impl NewFile {
+    /// Create a new file entry.
+    pub fn new(path: PathBuf, content: String) -> Self {
+        Self { path, content }
+    }
+}
+
 /// Collection of replacements that must succeed atomically.
+///
+/// Every replacement's offsets refer to the file as it was before the batch;
+/// no edit sees another's output. Replacements in a file are applied in
+/// `(start, end)` order, ties keeping the order they were pushed in, so:
+///
+/// - zero-width insertions at the same offset appear in push order;
+/// - an insertion at the offset where a replacement starts lands before the
+///   replacement's content, and one where it ends lands after it;
+/// - abutting replacements (`a..b` and `b..c`) are both applied, side by side.
+///
+/// Replacements that share any bytes are rejected as overlapping.
 #[derive(Debug, Clone)]
 pub struct SpanBatch {
     replacements: Vec<SpanReplacement>,
+    new_files: Vec<NewFile>,
 }
 
 impl SpanBatch {
     /// Create a batch from raw replacements.
     pub fn new(replacements: Vec<SpanReplacement>) -> Self {
-        Self { replacements }
+        Self {
+            replacements,
+            new_files: Vec::new(),
+        }
     }
 
     /// Borrow the replacements for inspection.
@@ -76,9 +117,22 @@ impl SpanBatch {
         self.replacements.push(replacement);
     }
 
+    /// Borrow the files this batch creates.
+    pub fn new_files(&self) -> &[NewFile] {
+        &self.new_files
+    }
+
+    /// Add a file to create with the batch.
+    ///
+    /// New files are validated with the edited ones and deleted again if
+    /// the batch is rolled back.
+    pub fn push_new_file(&mut self, new_file: NewFile) {
+        self.new_files.push(new_file);
+    }
+
     /// Returns true when the batch contains no work.
     pub fn is_empty(&self) -> bool {
-        self.replacements.is_empty()
+        self.replacements.is_empty() && self.new_files.is_empty()
     }
 }
 
@@ -112,6 +166,161 @@ pub struct PreviewReport {
     pub bytes_removed: usize,
 }
 
+/// Which validation gates run after a patch is written.
+///
+/// The default runs every gate. The analyzer gate also needs an
+/// `AnalyzerMode` other than `Off` to do anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GateSet {
+    /// Tree-sitter reparse of each patched file.
+    pub tree_sitter: bool,
+    /// Language compiler (`cargo check` for Rust).
+    pub compiler: bool,
+    /// rust-analyzer (Rust only).
+    pub analyzer: bool,
+}
+
+impl Default for GateSet {
+    fn default() -> Self {
+        Self {
+            tree_sitter: true,
+            compiler: true,
+            analyzer: true,
+        }
+    }
+}
+
+/// Extra configuration for the validation gates.
+///
+/// `Default` reproduces the gates as run by the plain `*_with_validation` entry points.
+#[derive(Debug, Clone)]
+pub struct ValidationOptions {
+    /// Feature selection forwarded to `cargo check` (Rust only).
+    pub cargo_features: CargoFeatures,
+
+    /// Patch targets larger than this many bytes are refused.
+    pub max_file_size: u64,
+
+    /// Limits for the tree-sitter reparse gate.
+    pub parse: ParseOptions,
+
+    /// Validate C/C++ batches as translation units with the workspace and
+    /// patched header directories on the include path, instead of checking
+    /// each file in isolation.
+    pub include_aware: bool,
+
+    /// Patch even when a span boundary falls inside a string literal or comment.
+    pub allow_literal_spans: bool,
+
+    /// Gates to run; skipped gates are treated as passing.
+    pub gates: GateSet,
+
+    /// `CARGO_TARGET_DIR` for the cargo check gate; `None` uses cargo's default.
+    ///
+    /// Previews run in a throwaway copy of the workspace with no `target/`, so
+    /// pointing this at a persistent directory lets them reuse compiled
+    /// dependencies instead of building from scratch every time.
+    pub cargo_target_dir: Option<PathBuf>,
+
+    /// Narrow the cargo check gate to the package and target owning the
+    /// patched files (`--manifest-path <package> --lib` or `--bins`).
+    ///
+    /// Much faster in large workspaces, but code outside that target is not
+    /// rechecked: other crates, binaries or tests depending on a changed
+    /// signature can still break without the gate noticing.
+    pub fast_check: bool,
+
+    /// Run the rust-analyzer gate in each package owning a patched file
+    /// instead of over the whole workspace.
+    ///
+    /// Workspace mode stays the default because it also catches breakage in
+    /// other members; the scoped run trades that for speed.
+    pub scoped_analyzer: bool,
+
+    /// Manifest of the package to validate, overriding the upward search for
+    /// `Cargo.toml`.
+    ///
+    /// The Rust gates run in its directory, for layouts where the manifest
+    /// does not sit above the patched sources.
+    pub manifest_path: Option<PathBuf>,
+
+    /// Run the language's formatter (`rustfmt`, `black`, `prettier`) over
+    /// each patched file before the gates, so they validate what is kept.
+    ///
+    /// The whole file is formatted, not just the patched span. A formatter
+    /// that is not installed is skipped.
+    pub format_after: bool,
+
+    /// Treat a replacement as a no-op when it has the same tokens as the
+    /// span it replaces, ignoring whitespace between them.
+    ///
+    /// A replacement identical byte for byte is always a no-op. Python and
+    /// Haskell, where indentation is syntax, are only compared byte for byte.
+    pub normalize_whitespace: bool,
+
+    /// Run the per-file gates of a batch (tree-sitter reparse, and compiler
+    /// checks that validate one file at a time) concurrently on this many
+    /// threads; `Some(0)` means one per CPU and `None` runs them in order.
+    ///
+    /// Gates covering the whole batch, such as cargo check, still run once
+    /// after them. Failures are reported as in a serial run.
+    pub parallel_validate: Option<usize>,
+
+    /// Receives the warnings reported by gates that passed.
+    ///
+    /// Clones of the options share the same sink, so the caller can read back
+    /// what a run reported once it succeeds.
+    pub warnings: GateWarnings,
+}
+
+/// Warning-level diagnostics collected from passing validation gates.
+#[derive(Debug, Clone, Default)]
+pub struct GateWarnings(Arc<Mutex<Vec<Diagnostic>>>);
+
+impl GateWarnings {
+    fn extend(&self, warnings: impl IntoIterator<Item = Diagnostic>) {
+        let mut collected = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        for warning in warnings {
+            // A package checked in several scopes reports the same warning each time
+            let duplicate = collected.iter().any(|seen| {
+                seen.file == warning.file
+                    && seen.line == warning.line
+                    && seen.column == warning.column
+                    && seen.message == warning.message
+            });
+            if !duplicate {
+                collected.push(warning);
+            }
+        }
+    }
+
+    /// Remove and return the warnings collected so far.
+    pub fn take(&self) -> Vec<Diagnostic> {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        Self {
+            cargo_features: CargoFeatures::default(),
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            parse: ParseOptions::default(),
+            include_aware: false,
+            allow_literal_spans: false,
+            gates: GateSet::default(),
+            cargo_target_dir: None,
+            fast_check: false,
+            scoped_analyzer: false,
+            manifest_path: None,
+            format_after: false,
+            normalize_whitespace: false,
+            parallel_validate: None,
+            warnings: GateWarnings::default(),
+        }
+    }
+}
+
 /// Apply a patch with full validation gates.
 ///
 /// This function:
@@ -144,7 +353,35 @@ pub fn apply_patch_with_validation(
     language: SymbolLanguage,
     analyzer_mode: AnalyzerMode,
 ) -> Result<(String, String)> {
-    // Step 1: Read original file and compute hash
+    apply_patch_with_options(
+        file_path,
+        start,
+        end,
+        new_content,
+        workspace_dir,
+        language,
+        analyzer_mode,
+        &ValidationOptions::default(),
+    )
+}
+
+/// Apply a patch with full validation gates, using explicit gate options.
+///
+/// Behaves like [`apply_patch_with_validation`] but forwards `options` to the gates
+/// (for example, the cargo feature set used by the compiler gate).
+#[allow(clippy::too_many_arguments)]
+pub fn apply_patch_with_options(
+    file_path: &Path,
+    start: usize,
+    end: usize,
+    new_content: &str,
+    workspace_dir: &Path,
+    language: SymbolLanguage,
+    analyzer_mode: AnalyzerMode,
+    options: &ValidationOptions,
+) -> Result<(String, String)> {
+    // Step 1: Refuse oversized targets, then read original file and compute hash
+    ensure_within_size_limit(file_path, options.max_file_size)?;
     let original = std::fs::read(file_path)?;
     let before_hash = compute_hash(&original);
 
@@ -163,6 +400,19 @@ pub fn apply_patch_with_validation(
         start,
         end,
     })?;
+    if !options.allow_literal_spans {
+        let source = SourceFile::from_bytes(file_path, language, original.clone())
+            .with_parse_options(options.parse);
+        ensure_span_outside_literals(&source, start, end)?;
+    }
+
+    // A replacement equal to the span changes nothing, so skip the write and the gates
+    let span = std::str::from_utf8(&original[start..end])?;
+    if span == new_content
+        || (options.normalize_whitespace && same_tokens(span, new_content, language))
+    {
+        return Ok((before_hash.clone(), before_hash));
+    }
 
     // Step 4: Apply byte-exact replacement using ropey
     let mut rope = Rope::from_str(std::str::from_utf8(&original)?);
@@ -178,18 +428,29 @@ pub fn apply_patch_with_validation(
     let patched_bytes = patched_content.into_bytes();
     write_atomic(file_path, &patched_bytes, "patch")?;
 
+    // Step 6: Optionally reformat before the gates see the file
+    if options.format_after {
+        format_file(file_path, language);
+    }
+
     // Step 7: Run validation gates
-    match run_validation_gates(file_path, workspace_dir, language, analyzer_mode) {
+    match run_validation_gates(file_path, workspace_dir, language, analyzer_mode, options) {
         Ok(_) => {}
         Err(e) => {
             log::warn!("Validation failed, rolling back patch: {:?}", e);
 
-            if let Err(rollback_err) = write_atomic(file_path, &original, "rollback") {
+            let restored = write_atomic(file_path, &original, "rollback")
+                .and_then(|()| verify_restored(file_path, &before_hash));
+            if let Err(rollback_err) = restored {
                 log::error!(
                     "Failed to restore {} during rollback: {}",
                     file_path.display(),
                     rollback_err
                 );
+                return Err(SpliceError::RollbackIncomplete {
+                    files: vec![file_path.to_path_buf()],
+                    cause: Box::new(e),
+                });
             }
             return Err(e);
         }
@@ -207,17 +468,39 @@ pub fn apply_patch_with_validation(
 /// All replacements are made durable before running validation gates. Any tree-sitter,
 /// compiler, or analyzer failure restores every file to its original bytes before returning
 /// the error.
+///
+/// Every file is reparsed before any compiler runs; a syntax error fails with
+/// `ParseValidationFailed` naming the broken file (and any others that also fail).
+/// Rust batches then run cargo check once for the whole batch, not per file.
 pub fn apply_batch_with_validation(
     batches: &[SpanBatch],
     workspace_dir: &Path,
     language: SymbolLanguage,
     analyzer_mode: AnalyzerMode,
+) -> Result<Vec<FilePatchSummary>> {
+    apply_batch_with_options(
+        batches,
+        workspace_dir,
+        language,
+        analyzer_mode,
+        &ValidationOptions::default(),
+    )
+}
+
+/// Apply multiple span replacements atomically, using explicit gate options.
+pub fn apply_batch_with_options(
+    batches: &[SpanBatch],
+    workspace_dir: &Path,
+    language: SymbolLanguage,
+    analyzer_mode: AnalyzerMode,
+    options: &ValidationOptions,
 ) -> Result<Vec<FilePatchSummary>> {
     if batches.is_empty() {
         return Ok(Vec::new());
     }
 
     let mut grouped: BTreeMap<PathBuf, Vec<SpanReplacement>> = BTreeMap::new();
+    let mut new_files: BTreeMap<PathBuf, &str> = BTreeMap::new();
     for batch in batches {
         for replacement in batch.replacements() {
             grouped
@@ -225,6 +508,30 @@ pub fn apply_batch_with_validation(
                 .or_default()
                 .push(replacement.clone());
         }
+        for new_file in batch.new_files() {
+            if new_files
+                .insert(new_file.path.clone(), &new_file.content)
+                .is_some()
+            {
+                return Err(SpliceError::Other(format!(
+                    "Batch creates {} more than once",
+                    new_file.path.display()
+                )));
+            }
+        }
+    }
+
+    // Refuse oversized targets and clobbering creations before anything is written
+    for file_path in grouped.keys() {
+        ensure_within_size_limit(file_path, options.max_file_size)?;
+    }
+    for file_path in new_files.keys() {
+        if file_path.exists() || grouped.contains_key(file_path) {
+            return Err(SpliceError::Other(format!(
+                "Cannot create {}: the file already exists or is also edited by the batch",
+                file_path.display()
+            )));
+        }
     }
 
     let mut applied = Vec::new();
@@ -234,15 +541,24 @@ pub fn apply_batch_with_validation(
             continue;
         }
 
-        replacements.sort_by_key(|r| std::cmp::Reverse(r.start));
+        sort_replacements(&mut replacements);
         let (original, before_hash) = read_with_hash(&file_path)?;
         validate_replacements(&file_path, &replacements, &original)?;
+        if !options.allow_literal_spans {
+            let source = SourceFile::from_bytes(&file_path, language, original.clone())
+                .with_parse_options(options.parse);
+            let outside = replacements
+                .iter()
+                .try_for_each(|r| ensure_span_outside_literals(&source, r.start, r.end));
+            if let Err(err) = outside {
+                return Err(rollback_after(&applied, err));
+            }
+        }
         let patched_bytes = apply_replacements(&original, &replacements)?;
         let after_hash = compute_hash(&patched_bytes);
 
         if let Err(write_err) = write_atomic(&file_path, &patched_bytes, "batch") {
-            rollback_files(&applied);
-            return Err(write_err);
+            return Err(rollback_after(&applied, write_err));
         }
 
         applied.push(AppliedFile {
@@ -250,13 +566,37 @@ pub fn apply_batch_with_validation(
             original,
             before_hash,
             after_hash,
+            created_dirs: None,
         });
     }
 
-    let validation = run_batch_validations(&applied, workspace_dir, language, analyzer_mode);
+    for (file_path, content) in new_files {
+        match create_file(&file_path, content.as_bytes()) {
+            Ok(created_dirs) => applied.push(AppliedFile {
+                file: file_path,
+                original: Vec::new(),
+                before_hash: compute_hash(&[]),
+                after_hash: compute_hash(content.as_bytes()),
+                created_dirs: Some(created_dirs),
+            }),
+            Err(err) => return Err(rollback_after(&applied, err)),
+        }
+    }
+
+    if options.format_after {
+        for file in &mut applied {
+            format_file(&file.file, language);
+            match read_with_hash(&file.file) {
+                Ok((_, after_hash)) => file.after_hash = after_hash,
+                Err(err) => return Err(rollback_after(&applied, err)),
+            }
+        }
+    }
+
+    let validation =
+        run_batch_validations(&applied, workspace_dir, language, analyzer_mode, options);
     if let Err(err) = validation {
-        rollback_files(&applied);
-        return Err(err);
+        return Err(rollback_after(&applied, err));
     }
 
     Ok(applied
@@ -279,13 +619,40 @@ pub fn preview_patch(
     language: SymbolLanguage,
     analyzer_mode: AnalyzerMode,
 ) -> Result<(FilePatchSummary, PreviewReport)> {
+    preview_patch_with_options(
+        file_path,
+        start,
+        end,
+        new_content,
+        workspace_root,
+        language,
+        analyzer_mode,
+        &ValidationOptions::default(),
+    )
+}
+
+/// Preview a patch in a cloned workspace, using explicit gate options.
+#[allow(clippy::too_many_arguments)]
+pub fn preview_patch_with_options(
+    file_path: &Path,
+    start: usize,
+    end: usize,
+    new_content: &str,
+    workspace_root: &Path,
+    language: SymbolLanguage,
+    analyzer_mode: AnalyzerMode,
+    options: &ValidationOptions,
+) -> Result<(FilePatchSummary, PreviewReport)> {
+    if let Some(target_dir) = &options.cargo_target_dir {
+        ensure_separate_target_dir(target_dir, workspace_root)?;
+    }
     let preview_workspace = clone_workspace_for_preview(workspace_root)?;
     let relative = file_path
         .strip_prefix(workspace_root)
         .map_err(|_| SpliceError::Other("File not under workspace root".to_string()))?;
     let preview_file = preview_workspace.path().join(relative);
 
-    let (before_hash, after_hash) = apply_patch_with_validation(
+    let (before_hash, after_hash) = apply_patch_with_options(
         &preview_file,
         start,
         end,
@@ -293,6 +660,7 @@ pub fn preview_patch(
         preview_workspace.path(),
         language,
         analyzer_mode,
+        options,
     )?;
 
     let preview_report = compute_preview_report(file_path, start, end, new_content)?;
@@ -307,9 +675,41 @@ pub fn preview_patch(
     ))
 }
 
-/// Run all validation gates in sequence.
+/// Refuse a preview target directory that is the workspace's own `target/`.
 ///
-/// Gates are executed in order:
+/// Preview builds compile a modified copy of the sources; sharing cargo's
+/// real target directory would leave those artifacts behind for the next
+/// genuine build.
+fn ensure_separate_target_dir(target_dir: &Path, workspace_root: &Path) -> Result<()> {
+    let real_target = workspace_root.join("target");
+    let same = match (fs::canonicalize(target_dir), fs::canonicalize(&real_target)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => target_dir == real_target,
+    };
+    if same {
+        return Err(SpliceError::Other(format!(
+            "Preview target directory {} is the workspace's own target directory; use a separate cache directory",
+            target_dir.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Error if `file_path` is larger than `max_file_size` bytes.
+fn ensure_within_size_limit(file_path: &Path, max_file_size: u64) -> Result<()> {
+    match oversized_file(file_path, max_file_size) {
+        Some(size) => Err(SpliceError::FileTooLarge {
+            file: file_path.to_path_buf(),
+            size,
+            limit: max_file_size,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Run the selected validation gates in sequence.
+///
+/// Gates are executed in order, each only if enabled in `options.gates`:
 /// 1. Tree-sitter reparse (syntax validation, language-specific)
 /// 2. Compiler validation (language-specific)
 /// 3. rust-analyzer (optional, Rust only)
@@ -320,17 +720,29 @@ fn run_validation_gates(
     workspace_dir: &Path,
     language: SymbolLanguage,
     analyzer_mode: AnalyzerMode,
+    options: &ValidationOptions,
 ) -> Result<()> {
+    let gates = options.gates;
+
     // Gate 1: Tree-sitter reparse (language-specific)
-    gate_tree_sitter_reparse(file_path, language)?;
+    if gates.tree_sitter {
+        let file = SourceFile::read(file_path, language)?.with_parse_options(options.parse);
+        gate_tree_sitter_reparse(&file)?;
+    }
 
     // Gate 2: Compiler validation (language-specific)
-    gate_compiler_validation(file_path, workspace_dir, language)?;
+    if gates.compiler {
+        gate_compiler_validation(file_path, workspace_dir, language, options)?;
+    }
 
     // Gate 3: rust-analyzer (Rust only, optional)
-    if language == SymbolLanguage::Rust {
-        use crate::validate::gate_rust_analyzer;
-        gate_rust_analyzer(workspace_dir, analyzer_mode)?;
+    if gates.analyzer && language == SymbolLanguage::Rust && analyzer_mode != AnalyzerMode::Off {
+        use crate::validate::gate_rust_analyzer_scoped;
+        let manifest = options.manifest_path.as_deref();
+        let gate_dir = rust_gate_dir(workspace_dir, options)?;
+        for scope in analyzer_scopes(&[file_path], options.scoped_analyzer, manifest)? {
+            gate_rust_analyzer_scoped(gate_dir, analyzer_mode, &scope)?;
+        }
     }
 
     Ok(())
@@ -340,33 +752,21 @@ fn run_validation_gates(
 ///
 /// Validates that the patched file can be parsed as valid syntax
 /// for the given programming language.
-fn gate_tree_sitter_reparse(file_path: &Path, language: SymbolLanguage) -> Result<()> {
-    let source = std::fs::read(file_path)?;
-
-    let mut parser = tree_sitter::Parser::new();
-    let tree_sitter_lang = get_tree_sitter_language(language);
-
-    parser
-        .set_language(&tree_sitter_lang)
-        .map_err(|e| SpliceError::Parse {
-            file: file_path.to_path_buf(),
-            message: format!("Failed to set language: {:?}", e),
-        })?;
-
-    let tree = parser
-        .parse(&source, None)
-        .ok_or_else(|| SpliceError::ParseValidationFailed {
-            file: file_path.to_path_buf(),
-            message: "Parse failed - no tree returned".to_string(),
-        })?;
+fn gate_tree_sitter_reparse(source: &SourceFile) -> Result<()> {
+    let tree = source.tree().map_err(|e| match e {
+        SpliceError::Parse { file, message } => {
+            SpliceError::ParseValidationFailed { file, message }
+        }
+        other => other,
+    })?;
 
     // Check for parse errors
     if tree.root_node().has_error() {
         return Err(SpliceError::ParseValidationFailed {
-            file: file_path.to_path_buf(),
+            file: source.path().to_path_buf(),
             message: format!(
                 "Tree-sitter detected syntax errors in patched {} file",
-                language.as_str()
+                source.language().as_str()
             ),
         });
     }
@@ -374,16 +774,88 @@ fn gate_tree_sitter_reparse(file_path: &Path, language: SymbolLanguage) -> Resul
     Ok(())
 }
 
-/// Get the appropriate tree-sitter language for the given SymbolLanguage.
-fn get_tree_sitter_language(language: SymbolLanguage) -> tree_sitter::Language {
-    match language {
-        SymbolLanguage::Rust => tree_sitter_rust::language(),
-        SymbolLanguage::Python => tree_sitter_python::language(),
-        SymbolLanguage::C => tree_sitter_c::language(),
-        SymbolLanguage::Cpp => tree_sitter_cpp::language(),
-        SymbolLanguage::Java => tree_sitter_java::language(),
-        SymbolLanguage::JavaScript => tree_sitter_javascript::language(),
-        SymbolLanguage::TypeScript => tree_sitter_typescript::language_typescript(),
+/// Node kinds treated as string literals or comments, across the supported grammars.
+const LITERAL_NODE_KINDS: &[&str] = &[
+    "string_literal",
+    "raw_string_literal",
+    "char_literal",
+    "string",
+    "template_string",
+    "line_comment",
+    "block_comment",
+    "comment",
+];
+
+/// Refuse a span whose start or end lies strictly inside a string literal or comment.
+///
+/// Such spans usually come from a stale index or a hand-written byte range; the
+/// patched file may still parse, but the edit rewrites text rather than code.
+fn ensure_span_outside_literals(source: &SourceFile, start: usize, end: usize) -> Result<()> {
+    let tree = source.tree()?;
+    for offset in [start, end] {
+        if let Some(literal) = enclosing_literal(tree, offset) {
+            return Err(SpliceError::SpanInsideLiteral {
+                file: source.path().to_path_buf(),
+                offset,
+                node_kind: literal.kind().to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// The string literal or comment that `offset` lies strictly inside, if any.
+fn enclosing_literal(tree: &tree_sitter::Tree, offset: usize) -> Option<tree_sitter::Node<'_>> {
+    let mut node = tree.root_node().descendant_for_byte_range(offset, offset);
+    while let Some(current) = node {
+        if current.start_byte() < offset
+            && offset < current.end_byte()
+            && LITERAL_NODE_KINDS.contains(&current.kind())
+        {
+            return Some(current);
+        }
+        node = current.parent();
+    }
+    None
+}
+
+/// Whether two snippets parse to the same token sequence, ignoring whitespace.
+///
+/// String literals and comments are compared whole, so whitespace inside them
+/// still counts. Snippets that do not parse cleanly, and languages where
+/// indentation is significant, never compare equal.
+fn same_tokens(a: &str, b: &str, language: SymbolLanguage) -> bool {
+    if matches!(language, SymbolLanguage::Python | SymbolLanguage::Haskell) {
+        return false;
+    }
+    let Ok(mut parser) = parser_for(language.as_str(), &grammar_for(language)) else {
+        return false;
+    };
+
+    let mut tokens = |text: &str| -> Option<Vec<String>> {
+        let tree = parser.parse(text, None)?;
+        if tree.root_node().has_error() {
+            return None;
+        }
+        let mut tokens = Vec::new();
+        let mut pending = vec![tree.root_node()];
+        while let Some(node) = pending.pop() {
+            let kind = node.kind();
+            if node.child_count() == 0 || kind.contains("string") || kind.contains("comment") {
+                tokens.push(text[node.byte_range()].trim_end().to_string());
+            } else {
+                let mut cursor = node.walk();
+                let children: Vec<_> = node.children(&mut cursor).collect();
+                pending.extend(children.into_iter().rev());
+            }
+        }
+        Some(tokens)
+    };
+
+    match (tokens(a), tokens(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
     }
 }
 
@@ -395,76 +867,177 @@ fn gate_compiler_validation(
     file_path: &Path,
     workspace_dir: &Path,
     language: SymbolLanguage,
+    options: &ValidationOptions,
 ) -> Result<()> {
     match language {
         SymbolLanguage::Rust => {
             // Rust: Use cargo check from workspace directory
-            gate_cargo_check(workspace_dir)?;
+            let manifest = options.manifest_path.as_deref();
+            let scopes = cargo_check_scopes(&[file_path], options.fast_check, manifest)?;
+            for scope in scopes {
+                gate_cargo_check(
+                    rust_gate_dir(workspace_dir, options)?,
+                    &scope,
+                    &options.cargo_features,
+                    options.cargo_target_dir.as_deref(),
+                    &options.warnings,
+                )?;
+            }
         }
         _ => {
             // Other languages: Use validate_file which auto-detects language
             use crate::validate::gates::validate_file;
 
-            let outcome = validate_file(file_path)?;
-            let tool_metadata = tool_invocation_for_language(language)
-                .map(|inv| validate::collect_tool_metadata(inv.binary, inv.version_args));
-
-            if !outcome.is_valid {
-                if !outcome.tool_available {
-                    // Tool not available is a soft failure - we can't validate
-                    // For now, we treat this as success but log a warning
-                    log::warn!(
-                        "Compiler validation tool not available for {}, skipping validation",
-                        language.as_str()
-                    );
-                    return Ok(());
-                }
-
-                // Tool is available but validation failed
-                let mut diagnostics = Vec::new();
-                let tool_name = format!("{}-compiler", language.as_str());
-
-                for err in outcome.errors {
-                    let remediation = err
-                        .code
-                        .as_deref()
-                        .and_then(validate::remediation_link_for_code);
-                    diagnostics.push(
-                        Diagnostic::new(&tool_name, DiagnosticLevel::Error, err.message)
-                            .with_file(file_for_diagnostic(&err.file, file_path))
-                            .with_position(nonzero(err.line), nonzero(err.column))
-                            .with_code(err.code.clone())
-                            .with_note(err.note.clone())
-                            .with_tool_metadata(tool_metadata.as_ref())
-                            .with_remediation(remediation),
-                    );
-                }
+            check_compiler_outcome(validate_file(file_path)?, file_path, language)?;
+        }
+    }
 
-                for warn in outcome.warnings {
-                    let remediation = warn
-                        .code
-                        .as_deref()
-                        .and_then(validate::remediation_link_for_code);
-                    diagnostics.push(
-                        Diagnostic::new(&tool_name, DiagnosticLevel::Warning, warn.message)
-                            .with_file(file_for_diagnostic(&warn.file, file_path))
-                            .with_position(nonzero(warn.line), nonzero(warn.column))
-                            .with_code(warn.code.clone())
-                            .with_note(warn.note.clone())
-                            .with_tool_metadata(tool_metadata.as_ref())
-                            .with_remediation(remediation),
-                    );
-                }
+    Ok(())
+}
 
-                return Err(SpliceError::CompilerValidationFailed {
-                    file: file_path.to_path_buf(),
-                    language: language.as_str().to_string(),
-                    diagnostics,
-                });
+/// Validate C/C++ batch files as translation units with shared include paths.
+///
+/// Headers are not compiled on their own when a source file in the batch
+/// includes them; the including source exercises the patched header instead.
+/// The workspace root and the directory of every patched header are passed
+/// with `-I`, so sources find headers that live in other directories.
+fn gate_c_family_batch(
+    files: &[AppliedFile],
+    workspace_dir: &Path,
+    language: SymbolLanguage,
+) -> Result<()> {
+    use crate::validate::gates::{validate_c_with_includes, validate_cpp_with_includes};
+
+    let (headers, sources): (Vec<&Path>, Vec<&Path>) = files
+        .iter()
+        .map(|f| f.file.as_path())
+        .partition(|path| is_c_header(path));
+
+    let mut include_dirs = vec![workspace_dir.to_path_buf()];
+    for header in &headers {
+        if let Some(dir) = header.parent() {
+            if !include_dirs.iter().any(|d| d == dir) {
+                include_dirs.push(dir.to_path_buf());
             }
         }
     }
 
+    let source_texts: Vec<String> = sources
+        .iter()
+        .map(|path| fs::read_to_string(path).unwrap_or_default())
+        .collect();
+    let standalone_headers = headers.into_iter().filter(|header| {
+        !source_texts
+            .iter()
+            .any(|text| includes_header(text, header))
+    });
+
+    for target in sources.iter().copied().chain(standalone_headers) {
+        let outcome = if language == SymbolLanguage::Cpp {
+            validate_cpp_with_includes(target, &include_dirs)?
+        } else {
+            validate_c_with_includes(target, &include_dirs)?
+        };
+        check_compiler_outcome(outcome, target, language)?;
+    }
+
+    Ok(())
+}
+
+fn is_c_header(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(OsStr::to_str),
+        Some("h" | "hh" | "hpp" | "hxx")
+    )
+}
+
+/// Whether `source` has an `#include` directive naming `header`'s file name.
+fn includes_header(source: &str, header: &Path) -> bool {
+    let Some(name) = header.file_name().and_then(OsStr::to_str) else {
+        return false;
+    };
+    source.lines().any(|line| {
+        let line = line.trim_start();
+        line.starts_with('#')
+            && line[1..].trim_start().starts_with("include")
+            && line
+                .split(['"', '<', '>'])
+                .any(|part| part == name || part.ends_with(&format!("/{}", name)))
+    })
+}
+
+/// Turn a compiler gate outcome into a `CompilerValidationFailed` error.
+///
+/// A missing tool is a soft failure: the gate passes with a warning.
+fn check_compiler_outcome(
+    outcome: ValidationOutcome,
+    file_path: &Path,
+    language: SymbolLanguage,
+) -> Result<()> {
+    let tool_metadata = tool_invocation_for_language(language)
+        .map(|inv| validate::collect_tool_metadata(inv.binary, inv.version_args));
+
+    if !outcome.is_valid {
+        if !outcome.tool_available {
+            // Tool not available is a soft failure - we can't validate
+            // For now, we treat this as success but log a warning
+            log::warn!(
+                "Compiler validation tool not available for {}, skipping validation",
+                language.as_str()
+            );
+            return Ok(());
+        }
+
+        // Tool is available but validation failed
+        let mut diagnostics = Vec::new();
+        let tool_name = format!("{}-compiler", language.as_str());
+        let mut sources = DiagnosticSources::new(validate::ColumnUnit::for_language(language));
+
+        for err in outcome.errors {
+            let remediation = err
+                .code
+                .as_deref()
+                .and_then(validate::remediation_link_for_code);
+            let file = file_for_diagnostic(&err.file, file_path);
+            let byte_offset = sources.byte_offset(&file, err.line, err.column);
+            diagnostics.push(
+                Diagnostic::new(&tool_name, DiagnosticLevel::Error, err.message)
+                    .with_file(file)
+                    .with_position(nonzero(err.line), nonzero(err.column))
+                    .with_byte_offset(byte_offset)
+                    .with_code(err.code.clone())
+                    .with_note(err.note.clone())
+                    .with_tool_metadata(tool_metadata.as_ref())
+                    .with_remediation(remediation),
+            );
+        }
+
+        for warn in outcome.warnings {
+            let remediation = warn
+                .code
+                .as_deref()
+                .and_then(validate::remediation_link_for_code);
+            let file = file_for_diagnostic(&warn.file, file_path);
+            let byte_offset = sources.byte_offset(&file, warn.line, warn.column);
+            diagnostics.push(
+                Diagnostic::new(&tool_name, DiagnosticLevel::Warning, warn.message)
+                    .with_file(file)
+                    .with_position(nonzero(warn.line), nonzero(warn.column))
+                    .with_byte_offset(byte_offset)
+                    .with_code(warn.code.clone())
+                    .with_note(warn.note.clone())
+                    .with_tool_metadata(tool_metadata.as_ref())
+                    .with_remediation(remediation),
+            );
+        }
+
+        return Err(SpliceError::CompilerValidationFailed {
+            file: file_path.to_path_buf(),
+            language: language.as_str().to_string(),
+            diagnostics,
+        });
+    }
+
     Ok(())
 }
 
@@ -476,6 +1049,34 @@ fn file_for_diagnostic(reported: &str, fallback: &Path) -> PathBuf {
     }
 }
 
+/// File contents read while normalizing diagnostic positions, cached per path.
+struct DiagnosticSources {
+    unit: validate::ColumnUnit,
+    files: BTreeMap<PathBuf, Option<String>>,
+}
+
+impl DiagnosticSources {
+    fn new(unit: validate::ColumnUnit) -> Self {
+        Self {
+            unit,
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Byte offset of a reported position, or `None` if unknown or unreadable.
+    fn byte_offset(&mut self, file: &Path, line: usize, column: usize) -> Option<usize> {
+        if line == 0 {
+            return None;
+        }
+        let source = self
+            .files
+            .entry(file.to_path_buf())
+            .or_insert_with(|| fs::read_to_string(file).ok())
+            .as_deref()?;
+        validate::byte_offset_for_position(source, line, column.max(1), self.unit)
+    }
+}
+
 fn nonzero(value: usize) -> Option<usize> {
     if value == 0 {
         None
@@ -515,57 +1116,282 @@ fn tool_invocation_for_language(language: SymbolLanguage) -> Option<ToolInvocati
             binary: "tsc",
             version_args: &["--version"],
         }),
+        SymbolLanguage::Haskell => Some(ToolInvocation {
+            binary: "ghc",
+            version_args: &["--version"],
+        }),
+        SymbolLanguage::Elixir => Some(ToolInvocation {
+            binary: "elixir",
+            version_args: &["--version"],
+        }),
         _ => None,
     }
 }
 
+/// Compiler or checker the validation gates run for `language`.
+pub fn validation_tool(language: SymbolLanguage) -> &'static str {
+    match tool_invocation_for_language(language) {
+        Some(tool) => tool.binary,
+        // Rust files are validated by the cargo check gate
+        None => "cargo",
+    }
+}
+
+/// Formatter run by `ValidationOptions::format_after`; the file path goes last.
+struct FormatterInvocation {
+    binary: &'static str,
+    args: &'static [&'static str],
+}
+
+fn formatter_for_language(language: SymbolLanguage) -> Option<FormatterInvocation> {
+    match language {
+        SymbolLanguage::Rust => Some(FormatterInvocation {
+            binary: "rustfmt",
+            args: &["--quiet"],
+        }),
+        SymbolLanguage::Python => Some(FormatterInvocation {
+            binary: "black",
+            args: &["--quiet"],
+        }),
+        SymbolLanguage::JavaScript | SymbolLanguage::TypeScript => Some(FormatterInvocation {
+            binary: "prettier",
+            args: &["--write"],
+        }),
+        _ => None,
+    }
+}
+
+/// Format `file_path` in place with the language's formatter, if it has one.
+///
+/// Best effort: a missing formatter, or one that rejects the file, leaves it
+/// untouched and only logs; the validation gates still decide the outcome.
+fn format_file(file_path: &Path, language: SymbolLanguage) {
+    let Some(formatter) = formatter_for_language(language) else {
+        return;
+    };
+
+    let mut command = std::process::Command::new(formatter.binary);
+    command.args(formatter.args);
+    if language == SymbolLanguage::Rust {
+        // rustfmt defaults to the 2015 edition when run outside cargo
+        command.args(["--edition", &rust_edition_for(file_path)]);
+    }
+
+    match command.arg(file_path).output() {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => log::warn!(
+            "{} could not format {}: {}",
+            formatter.binary,
+            file_path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            log::debug!(
+                "{} not installed; leaving {} unformatted",
+                formatter.binary,
+                file_path.display()
+            )
+        }
+        Err(err) => log::warn!("Failed to run {}: {}", formatter.binary, err),
+    }
+}
+
+/// The `edition` declared by the nearest `Cargo.toml` above `file_path`, or `2021`.
+fn rust_edition_for(file_path: &Path) -> String {
+    manifest_edition(file_path).unwrap_or_else(|| "2021".to_string())
+}
+
+/// `package.edition` from the nearest `Cargo.toml` above `file_path`.
+///
+/// An edition inherited with `edition.workspace = true` is read from
+/// `workspace.package.edition` of the nearest manifest with a `[workspace]`,
+/// which may be the package's own.
+fn manifest_edition(file_path: &Path) -> Option<String> {
+    let file = std::path::absolute(file_path).ok()?;
+    let mut manifests = file
+        .ancestors()
+        .skip(1)
+        .map(|dir| dir.join("Cargo.toml"))
+        .filter(|manifest| manifest.is_file())
+        .filter_map(|manifest| fs::read_to_string(manifest).ok())
+        .map(|text| text.parse::<toml::Table>().ok());
+
+    let package = manifests.next()??;
+    let edition = package.get("package")?.get("edition")?;
+    if let Some(edition) = edition.as_str() {
+        return Some(edition.to_string());
+    }
+    if edition.get("workspace")?.as_bool() != Some(true) {
+        return None;
+    }
+    let workspace_edition = |manifest: &toml::Table| {
+        let edition = manifest.get("workspace")?.get("package")?.get("edition")?;
+        edition.as_str().map(str::to_string)
+    };
+    if package.contains_key("workspace") {
+        return workspace_edition(&package);
+    }
+    let root = manifests
+        .flatten()
+        .find(|manifest| manifest.contains_key("workspace"))?;
+    workspace_edition(&root)
+}
+
+/// Extra `cargo check` arguments for each check the compiler gate runs.
+///
+/// Without `fast_check` this is a single check with no extra arguments. With
+/// it, files are grouped by the nearest package manifest and each package is
+/// checked once, limited to `--lib` or `--bins` when every patched file in it
+/// belongs to the library (`src/` outside `main.rs` and `bin/`) or to a
+/// binary. Files in tests, examples, or build scripts check the whole package.
+/// A `manifest` override owns every file.
+fn cargo_check_scopes(
+    files: &[&Path],
+    fast_check: bool,
+    manifest: Option<&Path>,
+) -> Result<Vec<Vec<String>>> {
+    if !fast_check {
+        return Ok(vec![Vec::new()]);
+    }
+
+    // Manifest -> target flags (`None` once any file needs the whole package)
+    let mut packages: BTreeMap<PathBuf, Option<Vec<&'static str>>> = BTreeMap::new();
+    for file in files {
+        let file = std::path::absolute(file)?;
+        let manifest = owning_manifest(&file, manifest)?;
+        let src = manifest.with_file_name("src");
+        let target = match file.strip_prefix(&src) {
+            Ok(rel) if rel == Path::new("main.rs") || rel.starts_with("bin") => Some("--bins"),
+            Ok(_) => Some("--lib"),
+            Err(_) => None,
+        };
+        let flags = packages.entry(manifest).or_insert_with(|| Some(Vec::new()));
+        match (flags.as_mut(), target) {
+            (Some(flags), Some(target)) if !flags.contains(&target) => flags.push(target),
+            (Some(_), Some(_)) => {}
+            _ => *flags = None,
+        }
+    }
+
+    packages
+        .into_iter()
+        .map(|(manifest, flags)| {
+            let mut args = vec![
+                "--manifest-path".to_string(),
+                utf8_path(&manifest)?.to_string(),
+            ];
+            args.extend(flags.into_iter().flatten().map(str::to_string));
+            Ok(args)
+        })
+        .collect()
+}
+
+/// Nearest `Cargo.toml` above an absolute file path, unless `manifest` overrides it.
+fn owning_manifest(file: &Path, manifest: Option<&Path>) -> Result<PathBuf> {
+    if let Some(manifest) = manifest {
+        return Ok(std::path::absolute(manifest)?);
+    }
+    file.ancestors()
+        .skip(1)
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|manifest| manifest.is_file())
+        .ok_or_else(|| SpliceError::Other(format!("No Cargo.toml above {}", file.display())))
+}
+
+/// Scopes the rust-analyzer gate runs over for a set of patched files.
+///
+/// Without `scoped` this is the whole workspace. With it, each distinct
+/// package owning a file (found like the `fast_check` cargo scopes) is
+/// analyzed once, in its own directory. A `manifest` override owns every file.
+pub fn analyzer_scopes(
+    files: &[&Path],
+    scoped: bool,
+    manifest: Option<&Path>,
+) -> Result<Vec<AnalyzerScope>> {
+    if !scoped {
+        return Ok(vec![AnalyzerScope::Workspace]);
+    }
+
+    let mut package_dirs = BTreeSet::new();
+    for file in files {
+        let manifest = owning_manifest(&std::path::absolute(file)?, manifest)?;
+        if let Some(dir) = manifest.parent() {
+            package_dirs.insert(dir.to_path_buf());
+        }
+    }
+    Ok(package_dirs
+        .into_iter()
+        .map(AnalyzerScope::Package)
+        .collect())
+}
+
+/// Directory the Rust gates run in: the `manifest_path` package's when one
+/// is set, `workspace_dir` otherwise.
+fn rust_gate_dir<'a>(workspace_dir: &'a Path, options: &'a ValidationOptions) -> Result<&'a Path> {
+    match options.manifest_path.as_deref() {
+        None => Ok(workspace_dir),
+        Some(manifest) if manifest.is_file() => Ok(manifest
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."))),
+        Some(manifest) => Err(SpliceError::Other(format!(
+            "Manifest not found: {}",
+            manifest.display()
+        ))),
+    }
+}
+
 /// Cargo check gate (Rust-specific).
 ///
-/// Validates that the workspace compiles after the patch.
-fn gate_cargo_check(workspace_dir: &Path) -> Result<()> {
+/// Validates that the workspace compiles after the patch, with the requested
+/// feature selection, building into `target_dir` when one is given. `scope`
+/// holds extra arguments narrowing what is checked (see `cargo_check_scopes`).
+/// When the check passes, the warnings it printed are added to `warnings`.
+fn gate_cargo_check(
+    workspace_dir: &Path,
+    scope: &[String],
+    features: &CargoFeatures,
+    target_dir: Option<&Path>,
+    warnings: &GateWarnings,
+) -> Result<()> {
     use std::process::Command;
 
-    let output = Command::new("cargo")
+    let mut command = Command::new("cargo");
+    command
         .arg("check")
-        .current_dir(workspace_dir)
-        .output()?;
+        .args(scope)
+        .args(features.to_args())
+        .current_dir(workspace_dir);
+    if let Some(target_dir) = target_dir {
+        command.env("CARGO_TARGET_DIR", target_dir);
+    }
+    let output = command.output()?;
 
     let stderr = String::from_utf8_lossy(&output.stderr);
     let stdout = String::from_utf8_lossy(&output.stdout);
 
     let combined = format!("{}{}", stderr, stdout);
 
+    let compiler_errors = validate::parse_cargo_output(&stderr);
+    let cargo_meta = validate::collect_tool_metadata("cargo", &["--version"]);
+
     if output.status.success() {
+        let reported = compiler_errors
+            .into_iter()
+            .filter(|err| err.level == validate::ErrorLevel::Warning);
+        warnings.extend(cargo_diagnostics(workspace_dir, reported, &cargo_meta));
         return Ok(());
     }
 
-    let compiler_errors = validate::parse_cargo_output(&stderr);
-    let mut diagnostics = Vec::new();
-    let cargo_meta = validate::collect_tool_metadata("cargo", &["--version"]);
-
-    if compiler_errors.is_empty() {
-        diagnostics.push(
+    let diagnostics = if compiler_errors.is_empty() {
+        vec![
             Diagnostic::new("cargo-check", DiagnosticLevel::Error, combined.clone())
                 .with_file(workspace_dir.to_path_buf())
                 .with_tool_metadata(Some(&cargo_meta)),
-        );
+        ]
     } else {
-        for err in compiler_errors {
-            let remediation = err
-                .code
-                .as_deref()
-                .and_then(validate::remediation_link_for_code);
-            diagnostics.push(
-                Diagnostic::new("cargo-check", DiagnosticLevel::from(err.level), err.message)
-                    .with_file(PathBuf::from(err.file))
-                    .with_position(nonzero(err.line), nonzero(err.column))
-                    .with_code(err.code.clone())
-                    .with_note(err.note.clone())
-                    .with_tool_metadata(Some(&cargo_meta))
-                    .with_remediation(remediation),
-            );
-        }
-    }
+        cargo_diagnostics(workspace_dir, compiler_errors, &cargo_meta)
+    };
 
     Err(SpliceError::CargoCheckFailed {
         workspace: workspace_dir.to_path_buf(),
@@ -574,6 +1400,52 @@ fn gate_cargo_check(workspace_dir: &Path) -> Result<()> {
     })
 }
 
+/// Turn parsed `cargo check` messages into diagnostics.
+fn cargo_diagnostics(
+    workspace_dir: &Path,
+    compiler_errors: impl IntoIterator<Item = validate::CompilerError>,
+    cargo_meta: &validate::ToolMetadata,
+) -> Vec<Diagnostic> {
+    let mut sources = DiagnosticSources::new(validate::ColumnUnit::Char);
+    compiler_errors
+        .into_iter()
+        .map(|err| {
+            let remediation = err
+                .code
+                .as_deref()
+                .and_then(validate::remediation_link_for_code);
+            // cargo reports paths relative to the workspace root
+            let byte_offset =
+                sources.byte_offset(&workspace_dir.join(&err.file), err.line, err.column);
+            Diagnostic::new("cargo-check", DiagnosticLevel::from(err.level), err.message)
+                .with_file(PathBuf::from(err.file))
+                .with_position(nonzero(err.line), nonzero(err.column))
+                .with_byte_offset(byte_offset)
+                .with_code(err.code.clone())
+                .with_note(err.note.clone())
+                .with_tool_metadata(Some(cargo_meta))
+                .with_remediation(remediation)
+        })
+        .collect()
+}
+
+/// Refuse to proceed unless `file_path` currently hashes to `expected` (SHA-256, hex).
+///
+/// Lets automation assert that a file is still in the state a plan was computed
+/// against before anything is written.
+pub fn verify_file_hash(file_path: &Path, expected: &str) -> Result<()> {
+    let (_, actual) = read_with_hash(file_path)?;
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(SpliceError::HashMismatch {
+            file: file_path.to_path_buf(),
+            expected: expected.trim().to_string(),
+            actual,
+        })
+    }
+}
+
 /// Compute SHA-256 hash of file contents.
 fn compute_hash(bytes: &[u8]) -> String {
     let mut hasher = Sha256::new();
@@ -618,32 +1490,493 @@ pub fn replace_span(file_path: &Path, start: usize, end: usize, new_content: &st
     Ok(())
 }
 
+/// Side of a symbol at which `patch --insert-before`/`--insert-after` adds content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertPosition {
+    /// Insert ahead of the symbol, above any attributes or doc comments attached to it.
+    Before,
+    /// Insert right after the symbol's span.
+    After,
+}
+
+/// Compute a zero-width edit that inserts `content` next to the symbol spanning `start..end`.
+///
+/// Returns the byte offset to insert at and the text to insert. The content is
+/// inserted as whole lines, separated from the symbol by a blank line; when
+/// inserting before, the edit starts at the beginning of the symbol's line, above
+/// the attributes, decorators and doc comments the syntax tree attaches to it, so
+/// the symbol keeps its indentation and annotations.
+pub fn insertion_edit(
+    source: &str,
+    start: usize,
+    end: usize,
+    content: &str,
+    position: InsertPosition,
+    language: SymbolLanguage,
+) -> (usize, String) {
+    let content = content.trim_matches(|c| c == '\n' || c == '\r');
+
+    match position {
+        InsertPosition::After => (end, format!("\n\n{}", content)),
+        InsertPosition::Before => {
+            let first = annotations_start(source, start, end, language);
+            let line_start = source[..first].rfind('\n').map_or(0, |i| i + 1);
+            let offset = if source[line_start..first].trim().is_empty() {
+                line_start
+            } else {
+                first
+            };
+            (offset, format!("{}\n\n", content))
+        }
+    }
+}
+
+/// Start of the attributes, decorators and doc comments preceding the item
+/// spanning `start..end`, or `start` when it has none or the source does not
+/// parse.
+fn annotations_start(source: &str, start: usize, end: usize, language: SymbolLanguage) -> usize {
+    let Ok(mut parser) = parser_for(language.as_str(), &grammar_for(language)) else {
+        return start;
+    };
+    let Some(tree) = parser.parse(source, None) else {
+        return start;
+    };
+    let Some(item) = tree.root_node().descendant_for_byte_range(start, end) else {
+        return start;
+    };
+
+    let mut first = start;
+    let mut sibling = item.prev_sibling();
+    while let Some(node) = sibling {
+        let text = &source[node.byte_range()];
+        let attached = match node.kind() {
+            "attribute_item" | "decorator" => true,
+            kind if kind.contains("comment") => text.starts_with("///") || text.starts_with("/**"),
+            _ => false,
+        };
+        if !attached {
+            break;
+        }
+        first = node.start_byte();
+        sibling = node.prev_sibling();
+    }
+    first
+}
+
+/// Compute replacements that collapse runs of blank lines longer than `max_blank_lines`.
+///
+/// A line is blank if it holds only whitespace. Each over-long run is replaced
+/// by `max_blank_lines` empty lines, ended the way the file ends its first
+/// line. Runs inside string literals and comments are text, not layout, and
+/// are left alone. Used after deletions, which tend to leave the blank lines
+/// from both sides of the removed item behind.
+pub fn blank_line_replacements(
+    file: &SourceFile,
+    max_blank_lines: usize,
+) -> Result<Vec<SpanReplacement>> {
+    let source = file.bytes();
+    let tree = file.tree()?;
+
+    // (start, end, line count) of each run of blank lines
+    let mut runs: Vec<(usize, usize, usize)> = Vec::new();
+    let mut offset = 0;
+    for line in source.split_inclusive(|&b| b == b'\n') {
+        let line_end = offset + line.len();
+        if line.ends_with(b"\n") && line.iter().all(u8::is_ascii_whitespace) {
+            match runs.last_mut() {
+                Some((_, end, count)) if *end == offset => {
+                    *end = line_end;
+                    *count += 1;
+                }
+                _ => runs.push((offset, line_end, 1)),
+            }
+        }
+        offset = line_end;
+    }
+
+    let terminator = match source.iter().position(|&b| b == b'\n') {
+        Some(newline) if newline > 0 && source[newline - 1] == b'\r' => "\r\n",
+        _ => "\n",
+    };
+    Ok(runs
+        .into_iter()
+        .filter(|&(start, _, count)| {
+            count > max_blank_lines && enclosing_literal(tree, start).is_none()
+        })
+        .map(|(start, end, _)| {
+            SpanReplacement::new(
+                file.path().to_path_buf(),
+                start,
+                end,
+                terminator.repeat(max_blank_lines),
+            )
+        })
+        .collect())
+}
+
+/// Fold a blank-line trim into `replacements`, planned on the text they produce.
+///
+/// Each file's replacements are applied in memory and its blank-line runs
+/// found on the result, as [`blank_line_replacements`] does. A file with runs
+/// to trim has its replacements swapped for one whole-file replacement, so the
+/// trim is validated and written, or rolled back, with the edits it tidies.
+/// Returns the number of runs trimmed.
+pub fn fold_blank_line_trim(
+    replacements: &mut Vec<SpanReplacement>,
+    language: SymbolLanguage,
+    parse: ParseOptions,
+    max_blank_lines: usize,
+) -> Result<usize> {
+    let mut by_file: BTreeMap<PathBuf, Vec<SpanReplacement>> = BTreeMap::new();
+    for replacement in replacements.drain(..) {
+        by_file
+            .entry(replacement.file.clone())
+            .or_default()
+            .push(replacement);
+    }
+
+    let mut runs_trimmed = 0;
+    for (file_path, edits) in by_file {
+        let original = fs::read(&file_path)?;
+        validate_replacements(&file_path, &edits, &original)?;
+        let file_language = SymbolLanguage::from_path(&file_path).unwrap_or(language);
+        let edited = SourceFile::from_bytes(
+            &file_path,
+            file_language,
+            apply_replacements(&original, &edits)?,
+        )
+        .with_parse_options(parse);
+        let trims = blank_line_replacements(&edited, max_blank_lines)?;
+        if trims.is_empty() {
+            replacements.extend(edits);
+            continue;
+        }
+
+        runs_trimmed += trims.len();
+        let trimmed = apply_replacements(edited.bytes(), &trims)?;
+        replacements.push(SpanReplacement::new(
+            file_path,
+            0,
+            original.len(),
+            std::str::from_utf8(&trimmed)?.to_string(),
+        ));
+    }
+
+    Ok(runs_trimmed)
+}
+
+/// Compute the edit that declares module `name` in a Rust file, unless it already does.
+///
+/// Returns `None` when the file already has a top-level `mod name` item, inline
+/// or not, so moving code into an existing module never adds a second
+/// declaration. Otherwise `mod name;` (prefixed by `visibility`, e.g. `"pub"`,
+/// or nothing when empty) is inserted after the file's last top-level `mod`
+/// item, or below its leading `//!` docs and `#![...]` attributes.
+pub fn mod_declaration(
+    file_path: &Path,
+    source: &[u8],
+    name: &str,
+    visibility: &str,
+) -> Result<Option<SpanReplacement>> {
+    use crate::ingest::rust::{extract_rust_symbols, RustSymbolKind};
+
+    let symbols = extract_rust_symbols(file_path, source)?;
+    // A `mod` item carries its own path, so top-level ones sit at `crate::<name>`
+    let modules: Vec<_> = symbols
+        .iter()
+        .filter(|symbol| {
+            symbol.kind == RustSymbolKind::Module
+                && symbol.module_path == format!("crate::{}", symbol.name)
+        })
+        .collect();
+    if modules.iter().any(|module| module.name == name) {
+        return Ok(None);
+    }
+
+    let declaration = if visibility.is_empty() {
+        format!("mod {};", name)
+    } else {
+        format!("{} mod {};", visibility, name)
+    };
+    if let Some(last) = modules.iter().max_by_key(|module| module.byte_end) {
+        let edit = SpanReplacement::new(
+            file_path.to_path_buf(),
+            last.byte_end,
+            last.byte_end,
+            format!("\n{}", declaration),
+        );
+        return Ok(Some(edit));
+    }
+
+    // Skip the crate or module header: inner docs, inner attributes, a shebang
+    let mut offset = 0;
+    for line in source.split_inclusive(|&b| b == b'\n') {
+        let trimmed = line.trim_ascii_start();
+        if trimmed.starts_with(b"//!") || trimmed.starts_with(b"#!") {
+            offset += line.len();
+        } else {
+            break;
+        }
+    }
+    let content = match (offset, source.len()) {
+        (_, 0) => format!("{}\n", declaration),
+        (0, _) => format!("{}\n\n", declaration),
+        _ => format!("\n{}\n", declaration),
+    };
+    Ok(Some(SpanReplacement::new(
+        file_path.to_path_buf(),
+        offset,
+        offset,
+        content,
+    )))
+}
+
+/// Compute the edit that removes one imported name from a Rust file.
+///
+/// `name` is either the name as bound in the file (`d` for `use a::c as d;`) or
+/// its full path (`a::c`). When the matching `use` declaration imports only that
+/// name, the whole declaration is removed along with its line; when the name is
+/// one of several in a braced group, only that entry and one adjoining comma
+/// are removed, so `use a::{b, c, d};` becomes `use a::{b, d};`.
+pub fn remove_import(file_path: &Path, source: &[u8], name: &str) -> Result<SpanReplacement> {
+    use crate::ingest::imports::rust::{extract_rust_imports, use_list_entries};
+    use crate::offset::{byte_to_position, PositionEncoding};
+
+    let (prefix, local) = match name.rsplit_once("::") {
+        Some((prefix, local)) => (Some(prefix), local),
+        None => (None, name),
+    };
+    let matches: Vec<_> = extract_rust_imports(file_path, source)?
+        .into_iter()
+        .filter(|import| prefix.is_none_or(|prefix| import.path.join("::") == prefix))
+        .filter(|import| {
+            import.imported_names.iter().any(|n| n == local)
+                || (prefix.is_some()
+                    && import.aliases.iter().any(|(original, _)| original == local))
+        })
+        .collect();
+
+    let import = match matches.as_slice() {
+        [] => return Err(SpliceError::symbol_not_found(name, Some(file_path))),
+        [import] => import,
+        [first, ..] => {
+            let text = std::str::from_utf8(source)?;
+            let line_col =
+                |offset| byte_to_position(text, offset, PositionEncoding::Utf8).unwrap_or_default();
+            let (line, col) = line_col(first.byte_span.0);
+            return Err(SpliceError::AmbiguousReference {
+                name: name.to_string(),
+                file: file_path.to_string_lossy().into_owned(),
+                line,
+                col,
+                candidates: matches
+                    .iter()
+                    .map(|import| {
+                        let (line, _) = line_col(import.byte_span.0);
+                        format!("{}::{} (line {})", import.path.join("::"), local, line)
+                    })
+                    .collect(),
+            });
+        }
+    };
+
+    let entries = use_list_entries(file_path, source, import.byte_span.0)?;
+    if entries.len() > 1 {
+        let index = entries
+            .iter()
+            .position(|entry| entry.local == local || (prefix.is_some() && entry.original == local))
+            .ok_or_else(|| SpliceError::symbol_not_found(name, Some(file_path)))?;
+        // Take the following separator, or the preceding one for the last entry
+        let (start, end) = match entries.get(index + 1) {
+            Some(next) => (entries[index].byte_span.0, next.byte_span.0),
+            None => (entries[index - 1].byte_span.1, entries[index].byte_span.1),
+        };
+        return Ok(SpanReplacement::new(
+            file_path.to_path_buf(),
+            start,
+            end,
+            String::new(),
+        ));
+    }
+
+    // The whole declaration goes; take its line too when nothing else is on it
+    let (mut start, mut end) = import.byte_span;
+    let line_start = source[..start]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    let line_end = source[end..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map(|i| end + i + 1);
+    let leading = &source[line_start..start];
+    if leading.iter().all(u8::is_ascii_whitespace) {
+        if let Some(line_end) = line_end {
+            if source[end..line_end].iter().all(u8::is_ascii_whitespace) {
+                start = line_start;
+                end = line_end;
+            }
+        }
+    }
+    Ok(SpanReplacement::new(
+        file_path.to_path_buf(),
+        start,
+        end,
+        String::new(),
+    ))
+}
+
+/// Refuse an edit whose new content defines a symbol the file already has.
+///
+/// Splices `new_content` over `start..end` in memory, extracts symbols from the
+/// result, and compares each symbol inside the new content with the rest of the
+/// file. A symbol with the same name and kind directly inside the same
+/// enclosing item (module, impl, trait, or function) is a duplicate definition,
+/// reported with the existing one's location before any compiler gate runs.
+///
+/// Impl blocks and macros may legitimately repeat, and languages with
+/// overloading or multi-clause functions (C, C++, Java, TypeScript, Haskell,
+/// Elixir) are not checked. Content that does not parse is left to the
+/// tree-sitter gate.
+pub fn ensure_no_duplicate_symbol(
+    file_path: &Path,
+    source: &[u8],
+    start: usize,
+    end: usize,
+    new_content: &str,
+    language: SymbolLanguage,
+) -> Result<()> {
+    use crate::ingest::extract_symbols_with_language;
+    use crate::symbol::{AnySymbol, Symbol};
+
+    if !matches!(
+        language,
+        SymbolLanguage::Rust | SymbolLanguage::Python | SymbolLanguage::JavaScript
+    ) {
+        return Ok(());
+    }
+
+    let mut patched = Vec::with_capacity(source.len() + new_content.len());
+    patched.extend_from_slice(&source[..start]);
+    patched.extend_from_slice(new_content.as_bytes());
+    patched.extend_from_slice(&source[end..]);
+
+    let Ok(symbols) = extract_symbols_with_language(file_path, &patched, language) else {
+        return Ok(());
+    };
+
+    let inserted = start..start + new_content.len();
+    let is_new = |symbol: &AnySymbol| {
+        inserted.start <= symbol.byte_start() && symbol.byte_end() <= inserted.end
+    };
+    // Innermost symbol strictly enclosing `symbol`, identified by its span
+    let scope = |symbol: &AnySymbol| {
+        let len = symbol.byte_end() - symbol.byte_start();
+        symbols
+            .iter()
+            .filter(|outer| {
+                outer.byte_start() <= symbol.byte_start()
+                    && symbol.byte_end() <= outer.byte_end()
+                    && outer.byte_end() - outer.byte_start() > len
+            })
+            .min_by_key(|outer| outer.byte_end() - outer.byte_start())
+            .map(|outer| (outer.byte_start(), outer.byte_end()))
+    };
+
+    for new in symbols.iter().filter(|symbol| is_new(symbol)) {
+        if matches!(new.kind(), "impl" | "macro") {
+            continue;
+        }
+        let existing = symbols.iter().filter(|symbol| !is_new(symbol)).find(|old| {
+            old.name() == new.name()
+                && old.kind() == new.kind()
+                && old.fully_qualified() == new.fully_qualified()
+                && scope(old) == scope(new)
+        });
+        if let Some(old) = existing {
+            // Report the existing definition where it sits in the unpatched file
+            let offset = if old.byte_start() >= inserted.end {
+                old.byte_start() - inserted.end + end
+            } else {
+                old.byte_start()
+            };
+            let line = source[..offset].iter().filter(|&&b| b == b'\n').count() + 1;
+            return Err(SpliceError::DuplicateSymbol {
+                name: new.name().to_string(),
+                kind: new.kind().to_string(),
+                file: file_path.to_path_buf(),
+                line,
+            });
+        }
+    }
+
+    Ok(())
+}
+
 fn run_batch_validations(
     files: &[AppliedFile],
     workspace_dir: &Path,
     language: SymbolLanguage,
     analyzer_mode: AnalyzerMode,
+    options: &ValidationOptions,
 ) -> Result<()> {
     if files.is_empty() {
         return Ok(());
     }
 
-    let mut requires_rust_validation = false;
-    for file in files {
-        gate_tree_sitter_reparse(&file.file, language)?;
-        if language == SymbolLanguage::Rust {
-            requires_rust_validation = true;
-        } else {
-            gate_compiler_validation(&file.file, workspace_dir, language)?;
+    let gates = options.gates;
+    let c_family = matches!(language, SymbolLanguage::C | SymbolLanguage::Cpp);
+
+    // Reparse every file before any compiler runs, so a syntax error is
+    // reported against the file that has it and no compile is wasted on it
+    if gates.tree_sitter {
+        let failures: Vec<SpliceError> = per_file_gate(files, options, |file| {
+            gate_tree_sitter_reparse(
+                &SourceFile::read(&file.file, language)?.with_parse_options(options.parse),
+            )
+        })?
+        .into_iter()
+        .filter_map(Result::err)
+        .collect();
+        if let Some(err) = combine_parse_failures(failures) {
+            return Err(err);
         }
     }
 
-    if requires_rust_validation {
-        gate_cargo_check(workspace_dir)?;
-        if language == SymbolLanguage::Rust {
-            if analyzer_mode != AnalyzerMode::Off {
-                use crate::validate::gate_rust_analyzer;
-                gate_rust_analyzer(workspace_dir, analyzer_mode)?;
+    if gates.compiler && language != SymbolLanguage::Rust && !(c_family && options.include_aware) {
+        // Every file is checked, but only the first failure in batch order is reported
+        per_file_gate(files, options, |file| {
+            gate_compiler_validation(&file.file, workspace_dir, language, options)
+        })?
+        .into_iter()
+        .collect::<Result<()>>()?;
+    }
+
+    if gates.compiler && c_family && options.include_aware {
+        gate_c_family_batch(files, workspace_dir, language)?;
+    }
+
+    if language == SymbolLanguage::Rust {
+        if gates.compiler {
+            let paths: Vec<&Path> = files.iter().map(|f| f.file.as_path()).collect();
+            let manifest = options.manifest_path.as_deref();
+            for scope in cargo_check_scopes(&paths, options.fast_check, manifest)? {
+                gate_cargo_check(
+                    rust_gate_dir(workspace_dir, options)?,
+                    &scope,
+                    &options.cargo_features,
+                    options.cargo_target_dir.as_deref(),
+                    &options.warnings,
+                )?;
+            }
+        }
+        if gates.analyzer && analyzer_mode != AnalyzerMode::Off {
+            use crate::validate::gate_rust_analyzer_scoped;
+            let paths: Vec<&Path> = files.iter().map(|f| f.file.as_path()).collect();
+            let manifest = options.manifest_path.as_deref();
+            let gate_dir = rust_gate_dir(workspace_dir, options)?;
+            for scope in analyzer_scopes(&paths, options.scoped_analyzer, manifest)? {
+                gate_rust_analyzer_scoped(gate_dir, analyzer_mode, &scope)?;
             }
         }
     }
@@ -651,6 +1984,49 @@ fn run_batch_validations(
     Ok(())
 }
 
+/// Run `gate` over each file of a batch, on `options.parallel_validate`
+/// threads when set, returning the results in batch order.
+fn per_file_gate<T: Send>(
+    files: &[AppliedFile],
+    options: &ValidationOptions,
+    gate: impl Fn(&AppliedFile) -> Result<T> + Sync,
+) -> Result<Vec<Result<T>>> {
+    let Some(jobs) = options.parallel_validate else {
+        return Ok(files.iter().map(gate).collect());
+    };
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .stack_size(crate::ingest::PARSER_STACK_SIZE)
+        .build()
+        .map_err(|err| {
+            SpliceError::Other(format!("Failed to start validation threads: {}", err))
+        })?;
+    Ok(pool.install(|| files.par_iter().map(&gate).collect()))
+}
+
+/// Report the first failing file of a batch, naming any others that failed too.
+fn combine_parse_failures(failures: Vec<SpliceError>) -> Option<SpliceError> {
+    let mut failures = failures.into_iter();
+    let first = failures.next()?;
+    let others: Vec<String> = failures
+        .filter_map(|err| err.file_path().map(|path| path.display().to_string()))
+        .collect();
+    match first {
+        SpliceError::ParseValidationFailed { file, message } if !others.is_empty() => {
+            Some(SpliceError::ParseValidationFailed {
+                file,
+                message: format!(
+                    "{}; {} other file(s) in the batch also failed to parse: {}",
+                    message,
+                    others.len(),
+                    others.join(", ")
+                ),
+            })
+        }
+        first => Some(first),
+    }
+}
+
 fn validate_replacements(
     file_path: &Path,
     replacements: &[SpanReplacement],
@@ -662,7 +2038,7 @@ fn validate_replacements(
     let file_len = original.len();
 
     let mut sorted = replacements.to_vec();
-    sorted.sort_by_key(|r| r.start);
+    sort_replacements(&mut sorted);
 
     let mut previous_end: Option<usize> = None;
     for replacement in &sorted {
@@ -696,18 +2072,33 @@ fn validate_replacements(
     Ok(())
 }
 
+/// Order replacements as `SpanBatch` documents: by `(start, end)`, ties in input order.
+fn sort_replacements(replacements: &mut [SpanReplacement]) {
+    // `sort_by_key` is stable, which is what keeps same-offset insertions in order
+    replacements.sort_by_key(|r| (r.start, r.end));
+}
+
+/// Splice validated, non-overlapping replacements into `original`.
+///
+/// Offsets are all relative to `original`, so the output is built front to
+/// back and no edit can shift another's position.
 fn apply_replacements(original: &[u8], replacements: &[SpanReplacement]) -> Result<Vec<u8>> {
-    let content = std::str::from_utf8(original)?;
-    let mut rope = Rope::from_str(content);
+    std::str::from_utf8(original)?;
+
+    let mut sorted = replacements.to_vec();
+    sort_replacements(&mut sorted);
 
-    for replacement in replacements {
-        let start_char = rope.byte_to_char(replacement.start);
-        let end_char = rope.byte_to_char(replacement.end);
-        rope.remove(start_char..end_char);
-        rope.insert(start_char, &replacement.content);
+    let added: usize = sorted.iter().map(|r| r.content.len()).sum();
+    let mut patched = Vec::with_capacity(original.len() + added);
+    let mut cursor = 0;
+    for replacement in &sorted {
+        patched.extend_from_slice(&original[cursor..replacement.start]);
+        patched.extend_from_slice(replacement.content.as_bytes());
+        cursor = replacement.end;
     }
+    patched.extend_from_slice(&original[cursor..]);
 
-    Ok(rope.to_string().into_bytes())
+    Ok(patched)
 }
 
 fn read_with_hash(path: &Path) -> Result<(Vec<u8>, String)> {
@@ -716,36 +2107,147 @@ fn read_with_hash(path: &Path) -> Result<(Vec<u8>, String)> {
     Ok((data, hash))
 }
 
-fn rollback_files(files: &[AppliedFile]) {
+/// Undo a partially applied batch after `cause`, returning the error to report.
+///
+/// If any file could not be put back the way it was, the returned error says
+/// so and carries `cause` along.
+fn rollback_after(files: &[AppliedFile], cause: SpliceError) -> SpliceError {
+    let unrestored = rollback_files(files);
+    if unrestored.is_empty() {
+        cause
+    } else {
+        SpliceError::RollbackIncomplete {
+            files: unrestored,
+            cause: Box::new(cause),
+        }
+    }
+}
+
+/// Restore every applied file and return the ones left in the wrong state.
+///
+/// Restored files are re-read and compared against `before_hash`, so a write
+/// that silently went wrong is caught rather than trusted.
+fn rollback_files(files: &[AppliedFile]) -> Vec<PathBuf> {
+    let mut unrestored = Vec::new();
     for file in files.iter().rev() {
-        if let Err(err) = write_atomic(&file.file, &file.original, "rollback") {
-            log::error!(
-                "Rollback failed for {}: {}",
-                file.file.display(),
-                err.to_string()
-            );
+        let result = match &file.created_dirs {
+            Some(created_dirs) => remove_created_file(&file.file, created_dirs),
+            None => write_atomic(&file.file, &file.original, "rollback")
+                .and_then(|()| verify_restored(&file.file, &file.before_hash)),
+        };
+        if let Err(err) = result {
+            log::error!("Rollback failed for {}: {}", file.file.display(), err);
+            unrestored.push(file.file.clone());
         }
     }
+    unrestored
+}
+
+/// Check that a rolled-back file hashes to what it did before the operation.
+fn verify_restored(file_path: &Path, before_hash: &str) -> Result<()> {
+    let (_, actual) = read_with_hash(file_path)?;
+    if actual != before_hash {
+        return Err(SpliceError::HashMismatch {
+            file: file_path.to_path_buf(),
+            expected: before_hash.to_string(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Write a file that does not exist yet, creating missing parent directories.
+///
+/// Returns the directories it created, outermost first, so a rollback can
+/// remove exactly those.
+fn create_file(file_path: &Path, content: &[u8]) -> Result<Vec<PathBuf>> {
+    let mut created_dirs: Vec<PathBuf> = file_path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+        .map(Path::to_path_buf)
+        .collect();
+    created_dirs.reverse();
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(file_path, content, "create")?;
+    Ok(created_dirs)
+}
+
+/// Undo `create_file`: delete the file and the directories made for it.
+fn remove_created_file(file_path: &Path, created_dirs: &[PathBuf]) -> Result<()> {
+    fs::remove_file(file_path)?;
+    for dir in created_dirs.iter().rev() {
+        fs::remove_dir(dir)?;
+    }
+    Ok(())
 }
 
 fn write_atomic(file_path: &Path, content: &[u8], suffix: &str) -> Result<()> {
+    // The rename replaces the inode, so carry over what the new file would reset
+    let original = fs::metadata(file_path).ok();
     let temp_path = temp_path_for(file_path, suffix)?;
-    let mut temp_file = File::create(&temp_path)?;
+    let mut temp_file = File::options()
+        .write(true)
+        .create_new(true)
+        .open(&temp_path)?;
     temp_file.write_all(content)?;
+    if let Some(original) = &original {
+        copy_permissions_and_owner(&temp_file, original)?;
+    }
     temp_file.sync_all()?;
     std::fs::rename(&temp_path, file_path)?;
     Ok(())
 }
 
+/// Give `file` the permissions (on Unix, all mode bits) of `original`, and
+/// its owner and group where the process is allowed to.
+///
+/// Only a privileged process can hand a file to another user, so a failed
+/// ownership change is logged rather than refusing the write.
+fn copy_permissions_and_owner(file: &File, original: &fs::Metadata) -> Result<()> {
+    file.set_permissions(original.permissions())?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let current = file.metadata()?;
+        let uid = (current.uid() != original.uid()).then_some(original.uid());
+        let gid = (current.gid() != original.gid()).then_some(original.gid());
+        if uid.is_some() || gid.is_some() {
+            if let Err(e) = std::os::unix::fs::fchown(file, uid, gid) {
+                log::warn!(
+                    "Could not keep owner {}:{} on the rewritten file: {}",
+                    original.uid(),
+                    original.gid(),
+                    e
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Temp path next to `file_path`, unique per process and call.
+///
+/// The pid and a process-wide counter keep concurrent writers (other
+/// threads or other splice processes) from sharing a temp file, while
+/// staying in the same directory so the final rename is atomic.
 fn temp_path_for(file_path: &Path, suffix: &str) -> Result<PathBuf> {
+    static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
     let file_dir = file_path
         .parent()
         .ok_or_else(|| SpliceError::Other("File has no parent directory".to_string()))?;
-    let file_name = file_path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("tmp");
-    Ok(file_dir.join(format!(".{}.{}.tmp", file_name, suffix)))
+    let file_name = file_path.file_name().unwrap_or(OsStr::new("tmp"));
+    let token = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    // Built as an `OsString` so names that are not UTF-8 survive intact
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}.{}.{}.tmp", suffix, std::process::id(), token));
+    Ok(file_dir.join(temp_name))
 }
 
 struct AppliedFile {
@@ -753,6 +2255,8 @@ struct AppliedFile {
     original: Vec<u8>,
     before_hash: String,
     after_hash: String,
+    /// Set when the batch created this file, listing the directories made for it.
+    created_dirs: Option<Vec<PathBuf>>,
 }
 
 fn clone_workspace_for_preview(workspace_root: &Path) -> Result<TempDir> {
@@ -826,7 +2330,7 @@ fn compute_preview_report(
     };
 
     let lines_removed = if end > start {
-        (&source[start..end]).lines().count()
+        source[start..end].lines().count()
     } else {
         0
     };
@@ -837,7 +2341,7 @@ fn compute_preview_report(
     };
 
     let bytes_removed = end.saturating_sub(start);
-    let bytes_added = new_content.as_bytes().len();
+    let bytes_added = new_content.len();
 
     Ok(PreviewReport {
         file: file_path.to_string_lossy().into_owned(),
@@ -5,6 +5,7 @@
 //! confirmation to ensure replacements land on the intended tokens.
 
 use crate::error::{Result, SpliceError};
+use crate::ingest::{exclude_set, is_excluded, oversized_file};
 use crate::symbol::Language;
 use crate::validate::AnalyzerMode;
 use glob::glob;
@@ -16,6 +17,8 @@ use std::path::{Path, PathBuf};
 pub struct PatternReplaceConfig {
     /// Glob pattern for matching files.
     pub glob_pattern: String,
+    /// Glob patterns for files to leave out, even when `glob_pattern` matches them.
+    pub exclude_patterns: Vec<String>,
     /// Text pattern to find.
     pub find_pattern: String,
     /// Replacement text.
@@ -24,6 +27,10 @@ pub struct PatternReplaceConfig {
     pub language: Option<Language>,
     /// Whether to apply validation gates.
     pub validate: bool,
+    /// Files larger than this many bytes are skipped with a warning.
+    pub max_file_size: u64,
+    /// Only match whole identifier tokens, never substrings, comments, or strings.
+    pub identifier_only: bool,
 }
 
 /// A match found during pattern search.
@@ -50,14 +57,14 @@ pub struct PatternReplaceResult {
     pub files_patched: Vec<PathBuf>,
     /// Number of replacements made.
     pub replacements_count: usize,
+    /// Bytes removed plus bytes inserted across all replacements.
+    pub bytes_changed: usize,
     /// Validation errors (if any).
     pub validation_errors: Vec<String>,
 }
 
 /// Create a tree-sitter parser for the given language.
 fn parser_for_language(language: Language) -> Result<tree_sitter::Parser> {
-    let mut parser = tree_sitter::Parser::new();
-
     let lang = match language {
         Language::Rust => tree_sitter_rust::language(),
         Language::Python => tree_sitter_python::language(),
@@ -66,36 +73,45 @@ fn parser_for_language(language: Language) -> Result<tree_sitter::Parser> {
         Language::Java => tree_sitter_java::language(),
         Language::JavaScript => tree_sitter_javascript::language(),
         Language::TypeScript => tree_sitter_typescript::language_typescript(),
+        Language::Haskell => tree_sitter_haskell::language(),
+        Language::Elixir => tree_sitter_elixir::language(),
     };
 
-    parser
-        .set_language(&lang)
-        .map_err(|e| SpliceError::Parse {
-            file: PathBuf::from("<unknown>"),
-            message: format!("Failed to set language for parser: {:?}", e),
-        })?;
-
-    Ok(parser)
+    crate::ingest::grammar::parser_for(language.as_str(), &lang)
 }
 
 /// Find all occurrences of a text pattern in files matching a glob pattern.
 ///
 /// This function performs text-based search and uses tree-sitter to confirm
 /// that matches are in valid AST nodes (not in comments or strings, unless
-/// the pattern specifically targets those).
+/// the pattern specifically targets those). With `identifier_only`, a match
+/// must cover exactly one identifier token. Files matching one of
+/// `exclude_patterns` are skipped.
 pub fn find_pattern_in_files(config: &PatternReplaceConfig) -> Result<Vec<PatternMatch>> {
     let mut matches = Vec::new();
 
     // Use glob to find matching files
     let glob_paths = glob(&config.glob_pattern)
         .map_err(|e| SpliceError::Other(format!("Invalid glob pattern: {}", e)))?;
+    let excludes = exclude_set(&config.exclude_patterns)?;
 
     for entry in glob_paths {
         let entry = entry.map_err(|e| SpliceError::Other(format!("Glob iteration error: {}", e)))?;
         let path = entry;
 
-        // Skip directories
-        if path.is_dir() {
+        // Skip directories and excluded files
+        if path.is_dir() || is_excluded(&excludes, &path) {
+            continue;
+        }
+
+        // Skip oversized files (minified or vendored code)
+        if let Some(size) = oversized_file(&path, config.max_file_size) {
+            log::warn!(
+                "Skipping {} ({} bytes exceeds --max-file-size {})",
+                path.display(),
+                size,
+                config.max_file_size
+            );
             continue;
         }
 
@@ -112,7 +128,12 @@ pub fn find_pattern_in_files(config: &PatternReplaceConfig) -> Result<Vec<Patter
         };
 
         // Find matches in this file
-        let file_matches = find_pattern_in_file(&path, &config.find_pattern, language)?;
+        let file_matches = find_pattern_in_file(
+            &path,
+            &config.find_pattern,
+            language,
+            config.identifier_only,
+        )?;
         matches.extend(file_matches);
     }
 
@@ -126,6 +147,7 @@ fn find_pattern_in_file(
     file_path: &Path,
     pattern: &str,
     language: Language,
+    identifier_only: bool,
 ) -> Result<Vec<PatternMatch>> {
     let content = std::fs::read_to_string(file_path)
         .map_err(|e| SpliceError::Io {
@@ -149,8 +171,18 @@ fn find_pattern_in_file(
         let abs_end = abs_start + pattern.len();
 
         // Check if this location is in a valid AST node
-        let byte_offset = abs_start as usize;
-        let node = tree.root_node().descendant_for_byte_range(byte_offset, byte_offset);
+        let byte_offset = abs_start;
+        let node = if identifier_only {
+            tree.root_node()
+                .descendant_for_byte_range(abs_start, abs_end)
+                .filter(|n| {
+                    n.start_byte() == abs_start
+                        && n.end_byte() == abs_end
+                        && is_identifier_kind(n.kind())
+                })
+        } else {
+            tree.root_node().descendant_for_byte_range(byte_offset, byte_offset)
+        };
 
         if let Some(node) = node {
             // Skip matches in comments unless the pattern starts with '//'
@@ -184,6 +216,14 @@ fn find_pattern_in_file(
     Ok(matches)
 }
 
+/// Whether a tree-sitter node kind names an identifier token.
+///
+/// Most grammars use `*identifier` kinds; Haskell uses `variable`/`constructor`
+/// and Elixir names modules with `alias`.
+fn is_identifier_kind(kind: &str) -> bool {
+    kind.ends_with("identifier") || matches!(kind, "variable" | "constructor" | "alias")
+}
+
 /// Apply pattern replacement to files with validation.
 ///
 /// This function:
@@ -201,6 +241,7 @@ pub fn apply_pattern_replace(
         return Ok(PatternReplaceResult {
             files_patched: Vec::new(),
             replacements_count: 0,
+            bytes_changed: 0,
             validation_errors: Vec::new(),
         });
     }
@@ -221,6 +262,7 @@ pub fn apply_pattern_replace(
     // Apply replacements per file
     let mut files_patched = Vec::new();
     let mut replacements_count = 0;
+    let mut bytes_changed = 0;
 
     for (file_path, file_matches) in matches_by_file {
         if file_matches.is_empty() {
@@ -242,6 +284,7 @@ pub fn apply_pattern_replace(
             // Replace the content
             content.replace_range(start_byte..end_byte, &config.replace_pattern);
             replacements_count += 1;
+            bytes_changed += (end_byte - start_byte) + config.replace_pattern.len();
         }
 
         // Write back
@@ -270,6 +313,7 @@ pub fn apply_pattern_replace(
                 workspace_dir,
                 lang,
                 AnalyzerMode::Off,
+                &crate::patch::ValidationOptions::default(),
             )?;
         }
     }
@@ -277,6 +321,7 @@ pub fn apply_pattern_replace(
     Ok(PatternReplaceResult {
         files_patched,
         replacements_count,
+        bytes_changed,
         validation_errors: Vec::new(),
     })
 }
@@ -304,7 +349,7 @@ fn foo() {
 "#,
         ).expect("Failed to write test file");
 
-        let matches = find_pattern_in_file(&test_file, "42", Language::Rust)
+        let matches = find_pattern_in_file(&test_file, "42", Language::Rust, false)
             .expect("Failed to find pattern");
 
         assert_eq!(matches.len(), 2, "Should find 2 occurrences of '42'");
@@ -328,10 +373,13 @@ def foo():
 
         let config = PatternReplaceConfig {
             glob_pattern: workspace_root.join("*.py").to_string_lossy().to_string(),
+            exclude_patterns: Vec::new(),
             find_pattern: "10".to_string(),
             replace_pattern: "20".to_string(),
             language: Some(Language::Python),
             validate: false,
+            max_file_size: crate::ingest::DEFAULT_MAX_FILE_SIZE,
+            identifier_only: false,
         };
 
         let result = apply_pattern_replace(&config, workspace_root)
@@ -343,4 +391,112 @@ def foo():
         let content = fs::read_to_string(&test_file).expect("Failed to read file");
         assert!(content.contains("20"), "Should contain replaced value");
     }
+
+    #[test]
+    fn test_find_pattern_skips_files_over_size_limit() {
+        let workspace = TempDir::new().expect("Failed to create temp dir");
+        let workspace_root = workspace.path();
+
+        fs::write(workspace_root.join("small.py"), "x = 10\n").expect("Failed to write file");
+        let padding = "# padding\n".repeat(20);
+        fs::write(
+            workspace_root.join("large.py"),
+            format!("{}x = 10\n", padding),
+        )
+        .expect("Failed to write file");
+
+        let config = PatternReplaceConfig {
+            glob_pattern: workspace_root.join("*.py").to_string_lossy().to_string(),
+            exclude_patterns: Vec::new(),
+            find_pattern: "10".to_string(),
+            replace_pattern: "20".to_string(),
+            language: Some(Language::Python),
+            validate: false,
+            max_file_size: 64,
+            identifier_only: false,
+        };
+
+        let matches = find_pattern_in_files(&config).expect("Failed to find pattern");
+
+        assert_eq!(matches.len(), 1, "Oversized file should be skipped");
+        assert!(matches[0].file.ends_with("small.py"));
+    }
+
+    #[test]
+    fn test_find_pattern_skips_excluded_files() {
+        let workspace = TempDir::new().expect("Failed to create temp dir");
+        let workspace_root = workspace.path();
+
+        fs::create_dir_all(workspace_root.join("src/generated")).expect("Failed to create dirs");
+        fs::write(workspace_root.join("src/lib.rs"), "fn a() -> u8 { 10 }\n")
+            .expect("Failed to write file");
+        fs::write(
+            workspace_root.join("src/generated/api.rs"),
+            "fn b() -> u8 { 10 }\n",
+        )
+        .expect("Failed to write file");
+
+        let config = PatternReplaceConfig {
+            glob_pattern: workspace_root
+                .join("src/**/*.rs")
+                .to_string_lossy()
+                .to_string(),
+            exclude_patterns: vec!["**/generated/**".to_string()],
+            find_pattern: "10".to_string(),
+            replace_pattern: "20".to_string(),
+            language: Some(Language::Rust),
+            validate: false,
+            max_file_size: crate::ingest::DEFAULT_MAX_FILE_SIZE,
+            identifier_only: false,
+        };
+
+        let matches = find_pattern_in_files(&config).expect("Failed to find pattern");
+
+        assert_eq!(matches.len(), 1, "Excluded file should be skipped");
+        assert!(matches[0].file.ends_with("src/lib.rs"));
+    }
+
+    #[test]
+    fn test_identifier_only_skips_substrings_and_strings() {
+        let workspace = TempDir::new().expect("Failed to create temp dir");
+        let workspace_root = workspace.path();
+
+        let test_file = workspace_root.join("lib.rs");
+        let source = r#"fn foo() {}
+
+fn foobar() -> &'static str {
+    foo();
+    // call foo here
+    "foo"
+}
+"#;
+        fs::write(&test_file, source).expect("Failed to write test file");
+
+        let mut config = PatternReplaceConfig {
+            glob_pattern: workspace_root.join("*.rs").to_string_lossy().to_string(),
+            exclude_patterns: Vec::new(),
+            find_pattern: "foo".to_string(),
+            replace_pattern: "baz".to_string(),
+            language: Some(Language::Rust),
+            validate: false,
+            max_file_size: crate::ingest::DEFAULT_MAX_FILE_SIZE,
+            identifier_only: true,
+        };
+
+        let matches = find_pattern_in_files(&config).expect("Failed to find pattern");
+        let lines: Vec<usize> = matches.iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![1, 4], "Only the definition and the call should match");
+
+        config.identifier_only = false;
+        let textual = find_pattern_in_files(&config).expect("Failed to find pattern");
+        assert!(textual.len() > matches.len());
+
+        config.identifier_only = true;
+        apply_pattern_replace(&config, workspace_root).expect("Failed to apply pattern replace");
+        let content = fs::read_to_string(&test_file).expect("Failed to read file");
+        assert!(content.starts_with("fn baz() {}"));
+        assert!(content.contains("fn foobar()"));
+        assert!(content.contains("    baz();"));
+        assert!(content.contains("\"foo\""));
+    }
 }
@@ -3,14 +3,16 @@
 //! This module provides plan parsing and execution for orchestrating
 //! multiple splice patch steps in a deterministic order.
 
+use crate::cli::SymbolKind;
 use crate::error::Result;
-use crate::ingest::rust::RustSymbolKind;
+use clap::ValueEnum;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 
 /// A refactoring plan containing multiple sequential patch steps.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Plan {
     /// Sequential patch steps to execute.
     pub steps: Vec<PatchStep>,
@@ -19,7 +21,7 @@ pub struct Plan {
 /// A single patch step in the plan.
 ///
 /// Each step is equivalent to one `splice patch` invocation.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PatchStep {
     /// Path to the source file containing the symbol.
     pub file: String,
@@ -36,6 +38,54 @@ pub struct PatchStep {
     pub with_file: String,
 }
 
+/// Outcome of validating a single plan step without applying it.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepValidation {
+    /// 1-based step number.
+    pub step: usize,
+
+    /// Source file named by the step (as written in the plan).
+    pub file: String,
+
+    /// Symbol named by the step.
+    pub symbol: String,
+
+    /// Error message if the step would fail to resolve, `None` if it is OK.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl StepValidation {
+    /// Returns true when the step resolved and its replacement file exists.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Outcome of applying a single plan step under `--keep-going`.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepOutcome {
+    /// 1-based step number.
+    pub step: usize,
+
+    /// Source file named by the step (as written in the plan).
+    pub file: String,
+
+    /// Symbol named by the step.
+    pub symbol: String,
+
+    /// Whether the step was applied.
+    pub applied: bool,
+
+    /// Success message for an applied step.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// Error message for a failed step.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 /// Parse a plan from a JSON file.
 ///
 /// # Arguments
@@ -84,19 +134,19 @@ pub fn parse_plan(plan_path: &Path) -> Result<Plan> {
 
         // Validate symbol kind if provided
         if let Some(ref kind) = step.symbol_kind {
-            match kind.as_str() {
-                "function" | "struct" | "enum" | "trait" | "impl" => {
-                    // Valid kind
-                }
-                _ => {
-                    return Err(crate::SpliceError::InvalidPlanSchema {
-                        message: format!(
-                            "Step {} has invalid 'kind': '{}'. Must be one of: function, struct, enum, trait, impl",
-                            i + 1,
-                            kind
-                        ),
-                    });
-                }
+            if SymbolKind::try_from(kind.as_str()).is_err() {
+                let valid: Vec<&str> = SymbolKind::value_variants()
+                    .iter()
+                    .map(|variant| <&str>::from(*variant))
+                    .collect();
+                return Err(crate::SpliceError::InvalidPlanSchema {
+                    message: format!(
+                        "Step {} has invalid 'kind': '{}'. Must be one of: {}",
+                        i + 1,
+                        kind,
+                        valid.join(", ")
+                    ),
+                });
             }
         }
     }
@@ -120,8 +170,6 @@ pub fn parse_plan(plan_path: &Path) -> Result<Plan> {
 /// * `Ok(Vec<String>)` - Success messages for each executed step
 /// * `Err(SpliceError)` - First error encountered during execution
 pub fn execute_plan(plan_path: &Path, workspace_dir: &Path) -> Result<Vec<String>> {
-    use crate::ingest::rust::RustSymbolKind;
-
     // Parse plan
     let plan = parse_plan(plan_path)?;
 
@@ -131,36 +179,7 @@ pub fn execute_plan(plan_path: &Path, workspace_dir: &Path) -> Result<Vec<String
     for (step_num, step) in plan.steps.iter().enumerate() {
         let step_index = step_num + 1;
 
-        // Resolve paths relative to workspace directory
-        let file_path = workspace_dir.join(&step.file);
-        let with_file_path = workspace_dir.join(&step.with_file);
-
-        // Convert CLI kind to RustSymbolKind
-        let rust_kind = match &step.symbol_kind {
-            None => None,
-            Some(kind) => Some(match kind.as_str() {
-                "function" => RustSymbolKind::Function,
-                "struct" => RustSymbolKind::Struct,
-                "enum" => RustSymbolKind::Enum,
-                "trait" => RustSymbolKind::Trait,
-                "impl" => RustSymbolKind::Impl,
-                _ => {
-                    return Err(crate::SpliceError::Other(format!(
-                        "Invalid symbol kind: {}",
-                        kind
-                    )));
-                }
-            }),
-        };
-
-        // Execute single patch step
-        match execute_single_step(
-            &file_path,
-            &step.symbol,
-            rust_kind,
-            &with_file_path,
-            workspace_dir,
-        ) {
+        match apply_step(step, workspace_dir) {
             Ok(msg) => {
                 println!("Step {}: {}", step_index, msg);
                 success_messages.push(msg);
@@ -177,53 +196,151 @@ pub fn execute_plan(plan_path: &Path, workspace_dir: &Path) -> Result<Vec<String
     Ok(success_messages)
 }
 
+/// Execute a plan, continuing past failing steps.
+///
+/// Unlike `execute_plan`, a failing step does not stop the run: its error is
+/// recorded and the remaining steps are still attempted. Each step is applied
+/// against the workspace as left by the steps before it, so a later step that
+/// depends on a failed one will usually fail too.
+///
+/// # Returns
+/// * `Ok(Vec<StepOutcome>)` - One outcome per step, in plan order
+/// * `Err(SpliceError)` - The plan itself could not be parsed
+pub fn execute_plan_keep_going(plan_path: &Path, workspace_dir: &Path) -> Result<Vec<StepOutcome>> {
+    let plan = parse_plan(plan_path)?;
+
+    let mut outcomes = Vec::new();
+    for (step_num, step) in plan.steps.iter().enumerate() {
+        let result = apply_step(step, workspace_dir);
+        outcomes.push(StepOutcome {
+            step: step_num + 1,
+            file: step.file.clone(),
+            symbol: step.symbol.clone(),
+            applied: result.is_ok(),
+            message: result.as_ref().ok().cloned(),
+            error: result.err().map(|e| e.to_string()),
+        });
+    }
+
+    Ok(outcomes)
+}
+
+/// Apply one step, resolving its paths relative to the workspace directory.
+fn apply_step(step: &PatchStep, workspace_dir: &Path) -> Result<String> {
+    let file_path = workspace_dir.join(&step.file);
+    let with_file_path = workspace_dir.join(&step.with_file);
+    let kind = step_kind(step)?;
+
+    execute_single_step(
+        &file_path,
+        &step.symbol,
+        kind,
+        &with_file_path,
+        workspace_dir,
+    )
+}
+
+/// Validate every step of a plan without mutating the workspace.
+///
+/// Each step is ingested and resolved exactly as `execute_plan` would, and its
+/// replacement file is read, but no patch is applied. Symbol resolution uses a
+/// throwaway graph database so nothing is written next to the source files.
+///
+/// # Returns
+/// * `Ok(Vec<StepValidation>)` - One outcome per step, in plan order
+/// * `Err(SpliceError)` - The plan itself could not be parsed
+pub fn validate_plan(plan_path: &Path, workspace_dir: &Path) -> Result<Vec<StepValidation>> {
+    let plan = parse_plan(plan_path)?;
+    let graph_db = tempfile::NamedTempFile::new()?;
+
+    let mut outcomes = Vec::new();
+    for (step_num, step) in plan.steps.iter().enumerate() {
+        let file_path = workspace_dir.join(&step.file);
+        let with_file_path = workspace_dir.join(&step.with_file);
+
+        let result = step_kind(step).and_then(|kind| {
+            resolve_step(&file_path, &step.symbol, kind, graph_db.path())?;
+            read_replacement(&with_file_path)
+        });
+
+        outcomes.push(StepValidation {
+            step: step_num + 1,
+            file: step.file.clone(),
+            symbol: step.symbol.clone(),
+            error: result.err().map(|e| e.to_string()),
+        });
+    }
+
+    Ok(outcomes)
+}
+
+/// Convert a step's optional kind string to the CLI symbol kind it names.
+fn step_kind(step: &PatchStep) -> Result<Option<SymbolKind>> {
+    step.symbol_kind
+        .as_deref()
+        .map(SymbolKind::try_from)
+        .transpose()
+}
+
+/// Ingest a step's file into the graph at `graph_db_path` and resolve its symbol.
+fn resolve_step(
+    file_path: &Path,
+    symbol_name: &str,
+    kind: Option<SymbolKind>,
+    graph_db_path: &Path,
+) -> Result<crate::resolve::ResolvedSpan> {
+    use crate::graph::{CodeGraph, SymbolRecord};
+    use crate::ingest::rust::extract_rust_symbols;
+    use crate::resolve::resolve_symbol;
+
+    // Read source file
+    let source = fs::read(file_path).map_err(|e| crate::SpliceError::Io {
+        path: file_path.to_path_buf(),
+        source: e,
+    })?;
+
+    // Extract symbols from source file (on-the-fly ingestion)
+    let symbols = extract_rust_symbols(file_path, &source)?;
+
+    // Store symbols in graph with language metadata
+    let mut code_graph = CodeGraph::open(graph_db_path)?;
+    for symbol in &symbols {
+        code_graph.store_symbol_record(file_path, &SymbolRecord::of(symbol))?;
+    }
+
+    // Resolve symbol to span
+    let kind_str = kind.map(<&str>::from);
+    resolve_symbol(&code_graph, Some(file_path), kind_str, symbol_name)
+}
+
+/// Read a step's replacement file, reporting the path on failure.
+fn read_replacement(replacement_file: &Path) -> Result<String> {
+    fs::read_to_string(replacement_file).map_err(|e| crate::SpliceError::Io {
+        path: replacement_file.to_path_buf(),
+        source: e,
+    })
+}
+
 /// Execute a single patch step.
 ///
 /// This is the core logic extracted from main.rs execute_patch.
 fn execute_single_step(
     file_path: &Path,
     symbol_name: &str,
-    kind: Option<RustSymbolKind>,
+    kind: Option<SymbolKind>,
     replacement_file: &Path,
     workspace_dir: &Path,
 ) -> Result<String> {
-    use crate::graph::CodeGraph;
-    use crate::ingest::rust::extract_rust_symbols;
     use crate::patch::apply_patch_with_validation;
-    use crate::resolve::resolve_symbol;
     use crate::symbol::Language;
     use crate::validate::AnalyzerMode;
 
-    // Step 1: Read source file
-    let source = std::fs::read(file_path)?;
-
-    // Step 2: Extract symbols from source file (on-the-fly ingestion)
-    let symbols = extract_rust_symbols(file_path, &source)?;
-
-    // Step 3: Create in-memory graph (no persistent database needed)
+    // Steps 1-6: Ingest the file and resolve the symbol to a span
     let graph_db_path = file_path.parent().unwrap().join(".splice_graph.db");
-    let mut code_graph = CodeGraph::open(&graph_db_path)?;
-
-    // Step 4: Store symbols in graph with language metadata
-    for symbol in &symbols {
-        code_graph.store_symbol_with_file_and_language(
-            file_path,
-            &symbol.name,
-            symbol.kind.as_str(),
-            Language::Rust,
-            symbol.byte_start,
-            symbol.byte_end,
-        )?;
-    }
-
-    // Step 5: Convert RustSymbolKind to string for resolution
-    let kind_str = kind.map(|k| k.as_str());
-
-    // Step 6: Resolve symbol to span
-    let resolved = resolve_symbol(&code_graph, Some(file_path), kind_str, symbol_name)?;
+    let resolved = resolve_step(file_path, symbol_name, kind, &graph_db_path)?;
 
     // Step 7: Read replacement content
-    let replacement_content = std::fs::read_to_string(replacement_file)?;
+    let replacement_content = read_replacement(replacement_file)?;
 
     // Step 8: Apply patch with validation (analyzer OFF for plan execution)
     let (before_hash, after_hash) = apply_patch_with_validation(
@@ -262,4 +379,30 @@ mod tests {
         let result = serde_json::from_str::<Plan>(r#"{"steps": []}"#).unwrap();
         assert!(result.steps.is_empty());
     }
+    #[test]
+    fn test_plan_accepts_every_cli_kind() {
+        let dir = tempfile::tempdir().unwrap();
+        let plan_path = dir.path().join("plan.json");
+        let write_plan = |kind: &str| {
+            let json = format!(
+                r#"{{"steps": [{{"file": "src/lib.rs", "symbol": "foo", "kind": "{}", "with": "patch.rs"}}]}}"#,
+                kind
+            );
+            fs::write(&plan_path, json).unwrap();
+        };
+
+        for &kind in SymbolKind::value_variants() {
+            let name: &str = kind.into();
+            assert_eq!(SymbolKind::try_from(name).unwrap(), kind);
+
+            write_plan(name);
+            let plan = parse_plan(&plan_path).unwrap();
+            assert_eq!(step_kind(&plan.steps[0]).unwrap(), Some(kind));
+        }
+
+        write_plan("widget");
+        let err = parse_plan(&plan_path).unwrap_err();
+        assert_eq!(err.kind(), "InvalidPlanSchema");
+        assert!(err.to_string().contains("type_alias"), "lists valid kinds: {}", err);
+    }
 }
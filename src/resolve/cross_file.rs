@@ -63,7 +63,7 @@ impl<'a> CrossFileResolver<'a> {
     /// # Arguments
     /// * `import` - Import fact to register
     pub fn add_import(&mut self, import: ImportFact) {
-        let file_path = import.file_path.to_str().unwrap_or("").to_string();
+        let file_path = import.file_path.to_string_lossy().into_owned();
         self.imports.entry(file_path).or_default().push(import);
     }
 
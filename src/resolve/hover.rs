@@ -0,0 +1,98 @@
+//! Graph-free lookup of the symbol under a cursor.
+//!
+//! An editor hover only needs the name, kind and span of the innermost
+//! symbol at a position. Extracting the file's symbols is enough for that,
+//! so this path skips storing them in the code graph and resolving there.
+
+use crate::error::{utf8_path, Result, SpliceError};
+use crate::ingest::dispatch::extract_symbols_with_options;
+use crate::ingest::ParseOptions;
+use crate::offset::{position_to_byte, PositionEncoding};
+use crate::symbol::{Language, Symbol};
+use serde::Serialize;
+use std::path::Path;
+
+/// The innermost symbol enclosing a position.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hover {
+    /// Symbol name.
+    pub name: String,
+    /// Symbol kind (function, class, etc.) as language-agnostic string.
+    pub kind: String,
+    /// Name qualified by its enclosing modules, classes or functions.
+    pub qualified_name: String,
+    /// Programming language (rust, python, etc.).
+    pub language: String,
+    /// File containing the symbol.
+    pub file_path: String,
+    /// Start byte offset.
+    pub byte_start: usize,
+    /// End byte offset.
+    pub byte_end: usize,
+    /// Start line (1-based).
+    pub line_start: usize,
+    /// End line (1-based).
+    pub line_end: usize,
+    /// Start column (0-based, in bytes).
+    pub col_start: usize,
+    /// End column (0-based, in bytes).
+    pub col_end: usize,
+}
+
+/// Find the innermost symbol in `file` enclosing `line`:`column`.
+///
+/// `column` is counted in `encoding` units. The language is detected from
+/// the extension unless given. `kind` restricts the match to symbols of that
+/// kind, so a cursor in a method body can still report its class. The file
+/// is parsed with `options`.
+pub fn hover(
+    file: &Path,
+    line: usize,
+    column: usize,
+    language: Option<Language>,
+    encoding: PositionEncoding,
+    kind: Option<&str>,
+    options: &ParseOptions,
+) -> Result<Hover> {
+    let language = language
+        .or_else(|| Language::from_path(file))
+        .ok_or_else(|| SpliceError::Parse {
+            file: file.to_path_buf(),
+            message: "Cannot detect language - unknown file extension".to_string(),
+        })?;
+    let source = std::fs::read(file)?;
+    let text = std::str::from_utf8(&source)?;
+    let byte = position_to_byte(text, line, column, encoding).ok_or_else(|| {
+        SpliceError::Other(format!(
+            "Position {}:{} is not a valid {:?} position in {}",
+            line,
+            column,
+            encoding,
+            file.display()
+        ))
+    })?;
+
+    let symbols = extract_symbols_with_options(file, &source, language, options)?;
+    let symbol = symbols
+        .iter()
+        .filter(|s| s.byte_start() <= byte && byte < s.byte_end())
+        .filter(|s| kind.is_none_or(|k| s.kind() == k))
+        .min_by_key(|s| s.byte_end() - s.byte_start())
+        .ok_or_else(|| {
+            SpliceError::symbol_not_found(format!("<symbol at {}:{}>", line, column), Some(file))
+        })?;
+
+    Ok(Hover {
+        name: symbol.name().to_string(),
+        kind: symbol.kind().to_string(),
+        qualified_name: symbol.fully_qualified().to_string(),
+        language: language.as_str().to_string(),
+        file_path: utf8_path(file)?.to_string(),
+        byte_start: symbol.byte_start(),
+        byte_end: symbol.byte_end(),
+        line_start: symbol.line_start(),
+        line_end: symbol.line_end(),
+        col_start: symbol.col_start(),
+        col_end: symbol.col_end(),
+    })
+}
@@ -5,18 +5,23 @@
 //! Supports multi-language code analysis.
 
 pub mod cross_file;
+pub mod hover;
 pub mod module_resolver;
 pub mod references;
 
-use crate::error::{Result, SpliceError};
+use crate::error::{utf8_path, Result, SpliceError};
 use crate::graph::CodeGraph;
+use crate::ingest::rust::macro_defining;
+use crate::symbol::{signatures_match, Language};
+use serde::Serialize;
 use sqlitegraph::NodeId;
 use std::path::Path;
 
 /// A resolved symbol with complete location information.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct ResolvedSpan {
-    /// Graph node ID for this symbol.
+    /// Graph node ID for this symbol (not serialized: only valid for this graph).
+    #[serde(skip)]
     pub node_id: NodeId,
 
     /// Symbol name.
@@ -28,6 +33,17 @@ pub struct ResolvedSpan {
     /// Programming language (rust, python, etc.).
     pub language: Option<String>,
 
+    /// Visibility modifier (`pub`, `pub(crate)`, `private`), when the language records one.
+    pub visibility: Option<String>,
+
+    /// Parameter types of an overloadable callable (Java, C++), e.g. `(int, String)`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+
+    /// Whether the symbol is test-only code (e.g., inside a Rust `#[cfg(test)]` module).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub in_test: bool,
+
     /// File containing this symbol.
     pub file_path: String,
 
@@ -62,21 +78,56 @@ pub struct ResolvedSpan {
 /// 1. If `file` is Some, resolves the symbol within that specific file
 /// 2. If `file` is None AND multiple matches exist across files → returns AmbiguousSymbol error
 /// 3. If `file` is None AND exactly one match exists globally → returns that symbol
+/// 4. Test-only symbols (inside a Rust `#[cfg(test)]` module) are only
+///    considered when no other symbol matches; see `resolve_symbol_in_scope`
 ///
 /// # Errors
-/// - `AmbiguousSymbol` - When name-only resolution finds multiple matches across files
+/// - `AmbiguousSymbol` - When name-only resolution finds multiple matches across files,
+///   or the name matches several overloads in the file
 /// - `SymbolNotFound` - When no symbol matches the query
 pub fn resolve_symbol(
     graph: &CodeGraph,
     file: Option<&Path>,
     kind: Option<&str>,
     name: &str,
+) -> Result<ResolvedSpan> {
+    resolve_symbol_with_signature(graph, file, kind, name, None)
+}
+
+/// Resolve a symbol like `resolve_symbol`, selecting an overload by signature.
+///
+/// `signature` is a parenthesized parameter type list such as `(int)` or
+/// `(const std::string&, int)`, compared ignoring whitespace. Only symbols
+/// stored with a signature (Java methods and constructors, C/C++ functions)
+/// can match one.
+pub fn resolve_symbol_with_signature(
+    graph: &CodeGraph,
+    file: Option<&Path>,
+    kind: Option<&str>,
+    name: &str,
+    signature: Option<&str>,
+) -> Result<ResolvedSpan> {
+    resolve_symbol_in_scope(graph, file, kind, name, signature, None)
+}
+
+/// Resolve a symbol like `resolve_symbol_with_signature`, choosing between
+/// test-only and regular symbols.
+///
+/// A `fn helper` in a `#[cfg(test)] mod tests` and a top-level `fn helper`
+/// are different symbols. `in_test` of `Some(true)` considers only test-only
+/// symbols and `Some(false)` only the others. With `None`, test-only symbols
+/// are dropped whenever a regular symbol also matches.
+pub fn resolve_symbol_in_scope(
+    graph: &CodeGraph,
+    file: Option<&Path>,
+    kind: Option<&str>,
+    name: &str,
+    signature: Option<&str>,
+    in_test: Option<bool>,
 ) -> Result<ResolvedSpan> {
     // Build cache key for lookup
     let _cache_key = if let Some(file_path) = file {
-        let file_str = file_path
-            .to_str()
-            .ok_or_else(|| SpliceError::Other(format!("Invalid UTF-8 in path: {:?}", file_path)))?;
+        let file_str = utf8_path(file_path)?;
         format!("{}::{}", file_str, name)
     } else {
         name.to_string()
@@ -84,11 +135,27 @@ pub fn resolve_symbol(
 
     // For file-specific resolution, use the cache directly
     if let Some(file_path) = file {
-        return resolve_symbol_in_file(graph, file_path, kind, name);
+        return resolve_symbol_in_file(graph, file_path, kind, name, signature, in_test);
     }
 
     // Name-only resolution: check for ambiguity
-    let all_matches = graph.find_symbols_by_name(name);
+    let mut all_matches = graph.find_symbols_by_name(name);
+    if let Some(wanted) = signature {
+        all_matches.retain(|(node_id, _)| {
+            graph
+                .inner()
+                .get_node(node_id.as_i64())
+                .ok()
+                .and_then(|node| node.data.get("signature")?.as_str().map(str::to_string))
+                .is_some_and(|found| signatures_match(&found, wanted))
+        });
+    }
+    let all_matches = select_test_scope(all_matches, in_test, |(node_id, _)| {
+        graph
+            .inner()
+            .get_node(node_id.as_i64())
+            .is_ok_and(|node| is_test_node(&node.data))
+    });
 
     if all_matches.is_empty() {
         return Err(SpliceError::symbol_not_found(name, None));
@@ -96,14 +163,17 @@ pub fn resolve_symbol(
 
     if all_matches.len() > 1 {
         // Multiple matches → ambiguous
-        let files: Vec<String> = all_matches
+        let mut files: Vec<String> = all_matches
             .into_iter()
             .filter_map(|(_id, path)| path)
             .collect();
+        files.sort();
 
         return Err(SpliceError::AmbiguousSymbol {
             name: name.to_string(),
             files,
+            signatures: Vec::new(),
+            kinds: Vec::new(),
         });
     }
 
@@ -145,6 +215,20 @@ pub fn resolve_symbol(
         .and_then(|v| v.as_str())
         .map(|s| s.to_string());
 
+    // Extract visibility (optional)
+    let visibility = node
+        .data
+        .get("visibility")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    let signature = node
+        .data
+        .get("signature")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+    let in_test = is_test_node(&node.data);
+
     // For now, we don't have line/col stored yet, use 0 as placeholders
     // TODO: Store line/col in graph during ingest
     Ok(ResolvedSpan {
@@ -152,6 +236,9 @@ pub fn resolve_symbol(
         name: name.to_string(),
         kind: kind_str,
         language,
+        visibility,
+        signature,
+        in_test,
         file_path: file_path_str,
         byte_start,
         byte_end,
@@ -162,25 +249,125 @@ pub fn resolve_symbol(
     })
 }
 
+/// `SymbolNotFound` for `name` in `file_path`, or `MacroGeneratedSymbol` when
+/// a Rust macro invocation in the file defines it.
+fn not_found_in_file(file_path: &Path, name: &str) -> SpliceError {
+    let generated_by = match Language::from_path(file_path) {
+        Some(Language::Rust) => std::fs::read(file_path)
+            .ok()
+            .and_then(|source| macro_defining(file_path, &source, name).ok().flatten()),
+        _ => None,
+    };
+    match generated_by {
+        Some(macro_name) => SpliceError::MacroGeneratedSymbol {
+            name: name.to_string(),
+            file: file_path.to_path_buf(),
+            macro_name,
+        },
+        None => SpliceError::symbol_not_found(name, Some(file_path)),
+    }
+}
+
 /// Resolve a symbol within a specific file.
+///
+/// Among same-named symbols, the first one matching `kind`, `signature` and
+/// `in_test` wins, unless the remaining candidates are overloads with
+/// different signatures.
 fn resolve_symbol_in_file(
     graph: &CodeGraph,
     file_path: &Path,
     kind: Option<&str>,
     name: &str,
+    signature: Option<&str>,
+    in_test: Option<bool>,
 ) -> Result<ResolvedSpan> {
-    let file_str = file_path
-        .to_str()
-        .ok_or_else(|| SpliceError::Other(format!("Invalid UTF-8 in path: {:?}", file_path)))?;
+    let file_str = utf8_path(file_path)?;
+
+    // Use the cache-based lookup from CodeGraph, keeping symbols that pass the filters
+    let mut candidates = Vec::new();
+    let mut signatures: Vec<String> = Vec::new();
+    for &node_id in graph.find_symbols_in_file(file_str, name) {
+        let node = graph.inner().get_node(node_id.as_i64())?;
+        let node_kind = node.data.get("kind").and_then(|v| v.as_str());
+        if kind.is_some_and(|k| node_kind != Some(k)) {
+            continue;
+        }
+        if in_test.is_some_and(|wanted| is_test_node(&node.data) != wanted) {
+            continue;
+        }
+        let node_signature = node.data.get("signature").and_then(|v| v.as_str());
+        if let Some(wanted) = signature {
+            if !node_signature.is_some_and(|found| signatures_match(found, wanted)) {
+                continue;
+            }
+        }
+        if let Some(found) = node_signature {
+            let overload = format!("{}{}", name, found);
+            if !signatures.contains(&overload) {
+                signatures.push(overload);
+            }
+        }
+        candidates.push((node_id, node));
+    }
+    let candidates = select_test_scope(candidates, in_test, |(_, node)| is_test_node(&node.data));
 
-    // Use the cache-based lookup from CodeGraph
-    let node_id = graph
-        .find_symbol_in_file(file_str, name)
-        .ok_or_else(|| SpliceError::symbol_not_found(name, Some(file_path)))?;
+    // Overloads differ only in their parameters; picking one silently would patch the wrong body
+    if signatures.len() > 1 {
+        return Err(SpliceError::AmbiguousSymbol {
+            name: name.to_string(),
+            files: vec![file_str.to_string()],
+            signatures,
+            kinds: Vec::new(),
+        });
+    }
 
-    // Get node data from graph
-    let node = graph.inner().get_node(node_id.as_i64())?;
+    // Likewise a name shared by, say, a class and a function needs `--kind`
+    if kind.is_none() {
+        let kinds = distinct_kinds(file_str, &candidates);
+        if !kinds.is_empty() {
+            return Err(SpliceError::AmbiguousSymbol {
+                name: name.to_string(),
+                files: vec![file_str.to_string()],
+                signatures: Vec::new(),
+                kinds,
+            });
+        }
+    }
 
+    let (node_id, node) = candidates
+        .into_iter()
+        .next()
+        .ok_or_else(|| not_found_in_file(file_path, name))?;
+    resolved_span(node_id, &node, name)
+}
+
+/// Resolve the symbol named `name` in `file_path` whose span starts at `byte_start`.
+///
+/// For callers that have already picked one definition among several sharing
+/// a name and kind, such as a method chosen by its qualified name.
+pub fn resolve_symbol_at(
+    graph: &CodeGraph,
+    file_path: &Path,
+    name: &str,
+    byte_start: usize,
+) -> Result<ResolvedSpan> {
+    let file_str = utf8_path(file_path)?;
+    for &node_id in graph.find_symbols_in_file(file_str, name) {
+        let node = graph.inner().get_node(node_id.as_i64())?;
+        let start = node.data.get("byte_start").and_then(|v| v.as_u64());
+        if start == Some(byte_start as u64) {
+            return resolved_span(node_id, &node, name);
+        }
+    }
+    Err(not_found_in_file(file_path, name))
+}
+
+/// Build the span of a stored symbol node.
+fn resolved_span(
+    node_id: NodeId,
+    node: &sqlitegraph::GraphEntity,
+    name: &str,
+) -> Result<ResolvedSpan> {
     // Extract span data
     let byte_start = node
         .data
@@ -211,12 +398,12 @@ fn resolve_symbol_in_file(
         .and_then(|v| v.as_str())
         .map(|s| s.to_string());
 
-    // Filter by kind if specified
-    if let Some(k) = kind {
-        if kind_str != k {
-            return Err(SpliceError::symbol_not_found(name, Some(file_path)));
-        }
-    }
+    // Extract visibility (optional)
+    let visibility = node
+        .data
+        .get("visibility")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
 
     // Get file path from node
     let node_file_path = node
@@ -226,12 +413,22 @@ fn resolve_symbol_in_file(
         .ok_or_else(|| SpliceError::Other("Missing file_path property".to_string()))?
         .to_string();
 
+    let signature = node
+        .data
+        .get("signature")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+    let in_test = is_test_node(&node.data);
+
     // TODO: Return actual line/col when we store it
     Ok(ResolvedSpan {
         node_id,
         name: name.to_string(),
         kind: kind_str,
         language,
+        visibility,
+        signature,
+        in_test,
         file_path: node_file_path,
         byte_start,
         byte_end,
@@ -242,6 +439,93 @@ fn resolve_symbol_in_file(
     })
 }
 
+/// Kind and location of each candidate (`struct at src/lib.rs:3`), if the
+/// candidates are of more than one kind; empty otherwise.
+///
+/// Impl blocks and constructors share their type's name without competing
+/// with it, and neither does a candidate nested inside another (a Java
+/// constructor inside its class), so those are left out.
+fn distinct_kinds(file: &str, candidates: &[(NodeId, sqlitegraph::GraphEntity)]) -> Vec<String> {
+    let spans: Vec<(&str, u64, u64)> = candidates
+        .iter()
+        .filter_map(|(_, node)| {
+            let kind = node.data.get("kind")?.as_str()?;
+            let start = node.data.get("byte_start")?.as_u64()?;
+            let end = node.data.get("byte_end")?.as_u64()?;
+            Some((kind, start, end))
+        })
+        .filter(|(kind, _, _)| !matches!(*kind, "impl" | "constructor"))
+        .collect();
+    let competing: Vec<(&str, u64)> = spans
+        .iter()
+        .filter(|(_, start, end)| {
+            !spans
+                .iter()
+                .any(|(_, s, e)| (s, e) != (start, end) && s <= start && end <= e)
+        })
+        .map(|(kind, start, _)| (*kind, *start))
+        .collect();
+    if competing.iter().all(|(kind, _)| *kind == competing[0].0) {
+        return Vec::new();
+    }
+
+    let source = std::fs::read(file).unwrap_or_default();
+    competing
+        .into_iter()
+        .map(|(kind, start)| match source.get(..start as usize) {
+            Some(before) => {
+                let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+                format!("{} at {}:{}", kind, file, line)
+            }
+            None => format!("{} at {} byte {}", kind, file, start),
+        })
+        .collect()
+}
+
+/// Whether a stored symbol node is test-only code.
+fn is_test_node(data: &serde_json::Value) -> bool {
+    matches!(data.get("in_test"), Some(serde_json::Value::Bool(true)))
+}
+
+/// Keep the candidates `in_test` asks for.
+///
+/// `Some(wanted)` keeps the candidates whose test flag equals `wanted`; `None`
+/// keeps every candidate unless a regular one exists, in which case the
+/// test-only ones are dropped.
+fn select_test_scope<T>(
+    mut candidates: Vec<T>,
+    in_test: Option<bool>,
+    is_test: impl Fn(&T) -> bool,
+) -> Vec<T> {
+    let wanted = match in_test {
+        Some(wanted) => wanted,
+        None if candidates.iter().any(|c| !is_test(c)) => false,
+        None => return candidates,
+    };
+    candidates.retain(|c| is_test(c) == wanted);
+    candidates
+}
+
+/// Refuse a resolution whose stored file is not `file`.
+///
+/// Symbols stored through the deprecated paths can carry a `file_path` that
+/// differs from the file they were looked up under; patching such a span
+/// would edit the wrong file. Paths are compared after canonicalization, so
+/// `./src/lib.rs` and an absolute path to the same file agree.
+pub fn ensure_resolved_in_file(resolved: &ResolvedSpan, file: &Path) -> Result<()> {
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let resolved_file = Path::new(&resolved.file_path);
+    if canonical(resolved_file) != canonical(file) {
+        return Err(SpliceError::Other(format!(
+            "Symbol '{}' resolved to {}, not the requested file {}",
+            resolved.name,
+            resolved_file.display(),
+            file.display()
+        )));
+    }
+    Ok(())
+}
+
 /// Backward compatibility: Resolve with Rust-specific symbol kind.
 ///
 /// This function is provided for backward compatibility with existing code
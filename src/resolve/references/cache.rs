@@ -0,0 +1,192 @@
+//! On-disk cache of the last computed `ReferenceSet`.
+//!
+//! A reference scan reads and parses every Rust file in the workspace. The
+//! "preview then apply" workflow (`delete --dry-run` followed by `delete`)
+//! would otherwise do that twice, so the dry run stores its result here and
+//! the real delete reuses it when nothing in the workspace has changed.
+//!
+//! The cache holds a single entry, keyed by the symbol, a content hash of the
+//! definition file, and a fingerprint of the workspace's Rust files (path,
+//! size, and modification time of each). Any edit, added, or removed file
+//! changes the key and the entry is ignored.
+
+use crate::error::{Result, SpliceError};
+use crate::ingest::rust::RustSymbolKind;
+use crate::resolve::references::rust::{find_all_rust_files, find_workspace_root};
+use crate::resolve::references::ReferenceSet;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Directory (under the workspace root) holding the cache file.
+const CACHE_DIR: &str = ".splice-cache";
+
+/// Cache file name.
+const CACHE_FILE: &str = "references.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    key: String,
+    references: ReferenceSet,
+}
+
+/// Path of the reference cache for the workspace containing `file_path`.
+///
+/// Returns `None` if the file is not inside a Cargo workspace.
+pub fn reference_cache_path(file_path: &Path) -> Option<PathBuf> {
+    let root = find_workspace_root(file_path).ok()?;
+    Some(root.join(CACHE_DIR).join(CACHE_FILE))
+}
+
+/// Store `references` as the cached result for this lookup.
+///
+/// Does nothing outside a Cargo workspace.
+pub fn store_cached_references(
+    file_path: &Path,
+    symbol_name: &str,
+    symbol_kind: Option<RustSymbolKind>,
+    max_file_size: u64,
+    references: &ReferenceSet,
+) -> Result<()> {
+    let Some(cache_path) = reference_cache_path(file_path) else {
+        return Ok(());
+    };
+    let key = cache_key(file_path, symbol_name, symbol_kind, max_file_size)?;
+    let entry = CacheEntry {
+        key,
+        references: references.clone(),
+    };
+    let json = serde_json::to_vec(&entry)
+        .map_err(|e| SpliceError::Other(format!("Failed to serialize reference cache: {}", e)))?;
+
+    // Written beside the cache and renamed over it, so a reader never sees a
+    // partial entry
+    let dir = cache_path.parent().unwrap_or(Path::new("."));
+    std::fs::create_dir_all(dir)?;
+    let mut temp = tempfile::NamedTempFile::new_in(dir)?;
+    temp.write_all(&json)?;
+    temp.persist(&cache_path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Load the cached result for this lookup, if it is still valid.
+///
+/// Returns `Ok(None)` when there is no cache, the entry belongs to another
+/// lookup, or any workspace file changed since it was stored.
+pub fn load_cached_references(
+    file_path: &Path,
+    symbol_name: &str,
+    symbol_kind: Option<RustSymbolKind>,
+    max_file_size: u64,
+) -> Result<Option<ReferenceSet>> {
+    let Some(cache_path) = reference_cache_path(file_path) else {
+        return Ok(None);
+    };
+    let Ok(data) = std::fs::read(&cache_path) else {
+        return Ok(None);
+    };
+    let entry: CacheEntry = match serde_json::from_slice(&data) {
+        Ok(entry) => entry,
+        Err(err) => {
+            log::warn!("Ignoring unreadable reference cache {}: {}", cache_path.display(), err);
+            return Ok(None);
+        }
+    };
+
+    let key = cache_key(file_path, symbol_name, symbol_kind, max_file_size)?;
+    Ok((entry.key == key).then_some(entry.references))
+}
+
+/// Compute the cache key for a lookup against the current workspace state.
+fn cache_key(
+    file_path: &Path,
+    symbol_name: &str,
+    symbol_kind: Option<RustSymbolKind>,
+    max_file_size: u64,
+) -> Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(symbol_name.as_bytes());
+    hasher.update([0]);
+    hasher.update(symbol_kind.map_or("", |k| k.as_str()).as_bytes());
+    hasher.update([0]);
+    hasher.update(max_file_size.to_le_bytes());
+    hasher.update(file_path.to_string_lossy().as_bytes());
+    hasher.update([0]);
+    hasher.update(Sha256::digest(std::fs::read(file_path)?));
+
+    let workspace_root = find_workspace_root(file_path)?;
+    let mut files = find_all_rust_files(&workspace_root, max_file_size)?;
+    files.sort();
+    for file in files {
+        // Unreadable files still contribute their path, so they can't be missed
+        let (len, modified) = std::fs::metadata(&file).map_or((0, 0), |metadata| {
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_nanos());
+            (metadata.len(), modified)
+        });
+        hasher.update(file.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(len.to_le_bytes());
+        hasher.update(modified.to_le_bytes());
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::CodeGraph;
+    use crate::resolve::references::find_references;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cache_hit_when_unchanged_and_miss_after_edit() {
+        let workspace = TempDir::new().unwrap();
+        // The Rust file scan skips hidden directories, including `.tmpXXXX` roots
+        let root = &workspace.path().join("project");
+        std::fs::create_dir(root).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"cache-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir(root.join("src")).unwrap();
+        let lib = root.join("src/lib.rs");
+        std::fs::write(&lib, "pub mod other;\npub fn helper() {}\n").unwrap();
+        let other = root.join("src/other.rs");
+        std::fs::write(&other, "use crate::helper;\npub fn call() { helper(); }\n").unwrap();
+
+        let graph = CodeGraph::open(&root.join("graph.db")).unwrap();
+        let refs = find_references(&graph, &lib, "helper", None).unwrap();
+        assert!(!refs.references.is_empty());
+
+        assert!(load_cached_references(&lib, "helper", None, 1024 * 1024)
+            .unwrap()
+            .is_none());
+        store_cached_references(&lib, "helper", None, 1024 * 1024, &refs).unwrap();
+
+        let cached = load_cached_references(&lib, "helper", None, 1024 * 1024)
+            .unwrap()
+            .expect("unchanged workspace should hit the cache");
+        assert_eq!(cached.references, refs.references);
+        assert_eq!(cached.definition.byte_start, refs.definition.byte_start);
+
+        // A different symbol does not reuse the entry
+        assert!(load_cached_references(&lib, "call", None, 1024 * 1024)
+            .unwrap()
+            .is_none());
+
+        // Any change to a workspace file invalidates it
+        std::fs::write(&other, "use crate::helper;\npub fn call() { helper(); helper(); }\n")
+            .unwrap();
+        assert!(load_cached_references(&lib, "helper", None, 1024 * 1024)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,134 @@
+//! Former symbol names recovered from git history.
+//!
+//! After a rename, stale usages of the old name can survive in code the
+//! rename missed. This module scans `git log -p` of the definition file for
+//! lines where one identifier was swapped for another, so reference finding
+//! can also search for the names a symbol used to have.
+
+use crate::error::{Result, SpliceError};
+use crate::ingest::diff::run_git;
+use std::path::Path;
+
+/// Names that `current_name` was renamed from in the history of `file_path`.
+///
+/// A rename is recognized when a removed line and the added line paired with
+/// it in the same hunk are identical except that every differing identifier
+/// changed from one old name to a known name. Chains (`a` → `b` → `current`)
+/// are followed. Names are returned most recent first.
+pub fn former_names(file_path: &Path, current_name: &str) -> Result<Vec<String>> {
+    // git runs in the file's directory, so the pathspec is just its name
+    let path = std::path::absolute(file_path)?;
+    let (Some(dir), Some(file)) = (path.parent(), path.file_name()) else {
+        return Err(SpliceError::Other(format!(
+            "No parent directory for {}",
+            file_path.display()
+        )));
+    };
+    let file = file.to_string_lossy();
+    let log = run_git(
+        dir,
+        &["log", "-p", "-U0", "--no-color", "--format=", "--", &file],
+    )?;
+    Ok(former_names_in_log(
+        &String::from_utf8_lossy(&log),
+        current_name,
+    ))
+}
+
+/// Scan `git log -p` output (newest commit first) for renames into `current_name`.
+fn former_names_in_log(log: &str, current_name: &str) -> Vec<String> {
+    let mut known = vec![current_name.to_string()];
+    let mut removed: Vec<&str> = Vec::new();
+    let mut added: Vec<&str> = Vec::new();
+
+    for line in log.lines().chain(std::iter::once("@@")) {
+        if line.starts_with("@@") || line.starts_with("diff ") {
+            for (old, new) in removed.iter().zip(&added) {
+                if let Some(name) = renamed_identifier(old, new, &known) {
+                    known.push(name);
+                }
+            }
+            removed.clear();
+            added.clear();
+        } else if let Some(text) = line.strip_prefix('-') {
+            if !line.starts_with("---") {
+                removed.push(text);
+            }
+        } else if let Some(text) = line.strip_prefix('+') {
+            if !line.starts_with("+++") {
+                added.push(text);
+            }
+        }
+    }
+
+    known.remove(0);
+    known
+}
+
+/// The old identifier if `new` is `old` with one identifier renamed to a known name.
+fn renamed_identifier(old: &str, new: &str, known: &[String]) -> Option<String> {
+    let old_tokens = identifiers(old);
+    let new_tokens = identifiers(new);
+    if old_tokens.len() != new_tokens.len() || non_identifiers(old) != non_identifiers(new) {
+        return None;
+    }
+
+    let mut rename: Option<(&str, &str)> = None;
+    for (&before, &after) in old_tokens.iter().zip(&new_tokens) {
+        if before == after {
+            continue;
+        }
+        match rename {
+            None => rename = Some((before, after)),
+            Some(pair) if pair == (before, after) => {}
+            Some(_) => return None,
+        }
+    }
+
+    let (before, after) = rename?;
+    let is_new = !known.iter().any(|name| name == before);
+    (is_new && known.iter().any(|name| name == after)).then(|| before.to_string())
+}
+
+/// Identifier-like tokens of a line, in order.
+fn identifiers(line: &str) -> Vec<&str> {
+    line.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|token| token.starts_with(|c: char| c.is_alphabetic() || c == '_'))
+        .collect()
+}
+
+/// Everything in a line except identifier characters, to compare line shapes.
+fn non_identifiers(line: &str) -> String {
+    line.chars()
+        .filter(|c| !(c.is_alphanumeric() || *c == '_'))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_former_names_follow_rename_chain() {
+        // Newest commit first, as `git log -p` prints it
+        let log = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1 +1 @@
+-pub fn middle(x: i32) -> i32 {
++pub fn current(x: i32) -> i32 {
+@@ -5 +5 @@
+-    let y = 2;
++    let z = 3;
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1 +1 @@
+-pub fn first(x: i32) -> i32 {
++pub fn middle(x: i32) -> i32 {
+";
+        assert_eq!(former_names_in_log(log, "current"), vec!["middle", "first"]);
+        assert!(former_names_in_log(log, "unrelated").is_empty());
+    }
+}
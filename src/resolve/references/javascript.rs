@@ -0,0 +1,246 @@
+//! JavaScript and TypeScript reference finding using tree-sitter.
+//!
+//! Only the defining file is searched; `import` specifiers are not resolved to
+//! files yet. JSX is part of the walk: a component rendered as `<Foo />` or
+//! `<Foo>...</Foo>` is a reference, and so is any name inside an embedded
+//! `{...}` expression, such as the call in `{helper()}`.
+
+use crate::error::{utf8_path, Result, SpliceError};
+use crate::ingest::dispatch::extract_symbols_with_options;
+use crate::ingest::rust::RustSymbolKind;
+use crate::ingest::source::SourceFile;
+use crate::ingest::{exclude_set, is_excluded};
+use crate::resolve::references::rust::reference_at;
+use crate::resolve::references::{
+    Reference, ReferenceContext, ReferenceScanOptions, ReferenceSet, SymbolDefinition,
+};
+use crate::symbol::{AnySymbol, Language, Symbol};
+use std::path::Path;
+use tree_sitter::Node;
+
+/// Find references to a JavaScript or TypeScript symbol in its own file.
+///
+/// # Algorithm
+/// 1. Find the definition among the file's symbols
+/// 2. Walk the tree, skipping every scope that declares its own binding of
+///    the name (parameters, `let`/`const`/`var`, nested functions and classes)
+/// 3. Collect identifiers with the name, including JSX element names and
+///    identifiers inside JSX expressions
+///
+/// Lowercase JSX element names are intrinsic elements such as `<div>`, not
+/// references. `options` supplies the parse limits and `exclude` globs; there
+/// is no cross-file scan to bound.
+pub fn find_javascript_references_with_options(
+    file_path: &Path,
+    symbol_name: &str,
+    symbol_kind: Option<RustSymbolKind>,
+    options: &ReferenceScanOptions,
+) -> Result<ReferenceSet> {
+    let definition_path = utf8_path(file_path)?;
+    let language = Language::from_path(file_path).unwrap_or(Language::JavaScript);
+    let file = SourceFile::read(file_path, language)?.with_parse_options(options.parse);
+
+    let symbols = extract_symbols_with_options(file_path, file.bytes(), language, &options.parse)?;
+    let target = symbols
+        .iter()
+        .find(|s| {
+            s.name() == symbol_name && symbol_kind.is_none_or(|k| definition_kind(s.kind()) == k)
+        })
+        .ok_or_else(|| SpliceError::symbol_not_found(symbol_name, Some(file_path)))?;
+    let definition = target.byte_start()..target.byte_end();
+
+    let (source, rope, tree) = (file.bytes(), file.rope()?, file.tree()?);
+    let mut references: Vec<Reference> = Vec::new();
+    let mut pending = vec![tree.root_node()];
+    while let Some(node) = pending.pop() {
+        let declared = scope_bindings(node).into_iter().any(|name| {
+            text(name, source) == symbol_name && !definition.contains(&name.start_byte())
+        });
+        if declared {
+            continue;
+        }
+        if let Some(context) = reference_context(node, source, symbol_name) {
+            references.push(reference_at(node, rope, file_path, context));
+            continue;
+        }
+        let mut cursor = node.walk();
+        pending.extend(node.children(&mut cursor));
+    }
+
+    let excludes = exclude_set(&options.exclude)?;
+    references.retain(|r| !is_excluded(&excludes, Path::new(&r.file_path)));
+    references.sort_by_key(|r| std::cmp::Reverse(r.byte_start));
+
+    Ok(ReferenceSet {
+        references,
+        definition: SymbolDefinition {
+            name: target.name().to_string(),
+            kind: definition_kind(target.kind()),
+            file_path: definition_path.to_string(),
+            byte_start: target.byte_start(),
+            byte_end: target.byte_end(),
+            is_public: is_exported(target),
+        },
+        has_glob_ambiguity: false,
+        skipped_files: Vec::new(),
+        public_reexports: Vec::new(),
+    })
+}
+
+/// Closest Rust kind for a JavaScript or TypeScript symbol kind, so the
+/// definition fits [`SymbolDefinition`] and the `symbol_kind` filter.
+fn definition_kind(kind: &str) -> RustSymbolKind {
+    match kind {
+        "function" | "method" | "arrow_function" => RustSymbolKind::Function,
+        "class" => RustSymbolKind::Struct,
+        "interface" => RustSymbolKind::Trait,
+        "type_alias" => RustSymbolKind::TypeAlias,
+        "enum" => RustSymbolKind::Enum,
+        "namespace" => RustSymbolKind::Module,
+        _ => RustSymbolKind::Const,
+    }
+}
+
+fn is_exported(symbol: &AnySymbol) -> bool {
+    match symbol {
+        AnySymbol::JavaScript(symbol) => symbol.is_exported,
+        AnySymbol::TypeScript(symbol) => symbol.is_exported,
+        _ => false,
+    }
+}
+
+fn text<'a>(node: Node, source: &'a [u8]) -> &'a str {
+    node.utf8_text(source).unwrap_or("")
+}
+
+/// Context of `node` if it is an identifier naming `name`.
+fn reference_context(node: Node, source: &[u8], name: &str) -> Option<ReferenceContext> {
+    if !matches!(
+        node.kind(),
+        "identifier" | "type_identifier" | "shorthand_property_identifier"
+    ) || text(node, source) != name
+    {
+        return None;
+    }
+    let parent = node.parent()?;
+    let is_field = |field: &str| parent.child_by_field_name(field) == Some(node);
+
+    match parent.kind() {
+        // The name of a declaration is a binding, not a use
+        "function_declaration"
+        | "generator_function_declaration"
+        | "function_expression"
+        | "class_declaration"
+        | "abstract_class_declaration"
+        | "class"
+        | "interface_declaration"
+        | "type_alias_declaration"
+        | "enum_declaration"
+        | "internal_module"
+        | "variable_declarator"
+            if is_field("name") =>
+        {
+            None
+        }
+        "jsx_opening_element" | "jsx_closing_element" | "jsx_self_closing_element"
+            if is_field("name") =>
+        {
+            // Lowercase names are intrinsic elements like `<div>`
+            name.starts_with(|c: char| !c.is_ascii_lowercase())
+                .then_some(ReferenceContext::JsxElement)
+        }
+        "call_expression" if is_field("function") => Some(ReferenceContext::FunctionCall {
+            is_qualified: false,
+        }),
+        "import_specifier" | "export_specifier" | "import_clause" | "namespace_import" => {
+            Some(ReferenceContext::ImportStatement)
+        }
+        _ if node.kind() == "type_identifier" => Some(ReferenceContext::TypeReference),
+        _ => Some(ReferenceContext::Identifier),
+    }
+}
+
+/// Name nodes of the bindings `scope` introduces for its whole extent.
+///
+/// Blocks declare their `let`/`const`/`var`, functions and classes; functions
+/// declare their parameters; `catch` and `for` declare their bindings.
+fn scope_bindings(scope: Node) -> Vec<Node> {
+    let mut patterns = Vec::new();
+    let mut cursor = scope.walk();
+    match scope.kind() {
+        "program" | "statement_block" => {
+            for child in scope.named_children(&mut cursor) {
+                let declaration = match child.kind() {
+                    "export_statement" => child.child_by_field_name("declaration"),
+                    _ => Some(child),
+                };
+                let Some(declaration) = declaration else {
+                    continue;
+                };
+                match declaration.kind() {
+                    "lexical_declaration" | "variable_declaration" => {
+                        let mut declarators = declaration.walk();
+                        patterns.extend(
+                            declaration
+                                .named_children(&mut declarators)
+                                .filter_map(|d| d.child_by_field_name("name")),
+                        );
+                    }
+                    "function_declaration"
+                    | "generator_function_declaration"
+                    | "class_declaration"
+                    | "abstract_class_declaration" => {
+                        patterns.extend(declaration.child_by_field_name("name"));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        "function_declaration"
+        | "generator_function_declaration"
+        | "function_expression"
+        | "generator_function"
+        | "arrow_function"
+        | "method_definition" => {
+            patterns.extend(scope.child_by_field_name("parameters"));
+            // `x => ...` has a single bare parameter
+            patterns.extend(scope.child_by_field_name("parameter"));
+        }
+        "catch_clause" => patterns.extend(scope.child_by_field_name("parameter")),
+        "for_in_statement" if scope.child_by_field_name("kind").is_some() => {
+            patterns.extend(scope.child_by_field_name("left"));
+        }
+        "for_statement" => {
+            if let Some(initializer) = scope.child_by_field_name("initializer") {
+                let mut declarators = initializer.walk();
+                patterns.extend(
+                    initializer
+                        .named_children(&mut declarators)
+                        .filter_map(|d| d.child_by_field_name("name")),
+                );
+            }
+        }
+        _ => {}
+    }
+
+    // Destructuring and parameter lists bind every identifier in the pattern,
+    // but not those in type annotations or default values
+    let mut names = Vec::new();
+    while let Some(pattern) = patterns.pop() {
+        match pattern.kind() {
+            "identifier" | "shorthand_property_identifier_pattern" => names.push(pattern),
+            _ => {
+                let mut children = pattern.walk();
+                for (index, child) in pattern.children(&mut children).enumerate() {
+                    let field = pattern.field_name_for_child(index as u32);
+                    if child.is_named()
+                        && !matches!(field, Some("type" | "value" | "right" | "key"))
+                    {
+                        patterns.push(child);
+                    }
+                }
+            }
+        }
+    }
+    names
+}
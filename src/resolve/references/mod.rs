@@ -12,14 +12,26 @@
 //! - **Shadowing**: Local definitions that shadow imported symbols
 //! - **Visibility**: Private symbols only have same-file references
 
+pub mod cache;
+pub mod history;
+pub mod javascript;
+pub mod rename;
 pub mod rust;
+pub mod unreferenced;
 
-use crate::error::Result;
+use crate::error::{Result, SpliceError};
 use crate::ingest::rust::RustSymbolKind;
-use std::path::Path;
+use crate::ingest::ParseOptions;
+use crate::symbol::Language;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Tab width used for [`Reference::column_display`].
+pub const DEFAULT_TAB_WIDTH: usize = 4;
 
 /// A reference to a symbol found in source code.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Reference {
     /// File containing the reference.
     pub file_path: String,
@@ -34,14 +46,28 @@ pub struct Reference {
     pub line: usize,
 
     /// Column number (0-based, in bytes).
-    pub column: usize,
+    pub column_byte: usize,
+
+    /// Column number (0-based, in Unicode scalar values).
+    pub column_char: usize,
+
+    /// Column an editor shows (0-based), expanding tabs to the next multiple of
+    /// [`DEFAULT_TAB_WIDTH`]. Use [`display_column`] for another tab width.
+    #[serde(default)]
+    pub column_display: usize,
 
     /// Context around the reference for verification.
     pub context: ReferenceContext,
+
+    /// Local name the reference uses when the file imports the symbol as
+    /// `use path::name as alias`. The span covers the alias, not the symbol's
+    /// own name, so a rename leaves it unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
 }
 
 /// Context information about a reference.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ReferenceContext {
     /// Function call: `foo()` or `module::foo()`
     FunctionCall {
@@ -59,14 +85,41 @@ pub enum ReferenceContext {
     ImportStatement,
 
     /// Field access: `struct.field` (for struct field references)
-    FieldAccess,
+    FieldAccess {
+        /// Whether the receiver is known to have the owning struct's type.
+        /// Without type inference only `self` and locals bound with the type
+        /// written out can be checked; accesses through any other receiver are
+        /// reported unverified, since another struct may share the field name.
+        is_verified: bool,
+    },
+
+    /// Field in a struct literal: `Struct { field: value }` or `Struct { field }`
+    FieldInitializer {
+        /// Whether written in shorthand form, where the span also names the value
+        is_shorthand: bool,
+    },
+
+    /// Field in a struct pattern: `Struct { field: pat }` or `Struct { field }`
+    FieldPattern {
+        /// Whether written in shorthand form, where the span also names the binding
+        is_shorthand: bool,
+    },
 
     /// Generic type parameter: `foo<T>()`
     GenericParameter,
+
+    /// Macro invocation: `foo!(...)` or `crate::foo!(...)`
+    MacroInvocation {
+        /// Whether invoked through a path (e.g., `crate::foo!()`)
+        is_qualified: bool,
+    },
+
+    /// Component rendered as a JSX element: `<Foo />` or `<Foo>...</Foo>`
+    JsxElement,
 }
 
 /// Result of finding references to a symbol.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReferenceSet {
     /// All references found (including same-file and cross-file).
     pub references: Vec<Reference>,
@@ -77,10 +130,30 @@ pub struct ReferenceSet {
     /// Whether any cross-file glob imports were found
     /// (reduces confidence since we can't enumerate glob exports).
     pub has_glob_ambiguity: bool,
+
+    /// Workspace files the cross-file scan could not read or parse, with the
+    /// reason. References in these files are missing from `references`.
+    #[serde(default)]
+    pub skipped_files: Vec<(PathBuf, String)>,
+
+    /// `pub use` items at the crate root exposing the symbol, directly or via
+    /// intermediate re-exports. Removing the symbol changes the public API.
+    #[serde(default)]
+    pub public_reexports: Vec<PublicReexport>,
+}
+
+/// A crate-root `pub use` that exposes a symbol to downstream crates.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublicReexport {
+    /// File containing the `pub use`.
+    pub file_path: PathBuf,
+
+    /// Line of the `pub use` (1-based).
+    pub line: usize,
 }
 
 /// Information about the symbol definition.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolDefinition {
     /// Symbol name.
     pub name: String,
@@ -101,6 +174,171 @@ pub struct SymbolDefinition {
     pub is_public: bool,
 }
 
+/// Editor column (0-based) of byte offset `column_byte` within `line`.
+///
+/// A tab advances to the next multiple of `tab_width`; every other character
+/// takes one column. A `column_byte` inside a multibyte character counts that
+/// character as already passed.
+///
+/// ```
+/// use splice::resolve::references::display_column;
+///
+/// assert_eq!(display_column("\t\tfoo();", 2, 4), 8);
+/// assert_eq!(display_column("  \tfoo();", 3, 8), 8);
+/// ```
+pub fn display_column(line: &str, column_byte: usize, tab_width: usize) -> usize {
+    let tab_width = tab_width.max(1);
+    line.char_indices()
+        .take_while(|&(offset, _)| offset < column_byte)
+        .fold(0, |column, (_, c)| match c {
+            '\t' => (column / tab_width + 1) * tab_width,
+            _ => column + 1,
+        })
+}
+
+/// The lines of `text` around 1-based `line`: up to `before` lines above it
+/// and `after` below, clamped to the file and joined without a final newline.
+///
+/// ```
+/// use splice::resolve::references::context_snippet;
+///
+/// let text = "a\nb\nc\nd\n";
+/// assert_eq!(context_snippet(text, 2, 0, 0), "b");
+/// assert_eq!(context_snippet(text, 2, 5, 1), "a\nb\nc");
+/// ```
+pub fn context_snippet(text: &str, line: usize, before: usize, after: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let index = line.saturating_sub(1);
+    if index >= lines.len() {
+        return String::new();
+    }
+    let first = index.saturating_sub(before);
+    let last = index.saturating_add(after).min(lines.len() - 1);
+    lines[first..=last].join("\n")
+}
+
+/// Render a symbol's references as a GraphViz DOT digraph.
+///
+/// The definition is the root node, with an edge (labelled with the count) to
+/// one node per referencing file, in order of first appearance, and from each
+/// file to a `line:column` node per reference in it.
+///
+/// ```
+/// use splice::resolve::references::{references_dot, Reference, ReferenceContext, SymbolDefinition};
+/// use splice::ingest::rust::RustSymbolKind;
+///
+/// let definition = SymbolDefinition {
+///     name: "helper".to_string(),
+///     kind: RustSymbolKind::Function,
+///     file_path: "src/lib.rs".to_string(),
+///     byte_start: 0,
+///     byte_end: 10,
+///     is_public: true,
+/// };
+/// let reference = Reference {
+///     file_path: "src/a.rs".to_string(),
+///     byte_start: 40,
+///     byte_end: 46,
+///     line: 3,
+///     column_byte: 4,
+///     column_char: 4,
+///     column_display: 4,
+///     context: ReferenceContext::Identifier,
+///     alias: None,
+/// };
+/// let dot = references_dot(&definition, &[reference]);
+/// assert!(dot.contains("\"def:helper\" -> \"file:src/a.rs\" [label=\"1\"];"));
+/// assert!(dot.contains("\"file:src/a.rs\" -> \"ref:src/a.rs:3:4\";"));
+/// ```
+pub fn references_dot(definition: &SymbolDefinition, references: &[Reference]) -> String {
+    let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+    let quote = |text: &str| format!("\"{}\"", escape(text));
+    let root = quote(&format!("def:{}", definition.name));
+
+    let mut files: Vec<(&str, Vec<&Reference>)> = Vec::new();
+    for reference in references {
+        match files
+            .iter_mut()
+            .find(|(file, _)| *file == reference.file_path)
+        {
+            Some((_, refs)) => refs.push(reference),
+            None => files.push((&reference.file_path, vec![reference])),
+        }
+    }
+
+    let mut dot = String::from("digraph references {\n    rankdir=LR;\n");
+    dot.push_str(&format!(
+        "    {} [shape=doubleoctagon, label=\"{} ({})\\n{}\"];\n",
+        root,
+        escape(&definition.name),
+        definition.kind.as_str(),
+        escape(&definition.file_path)
+    ));
+    for (file, refs) in &files {
+        let file_node = quote(&format!("file:{}", file));
+        dot.push_str(&format!(
+            "    {} [shape=folder, label={}];\n",
+            file_node,
+            quote(file)
+        ));
+        dot.push_str(&format!(
+            "    {} -> {} [label=\"{}\"];\n",
+            root,
+            file_node,
+            refs.len()
+        ));
+        for reference in refs {
+            let position = format!("{}:{}", reference.line, reference.column_byte);
+            let ref_node = quote(&format!("ref:{}:{}", file, position));
+            dot.push_str(&format!(
+                "    {} [shape=box, label={}];\n",
+                ref_node,
+                quote(&position)
+            ));
+            dot.push_str(&format!("    {} -> {};\n", file_node, ref_node));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Bounds on the cross-file part of a reference search.
+///
+/// The defining file is always searched in full; these only narrow which
+/// other workspace files are scanned.
+#[derive(Debug, Clone)]
+pub struct ReferenceScanOptions {
+    /// Workspace files larger than this many bytes are skipped.
+    pub max_file_size: u64,
+
+    /// Directory to scan instead of the nearest one above the defining file
+    /// holding a `Cargo.toml`.
+    pub scan_root: Option<PathBuf>,
+
+    /// Glob patterns for files to leave out. References in matching files,
+    /// the defining file included, are dropped.
+    pub exclude: Vec<String>,
+
+    /// Only files modified after this time are scanned, for periodic jobs
+    /// that only care about recently touched files.
+    pub since: Option<SystemTime>,
+
+    /// Limits applied when parsing the defining file and the scanned files.
+    pub parse: ParseOptions,
+}
+
+impl Default for ReferenceScanOptions {
+    fn default() -> Self {
+        Self {
+            max_file_size: crate::ingest::DEFAULT_MAX_FILE_SIZE,
+            scan_root: None,
+            exclude: Vec::new(),
+            since: None,
+            parse: ParseOptions::default(),
+        }
+    }
+}
+
 /// Find all references to a symbol.
 ///
 /// # Arguments
@@ -117,7 +355,107 @@ pub fn find_references(
     file_path: &Path,
     symbol_name: &str,
     symbol_kind: Option<RustSymbolKind>,
+) -> Result<ReferenceSet> {
+    find_references_with_options(
+        graph,
+        file_path,
+        symbol_name,
+        symbol_kind,
+        &ReferenceScanOptions::default(),
+    )
+}
+
+/// Find all references to a symbol, bounding the cross-file scan by `options`.
+///
+/// Rust, JavaScript and TypeScript definitions are supported; a file in
+/// another detected language fails with `ReferenceFailed`.
+pub fn find_references_with_options(
+    graph: &crate::graph::CodeGraph,
+    file_path: &Path,
+    symbol_name: &str,
+    symbol_kind: Option<RustSymbolKind>,
+    options: &ReferenceScanOptions,
 ) -> Result<ReferenceSet> {
     // Delegate to language-specific implementation
-    rust::find_rust_references(graph, file_path, symbol_name, symbol_kind)
+    match Language::from_path(file_path) {
+        Some(Language::Rust) | None => rust::find_rust_references_with_options(
+            graph,
+            file_path,
+            symbol_name,
+            symbol_kind,
+            options,
+        ),
+        Some(Language::JavaScript | Language::TypeScript) => {
+            javascript::find_javascript_references_with_options(
+                file_path,
+                symbol_name,
+                symbol_kind,
+                options,
+            )
+        }
+        Some(language) => {
+            use crate::symbol::Symbol;
+
+            let source = std::fs::read(file_path)?;
+            let symbols = crate::ingest::extract_symbols(file_path, &source)?;
+            if !symbols.iter().any(|symbol| symbol.name() == symbol_name) {
+                return Err(SpliceError::symbol_not_found(symbol_name, Some(file_path)));
+            }
+            Err(SpliceError::ReferenceFailed {
+                name: symbol_name.to_string(),
+                reason: format!(
+                    "reference finding is not implemented for {} files yet",
+                    language.as_str()
+                ),
+            })
+        }
+    }
+}
+
+/// Drop every reference that does not lie inside a symbol named `context_symbol`.
+///
+/// Each file holding references is parsed with its language's extractor, and a
+/// reference is kept when its span falls within a same-named symbol in that file.
+/// `definition_file` is searched too, so a name defined nowhere among them is
+/// reported as `SymbolNotFound` instead of silently emptying the list.
+pub fn retain_within_symbol(
+    references: &mut Vec<Reference>,
+    definition_file: &Path,
+    context_symbol: &str,
+) -> Result<()> {
+    use crate::symbol::Symbol;
+    use std::collections::HashMap;
+
+    let definition_file = definition_file.to_string_lossy();
+    let mut files: Vec<&str> = references.iter().map(|r| r.file_path.as_str()).collect();
+    files.push(&definition_file);
+    files.sort_unstable();
+    files.dedup();
+
+    let mut spans: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+    let mut found = false;
+    for file in files {
+        let path = Path::new(file);
+        let source = std::fs::read(path)?;
+        let file_spans: Vec<(usize, usize)> = crate::ingest::extract_symbols(path, &source)?
+            .iter()
+            .filter(|symbol| symbol.name() == context_symbol)
+            .map(|symbol| (symbol.byte_start(), symbol.byte_end()))
+            .collect();
+        found |= !file_spans.is_empty();
+        spans.insert(file.to_string(), file_spans);
+    }
+    if !found {
+        return Err(SpliceError::symbol_not_found(
+            context_symbol,
+            Some(Path::new(definition_file.as_ref())),
+        ));
+    }
+
+    references.retain(|r| {
+        spans[&r.file_path]
+            .iter()
+            .any(|&(start, end)| start <= r.byte_start && r.byte_end <= end)
+    });
+    Ok(())
 }
@@ -0,0 +1,180 @@
+//! Identifier rewrites for renaming a Rust symbol.
+//!
+//! A rename replaces the symbol's name at its definition and at every
+//! reference the reference engine finds. This module only plans those
+//! rewrites so they can be reviewed; it never writes to disk.
+
+use super::{find_references_with_options, Reference, ReferenceContext, ReferenceScanOptions};
+use crate::error::{utf8_path, Result, SpliceError};
+use crate::graph::CodeGraph;
+use crate::ingest::rust::RustSymbolKind;
+use crate::ingest::source::SourceFile;
+use crate::symbol::Language;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// One identifier a rename rewrites.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RenameEdit {
+    /// File containing the identifier.
+    pub file_path: String,
+    /// Byte start offset of the identifier.
+    pub byte_start: usize,
+    /// Byte end offset of the identifier.
+    pub byte_end: usize,
+    /// Line number (1-based).
+    pub line: usize,
+    /// Column number (0-based, in bytes).
+    pub column: usize,
+    /// Text currently in the span.
+    pub old: String,
+    /// Text replacing it.
+    pub new: String,
+    /// Whether this is the name at the definition rather than a reference.
+    pub definition: bool,
+}
+
+/// Every rewrite a rename would make, plus what it deliberately leaves alone.
+#[derive(Debug, Clone, Serialize)]
+pub struct RenamePlan {
+    /// Rewrites ordered by file, then position.
+    pub edits: Vec<RenameEdit>,
+    /// References made through a `use path::name as alias` import. They use
+    /// the alias, which a rename keeps; only the import's own path segment
+    /// is rewritten.
+    pub aliased: Vec<Reference>,
+    /// Field accesses whose receiver type could not be checked. Another struct
+    /// may own a field of the same name, so these are left for review rather
+    /// than rewritten.
+    pub unverified: Vec<Reference>,
+    /// Workspace files the reference scan could not read or parse.
+    pub skipped_files: Vec<(PathBuf, String)>,
+    /// Whether a glob import might bring the symbol into scope unseen.
+    pub has_glob_ambiguity: bool,
+}
+
+/// Plan renaming `symbol_name`, defined in `file_path`, to `new_name`.
+///
+/// References are found with [`find_references_with_options`], so shadowed
+/// locals are already excluded.
+pub fn plan_rename(
+    graph: &CodeGraph,
+    file_path: &Path,
+    symbol_name: &str,
+    symbol_kind: Option<RustSymbolKind>,
+    new_name: &str,
+    options: &ReferenceScanOptions,
+) -> Result<RenamePlan> {
+    if !is_identifier(new_name) {
+        return Err(SpliceError::Other(format!(
+            "'{}' is not a valid identifier",
+            new_name
+        )));
+    }
+
+    let ref_set =
+        find_references_with_options(graph, file_path, symbol_name, symbol_kind, options)?;
+
+    let definition = SourceFile::read(file_path, Language::Rust)?.with_parse_options(options.parse);
+    let (start, end) = definition_name_span(
+        &definition,
+        ref_set.definition.byte_start,
+        ref_set.definition.byte_end,
+    )?;
+    let mut edits = vec![edit_at(
+        utf8_path(file_path)?,
+        definition.bytes(),
+        start,
+        end,
+        new_name,
+        true,
+    )?];
+
+    let (aliased, references): (Vec<Reference>, Vec<Reference>) = ref_set
+        .references
+        .into_iter()
+        .partition(|r| r.alias.is_some());
+    let (unverified, references): (Vec<Reference>, Vec<Reference>) =
+        references.into_iter().partition(|r| {
+            matches!(
+                r.context,
+                ReferenceContext::FieldAccess { is_verified: false }
+            )
+        });
+    let mut files: Vec<&str> = references.iter().map(|r| r.file_path.as_str()).collect();
+    files.sort_unstable();
+    files.dedup();
+    for file in files {
+        let source = std::fs::read(file)?;
+        for r in references.iter().filter(|r| r.file_path == file) {
+            // Path references such as `crate::m::name` span the whole path
+            let start = r.byte_end.saturating_sub(symbol_name.len());
+            let start = start.max(r.byte_start);
+            edits.push(edit_at(file, &source, start, r.byte_end, new_name, false)?);
+        }
+    }
+    edits.sort_by(|a, b| (&a.file_path, a.byte_start).cmp(&(&b.file_path, b.byte_start)));
+    edits.dedup_by(|a, b| a.file_path == b.file_path && a.byte_start == b.byte_start);
+
+    Ok(RenamePlan {
+        edits,
+        aliased,
+        unverified,
+        skipped_files: ref_set.skipped_files,
+        has_glob_ambiguity: ref_set.has_glob_ambiguity,
+    })
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+        && name != "_"
+}
+
+/// Span of the `name` field of the item spanning `start..end`.
+fn definition_name_span(file: &SourceFile, start: usize, end: usize) -> Result<(usize, usize)> {
+    let name = file
+        .tree()?
+        .root_node()
+        .descendant_for_byte_range(start, end)
+        .and_then(|item| item.child_by_field_name("name"))
+        .ok_or_else(|| SpliceError::InvalidSpan {
+            file: file.path().to_path_buf(),
+            start,
+            end,
+        })?;
+    Ok((name.start_byte(), name.end_byte()))
+}
+
+fn edit_at(
+    file: &str,
+    source: &[u8],
+    start: usize,
+    end: usize,
+    new_name: &str,
+    definition: bool,
+) -> Result<RenameEdit> {
+    let old = source
+        .get(start..end)
+        .map(String::from_utf8_lossy)
+        .ok_or_else(|| SpliceError::InvalidSpan {
+            file: PathBuf::from(file),
+            start,
+            end,
+        })?;
+    let line_start = source[..start]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    Ok(RenameEdit {
+        file_path: file.to_string(),
+        byte_start: start,
+        byte_end: end,
+        line: source[..start].iter().filter(|&&b| b == b'\n').count() + 1,
+        column: start - line_start,
+        old: old.into_owned(),
+        new: new_name.to_string(),
+        definition,
+    })
+}
@@ -3,13 +3,24 @@
 //! This module traverses the AST to find identifier references
 //! that point to a specific symbol definition.
 
-use crate::error::{Result, SpliceError};
+use crate::error::{utf8_path, Result, SpliceError};
 use crate::graph::CodeGraph;
 use crate::ingest::imports::extract_rust_imports;
-use crate::ingest::rust::{extract_rust_symbols, RustSymbol, RustSymbolKind, Visibility};
-use crate::resolve::references::{Reference, ReferenceContext, ReferenceSet, SymbolDefinition};
+use crate::ingest::rust::{
+    extract_rust_symbols_with_options, RustSymbol, RustSymbolKind, Visibility,
+};
+use crate::ingest::source::SourceFile;
+use crate::ingest::{exclude_set, is_excluded, oversized_file};
+use crate::resolve::references::{
+    display_column, PublicReexport, Reference, ReferenceContext, ReferenceScanOptions,
+    ReferenceSet, SymbolDefinition, DEFAULT_TAB_WIDTH,
+};
+use crate::symbol::Language;
 use ropey::Rope;
+use serde::Serialize;
+use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 /// Find all references to a Rust symbol.
 ///
@@ -19,17 +30,39 @@ use std::path::{Path, PathBuf};
 /// 3. Filter out the definition itself
 /// 4. Collect matching references with context
 pub fn find_rust_references(
+    graph: &CodeGraph,
+    file_path: &Path,
+    symbol_name: &str,
+    symbol_kind: Option<RustSymbolKind>,
+) -> Result<ReferenceSet> {
+    find_rust_references_with_options(
+        graph,
+        file_path,
+        symbol_name,
+        symbol_kind,
+        &ReferenceScanOptions::default(),
+    )
+}
+
+/// Find all references to a Rust symbol, bounding the cross-file scan by
+/// `options`.
+///
+/// Re-exports are traced through every workspace file even when `since`
+/// narrows the scan, so an import routed through an untouched module is
+/// still recognized in a recently modified one.
+pub fn find_rust_references_with_options(
     _graph: &CodeGraph,
     file_path: &Path,
     symbol_name: &str,
     symbol_kind: Option<RustSymbolKind>,
+    options: &ReferenceScanOptions,
 ) -> Result<ReferenceSet> {
     // Step 1: Read and parse the source file
-    let source = std::fs::read(file_path)?;
-    let rope = Rope::from_str(std::str::from_utf8(&source)?);
+    let definition_path = utf8_path(file_path)?;
+    let file = SourceFile::read(file_path, Language::Rust)?.with_parse_options(options.parse);
 
     // Step 2: Extract all symbols to find the target definition
-    let symbols = extract_rust_symbols(file_path, &source)?;
+    let symbols = extract_rust_symbols_with_options(file_path, file.bytes(), &options.parse)?;
 
     // Find the target symbol definition
     let target_symbol = symbols
@@ -38,18 +71,20 @@ pub fn find_rust_references(
         .ok_or_else(|| SpliceError::symbol_not_found(symbol_name, Some(file_path)))?;
 
     // Step 3: Find same-file references
-    let same_file_refs = find_same_file_references(&source, &rope, target_symbol, file_path)?;
+    let same_file_refs = find_same_file_references(&file, target_symbol)?;
 
     // Step 4: Find cross-file references (if public)
-    let (cross_file_refs, has_glob_ambiguity) = if target_symbol.visibility != Visibility::Private {
-        find_cross_file_references(file_path, target_symbol)?
+    let cross_file = if target_symbol.visibility != Visibility::Private {
+        find_cross_file_references(file_path, target_symbol, options)?
     } else {
-        (Vec::new(), false)
+        CrossFileScan::default()
     };
 
     // Step 5: Combine and sort references (by byte offset descending for deletion order)
+    let excludes = exclude_set(&options.exclude)?;
     let mut all_refs = same_file_refs;
-    all_refs.extend(cross_file_refs);
+    all_refs.extend(cross_file.references);
+    all_refs.retain(|r| !is_excluded(&excludes, Path::new(&r.file_path)));
     all_refs.sort_by_key(|r| std::cmp::Reverse(r.byte_start));
 
     Ok(ReferenceSet {
@@ -57,38 +92,73 @@ pub fn find_rust_references(
         definition: SymbolDefinition {
             name: target_symbol.name.clone(),
             kind: target_symbol.kind,
-            file_path: file_path.to_str().unwrap_or("").to_string(),
+            file_path: definition_path.to_string(),
             byte_start: target_symbol.byte_start,
             byte_end: target_symbol.byte_end,
             is_public: target_symbol.visibility != Visibility::Private,
         },
-        has_glob_ambiguity,
+        has_glob_ambiguity: cross_file.has_glob_ambiguity,
+        skipped_files: cross_file.skipped_files,
+        public_reexports: cross_file.public_reexports,
     })
 }
 
+/// Find references to a Rust symbol under a name it used to have.
+///
+/// The definition is looked up in `file_path` by its current `symbol_name`,
+/// then searched for as if it were still called `former_name`, so stale usages
+/// left behind by an incomplete rename are found.
+pub fn find_rust_references_to_former_name(
+    file_path: &Path,
+    symbol_name: &str,
+    symbol_kind: Option<RustSymbolKind>,
+    former_name: &str,
+    options: &ReferenceScanOptions,
+) -> Result<Vec<Reference>> {
+    let file = SourceFile::read(file_path, Language::Rust)?.with_parse_options(options.parse);
+    let symbols = extract_rust_symbols_with_options(file_path, file.bytes(), &options.parse)?;
+    let current = symbols
+        .iter()
+        .find(|s| s.name == symbol_name && symbol_kind.is_none_or(|k| s.kind == k))
+        .ok_or_else(|| SpliceError::symbol_not_found(symbol_name, Some(file_path)))?;
+
+    let mut former = current.clone();
+    former.name = former_name.to_string();
+    former.fully_qualified = format!("{}::{}", former.module_path, former_name);
+
+    let mut references = find_same_file_references(&file, &former)?;
+    if former.visibility != Visibility::Private {
+        let cross_file = find_cross_file_references(file_path, &former, options)?;
+        references.extend(cross_file.references);
+    }
+    let excludes = exclude_set(&options.exclude)?;
+    references.retain(|r| !is_excluded(&excludes, Path::new(&r.file_path)));
+    references.sort_by_key(|r| std::cmp::Reverse(r.byte_start));
+    Ok(references)
+}
+
 /// A symbol declared in a scope, with its declaration position.
-#[derive(Debug, Clone)]
-struct ScopedSymbol {
+#[derive(Debug, Clone, Serialize)]
+pub struct ScopedSymbol {
     /// Name of the symbol.
-    name: String,
+    pub name: String,
     /// Byte offset where this symbol is declared (shadowing starts here).
-    declaration_pos: usize,
+    pub declaration_pos: usize,
 }
 
 /// Scope information for tracking local symbols that shadow imported/global symbols.
 ///
 /// Each scope has a byte range [start, end) and a set of symbol names defined in that scope.
-#[derive(Debug, Clone)]
-struct Scope {
+#[derive(Debug, Clone, Serialize)]
+pub struct Scope {
     /// Byte offset where this scope starts (inclusive).
-    start: usize,
+    pub start: usize,
     /// Byte offset where this scope ends (exclusive).
-    end: usize,
+    pub end: usize,
     /// Symbols defined in this scope, with their declaration positions.
-    symbols: Vec<ScopedSymbol>,
+    pub symbols: Vec<ScopedSymbol>,
     /// Parent scope index in the scope map (for nested scopes).
-    #[allow(dead_code)]
-    parent: Option<usize>,
+    pub parent: Option<usize>,
 }
 
 /// A map of all scopes in a file, used to detect shadowing.
@@ -148,26 +218,37 @@ impl ScopeMap {
     }
 }
 
+/// List the scopes the shadowing analysis computes for a Rust file.
+///
+/// Scope 0 covers the whole file; every other scope names its parent by
+/// index. A reference is treated as shadowed when a scope containing it
+/// declares the same name at or before its offset. Meant for checking why a
+/// reference was kept or dropped.
+///
+/// # Example
+///
+/// ```
+/// use splice::resolve::references::rust::dump_scopes;
+///
+/// let scopes = dump_scopes(b"fn f(a: i32) { let b = a; }")?;
+/// assert_eq!(scopes.len(), 2);
+/// assert_eq!(scopes[1].symbols[0].name, "a");
+/// assert_eq!(scopes[1].symbols[1].name, "b");
+/// # Ok::<(), splice::SpliceError>(())
+/// ```
+pub fn dump_scopes(source: &[u8]) -> Result<Vec<Scope>> {
+    let file = SourceFile::from_bytes(Path::new("<source>"), Language::Rust, source.to_vec());
+    Ok(build_scope_map(&file)?.scopes)
+}
+
 /// Build a scope map for the given source code.
 ///
 /// This identifies all local scopes (functions, blocks, closures, match arms)
 /// and tracks which symbols are defined in each scope.
-fn build_scope_map(source: &[u8]) -> Result<ScopeMap> {
+fn build_scope_map(file: &SourceFile) -> Result<ScopeMap> {
     let mut scope_map = ScopeMap::new();
-    let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&tree_sitter_rust::language())
-        .map_err(|e| SpliceError::Parse {
-            file: PathBuf::from("<source>"),
-            message: format!("Failed to set Rust language: {:?}", e),
-        })?;
-
-    let tree = parser
-        .parse(source, None)
-        .ok_or_else(|| SpliceError::Parse {
-            file: PathBuf::from("<source>"),
-            message: "Parse failed - no tree returned".to_string(),
-        })?;
+    let source = file.bytes();
+    let tree = file.tree()?;
 
     // Add the file-level scope (covers the entire file)
     let file_scope = scope_map.add_scope(0, source.len(), None);
@@ -384,42 +465,60 @@ fn extract_pattern_bindings(node: tree_sitter::Node, source: &[u8]) -> Vec<Strin
 
 /// Find references within the same file.
 fn find_same_file_references(
-    source: &[u8],
-    rope: &Rope,
+    file: &SourceFile,
     target_symbol: &RustSymbol,
-    file_path: &Path,
 ) -> Result<Vec<Reference>> {
     let mut references = Vec::new();
+    let (source, rope, file_path) = (file.bytes(), file.rope()?, file.path());
 
     // Build scope map for shadowing detection
-    let scope_map = build_scope_map(source)?;
-
-    // Parse the file
-    let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&tree_sitter_rust::language())
-        .map_err(|e| SpliceError::Parse {
-            file: file_path.to_path_buf(),
-            message: format!("Failed to set Rust language: {:?}", e),
-        })?;
-
-    let tree = parser
-        .parse(source, None)
-        .ok_or_else(|| SpliceError::Parse {
-            file: file_path.to_path_buf(),
-            message: "Parse failed - no tree returned".to_string(),
-        })?;
-
-    // Walk the AST looking for identifier nodes
-    find_identifiers_recursive(
-        tree.root_node(),
-        source,
-        rope,
-        target_symbol,
-        file_path,
-        &scope_map,
-        &mut references,
-    );
+    let scope_map = build_scope_map(file)?;
+
+    let tree = file.tree()?;
+
+    // Walk the AST looking for identifier nodes (or field and associated item sites)
+    if let Some(owner) = field_owner(target_symbol) {
+        find_field_references_recursive(
+            tree.root_node(),
+            source,
+            rope,
+            &target_symbol.name,
+            owner,
+            file_path,
+            &mut references,
+        );
+    } else if let Some(owner) = associated_owner(target_symbol) {
+        let implementors = trait_implementors(tree.root_node(), source, owner);
+        find_associated_references_recursive(
+            tree.root_node(),
+            source,
+            rope,
+            &target_symbol.name,
+            owner,
+            &implementors,
+            file_path,
+            &mut references,
+        );
+    } else if target_symbol.kind == RustSymbolKind::Macro {
+        find_macro_invocations_recursive(
+            tree.root_node(),
+            source,
+            rope,
+            &target_symbol.name,
+            file_path,
+            &mut references,
+        );
+    } else {
+        find_identifiers(
+            tree.root_node(),
+            source,
+            rope,
+            target_symbol,
+            file_path,
+            &scope_map,
+            &mut references,
+        );
+    }
 
     // Filter out the definition itself
     references.retain(|r| {
@@ -435,7 +534,6 @@ struct Reexport {
     /// The module path that re-exports the symbol (e.g., "crate::mod_a")
     reexporting_module: String,
     /// The name the symbol is re-exported as (might differ with `as`)
-    #[allow(dead_code)]
     reexported_name: String,
     /// The original module path being re-exported (e.g., "crate::utils")
     #[allow(dead_code)]
@@ -443,6 +541,12 @@ struct Reexport {
     /// The original symbol name being re-exported
     #[allow(dead_code)]
     original_name: String,
+    /// File containing the `use` item
+    file_path: PathBuf,
+    /// Line of the `use` item (1-based)
+    line: usize,
+    /// Whether the item is plain `pub use` rather than `pub(crate)` or similar
+    is_public: bool,
 }
 
 /// Build a map of all re-exports in the workspace.
@@ -479,7 +583,13 @@ fn build_reexport_map(
             }
 
             // Build the full path of the module being re-exported
-            let imported_module = import.path.join("::");
+            let imported_module = absolute_use_path(&module_path, &import.path);
+            let line = source[..import.byte_span.0]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count()
+                + 1;
+            let is_public = is_plain_pub(&source[import.byte_span.0..]);
 
             // For each re-exported name, record the re-export
             for name in &import.imported_names {
@@ -496,6 +606,9 @@ fn build_reexport_map(
                     reexported_name: name.clone(),
                     original_module: imported_module.clone(),
                     original_name: name.clone(),
+                    file_path: file_path.clone(),
+                    line,
+                    is_public,
                 };
 
                 let key = (imported_module.clone(), name.clone());
@@ -507,6 +620,83 @@ fn build_reexport_map(
     Ok(reexport_map)
 }
 
+/// Resolve a `use` path written in `module_path` to a `crate::`-rooted module path.
+///
+/// `self::` and `super::` are resolved against `module_path`. Other paths not
+/// starting with `crate` are taken as relative to `module_path` (2018-style
+/// `pub use child::Item`); external crate paths never match a local symbol,
+/// so treating them this way is harmless.
+fn absolute_use_path(module_path: &str, path: &[String]) -> String {
+    let mut segments: Vec<&str> = module_path.split("::").collect();
+    let mut rest = path.iter().map(String::as_str).peekable();
+
+    match rest.peek() {
+        Some(&"crate") => {
+            segments.clear();
+        }
+        Some(&"self") => {
+            rest.next();
+        }
+        Some(&"super") => {
+            while rest.peek() == Some(&"super") {
+                rest.next();
+                if segments.len() > 1 {
+                    segments.pop();
+                }
+            }
+        }
+        _ => {}
+    }
+
+    segments.extend(rest);
+    segments.join("::")
+}
+
+/// Whether a `use` item starts with a bare `pub` (not `pub(crate)`, `pub(super)`, ...).
+fn is_plain_pub(item: &[u8]) -> bool {
+    match item.strip_prefix(b"pub") {
+        Some(rest) => !rest.trim_ascii_start().starts_with(b"("),
+        None => false,
+    }
+}
+
+/// Find crate-root `pub use` items that expose `(module, name)`, following
+/// chains of intermediate re-exports.
+fn find_public_reexports(
+    module: &str,
+    name: &str,
+    reexport_map: &std::collections::HashMap<(String, String), Vec<Reexport>>,
+) -> Vec<PublicReexport> {
+    let mut found = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    let mut pending = vec![(module.to_string(), name.to_string())];
+
+    while let Some(key) = pending.pop() {
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+        for reexport in reexport_map.get(&key).into_iter().flatten() {
+            if !reexport.is_public {
+                continue;
+            }
+            if reexport.reexporting_module == "crate" {
+                found.push(PublicReexport {
+                    file_path: reexport.file_path.clone(),
+                    line: reexport.line,
+                });
+            } else {
+                pending.push((
+                    reexport.reexporting_module.clone(),
+                    reexport.reexported_name.clone(),
+                ));
+            }
+        }
+    }
+
+    found.sort_by(|a, b| (&a.file_path, a.line).cmp(&(&b.file_path, b.line)));
+    found
+}
+
 /// Get the module path for a file path.
 ///
 /// Converts /path/to/workspace/src/utils/helpers.rs to "crate::utils::helpers"
@@ -521,6 +711,11 @@ fn module_path_from_file(workspace_root: &Path, file_path: &Path) -> Result<Stri
         .to_str()
         .ok_or_else(|| SpliceError::Other("Invalid UTF-8 in path".to_string()))?;
 
+    // The crate root files define the `crate` module itself
+    if matches!(path_str, "src/lib.rs" | "src/main.rs") {
+        return Ok("crate".to_string());
+    }
+
     // Remove .rs extension and convert slashes to ::
     let module_path = path_str
         .trim_end_matches(".rs")
@@ -550,26 +745,48 @@ fn module_path_from_file(workspace_root: &Path, file_path: &Path) -> Result<Stri
 
 /// Check if a module re-exports the target symbol.
 ///
-/// Returns true if the given module re-exports the symbol from the original module.
+/// Returns true if the given module re-exports the symbol from the original
+/// module, directly or through a chain of intermediate re-exports. Chains are
+/// walked with a visited set, so cyclic `pub use` items (`a` re-exporting from
+/// `b` and `b` from `a`) terminate.
 fn module_reexports_symbol(
     module_path: &str,
     target_module: &str,
     target_symbol: &str,
     reexport_map: &std::collections::HashMap<(String, String), Vec<Reexport>>,
 ) -> bool {
-    // Check if this module directly re-exports the target symbol
-    let key = (target_module.to_string(), target_symbol.to_string());
-    if let Some(reexports) = reexport_map.get(&key) {
-        for reexport in reexports {
+    let mut seen = std::collections::HashSet::new();
+    let mut pending = vec![(target_module.to_string(), target_symbol.to_string())];
+
+    while let Some(key) = pending.pop() {
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+        for reexport in reexport_map.get(&key).into_iter().flatten() {
             if reexport.reexporting_module == module_path {
                 return true;
             }
+            pending.push((
+                reexport.reexporting_module.clone(),
+                reexport.reexported_name.clone(),
+            ));
         }
     }
 
     false
 }
 
+/// Outcome of scanning the workspace for references in other files.
+#[derive(Debug, Default)]
+struct CrossFileScan {
+    references: Vec<Reference>,
+    has_glob_ambiguity: bool,
+    /// Files that could not be scanned, with the reason.
+    skipped_files: Vec<(PathBuf, String)>,
+    /// Crate-root `pub use` items exposing the symbol.
+    public_reexports: Vec<PublicReexport>,
+}
+
 /// Find cross-file references to a symbol.
 ///
 /// This function:
@@ -583,22 +800,31 @@ fn module_reexports_symbol(
 /// # Arguments
 /// * `definition_file` - Path to the file containing the symbol definition
 /// * `target_symbol` - The symbol to find references for
+/// * `options` - Scan root, file size limit, excludes and modification cutoff
 ///
 /// # Returns
-/// * Vector of references from other files
-/// * Boolean indicating if glob imports were found (reduces confidence)
+/// * References from other files
+/// * Whether glob imports were found (reduces confidence)
+/// * Files that were skipped because they could not be read or parsed
 fn find_cross_file_references(
     definition_file: &Path,
     target_symbol: &RustSymbol,
-) -> Result<(Vec<Reference>, bool)> {
-    let mut all_references = Vec::new();
-    let mut has_glob_ambiguity = false;
+    options: &ReferenceScanOptions,
+) -> Result<CrossFileScan> {
+    let mut scan = CrossFileScan::default();
 
     // Step 1: Find workspace root
-    let workspace_root = find_workspace_root(definition_file)?;
+    let workspace_root = match &options.scan_root {
+        Some(root) => root.clone(),
+        None => find_workspace_root(definition_file)?,
+    };
 
     // Step 2: Find all .rs files in workspace
-    let rust_files = find_all_rust_files(&workspace_root)?;
+    let rust_files = find_all_rust_files(&workspace_root, options.max_file_size)?;
+
+    // A build script is compiled as a crate of its own: it cannot name the
+    // package's items, and nothing else can name its items
+    let in_build_script = is_build_script(definition_file);
 
     // Step 3: Build re-export map to track re-exported symbols
     let reexport_map = match build_reexport_map(&workspace_root, &rust_files) {
@@ -610,60 +836,168 @@ fn find_cross_file_references(
         }
     };
 
-    // Step 4: Get the module path of the target symbol
+    // Step 4: Get the module path of the target symbol.
+    // Fields are never imported directly, so files must import the owning struct.
     let target_module = &target_symbol.module_path;
+    let imported_name = field_owner(target_symbol).unwrap_or(&target_symbol.name);
+
+    // Symbol module paths are relative to their file, so qualify with the file's module.
+    // The package cannot re-export a build script's items.
+    let file_module = module_path_from_file(&workspace_root, definition_file);
+    let qualified_module = file_module.map(|file_module| {
+        let inner = target_module.strip_prefix("crate").unwrap_or(target_module);
+        format!("{}{}", file_module, inner)
+    });
+    if let (Ok(module), false) = (&qualified_module, in_build_script) {
+        scan.public_reexports = find_public_reexports(module, imported_name, &reexport_map);
+    }
+
+    // `pub(crate)`, `pub(super)`, and `pub(in path)` items can only be named
+    // from inside their module subtree, so files elsewhere are not scanned
+    let visible_in = qualified_module
+        .as_ref()
+        .ok()
+        .and_then(|module| target_symbol.visibility.restricted_scope(module));
+    let in_scope = |file_path: &Path| match &visible_in {
+        Some(scope) => module_path_from_file(&workspace_root, file_path)
+            .is_ok_and(|module| module == *scope || module.starts_with(&format!("{}::", scope))),
+        None => true,
+    };
 
     // Step 5: For each file (except the definition file), check imports and search
+    let excludes = exclude_set(&options.exclude)?;
+    let mut rust_files = rust_files;
+    rust_files.retain(|file| !is_excluded(&excludes, file));
+    if let Some(since) = options.since {
+        rust_files.retain(|file| modified_after(file, since));
+    }
     for file_path in rust_files {
         // Skip the definition file (already handled in same-file search)
-        if file_path == definition_file {
+        if file_path == definition_file
+            || is_build_script(&file_path) != in_build_script
+            || !in_scope(&file_path)
+        {
             continue;
         }
 
-        // Read source
+        // Read source; files we can't read or parse are skipped but reported
         let source = match std::fs::read(&file_path) {
             Ok(s) => s,
-            Err(_) => continue, // Skip files we can't read
+            Err(e) => {
+                log::warn!("Skipping {}: {}", file_path.display(), e);
+                scan.skipped_files.push((file_path, format!("unreadable: {}", e)));
+                continue;
+            }
         };
 
         // Extract imports from this file
         let imports = match extract_rust_imports(&file_path, &source) {
             Ok(i) => i,
-            Err(_) => continue, // Skip files that fail to parse
+            Err(e) => {
+                log::warn!("Skipping {}: {}", file_path.display(), e);
+                scan.skipped_files.push((file_path, format!("parse failed: {}", e)));
+                continue;
+            }
         };
 
         // Check if any import matches the target module directly
         let (matches, has_glob) =
-            import_matches_module(&imports, target_module, &target_symbol.name);
-
-        // Also check if any import is from a module that re-exports the target symbol
-        let matches_reexport =
-            check_reexport_matches(&imports, target_module, &target_symbol.name, &reexport_map);
+            import_matches_module(&imports, target_module, imported_name);
+
+        // Also check if any import is from a module that re-exports the target
+        // symbol, possibly through several hops; both sides use absolute paths
+        let file_module = module_path_from_file(&workspace_root, &file_path);
+        let matches_reexport = match (&qualified_module, file_module) {
+            (Ok(target), Ok(file_module)) => {
+                check_reexport_matches(&imports, &file_module, target, imported_name, &reexport_map)
+            }
+            _ => false,
+        };
 
         if has_glob {
-            has_glob_ambiguity = true;
+            scan.has_glob_ambiguity = true;
+        }
+
+        let aliased = aliased_imports(&imports, target_module, imported_name);
+        let in_aliased_use = |r: &Reference| {
+            aliased
+                .iter()
+                .any(|(start, end, _)| *start <= r.byte_start && r.byte_end <= *end)
+        };
+
+        if !(matches || matches_reexport || !aliased.is_empty()) {
+            continue;
+        }
+        // References are reported by path, so a file that uses the symbol needs a UTF-8 one
+        utf8_path(&file_path)?;
+        let file = SourceFile::from_bytes(&file_path, Language::Rust, source)
+            .with_parse_options(options.parse);
+        if let Err(e) = file.tree() {
+            log::warn!("Skipping {}: {}", file_path.display(), e);
+            let reason = format!("parse failed: {}", e);
+            scan.skipped_files.push((file_path, reason));
+            continue;
         }
+        let rope = file.rope()?;
 
         if matches || matches_reexport {
             // This file imports from the target module (or a re-exporting module), search for references
-            let rope = Rope::from_str(std::str::from_utf8(&source)?);
-            let refs = find_references_in_file(&source, &rope, target_symbol, &file_path)?;
-            all_references.extend(refs);
+            let refs = find_references_in_file(&file, target_symbol)?;
+            scan.references.extend(refs);
+        } else {
+            // Imported only as `use ...::name as alias`: the original name appears
+            // only in the `use` path, so keep just that segment of each match
+            let refs = find_references_in_file(&file, target_symbol)?;
+            let name_len = target_symbol.name.len();
+            scan.references.extend(refs.into_iter().filter(in_aliased_use).map(|r| {
+                reference_for_span(r.byte_end - name_len, r.byte_end, rope, &file_path, r.context)
+            }));
+        }
+
+        // Local code refers to an aliased import by its alias
+        if !aliased.is_empty() {
+            // `use ... as _` imports only for trait methods and has no usable name
+            let mut aliases: Vec<&str> = aliased
+                .iter()
+                .map(|(_, _, alias)| alias.as_str())
+                .filter(|alias| *alias != "_")
+                .collect();
+            aliases.sort_unstable();
+            aliases.dedup();
+            for alias in aliases {
+                let mut local = target_symbol.clone();
+                local.name = alias.to_string();
+                let refs = find_references_in_file(&file, &local)?;
+                scan.references.extend(
+                    refs.into_iter()
+                        .filter(|r| !in_aliased_use(r))
+                        .map(|r| Reference {
+                            alias: Some(alias.to_string()),
+                            ..r
+                        }),
+                );
+            }
         }
     }
 
-    Ok((all_references, has_glob_ambiguity))
+    Ok(scan)
 }
 
 /// Check if any import is from a module that re-exports the target symbol.
+///
+/// `file_module` is the module of the importing file, used to resolve
+/// `self::`, `super::` and relative `use` paths; `target_module` is the
+/// defining module's absolute path. Re-exports of re-exports are followed
+/// through `module_reexports_symbol`.
 fn check_reexport_matches(
     imports: &[crate::ingest::imports::ImportFact],
+    file_module: &str,
     target_module: &str,
     target_symbol: &str,
     reexport_map: &std::collections::HashMap<(String, String), Vec<Reexport>>,
 ) -> bool {
     for import in imports {
-        let imported_module = import.path.join("::");
+        let imported_module = absolute_use_path(file_module, &import.path);
 
         // For each name imported, check if it's a re-export of our target symbol
         for name in &import.imported_names {
@@ -693,8 +1027,16 @@ fn check_reexport_matches(
     false
 }
 
+/// Whether `path` is a package's build script: a `build.rs` beside a `Cargo.toml`.
+pub(crate) fn is_build_script(path: &Path) -> bool {
+    path.file_name() == Some(std::ffi::OsStr::new("build.rs"))
+        && path
+            .parent()
+            .is_some_and(|dir| dir.join("Cargo.toml").is_file())
+}
+
 /// Find the workspace root by searching upward for Cargo.toml.
-fn find_workspace_root(start_path: &Path) -> Result<PathBuf> {
+pub(crate) fn find_workspace_root(start_path: &Path) -> Result<PathBuf> {
     let mut current = start_path
         .parent()
         .ok_or_else(|| SpliceError::Other("Cannot determine workspace root".to_string()))?;
@@ -716,39 +1058,33 @@ fn find_workspace_root(start_path: &Path) -> Result<PathBuf> {
     }
 }
 
+/// Whether `path` was last modified after `since`; an unknown time counts as modified.
+fn modified_after(path: &Path, since: SystemTime) -> bool {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .map_or(true, |modified| modified > since)
+}
+
 /// Find all .rs files in the workspace directory.
 ///
 /// Excludes common build/output directories:
 /// - target/
 /// - .git/
 /// - Any directory starting with "."
-fn find_all_rust_files(workspace_root: &Path) -> Result<Vec<PathBuf>> {
+///
+/// Files larger than `max_file_size` bytes are skipped with a warning.
+pub(crate) fn find_all_rust_files(workspace_root: &Path, max_file_size: u64) -> Result<Vec<PathBuf>> {
     let mut rust_files = Vec::new();
 
-    fn visit_dirs(dir: &Path, rust_files: &mut Vec<PathBuf>) -> Result<()> {
-        // Skip certain directories
-        if dir
-            .file_name()
-            .map(|n| n.to_str().unwrap_or(""))
-            .unwrap_or("")
-            == "target"
-        {
-            return Ok(());
-        }
-        if dir
-            .file_name()
-            .map(|n| n.to_str().unwrap_or(""))
-            .unwrap_or("")
-            == ".git"
-        {
+    fn visit_dirs(dir: &Path, max_file_size: u64, rust_files: &mut Vec<PathBuf>) -> Result<()> {
+        // Skip build output
+        if dir.file_name() == Some(OsStr::new("target")) {
             return Ok(());
         }
-        // Skip hidden directories
+        // Skip hidden directories, `.git` included
         if dir
             .file_name()
-            .and_then(|n| n.to_str())
-            .map(|s| s.starts_with('.'))
-            .unwrap_or(false)
+            .is_some_and(|n| n.as_encoded_bytes().starts_with(b"."))
         {
             return Ok(());
         }
@@ -766,15 +1102,24 @@ fn find_all_rust_files(workspace_root: &Path) -> Result<Vec<PathBuf>> {
             let path = entry.path();
 
             if path.is_dir() {
-                visit_dirs(&path, rust_files)?;
+                visit_dirs(&path, max_file_size, rust_files)?;
             } else if path.extension().and_then(|s| s.to_str()) == Some("rs") {
+                if let Some(size) = oversized_file(&path, max_file_size) {
+                    log::warn!(
+                        "Skipping {} ({} bytes exceeds --max-file-size {})",
+                        path.display(),
+                        size,
+                        max_file_size
+                    );
+                    continue;
+                }
                 rust_files.push(path);
             }
         }
         Ok(())
     }
 
-    visit_dirs(workspace_root, &mut rust_files)?;
+    visit_dirs(workspace_root, max_file_size, &mut rust_files)?;
     Ok(rust_files)
 }
 
@@ -816,6 +1161,26 @@ fn import_matches_module(
     (matches, has_glob)
 }
 
+/// `use` items importing `target_symbol_name` under an alias, as
+/// `(byte_start, byte_end, alias)`.
+fn aliased_imports(
+    imports: &[crate::ingest::imports::ImportFact],
+    target_module: &str,
+    target_symbol_name: &str,
+) -> Vec<(usize, usize, String)> {
+    imports
+        .iter()
+        .filter(|import| import_path_matches_target(&import.path.join("::"), target_module))
+        .flat_map(|import| {
+            import
+                .aliases
+                .iter()
+                .filter(|(original, _)| original == target_symbol_name)
+                .map(|(_, alias)| (import.byte_span.0, import.byte_span.1, alias.clone()))
+        })
+        .collect()
+}
+
 /// Check if an import path matches the target module.
 ///
 /// The import path matches if:
@@ -848,49 +1213,70 @@ fn import_path_matches_target(import_path: &str, target_module: &str) -> bool {
 /// This is a simplified version of find_same_file_references that doesn't
 /// do symbol extraction (since we already know the target symbol info).
 fn find_references_in_file(
-    source: &[u8],
-    rope: &Rope,
+    file: &SourceFile,
     target_symbol: &RustSymbol,
-    file_path: &Path,
 ) -> Result<Vec<Reference>> {
     let mut references = Vec::new();
+    let (source, rope, file_path) = (file.bytes(), file.rope()?, file.path());
 
     // Build scope map for shadowing detection
-    let scope_map = build_scope_map(source)?;
-
-    // Parse the file
-    let mut parser = tree_sitter::Parser::new();
-    parser
-        .set_language(&tree_sitter_rust::language())
-        .map_err(|e| SpliceError::Parse {
-            file: file_path.to_path_buf(),
-            message: format!("Failed to set Rust language: {:?}", e),
-        })?;
-
-    let tree = parser
-        .parse(source, None)
-        .ok_or_else(|| SpliceError::Parse {
-            file: file_path.to_path_buf(),
-            message: "Parse failed - no tree returned".to_string(),
-        })?;
-
-    // Walk the AST looking for identifier nodes
-    find_identifiers_recursive(
-        tree.root_node(),
-        source,
-        rope,
-        target_symbol,
-        file_path,
-        &scope_map,
-        &mut references,
-    );
+    let scope_map = build_scope_map(file)?;
+
+    let tree = file.tree()?;
+
+    // Walk the AST looking for identifier nodes (or field and associated item sites)
+    if let Some(owner) = field_owner(target_symbol) {
+        find_field_references_recursive(
+            tree.root_node(),
+            source,
+            rope,
+            &target_symbol.name,
+            owner,
+            file_path,
+            &mut references,
+        );
+    } else if let Some(owner) = associated_owner(target_symbol) {
+        let implementors = trait_implementors(tree.root_node(), source, owner);
+        find_associated_references_recursive(
+            tree.root_node(),
+            source,
+            rope,
+            &target_symbol.name,
+            owner,
+            &implementors,
+            file_path,
+            &mut references,
+        );
+    } else if target_symbol.kind == RustSymbolKind::Macro {
+        find_macro_invocations_recursive(
+            tree.root_node(),
+            source,
+            rope,
+            &target_symbol.name,
+            file_path,
+            &mut references,
+        );
+    } else {
+        find_identifiers(
+            tree.root_node(),
+            source,
+            rope,
+            target_symbol,
+            file_path,
+            &scope_map,
+            &mut references,
+        );
+    }
 
     Ok(references)
 }
 
-/// Recursively find identifier references.
-fn find_identifiers_recursive(
-    node: tree_sitter::Node,
+/// Find identifier references.
+///
+/// Walks the tree with an explicit worklist rather than recursing, so a
+/// long method chain or deeply nested expression cannot exhaust the stack.
+fn find_identifiers(
+    root: tree_sitter::Node,
     source: &[u8],
     rope: &Rope,
     target_symbol: &RustSymbol,
@@ -898,242 +1284,785 @@ fn find_identifiers_recursive(
     scope_map: &ScopeMap,
     references: &mut Vec<Reference>,
 ) {
-    let kind = node.kind();
-
-    // Check if this node could be a reference
-    match kind {
-        "identifier" => {
-            // Skip if parent is a call_expression (already handled there)
-            let parent = node.parent();
-            if let Some(p) = parent {
-                if p.kind() == "call_expression" {
-                    // Already handled in call_expression case, skip
-                    // But still recurse to find other references in arguments
-                    let mut cursor = node.walk();
-                    for child in node.children(&mut cursor) {
-                        find_identifiers_recursive(
-                            child,
-                            source,
-                            rope,
-                            target_symbol,
-                            file_path,
-                            scope_map,
-                            references,
-                        );
-                    }
-                    return;
-                }
-            }
-
-            if let Ok(text) = node.utf8_text(source) {
-                if text == target_symbol.name {
-                    // Check if this identifier is shadowed by a local definition
-                    if scope_map.is_shadowed_at(&target_symbol.name, node.start_byte()) {
-                        // Shadowed - don't count as a reference to the target symbol
-                        // But still recurse to find other references
-                        let mut cursor = node.walk();
-                        for child in node.children(&mut cursor) {
-                            find_identifiers_recursive(
-                                child,
-                                source,
-                                rope,
-                                target_symbol,
-                                file_path,
-                                scope_map,
-                                references,
-                            );
-                        }
-                        return;
-                    }
-
+    let mut worklist = vec![root];
+    while let Some(node) = worklist.pop() {
+        // Check if this node could be a reference
+        match node.kind() {
+            "identifier" => {
+                // Skip if parent is a call_expression (already handled there), or if
+                // this names a macro (`helper!` is not `helper`)
+                let handled = node
+                    .parent()
+                    .is_some_and(|p| p.kind() == "call_expression" || is_macro_name(node));
+                // Skip if shadowed by a local definition
+                if !handled
+                    && node.utf8_text(source) == Ok(target_symbol.name.as_str())
+                    && !scope_map.is_shadowed_at(&target_symbol.name, node.start_byte())
+                {
                     let context = extract_context(node, source);
-
-                    let start_char = rope.byte_to_char(node.start_byte());
-                    let line = rope.char_to_line(start_char);
-                    let line_byte = rope.line_to_byte(line);
-                    let col = node.start_byte() - line_byte;
-
-                    references.push(Reference {
-                        file_path: file_path.to_str().unwrap_or("").to_string(),
-                        byte_start: node.start_byte(),
-                        byte_end: node.end_byte(),
-                        line: line + 1,
-                        column: col,
-                        context,
-                    });
+                    references.push(reference_at(node, rope, file_path, context));
                 }
             }
-        }
-        "scoped_identifier" | "scoped_type_identifier" => {
-            // Check if the last segment matches our symbol name
-            if let Ok(text) = node.utf8_text(source) {
-                if text.ends_with(&format!("::{}", target_symbol.name)) {
-                    let context = extract_context(node, source);
-
-                    let start_char = rope.byte_to_char(node.start_byte());
-                    let line = rope.char_to_line(start_char);
-                    let line_byte = rope.line_to_byte(line);
-                    let col = node.start_byte() - line_byte;
-
-                    references.push(Reference {
-                        file_path: file_path.to_str().unwrap_or("").to_string(),
-                        byte_start: node.start_byte(),
-                        byte_end: node.end_byte(),
-                        line: line + 1,
-                        column: col,
-                        context,
-                    });
-                }
+            // A re-export may name a type, so `-> Baz` or `Vec<Baz>` uses it too
+            "type_identifier"
+                if target_symbol.kind == RustSymbolKind::Reexport
+                    && node.utf8_text(source) == Ok(target_symbol.name.as_str()) =>
+            {
+                references.push(reference_at(
+                    node,
+                    rope,
+                    file_path,
+                    ReferenceContext::TypeReference,
+                ));
             }
-        }
-        "call_expression" => {
-            // Check if function being called is our target
-            if let Some(func) = node.child_by_field_name("function") {
-                let func_kind = func.kind();
-                if func_kind == "identifier"
-                    || func_kind == "scoped_identifier"
-                    || func_kind == "field_expression"
-                {
-                    if let Ok(text) = func.utf8_text(source) {
-                        let matches = if func_kind == "identifier" {
-                            text == target_symbol.name
-                        } else if func_kind == "field_expression" {
-                            // field_expression: receiver.method_name()
-                            // Extract the field name (method name)
-                            if let Some(field) = func.child_by_field_name("field") {
-                                if let Ok(field_text) = field.utf8_text(source) {
-                                    field_text == target_symbol.name
-                                } else {
-                                    false
-                                }
-                            } else {
-                                false
-                            }
-                        } else {
-                            text.ends_with(&format!("::{}", target_symbol.name))
-                        };
-
-                        if matches && target_symbol.kind == RustSymbolKind::Function {
-                            // For unqualified calls, check for shadowing
-                            if func_kind == "identifier"
-                                && scope_map.is_shadowed_at(&target_symbol.name, func.start_byte())
-                            {
-                                // Shadowed - don't count as a reference
-                            } else {
-                                let context = ReferenceContext::FunctionCall {
-                                    is_qualified: func_kind == "scoped_identifier"
-                                        || func_kind == "field_expression",
-                                };
-
-                                // For field_expression, use the field node's position
-                                let (start, end) = if func_kind == "field_expression" {
-                                    if let Some(field) = func.child_by_field_name("field") {
-                                        (field.start_byte(), field.end_byte())
-                                    } else {
-                                        (func.start_byte(), func.end_byte())
-                                    }
-                                } else {
-                                    (func.start_byte(), func.end_byte())
-                                };
-
-                                let start_char = rope.byte_to_char(start);
-                                let line = rope.char_to_line(start_char);
-                                let line_byte = rope.line_to_byte(line);
-                                let col = start - line_byte;
-
-                                references.push(Reference {
-                                    file_path: file_path.to_str().unwrap_or("").to_string(),
-                                    byte_start: start,
-                                    byte_end: end,
-                                    line: line + 1,
-                                    column: col,
-                                    context,
-                                });
-                            }
-                        }
+            "scoped_identifier" | "scoped_type_identifier" => {
+                // Check if the last segment matches our symbol name; the
+                // segments themselves are not references
+                if let Ok(text) = node.utf8_text(source) {
+                    if text.ends_with(&format!("::{}", target_symbol.name)) {
+                        let context = extract_context(node, source);
+                        references.push(reference_at(node, rope, file_path, context));
                     }
                 }
+                continue;
             }
-
-            // Recurse into arguments
-            let mut cursor = node.walk();
-            for child in node.children(&mut cursor) {
-                find_identifiers_recursive(
-                    child,
-                    source,
-                    rope,
-                    target_symbol,
-                    file_path,
-                    scope_map,
-                    references,
-                );
-            }
-        }
-        _ => {
-            // Recurse into other nodes
-            let mut cursor = node.walk();
-            for child in node.children(&mut cursor) {
-                find_identifiers_recursive(
-                    child,
-                    source,
-                    rope,
-                    target_symbol,
-                    file_path,
-                    scope_map,
-                    references,
-                );
+            "call_expression" => {
+                // Check if function being called is our target
+                if let Some(func) = node.child_by_field_name("function") {
+                    find_call_reference(
+                        func,
+                        source,
+                        rope,
+                        target_symbol,
+                        file_path,
+                        scope_map,
+                        references,
+                    );
+                }
             }
+            _ => {}
         }
+
+        // Visit children in source order
+        let mut cursor = node.walk();
+        let children: Vec<tree_sitter::Node> = node.children(&mut cursor).collect();
+        worklist.extend(children.into_iter().rev());
     }
 }
 
-/// Extract context information from a reference node.
-fn extract_context(node: tree_sitter::Node, _source: &[u8]) -> ReferenceContext {
-    let parent = match node.parent() {
-        Some(p) => p,
-        None => return ReferenceContext::Identifier,
+/// Record the call through `func`, the `function` of a `call_expression`,
+/// when it calls the target function.
+fn find_call_reference(
+    func: tree_sitter::Node,
+    source: &[u8],
+    rope: &Rope,
+    target_symbol: &RustSymbol,
+    file_path: &Path,
+    scope_map: &ScopeMap,
+    references: &mut Vec<Reference>,
+) {
+    let func_kind = func.kind();
+    if target_symbol.kind != RustSymbolKind::Function
+        || !matches!(
+            func_kind,
+            "identifier" | "scoped_identifier" | "field_expression"
+        )
+    {
+        return;
+    }
+    let Ok(text) = func.utf8_text(source) else {
+        return;
     };
 
-    let parent_kind = parent.kind();
-
-    match parent_kind {
-        "call_expression" => ReferenceContext::FunctionCall {
-            is_qualified: node.kind() == "scoped_identifier",
+    // field_expression: receiver.method_name(); the field node is the name
+    let name_node = match func_kind {
+        "field_expression" => match func.child_by_field_name("field") {
+            Some(field) => field,
+            None => return,
         },
-        "use_declaration" => ReferenceContext::ImportStatement,
-        "field_expression" => ReferenceContext::FieldAccess,
-        "type_identifier" | "generic_type" | "type_arguments" => ReferenceContext::TypeReference,
-        _ => ReferenceContext::Identifier,
+        _ => func,
+    };
+    let matches = match func_kind {
+        "identifier" => text == target_symbol.name,
+        "field_expression" => name_node.utf8_text(source) == Ok(target_symbol.name.as_str()),
+        _ => text.ends_with(&format!("::{}", target_symbol.name)),
+    };
+    if !matches {
+        return;
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::io::Write;
-    use tempfile::NamedTempFile;
 
-    fn create_test_graph() -> CodeGraph {
-        let temp = NamedTempFile::new().unwrap();
-        CodeGraph::open(temp.path()).unwrap()
+    // For unqualified calls, check for shadowing
+    if func_kind == "identifier" && scope_map.is_shadowed_at(&target_symbol.name, func.start_byte())
+    {
+        return;
     }
 
-    #[test]
-    fn test_find_same_file_function_references() {
-        let source = r#"
-fn helper() -> i32 {
-    42
+    let context = ReferenceContext::FunctionCall {
+        is_qualified: func_kind != "identifier",
+    };
+    references.push(reference_for_span(
+        name_node.start_byte(),
+        name_node.end_byte(),
+        rope,
+        file_path,
+        context,
+    ));
 }
 
-fn main() {
-    let x = helper();
-    let y = helper();
-    println!("{}", helper());
+/// Whether an identifier names a macro, in its definition or an invocation.
+fn is_macro_name(node: tree_sitter::Node) -> bool {
+    match node.parent().map(|p| p.kind()) {
+        Some("macro_definition" | "macro_invocation") => true,
+        Some("token_tree") => node.next_sibling().is_some_and(|n| n.kind() == "!"),
+        _ => false,
+    }
 }
-"#;
 
-        let mut temp_file = NamedTempFile::new().unwrap();
-        write!(temp_file, "{}", source).unwrap();
+/// Recursively find invocations of a `macro_rules!` macro.
+///
+/// Matches `name!(..)` and path-qualified `path::name!(..)` invocations. Inside
+/// another macro's token tree, invocations are not parsed as `macro_invocation`
+/// nodes, so an identifier followed by `!` and a delimited group also counts.
+fn find_macro_invocations_recursive(
+    node: tree_sitter::Node,
+    source: &[u8],
+    rope: &Rope,
+    macro_name: &str,
+    file_path: &Path,
+    references: &mut Vec<Reference>,
+) {
+    match node.kind() {
+        "macro_invocation" => {
+            if let Some(name_node) = node.child_by_field_name("macro") {
+                let name_matches = match name_node.kind() {
+                    "identifier" => name_node.utf8_text(source).is_ok_and(|t| t == macro_name),
+                    "scoped_identifier" => name_node
+                        .child_by_field_name("name")
+                        .and_then(|n| n.utf8_text(source).ok())
+                        .is_some_and(|t| t == macro_name),
+                    _ => false,
+                };
+                if name_matches {
+                    let context = ReferenceContext::MacroInvocation {
+                        is_qualified: name_node.kind() == "scoped_identifier",
+                    };
+                    references.push(reference_at(name_node, rope, file_path, context));
+                }
+            }
+        }
+        "identifier" if node.parent().is_some_and(|p| p.kind() == "token_tree") => {
+            let group = node.next_sibling().and_then(|bang| bang.next_sibling());
+            if is_macro_name(node)
+                && group.is_some_and(|g| g.kind() == "token_tree")
+                && node.utf8_text(source).is_ok_and(|t| t == macro_name)
+            {
+                let context = ReferenceContext::MacroInvocation {
+                    is_qualified: node.prev_sibling().is_some_and(|p| p.kind() == "::"),
+                };
+                references.push(reference_at(node, rope, file_path, context));
+            }
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        find_macro_invocations_recursive(child, source, rope, macro_name, file_path, references);
+    }
+}
+
+/// Name of the struct owning a field symbol (`crate::m::Point::x` -> `Point`).
+///
+/// Returns `None` for every other symbol kind.
+fn field_owner(target_symbol: &RustSymbol) -> Option<&str> {
+    if target_symbol.kind != RustSymbolKind::Field {
+        return None;
+    }
+    let mut segments = target_symbol.fully_qualified.rsplit("::");
+    segments.next()?;
+    segments.next()
+}
+
+/// Trait or type owning an associated type or const (`crate::m::Shape::Item` -> `Shape`).
+///
+/// Returns `None` for every other symbol, including items qualified directly
+/// by their module.
+fn associated_owner(target_symbol: &RustSymbol) -> Option<&str> {
+    if !matches!(
+        target_symbol.kind,
+        RustSymbolKind::TypeAlias | RustSymbolKind::Const
+    ) {
+        return None;
+    }
+    let owner_path = target_symbol
+        .fully_qualified
+        .strip_suffix(target_symbol.name.as_str())?
+        .strip_suffix("::")?;
+    if owner_path == target_symbol.module_path {
+        return None;
+    }
+    owner_path.rsplit("::").next()
+}
+
+/// Base names of the types a file implements `owner` for (`impl Owner for S`).
+fn trait_implementors<'a>(node: tree_sitter::Node, source: &'a [u8], owner: &str) -> Vec<&'a str> {
+    let mut implementors = Vec::new();
+    if node.kind() == "impl_item" {
+        let implements_owner = node
+            .child_by_field_name("trait")
+            .and_then(|t| t.utf8_text(source).ok())
+            .is_some_and(|t| base_type_name(t) == owner);
+        if let Some(ty) = node
+            .child_by_field_name("type")
+            .filter(|_| implements_owner)
+            .and_then(|ty| ty.utf8_text(source).ok())
+        {
+            implementors.push(base_type_name(ty));
+        }
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        implementors.extend(trait_implementors(child, source, owner));
+    }
+    implementors
+}
+
+/// Whether a trait or impl block declares items of `owner`.
+///
+/// True for `trait Owner`, `impl Owner for T`, and inherent `impl Owner`.
+fn block_declares_for_owner(block: tree_sitter::Node, source: &[u8], owner: &str) -> bool {
+    let base = |field: &str| {
+        block
+            .child_by_field_name(field)
+            .and_then(|n| n.utf8_text(source).ok())
+            .map(base_type_name)
+    };
+    match block.kind() {
+        "trait_item" => base("name") == Some(owner),
+        "impl_item" => match base("trait") {
+            Some(name) => name == owner,
+            None => base("type") == Some(owner),
+        },
+        _ => false,
+    }
+}
+
+/// Whether the path before `::Name` may refer to `owner`.
+///
+/// `Self` resolves through the nearest enclosing trait or impl block,
+/// `<T as Owner>` through its trait, and a plain type matches the owner or one
+/// of its implementors in this file.
+fn path_names_owner(
+    path: tree_sitter::Node,
+    source: &[u8],
+    owner: &str,
+    implementors: &[&str],
+) -> bool {
+    if path.kind() == "bracketed_type" {
+        let mut cursor = path.walk();
+        let Some(qualified) = path
+            .children(&mut cursor)
+            .find(|c| c.kind() == "qualified_type")
+        else {
+            return false;
+        };
+        return qualified
+            .child_by_field_name("alias")
+            .and_then(|alias| alias.utf8_text(source).ok())
+            .is_some_and(|alias| base_type_name(alias) == owner);
+    }
+
+    let Ok(text) = path.utf8_text(source) else {
+        return false;
+    };
+    match base_type_name(text) {
+        "Self" => {
+            let mut current = path.parent();
+            while let Some(n) = current {
+                if matches!(n.kind(), "trait_item" | "impl_item") {
+                    return block_declares_for_owner(n, source, owner);
+                }
+                current = n.parent();
+            }
+            false
+        }
+        name => name == owner || implementors.contains(&name),
+    }
+}
+
+/// Recursively find references to an associated type or const.
+///
+/// Covers `Self::Name` inside the owner's trait and impls, `Owner::Name` and
+/// `Implementor::Name`, `<T as Owner>::Name`, `Owner<Name = ...>` bindings, and
+/// the items declaring the name in `impl Owner for T` blocks. Paths through a
+/// generic parameter (`T::Name`) need type information and are not matched.
+#[allow(clippy::too_many_arguments)]
+fn find_associated_references_recursive(
+    node: tree_sitter::Node,
+    source: &[u8],
+    rope: &Rope,
+    item_name: &str,
+    owner: &str,
+    implementors: &[&str],
+    file_path: &Path,
+    references: &mut Vec<Reference>,
+) {
+    match node.kind() {
+        "scoped_type_identifier" | "scoped_identifier" => {
+            if let (Some(path), Some(name)) = (
+                node.child_by_field_name("path"),
+                node.child_by_field_name("name"),
+            ) {
+                if name.utf8_text(source) == Ok(item_name)
+                    && path_names_owner(path, source, owner, implementors)
+                {
+                    let context = if node.kind() == "scoped_type_identifier" {
+                        ReferenceContext::TypeReference
+                    } else {
+                        ReferenceContext::Identifier
+                    };
+                    references.push(reference_at(name, rope, file_path, context));
+                }
+            }
+        }
+        "type_binding" => {
+            let bound_trait = node
+                .parent()
+                .and_then(|args| args.parent())
+                .filter(|generic| generic.kind() == "generic_type")
+                .and_then(|generic| generic.child_by_field_name("type"))
+                .and_then(|ty| ty.utf8_text(source).ok());
+            if let Some(name) = node
+                .child_by_field_name("name")
+                .filter(|name| name.utf8_text(source) == Ok(item_name))
+            {
+                if bound_trait.is_some_and(|t| base_type_name(t) == owner) {
+                    references.push(reference_at(
+                        name,
+                        rope,
+                        file_path,
+                        ReferenceContext::TypeReference,
+                    ));
+                }
+            }
+        }
+        "associated_type" | "type_item" | "const_item" => {
+            let block = node
+                .parent()
+                .filter(|body| body.kind() == "declaration_list")
+                .and_then(|body| body.parent());
+            if let (Some(block), Some(name)) = (block, node.child_by_field_name("name")) {
+                if name.utf8_text(source) == Ok(item_name)
+                    && block_declares_for_owner(block, source, owner)
+                {
+                    let context = if node.kind() == "const_item" {
+                        ReferenceContext::Identifier
+                    } else {
+                        ReferenceContext::TypeReference
+                    };
+                    references.push(reference_at(name, rope, file_path, context));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        find_associated_references_recursive(
+            child,
+            source,
+            rope,
+            item_name,
+            owner,
+            implementors,
+            file_path,
+            references,
+        );
+    }
+}
+
+/// Last path segment of a type, without generic arguments (`a::Point<T>` -> `Point`).
+fn base_type_name(text: &str) -> &str {
+    let path = text.split('<').next().unwrap_or(text).trim_end_matches("::");
+    path.rsplit("::").next().unwrap_or(path).trim()
+}
+
+/// Base name of the type implemented by the nearest enclosing impl block.
+fn enclosing_impl_type<'a>(node: tree_sitter::Node, source: &'a [u8]) -> Option<&'a str> {
+    let mut current = node.parent();
+    while let Some(n) = current {
+        if n.kind() == "impl_item" {
+            let type_node = n.child_by_field_name("type")?;
+            return type_node.utf8_text(source).ok().map(base_type_name);
+        }
+        current = n.parent();
+    }
+    None
+}
+
+/// Check whether a type node in a struct literal or pattern names the owning struct.
+///
+/// `Self` is resolved through the enclosing impl block.
+fn names_owner(type_node: tree_sitter::Node, source: &[u8], owner: &str) -> bool {
+    let Ok(text) = type_node.utf8_text(source) else {
+        return false;
+    };
+    match base_type_name(text) {
+        "Self" => enclosing_impl_type(type_node, source) == Some(owner),
+        name => name == owner,
+    }
+}
+
+/// Base name of a field access receiver's type, as far as it can be told
+/// without type inference.
+enum ReceiverType<'a> {
+    Known(&'a str),
+    Unknown,
+}
+
+/// Determine the type of a field access receiver.
+///
+/// `self` takes the enclosing impl's type. A plain identifier takes the type
+/// written on its nearest binding: a typed parameter, a `let` with a type
+/// annotation, or a `let` initialised from a struct literal. Anything else,
+/// such as a call result or an untyped binding, is unknown.
+fn receiver_type<'a>(receiver: tree_sitter::Node, source: &'a [u8]) -> ReceiverType<'a> {
+    let known = |name: Option<&'a str>| name.map_or(ReceiverType::Unknown, ReceiverType::Known);
+    match receiver.kind() {
+        "self" => known(enclosing_impl_type(receiver, source)),
+        "identifier" => match receiver.utf8_text(source) {
+            Ok(name) => known(binding_type(receiver, name, source)),
+            Err(_) => ReceiverType::Unknown,
+        },
+        _ => ReceiverType::Unknown,
+    }
+}
+
+/// Base type name written on the nearest binding of `name` visible at `node`.
+///
+/// Returns `None` when the binding is not found, has no written type, or is
+/// part of a destructuring pattern.
+fn binding_type<'a>(node: tree_sitter::Node, name: &str, source: &'a [u8]) -> Option<&'a str> {
+    let typed = |pattern: tree_sitter::Node, type_node: Option<tree_sitter::Node>| {
+        let pattern = match pattern.kind() {
+            "mut_pattern" => pattern.named_child(0)?,
+            _ => pattern,
+        };
+        (pattern.kind() == "identifier")
+            .then_some(type_node)
+            .flatten()
+            .and_then(|t| written_type_name(t, source))
+    };
+    let mut child = node;
+    let mut current = node.parent();
+    while let Some(scope) = current {
+        match scope.kind() {
+            "block" => {
+                let mut cursor = scope.walk();
+                let binding = scope
+                    .children(&mut cursor)
+                    .take_while(|c| c.end_byte() <= node.start_byte())
+                    .filter(|c| c.kind() == "let_declaration")
+                    .filter_map(|c| Some((c, c.child_by_field_name("pattern")?)))
+                    .filter(|(_, pattern)| pattern_binds(*pattern, name, source))
+                    .last();
+                if let Some((binding, pattern)) = binding {
+                    let written = binding.child_by_field_name("type").or_else(|| {
+                        binding
+                            .child_by_field_name("value")
+                            .filter(|v| v.kind() == "struct_expression")
+                            .and_then(|v| v.child_by_field_name("name"))
+                    });
+                    return typed(pattern, written);
+                }
+            }
+            "function_item" | "closure_expression" => {
+                let params = scope.child_by_field_name("parameters")?;
+                let mut cursor = params.walk();
+                let param = params.named_children(&mut cursor).find(|p| {
+                    let pattern = p.child_by_field_name("pattern").unwrap_or(*p);
+                    pattern_binds(pattern, name, source)
+                });
+                match param {
+                    Some(param) => {
+                        let pattern = param.child_by_field_name("pattern").unwrap_or(param);
+                        return typed(pattern, param.child_by_field_name("type"));
+                    }
+                    // Items cannot see the locals of an enclosing function
+                    None if scope.kind() == "function_item" => return None,
+                    None => {}
+                }
+            }
+            // Bindings from `match`, `for`, `if let` and `while let` patterns
+            // are never written with a type
+            "match_arm" | "for_expression" | "if_expression" | "while_expression" => {
+                let body = match scope.kind() {
+                    "match_arm" => "value",
+                    "if_expression" => "consequence",
+                    _ => "body",
+                };
+                let binds = match scope.child_by_field_name("pattern") {
+                    Some(pattern) => pattern_binds(pattern, name, source),
+                    None => scope
+                        .child_by_field_name("condition")
+                        .is_some_and(|c| let_binds(c, name, source)),
+                };
+                if binds && scope.child_by_field_name(body) == Some(child) {
+                    return None;
+                }
+            }
+            _ => {}
+        }
+        child = scope;
+        current = scope.parent();
+    }
+    None
+}
+
+/// Whether an `if`/`while` condition binds `name` in a `let` pattern.
+fn let_binds(condition: tree_sitter::Node, name: &str, source: &[u8]) -> bool {
+    let binds = |c: tree_sitter::Node| {
+        c.kind() == "let_condition"
+            && c.child_by_field_name("pattern")
+                .is_some_and(|p| pattern_binds(p, name, source))
+    };
+    let mut cursor = condition.walk();
+    binds(condition)
+        || condition.kind() == "let_chain" && condition.named_children(&mut cursor).any(binds)
+}
+
+/// Whether `pattern` contains an identifier spelled `name`.
+///
+/// Patterns hold no expressions of their own, so any such identifier binds
+/// `name` (or names a constant, which is treated the same way).
+fn pattern_binds(pattern: tree_sitter::Node, name: &str, source: &[u8]) -> bool {
+    let mut worklist = vec![pattern];
+    while let Some(node) = worklist.pop() {
+        if node.kind() == "identifier" && node.utf8_text(source) == Ok(name) {
+            return true;
+        }
+        let mut cursor = node.walk();
+        worklist.extend(node.children(&mut cursor));
+    }
+    false
+}
+
+/// Base name of a written type, looking through references and resolving
+/// `Self` through the enclosing impl.
+fn written_type_name<'a>(type_node: tree_sitter::Node, source: &'a [u8]) -> Option<&'a str> {
+    let mut type_node = type_node;
+    while type_node.kind() == "reference_type" {
+        type_node = type_node.child_by_field_name("type")?;
+    }
+    match base_type_name(type_node.utf8_text(source).ok()?) {
+        "Self" => enclosing_impl_type(type_node, source),
+        name => Some(name),
+    }
+}
+
+/// Build a reference for the given node span.
+pub(crate) fn reference_at(
+    node: tree_sitter::Node,
+    rope: &Rope,
+    file_path: &Path,
+    context: ReferenceContext,
+) -> Reference {
+    reference_for_span(node.start_byte(), node.end_byte(), rope, file_path, context)
+}
+
+/// Build a reference for a byte span, computing byte, char, and display columns.
+///
+/// All columns are relative to the start of the line. The byte and char columns
+/// differ when multibyte characters precede the reference; the display column
+/// also expands tabs.
+fn reference_for_span(
+    start: usize,
+    end: usize,
+    rope: &Rope,
+    file_path: &Path,
+    context: ReferenceContext,
+) -> Reference {
+    let start_char = rope.byte_to_char(start);
+    let line = rope.char_to_line(start_char);
+    let line_start_char = rope.line_to_char(line);
+    let prefix = rope.slice(line_start_char..start_char).to_string();
+
+    Reference {
+        // Lossless: callers check the path with `utf8_path` before scanning
+        file_path: file_path.to_string_lossy().into_owned(),
+        byte_start: start,
+        byte_end: end,
+        line: line + 1,
+        column_byte: start - rope.line_to_byte(line),
+        column_char: start_char - line_start_char,
+        column_display: display_column(&prefix, prefix.len(), DEFAULT_TAB_WIDTH),
+        context,
+        alias: None,
+    }
+}
+
+/// Recursively find references to a struct field.
+///
+/// Covers `.field` accesses, struct literals (`Owner { field: v }`, `Owner { field }`)
+/// and struct patterns (`Owner { field: p }`, `Owner { field }`). Method calls with the
+/// same name are not field accesses and are skipped.
+fn find_field_references_recursive(
+    node: tree_sitter::Node,
+    source: &[u8],
+    rope: &Rope,
+    field_name: &str,
+    owner: &str,
+    file_path: &Path,
+    references: &mut Vec<Reference>,
+) {
+    match node.kind() {
+        "field_expression" => {
+            let is_method_call = node.parent().is_some_and(|p| {
+                p.kind() == "call_expression" && p.child_by_field_name("function") == Some(node)
+            });
+            if let (false, Some(field), Some(receiver)) = (
+                is_method_call,
+                node.child_by_field_name("field"),
+                node.child_by_field_name("value"),
+            ) {
+                let is_owner = match receiver_type(receiver, source) {
+                    ReceiverType::Known(name) => Some(name == owner),
+                    ReceiverType::Unknown => None,
+                };
+                if field.utf8_text(source) == Ok(field_name) && is_owner != Some(false) {
+                    references.push(reference_at(
+                        field,
+                        rope,
+                        file_path,
+                        ReferenceContext::FieldAccess {
+                            is_verified: is_owner.is_some(),
+                        },
+                    ));
+                }
+            }
+        }
+        "struct_expression" => {
+            let is_owner = node
+                .child_by_field_name("name")
+                .is_some_and(|name| names_owner(name, source, owner));
+            if let (true, Some(body)) = (is_owner, node.child_by_field_name("body")) {
+                let mut cursor = body.walk();
+                for initializer in body.children(&mut cursor) {
+                    let (field, is_shorthand) = match initializer.kind() {
+                        "field_initializer" => (initializer.child_by_field_name("field"), false),
+                        "shorthand_field_initializer" => {
+                            let mut inner = initializer.walk();
+                            let ident = initializer
+                                .children(&mut inner)
+                                .find(|c| c.kind() == "identifier");
+                            (ident, true)
+                        }
+                        _ => continue,
+                    };
+                    if let Some(field) = field.filter(|f| f.utf8_text(source) == Ok(field_name)) {
+                        references.push(reference_at(
+                            field,
+                            rope,
+                            file_path,
+                            ReferenceContext::FieldInitializer { is_shorthand },
+                        ));
+                    }
+                }
+            }
+        }
+        "struct_pattern" => {
+            let is_owner = node
+                .child_by_field_name("type")
+                .is_some_and(|ty| names_owner(ty, source, owner));
+            if is_owner {
+                let mut cursor = node.walk();
+                for pattern in node.children(&mut cursor) {
+                    if pattern.kind() != "field_pattern" {
+                        continue;
+                    }
+                    if let Some(field) = pattern
+                        .child_by_field_name("name")
+                        .filter(|f| f.utf8_text(source) == Ok(field_name))
+                    {
+                        let is_shorthand = pattern.child_by_field_name("pattern").is_none();
+                        references.push(reference_at(
+                            field,
+                            rope,
+                            file_path,
+                            ReferenceContext::FieldPattern { is_shorthand },
+                        ));
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        find_field_references_recursive(
+            child, source, rope, field_name, owner, file_path, references,
+        );
+    }
+}
+
+/// Extract context information from a reference node.
+fn extract_context(node: tree_sitter::Node, _source: &[u8]) -> ReferenceContext {
+    let parent = match node.parent() {
+        Some(p) => p,
+        None => return ReferenceContext::Identifier,
+    };
+
+    let mut parent_kind = parent.kind();
+
+    // Turbofish calls wrap the callee: `helper::<T>()` is
+    // call_expression > generic_function > identifier
+    if parent_kind == "generic_function"
+        && parent.parent().is_some_and(|p| p.kind() == "call_expression")
+    {
+        parent_kind = "call_expression";
+    }
+
+    match parent_kind {
+        "call_expression" => ReferenceContext::FunctionCall {
+            is_qualified: node.kind() == "scoped_identifier",
+        },
+        "use_declaration" => ReferenceContext::ImportStatement,
+        "field_expression" => ReferenceContext::FieldAccess { is_verified: true },
+        "type_identifier" | "generic_type" | "type_arguments" => ReferenceContext::TypeReference,
+        _ => ReferenceContext::Identifier,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingest::rust::extract_rust_symbols;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn create_test_graph() -> CodeGraph {
+        let temp = NamedTempFile::new().unwrap();
+        CodeGraph::open(temp.path()).unwrap()
+    }
+
+    #[test]
+    fn test_find_same_file_function_references() {
+        let source = r#"
+fn helper() -> i32 {
+    42
+}
+
+fn main() {
+    let x = helper();
+    let y = helper();
+    println!("{}", helper());
+}
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", source).unwrap();
 
         let graph = create_test_graph();
         let refs = find_rust_references(
@@ -1148,6 +2077,84 @@ fn main() {
         assert_eq!(refs.references.len(), 3);
     }
 
+    #[test]
+    fn test_async_await_call_references() {
+        let source = r#"
+async fn helper() -> i32 {
+    42
+}
+
+async fn run() -> i32 {
+    let a = helper().await;
+    helper().await + a
+}
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", source).unwrap();
+
+        let graph = create_test_graph();
+        let refs = find_rust_references(
+            &graph,
+            temp_file.path(),
+            "helper",
+            Some(RustSymbolKind::Function),
+        )
+        .unwrap();
+
+        assert_eq!(refs.references.len(), 2);
+        for r in &refs.references {
+            assert_eq!(&source[r.byte_start..r.byte_end], "helper");
+            assert_eq!(r.context, ReferenceContext::FunctionCall { is_qualified: false });
+        }
+    }
+
+    #[test]
+    fn test_turbofish_call_references() {
+        let source = r#"
+fn helper<T: Default>() -> T {
+    T::default()
+}
+
+fn main() {
+    let a = helper::<u32>();
+    let b: String = crate::helper::<String>();
+    let c: u8 = helper();
+}
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", source).unwrap();
+
+        let graph = create_test_graph();
+        let refs = find_rust_references(
+            &graph,
+            temp_file.path(),
+            "helper",
+            Some(RustSymbolKind::Function),
+        )
+        .unwrap();
+
+        let mut found: Vec<(&str, ReferenceContext)> = refs
+            .references
+            .iter()
+            .map(|r| (&source[r.byte_start..r.byte_end], r.context.clone()))
+            .collect();
+        found.reverse();
+
+        assert_eq!(
+            found,
+            vec![
+                ("helper", ReferenceContext::FunctionCall { is_qualified: false }),
+                (
+                    "crate::helper",
+                    ReferenceContext::FunctionCall { is_qualified: true }
+                ),
+                ("helper", ReferenceContext::FunctionCall { is_qualified: false }),
+            ]
+        );
+    }
+
     #[test]
     fn test_qualified_path_references() {
         let source = r#"
@@ -1381,7 +2388,519 @@ fn main() {
         assert_eq!(refs.references.len(), 3);
     }
 
+    #[test]
+    fn test_multibyte_identifier_columns() {
+        let source = "fn café() {}\n\nfn main() {\n    let é = 1; let r = café();\n}\n";
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", source).unwrap();
+
+        let graph = create_test_graph();
+        let refs = find_rust_references(
+            &graph,
+            temp_file.path(),
+            "café",
+            Some(RustSymbolKind::Function),
+        )
+        .unwrap();
+
+        assert_eq!(refs.references.len(), 1);
+        let r = &refs.references[0];
+        assert_eq!(r.line, 4);
+        assert_eq!(r.column_byte, 24);
+        // `é` before the call is two bytes but one char
+        assert_eq!(r.column_char, 23);
+        assert_eq!(&source[r.byte_start..r.byte_end], "café");
+    }
+
+    #[test]
+    fn test_rename_associated_type_via_self_paths() {
+        let source = r#"
+trait Shape {
+    type Item;
+    const SIDES: usize;
+    fn get(&self) -> Self::Item;
+}
+
+trait Other {
+    type Item;
+    fn other(&self) -> Self::Item;
+}
+
+struct Square;
+
+impl Shape for Square {
+    type Item = u8;
+    const SIDES: usize = 4;
+    fn get(&self) -> Self::Item {
+        Self::SIDES as Self::Item
+    }
+}
+
+impl Other for Square {
+    type Item = i64;
+    fn other(&self) -> Self::Item {
+        0
+    }
+}
+
+fn area<T: Shape<Item = u8>>(shape: &T) -> <T as Shape>::Item {
+    shape.get()
+}
+
+fn main() {
+    let _: <Square as Shape>::Item = Square.get();
+    let _ = Square::SIDES;
+}
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", source).unwrap();
+
+        let graph = create_test_graph();
+        let refs = find_rust_references(
+            &graph,
+            temp_file.path(),
+            "Item",
+            Some(RustSymbolKind::TypeAlias),
+        )
+        .unwrap();
+        assert_eq!(
+            refs.definition.byte_start,
+            source.find("type Item;").unwrap()
+        );
+
+        let mut renamed = source.to_string();
+        for r in &refs.references {
+            renamed.replace_range(r.byte_start..r.byte_end, "Output");
+        }
+        let def_name = refs.definition.byte_start + "type ".len();
+        renamed.replace_range(def_name..def_name + "Item".len(), "Output");
+
+        // Every `Shape` usage is renamed; `Other`'s own `Item` is untouched
+        assert_eq!(renamed.matches("Output").count(), 8);
+        assert!(renamed.contains("impl Shape for Square {\n    type Output = u8;"));
+        assert!(renamed
+            .contains("fn get(&self) -> Self::Output {\n        Self::SIDES as Self::Output"));
+        assert!(
+            renamed.contains("fn area<T: Shape<Output = u8>>(shape: &T) -> <T as Shape>::Output")
+        );
+        assert!(renamed.contains("let _: <Square as Shape>::Output"));
+        assert!(
+            renamed.contains("trait Other {\n    type Item;\n    fn other(&self) -> Self::Item;")
+        );
+        assert!(renamed.contains("type Item = i64;\n    fn other(&self) -> Self::Item {"));
+
+        let consts = find_rust_references(
+            &graph,
+            temp_file.path(),
+            "SIDES",
+            Some(RustSymbolKind::Const),
+        )
+        .unwrap();
+        // The impl's definition, `Self::SIDES`, and `Square::SIDES`
+        assert_eq!(consts.references.len(), 3);
+    }
+
+    #[test]
+    fn test_tab_indented_reference_display_column() {
+        let source = "fn helper() -> i32 {\n\t1\n}\n\nfn main() {\n\tlet x = 1;\n\tif x > 0 {\n\t\tlet helper = 2;\n\t\tlet _ = helper;\n\t}\n\tlet _ = helper();\n}\n";
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", source).unwrap();
+
+        let graph = create_test_graph();
+        let refs = find_rust_references(
+            &graph,
+            temp_file.path(),
+            "helper",
+            Some(RustSymbolKind::Function),
+        )
+        .unwrap();
+
+        // The shadowed `helper` in the doubly indented block is not a reference
+        assert_eq!(refs.references.len(), 1);
+        let r = &refs.references[0];
+        assert_eq!(r.line, 11);
+        assert_eq!(r.column_byte, 9);
+        assert_eq!(r.column_char, 9);
+        assert_eq!(r.column_display, 12);
+
+        let line = source.lines().nth(r.line - 1).unwrap();
+        assert_eq!(display_column(line, r.column_byte, 8), 16);
+    }
+
+    #[test]
+    fn test_rename_struct_field_references() {
+        let source = r#"
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+struct Other {
+    x: i32,
+}
+
+impl Point {
+    fn shift(&self) -> i32 {
+        self.x + 1
+    }
+}
+
+impl Other {
+    fn get(&self) -> i32 {
+        self.x
+    }
+}
+
+fn main() {
+    let x = 3;
+    let p = Point { x: 1, y: 2 };
+    let q = Point { x, y: 4 };
+    let o = Other { x: 5 };
+    let Point { x, y } = p;
+    match q {
+        Point { x: inner, .. } => println!("{} {} {}", inner, x, y),
+    }
+    let total = p.x + o.get();
+}
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", source).unwrap();
+
+        let graph = create_test_graph();
+        let refs = find_rust_references(
+            &graph,
+            temp_file.path(),
+            "x",
+            Some(RustSymbolKind::Field),
+        )
+        .unwrap();
+
+        // self.x in impl Point, two literals, two patterns, p.x
+        assert_eq!(refs.references.len(), 6);
+        assert!(refs.references.contains(&Reference {
+            file_path: temp_file.path().to_str().unwrap().to_string(),
+            byte_start: source.find("Point { x, y: 4 }").unwrap() + 8,
+            byte_end: source.find("Point { x, y: 4 }").unwrap() + 9,
+            line: 26,
+            column_byte: 20,
+            column_char: 20,
+            column_display: 20,
+            context: ReferenceContext::FieldInitializer { is_shorthand: true },
+            alias: None,
+        }));
+
+        // Apply the rename back-to-front, expanding shorthand forms
+        let mut renamed = source.to_string();
+        for r in &refs.references {
+            let replacement = match r.context {
+                ReferenceContext::FieldInitializer { is_shorthand: true }
+                | ReferenceContext::FieldPattern { is_shorthand: true } => "horizontal: x",
+                _ => "horizontal",
+            };
+            renamed.replace_range(r.byte_start..r.byte_end, replacement);
+        }
+        let def_start = refs.definition.byte_start;
+        renamed.replace_range(def_start..def_start + 1, "horizontal");
+
+        assert!(renamed.contains("struct Point {\n    horizontal: i32,"));
+        assert!(renamed.contains("struct Other {\n    x: i32,"));
+        assert!(renamed.contains("self.horizontal + 1"));
+        assert!(renamed.contains("        self.x\n"));
+        assert!(renamed.contains("Point { horizontal: 1, y: 2 }"));
+        assert!(renamed.contains("Point { horizontal: x, y: 4 }"));
+        assert!(renamed.contains("Other { x: 5 }"));
+        assert!(renamed.contains("let Point { horizontal: x, y } = p;"));
+        assert!(renamed.contains("Point { horizontal: inner, .. }"));
+        assert!(renamed.contains("p.horizontal + o.get()"));
+    }
+
+    #[test]
+    fn test_field_access_through_other_struct_is_not_a_reference() {
+        let source = r#"
+struct Point {
+    x: i32,
+}
+
+struct Other {
+    x: i32,
+}
+
+fn make() -> Point {
+    Point { x: 0 }
+}
+
+fn sum(p: &Point, o: Other, q: &mut Other) -> i32 {
+    let t: Other = Other { x: 1 };
+    let u = Other { x: 2 };
+    let v = Point { x: 3 };
+    let total = p.x + o.x + q.x + t.x + u.x + v.x;
+    if let Some(p) = Some(o) {
+        return p.x;
+    }
+    total + make().x
+}
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", source).unwrap();
+
+        let graph = create_test_graph();
+        let refs = find_rust_references(&graph, temp_file.path(), "x", Some(RustSymbolKind::Field))
+            .unwrap();
+
+        let mut references = refs.references;
+        references.sort_by_key(|r| r.byte_start);
+        let accesses: Vec<(&str, bool)> = references
+            .iter()
+            .filter_map(|r| match r.context {
+                ReferenceContext::FieldAccess { is_verified } => {
+                    let receiver = &source[..r.byte_start - 1];
+                    let start = receiver.rfind(' ').unwrap() + 1;
+                    Some((&receiver[start..], is_verified))
+                }
+                _ => None,
+            })
+            .collect();
+        // Other's bindings are excluded; a rebound or inferred receiver is unverified
+        assert_eq!(
+            accesses,
+            [("p", true), ("v", true), ("p", false), ("make()", false)]
+        );
+    }
+
     // Note: Full cross-file reference testing requires a real Cargo workspace
     // because find_cross_file_references() searches for Cargo.toml and .rs files.
     // Integration tests should be added to tests/ directory with proper workspace setup.
+
+    #[test]
+    fn test_macro_rules_definition_and_invocations() {
+        let source = r#"
+macro_rules! foo {
+    () => {
+        1
+    };
+}
+
+fn foo() -> i32 {
+    0
+}
+
+fn main() {
+    let a = foo!();
+    println!("{}", foo!() + foo());
+}
+"#;
+
+        let symbols = extract_rust_symbols(Path::new("lib.rs"), source.as_bytes()).unwrap();
+        let macro_symbol = symbols
+            .iter()
+            .find(|s| s.kind == RustSymbolKind::Macro)
+            .expect("macro_rules! should be extracted");
+        assert_eq!(macro_symbol.name, "foo");
+        assert_eq!(macro_symbol.kind.as_str(), "macro");
+        assert!(source[macro_symbol.byte_start..macro_symbol.byte_end].starts_with("macro_rules!"));
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", source).unwrap();
+
+        let graph = create_test_graph();
+        let refs = find_rust_references(
+            &graph,
+            temp_file.path(),
+            "foo",
+            Some(RustSymbolKind::Macro),
+        )
+        .unwrap();
+
+        // `let a = foo!()` and the one nested in `println!`; `foo()` is the function
+        assert_eq!(refs.references.len(), 2);
+        assert!(refs.references.iter().all(|r| matches!(
+            r.context,
+            ReferenceContext::MacroInvocation { is_qualified: false }
+        )));
+        assert!(refs.references.iter().all(|r| &source[r.byte_start..r.byte_end] == "foo"));
+
+        // The function target does not pick up the macro invocations
+        let fn_refs = find_rust_references(
+            &graph,
+            temp_file.path(),
+            "foo",
+            Some(RustSymbolKind::Function),
+        )
+        .unwrap();
+        assert_eq!(fn_refs.references.len(), 1);
+    }
+
+    #[test]
+    fn test_cyclic_reexports_terminate() {
+        let workspace = tempfile::Builder::new().prefix("cycle").tempdir().unwrap();
+        let root = workspace.path();
+        std::fs::create_dir(root.join("src")).unwrap();
+        let files = [
+            ("src/lib.rs", "pub mod a;\npub mod b;\npub mod defs;\n"),
+            ("src/defs.rs", "pub struct Foo;\n"),
+            ("src/a.rs", "pub use crate::defs::Foo;\npub use crate::b::Bar;\n"),
+            ("src/b.rs", "pub use crate::a::Bar;\npub use crate::a::Foo;\n"),
+        ];
+        let mut rust_files = Vec::new();
+        for (path, source) in files {
+            std::fs::write(root.join(path), source).unwrap();
+            rust_files.push(root.join(path));
+        }
+
+        let reexport_map = build_reexport_map(root, &rust_files).unwrap();
+
+        // `b` reaches `defs::Foo` through `a`
+        assert!(module_reexports_symbol(
+            "crate::b",
+            "crate::defs",
+            "Foo",
+            &reexport_map
+        ));
+        assert!(module_reexports_symbol(
+            "crate::b",
+            "crate::a",
+            "Bar",
+            &reexport_map
+        ));
+        // `Bar` cycles between `a` and `b`; lookups still finish
+        assert!(!module_reexports_symbol(
+            "crate::defs",
+            "crate::a",
+            "Bar",
+            &reexport_map
+        ));
+        assert!(find_public_reexports("crate::a", "Bar", &reexport_map).is_empty());
+    }
+
+    #[test]
+    fn test_build_script_is_scanned_as_its_own_crate() {
+        let workspace = tempfile::Builder::new()
+            .prefix("build_script")
+            .tempdir()
+            .unwrap();
+        let root = workspace.path();
+        std::fs::create_dir(root.join("src")).unwrap();
+        let files = [
+            (
+                "Cargo.toml",
+                "[package]\nname = \"pkg\"\nversion = \"0.1.0\"\n",
+            ),
+            ("src/lib.rs", "pub mod build;\npub mod other;\n"),
+            ("src/build.rs", "pub fn stamp() -> u32 {\n    1\n}\n"),
+            (
+                "src/other.rs",
+                "use crate::build::stamp;\n\npub fn twice() -> u32 {\n    stamp() * 2\n}\n",
+            ),
+            (
+                "build.rs",
+                "mod helpers {\n    pub fn stamp() -> u32 {\n        3\n    }\n}\n\n\
+                 use crate::helpers::stamp;\n\nfn main() {\n    let _ = stamp();\n}\n",
+            ),
+        ];
+        for (path, source) in files {
+            std::fs::write(root.join(path), source).unwrap();
+        }
+        let graph = create_test_graph();
+
+        // `crate::build` in the library is not the package's build script
+        let lib_refs =
+            find_rust_references(&graph, &root.join("src/build.rs"), "stamp", None).unwrap();
+        assert_eq!(lib_refs.references.len(), 2);
+        assert!(lib_refs
+            .references
+            .iter()
+            .all(|r| r.file_path.ends_with("other.rs")));
+
+        // The build script's own items are found inside it
+        let script = root.join("build.rs");
+        let script_refs = find_rust_references(&graph, &script, "stamp", None).unwrap();
+        assert_eq!(script_refs.references.len(), 2);
+        assert!(script_refs
+            .references
+            .iter()
+            .all(|r| Path::new(&r.file_path) == script));
+    }
+
+    #[test]
+    fn test_restricted_visibility_limits_scan_to_module_subtree() {
+        let workspace = tempfile::Builder::new()
+            .prefix("restricted")
+            .tempdir()
+            .unwrap();
+        let root = workspace.path();
+        std::fs::create_dir_all(root.join("src/a")).unwrap();
+        let import_and_call = "use crate::a::helper;\n\npub fn run() -> i32 {\n    helper()\n}\n";
+        let files = [
+            (
+                "Cargo.toml",
+                "[package]\nname = \"pkg\"\nversion = \"0.1.0\"\n",
+            ),
+            ("src/lib.rs", "pub mod a;\npub mod c;\n"),
+            (
+                "src/a.rs",
+                "pub mod b;\n\npub(in crate::a) fn helper() -> i32 {\n    1\n}\n",
+            ),
+            ("src/a/b.rs", import_and_call),
+            // Would not compile, so a match here can only be a false positive
+            ("src/c.rs", import_and_call),
+        ];
+        for (path, source) in files {
+            std::fs::write(root.join(path), source).unwrap();
+        }
+        let graph = create_test_graph();
+
+        let refs = find_rust_references(&graph, &root.join("src/a.rs"), "helper", None).unwrap();
+        let mut files: Vec<&str> = refs
+            .references
+            .iter()
+            .map(|r| r.file_path.as_str())
+            .collect();
+        files.dedup();
+        assert_eq!(files.len(), 1, "references: {:?}", refs.references);
+        assert!(files[0].ends_with("b.rs"));
+        assert_eq!(refs.references.len(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_path_is_reported_not_dropped() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let workspace = tempfile::tempdir().unwrap();
+        let file = workspace.path().join(OsStr::from_bytes(b"lib\xff.rs"));
+        std::fs::write(&file, "fn helper() {}\n\nfn user() {\n    helper();\n}\n").unwrap();
+        let graph = create_test_graph();
+
+        match find_rust_references(&graph, &file, "helper", None) {
+            Err(SpliceError::NonUtf8Path { path }) => assert_eq!(path, file),
+            other => panic!("expected NonUtf8Path, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_dump_scopes_lists_nested_block() {
+        let source = "fn outer(x: i32) -> i32 {\n    let y = x;\n    {\n        let x = 2;\n        x\n    }\n}\n";
+        let scopes = dump_scopes(source.as_bytes()).unwrap();
+
+        // File, function body, nested block
+        assert_eq!(scopes.len(), 3);
+        let names =
+            |i: usize| -> Vec<&str> { scopes[i].symbols.iter().map(|s| s.name.as_str()).collect() };
+        assert_eq!(names(1), vec!["x", "y"]);
+        assert_eq!(names(2), vec!["x"]);
+        assert_eq!(scopes[2].parent, Some(1));
+
+        let block_start = source.find("{\n        let").unwrap();
+        assert_eq!(scopes[2].start, block_start);
+        assert_eq!(&source[scopes[2].end - 1..scopes[2].end], "}");
+        assert_eq!(
+            scopes[2].symbols[0].declaration_pos,
+            source.find("let x = 2").unwrap()
+        );
+    }
 }
@@ -0,0 +1,132 @@
+//! Dead-code candidates: crate-internal Rust items with no references.
+//!
+//! Every private or restricted (`pub(crate)`, `pub(super)`, ...) item is run
+//! through the reference engine, and those it finds no usage of are reported.
+//! Plain `pub` items are left out because code outside the workspace may use
+//! them.
+
+use super::rust::find_all_rust_files;
+use super::{find_references_with_options, ReferenceScanOptions};
+use crate::error::{utf8_path, Result};
+use crate::graph::CodeGraph;
+use crate::ingest::rust::{
+    extract_rust_symbols_with_options, RustSymbol, RustSymbolKind, Visibility,
+};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// An item the reference engine found no usage of.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnreferencedSymbol {
+    /// File defining the item.
+    pub file_path: String,
+    /// Item name.
+    pub name: String,
+    /// Item kind.
+    pub kind: RustSymbolKind,
+    /// Visibility modifier (`private`, `pub(crate)`, ...).
+    pub visibility: String,
+    /// Line of the definition (1-based).
+    pub line: usize,
+    /// Byte start offset of the definition.
+    pub byte_start: usize,
+    /// Byte end offset of the definition.
+    pub byte_end: usize,
+}
+
+/// Result of scanning for unreferenced items.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UnreferencedReport {
+    /// Unreferenced items, ordered by file, then position.
+    pub symbols: Vec<UnreferencedSymbol>,
+    /// Number of files whose items were checked.
+    pub scanned_files: usize,
+    /// Files that could not be read or parsed, with the reason.
+    pub skipped_files: Vec<(PathBuf, String)>,
+}
+
+/// Report crate-internal items defined in `path` that have no references.
+///
+/// `path` is a Rust file or a directory whose `.rs` files are all checked.
+/// Methods and associated items are skipped, since calls through a receiver
+/// are not resolved to their impl; so are `main`, test-only code, and an item
+/// whose name and kind are shared by an earlier item in the same file.
+pub fn find_unreferenced(
+    graph: &CodeGraph,
+    path: &Path,
+    options: &ReferenceScanOptions,
+) -> Result<UnreferencedReport> {
+    let files = if path.is_dir() {
+        let mut files = find_all_rust_files(path, options.max_file_size)?;
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut report = UnreferencedReport::default();
+    for file in files {
+        let symbols = match std::fs::read(&file)
+            .map_err(Into::into)
+            .and_then(|source| extract_rust_symbols_with_options(&file, &source, &options.parse))
+        {
+            Ok(symbols) => symbols,
+            Err(e) => {
+                log::warn!("Skipping {}: {}", file.display(), e);
+                report.skipped_files.push((file, e.to_string()));
+                continue;
+            }
+        };
+        report.scanned_files += 1;
+
+        for symbol in symbols.iter().filter(|s| is_candidate(s, &symbols)) {
+            let ref_set = find_references_with_options(
+                graph,
+                &file,
+                &symbol.name,
+                Some(symbol.kind),
+                options,
+            )?;
+            // The engine resolves a name to its first definition in the file
+            let resolved_here = ref_set.definition.byte_start == symbol.byte_start;
+            if !resolved_here || !ref_set.references.is_empty() {
+                continue;
+            }
+            report.symbols.push(UnreferencedSymbol {
+                file_path: utf8_path(&file)?.to_string(),
+                name: symbol.name.clone(),
+                kind: symbol.kind,
+                visibility: symbol.visibility.as_str().to_string(),
+                line: symbol.line_start,
+                byte_start: symbol.byte_start,
+                byte_end: symbol.byte_end,
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Whether `symbol` is a crate-internal, free-standing item worth checking.
+fn is_candidate(symbol: &RustSymbol, all: &[RustSymbol]) -> bool {
+    let kind_checked = matches!(
+        symbol.kind,
+        RustSymbolKind::Function
+            | RustSymbolKind::Struct
+            | RustSymbolKind::Enum
+            | RustSymbolKind::Trait
+            | RustSymbolKind::TypeAlias
+            | RustSymbolKind::Const
+            | RustSymbolKind::Static
+            | RustSymbolKind::Macro
+    );
+    let associated = all.iter().any(|owner| {
+        matches!(owner.kind, RustSymbolKind::Impl | RustSymbolKind::Trait)
+            && owner.byte_start < symbol.byte_start
+            && symbol.byte_end <= owner.byte_end
+    });
+    kind_checked
+        && symbol.visibility != Visibility::Public
+        && !symbol.in_test
+        && !(symbol.kind == RustSymbolKind::Function && symbol.name == "main")
+        && !associated
+}
@@ -0,0 +1,468 @@
+//! Long-running JSON-RPC mode for editor integrations.
+//!
+//! `splice serve` reads one JSON-RPC 2.0 request per line on stdin and writes
+//! one response per line on stdout. Unlike the one-shot commands, the code
+//! graph stays open between requests and a file is only re-ingested when its
+//! contents change, so repeated lookups from an editor stay cheap.
+//!
+//! Methods:
+//! - `resolve` — `{file, symbol?, position?, kind?}` → symbol span
+//! - `references` — `{file, symbol}` → references (Rust only)
+//! - `preview` — `{file, symbol, content}` → validated preview of a replacement
+//! - `applyPatch` — `{file, symbol, content, verifyHash?}` → before/after hashes
+//! - `shutdown` / `exit` — stop the server
+//!
+//! Positions are `{line, column}` objects with a 1-based line and a 0-based
+//! column counted in the server's [`PositionEncoding`].
+
+use crate::error::{Result, SpliceError};
+use crate::graph::CodeGraph;
+use crate::ingest::extract_symbols_with_language;
+use crate::offset::{byte_to_position, position_to_byte, PositionEncoding};
+use crate::patch::{apply_patch_with_options, preview_patch_with_options, ValidationOptions};
+use crate::resolve::references::rust::find_workspace_root;
+use crate::resolve::references::{find_references_with_options, ReferenceScanOptions};
+use crate::resolve::resolve_symbol;
+use crate::symbol::{Language, Symbol};
+use crate::validate::AnalyzerMode;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Invalid JSON was received.
+pub const PARSE_ERROR: i64 = -32700;
+/// The JSON sent is not a valid request object.
+pub const INVALID_REQUEST: i64 = -32600;
+/// The method does not exist.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// Invalid method parameters.
+pub const INVALID_PARAMS: i64 = -32602;
+/// The operation failed with a [`SpliceError`]; `data` carries its kind and hint.
+pub const SPLICE_ERROR: i64 = -32000;
+
+/// A JSON-RPC error before it is wrapped in a response.
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl From<SpliceError> for RpcError {
+    fn from(err: SpliceError) -> Self {
+        Self {
+            code: SPLICE_ERROR,
+            message: err.to_string(),
+            data: Some(json!({ "kind": err.kind(), "hint": err.hint() })),
+        }
+    }
+}
+
+/// Symbols extracted from one file, keyed by the content they came from.
+struct IngestedFile {
+    content_hash: String,
+    symbols: Vec<crate::symbol::AnySymbol>,
+}
+
+/// JSON-RPC server holding a persistent code graph.
+pub struct Server {
+    graph: CodeGraph,
+    encoding: PositionEncoding,
+    options: ValidationOptions,
+    files: HashMap<PathBuf, IngestedFile>,
+}
+
+impl Server {
+    /// Create a server over `graph`, reporting positions in `encoding` and
+    /// validating patches with `options`.
+    pub fn new(graph: CodeGraph, encoding: PositionEncoding, options: ValidationOptions) -> Self {
+        Self {
+            graph,
+            encoding,
+            options,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Serve requests from `input` until EOF, `shutdown`, or `exit`.
+    ///
+    /// Each line of `input` is one request; each response is written as one
+    /// line to `output`. Notifications (requests without an `id`) get no response.
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (response, stop) = match serde_json::from_str::<Value>(&line) {
+                Ok(request) => {
+                    let stop = matches!(
+                        request.get("method").and_then(Value::as_str),
+                        Some("shutdown" | "exit")
+                    );
+                    (self.handle(&request), stop)
+                }
+                Err(err) => (
+                    Some(error_response(
+                        Value::Null,
+                        RpcError::new(PARSE_ERROR, format!("Parse error: {}", err)),
+                    )),
+                    false,
+                ),
+            };
+            if let Some(response) = response {
+                writeln!(output, "{}", response)?;
+                output.flush()?;
+            }
+            if stop {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle a single request, returning the response (or `None` for a notification).
+    pub fn handle(&mut self, request: &Value) -> Option<Value> {
+        let id = request.get("id").cloned();
+        let method = request.get("method").and_then(Value::as_str);
+        let result = match method {
+            Some(method) if request.get("jsonrpc").and_then(Value::as_str) == Some("2.0") => {
+                let params = request.get("params").cloned().unwrap_or(Value::Null);
+                self.dispatch(method, &params)
+            }
+            _ => Err(RpcError::new(INVALID_REQUEST, "Invalid request")),
+        };
+
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(err) => error_response(id, err),
+        })
+    }
+
+    fn dispatch(&mut self, method: &str, params: &Value) -> std::result::Result<Value, RpcError> {
+        match method {
+            "resolve" => self.resolve(params),
+            "references" => self.references(params),
+            "preview" => self.patch(params, true),
+            "applyPatch" => self.patch(params, false),
+            "shutdown" | "exit" => Ok(Value::Null),
+            other => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method not found: {}", other),
+            )),
+        }
+    }
+
+    fn resolve(&mut self, params: &Value) -> std::result::Result<Value, RpcError> {
+        let file = file_param(params)?;
+        let kind = optional_str_param(params, "kind")?;
+        let (name, kind) = match (optional_str_param(params, "symbol")?, params.get("position")) {
+            (Some(name), _) => (name.to_string(), kind.map(str::to_string)),
+            (None, Some(position)) => {
+                let (name, kind) = self.symbol_at(&file, position, kind)?;
+                (name, Some(kind))
+            }
+            (None, None) => {
+                return Err(RpcError::new(
+                    INVALID_PARAMS,
+                    "Either 'symbol' or 'position' is required",
+                ))
+            }
+        };
+        self.ingest(&file)?;
+
+        let resolved = resolve_symbol(&self.graph, Some(&file), kind.as_deref(), &name)?;
+        let source = std::fs::read_to_string(&file).map_err(SpliceError::from)?;
+        Ok(json!({
+            "name": resolved.name,
+            "kind": resolved.kind,
+            "language": resolved.language,
+            "visibility": resolved.visibility,
+            "file_path": resolved.file_path,
+            "byte_start": resolved.byte_start,
+            "byte_end": resolved.byte_end,
+            "start": self.position_json(&source, resolved.byte_start),
+            "end": self.position_json(&source, resolved.byte_end),
+        }))
+    }
+
+    fn references(&mut self, params: &Value) -> std::result::Result<Value, RpcError> {
+        let file = file_param(params)?;
+        let name = str_param(params, "symbol")?;
+        self.ingest(&file)?;
+
+        let scan = ReferenceScanOptions {
+            max_file_size: self.options.max_file_size,
+            ..ReferenceScanOptions::default()
+        };
+        let ref_set = find_references_with_options(&self.graph, &file, name, None, &scan)?;
+
+        let mut sources: HashMap<&str, String> = HashMap::new();
+        let mut references = Vec::with_capacity(ref_set.references.len());
+        for r in &ref_set.references {
+            let text = match sources.get(r.file_path.as_str()) {
+                Some(text) => text,
+                None => {
+                    let text = std::fs::read_to_string(&r.file_path).map_err(SpliceError::from)?;
+                    sources.entry(r.file_path.as_str()).or_insert(text)
+                }
+            };
+            references.push(json!({
+                "file_path": r.file_path,
+                "byte_start": r.byte_start,
+                "byte_end": r.byte_end,
+                "start": self.position_json(text, r.byte_start),
+                "end": self.position_json(text, r.byte_end),
+            }));
+        }
+
+        Ok(json!({
+            "symbol": name,
+            "references": references,
+            "has_glob_ambiguity": ref_set.has_glob_ambiguity,
+            "skipped_files": ref_set
+                .skipped_files
+                .iter()
+                .map(|(file, reason)| json!({ "file": file.to_string_lossy(), "reason": reason }))
+                .collect::<Vec<_>>(),
+        }))
+    }
+
+    fn patch(&mut self, params: &Value, preview: bool) -> std::result::Result<Value, RpcError> {
+        let file = file_param(params)?;
+        let name = str_param(params, "symbol")?;
+        let content = str_param(params, "content")?;
+        let kind = optional_str_param(params, "kind")?;
+        if let Some(expected) = optional_str_param(params, "verifyHash")? {
+            crate::patch::verify_file_hash(&file, expected)?;
+        }
+        let language = self.ingest(&file)?;
+
+        let resolved = resolve_symbol(&self.graph, Some(&file), kind, name)?;
+        let (start, end) = (resolved.byte_start, resolved.byte_end);
+
+        if preview {
+            let workspace_root = find_workspace_root(&file)?;
+            let (summary, report) = preview_patch_with_options(
+                &file,
+                start,
+                end,
+                content,
+                &workspace_root,
+                language,
+                AnalyzerMode::Off,
+                &self.options,
+            )?;
+            return Ok(json!({
+                "symbol": name,
+                "file": summary.file.to_string_lossy(),
+                "before_hash": summary.before_hash,
+                "after_hash": summary.after_hash,
+                "preview_report": report,
+            }));
+        }
+
+        let workspace_dir = file
+            .parent()
+            .ok_or_else(|| SpliceError::Other("Cannot determine workspace directory".to_string()))?;
+        let (before_hash, after_hash) = apply_patch_with_options(
+            &file,
+            start,
+            end,
+            content,
+            workspace_dir,
+            language,
+            AnalyzerMode::Off,
+            &self.options,
+        )?;
+        // Spans in this file moved; pick up the new ones on the next request
+        self.ingest(&file)?;
+
+        Ok(json!({
+            "symbol": name,
+            "file": file.to_string_lossy(),
+            "before_hash": before_hash,
+            "after_hash": after_hash,
+        }))
+    }
+
+    /// Name and kind of the innermost symbol (optionally of `kind`) enclosing `position`.
+    fn symbol_at(
+        &mut self,
+        file: &Path,
+        position: &Value,
+        kind: Option<&str>,
+    ) -> std::result::Result<(String, String), RpcError> {
+        let line = position.get("line").and_then(Value::as_u64);
+        let column = position.get("column").and_then(Value::as_u64);
+        let (Some(line), Some(column)) = (line, column) else {
+            return Err(RpcError::new(
+                INVALID_PARAMS,
+                "'position' must be {\"line\": N, \"column\": N}",
+            ));
+        };
+        let (line, column) = (line as usize, column as usize);
+
+        self.ingest(file)?;
+        let source = std::fs::read_to_string(file).map_err(SpliceError::from)?;
+        let byte = position_to_byte(&source, line, column, self.encoding).ok_or_else(|| {
+            RpcError::new(
+                INVALID_PARAMS,
+                format!(
+                    "Position {}:{} is not a valid {:?} position in {}",
+                    line,
+                    column,
+                    self.encoding,
+                    file.display()
+                ),
+            )
+        })?;
+
+        let symbols = &self.files[file].symbols;
+        symbols
+            .iter()
+            .filter(|s| s.byte_start() <= byte && byte < s.byte_end())
+            .filter(|s| kind.is_none_or(|k| s.kind() == k))
+            .min_by_key(|s| s.byte_end() - s.byte_start())
+            .map(|s| (s.name().to_string(), s.kind().to_string()))
+            .ok_or_else(|| {
+                SpliceError::symbol_not_found(format!("<symbol at {}:{}>", line, column), Some(file))
+                    .into()
+            })
+    }
+
+    /// Make sure the graph reflects the current contents of `file`.
+    ///
+    /// Returns the file's language.
+    fn ingest(&mut self, file: &Path) -> Result<Language> {
+        let language = Language::from_path(file).ok_or_else(|| SpliceError::Parse {
+            file: file.to_path_buf(),
+            message: "Cannot detect language - unknown file extension".to_string(),
+        })?;
+        let source = std::fs::read(file)?;
+        let content_hash = format!("{:x}", Sha256::digest(&source));
+        if self
+            .files
+            .get(file)
+            .is_some_and(|ingested| ingested.content_hash == content_hash)
+        {
+            return Ok(language);
+        }
+
+        let symbols = extract_symbols_with_language(file, &source, language)?;
+        self.graph.remove_file(file)?;
+        self.graph.store_file_symbols(file, &symbols)?;
+        self.files.insert(
+            file.to_path_buf(),
+            IngestedFile {
+                content_hash,
+                symbols,
+            },
+        );
+        Ok(language)
+    }
+
+    fn position_json(&self, source: &str, byte: usize) -> Value {
+        match byte_to_position(source, byte, self.encoding) {
+            Some((line, column)) => json!({ "line": line, "column": column }),
+            None => Value::Null,
+        }
+    }
+}
+
+fn error_response(id: Value, err: RpcError) -> Value {
+    let mut error = json!({ "code": err.code, "message": err.message });
+    if let Some(data) = err.data {
+        error["data"] = data;
+    }
+    json!({ "jsonrpc": "2.0", "id": id, "error": error })
+}
+
+fn str_param<'a>(params: &'a Value, name: &str) -> std::result::Result<&'a str, RpcError> {
+    optional_str_param(params, name)?.ok_or_else(|| {
+        RpcError::new(
+            INVALID_PARAMS,
+            format!("Missing required parameter '{}'", name),
+        )
+    })
+}
+
+fn optional_str_param<'a>(
+    params: &'a Value,
+    name: &str,
+) -> std::result::Result<Option<&'a str>, RpcError> {
+    match params.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value)),
+        Some(_) => Err(RpcError::new(
+            INVALID_PARAMS,
+            format!("Parameter '{}' must be a string", name),
+        )),
+    }
+}
+
+fn file_param(params: &Value) -> std::result::Result<PathBuf, RpcError> {
+    str_param(params, "file").map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn server(dir: &TempDir) -> Server {
+        let graph = CodeGraph::open(&dir.path().join("graph.db")).unwrap();
+        Server::new(graph, PositionEncoding::Utf16, ValidationOptions::default())
+    }
+
+    #[test]
+    fn test_unknown_method_and_notifications() {
+        let dir = TempDir::new().unwrap();
+        let mut server = server(&dir);
+
+        let response = server
+            .handle(&json!({ "jsonrpc": "2.0", "id": 7, "method": "frobnicate" }))
+            .unwrap();
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        assert!(server
+            .handle(&json!({ "jsonrpc": "2.0", "method": "frobnicate" }))
+            .is_none());
+    }
+
+    #[test]
+    fn test_resolve_reingests_changed_file() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("lib.rs");
+        std::fs::write(&file, "fn first() {}\nfn target() {}\n").unwrap();
+        let mut server = server(&dir);
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "resolve",
+            "params": { "file": file.to_string_lossy(), "symbol": "target" },
+        });
+
+        let response = server.handle(&request).unwrap();
+        assert_eq!(response["result"]["byte_start"], 14);
+        assert_eq!(response["result"]["start"], json!({ "line": 2, "column": 0 }));
+
+        std::fs::write(&file, "fn target() {}\n").unwrap();
+        let response = server.handle(&request).unwrap();
+        assert_eq!(response["result"]["byte_start"], 0);
+        assert_eq!(response["result"]["start"], json!({ "line": 1, "column": 0 }));
+    }
+}
@@ -5,8 +5,9 @@
 //! but they all share common properties accessible through the `Symbol` trait.
 
 use crate::ingest::{
-    cpp::CppSymbol, java::JavaSymbol, javascript::JavaScriptSymbol, python::PythonSymbol,
-    rust::RustSymbol, typescript::TypeScriptSymbol,
+    cpp::CppSymbol, elixir::ElixirSymbol, haskell::HaskellSymbol, java::JavaSymbol,
+    javascript::JavaScriptSymbol, python::PythonSymbol, rust::RustSymbol,
+    typescript::TypeScriptSymbol,
 };
 use std::path::Path;
 
@@ -46,6 +47,50 @@ pub trait Symbol {
 
     /// Get the programming language this symbol belongs to.
     fn language(&self) -> Language;
+
+    /// Get the visibility modifier (e.g., `pub`, `pub(crate)`, `private`).
+    ///
+    /// Returns `None` for languages where visibility is not extracted.
+    fn visibility(&self) -> Option<&str> {
+        None
+    }
+
+    /// Get the parameter type list of an overloadable callable, e.g. `(int, String)`.
+    ///
+    /// Returns `None` for languages without overloading and for non-callables.
+    fn signature(&self) -> Option<String> {
+        None
+    }
+
+    /// Whether the symbol is test-only code (e.g., inside a `#[cfg(test)]` module).
+    ///
+    /// Only Rust records this; other languages always return `false`.
+    fn in_test(&self) -> bool {
+        false
+    }
+}
+
+/// Format parameter types as a signature, e.g. `(int, String)`.
+fn format_signature(parameter_types: &[String]) -> String {
+    format!("({})", parameter_types.join(", "))
+}
+
+/// Whether two signatures name the same parameter list, ignoring whitespace.
+pub fn signatures_match(a: &str, b: &str) -> bool {
+    a.chars()
+        .filter(|c| !c.is_whitespace())
+        .eq(b.chars().filter(|c| !c.is_whitespace()))
+}
+
+/// Whether `fully_qualified` (e.g. `module::Calculator::add`) is named by
+/// `wanted`, a trailing part of it whose segments may be separated by `.` or
+/// `::` (`Calculator.add`, `Calculator::add`, `add`).
+pub fn qualified_names_match(fully_qualified: &str, wanted: &str) -> bool {
+    let wanted = wanted.replace('.', "::");
+    fully_qualified == wanted
+        || fully_qualified
+            .strip_suffix(wanted.as_str())
+            .is_some_and(|prefix| prefix.ends_with("::"))
 }
 
 /// Programming languages supported by Splice.
@@ -65,6 +110,10 @@ pub enum Language {
     JavaScript,
     /// TypeScript (.ts, .tsx)
     TypeScript,
+    /// Haskell (.hs)
+    Haskell,
+    /// Elixir (.ex, .exs)
+    Elixir,
 }
 
 impl Language {
@@ -78,13 +127,20 @@ impl Language {
             Language::Java => "java",
             Language::JavaScript => "javascript",
             Language::TypeScript => "typescript",
+            Language::Haskell => "haskell",
+            Language::Elixir => "elixir",
         }
     }
 
     /// Detect language from file path extension.
     pub fn from_path(path: &Path) -> Option<Self> {
-        use crate::ingest::detect::detect_language;
-        detect_language(path).map(|lang| match lang {
+        crate::ingest::detect::detect_language(path).map(Language::from)
+    }
+}
+
+impl From<crate::ingest::detect::Language> for Language {
+    fn from(language: crate::ingest::detect::Language) -> Self {
+        match language {
             crate::ingest::detect::Language::Rust => Language::Rust,
             crate::ingest::detect::Language::Python => Language::Python,
             crate::ingest::detect::Language::C => Language::C,
@@ -92,7 +148,9 @@ impl Language {
             crate::ingest::detect::Language::Java => Language::Java,
             crate::ingest::detect::Language::JavaScript => Language::JavaScript,
             crate::ingest::detect::Language::TypeScript => Language::TypeScript,
-        })
+            crate::ingest::detect::Language::Haskell => Language::Haskell,
+            crate::ingest::detect::Language::Elixir => Language::Elixir,
+        }
     }
 }
 
@@ -114,6 +172,10 @@ pub enum AnySymbol {
     JavaScript(JavaScriptSymbol),
     /// TypeScript symbol.
     TypeScript(TypeScriptSymbol),
+    /// Haskell symbol.
+    Haskell(HaskellSymbol),
+    /// Elixir symbol.
+    Elixir(ElixirSymbol),
 }
 
 impl Symbol for AnySymbol {
@@ -125,6 +187,8 @@ impl Symbol for AnySymbol {
             AnySymbol::Java(s) => s.name.as_str(),
             AnySymbol::JavaScript(s) => s.name.as_str(),
             AnySymbol::TypeScript(s) => s.name.as_str(),
+            AnySymbol::Haskell(s) => s.name.as_str(),
+            AnySymbol::Elixir(s) => s.name.as_str(),
         }
     }
 
@@ -136,6 +200,8 @@ impl Symbol for AnySymbol {
             AnySymbol::Java(s) => s.kind.as_str(),
             AnySymbol::JavaScript(s) => s.kind.as_str(),
             AnySymbol::TypeScript(s) => s.kind.as_str(),
+            AnySymbol::Haskell(s) => s.kind.as_str(),
+            AnySymbol::Elixir(s) => s.kind.as_str(),
         }
     }
 
@@ -147,6 +213,8 @@ impl Symbol for AnySymbol {
             AnySymbol::Java(s) => s.byte_start,
             AnySymbol::JavaScript(s) => s.byte_start,
             AnySymbol::TypeScript(s) => s.byte_start,
+            AnySymbol::Haskell(s) => s.byte_start,
+            AnySymbol::Elixir(s) => s.byte_start,
         }
     }
 
@@ -158,6 +226,8 @@ impl Symbol for AnySymbol {
             AnySymbol::Java(s) => s.byte_end,
             AnySymbol::JavaScript(s) => s.byte_end,
             AnySymbol::TypeScript(s) => s.byte_end,
+            AnySymbol::Haskell(s) => s.byte_end,
+            AnySymbol::Elixir(s) => s.byte_end,
         }
     }
 
@@ -169,6 +239,8 @@ impl Symbol for AnySymbol {
             AnySymbol::Java(s) => s.line_start,
             AnySymbol::JavaScript(s) => s.line_start,
             AnySymbol::TypeScript(s) => s.line_start,
+            AnySymbol::Haskell(s) => s.line_start,
+            AnySymbol::Elixir(s) => s.line_start,
         }
     }
 
@@ -180,6 +252,8 @@ impl Symbol for AnySymbol {
             AnySymbol::Java(s) => s.line_end,
             AnySymbol::JavaScript(s) => s.line_end,
             AnySymbol::TypeScript(s) => s.line_end,
+            AnySymbol::Haskell(s) => s.line_end,
+            AnySymbol::Elixir(s) => s.line_end,
         }
     }
 
@@ -191,6 +265,8 @@ impl Symbol for AnySymbol {
             AnySymbol::Java(s) => s.col_start,
             AnySymbol::JavaScript(s) => s.col_start,
             AnySymbol::TypeScript(s) => s.col_end,
+            AnySymbol::Haskell(s) => s.col_start,
+            AnySymbol::Elixir(s) => s.col_start,
         }
     }
 
@@ -202,6 +278,8 @@ impl Symbol for AnySymbol {
             AnySymbol::Java(s) => s.col_end,
             AnySymbol::JavaScript(s) => s.col_end,
             AnySymbol::TypeScript(s) => s.col_end,
+            AnySymbol::Haskell(s) => s.col_end,
+            AnySymbol::Elixir(s) => s.col_end,
         }
     }
 
@@ -213,6 +291,8 @@ impl Symbol for AnySymbol {
             AnySymbol::Java(s) => s.fully_qualified.as_str(),
             AnySymbol::JavaScript(s) => s.fully_qualified.as_str(),
             AnySymbol::TypeScript(s) => s.fully_qualified.as_str(),
+            AnySymbol::Haskell(s) => s.fully_qualified.as_str(),
+            AnySymbol::Elixir(s) => s.fully_qualified.as_str(),
         }
     }
 
@@ -224,8 +304,21 @@ impl Symbol for AnySymbol {
             AnySymbol::Java(_) => Language::Java,
             AnySymbol::JavaScript(_) => Language::JavaScript,
             AnySymbol::TypeScript(_) => Language::TypeScript,
+            AnySymbol::Haskell(_) => Language::Haskell,
+            AnySymbol::Elixir(_) => Language::Elixir,
         }
     }
+
+    fn signature(&self) -> Option<String> {
+        match self {
+            AnySymbol::Cpp(s) => s.signature(),
+            AnySymbol::Java(s) => s.signature(),
+            _ => None,
+        }
+    }
+    fn in_test(&self) -> bool {
+        matches!(self, AnySymbol::Rust(s) if s.in_test)
+    }
 }
 
 // Implement Symbol for all language-specific symbols
@@ -270,6 +363,14 @@ impl Symbol for RustSymbol {
     fn language(&self) -> Language {
         Language::Rust
     }
+
+    fn visibility(&self) -> Option<&str> {
+        Some(self.visibility.as_str())
+    }
+
+    fn in_test(&self) -> bool {
+        self.in_test
+    }
 }
 
 impl Symbol for PythonSymbol {
@@ -354,6 +455,16 @@ impl Symbol for CppSymbol {
     fn language(&self) -> Language {
         Language::Cpp
     }
+
+    fn signature(&self) -> Option<String> {
+        use crate::ingest::cpp::CppSymbolKind;
+
+        matches!(
+            self.kind,
+            CppSymbolKind::Function | CppSymbolKind::Method | CppSymbolKind::TemplateFunction
+        )
+        .then(|| format_signature(&self.parameter_types))
+    }
 }
 
 impl Symbol for JavaSymbol {
@@ -396,6 +507,16 @@ impl Symbol for JavaSymbol {
     fn language(&self) -> Language {
         Language::Java
     }
+
+    fn signature(&self) -> Option<String> {
+        use crate::ingest::java::JavaSymbolKind;
+
+        matches!(
+            self.kind,
+            JavaSymbolKind::Method | JavaSymbolKind::Constructor
+        )
+        .then(|| format_signature(&self.parameter_types))
+    }
 }
 
 impl Symbol for JavaScriptSymbol {
@@ -482,6 +603,90 @@ impl Symbol for TypeScriptSymbol {
     }
 }
 
+impl Symbol for HaskellSymbol {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn kind(&self) -> &str {
+        self.kind.as_str()
+    }
+
+    fn byte_start(&self) -> usize {
+        self.byte_start
+    }
+
+    fn byte_end(&self) -> usize {
+        self.byte_end
+    }
+
+    fn line_start(&self) -> usize {
+        self.line_start
+    }
+
+    fn line_end(&self) -> usize {
+        self.line_end
+    }
+
+    fn col_start(&self) -> usize {
+        self.col_start
+    }
+
+    fn col_end(&self) -> usize {
+        self.col_end
+    }
+
+    fn fully_qualified(&self) -> &str {
+        self.fully_qualified.as_str()
+    }
+
+    fn language(&self) -> Language {
+        Language::Haskell
+    }
+}
+
+impl Symbol for ElixirSymbol {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn kind(&self) -> &str {
+        self.kind.as_str()
+    }
+
+    fn byte_start(&self) -> usize {
+        self.byte_start
+    }
+
+    fn byte_end(&self) -> usize {
+        self.byte_end
+    }
+
+    fn line_start(&self) -> usize {
+        self.line_start
+    }
+
+    fn line_end(&self) -> usize {
+        self.line_end
+    }
+
+    fn col_start(&self) -> usize {
+        self.col_start
+    }
+
+    fn col_end(&self) -> usize {
+        self.col_end
+    }
+
+    fn fully_qualified(&self) -> &str {
+        self.fully_qualified.as_str()
+    }
+
+    fn language(&self) -> Language {
+        Language::Elixir
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -494,6 +699,8 @@ mod tests {
         assert_eq!(Language::Java.as_str(), "java");
         assert_eq!(Language::JavaScript.as_str(), "javascript");
         assert_eq!(Language::TypeScript.as_str(), "typescript");
+        assert_eq!(Language::Haskell.as_str(), "haskell");
+        assert_eq!(Language::Elixir.as_str(), "elixir");
     }
 
     #[test]
@@ -523,6 +730,14 @@ mod tests {
             Language::from_path(Path::new("test.ts")),
             Some(Language::TypeScript)
         );
+        assert_eq!(
+            Language::from_path(Path::new("Main.hs")),
+            Some(Language::Haskell)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("lib/app.ex")),
+            Some(Language::Elixir)
+        );
         assert_eq!(Language::from_path(Path::new("file.txt")), None);
     }
 }
@@ -5,7 +5,7 @@
 
 use crate::error::{Result, SpliceError};
 use crate::ingest::detect::{detect_language, Language};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Outcome of validating a file with its language's compiler.
@@ -84,13 +84,16 @@ pub fn validate_file(path: &Path) -> Result<ValidationOutcome> {
         Language::Java => validate_java(path),
         Language::JavaScript => validate_javascript(path),
         Language::TypeScript => validate_typescript(path),
+        Language::Haskell => validate_haskell(path),
+        Language::Elixir => validate_elixir(path),
     }
 }
 
 /// Validate a Python file using `python -m py_compile`.
 fn validate_python(path: &Path) -> Result<ValidationOutcome> {
     let output = Command::new("python")
-        .args(["-m", "py_compile", path.to_str().unwrap()])
+        .args(["-m", "py_compile"])
+        .arg(path)
         .output();
 
     match output {
@@ -131,50 +134,41 @@ fn validate_python(path: &Path) -> Result<ValidationOutcome> {
 
 /// Validate a C file using `gcc -fsyntax-only`.
 fn validate_c(path: &Path) -> Result<ValidationOutcome> {
-    let output = Command::new("gcc")
-        .args(["-fsyntax-only", "-c", path.to_str().unwrap()])
-        .output();
+    run_gcc_syntax_check("gcc", path, &[])
+}
 
-    match output {
-        Ok(result) => {
-            if result.status.success() {
-                return Ok(ValidationOutcome {
-                    is_valid: true,
-                    errors: vec![],
-                    warnings: vec![],
-                    tool_available: true,
-                });
-            }
+/// Validate a C++ file using `g++ -fsyntax-only`.
+fn validate_cpp(path: &Path) -> Result<ValidationOutcome> {
+    run_gcc_syntax_check("g++", path, &[])
+}
 
-            // Parse GCC error output
-            let stderr = String::from_utf8_lossy(&result.stderr);
-            let (errors, warnings) = parse_gcc_output(&stderr);
+/// Validate a C file with `gcc -fsyntax-only`, searching `include_dirs` for headers.
+pub fn validate_c_with_includes(path: &Path, include_dirs: &[PathBuf]) -> Result<ValidationOutcome> {
+    run_gcc_syntax_check("gcc", path, include_dirs)
+}
 
-            Ok(ValidationOutcome {
-                is_valid: errors.is_empty(),
-                errors,
-                warnings,
-                tool_available: true,
-            })
-        }
-        Err(e) => {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                return Ok(ValidationOutcome {
-                    is_valid: false,
-                    errors: vec![],
-                    warnings: vec![],
-                    tool_available: false,
-                });
-            }
-            Err(SpliceError::Other(format!("Failed to run gcc: {}", e)))
-        }
-    }
+/// Validate a C++ file with `g++ -fsyntax-only`, searching `include_dirs` for headers.
+pub fn validate_cpp_with_includes(
+    path: &Path,
+    include_dirs: &[PathBuf],
+) -> Result<ValidationOutcome> {
+    run_gcc_syntax_check("g++", path, include_dirs)
 }
 
-/// Validate a C++ file using `g++ -fsyntax-only`.
-fn validate_cpp(path: &Path) -> Result<ValidationOutcome> {
-    let output = Command::new("g++")
-        .args(["-fsyntax-only", "-c", path.to_str().unwrap()])
+/// Run `gcc`/`g++ -fsyntax-only` on a single translation unit.
+fn run_gcc_syntax_check(
+    compiler: &str,
+    path: &Path,
+    include_dirs: &[PathBuf],
+) -> Result<ValidationOutcome> {
+    let output = Command::new(compiler)
+        .args(include_dirs.iter().map(|dir| {
+            let mut flag = std::ffi::OsString::from("-I");
+            flag.push(dir);
+            flag
+        }))
+        .args(["-fsyntax-only", "-c"])
+        .arg(path)
         .output();
 
     match output {
@@ -188,7 +182,7 @@ fn validate_cpp(path: &Path) -> Result<ValidationOutcome> {
                 });
             }
 
-            // Parse g++ error output (same format as gcc)
+            // gcc and g++ share the same diagnostic format
             let stderr = String::from_utf8_lossy(&result.stderr);
             let (errors, warnings) = parse_gcc_output(&stderr);
 
@@ -208,15 +202,24 @@ fn validate_cpp(path: &Path) -> Result<ValidationOutcome> {
                     tool_available: false,
                 });
             }
-            Err(SpliceError::Other(format!("Failed to run g++: {}", e)))
+            Err(SpliceError::Other(format!("Failed to run {}: {}", compiler, e)))
         }
     }
 }
 
 /// Validate a Java file using `javac`.
+///
+/// The file's source root is passed as `-sourcepath`, so classes it uses from
+/// sibling files are found and compiled along with it. All class files go to
+/// a scratch directory, leaving none in the workspace.
 fn validate_java(path: &Path) -> Result<ValidationOutcome> {
+    let scratch = tempfile::tempdir()?;
     let output = Command::new("javac")
-        .args([path.to_str().unwrap()])
+        .arg("-d")
+        .arg(scratch.path())
+        .arg("-sourcepath")
+        .arg(java_source_root(path))
+        .arg(path)
         .output();
 
     match output {
@@ -255,12 +258,45 @@ fn validate_java(path: &Path) -> Result<ValidationOutcome> {
     }
 }
 
+/// Directory a Java file's package is rooted in.
+///
+/// For `src/com/example/Main.java` declaring `package com.example;` this is
+/// `src`. A file in the default package, or one whose directories do not
+/// match its package, is rooted in its own directory.
+fn java_source_root(path: &Path) -> PathBuf {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let source = std::fs::read_to_string(path).unwrap_or_default();
+    let package = source.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix("package ")
+            .and_then(|rest| rest.split(';').next())
+            .map(str::trim)
+    });
+    let Some(package) = package else {
+        return dir.to_path_buf();
+    };
+
+    let mut root = dir;
+    for segment in package.rsplit('.') {
+        match root.parent() {
+            Some(parent) if root.file_name().is_some_and(|name| name == segment) => root = parent,
+            _ => return dir.to_path_buf(),
+        }
+    }
+    if root.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        root.to_path_buf()
+    }
+}
+
 /// Validate a JavaScript file using `node --check`.
 fn validate_javascript(path: &Path) -> Result<ValidationOutcome> {
     // node --check is available in Node 16+
-    let output = Command::new("node")
-        .args(["--check", path.to_str().unwrap()])
-        .output();
+    let output = Command::new("node").arg("--check").arg(path).output();
 
     match output {
         Ok(result) => {
@@ -311,8 +347,10 @@ fn validate_typescript(path: &Path) -> Result<ValidationOutcome> {
     // We need to run it in the directory containing tsconfig.json (if it exists)
     let parent_dir = path.parent().unwrap_or_else(|| Path::new("."));
 
+    // A relative path would be resolved against `parent_dir` a second time
     let output = Command::new("tsc")
-        .args(["--noEmit", path.to_str().unwrap()])
+        .arg("--noEmit")
+        .arg(std::path::absolute(path)?)
         .current_dir(parent_dir)
         .output();
 
@@ -353,6 +391,121 @@ fn validate_typescript(path: &Path) -> Result<ValidationOutcome> {
     }
 }
 
+/// Validate a Haskell file using `ghc -fno-code`.
+///
+/// Best effort: a standalone module that imports packages outside the global
+/// package database will be reported as failing to compile.
+fn validate_haskell(path: &Path) -> Result<ValidationOutcome> {
+    let parent_dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+    // A relative path would be resolved against `parent_dir` a second time
+    let output = Command::new("ghc")
+        .arg("-fno-code")
+        .arg(std::path::absolute(path)?)
+        .current_dir(parent_dir)
+        .output();
+
+    match output {
+        Ok(result) => {
+            if result.status.success() {
+                return Ok(ValidationOutcome {
+                    is_valid: true,
+                    errors: vec![],
+                    warnings: vec![],
+                    tool_available: true,
+                });
+            }
+
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            let (errors, warnings) = parse_ghc_output(&stderr);
+
+            Ok(ValidationOutcome {
+                is_valid: false,
+                errors,
+                warnings,
+                tool_available: true,
+            })
+        }
+        Err(e) => {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                return Ok(ValidationOutcome {
+                    is_valid: false,
+                    errors: vec![],
+                    warnings: vec![],
+                    tool_available: false,
+                });
+            }
+            Err(SpliceError::Other(format!("Failed to run ghc: {}", e)))
+        }
+    }
+}
+
+/// Validate an Elixir file with `mix compile`, or `elixirc` outside a Mix project.
+///
+/// Best effort: `mix compile` builds the whole project containing the file
+/// (found by walking up to `mix.exs`), so unrelated errors elsewhere in the
+/// project also fail the gate. Standalone files are compiled into a scratch
+/// directory so no `.beam` files are left behind.
+fn validate_elixir(path: &Path) -> Result<ValidationOutcome> {
+    let mix_root = path
+        .parent()
+        .and_then(|dir| dir.ancestors().find(|d| d.join("mix.exs").is_file()));
+
+    let scratch = tempfile::tempdir()?;
+    let (tool, output) = match mix_root {
+        Some(root) => (
+            "mix",
+            Command::new("mix")
+                .args(["compile", "--return-errors"])
+                .current_dir(root)
+                .output(),
+        ),
+        None => (
+            "elixirc",
+            Command::new("elixirc")
+                .arg("-o")
+                .arg(scratch.path())
+                .arg(path)
+                .output(),
+        ),
+    };
+
+    match output {
+        Ok(result) => {
+            if result.status.success() {
+                return Ok(ValidationOutcome {
+                    is_valid: true,
+                    errors: vec![],
+                    warnings: vec![],
+                    tool_available: true,
+                });
+            }
+
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            let stdout = String::from_utf8_lossy(&result.stdout);
+            let (errors, warnings) = parse_elixir_output(&format!("{}{}", stderr, stdout));
+
+            Ok(ValidationOutcome {
+                is_valid: false,
+                errors,
+                warnings,
+                tool_available: true,
+            })
+        }
+        Err(e) => {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                return Ok(ValidationOutcome {
+                    is_valid: false,
+                    errors: vec![],
+                    warnings: vec![],
+                    tool_available: false,
+                });
+            }
+            Err(SpliceError::Other(format!("Failed to run {}: {}", tool, e)))
+        }
+    }
+}
+
 /// Parse Python error output from py_compile.
 ///
 /// Format (multi-line):
@@ -434,14 +587,14 @@ fn parse_python_errors(output: &str, file: &Path) -> Vec<ValidationError> {
 
 /// Parse GCC/g++ error output.
 ///
-/// Format: `<file>:<line>:<col>: error: <msg>` or `warning: <msg>`
+/// Format: `<file>:<line>:<col>: error: <msg>` (or `fatal error:`) or `warning: <msg>`
 fn parse_gcc_output(output: &str) -> (Vec<ValidationError>, Vec<ValidationError>) {
     let mut errors = Vec::new();
     let mut warnings = Vec::new();
 
     for line in output.lines() {
-        // Parse: "file:line:col: error: message"
-        if line.contains(": error: ") {
+        // Parse: "file:line:col: error: message" or "file:line:col: fatal error: message"
+        if line.contains(": error: ") || line.contains(": fatal error: ") {
             if let Some(error) = parse_gcc_line(line) {
                 errors.push(error);
             }
@@ -480,6 +633,187 @@ fn parse_gcc_line(line: &str) -> Option<ValidationError> {
     None
 }
 
+/// Parse GHC error/warning output.
+///
+/// Format: a header line `<file>:<line>:<col>: error: [GHC-xxxxx]` (the column
+/// may be a range such as `5-9`) followed by indented message lines.
+fn parse_ghc_output(output: &str) -> (Vec<ValidationError>, Vec<ValidationError>) {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    let mut current: Option<(bool, ValidationError)> = None;
+
+    for line in output.lines() {
+        if let Some((is_error, entry)) = parse_ghc_header(line) {
+            if let Some((was_error, done)) = current.replace((is_error, entry)) {
+                if was_error {
+                    errors.push(done);
+                } else {
+                    warnings.push(done);
+                }
+            }
+        } else if let Some((_, entry)) = current.as_mut() {
+            let text = line.trim();
+            // Skip the source excerpt GHC prints under the message
+            if text.is_empty() || text.contains(" | ") || text.ends_with('|') {
+                continue;
+            }
+            if !entry.message.is_empty() {
+                entry.message.push(' ');
+            }
+            entry.message.push_str(text);
+        }
+    }
+
+    if let Some((is_error, done)) = current {
+        if is_error {
+            errors.push(done);
+        } else {
+            warnings.push(done);
+        }
+    }
+
+    (errors, warnings)
+}
+
+/// Parse a GHC diagnostic header line, returning whether it is an error.
+fn parse_ghc_header(line: &str) -> Option<(bool, ValidationError)> {
+    let (is_error, marker) = if let Some(idx) = line.find(": error:") {
+        (true, idx)
+    } else if let Some(idx) = line.find(": warning:") {
+        (false, idx)
+    } else {
+        return None;
+    };
+
+    let location = &line[..marker];
+    let mut parts = location.rsplitn(3, ':');
+    let column = parts.next()?.split('-').next()?.parse::<usize>().ok()?;
+    let line_num = parts.next()?.parse::<usize>().ok()?;
+    let file = parts.next()?.to_string();
+
+    let rest = line[marker..].splitn(3, ':').nth(2).unwrap_or("").trim();
+    let (code, message) = match rest.strip_prefix('[') {
+        Some(tagged) => match tagged.split_once(']') {
+            Some((tag, tail)) => (
+                tag.split(',').next().map(|c| c.trim().to_string()),
+                tail.trim().to_string(),
+            ),
+            None => (None, rest.to_string()),
+        },
+        None => (None, rest.to_string()),
+    };
+
+    Some((
+        is_error,
+        ValidationError {
+            file,
+            line: line_num,
+            column,
+            message,
+            code,
+            note: None,
+        },
+    ))
+}
+
+/// Parse Elixir compiler output into errors and warnings.
+///
+/// Handles both exception banners (`** (SyntaxError) lib/a.ex:3:5: ...`)
+/// and the newer `error:`/`warning:` blocks whose location follows on a
+/// `└─ lib/a.ex:3:5` line.
+fn parse_elixir_output(output: &str) -> (Vec<ValidationError>, Vec<ValidationError>) {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    let mut pending: Option<(bool, ValidationError)> = None;
+
+    let mut flush = |pending: &mut Option<(bool, ValidationError)>| {
+        if let Some((is_error, entry)) = pending.take() {
+            if is_error {
+                errors.push(entry);
+            } else {
+                warnings.push(entry);
+            }
+        }
+    };
+
+    for line in output.lines() {
+        let text = line.trim();
+
+        if let Some(banner) = text.strip_prefix("** (") {
+            flush(&mut pending);
+            let Some((kind, rest)) = banner.split_once(')') else {
+                continue;
+            };
+            let mut entry = ValidationError {
+                file: String::new(),
+                line: 0,
+                column: 0,
+                message: rest.trim().to_string(),
+                code: Some(kind.to_string()),
+                note: None,
+            };
+            if let Some((file, line_num, column)) = find_elixir_location(rest) {
+                entry.file = file;
+                entry.line = line_num;
+                entry.column = column;
+            }
+            pending = Some((true, entry));
+            continue;
+        }
+
+        let header = text
+            .strip_prefix("error:")
+            .map(|m| (true, m))
+            .or_else(|| text.strip_prefix("warning:").map(|m| (false, m)));
+        if let Some((is_error, message)) = header {
+            flush(&mut pending);
+            pending = Some((
+                is_error,
+                ValidationError {
+                    file: String::new(),
+                    line: 0,
+                    column: 0,
+                    message: message.trim().to_string(),
+                    code: None,
+                    note: None,
+                },
+            ));
+            continue;
+        }
+
+        if let Some(location) = text.strip_prefix("└─ ") {
+            if let Some((_, entry)) = pending.as_mut() {
+                if let Some((file, line_num, column)) = find_elixir_location(location) {
+                    entry.file = file;
+                    entry.line = line_num;
+                    entry.column = column;
+                }
+            }
+            flush(&mut pending);
+        }
+    }
+    flush(&mut pending);
+
+    (errors, warnings)
+}
+
+/// Find the first `file.ex[s]:line[:col]` location in `text`.
+fn find_elixir_location(text: &str) -> Option<(String, usize, usize)> {
+    text.split_whitespace().find_map(|token| {
+        let mut parts = token.trim_end_matches(':').split(':');
+        let file = parts.next()?;
+        if !(file.ends_with(".ex") || file.ends_with(".exs")) {
+            return None;
+        }
+        let line_num = parts.next()?.parse::<usize>().ok()?;
+        let column = parts
+            .next()
+            .and_then(|c| c.parse::<usize>().ok())
+            .unwrap_or(0);
+        Some((file.to_string(), line_num, column))
+    })
+}
+
 /// Parse javac error output.
 ///
 /// Format: `<file>:<line>: error: <msg>`
@@ -533,7 +867,7 @@ fn parse_node_errors(output: &str, file: &Path) -> Vec<ValidationError> {
     let mut errors = Vec::new();
 
     for line in output.lines() {
-        if line.contains(file.to_str().unwrap_or("")) {
+        if line.contains(&*file.to_string_lossy()) {
             // Parse: "file:line (col) message" or "file:line message"
             if let Some(first_colon) = line.find(':') {
                 let after_file = &line[first_colon + 1..];
@@ -593,12 +927,16 @@ fn parse_tsc_errors(stderr: &str, stdout: &str, file: &Path) -> Vec<ValidationEr
     // Combine stderr and stdout (tsc outputs to both)
     let combined = format!("{}\n{}", stderr, stdout);
 
+    // tsc prints paths relative to its working directory, the file's own
+    let name = file
+        .file_name()
+        .unwrap_or(file.as_os_str())
+        .to_string_lossy();
+
     for line in combined.lines() {
         // Parse: "file.ts(line,col): error TS<code>: message"
         // or "file.ts(line,col): message"
-        if line.contains(file.to_str().unwrap_or(""))
-            && (line.contains(": error ") || line.contains("TS"))
-        {
+        if line.contains(&*name) && (line.contains(": error ") || line.contains("TS")) {
             // Try to extract line and column
             if let Some(open_paren) = line.find('(') {
                 let after_paren = &line[open_paren + 1..];
@@ -688,6 +1026,15 @@ mod tests {
         assert!(errors[0].message.contains("expected ';'"));
     }
 
+    #[test]
+    fn test_parse_gcc_fatal_error() {
+        let output = "src/main.c:1:10: fatal error: shapes.h: No such file or directory\n";
+        let (errors, _warnings) = parse_gcc_output(output);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].file, "src/main.c");
+        assert!(errors[0].message.contains("shapes.h"));
+    }
+
     #[test]
     fn test_parse_gcc_warning() {
         let output = "test.c:5:10: warning: unused variable 'x'\n";
@@ -709,6 +1056,40 @@ mod tests {
             .contains("class, interface, or enum expected"));
     }
 
+    #[test]
+    fn test_parse_ghc_error() {
+        let output = "Main.hs:3:9: error: [GHC-58481]\n    parse error on input \u{2018}=\u{2019}\n  |\n3 | add x = = y\n  |         ^\n";
+        let (errors, warnings) = parse_ghc_output(output);
+        assert!(warnings.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].file, "Main.hs");
+        assert_eq!(errors[0].line, 3);
+        assert_eq!(errors[0].column, 9);
+        assert_eq!(errors[0].code.as_deref(), Some("GHC-58481"));
+        assert!(errors[0].message.starts_with("parse error on input"));
+    }
+
+    #[test]
+    fn test_parse_elixir_errors() {
+        let output = "** (SyntaxError) lib/greeter.ex:3:7: syntax error before: ')'\n    (elixir 1.14.0) lib/kernel/parallel_compiler.ex:346: anonymous fn/5\n";
+        let (errors, warnings) = parse_elixir_output(output);
+        assert!(warnings.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].file, "lib/greeter.ex");
+        assert_eq!(errors[0].line, 3);
+        assert_eq!(errors[0].column, 7);
+        assert_eq!(errors[0].code.as_deref(), Some("SyntaxError"));
+
+        let output = "warning: variable \"x\" is unused\n  \u{2502}\n 2 \u{2502}   def f(x), do: 1\n  \u{2502}         ~\n  \u{2502}\n  \u{2514}\u{2500} lib/a.ex:2:9: A.f/1\n\nerror: undefined function g/0\n  \u{2514}\u{2500} lib/a.ex:4:5: A.h/0\n";
+        let (errors, warnings) = parse_elixir_output(output);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].line, 2);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].file, "lib/a.ex");
+        assert_eq!((errors[0].line, errors[0].column), (4, 5));
+        assert_eq!(errors[0].message, "undefined function g/0");
+    }
+
     #[test]
     fn test_parse_node_error() {
         let output = "test.js:2 (5) SyntaxError: Unexpected token\n";
@@ -742,6 +1123,15 @@ mod tests {
         assert!(errors[0].message.contains("Unterminated string literal"));
     }
 
+    #[test]
+    fn test_parse_tsc_error_matches_path_relative_to_file_directory() {
+        let output = "test.ts(2,5): error TS1002: Unterminated string literal\n";
+        let path = Path::new("src/test.ts");
+        let errors = parse_tsc_errors(output, "", path);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].file, "src/test.ts");
+    }
+
     #[test]
     fn test_parse_tsc_error_with_stderr() {
         let stderr = "";
@@ -753,4 +1143,34 @@ mod tests {
         assert_eq!(errors[0].column, 1);
         assert!(errors[0].message.contains("Cannot find name"));
     }
+
+    #[test]
+    fn test_java_file_validates_against_sibling_class_without_class_files() {
+        let workspace = tempfile::tempdir().unwrap();
+        let package_dir = workspace.path().join("src/com/example");
+        std::fs::create_dir_all(&package_dir).unwrap();
+        std::fs::write(
+            package_dir.join("Greeter.java"),
+            "package com.example;\n\npublic class Greeter {\n    public String greet() { return \"hi\"; }\n}\n",
+        )
+        .unwrap();
+        let main = package_dir.join("Main.java");
+        std::fs::write(
+            &main,
+            "package com.example;\n\npublic class Main {\n    public static void main(String[] args) {\n        System.out.println(new Greeter().greet());\n    }\n}\n",
+        )
+        .unwrap();
+        assert_eq!(java_source_root(&main), workspace.path().join("src"));
+
+        let outcome = validate_file(&main).unwrap();
+        if !outcome.tool_available {
+            println!("javac not available, skipping sourcepath validation test");
+            return;
+        }
+        assert!(outcome.is_valid, "errors: {:?}", outcome.errors);
+        for entry in std::fs::read_dir(&package_dir).unwrap() {
+            let path = entry.unwrap().path();
+            assert_ne!(path.extension().unwrap(), "class", "left behind {:?}", path);
+        }
+    }
 }
@@ -23,6 +23,59 @@ pub enum AnalyzerMode {
     Explicit(&'static str),
 }
 
+/// What the rust-analyzer gate checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnalyzerScope {
+    /// Every workspace member (`check --workspace`); the thorough default.
+    Workspace,
+
+    /// Only the package whose manifest lives in this directory.
+    Package(PathBuf),
+}
+
+impl AnalyzerScope {
+    /// Working directory and arguments for running rust-analyzer over this scope.
+    pub fn invocation<'a>(&'a self, workspace_dir: &'a Path) -> (&'a Path, Vec<&'static str>) {
+        match self {
+            AnalyzerScope::Workspace => (workspace_dir, vec!["check", "--workspace"]),
+            AnalyzerScope::Package(package_dir) => (package_dir, vec!["check"]),
+        }
+    }
+}
+
+/// Cargo feature selection forwarded to the `cargo check` gate.
+///
+/// The default selection checks only default features, matching plain `cargo check`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CargoFeatures {
+    /// Features to enable (`--features`).
+    pub features: Vec<String>,
+
+    /// Enable every feature of every workspace member (`--all-features`).
+    pub all_features: bool,
+
+    /// Disable the default feature set (`--no-default-features`).
+    pub no_default_features: bool,
+}
+
+impl CargoFeatures {
+    /// Command-line arguments for `cargo check`, in cargo's own flag syntax.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(","));
+        }
+        if self.all_features {
+            args.push("--all-features".to_string());
+        }
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+        args
+    }
+}
+
 /// Validation result from cargo check.
 #[derive(Debug, Clone, PartialEq)]
 pub enum ValidationResult {
@@ -74,6 +127,80 @@ pub enum ErrorLevel {
     Help,
 }
 
+/// How a tool counts the column of a reported position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnUnit {
+    /// UTF-8 bytes.
+    Byte,
+    /// Unicode scalar values; a tab counts as one column.
+    Char,
+    /// Display columns; a tab advances to the next multiple of `tab_width`.
+    Display {
+        /// Width of a tab stop.
+        tab_width: usize,
+    },
+}
+
+impl ColumnUnit {
+    /// Column convention of the validation tool used for `language`.
+    ///
+    /// gcc/g++, javac, and ghc expand tabs to 8-column stops; rustc, python,
+    /// node, tsc, and elixirc count characters.
+    pub fn for_language(language: crate::symbol::Language) -> Self {
+        use crate::symbol::Language;
+
+        match language {
+            Language::C | Language::Cpp | Language::Java | Language::Haskell => {
+                ColumnUnit::Display { tab_width: 8 }
+            }
+            Language::Rust
+            | Language::Python
+            | Language::JavaScript
+            | Language::TypeScript
+            | Language::Elixir => ColumnUnit::Char,
+        }
+    }
+}
+
+/// Convert a reported 1-based `line` and `column` into a byte offset in `source`.
+///
+/// Returns `None` if the line does not exist. A column past the end of the
+/// line clamps to the line end; a column inside a tab's expansion maps to the tab.
+pub fn byte_offset_for_position(
+    source: &str,
+    line: usize,
+    column: usize,
+    unit: ColumnUnit,
+) -> Option<usize> {
+    let rope = ropey::Rope::from_str(source);
+    if line == 0 || line > rope.len_lines() {
+        return None;
+    }
+
+    let mut offset = rope.line_to_byte(line - 1);
+    let mut current = 1;
+    for ch in rope.line(line - 1).chars() {
+        if current >= column || ch == '\n' || ch == '\r' {
+            break;
+        }
+        let next = match unit {
+            ColumnUnit::Byte => current + ch.len_utf8(),
+            ColumnUnit::Char => current + 1,
+            ColumnUnit::Display { tab_width } if ch == '\t' && tab_width > 0 => {
+                ((current - 1) / tab_width + 1) * tab_width + 1
+            }
+            ColumnUnit::Display { .. } => current + 1,
+        };
+        if next > column {
+            break;
+        }
+        current = next;
+        offset += ch.len_utf8();
+    }
+
+    Some(offset)
+}
+
 /// Run rust-analyzer validation gate.
 ///
 /// This function invokes rust-analyzer as an external process and treats
@@ -89,6 +216,19 @@ pub enum ErrorLevel {
 /// * `Err(SpliceError::AnalyzerNotAvailable)` - rust-analyzer not found
 /// * `Err(SpliceError::AnalyzerFailed)` - Diagnostics detected
 pub fn gate_rust_analyzer(workspace_dir: &Path, mode: AnalyzerMode) -> Result<()> {
+    gate_rust_analyzer_scoped(workspace_dir, mode, &AnalyzerScope::Workspace)
+}
+
+/// Run the rust-analyzer gate over `scope` only.
+///
+/// [`AnalyzerScope::Package`] runs in the package's directory without
+/// `--workspace`, which is much faster in a large workspace but does not
+/// recheck other members that depend on the change.
+pub fn gate_rust_analyzer_scoped(
+    workspace_dir: &Path,
+    mode: AnalyzerMode,
+    scope: &AnalyzerScope,
+) -> Result<()> {
     // If analyzer is off, skip gate
     if matches!(mode, AnalyzerMode::Off) {
         return Ok(());
@@ -104,9 +244,10 @@ pub fn gate_rust_analyzer(workspace_dir: &Path, mode: AnalyzerMode) -> Result<()
 
     // Invoke rust-analyzer to check for diagnostics
     // We use "analyze" command which outputs diagnostics to stdout
+    let (run_dir, args) = scope.invocation(workspace_dir);
     let output = Command::new(analyzer_binary)
-        .args(["check", "--workspace"])
-        .current_dir(workspace_dir)
+        .args(args)
+        .current_dir(run_dir)
         .output();
 
     match output {
@@ -242,6 +383,10 @@ fn parse_rust_style_output(output: &str) -> Vec<CompilerError> {
         if let Some(note) = parse_note_line(trimmed) {
             if let Some(idx) = last_index {
                 if let Some(entry) = errors.get_mut(idx) {
+                    // A lint raised to deny/forbid fails the build, whatever its header says
+                    if entry.level == ErrorLevel::Warning && lint_is_denied(&note) {
+                        entry.level = ErrorLevel::Error;
+                    }
                     entry.note = Some(match &entry.note {
                         Some(existing) => format!("{}\n{}", existing, note),
                         None => note,
@@ -313,6 +458,15 @@ struct PendingDiagnostic {
     code: Option<String>,
 }
 
+/// Whether a lint-level note says the lint is denied or forbidden, e.g.
+/// "`#[deny(unused_variables)]` implied by `#[deny(warnings)]`" or
+/// "`-D unused-variables` implied by `-D warnings`".
+fn lint_is_denied(note: &str) -> bool {
+    ["`#[deny(", "`#[forbid(", "`-D ", "`-F "]
+        .iter()
+        .any(|marker| note.contains(marker))
+}
+
 fn parse_note_line(line: &str) -> Option<String> {
     parse_labelled_line(line, "note")
 }
@@ -395,6 +549,44 @@ help: consider importing `missing_helper`
             "diagnostic note should capture help text"
         );
     }
+
+    #[test]
+    fn byte_offset_for_position_handles_tab_indentation() {
+        let source = "int main() {\n\t\treturn x;\n}\n";
+        let expected = source.find('x').unwrap();
+
+        // rustc-style character column: two tabs, then `return ` (7 chars)
+        assert_eq!(
+            byte_offset_for_position(source, 2, 10, ColumnUnit::Char),
+            Some(expected)
+        );
+        // gcc-style display column: two tabs expand to column 17
+        assert_eq!(
+            byte_offset_for_position(source, 2, 24, ColumnUnit::Display { tab_width: 8 }),
+            Some(expected)
+        );
+        assert_eq!(
+            byte_offset_for_position(source, 2, 10, ColumnUnit::Byte),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn byte_offset_for_position_clamps_and_rejects_out_of_range() {
+        let source = "\tab\ncd\n";
+
+        // Column inside the tab's expansion maps to the tab itself
+        assert_eq!(
+            byte_offset_for_position(source, 1, 4, ColumnUnit::Display { tab_width: 8 }),
+            Some(0)
+        );
+        // Column past the end of the line stops before the newline
+        assert_eq!(
+            byte_offset_for_position(source, 2, 40, ColumnUnit::Char),
+            Some(6)
+        );
+        assert_eq!(byte_offset_for_position(source, 9, 1, ColumnUnit::Char), None);
+    }
 }
 
 fn nonzero(value: usize) -> Option<usize> {
@@ -406,6 +406,156 @@ pub fn farewell(name: &str) -> String {
         // Will implement in STEP 2 after plan parsing is complete
     }
 
+    /// Test I2: `plan --validate-only` reports per-step failures without patching.
+    #[test]
+    fn test_plan_validate_only_reports_step_failures() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        let source = "pub fn greet() -> i32 {\n    1\n}\n";
+        std::fs::write(&lib_rs_path, source).expect("Failed to write lib.rs");
+        std::fs::write(
+            workspace_path.join("greet.rs"),
+            "pub fn greet() -> i32 {\n    2\n}\n",
+        )
+        .expect("Failed to write greet.rs");
+
+        let plan_path = workspace_path.join("plan.json");
+        std::fs::write(
+            &plan_path,
+            r#"{
+  "steps": [
+    {"file": "src/lib.rs", "symbol": "greet", "kind": "function", "with": "greet.rs"},
+    {"file": "src/lib.rs", "symbol": "graet", "kind": "function", "with": "greet.rs"}
+  ]
+}
+"#,
+        )
+        .expect("Failed to write plan.json");
+
+        let output = Command::new(get_splice_binary())
+            .arg("plan")
+            .arg("--file")
+            .arg(&plan_path)
+            .arg("--validate-only")
+            .current_dir(workspace_path)
+            .output()
+            .expect("Failed to run splice CLI");
+
+        assert!(!output.status.success(), "Validation should fail");
+
+        let payload: Value = serde_json::from_slice(&output.stderr)
+            .expect("stderr should contain JSON payload");
+        let error = &payload["error"];
+        assert_eq!(error["kind"], "PlanValidationFailed");
+
+        let diagnostics = error["diagnostics"]
+            .as_array()
+            .expect("diagnostics should be an array");
+        assert_eq!(diagnostics.len(), 1, "Only step 2 should fail");
+        let message = diagnostics[0]["message"].as_str().unwrap();
+        assert!(message.starts_with("Step 2:"), "got: {}", message);
+        assert!(message.contains("graet"), "got: {}", message);
+
+        assert_eq!(
+            std::fs::read_to_string(&lib_rs_path).unwrap(),
+            source,
+            "Validation must not modify source files"
+        );
+        assert!(
+            !src_dir.join(".splice_graph.db").exists(),
+            "Validation must not leave a graph database behind"
+        );
+    }
+
+    #[test]
+    fn test_plan_keep_going_applies_steps_around_a_failure() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        std::fs::write(
+            &lib_rs_path,
+            "pub fn first() -> i32 {\n    1\n}\n\npub fn third() -> i32 {\n    3\n}\n",
+        )
+        .expect("Failed to write lib.rs");
+        std::fs::write(
+            workspace_path.join("first.rs"),
+            "pub fn first() -> i32 {\n    10\n}\n",
+        )
+        .expect("Failed to write first.rs");
+        std::fs::write(
+            workspace_path.join("third.rs"),
+            "pub fn third() -> i32 {\n    30\n}\n",
+        )
+        .expect("Failed to write third.rs");
+
+        let plan_path = workspace_path.join("plan.json");
+        std::fs::write(
+            &plan_path,
+            r#"{
+  "steps": [
+    {"file": "src/lib.rs", "symbol": "first", "kind": "function", "with": "first.rs"},
+    {"file": "src/lib.rs", "symbol": "second", "kind": "function", "with": "first.rs"},
+    {"file": "src/lib.rs", "symbol": "third", "kind": "function", "with": "third.rs"}
+  ]
+}
+"#,
+        )
+        .expect("Failed to write plan.json");
+
+        let output = Command::new(get_splice_binary())
+            .arg("plan")
+            .arg("--file")
+            .arg(&plan_path)
+            .arg("--keep-going")
+            .current_dir(workspace_path)
+            .output()
+            .expect("Failed to run splice CLI");
+
+        assert!(
+            !output.status.success(),
+            "A failed step should fail the run"
+        );
+
+        let patched = std::fs::read_to_string(&lib_rs_path).unwrap();
+        assert!(
+            patched.contains("    10\n"),
+            "Step 1 should be applied: {}",
+            patched
+        );
+        assert!(
+            patched.contains("    30\n"),
+            "Step 3 should be applied: {}",
+            patched
+        );
+
+        let payload: Value =
+            serde_json::from_slice(&output.stderr).expect("stderr should contain JSON payload");
+        let error = &payload["error"];
+        assert_eq!(error["kind"], "PlanStepsFailed");
+
+        let diagnostics = error["diagnostics"]
+            .as_array()
+            .expect("diagnostics should be an array");
+        assert_eq!(diagnostics.len(), 3, "Every step should be reported");
+        let levels: Vec<_> = diagnostics.iter().map(|d| d["level"].clone()).collect();
+        assert_eq!(levels, vec![json!("note"), json!("error"), json!("note")]);
+        let message = diagnostics[1]["message"].as_str().unwrap();
+        assert!(message.starts_with("Step 2:"), "got: {}", message);
+        assert!(message.contains("second"), "got: {}", message);
+    }
+
     /// Test J: Symbol not found returns structured JSON payload.
     #[test]
     fn test_cli_symbol_not_found_returns_structured_json() {
@@ -1307,6 +1457,58 @@ pub fn value() -> i32 {
         );
     }
 
+    /// `--create-backup` has nothing to protect in a preview and must not touch the real workspace.
+    #[test]
+    fn test_cli_patch_preview_ignores_create_backup() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        let original = "pub fn value() -> i32 {\n    1\n}\n";
+        std::fs::write(&lib_rs_path, original).expect("Failed to write lib.rs");
+        let patch_path = workspace_path.join("patch.rs");
+        std::fs::write(&patch_path, "pub fn value() -> i32 {\n    2\n}\n")
+            .expect("Failed to write patch file");
+
+        let output = Command::new(get_splice_binary())
+            .arg("patch")
+            .arg("--file")
+            .arg(&lib_rs_path)
+            .arg("--symbol")
+            .arg("value")
+            .arg("--with")
+            .arg(&patch_path)
+            .arg("--preview")
+            .arg("--create-backup")
+            .args(["--gates", "tree-sitter"])
+            .current_dir(workspace_path)
+            .output()
+            .expect("Failed to run splice CLI");
+
+        assert!(
+            output.status.success(),
+            "preview with --create-backup should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let payload: Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be JSON payload");
+        assert!(payload["data"]["preview_report"].is_object());
+        assert!(
+            !workspace_path.join(".splice-backup").exists(),
+            "preview must not write a backup manifest"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&lib_rs_path).expect("Failed to read lib.rs"),
+            original
+        );
+    }
+
     /// Test O: Backup creation and undo restores files.
     #[test]
     fn test_cli_backup_and_undo() {
@@ -1457,10 +1659,2039 @@ pub fn greet(name: &str) -> String {
         );
     }
 
-    fn hash_file(path: &std::path::Path) -> String {
-        let bytes = std::fs::read(path).expect("Failed to read file for hashing");
-        let mut hasher = Sha256::new();
-        hasher.update(&bytes);
-        format!("{:x}", hasher.finalize())
+    /// Undo of a batch that created a file deletes it again.
+    #[test]
+    fn test_cli_undo_deletes_files_created_by_batch() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        let original_content = "pub fn helper() -> i32 {\n    1\n}\n";
+        std::fs::write(&lib_rs_path, original_content).expect("Failed to write lib.rs");
+
+        let batch_path = workspace_path.join("batch.json");
+        let batch_json = json!({
+            "batches": [{
+                "replacements": [
+                    { "file": "src/lib.rs", "start": 0, "end": 0, "content": "pub mod extra;\n" }
+                ],
+                "new_files": [
+                    { "file": "src/extra.rs", "content": "pub fn extra() -> i32 {\n    2\n}\n" }
+                ]
+            }]
+        });
+        std::fs::write(&batch_path, batch_json.to_string()).expect("write batch.json");
+
+        let output = Command::new(get_splice_binary())
+            .args(["patch", "--batch"])
+            .arg(&batch_path)
+            .args(["--language", "rust", "--create-backup"])
+            .current_dir(workspace_path)
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "CLI should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(src_dir.join("extra.rs").is_file());
+
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        let manifest_path = payload["data"]["backup_manifest"].as_str().unwrap();
+        let undo_output = Command::new(get_splice_binary())
+            .args(["undo", "--manifest", manifest_path])
+            .current_dir(workspace_path)
+            .output()
+            .expect("Failed to run splice undo");
+        assert!(
+            undo_output.status.success(),
+            "Undo should succeed: {}",
+            String::from_utf8_lossy(&undo_output.stderr)
+        );
+
+        assert_eq!(
+            std::fs::read_to_string(&lib_rs_path).unwrap(),
+            original_content
+        );
+        assert!(
+            !src_dir.join("extra.rs").exists(),
+            "created file should be deleted"
+        );
+    }
+
+    /// The resolve command reports a symbol's span as JSON and leaves the file alone.
+    #[test]
+    fn test_cli_resolve_prints_span() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let source_path = workspace_dir.path().join("lib.rs");
+        let source = "pub fn first() {}\n\npub fn greet(name: &str) -> String {\n    name.to_string()\n}\n";
+        std::fs::write(&source_path, source).expect("Failed to write lib.rs");
+
+        let output = Command::new(get_splice_binary())
+            .arg("resolve")
+            .arg("--file")
+            .arg(&source_path)
+            .arg("--symbol")
+            .arg("greet")
+            .arg("--kind")
+            .arg("function")
+            .output()
+            .expect("Failed to run splice CLI");
+
+        assert!(
+            output.status.success(),
+            "CLI should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let payload: Value = serde_json::from_str(&stdout).expect("stdout should be JSON payload");
+        let data = &payload["data"];
+
+        let byte_start = source.find("pub fn greet").unwrap();
+        assert_eq!(data["byte_start"], json!(byte_start));
+        assert_eq!(data["byte_end"], json!(source.trim_end().len()));
+        assert_eq!(data["name"], json!("greet"));
+        assert_eq!(data["kind"], json!("function"));
+        assert_eq!(data["line_start"], json!(3));
+        assert_eq!(data["line_end"], json!(5));
+        assert!(data.get("node_id").is_none());
+
+        let after = std::fs::read_to_string(&source_path).expect("Failed to read lib.rs");
+        assert_eq!(after, source, "resolve must not modify the file");
+    }
+
+    /// `--at` with UTF-16 columns resolves the symbol an LSP client points at.
+    #[test]
+    fn test_cli_resolve_at_utf16_position() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let source_path = workspace_dir.path().join("lib.rs");
+        let source = "pub fn crab() -> &'static str { \"🦀\" } pub fn after() {}\n";
+        std::fs::write(&source_path, source).expect("Failed to write lib.rs");
+
+        let after_byte = source.find("pub fn after").unwrap();
+        let utf16_col = source[..after_byte].encode_utf16().count();
+        assert_eq!(utf16_col, after_byte - 2);
+
+        let output = Command::new(get_splice_binary())
+            .arg("resolve")
+            .arg("--file")
+            .arg(&source_path)
+            .arg("--at")
+            .arg(format!("1:{}", utf16_col))
+            .arg("--position-encoding")
+            .arg("utf16")
+            .output()
+            .expect("Failed to run splice CLI");
+
+        assert!(
+            output.status.success(),
+            "CLI should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let payload: Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be JSON payload");
+        assert_eq!(payload["data"]["name"], json!("after"));
+        assert_eq!(payload["data"]["byte_start"], json!(after_byte));
+    }
+
+    /// `--no-graph` answers a hover from the extracted symbols and leaves no graph behind.
+    #[test]
+    fn test_cli_resolve_at_without_graph() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let source_path = workspace_dir.path().join("lib.rs");
+        let source =
+            "struct Meter;\n\nimpl Meter {\n    fn read(&self) -> u32 {\n        42\n    }\n}\n";
+        std::fs::write(&source_path, source).expect("Failed to write lib.rs");
+
+        let output = Command::new(get_splice_binary())
+            .arg("resolve")
+            .arg("--file")
+            .arg(&source_path)
+            .args(["--at", "5:8", "--no-graph"])
+            .output()
+            .expect("Failed to run splice CLI");
+
+        assert!(
+            output.status.success(),
+            "CLI should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let payload: Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be JSON payload");
+        let data = &payload["data"];
+        assert_eq!(data["name"], json!("read"));
+        assert_eq!(data["kind"], json!("function"));
+        assert_eq!(data["byte_start"], json!(source.find("fn read").unwrap()));
+        assert_eq!(data["line_start"], json!(4));
+        assert!(!workspace_dir.path().join(".splice_graph.db").exists());
+    }
+
+    /// `--qualified` picks one Python method or nested function among same-named ones.
+    #[test]
+    fn test_cli_resolve_python_method_by_qualified_name() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let source_path = workspace_dir.path().join("calc.py");
+        let source = "class Calculator:\n    def add(self, a, b):\n        return a + b\n\n    def reset(self):\n        self.total = 0\n\n\nclass Counter:\n    def reset(self):\n        self.count = 0\n\n\ndef outer():\n    def reset():\n        pass\n    return reset\n";
+        std::fs::write(&source_path, source).expect("Failed to write calc.py");
+
+        let resolve = |qualified: &str| {
+            let output = Command::new(get_splice_binary())
+                .arg("resolve")
+                .arg("--file")
+                .arg(&source_path)
+                .args(["--qualified", qualified])
+                .output()
+                .expect("Failed to run splice CLI");
+            assert!(
+                output.status.success(),
+                "CLI should succeed for {}: {}",
+                qualified,
+                String::from_utf8_lossy(&output.stderr)
+            );
+            let payload: Value =
+                serde_json::from_slice(&output.stdout).expect("stdout should be JSON payload");
+            payload["data"].clone()
+        };
+
+        let data = resolve("Calculator.reset");
+        assert_eq!(data["name"], json!("reset"));
+        assert_eq!(data["kind"], json!("function"));
+        assert_eq!(data["byte_start"], json!(source.find("def reset").unwrap()));
+        assert_eq!(data["line_start"], json!(5));
+
+        let data = resolve("Counter.reset");
+        assert_eq!(data["line_start"], json!(10));
+        let data = resolve("outer.reset");
+        assert_eq!(data["line_start"], json!(15));
+
+        let output = Command::new(get_splice_binary())
+            .arg("resolve")
+            .arg("--file")
+            .arg(&source_path)
+            .args(["--qualified", "Calculator.missing"])
+            .output()
+            .expect("Failed to run splice CLI");
+        assert_eq!(output.status.code(), Some(3));
+    }
+
+    /// `--insert-after` adds a new function below an existing one without replacing it.
+    #[test]
+    fn test_cli_patch_insert_after_keeps_existing_symbol() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\npath = \"src/lib.rs\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        std::fs::write(&lib_rs_path, "pub fn first() -> i32 {\n    1\n}\n")
+            .expect("Failed to write lib.rs");
+
+        let insert_path = workspace_path.join("second.rs");
+        std::fs::write(&insert_path, "pub fn second() -> i32 {\n    first() + 1\n}\n")
+            .expect("Failed to write insertion file");
+
+        // Default gates include cargo check, so success means both functions compile
+        let output = Command::new(get_splice_binary())
+            .arg("patch")
+            .arg("--file")
+            .arg(&lib_rs_path)
+            .arg("--insert-after")
+            .arg("first")
+            .arg("--with")
+            .arg(&insert_path)
+            .current_dir(workspace_path)
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "insert should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let after = std::fs::read_to_string(&lib_rs_path).expect("Failed to read lib.rs");
+        assert_eq!(
+            after,
+            "pub fn first() -> i32 {\n    1\n}\n\npub fn second() -> i32 {\n    first() + 1\n}\n"
+        );
+
+        let payload: Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be JSON payload");
+        let span = &payload["data"]["span_ids"][0];
+        assert_eq!(span["byte_start"], span["byte_end"], "insertion is zero-width");
+    }
+
+    /// A patch that compiles with a warning succeeds and reports the warning.
+    #[test]
+    fn test_cli_patch_success_reports_cargo_warnings() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\npath = \"src/lib.rs\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        std::fs::write(&lib_rs_path, "pub fn first() -> i32 {\n    1\n}\n")
+            .expect("Failed to write lib.rs");
+
+        let replacement_path = workspace_path.join("first.rs");
+        std::fs::write(
+            &replacement_path,
+            "pub fn first() -> i32 {\n    let unused_value = 2;\n    1\n}\n",
+        )
+        .expect("Failed to write replacement file");
+
+        let output = Command::new(get_splice_binary())
+            .arg("patch")
+            .arg("--file")
+            .arg(&lib_rs_path)
+            .arg("--symbol")
+            .arg("first")
+            .arg("--with")
+            .arg(&replacement_path)
+            .current_dir(workspace_path)
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "patch should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let payload: Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be JSON payload");
+        let warnings = payload["data"]["warnings"]
+            .as_array()
+            .expect("warnings should be reported");
+        let warning = warnings
+            .iter()
+            .find(|w| w["message"].as_str().unwrap_or("").contains("unused_value"))
+            .expect("unused variable warning should be reported");
+        assert_eq!(warning["tool"], json!("cargo-check"));
+        assert_eq!(warning["level"], json!("warning"));
+        assert_eq!(warning["file"], json!("src/lib.rs"));
+        assert_eq!(warning["line"], json!(2));
+    }
+
+    /// Inserting a function whose name is already defined fails before any compiler gate.
+    #[test]
+    fn test_cli_patch_insert_duplicate_symbol_is_refused() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        let source = "pub fn first() -> i32 {\n    1\n}\n\npub fn second() -> i32 {\n    2\n}\n";
+        std::fs::write(&lib_rs_path, source).expect("Failed to write lib.rs");
+
+        let insert_path = workspace_path.join("dup.rs");
+        std::fs::write(&insert_path, "pub fn second() -> i32 {\n    3\n}\n")
+            .expect("Failed to write insertion file");
+
+        let output = Command::new(get_splice_binary())
+            .arg("patch")
+            .arg("--file")
+            .arg(&lib_rs_path)
+            .arg("--insert-after")
+            .arg("first")
+            .arg("--with")
+            .arg(&insert_path)
+            .current_dir(workspace_path)
+            .output()
+            .expect("Failed to run splice CLI");
+        assert_eq!(output.status.code(), Some(5));
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let payload: Value = serde_json::from_str(stderr.lines().last().unwrap_or_default())
+            .expect("stderr should end with JSON error payload");
+        assert_eq!(payload["error"]["kind"], "DuplicateSymbol");
+        assert!(
+            payload["error"]["message"]
+                .as_str()
+                .unwrap()
+                .ends_with("lib.rs:5"),
+            "error should point at the existing definition: {}",
+            payload["error"]["message"]
+        );
+        assert!(!workspace_path.join("target").exists(), "cargo check should not run");
+        assert_eq!(std::fs::read_to_string(&lib_rs_path).unwrap(), source);
+    }
+
+    /// `--verify-hash` refuses to patch a file whose contents differ from the expected hash.
+    #[test]
+    fn test_cli_patch_verify_hash_mismatch_is_refused() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        let source = "pub fn value() -> i32 {\n    1\n}\n";
+        std::fs::write(&lib_rs_path, source).expect("Failed to write lib.rs");
+        let patch_path = workspace_path.join("patch.rs");
+        std::fs::write(&patch_path, "pub fn value() -> i32 {\n    2\n}")
+            .expect("Failed to write patch file");
+
+        let run = |hash: &str| {
+            Command::new(get_splice_binary())
+                .arg("patch")
+                .arg("--file")
+                .arg(&lib_rs_path)
+                .arg("--symbol")
+                .arg("value")
+                .arg("--with")
+                .arg(&patch_path)
+                .arg("--gates")
+                .arg("tree-sitter")
+                .arg("--verify-hash")
+                .arg(hash)
+                .output()
+                .expect("Failed to run splice CLI")
+        };
+
+        let wrong = "0".repeat(64);
+        let output = run(&wrong);
+        assert!(!output.status.success(), "stale hash must be refused");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let payload: Value = serde_json::from_str(stderr.lines().last().unwrap())
+            .expect("stderr should end with a JSON payload");
+        assert_eq!(payload["error"]["kind"], "HashMismatch");
+        assert_eq!(std::fs::read_to_string(&lib_rs_path).unwrap(), source);
+
+        let actual = format!("{:x}", Sha256::digest(source.as_bytes()));
+        let output = run(&actual);
+        assert!(
+            output.status.success(),
+            "matching hash should patch: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(std::fs::read_to_string(&lib_rs_path).unwrap().contains("    2\n"));
+    }
+
+    /// Markdown context format wraps each reference snippet in a fenced block.
+    #[test]
+    fn test_cli_references_markdown_context_format() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let source_path = workspace_dir.path().join("lib.rs");
+        let source = "fn helper() -> i32 {\n    1\n}\n\nfn caller() -> i32 {\n    helper() + 1\n}\n";
+        std::fs::write(&source_path, source).expect("Failed to write lib.rs");
+
+        let output = Command::new(get_splice_binary())
+            .arg("references")
+            .arg("--file")
+            .arg(&source_path)
+            .arg("--symbol")
+            .arg("helper")
+            .arg("--context-format")
+            .arg("markdown")
+            .output()
+            .expect("Failed to run splice CLI");
+
+        assert!(
+            output.status.success(),
+            "CLI should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let payload: Value = serde_json::from_str(&stdout).expect("stdout should be JSON payload");
+        let references = payload["data"]["references"]
+            .as_array()
+            .expect("references should be an array");
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0]["line"], json!(6));
+
+        let snippet = references[0]["snippet"].as_str().expect("snippet string");
+        let caption = format!("`{}:6`", source_path.display());
+        assert_eq!(
+            snippet,
+            format!("{}\n```rust\n    helper() + 1\n```", caption)
+        );
+    }
+
+    /// `--context-symbol` drops references outside the named enclosing function.
+    #[test]
+    fn test_cli_references_context_symbol_excludes_other_functions() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let source_path = workspace_dir.path().join("lib.rs");
+        let source = "fn helper() -> i32 {\n    1\n}\n\nfn first() -> i32 {\n    helper() + 1\n}\n\nfn second() -> i32 {\n    helper() * helper()\n}\n";
+        std::fs::write(&source_path, source).expect("Failed to write lib.rs");
+
+        let references = |context: &str| {
+            Command::new(get_splice_binary())
+                .arg("references")
+                .arg("--file")
+                .arg(&source_path)
+                .arg("--symbol")
+                .arg("helper")
+                .arg("--context-symbol")
+                .arg(context)
+                .output()
+                .expect("Failed to run splice CLI")
+        };
+
+        let output = references("second");
+        assert!(
+            output.status.success(),
+            "CLI should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let payload: Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be JSON payload");
+        let lines: Vec<&Value> = payload["data"]["references"]
+            .as_array()
+            .expect("references should be an array")
+            .iter()
+            .map(|r| &r["line"])
+            .collect();
+        assert_eq!(lines, vec![&json!(10), &json!(10)]);
+
+        let output = references("missing");
+        assert_eq!(
+            output.status.code(),
+            Some(3),
+            "an unknown context symbol is not found"
+        );
+    }
+
+    /// `--before-context`/`--after-context` widen each snippet asymmetrically.
+    #[test]
+    fn test_cli_references_asymmetric_context_window() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let source_path = workspace_dir.path().join("lib.rs");
+        let source = "fn helper() -> i32 {\n    1\n}\n\nfn user() -> i32 {\n    let x = 1;\n    let y = helper();\n    let z = 2;\n    x + y + z\n}\n";
+        std::fs::write(&source_path, source).expect("Failed to write lib.rs");
+
+        let snippet = |flags: &[&str]| {
+            let output = Command::new(get_splice_binary())
+                .arg("references")
+                .arg("--file")
+                .arg(&source_path)
+                .arg("--symbol")
+                .arg("helper")
+                .args(flags)
+                .output()
+                .expect("Failed to run splice CLI");
+            assert!(
+                output.status.success(),
+                "CLI should succeed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            let payload: Value =
+                serde_json::from_slice(&output.stdout).expect("stdout should be JSON payload");
+            assert_eq!(payload["data"]["references"][0]["line"], json!(7));
+            payload["data"]["references"][0]["snippet"]
+                .as_str()
+                .expect("snippet should be a string")
+                .to_string()
+        };
+
+        assert_eq!(snippet(&[]), "    let y = helper();");
+
+        let window = "    let x = 1;\n    let y = helper();\n    let z = 2;\n    x + y + z\n}";
+        assert_eq!(
+            snippet(&["--before-context", "1", "--after-context", "3"]),
+            window
+        );
+        // `--context` fills in whichever side is not given
+        assert_eq!(snippet(&["--context", "1", "--after-context", "3"]), window);
+        assert_eq!(
+            snippet(&["-C", "1"]),
+            "    let x = 1;\n    let y = helper();\n    let z = 2;"
+        );
+        // The window stops at the end of the file
+        assert!(snippet(&["-A", "10"]).ends_with("x + y + z\n}"));
+    }
+
+    /// `references --graph` renders the definition and each referencing file as DOT.
+    #[test]
+    fn test_cli_references_graph_links_definition_to_files() {
+        let workspace_dir = tempfile::Builder::new()
+            .prefix("graph")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace_dir.path();
+        std::fs::create_dir(root.join("src")).expect("Failed to create src directory");
+        let files = [
+            (
+                "Cargo.toml",
+                "[package]\nname = \"graph\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+            ),
+            (
+                "src/lib.rs",
+                "pub mod a;\npub mod b;\n\npub fn helper() -> i32 {\n    1\n}\n",
+            ),
+            (
+                "src/a.rs",
+                "use crate::helper;\n\npub fn one() -> i32 {\n    helper()\n}\n",
+            ),
+            (
+                "src/b.rs",
+                "use crate::helper;\n\npub fn two() -> i32 {\n    helper() + helper()\n}\n",
+            ),
+        ];
+        for (path, source) in files {
+            std::fs::write(root.join(path), source).expect("Failed to write workspace file");
+        }
+        let lib_path = root.join("src/lib.rs");
+
+        let output = Command::new(get_splice_binary())
+            .arg("references")
+            .arg("--file")
+            .arg(&lib_path)
+            .arg("--symbol")
+            .arg("helper")
+            .arg("--graph")
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "CLI should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        let dot = payload["data"]["graph"]
+            .as_str()
+            .expect("graph should be DOT text");
+
+        assert!(dot.starts_with("digraph references {"), "dot: {}", dot);
+        assert!(
+            dot.contains("\"def:helper\" [shape=doubleoctagon"),
+            "dot: {}",
+            dot
+        );
+        for (file, count) in [("a.rs", 2), ("b.rs", 3)] {
+            let file_node = format!("\"file:{}\"", root.join("src").join(file).display());
+            let edge = format!("\"def:helper\" -> {} [label=\"{}\"];", file_node, count);
+            assert!(dot.contains(&edge), "missing `{}` in {}", edge, dot);
+            assert_eq!(
+                dot.matches(&format!("{} -> \"ref:", file_node)).count(),
+                count
+            );
+        }
+    }
+
+    /// `references --stream` prints the same references as a bare JSON array.
+    #[test]
+    fn test_cli_references_stream_matches_buffered_output() {
+        let workspace_dir = tempfile::Builder::new()
+            .prefix("stream")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace_dir.path();
+        std::fs::create_dir(root.join("src")).expect("Failed to create src directory");
+        let files = [
+            (
+                "Cargo.toml",
+                "[package]\nname = \"stream\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+            ),
+            (
+                "src/lib.rs",
+                "pub mod a;\npub mod b;\n\npub fn helper() -> i32 {\n    1\n}\n\nfn local() -> i32 {\n    helper()\n}\n",
+            ),
+            (
+                "src/a.rs",
+                "use crate::helper;\n\npub fn one() -> i32 {\n    helper()\n}\n",
+            ),
+            (
+                "src/b.rs",
+                "use crate::helper;\n\npub fn two() -> i32 {\n    helper() + helper()\n}\n",
+            ),
+        ];
+        for (path, source) in files {
+            std::fs::write(root.join(path), source).expect("Failed to write workspace file");
+        }
+        let lib_path = root.join("src/lib.rs");
+
+        let run = |flags: &[&str]| {
+            let output = Command::new(get_splice_binary())
+                .arg("references")
+                .arg("--file")
+                .arg(&lib_path)
+                .args(["--symbol", "helper", "-C", "1"])
+                .args(flags)
+                .output()
+                .expect("Failed to run splice CLI");
+            assert!(
+                output.status.success(),
+                "CLI should succeed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            output.stdout
+        };
+
+        let buffered: Value = serde_json::from_slice(&run(&[])).unwrap();
+        let stdout = run(&["--stream"]);
+        let streamed: Value =
+            serde_json::from_slice(&stdout).expect("streamed output should be a JSON array");
+
+        assert_eq!(streamed.as_array().map(Vec::len), Some(6));
+        assert_eq!(streamed, buffered["data"]["references"]);
+        // One reference per line, between the brackets
+        assert_eq!(String::from_utf8_lossy(&stdout).lines().count(), 8);
+    }
+
+    /// `rename --dry-run` lists the definition and reference rewrites and writes nothing.
+    #[test]
+    fn test_cli_rename_dry_run_lists_rewrites_without_writing() {
+        let workspace_dir = tempfile::Builder::new()
+            .prefix("rename")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace_dir.path();
+        std::fs::create_dir(root.join("src")).expect("Failed to create src directory");
+        let files = [
+            (
+                "Cargo.toml",
+                "[package]\nname = \"rename\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+            ),
+            (
+                "src/lib.rs",
+                "pub mod a;\npub mod b;\n\npub fn helper() -> i32 {\n    1\n}\n\nfn local() -> i32 {\n    helper()\n}\n",
+            ),
+            (
+                "src/a.rs",
+                "use crate::helper;\n\npub fn one() -> i32 {\n    helper()\n}\n\npub fn shadowed() -> i32 {\n    let helper = 2;\n    helper\n}\n",
+            ),
+            (
+                "src/b.rs",
+                "use crate::helper as h;\n\npub fn two() -> i32 {\n    h()\n}\n",
+            ),
+        ];
+        for (path, source) in files {
+            std::fs::write(root.join(path), source).expect("Failed to write workspace file");
+        }
+        let snapshot = || {
+            let mut entries: Vec<_> = std::fs::read_dir(root.join("src"))
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .collect();
+            entries.sort();
+            let contents: Vec<_> = entries
+                .iter()
+                .map(|path| std::fs::read(path).unwrap())
+                .collect();
+            (entries, contents)
+        };
+        let before = snapshot();
+
+        let output = Command::new(get_splice_binary())
+            .arg("rename")
+            .arg("--file")
+            .arg(root.join("src/lib.rs"))
+            .args(["--symbol", "helper", "--to", "assist", "--dry-run"])
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "CLI should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(snapshot(), before, "dry run must not touch the workspace");
+
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        let mut rewrites: Vec<(String, u64, u64, bool)> = Vec::new();
+        for file in payload["data"]["files"].as_array().unwrap() {
+            let name = file["file_path"]
+                .as_str()
+                .unwrap()
+                .rsplit('/')
+                .next()
+                .unwrap();
+            for edit in file["edits"].as_array().unwrap() {
+                assert_eq!(edit["old"], json!("helper"));
+                assert_eq!(edit["new"], json!("assist"));
+                rewrites.push((
+                    name.to_string(),
+                    edit["line"].as_u64().unwrap(),
+                    edit["column"].as_u64().unwrap(),
+                    edit["definition"].as_bool().unwrap(),
+                ));
+            }
+        }
+        // The shadowing local in a.rs and the `h` alias in b.rs are left alone;
+        // only the aliased import's own path segment changes
+        assert_eq!(
+            rewrites,
+            vec![
+                ("a.rs".to_string(), 1, 11, false),
+                ("a.rs".to_string(), 4, 4, false),
+                ("b.rs".to_string(), 1, 11, false),
+                ("lib.rs".to_string(), 4, 7, true),
+                ("lib.rs".to_string(), 9, 4, false),
+            ]
+        );
+        let aliased = payload["data"]["aliased"].as_array().unwrap();
+        assert_eq!(aliased.len(), 1);
+        assert_eq!(aliased[0]["alias"], json!("h"));
+    }
+
+    #[test]
+    fn test_cli_rename_rewrites_every_file_through_the_gates() {
+        let workspace_dir = tempfile::Builder::new()
+            .prefix("rename_apply")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace_dir.path();
+        std::fs::create_dir(root.join("src")).expect("Failed to create src directory");
+        let files = [
+            (
+                "Cargo.toml",
+                "[package]\nname = \"rename_apply\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+            ),
+            (
+                "src/lib.rs",
+                "pub mod a;\n\npub fn helper() -> i32 {\n    1\n}\n",
+            ),
+            (
+                "src/a.rs",
+                "use crate::helper;\n\npub fn one() -> i32 {\n    helper()\n}\n\npub fn shadowed() -> i32 {\n    let helper = 2;\n    helper\n}\n",
+            ),
+        ];
+        for (path, source) in files {
+            std::fs::write(root.join(path), source).expect("Failed to write workspace file");
+        }
+
+        let output = Command::new(get_splice_binary())
+            .arg("rename")
+            .arg("--file")
+            .arg(root.join("src/lib.rs"))
+            .args(["--symbol", "helper", "--to", "assist"])
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "CLI should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+            "pub mod a;\n\npub fn assist() -> i32 {\n    1\n}\n"
+        );
+        // The shadowing local keeps its name
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/a.rs")).unwrap(),
+            "use crate::assist;\n\npub fn one() -> i32 {\n    assist()\n}\n\npub fn shadowed() -> i32 {\n    let helper = 2;\n    helper\n}\n"
+        );
+
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(payload["data"]["dry_run"], json!(false));
+        let files = payload["data"]["files"].as_array().unwrap();
+        assert_eq!(files.len(), 2);
+        for file in files {
+            assert_ne!(file["before_hash"], file["after_hash"]);
+        }
+    }
+
+    #[test]
+    fn test_cli_report_unreferenced_lists_only_unused_private_items() {
+        let workspace_dir = tempfile::Builder::new()
+            .prefix("unreferenced")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace_dir.path();
+        std::fs::create_dir(root.join("src")).expect("Failed to create src directory");
+        let files = [
+            (
+                "Cargo.toml",
+                "[package]\nname = \"unreferenced\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+            ),
+            (
+                "src/lib.rs",
+                "pub fn api() -> i32 {\n    used()\n}\n\nfn used() -> i32 {\n    1\n}\n\nfn unused() -> i32 {\n    2\n}\n\npub struct S;\n\nimpl S {\n    fn method(&self) {}\n}\n",
+            ),
+        ];
+        for (path, source) in files {
+            std::fs::write(root.join(path), source).expect("Failed to write workspace file");
+        }
+
+        let output = Command::new(get_splice_binary())
+            .args(["references", "--report-unreferenced", "--file"])
+            .arg(root.join("src"))
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "CLI should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        let unreferenced = payload["data"]["unreferenced"].as_array().unwrap();
+        let names: Vec<&str> = unreferenced
+            .iter()
+            .map(|s| s["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["unused"]);
+        assert_eq!(unreferenced[0]["kind"], "function");
+        assert_eq!(unreferenced[0]["visibility"], "private");
+        assert_eq!(unreferenced[0]["line"], 9);
+        assert_eq!(payload["data"]["scanned_files"], 1);
+    }
+
+    #[test]
+    fn test_cli_deeply_nested_expression_fails_without_crashing() {
+        let workspace_dir = tempfile::Builder::new()
+            .prefix("deep")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let file = workspace_dir.path().join("deep.rs");
+        let depth = 100_000;
+        let source = format!(
+            "fn helper() -> i32 {{ 1 }}\nfn f() -> i32 {{ {}helper(){} }}\n",
+            "(".repeat(depth),
+            ")".repeat(depth)
+        );
+        std::fs::write(&file, source).expect("Failed to write source file");
+
+        let output = Command::new(get_splice_binary())
+            .arg("references")
+            .arg("--file")
+            .arg(&file)
+            .args(["--symbol", "helper"])
+            .output()
+            .expect("Failed to run splice CLI");
+
+        assert_eq!(
+            output.status.code(),
+            Some(1),
+            "should exit with an error, not abort"
+        );
+        let payload: Value = serde_json::from_slice(&output.stderr).unwrap();
+        assert_eq!(payload["error"]["kind"], "AstTooDeep");
+        assert_eq!(payload["error"]["file"], file.to_str().unwrap());
+    }
+
+    #[test]
+    fn test_cli_long_builder_chain_is_within_default_depth() {
+        let workspace_dir = tempfile::Builder::new()
+            .prefix("chain")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let file = workspace_dir.path().join("chain.rs");
+        let source = format!(
+            "struct B;\nimpl B {{\n    fn helper(self) -> B {{ self }}\n}}\nfn f() -> B {{ B{} }}\n",
+            ".helper()".repeat(500)
+        );
+        std::fs::write(&file, source).expect("Failed to write source file");
+
+        let output = Command::new(get_splice_binary())
+            .arg("references")
+            .arg("--file")
+            .arg(&file)
+            .args(["--symbol", "helper"])
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let graph = workspace_dir.path().join("graph.db");
+        let output = Command::new(get_splice_binary())
+            .arg("ingest")
+            .arg("--graph")
+            .arg(&graph)
+            .arg(workspace_dir.path())
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(payload["data"]["files"], 1);
+    }
+
+    /// `--profile` fills in flags from `splice.toml`; explicit flags still win.
+    #[test]
+    fn test_cli_profile_flags_apply_and_yield_to_explicit_flags() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        let source = "fn helper() -> i32 {\n    1\n}\n\nfn user() -> i32 {\n    let x = 1;\n    let y = helper();\n    let z = 2;\n    x + y + z\n}\n";
+        std::fs::write(workspace_path.join("lib.rs"), source).expect("Failed to write lib.rs");
+        std::fs::write(
+            workspace_path.join("splice.toml"),
+            "[profile.review]\ncontext = 1\nanalyzer = \"off\"\n",
+        )
+        .expect("Failed to write splice.toml");
+
+        let run = |flags: &[&str]| {
+            Command::new(get_splice_binary())
+                .current_dir(workspace_path)
+                .args(["references", "--file", "lib.rs", "--symbol", "helper"])
+                .args(flags)
+                .output()
+                .expect("Failed to run splice CLI")
+        };
+        let snippet = |flags: &[&str]| {
+            let output = run(flags);
+            assert!(
+                output.status.success(),
+                "CLI should succeed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            let payload: Value =
+                serde_json::from_slice(&output.stdout).expect("stdout should be JSON payload");
+            payload["data"]["references"][0]["snippet"]
+                .as_str()
+                .expect("snippet should be a string")
+                .to_string()
+        };
+
+        assert_eq!(
+            snippet(&["--profile", "review"]),
+            "    let x = 1;\n    let y = helper();\n    let z = 2;"
+        );
+        assert_eq!(
+            snippet(&["--profile=review", "--context", "0"]),
+            "    let y = helper();"
+        );
+
+        let output = run(&["--profile", "missing"]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("no profile `missing`"));
+    }
+
+    /// `remove-import` drops one name from a braced `use` group and keeps the rest.
+    #[test]
+    fn test_cli_remove_import_drops_one_name_from_group() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\npath = \"src/lib.rs\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        let source = "mod a {\n    pub fn b() {}\n    pub fn c() {}\n    pub fn d() {}\n}\n\nuse a::{b, c, d};\n\npub fn run() {\n    b();\n    d();\n}\n";
+        std::fs::write(&lib_rs_path, source).expect("Failed to write lib.rs");
+
+        let remove = |name: &str| {
+            Command::new(get_splice_binary())
+                .arg("remove-import")
+                .arg("--file")
+                .arg(&lib_rs_path)
+                .arg("--name")
+                .arg(name)
+                .output()
+                .expect("Failed to run splice CLI")
+        };
+
+        let output = remove("c");
+        assert!(
+            output.status.success(),
+            "remove-import should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(
+            std::fs::read_to_string(&lib_rs_path).unwrap(),
+            source.replace("use a::{b, c, d};", "use a::{b, d};")
+        );
+
+        // Removing a name that is still used fails the compiler gate and is rolled back
+        let output = remove("a::d");
+        assert_eq!(
+            output.status.code(),
+            Some(5),
+            "unresolved d should fail validation"
+        );
+        assert!(std::fs::read_to_string(&lib_rs_path)
+            .unwrap()
+            .contains("use a::{b, d};"));
+    }
+
+    /// `replace-file` rewrites a whole file through the gates and rolls back a broken version.
+    #[test]
+    fn test_cli_replace_file_rolls_back_invalid_contents() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\npath = \"src/lib.rs\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        std::fs::write(&lib_rs_path, "pub fn answer() -> i32 {\n    41\n}\n")
+            .expect("Failed to write lib.rs");
+
+        let replace_with = |content: &str| {
+            let with_path = workspace_path.join("new_lib.rs");
+            std::fs::write(&with_path, content).expect("Failed to write new_lib.rs");
+            Command::new(get_splice_binary())
+                .arg("replace-file")
+                .arg("--file")
+                .arg(&lib_rs_path)
+                .arg("--with")
+                .arg(&with_path)
+                .output()
+                .expect("Failed to run splice CLI")
+        };
+
+        let valid = "pub fn answer() -> i32 {\n    42\n}\n\npub fn question() -> &'static str {\n    \"?\"\n}\n";
+        let output = replace_with(valid);
+        assert!(
+            output.status.success(),
+            "Valid replacement should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(std::fs::read_to_string(&lib_rs_path).unwrap(), valid);
+
+        let output = replace_with("pub fn answer() -> i32 {\n    \"forty-two\"\n}\n");
+        assert_eq!(output.status.code(), Some(5), "type error should fail validation");
+        assert_eq!(
+            std::fs::read_to_string(&lib_rs_path).unwrap(),
+            valid,
+            "Failed replacement must restore the previous contents"
+        );
+    }
+
+    /// Undo refuses to overwrite edits made after the operation unless forced.
+    #[test]
+    fn test_cli_undo_refuses_diverged_files_without_force() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\npath = \"src/lib.rs\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+
+        let lib_rs_path = src_dir.join("lib.rs");
+        let original_content = "pub fn answer() -> i32 {\n    41\n}\n";
+        std::fs::write(&lib_rs_path, original_content).expect("Failed to write lib.rs");
+        let patch_path = workspace_path.join("patch.rs");
+        std::fs::write(&patch_path, "pub fn answer() -> i32 {\n    42\n}")
+            .expect("Failed to write patch.rs");
+
+        let splice_binary = get_splice_binary();
+        let output = Command::new(&splice_binary)
+            .arg("patch")
+            .arg("--file")
+            .arg(&lib_rs_path)
+            .arg("--symbol")
+            .arg("answer")
+            .arg("--with")
+            .arg(&patch_path)
+            .arg("--create-backup")
+            .current_dir(workspace_path)
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "Patch should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let payload: Value = serde_json::from_slice(&output.stdout).expect("stdout should be JSON");
+        let manifest_path = PathBuf::from(
+            payload["data"]["backup_manifest"]
+                .as_str()
+                .expect("backup_manifest should be in response"),
+        );
+
+        // Edit the file after the operation
+        let edited_content = "pub fn answer() -> i32 {\n    42\n}\n\npub fn later() {}\n";
+        std::fs::write(&lib_rs_path, edited_content).expect("Failed to edit lib.rs");
+
+        let refused = Command::new(&splice_binary)
+            .arg("undo")
+            .arg("--manifest")
+            .arg(&manifest_path)
+            .output()
+            .expect("Failed to run splice undo");
+        assert!(!refused.status.success(), "Undo should refuse diverged files");
+
+        let payload: Value = serde_json::from_slice(&refused.stderr)
+            .expect("stderr should contain JSON payload");
+        assert_eq!(payload["error"]["kind"], "UndoDiverged");
+        assert_eq!(
+            payload["error"]["diagnostics"][0]["file"],
+            json!("src/lib.rs")
+        );
+        assert_eq!(
+            std::fs::read_to_string(&lib_rs_path).unwrap(),
+            edited_content,
+            "Refused undo must not touch the file"
+        );
+
+        let forced = Command::new(&splice_binary)
+            .arg("undo")
+            .arg("--manifest")
+            .arg(&manifest_path)
+            .arg("--force")
+            .output()
+            .expect("Failed to run splice undo --force");
+        assert!(
+            forced.status.success(),
+            "Forced undo should succeed: {}",
+            String::from_utf8_lossy(&forced.stderr)
+        );
+        assert_eq!(
+            std::fs::read_to_string(&lib_rs_path).unwrap(),
+            original_content
+        );
+    }
+
+    fn hash_file(path: &std::path::Path) -> String {
+        let bytes = std::fs::read(path).expect("Failed to read file for hashing");
+        let mut hasher = Sha256::new();
+        hasher.update(&bytes);
+        format!("{:x}", hasher.finalize())
+    }
+
+    #[test]
+    fn test_cli_payloads_echo_operation_id() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        let py_path = workspace_path.join("app.py");
+        std::fs::write(&py_path, "x = 10\n").expect("Failed to write app.py");
+
+        let splice_binary = get_splice_binary();
+        let output = Command::new(&splice_binary)
+            .arg("apply-files")
+            .arg("--glob")
+            .arg("*.py")
+            .arg("--find")
+            .arg("10")
+            .arg("--replace")
+            .arg("20")
+            .arg("--no-validate")
+            .arg("--operation-id")
+            .arg("op-apply-1")
+            .current_dir(workspace_path)
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "apply-files should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let payload: Value = serde_json::from_slice(&output.stdout).expect("stdout should be JSON");
+        assert_eq!(payload["operation_id"], "op-apply-1");
+
+        // Errors carry the ID too, and verbose log lines are prefixed with it
+        let output = Command::new(&splice_binary)
+            .arg("--verbose")
+            .arg("delete")
+            .arg("--file")
+            .arg(&py_path)
+            .arg("--symbol")
+            .arg("missing")
+            .arg("--operation-id")
+            .arg("op-delete-2")
+            .env("RUST_LOG", "error")
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(!output.status.success());
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.lines().any(|line| line.contains("op=op-delete-2")),
+            "log lines should carry the operation id: {}",
+            stderr
+        );
+        let json_line = stderr.lines().last().expect("stderr should not be empty");
+        let payload: Value = serde_json::from_str(json_line).expect("last line should be JSON");
+        assert_eq!(payload["operation_id"], "op-delete-2");
+        assert_eq!(payload["error"]["kind"], "SymbolNotFound");
+    }
+
+    #[test]
+    fn test_apply_files_summary_only_reports_totals() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(workspace_path.join("a.py"), "x = 10\ny = 10\n").expect("Failed to write a.py");
+        std::fs::write(workspace_path.join("b.py"), "z = 10\n").expect("Failed to write b.py");
+
+        let splice_binary = get_splice_binary();
+        let output = Command::new(&splice_binary)
+            .arg("apply-files")
+            .arg("--glob")
+            .arg("*.py")
+            .arg("--find")
+            .arg("10")
+            .arg("--replace")
+            .arg("200")
+            .arg("--no-validate")
+            .arg("--summary-only")
+            .current_dir(workspace_path)
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "apply-files should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let payload: Value = serde_json::from_slice(&output.stdout).expect("stdout should be JSON");
+        let data = &payload["data"];
+        assert!(data.get("files_patched").is_none(), "per-file array should be omitted: {}", data);
+        assert_eq!(data["files_patched_count"], 2);
+        assert_eq!(data["replacements_count"], 3);
+        // Each replacement removes 2 bytes and inserts 3
+        assert_eq!(data["bytes_changed"], 15);
+        assert_eq!(
+            std::fs::read_to_string(workspace_path.join("a.py")).unwrap(),
+            "x = 200\ny = 200\n"
+        );
+    }
+
+    /// `splice serve` answers a `resolve` request on stdin with the symbol span on stdout.
+    #[test]
+    fn test_cli_serve_resolve_over_stdio() {
+        use std::io::{BufRead, BufReader};
+        use std::process::Stdio;
+
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let source_path = workspace_dir.path().join("lib.rs");
+        let source = "pub fn crab() -> &'static str { \"🦀\" }\npub fn after() {}\n";
+        std::fs::write(&source_path, source).expect("Failed to write lib.rs");
+        let after_byte = source.find("pub fn after").unwrap();
+
+        let mut child = Command::new(get_splice_binary())
+            .arg("serve")
+            .arg("--position-encoding")
+            .arg("utf16")
+            .arg("--graph")
+            .arg(workspace_dir.path().join("graph.db"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to spawn splice serve");
+
+        let mut stdin = child.stdin.take().unwrap();
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "resolve",
+            "params": { "file": source_path, "position": { "line": 1, "column": 10 } },
+        });
+        writeln!(stdin, "{}", request).unwrap();
+
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .expect("Failed to read response");
+        writeln!(stdin, "{}", json!({ "jsonrpc": "2.0", "method": "exit" })).unwrap();
+        drop(stdin);
+        assert!(child.wait().unwrap().success());
+
+        let response: Value = serde_json::from_str(&line).expect("response should be JSON");
+        assert_eq!(response["id"], json!(1));
+        let result = &response["result"];
+        assert_eq!(result["name"], json!("crab"));
+        assert_eq!(result["byte_start"], json!(0));
+        assert_eq!(result["byte_end"], json!(after_byte - 1));
+        // The closing brace follows the 2-unit emoji, so UTF-16 is 2 columns short of bytes
+        assert_eq!(result["end"], json!({ "line": 1, "column": after_byte - 1 - 2 }));
+    }
+
+    /// `graph-query` lists symbols a `serve --graph` session stored, filtered by selector.
+    #[test]
+    fn test_cli_graph_query_filters_stored_symbols() {
+        use std::io::{BufRead, BufReader};
+        use std::process::Stdio;
+
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let source_path = workspace_dir.path().join("lib.rs");
+        std::fs::write(&source_path, "pub fn foo() {}\npub fn foobar() {}\npub struct Foo;\n")
+            .expect("Failed to write lib.rs");
+        let graph_path = workspace_dir.path().join("graph.db");
+
+        // Ingest through a serve session that keeps the graph
+        let mut child = Command::new(get_splice_binary())
+            .arg("serve")
+            .arg("--graph")
+            .arg(&graph_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to spawn splice serve");
+        let mut stdin = child.stdin.take().unwrap();
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "resolve",
+            "params": { "file": source_path, "symbol": "foo" },
+        });
+        writeln!(stdin, "{}", request).unwrap();
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .expect("Failed to read response");
+        writeln!(stdin, "{}", json!({ "jsonrpc": "2.0", "method": "exit" })).unwrap();
+        drop(stdin);
+        assert!(child.wait().unwrap().success());
+
+        let output = Command::new(get_splice_binary())
+            .arg("graph-query")
+            .arg("--graph")
+            .arg(&graph_path)
+            .arg("kind:function name:foo*")
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "graph-query failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let payload: Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be JSON payload");
+        let names: Vec<&str> = payload["data"]["symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|symbol| symbol["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["foo", "foobar"]);
+        assert_eq!(payload["data"]["count"], json!(2));
+
+        let output = Command::new(get_splice_binary())
+            .arg("graph-query")
+            .arg("--graph")
+            .arg(&graph_path)
+            .arg("shape:round")
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(!output.status.success(), "unknown selector keys are rejected");
+    }
+
+    /// Deleting a symbol re-exported at the crate root is refused unless `--force` is given.
+    #[test]
+    fn test_cli_delete_refuses_public_api_reexport() {
+        // Workspace scanning skips hidden directories, so avoid the default `.tmp` prefix
+        let workspace_dir = tempfile::Builder::new()
+            .prefix("public-api")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace_dir.path();
+        std::fs::create_dir(root.join("src")).expect("Failed to create src directory");
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"public-api\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        // Re-exported through `api` and then again at the crate root
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "mod api;\nmod utils;\n\npub use crate::api::helper;\npub(crate) use crate::utils::other;\n",
+        )
+        .expect("Failed to write lib.rs");
+        std::fs::write(root.join("src/api.rs"), "pub use crate::utils::helper;\n")
+            .expect("Failed to write api.rs");
+        let utils_path = root.join("src/utils.rs");
+        let utils_source = "pub fn helper() {}\n\npub fn other() {}\n";
+        std::fs::write(&utils_path, utils_source).expect("Failed to write utils.rs");
+
+        let run_delete = |symbol: &str, extra: &[&str]| {
+            Command::new(get_splice_binary())
+                .arg("delete")
+                .arg("--file")
+                .arg(&utils_path)
+                .arg("--symbol")
+                .arg(symbol)
+                .args(extra)
+                .output()
+                .expect("Failed to run splice CLI")
+        };
+
+        let output = run_delete("helper", &[]);
+        assert!(!output.status.success(), "delete should be refused");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let payload: Value = serde_json::from_str(stderr.lines().last().unwrap())
+            .expect("stderr should end with JSON payload");
+        assert_eq!(payload["error"]["kind"], json!("PublicApiChange"));
+        let message = payload["error"]["message"].as_str().unwrap();
+        assert!(message.contains("lib.rs:4"), "unexpected message: {}", message);
+        assert_eq!(std::fs::read_to_string(&utils_path).unwrap(), utils_source);
+
+        // A dry run reports the re-export instead of failing
+        let output = run_delete("helper", &["--dry-run"]);
+        assert!(output.status.success());
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        let reexports = payload["data"]["public_reexports"].as_array().unwrap();
+        assert_eq!(reexports.len(), 1);
+        assert!(reexports[0].as_str().unwrap().ends_with("lib.rs:4"));
+
+        // `pub(crate) use` does not reach downstream crates
+        let output = run_delete("other", &["--dry-run"]);
+        assert!(output.status.success());
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert!(payload["data"].get("public_reexports").is_none());
+    }
+
+    /// `--trim-blank-lines` collapses the gap a deleted item leaves between its neighbours.
+    #[test]
+    fn test_cli_delete_trim_blank_lines_collapses_gap() {
+        let workspace_dir = tempfile::Builder::new()
+            .prefix("trim-blank")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace_dir.path();
+        std::fs::create_dir(root.join("src")).expect("Failed to create src directory");
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"trim-blank\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let lib_path = root.join("src/lib.rs");
+        let source = "pub fn first() {}\n\n\npub fn middle() {}\n\n\npub fn last() {}\n";
+
+        let run_delete = |extra: &[&str]| {
+            std::fs::write(&lib_path, source).expect("Failed to write lib.rs");
+            let output = Command::new(get_splice_binary())
+                .arg("delete")
+                .arg("--file")
+                .arg(&lib_path)
+                .arg("--symbol")
+                .arg("middle")
+                .args(extra)
+                .output()
+                .expect("Failed to run splice CLI");
+            assert!(
+                output.status.success(),
+                "delete failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+            (std::fs::read_to_string(&lib_path).unwrap(), payload)
+        };
+
+        // Without the flag the blank lines from both sides are left behind
+        let (untrimmed, payload) = run_delete(&[]);
+        assert!(untrimmed.contains("\n\n\n\n"));
+        assert!(payload["data"].get("blank_runs_trimmed").is_none());
+
+        let (trimmed, payload) = run_delete(&["--trim-blank-lines"]);
+        assert_eq!(trimmed, "pub fn first() {}\n\n\npub fn last() {}\n");
+        assert!(!trimmed.contains("\n\n\n\n"), "no triple blank line may remain");
+        assert_eq!(payload["data"]["blank_runs_trimmed"], json!(1));
+
+        let (trimmed, _) = run_delete(&["--trim-blank-lines=1"]);
+        assert_eq!(trimmed, "pub fn first() {}\n\npub fn last() {}\n");
+    }
+
+    /// The trim goes in the delete's batch and skips blank lines inside literals.
+    #[test]
+    fn test_cli_delete_trim_blank_lines_keeps_literals() {
+        let workspace_dir = tempfile::Builder::new()
+            .prefix("trim-literal")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace_dir.path();
+        std::fs::create_dir(root.join("src")).expect("Failed to create src directory");
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"trim-literal\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let lib_path = root.join("src/lib.rs");
+        let text = "pub const TEXT: &str = \"a\n\n\n\n\nb\";\n";
+        std::fs::write(
+            &lib_path,
+            format!(
+                "{}\npub fn caller() {{\n    gone();\n}}\n\n\npub fn gone() {{}}\n",
+                text
+            ),
+        )
+        .expect("Failed to write lib.rs");
+
+        let output = Command::new(get_splice_binary())
+            .arg("delete")
+            .arg("--file")
+            .arg(&lib_path)
+            .arg("--symbol")
+            .arg("gone")
+            .arg("--trim-blank-lines")
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "delete failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(payload["data"]["blank_runs_trimmed"], json!(1));
+
+        let lib = std::fs::read_to_string(&lib_path).unwrap();
+        assert!(lib.starts_with(text), "literal must be untouched");
+        assert!(!lib.contains("gone"), "reference must go too");
+    }
+
+    /// `--symbol-regex` deletes every matching symbol in one batch and keeps the rest.
+    #[test]
+    fn test_cli_delete_symbol_regex_removes_matching_functions() {
+        let workspace_dir = tempfile::Builder::new()
+            .prefix("symbol-regex")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace_dir.path();
+        std::fs::create_dir(root.join("src")).expect("Failed to create src directory");
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"symbol-regex\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let lib_path = root.join("src/lib.rs");
+        let source = "fn tmp_helper() -> i32 {\n    1\n}\n\nfn tmp_caller() -> i32 {\n    tmp_helper() + 1\n}\n\npub fn keep() -> i32 {\n    2\n}\n\npub fn helper_tmp_name() -> i32 {\n    3\n}\n";
+        std::fs::write(&lib_path, source).expect("Failed to write lib.rs");
+
+        let delete = |extra: &[&str]| {
+            Command::new(get_splice_binary())
+                .arg("delete")
+                .arg("--file")
+                .arg(&lib_path)
+                .arg("--symbol-regex")
+                .arg("^tmp_")
+                .args(extra)
+                .output()
+                .expect("Failed to run splice CLI")
+        };
+
+        // The call inside tmp_caller is the only reference; a limit of 0 refuses it
+        let output = delete(&["--max-references", "0"]);
+        assert_eq!(output.status.code(), Some(1));
+        let payload: Value = serde_json::from_slice(&output.stderr).unwrap();
+        assert_eq!(payload["error"]["kind"], json!("TooManyReferences"));
+        assert_eq!(std::fs::read_to_string(&lib_path).unwrap(), source);
+
+        // No struct matches, so nothing is resolved
+        let output = delete(&["--kind", "struct"]);
+        assert_eq!(output.status.code(), Some(3));
+
+        let output = delete(&["--max-references", "1"]);
+        assert!(
+            output.status.success(),
+            "delete failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(
+            payload["data"]["symbols"],
+            json!(["tmp_helper", "tmp_caller"])
+        );
+        // The call went with tmp_caller's definition, leaving two spans
+        assert_eq!(payload["data"]["span_ids"].as_array().unwrap().len(), 2);
+
+        let remaining = std::fs::read_to_string(&lib_path).unwrap();
+        assert!(!remaining.contains("fn tmp_"));
+        assert!(remaining.contains("pub fn keep() -> i32 {\n    2\n}"));
+        assert!(remaining.contains("pub fn helper_tmp_name() -> i32 {\n    3\n}"));
+        // Earlier runs' nodes and the deleted symbols are gone from the graph,
+        // leaving the file and the two kept functions
+        let graph = splice::graph::CodeGraph::open(&root.join("src/.splice_graph.db"))
+            .expect("Failed to open graph");
+        assert_eq!(graph.node_ids().unwrap().len(), 3);
+    }
+
+    /// `--explain` lists the steps and gates of a patch without touching the target.
+    #[test]
+    fn test_cli_patch_explain_describes_gates_without_running() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let lib_path = workspace_dir.path().join("lib.rs");
+        let source = "pub fn answer() -> i32 {\n    42\n}\n";
+        std::fs::write(&lib_path, source).expect("Failed to write lib.rs");
+        let patch_path = workspace_dir.path().join("patch.rs");
+        std::fs::write(&patch_path, "pub fn answer() -> i32 {\n    41\n}\n")
+            .expect("Failed to write patch.rs");
+
+        let output = Command::new(get_splice_binary())
+            .arg("patch")
+            .arg("--file")
+            .arg(&lib_path)
+            .arg("--symbol")
+            .arg("answer")
+            .arg("--with")
+            .arg(&patch_path)
+            .arg("--fast-check")
+            .arg("--explain")
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(output.status.success());
+
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(payload["data"]["command"], json!("patch"));
+        assert_eq!(
+            payload["data"]["gates"],
+            json!(["tree-sitter reparse", "cargo check (owning target only)"])
+        );
+        let steps: Vec<&str> = payload["data"]["steps"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|step| step.as_str().unwrap())
+            .collect();
+        let lib = lib_path.display().to_string();
+        assert_eq!(
+            steps[0],
+            format!("Resolve symbol 'answer' in {}", lib),
+            "steps: {:?}",
+            steps
+        );
+        assert!(steps.contains(&"Run gates: tree-sitter reparse, cargo check (owning target only)"));
+
+        // Nothing was resolved or patched
+        assert_eq!(std::fs::read_to_string(&lib_path).unwrap(), source);
+    }
+
+    /// Usage, not-found and validation failures exit with different codes; `--quiet` silences success.
+    #[test]
+    fn test_cli_exit_codes_distinguish_error_classes() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        // Patch locates the workspace root by its Cargo.toml
+        std::fs::write(
+            workspace_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"exit-codes\"\nversion = \"0.1.0\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let source_path = workspace_dir.path().join("lib.py");
+        let source = "def greet():\n    return 1\n";
+        std::fs::write(&source_path, source).expect("Failed to write lib.py");
+        let broken_path = workspace_dir.path().join("broken.py");
+        std::fs::write(&broken_path, "def greet(:\n    return 2\n")
+            .expect("Failed to write broken.py");
+        let fixed_path = workspace_dir.path().join("fixed.py");
+        std::fs::write(&fixed_path, "def greet():\n    return 2\n")
+            .expect("Failed to write fixed.py");
+
+        let run_patch = |symbol: &str, with: &PathBuf, quiet: bool| {
+            let mut command = Command::new(get_splice_binary());
+            if quiet {
+                command.arg("--quiet");
+            }
+            command
+                .arg("patch")
+                .arg("--file")
+                .arg(&source_path)
+                .arg("--symbol")
+                .arg(symbol)
+                .arg("--with")
+                .arg(with)
+                .arg("--gates")
+                .arg("tree-sitter")
+                .output()
+                .expect("Failed to run splice CLI")
+        };
+
+        let not_found = run_patch("missing", &fixed_path, false);
+        assert_eq!(not_found.status.code(), Some(3));
+
+        let invalid = run_patch("greet", &broken_path, false);
+        assert_eq!(
+            invalid.status.code(),
+            Some(5),
+            "stderr: {}",
+            String::from_utf8_lossy(&invalid.stderr)
+        );
+        assert_eq!(std::fs::read_to_string(&source_path).unwrap(), source);
+
+        let quiet = run_patch("greet", &fixed_path, true);
+        assert_eq!(quiet.status.code(), Some(0));
+        assert!(quiet.stdout.is_empty());
+
+        // clap's own usage error keeps a code of its own
+        let usage = Command::new(get_splice_binary())
+            .args(["patch", "--no-such-flag"])
+            .output()
+            .expect("Failed to run splice CLI");
+        assert_eq!(usage.status.code(), Some(2));
+        assert_ne!(usage.status.code(), not_found.status.code());
+    }
+
+    /// `references --follow-renames` finds stragglers still using a name from before a rename commit.
+    #[test]
+    fn test_cli_references_follow_renames_finds_stale_usages() {
+        let workspace_dir = tempfile::Builder::new()
+            .prefix("renames")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace_dir.path();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .args(["-c", "user.name=Splice", "-c", "user.email=splice@example.com"])
+                .args(args)
+                .current_dir(root)
+                .output()
+                .expect("Failed to run git");
+            assert!(output.status.success(), "git {:?} failed", args);
+        };
+
+        std::fs::create_dir(root.join("src")).expect("Failed to create src directory");
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"renames\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let lib_path = root.join("src/lib.rs");
+        std::fs::write(&lib_path, "pub mod caller;\n\npub fn old_total() -> i32 {\n    1\n}\n")
+            .expect("Failed to write lib.rs");
+        std::fs::write(
+            root.join("src/caller.rs"),
+            "use crate::old_total;\n\npub fn run() -> i32 {\n    old_total()\n}\n",
+        )
+        .expect("Failed to write caller.rs");
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "initial"]);
+
+        // The rename commit misses caller.rs
+        std::fs::write(&lib_path, "pub mod caller;\n\npub fn total() -> i32 {\n    1\n}\n")
+            .expect("Failed to write lib.rs");
+        git(&["commit", "-q", "-am", "rename old_total to total"]);
+
+        let output = Command::new(get_splice_binary())
+            .arg("references")
+            .arg("--file")
+            .arg(&lib_path)
+            .arg("--symbol")
+            .arg("total")
+            .arg("--follow-renames")
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "CLI should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(payload["data"]["former_names"], json!(["old_total"]));
+        let stale: Vec<&Value> = payload["data"]["references"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|r| r["former_name"] == json!("old_total"))
+            .collect();
+        let mut lines: Vec<u64> = stale.iter().map(|r| r["line"].as_u64().unwrap()).collect();
+        lines.sort();
+        assert_eq!(lines, vec![1, 4]);
+        assert!(stale
+            .iter()
+            .all(|r| r["file_path"].as_str().unwrap().ends_with("caller.rs")));
+
+        // A path relative to the working directory finds the same history
+        let output = Command::new(get_splice_binary())
+            .args(["references", "--file", "src/lib.rs", "--symbol", "total"])
+            .arg("--follow-renames")
+            .current_dir(root)
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "CLI should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(payload["data"]["former_names"], json!(["old_total"]));
+    }
+
+    /// The hidden `dump-ast` command shows the parse tree and, on request, the extracted symbols.
+    #[test]
+    fn test_cli_dump_ast_shows_function_item() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let file_path = workspace_dir.path().join("lib.rs");
+        std::fs::write(&file_path, "fn answer() -> i32 {\n    42\n}\n")
+            .expect("Failed to write lib.rs");
+
+        let output = Command::new(get_splice_binary())
+            .arg("dump-ast")
+            .arg("--file")
+            .arg(&file_path)
+            .arg("--symbols")
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "dump-ast should succeed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let payload: Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should contain JSON payload");
+        let ast = payload["data"]["ast"]
+            .as_str()
+            .expect("ast should be a string");
+        assert!(
+            ast.contains("(function_item name: (identifier)"),
+            "got: {}",
+            ast
+        );
+        assert_eq!(payload["data"]["symbols"][0]["name"], "answer");
+        assert_eq!(payload["data"]["symbols"][0]["byte_start"], 0);
+    }
+
+    /// `languages` lists every supported language, as a table and as JSON.
+    #[test]
+    fn test_cli_languages_lists_every_language() {
+        let expected = [
+            ("rust", "cargo"),
+            ("python", "python"),
+            ("c", "gcc"),
+            ("cpp", "g++"),
+            ("java", "javac"),
+            ("javascript", "node"),
+            ("typescript", "tsc"),
+            ("haskell", "ghc"),
+            ("elixir", "elixir"),
+        ];
+
+        let output = Command::new(get_splice_binary())
+            .args(["languages", "--format", "json"])
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(output.status.success());
+        let payload: Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be JSON payload");
+        let languages = payload["data"]["languages"]
+            .as_array()
+            .expect("languages array");
+        assert_eq!(languages.len(), expected.len());
+        for (entry, (language, tool)) in languages.iter().zip(expected) {
+            assert_eq!(entry["language"], json!(language));
+            assert_eq!(entry["validation_tool"], json!(tool));
+            assert_eq!(entry["grammar"], json!(true));
+        }
+        assert_eq!(
+            languages[3]["extensions"],
+            json!(["cpp", "hpp", "cc", "cxx"])
+        );
+
+        let output = Command::new(get_splice_binary())
+            .arg("languages")
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(output.status.success());
+        let table = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(table.lines().count(), expected.len() + 1);
+        for (language, _) in expected {
+            assert!(
+                table.lines().any(|line| line.starts_with(language)),
+                "missing {} in:\n{}",
+                language,
+                table
+            );
+        }
+    }
+
+    /// The plan schema printed by `json-schema` accepts a well-formed plan and rejects a broken one.
+    #[test]
+    fn test_cli_json_schema_plan_validates_plan_document() {
+        let output = Command::new(get_splice_binary())
+            .arg("json-schema")
+            .arg("plan")
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(output.status.success());
+
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        let schema = jsonschema::JSONSchema::compile(&payload["data"])
+            .expect("plan schema should compile");
+
+        let plan = json!({
+            "steps": [
+                {"file": "src/lib.rs", "symbol": "foo", "kind": "function", "with": "patches/foo.rs"},
+                {"file": "src/lib.rs", "symbol": "bar", "with": "patches/bar.rs"}
+            ]
+        });
+        assert!(schema.is_valid(&plan));
+
+        let missing_with = json!({"steps": [{"file": "src/lib.rs", "symbol": "foo"}]});
+        assert!(!schema.is_valid(&missing_with));
+
+        // Without an argument every format is listed
+        let output = Command::new(get_splice_binary())
+            .arg("json-schema")
+            .output()
+            .expect("Failed to run splice CLI");
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        let formats: Vec<&String> = payload["data"].as_object().unwrap().keys().collect();
+        assert_eq!(formats, vec!["batch", "error", "plan", "success"]);
+    }
+
+    /// `ingest` stores a directory's symbols in a new graph that `graph-query` can search.
+    #[test]
+    fn test_cli_ingest_then_graph_query() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let src = workspace_dir.path().join("src");
+        std::fs::create_dir(&src).expect("Failed to create src directory");
+        std::fs::write(src.join("lib.rs"), "pub fn alpha() {}\npub struct Beta;\n").unwrap();
+        std::fs::write(src.join("tool.py"), "def gamma():\n    pass\n").unwrap();
+        let graph_path = workspace_dir.path().join("graph.db");
+
+        let ingest = || {
+            Command::new(get_splice_binary())
+                .arg("ingest")
+                .arg("--graph")
+                .arg(&graph_path)
+                .arg("--jobs")
+                .arg("2")
+                .arg(workspace_dir.path())
+                .output()
+                .expect("Failed to run splice CLI")
+        };
+        let output = ingest();
+        assert!(
+            output.status.success(),
+            "ingest failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(payload["data"]["files"], json!(2));
+        assert_eq!(payload["data"]["symbols"], json!(3));
+
+        let output = Command::new(get_splice_binary())
+            .arg("graph-query")
+            .arg("--graph")
+            .arg(&graph_path)
+            .arg("kind:function")
+            .output()
+            .expect("Failed to run splice CLI");
+        let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+        let mut names: Vec<&str> = payload["data"]["symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|symbol| symbol["name"].as_str().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["alpha", "gamma"]);
+
+        // A second run would duplicate every node, so it is refused
+        assert!(!ingest().status.success());
     }
 }
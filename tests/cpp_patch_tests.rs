@@ -4,8 +4,12 @@
 //! resolve → patch-by-span → tree-sitter reparse gate → g++ compilation gate
 
 use splice::graph::CodeGraph;
+use splice::SpliceError;
 use splice::ingest::cpp::extract_cpp_symbols;
-use splice::patch::apply_patch_with_validation;
+use splice::patch::{
+    apply_batch_with_options, apply_patch_with_validation, SpanBatch, SpanReplacement,
+    ValidationOptions,
+};
 use splice::resolve::resolve_symbol;
 use splice::symbol::Language;
 use splice::validate::AnalyzerMode;
@@ -259,4 +263,130 @@ int get_number(void) {
             println!("gcc not available, skipping C patch validation");
         }
     }
+
+    /// Test D: gcc diagnostics on a tab-indented file carry the byte offset of
+    /// the reported position, not the tab-expanded display column.
+    #[test]
+    fn test_c_diagnostic_byte_offset_with_tab_indentation() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        let c_path = workspace_path.join("tabs.c");
+        let source = "int get_number(void) {\n\t\treturn 10;\n}\n";
+        std::fs::write(&c_path, source).expect("Failed to write tabs.c");
+
+        let symbols =
+            extract_cpp_symbols(&c_path, source.as_bytes()).expect("Failed to parse tabs.c");
+        let symbol = &symbols[0];
+
+        // Undeclared identifier: parses fine, fails in gcc
+        let replacement = "int get_number(void) {\n\t\treturn undefined_var;\n}";
+        let result = apply_patch_with_validation(
+            &c_path,
+            symbol.byte_start,
+            symbol.byte_end,
+            replacement,
+            workspace_path,
+            Language::C,
+            AnalyzerMode::Off,
+        );
+
+        let diagnostics = match result {
+            Err(SpliceError::CompilerValidationFailed { diagnostics, .. }) => diagnostics,
+            Ok(_) => {
+                println!("gcc not available, skipping diagnostic offset check");
+                return;
+            }
+            Err(other) => panic!("unexpected error: {:?}", other),
+        };
+
+        let patched = format!(
+            "{}{}{}",
+            &source[..symbol.byte_start],
+            replacement,
+            &source[symbol.byte_end..]
+        );
+        let expected = patched.find("undefined_var").unwrap();
+
+        let diag = diagnostics
+            .iter()
+            .find(|d| d.message.contains("undefined_var"))
+            .expect("gcc should report the undeclared identifier");
+        assert_eq!(diag.line, Some(2));
+        assert_eq!(diag.byte_offset, Some(expected));
+    }
+
+    /// Test E: a batch editing a header in `include/` and the source that
+    /// includes it only validates when include paths are set up.
+    #[test]
+    fn test_c_batch_header_and_user_validate_in_include_aware_mode() {
+        if std::process::Command::new("gcc")
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            println!("gcc not available, skipping include-aware batch test");
+            return;
+        }
+
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::create_dir_all(workspace_path.join("include")).unwrap();
+        std::fs::create_dir_all(workspace_path.join("src")).unwrap();
+
+        let header_path = workspace_path.join("include/shapes.h");
+        let source_path = workspace_path.join("src/main.c");
+        let header = "int area(int side);\n";
+        let source = "#include \"shapes.h\"\n\nint main(void) {\n    return area(2);\n}\n";
+        std::fs::write(&header_path, header).unwrap();
+        std::fs::write(&source_path, source).unwrap();
+
+        let call_start = source.find("area(2)").unwrap();
+        let batches = vec![SpanBatch::new(vec![
+            SpanReplacement::new(
+                header_path.clone(),
+                0,
+                header.len(),
+                "int area(int side);\nint perimeter(int side);\n".to_string(),
+            ),
+            SpanReplacement::new(
+                source_path.clone(),
+                call_start,
+                call_start + "area(2)".len(),
+                "area(2) + perimeter(2)".to_string(),
+            ),
+        ])];
+
+        // Checked in isolation, main.c cannot find shapes.h
+        let isolated = apply_batch_with_options(
+            &batches,
+            workspace_path,
+            Language::C,
+            AnalyzerMode::Off,
+            &ValidationOptions::default(),
+        );
+        assert!(
+            matches!(isolated, Err(SpliceError::CompilerValidationFailed { .. })),
+            "isolated validation should fail: {:?}",
+            isolated
+        );
+        assert_eq!(std::fs::read_to_string(&header_path).unwrap(), header);
+        assert_eq!(std::fs::read_to_string(&source_path).unwrap(), source);
+
+        let include_aware = ValidationOptions {
+            include_aware: true,
+            ..ValidationOptions::default()
+        };
+        apply_batch_with_options(
+            &batches,
+            workspace_path,
+            Language::C,
+            AnalyzerMode::Off,
+            &include_aware,
+        )
+        .expect("include-aware validation should pass");
+        assert!(std::fs::read_to_string(&source_path)
+            .unwrap()
+            .contains("area(2) + perimeter(2)"));
+    }
 }
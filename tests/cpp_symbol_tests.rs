@@ -289,4 +289,20 @@ mod tests {
         assert_eq!(symbols[1].name, "MyClass");
         assert_eq!(symbols[1].fully_qualified, "ns::MyClass");
     }
+
+    #[test]
+    fn test_extract_parameter_types_for_overloads() {
+        let source = b"void log(int level) {}\nvoid log(const std::string &msg, int count = 1) {}\nvoid log(char *fmt, ...) {}\n";
+
+        let symbols =
+            extract_cpp_symbols(Path::new("/tmp/test.cpp"), source).expect("Failed to parse");
+
+        assert_eq!(symbols.len(), 3);
+        assert_eq!(symbols[0].parameter_types, vec!["int"]);
+        assert_eq!(
+            symbols[1].parameter_types,
+            vec!["const std::string &", "int"]
+        );
+        assert_eq!(symbols[2].parameter_types, vec!["char *", "..."]);
+    }
 }
@@ -63,6 +63,7 @@ mod tests {
             imported_names: vec!["foo".to_string()],
             is_glob: false,
             is_reexport: false,
+            aliases: Vec::new(),
             byte_span: (0, 20),
         };
         resolver.add_import(import);
@@ -98,6 +99,7 @@ mod tests {
             imported_names: vec!["foo".to_string(), "bar".to_string()],
             is_glob: false,
             is_reexport: false,
+            aliases: Vec::new(),
             byte_span: (0, 30),
         };
         resolver.add_import(import);
@@ -132,6 +134,7 @@ mod tests {
             imported_names: vec!["*".to_string()],
             is_glob: true,
             is_reexport: false,
+            aliases: Vec::new(),
             byte_span: (0, 25),
         };
         resolver.add_import(import);
@@ -165,6 +168,7 @@ mod tests {
             imported_names: vec!["foo".to_string()],
             is_glob: false,
             is_reexport: false,
+            aliases: Vec::new(),
             byte_span: (0, 20),
         };
         resolver.add_import(import);
@@ -215,6 +219,7 @@ mod tests {
             imported_names: vec!["bar".to_string()],
             is_glob: false,
             is_reexport: false,
+            aliases: Vec::new(),
             byte_span: (0, 15),
         };
         resolver.add_import(import);
@@ -249,6 +254,7 @@ mod tests {
             imported_names: vec!["parent_func".to_string()],
             is_glob: false,
             is_reexport: false,
+            aliases: Vec::new(),
             byte_span: (0, 25),
         };
         resolver.add_import(import);
@@ -283,6 +289,7 @@ mod tests {
             imported_names: vec!["foo".to_string()],
             is_glob: false,
             is_reexport: false,
+            aliases: Vec::new(),
             byte_span: (0, 20),
         };
         resolver.add_import(import_a);
@@ -294,6 +301,7 @@ mod tests {
             imported_names: vec!["foo".to_string()],
             is_glob: false,
             is_reexport: false,
+            aliases: Vec::new(),
             byte_span: (20, 40),
         };
         resolver.add_import(import_b);
@@ -323,6 +331,7 @@ mod tests {
             imported_names: vec!["util".to_string()], // Local alias
             is_glob: false,
             is_reexport: false,
+            aliases: Vec::new(),
             byte_span: (0, 35),
         };
         resolver.add_import(import);
@@ -358,6 +367,7 @@ mod tests {
             imported_names: vec!["deep_func".to_string()],
             is_glob: false,
             is_reexport: false,
+            aliases: Vec::new(),
             byte_span: (0, 35),
         };
         resolver.add_import(import);
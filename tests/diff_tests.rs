@@ -0,0 +1,85 @@
+//! Symbol diff tests.
+//!
+//! Compares symbol sets extracted from two directory snapshots.
+
+use splice::ingest::diff::{collect_dir_symbols, diff_symbols};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_file(root: &Path, relative: &str, contents: &str) {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_diff_reports_added_and_removed_functions() {
+        let before = TempDir::new().unwrap();
+        let after = TempDir::new().unwrap();
+
+        write_file(
+            before.path(),
+            "src/lib.rs",
+            "pub fn kept() {}\npub fn old_helper() {}\n",
+        );
+        write_file(before.path(), "util.py", "def shared():\n    pass\n");
+        write_file(
+            after.path(),
+            "src/lib.rs",
+            "pub fn kept() {}\npub fn new_helper() {}\n",
+        );
+        write_file(after.path(), "util.py", "def shared():\n    pass\n");
+
+        let diff = diff_symbols(
+            &collect_dir_symbols(before.path()).unwrap(),
+            &collect_dir_symbols(after.path()).unwrap(),
+        );
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].name, "new_helper");
+        assert_eq!(diff.added[0].kind, "function");
+        assert_eq!(diff.added[0].fully_qualified, "crate::new_helper");
+        assert_eq!(diff.added[0].file, PathBuf::from("src/lib.rs"));
+
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].name, "old_helper");
+
+        assert!(diff.moved.is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_moved_symbol() {
+        let before = TempDir::new().unwrap();
+        let after = TempDir::new().unwrap();
+
+        write_file(before.path(), "src/a.rs", "pub fn wander() {}\n");
+        write_file(before.path(), "src/b.rs", "");
+        write_file(after.path(), "src/a.rs", "");
+        write_file(after.path(), "src/b.rs", "pub fn wander() {}\n");
+
+        let diff = diff_symbols(
+            &collect_dir_symbols(before.path()).unwrap(),
+            &collect_dir_symbols(after.path()).unwrap(),
+        );
+
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.moved.len(), 1);
+        assert_eq!(diff.moved[0].symbol.name, "wander");
+        assert_eq!(diff.moved[0].from_file, PathBuf::from("src/a.rs"));
+        assert_eq!(diff.moved[0].symbol.file, PathBuf::from("src/b.rs"));
+    }
+
+    #[test]
+    fn test_identical_snapshots_produce_empty_diff() {
+        let before = TempDir::new().unwrap();
+        write_file(before.path(), "src/lib.rs", "pub struct Same;\n");
+
+        let symbols = collect_dir_symbols(before.path()).unwrap();
+        assert!(diff_symbols(&symbols, &symbols).is_empty());
+    }
+}
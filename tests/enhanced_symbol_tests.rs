@@ -124,4 +124,47 @@ mod tests {
         assert_eq!(symbols[2].name, "baz");
         assert_eq!(symbols[2].kind, RustSymbolKind::Module);
     }
+
+    #[test]
+    fn test_struct_fields_extracted_after_struct() {
+        let source = b"pub struct Point { pub x: i32, y: i32 }\nstruct Unit;\n";
+
+        let symbols = extract_rust_symbols(std::path::Path::new("/tmp/test.rs"), source)
+            .expect("Failed to parse");
+
+        assert_eq!(symbols.len(), 4);
+        assert_eq!(symbols[1].name, "x");
+        assert_eq!(symbols[1].kind, RustSymbolKind::Field);
+        assert_eq!(symbols[1].fully_qualified, "crate::Point::x");
+        assert_eq!(symbols[1].module_path, "crate");
+        assert_eq!(symbols[1].visibility, Visibility::Public);
+        assert_eq!(symbols[2].name, "y");
+        assert_eq!(symbols[2].visibility, Visibility::Private);
+        assert_eq!(symbols[3].name, "Unit");
+    }
+
+    #[test]
+    fn test_associated_items_qualified_by_owner() {
+        let source = b"mod shapes {\n    pub trait Shape { type Item; const SIDES: usize; }\n    impl Shape for Square { type Item = u8; const SIDES: usize = 4; }\n    impl Square { pub const ORIGIN: u8 = 0; }\n    pub struct Square;\n}\n";
+
+        let symbols = extract_rust_symbols(std::path::Path::new("/tmp/test.rs"), source)
+            .expect("Failed to parse");
+
+        let associated: Vec<(&str, RustSymbolKind)> = symbols
+            .iter()
+            .filter(|s| matches!(s.kind, RustSymbolKind::TypeAlias | RustSymbolKind::Const))
+            .map(|s| (s.fully_qualified.as_str(), s.kind))
+            .collect();
+        assert_eq!(
+            associated,
+            vec![
+                ("crate::shapes::Shape::Item", RustSymbolKind::TypeAlias),
+                ("crate::shapes::Shape::SIDES", RustSymbolKind::Const),
+                // Trait impl items are qualified by the trait they implement
+                ("crate::shapes::Shape::Item", RustSymbolKind::TypeAlias),
+                ("crate::shapes::Shape::SIDES", RustSymbolKind::Const),
+                ("crate::shapes::Square::ORIGIN", RustSymbolKind::Const),
+            ]
+        );
+    }
 }
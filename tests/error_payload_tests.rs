@@ -0,0 +1,60 @@
+//! Structured error payload tests.
+//!
+//! Checks how errors are rendered into the JSON emitted on stderr.
+
+use splice::cli::CliErrorPayload;
+use splice::SpliceError;
+use std::path::Path;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_payload_lists_each_failure() {
+        let error = SpliceError::aggregate(vec![
+            SpliceError::symbol_not_found("missing", Some(Path::new("src/lib.rs"))),
+            SpliceError::AmbiguousSymbol {
+                name: "helper".to_string(),
+                files: vec!["src/a.rs".to_string(), "src/b.rs".to_string()],
+                signatures: Vec::new(),
+                kinds: Vec::new(),
+            },
+        ]);
+        assert_eq!(error.kind(), "Aggregate");
+        // The failures disagree on an exit code, so the generic one is used
+        assert_eq!(error.exit_code(), 1);
+
+        let payload = serde_json::to_value(CliErrorPayload::from_error(&error)).unwrap();
+        let errors = payload["error"]["errors"].as_array().unwrap();
+        assert_eq!(errors.len(), 2);
+
+        assert_eq!(errors[0]["kind"], "SymbolNotFound");
+        assert_eq!(errors[0]["symbol"], "missing");
+        assert_eq!(errors[0]["file"], "src/lib.rs");
+        assert!(errors[0]["hint"].is_string());
+
+        assert_eq!(errors[1]["kind"], "AmbiguousSymbol");
+        assert_eq!(errors[1]["symbol"], "helper");
+        assert!(errors[1]["message"].as_str().unwrap().contains("src/b.rs"));
+    }
+
+    #[test]
+    fn test_aggregate_flattens_nested_and_single_failures() {
+        let single = SpliceError::aggregate(vec![SpliceError::Other("only".to_string())]);
+        assert_eq!(single.kind(), "Other");
+
+        let nested = SpliceError::aggregate(vec![
+            SpliceError::aggregate(vec![
+                SpliceError::symbol_not_found("a", None),
+                SpliceError::symbol_not_found("b", None),
+            ]),
+            SpliceError::symbol_not_found("c", None),
+        ]);
+        match &nested {
+            SpliceError::Aggregate(errors) => assert_eq!(errors.len(), 3),
+            other => panic!("expected an aggregate, got {:?}", other),
+        }
+        assert_eq!(nested.exit_code(), 3);
+    }
+}
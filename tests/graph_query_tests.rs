@@ -0,0 +1,74 @@
+//! Selector queries against a graph populated from an ingested crate.
+
+use splice::graph::query::{query_symbols, Selector};
+use splice::graph::{CodeGraph, SymbolRecord};
+use splice::ingest::extract_symbols_with_language;
+use splice::symbol::Language;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Store every symbol of `file` in `graph`, as `serve` does on first use.
+    fn ingest(graph: &mut CodeGraph, file: &Path) {
+        let source = std::fs::read(file).unwrap();
+        for symbol in extract_symbols_with_language(file, &source, Language::Rust).unwrap() {
+            graph
+                .store_symbol_record(file, &SymbolRecord::of(&symbol))
+                .unwrap();
+        }
+    }
+
+    fn names(graph: &CodeGraph, selector: &str) -> Vec<String> {
+        let selector = Selector::parse(selector).unwrap();
+        query_symbols(graph, &selector)
+            .unwrap()
+            .into_iter()
+            .map(|symbol| symbol.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_query_by_name_glob_kind_and_file() {
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir(&src).unwrap();
+        let lib = src.join("lib.rs");
+        std::fs::write(
+            &lib,
+            "pub mod parse;\n\npub fn foo() {}\npub fn foo_bar() {}\npub struct FooConfig;\nfn other() {}\n",
+        )
+        .unwrap();
+        let parse = src.join("parse.rs");
+        std::fs::write(&parse, "pub fn foo_parse() {}\npub enum Token { A }\n").unwrap();
+
+        let db = dir.path().join("graph.db");
+        let mut graph = CodeGraph::open(&db).unwrap();
+        ingest(&mut graph, &lib);
+        ingest(&mut graph, &parse);
+        drop(graph);
+
+        // Reopen: queries see what earlier runs stored, not just the in-memory cache
+        let graph = CodeGraph::open(&db).unwrap();
+        // Globs are case-sensitive, so `FooConfig` is not a `foo*`
+        assert_eq!(
+            names(&graph, "name:foo*"),
+            vec!["foo", "foo_bar", "foo_parse"]
+        );
+        assert_eq!(
+            names(&graph, "kind:function name:foo_*"),
+            vec!["foo_bar", "foo_parse"]
+        );
+        assert_eq!(
+            names(&graph, "kind:function file:src/lib.rs name:foo*"),
+            vec!["foo", "foo_bar"]
+        );
+        assert_eq!(names(&graph, "kind:enum"), vec!["Token"]);
+        assert_eq!(names(&graph, "name:F?o*"), vec!["FooConfig"]);
+        assert!(names(&graph, "lang:python").is_empty());
+        // lib.rs: mod, 3 functions, struct; parse.rs: function, enum
+        assert_eq!(names(&graph, "").len(), 7);
+    }
+}
@@ -1,8 +1,11 @@
 //! Ingest pipeline tests.
 
+use splice::graph::CodeGraph;
 use splice::ingest::rust::{extract_rust_symbols, RustSymbolKind};
+use splice::ingest::Ingestor;
+use splice::resolve::resolve_symbol;
 use std::io::Write;
-use tempfile::NamedTempFile;
+use tempfile::{NamedTempFile, TempDir};
 
 #[cfg(test)]
 mod tests {
@@ -256,4 +259,101 @@ impl Default for MyStruct {
             "Second impl should have name 'MyStruct'"
         );
     }
+    #[test]
+    fn test_rust_symbol_kind_round_trips_through_str() {
+        let kinds = [
+            RustSymbolKind::Function,
+            RustSymbolKind::Struct,
+            RustSymbolKind::Enum,
+            RustSymbolKind::Impl,
+            RustSymbolKind::Module,
+            RustSymbolKind::Trait,
+            RustSymbolKind::TypeAlias,
+            RustSymbolKind::Const,
+            RustSymbolKind::Static,
+            RustSymbolKind::Field,
+            RustSymbolKind::Reexport,
+            RustSymbolKind::Macro,
+        ];
+        for kind in kinds {
+            assert_eq!(RustSymbolKind::try_from(kind.as_str()).unwrap(), kind);
+        }
+        assert!(RustSymbolKind::try_from("method").is_err());
+        assert!(RustSymbolKind::try_from("Function").is_err());
+    }
+
+    #[test]
+    fn test_shebang_with_arguments_keeps_spans() {
+        let header = "#!/usr/bin/env rust-script\n\n";
+        let source = format!("{}fn first() -> i32 {{\n    1\n}}\n", header);
+        let symbols = extract_rust_symbols(std::path::Path::new("script.rs"), source.as_bytes())
+            .expect("Failed to parse");
+
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "first");
+        assert_eq!(symbols[0].line_start, 3);
+        assert_eq!(symbols[0].byte_start, header.len());
+
+        // Inner attributes look like a shebang but must still be parsed
+        let attr = "#![allow(dead_code)]\nfn second() {}\n";
+        let symbols =
+            extract_rust_symbols(std::path::Path::new("lib.rs"), attr.as_bytes()).unwrap();
+        assert_eq!(symbols[0].name, "second");
+        assert_eq!(symbols[0].line_start, 2);
+    }
+
+    #[test]
+    fn test_ingest_dir_parses_500_files_in_parallel() {
+        let workspace = TempDir::new().expect("Failed to create temp workspace");
+        for i in 0..500 {
+            let dir = workspace.path().join(format!("module_{}", i % 10));
+            std::fs::create_dir_all(&dir).expect("Failed to create module dir");
+            let (name, source) = if i % 2 == 0 {
+                (
+                    format!("file_{}.rs", i),
+                    format!("pub fn func_{i}() -> i32 {{ {i} }}\n\npub struct Type{i};\n"),
+                )
+            } else {
+                (
+                    format!("file_{}.py", i),
+                    format!("def func_{i}():\n    return {i}\n\nclass Type{i}:\n    pass\n"),
+                )
+            };
+            std::fs::write(dir.join(name), source).expect("Failed to write source file");
+        }
+        // Neither skipped directories nor unknown extensions are ingested
+        std::fs::create_dir(workspace.path().join("target")).unwrap();
+        std::fs::write(workspace.path().join("target/build.rs"), "fn built() {}\n").unwrap();
+        std::fs::write(workspace.path().join("README.md"), "# readme\n").unwrap();
+
+        let graph_db = NamedTempFile::new().expect("Failed to create temp db");
+        let graph = CodeGraph::open(graph_db.path()).expect("Failed to open graph");
+        let mut ingestor = Ingestor::new(graph).with_jobs(4);
+        let summary = ingestor
+            .ingest_dir(workspace.path())
+            .expect("Failed to ingest workspace");
+
+        assert_eq!(summary.files, 500);
+        assert_eq!(summary.symbols, 1000);
+        assert!(summary.skipped_files.is_empty(), "{:?}", summary.skipped_files);
+
+        let graph = ingestor.into_graph();
+        for i in 0..500 {
+            let ext = if i % 2 == 0 { "rs" } else { "py" };
+            let file = workspace
+                .path()
+                .join(format!("module_{}", i % 10))
+                .join(format!("file_{}.{}", i, ext));
+            let func = resolve_symbol(&graph, Some(&file), None, &format!("func_{}", i))
+                .expect("Function should be queryable");
+            assert_eq!(func.file_path, file.to_string_lossy());
+            resolve_symbol(&graph, Some(&file), None, &format!("Type{}", i))
+                .expect("Type should be queryable");
+        }
+        assert!(resolve_symbol(&graph, None, None, "built").is_err());
+
+        // The batched inserts are committed: one node per file and per symbol
+        let reopened = CodeGraph::open(graph_db.path()).expect("Failed to reopen graph");
+        assert_eq!(reopened.node_ids().unwrap().len(), 1500);
+    }
 }
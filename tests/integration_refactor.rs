@@ -88,7 +88,7 @@ fn foo() {
         // Should fail with AmbiguousSymbol error
         assert!(result.is_err(), "Expected error for ambiguous symbol");
         match result {
-            Err(splice::SpliceError::AmbiguousSymbol { name, files }) => {
+            Err(splice::SpliceError::AmbiguousSymbol { name, files, .. }) => {
                 assert_eq!(name, "foo");
                 assert_eq!(files.len(), 2);
             }
@@ -222,4 +222,46 @@ fn world() {
             // assert_eq!(resolved.col_end, symbol.col_end);
         }
     }
+
+    /// Test that the ambiguity error lists files in sorted order on every run.
+    #[test]
+    fn test_ambiguous_symbol_files_are_sorted() {
+        let insertion_order = ["src/c.rs", "src/a.rs", "src/d.rs", "src/b.rs"];
+        let expected = vec![
+            "src/a.rs".to_string(),
+            "src/b.rs".to_string(),
+            "src/c.rs".to_string(),
+            "src/d.rs".to_string(),
+        ];
+
+        for _ in 0..5 {
+            let graph_db = NamedTempFile::new().expect("Failed to create temp db");
+            let mut code_graph =
+                CodeGraph::open(graph_db.path()).expect("Failed to open graph database");
+
+            for file in insertion_order {
+                code_graph
+                    .store_symbol_with_file_and_language(
+                        std::path::Path::new(file),
+                        "foo",
+                        "function",
+                        Language::Rust,
+                        0,
+                        12,
+                    )
+                    .expect("Failed to store symbol");
+            }
+
+            let matches = code_graph.find_symbols_by_name("foo");
+            let match_files: Vec<String> = matches.into_iter().filter_map(|(_, f)| f).collect();
+            assert_eq!(match_files, expected);
+
+            match resolve_symbol(&code_graph, None, Some("function"), "foo") {
+                Err(splice::SpliceError::AmbiguousSymbol { files, .. }) => {
+                    assert_eq!(files, expected);
+                }
+                other => panic!("Expected AmbiguousSymbol error, got: {:?}", other),
+            }
+        }
+    }
 }
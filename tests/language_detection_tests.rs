@@ -2,7 +2,7 @@
 //!
 //! TDD for table-driven language detection from file extensions.
 
-use splice::ingest::detect::{detect_language, Language};
+use splice::ingest::detect::{detect_language, is_declaration_file, Language};
 use std::path::Path;
 
 #[cfg(test)]
@@ -72,6 +72,21 @@ mod tests {
         assert_eq!(detect_language(path2), Some(Language::TypeScript));
     }
 
+    #[test]
+    fn test_detect_haskell_file() {
+        let path = Path::new("Main.hs");
+        assert_eq!(detect_language(path), Some(Language::Haskell));
+    }
+
+    #[test]
+    fn test_detect_elixir_file() {
+        let path = Path::new("lib/my_app/greeter.ex");
+        assert_eq!(detect_language(path), Some(Language::Elixir));
+
+        let path2 = Path::new("test/greeter_test.exs");
+        assert_eq!(detect_language(path2), Some(Language::Elixir));
+    }
+
     #[test]
     fn test_unknown_extension_returns_none() {
         let path = Path::new("file.unknown");
@@ -130,5 +145,38 @@ mod tests {
         assert_eq!(Language::Java.as_str(), "java");
         assert_eq!(Language::JavaScript.as_str(), "javascript");
         assert_eq!(Language::TypeScript.as_str(), "typescript");
+        assert_eq!(Language::Haskell.as_str(), "haskell");
+        assert_eq!(Language::Elixir.as_str(), "elixir");
+    }
+
+    #[test]
+    fn test_detect_compound_extensions() {
+        let cases = [
+            ("types/component.d.ts", Language::TypeScript),
+            ("src/App.tsx", Language::TypeScript),
+            ("src/app.test.ts", Language::TypeScript),
+            ("src/app.test.tsx", Language::TypeScript),
+            ("src/app.spec.ts", Language::TypeScript),
+            ("dist/worker.min.js", Language::JavaScript),
+            ("config.esm.mjs", Language::JavaScript),
+            ("config.cjs", Language::JavaScript),
+        ];
+        for (path, language) in cases {
+            assert_eq!(detect_language(Path::new(path)), Some(language), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_is_declaration_file() {
+        assert!(is_declaration_file(Path::new("component.d.ts")));
+        assert!(is_declaration_file(Path::new(
+            "node_modules/@types/node/index.d.ts"
+        )));
+
+        assert!(!is_declaration_file(Path::new("component.ts")));
+        assert!(!is_declaration_file(Path::new("app.test.tsx")));
+        assert!(!is_declaration_file(Path::new("worker.d.js")));
+        // A file named just ".d.ts" has no stem to declare anything for
+        assert!(!is_declaration_file(Path::new(".d.ts")));
     }
 }
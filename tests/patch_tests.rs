@@ -5,11 +5,22 @@
 
 use splice::graph::CodeGraph;
 use splice::ingest::rust::extract_rust_symbols;
+use splice::ingest::source::SourceFile;
+use splice::patch::analyzer_scopes;
 use splice::patch::apply_patch_with_validation;
-use splice::patch::{apply_batch_with_validation, SpanBatch, SpanReplacement};
+use splice::patch::blank_line_replacements;
+use splice::patch::{
+    apply_batch_with_options, apply_patch_with_options, GateSet, ValidationOptions,
+};
+use splice::patch::{
+    ensure_no_duplicate_symbol, insertion_edit, preview_patch_with_options, InsertPosition,
+};
+use splice::patch::{
+    apply_batch_with_validation, mod_declaration, NewFile, SpanBatch, SpanReplacement,
+};
 use splice::resolve::resolve_symbol;
 use splice::symbol::Language;
-use splice::validate::AnalyzerMode;
+use splice::validate::{AnalyzerMode, AnalyzerScope, CargoFeatures};
 use std::io::Write;
 use tempfile::{NamedTempFile, TempDir};
 
@@ -513,4 +524,1406 @@ pub fn broken() -> i32 {
             "File b.rs should remain unchanged after batch failure"
         );
     }
+
+    /// Test E: Feature-gated code is only compiled when its feature is selected.
+    ///
+    /// A type error inside `#[cfg(feature = "extra")]` slips past a default
+    /// `cargo check`, but is caught (and rolled back) with `--all-features`.
+    #[test]
+    fn test_cargo_check_gate_respects_feature_selection() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            r#"[package]
+name = "temp-test"
+version = "0.1.0"
+edition = "2021"
+
+[features]
+extra = []
+
+[lib]
+name = "temp_test"
+path = "src/lib.rs"
+"#,
+        )
+        .expect("Failed to write Cargo.toml");
+
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+
+        let lib_rs_path = src_dir.join("lib.rs");
+        let source = r#"
+#[cfg(feature = "extra")]
+pub fn extra_number() -> i32 {
+    42
+}
+"#;
+        std::fs::write(&lib_rs_path, source).expect("Failed to write lib.rs");
+
+        let symbols =
+            extract_rust_symbols(&lib_rs_path, source.as_bytes()).expect("Failed to parse lib.rs");
+        let symbol = &symbols[0];
+        assert_eq!(symbol.name, "extra_number");
+
+        let broken = r#"pub fn extra_number() -> i32 {
+    "not an i32"
+}"#;
+
+        let all_features = ValidationOptions {
+            cargo_features: CargoFeatures {
+                all_features: true,
+                ..CargoFeatures::default()
+            },
+            ..ValidationOptions::default()
+        };
+        let result = apply_patch_with_options(
+            &lib_rs_path,
+            symbol.byte_start,
+            symbol.byte_end,
+            broken,
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+            &all_features,
+        );
+        assert!(
+            matches!(result, Err(splice::SpliceError::CargoCheckFailed { .. })),
+            "Expected CargoCheckFailed with --all-features, got {:?}",
+            result
+        );
+        assert_eq!(
+            source,
+            std::fs::read_to_string(&lib_rs_path).unwrap(),
+            "File should be rolled back after the feature-gated break"
+        );
+
+        // Default features never compile the gated function, so the break passes
+        let result = apply_patch_with_validation(
+            &lib_rs_path,
+            symbol.byte_start,
+            symbol.byte_end,
+            broken,
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+        );
+        assert!(
+            result.is_ok(),
+            "Default cargo check should not see feature-gated code: {:?}",
+            result
+        );
+    }
+
+    /// A warning denied by `#![deny(warnings)]` fails the gate and is reported as an error.
+    #[test]
+    fn test_cargo_check_reports_denied_warning_as_error() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        let source = "#![deny(warnings)]\n\npub fn answer() -> i32 {\n    42\n}\n";
+        std::fs::write(&lib_rs_path, source).expect("Failed to write lib.rs");
+
+        let symbols =
+            extract_rust_symbols(&lib_rs_path, source.as_bytes()).expect("Failed to parse lib.rs");
+        let symbol = symbols.iter().find(|s| s.name == "answer").unwrap();
+        let result = apply_patch_with_validation(
+            &lib_rs_path,
+            symbol.byte_start,
+            symbol.byte_end,
+            "pub fn answer() -> i32 {\n    let unused = 1;\n    42\n}",
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+        );
+
+        let Err(splice::SpliceError::CargoCheckFailed { diagnostics, .. }) = result else {
+            panic!("Expected CargoCheckFailed, got {:?}", result);
+        };
+        let unused = diagnostics
+            .iter()
+            .find(|d| d.message.contains("unused variable"))
+            .expect("unused variable should be reported");
+        assert!(matches!(
+            unused.level,
+            splice::error::DiagnosticLevel::Error
+        ));
+        assert_eq!(unused.line, Some(4));
+        assert_eq!(source, std::fs::read_to_string(&lib_rs_path).unwrap());
+    }
+
+    #[test]
+    fn test_patch_refuses_target_over_max_file_size() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        let file_path = workspace_path.join("big.rs");
+        let source = format!("{}fn target() {{}}\n", "// filler line\n".repeat(10));
+        std::fs::write(&file_path, &source).expect("Failed to write big.rs");
+
+        let start = source.find("fn target").unwrap();
+        let options = ValidationOptions {
+            max_file_size: 64,
+            ..ValidationOptions::default()
+        };
+        let result = apply_patch_with_options(
+            &file_path,
+            start,
+            source.len() - 1,
+            "fn target() { }",
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+            &options,
+        );
+
+        match result {
+            Err(splice::SpliceError::FileTooLarge { size, limit, .. }) => {
+                assert_eq!(size, source.len() as u64);
+                assert_eq!(limit, 64);
+            }
+            other => panic!("Expected FileTooLarge, got {:?}", other),
+        }
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), source);
+    }
+
+    #[test]
+    fn test_patch_refuses_span_inside_string_literal() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        let file_path = workspace_path.join("lib.rs");
+        let source = "pub fn greet() -> &'static str {\n    \"fn stale() {}\"\n}\n";
+        std::fs::write(&file_path, source).expect("Failed to write lib.rs");
+
+        // A stale span that now points at text inside the string literal
+        let start = source.find("fn stale").unwrap();
+        let end = start + "fn stale() {}".len();
+        let result = apply_patch_with_validation(
+            &file_path,
+            start,
+            end,
+            "fn fresh() {}",
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+        );
+
+        match result {
+            Err(splice::SpliceError::SpanInsideLiteral {
+                offset, node_kind, ..
+            }) => {
+                assert_eq!(offset, start);
+                assert_eq!(node_kind, "string_literal");
+            }
+            other => panic!("Expected SpanInsideLiteral, got {:?}", other),
+        }
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), source);
+    }
+
+    /// The literal check parses with the caller's limits and reports their errors.
+    #[test]
+    fn test_literal_check_reports_parse_limit() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        let file_path = workspace_path.join("lib.rs");
+        let source = "pub fn answer() -> i32 {\n    42\n}\n";
+        std::fs::write(&file_path, source).expect("Failed to write lib.rs");
+
+        let start = source.find("42").unwrap();
+        let options = ValidationOptions {
+            gates: GateSet {
+                tree_sitter: false,
+                compiler: false,
+                analyzer: false,
+            },
+            parse: splice::ingest::ParseOptions { max_ast_depth: 2 },
+            ..Default::default()
+        };
+        let result = apply_patch_with_options(
+            &file_path,
+            start,
+            start + 2,
+            "43",
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+            &options,
+        );
+
+        assert!(
+            matches!(result, Err(splice::SpliceError::AstTooDeep { .. })),
+            "Expected AstTooDeep, got {:?}",
+            result
+        );
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), source);
+    }
+
+    #[test]
+    fn test_tree_sitter_only_gate_set_skips_cargo_check() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\npath = \"src/lib.rs\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        let source = "pub fn answer() -> i32 {\n    42\n}\n";
+        std::fs::write(&lib_rs_path, source).expect("Failed to write lib.rs");
+
+        // Parses fine, but is a type error that cargo check would reject
+        let ill_typed = "pub fn answer() -> i32 {\n    \"forty-two\"\n}";
+        let end = source.trim_end().len();
+
+        let result = apply_patch_with_validation(
+            &lib_rs_path,
+            0,
+            end,
+            ill_typed,
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+        );
+        assert!(
+            matches!(result, Err(splice::SpliceError::CargoCheckFailed { .. })),
+            "Default gates should run cargo check: {:?}",
+            result
+        );
+        assert_eq!(std::fs::read_to_string(&lib_rs_path).unwrap(), source);
+        std::fs::remove_dir_all(workspace_path.join("target")).expect("Failed to remove target");
+
+        let options = ValidationOptions {
+            gates: GateSet {
+                tree_sitter: true,
+                compiler: false,
+                analyzer: false,
+            },
+            ..ValidationOptions::default()
+        };
+        let result = apply_patch_with_options(
+            &lib_rs_path,
+            0,
+            end,
+            ill_typed,
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+            &options,
+        );
+        assert!(result.is_ok(), "Only tree-sitter should run: {:?}", result);
+        assert!(std::fs::read_to_string(&lib_rs_path)
+            .unwrap()
+            .contains("\"forty-two\""));
+        assert!(
+            !workspace_path.join("target").exists(),
+            "cargo check should not have been invoked"
+        );
+    }
+
+    #[test]
+    fn test_fast_check_catches_type_error_but_skips_other_targets() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir_all(src_dir.join("bin")).expect("Failed to create src/bin");
+        let lib_rs_path = src_dir.join("lib.rs");
+        let source = "pub fn answer() -> i32 {\n    42\n}\n";
+        std::fs::write(&lib_rs_path, source).expect("Failed to write lib.rs");
+        // A binary that fails to compile: only a whole-package check sees it
+        std::fs::write(src_dir.join("bin/broken.rs"), "fn main() {\n    let x: u8 = \"no\";\n}\n")
+            .expect("Failed to write broken binary");
+
+        let fast = ValidationOptions {
+            fast_check: true,
+            ..ValidationOptions::default()
+        };
+        // Each patch replaces the whole function as it currently stands
+        let patch = |content: &str, options: &ValidationOptions| {
+            let current = std::fs::read_to_string(&lib_rs_path).unwrap();
+            apply_patch_with_options(
+                &lib_rs_path,
+                0,
+                current.trim_end().len(),
+                content,
+                workspace_path,
+                Language::Rust,
+                AnalyzerMode::Off,
+                options,
+            )
+        };
+
+        // The in-file type error is still caught by the narrowed check
+        let result = patch("pub fn answer() -> i32 {\n    \"forty-two\"\n}", &fast);
+        assert!(
+            matches!(result, Err(splice::SpliceError::CargoCheckFailed { .. })),
+            "fast check should reject the type error: {:?}",
+            result
+        );
+        assert_eq!(std::fs::read_to_string(&lib_rs_path).unwrap(), source);
+
+        // A valid lib patch passes because the broken binary is not checked
+        let valid = "pub fn answer() -> i32 {\n    41 + 1\n}";
+        let result = patch(valid, &fast);
+        assert!(result.is_ok(), "fast check should only check the lib: {:?}", result);
+
+        // The full check covers every target and fails on the binary
+        let result = patch("pub fn answer() -> i32 {\n    40 + 2\n}", &ValidationOptions::default());
+        assert!(
+            matches!(result, Err(splice::SpliceError::CargoCheckFailed { .. })),
+            "full check should include the binary: {:?}",
+            result
+        );
+        assert!(std::fs::read_to_string(&lib_rs_path).unwrap().contains("41 + 1"));
+    }
+
+    #[test]
+    fn test_scoped_analyzer_runs_in_owning_package() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/core\", \"crates/app\"]\n",
+        )
+        .expect("Failed to write workspace Cargo.toml");
+        for name in ["core", "app"] {
+            let src = workspace_path.join("crates").join(name).join("src");
+            std::fs::create_dir_all(&src).expect("Failed to create member src");
+            std::fs::write(
+                src.parent().unwrap().join("Cargo.toml"),
+                format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", name),
+            )
+            .expect("Failed to write member Cargo.toml");
+            std::fs::write(src.join("lib.rs"), "").expect("Failed to write lib.rs");
+        }
+        let core_lib = workspace_path.join("crates/core/src/lib.rs");
+        let app_lib = workspace_path.join("crates/app/src/lib.rs");
+
+        // Workspace mode stays a single run over every member
+        let scopes = analyzer_scopes(&[core_lib.as_path()], false, None).unwrap();
+        assert_eq!(scopes, vec![AnalyzerScope::Workspace]);
+        assert_eq!(
+            scopes[0].invocation(workspace_path),
+            (workspace_path, vec!["check", "--workspace"])
+        );
+
+        // Scoped mode runs once per owning package, from its directory
+        let scopes = analyzer_scopes(&[core_lib.as_path()], true, None).unwrap();
+        let core_dir = workspace_path.join("crates/core");
+        assert_eq!(scopes, vec![AnalyzerScope::Package(core_dir.clone())]);
+        assert_eq!(
+            scopes[0].invocation(workspace_path),
+            (core_dir.as_path(), vec!["check"])
+        );
+
+        let scopes = analyzer_scopes(
+            &[app_lib.as_path(), core_lib.as_path(), app_lib.as_path()],
+            true,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            scopes,
+            vec![
+                AnalyzerScope::Package(workspace_path.join("crates/app")),
+                AnalyzerScope::Package(core_dir),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_patch_under_path_with_spaces_and_unicode() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path().join("my crate \u{e9}t\u{e9}");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir_all(&src_dir).expect("Failed to create src directory");
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let lib_rs_path = src_dir.join("lib.rs");
+        let source = "pub fn value() -> i32 {\n    1\n}\n";
+        std::fs::write(&lib_rs_path, source).expect("Failed to write lib.rs");
+
+        let start = source.find('1').unwrap();
+        apply_patch_with_validation(
+            &lib_rs_path,
+            start,
+            start + 1,
+            "2",
+            &workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+        )
+        .expect("Patch under a path with spaces and unicode should succeed");
+        assert_eq!(
+            std::fs::read_to_string(&lib_rs_path).unwrap(),
+            "pub fn value() -> i32 {\n    2\n}\n"
+        );
+
+        // The atomic write's temp file was renamed into place, not left behind
+        let names: Vec<_> = std::fs::read_dir(&src_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["lib.rs"]);
+    }
+
+    #[test]
+    fn test_manifest_path_runs_cargo_check_in_its_directory() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        // The manifest sits beside the sources, not above them
+        let build_dir = workspace_path.join("build");
+        std::fs::create_dir_all(&build_dir).expect("Failed to create build dir");
+        std::fs::write(
+            build_dir.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+             [lib]\npath = \"../src/lib.rs\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir_all(&src_dir).expect("Failed to create src dir");
+        let lib_rs_path = src_dir.join("lib.rs");
+        let source = "pub fn answer() -> i32 {\n    42\n}\n";
+        std::fs::write(&lib_rs_path, source).expect("Failed to write lib.rs");
+
+        let options = ValidationOptions {
+            manifest_path: Some(build_dir.join("Cargo.toml")),
+            ..ValidationOptions::default()
+        };
+        let patch = |content: &str| {
+            apply_patch_with_options(
+                &lib_rs_path,
+                0,
+                source.trim_end().len(),
+                content,
+                &src_dir,
+                Language::Rust,
+                AnalyzerMode::Off,
+                &options,
+            )
+        };
+
+        let result = patch("pub fn answer() -> i32 {\n    \"forty-two\"\n}");
+        assert!(
+            matches!(result, Err(splice::SpliceError::CargoCheckFailed { .. })),
+            "the overridden manifest should still catch the type error: {:?}",
+            result
+        );
+        assert_eq!(std::fs::read_to_string(&lib_rs_path).unwrap(), source);
+
+        let result = patch("pub fn answer() -> i32 {\n    41 + 1\n}");
+        assert!(result.is_ok(), "valid patch should pass: {:?}", result);
+        assert!(
+            build_dir.join("target").is_dir(),
+            "cargo check should run in the manifest's directory"
+        );
+        assert!(!src_dir.join("target").exists());
+
+        // A manifest that does not exist is reported, not searched around
+        let missing = ValidationOptions {
+            manifest_path: Some(workspace_path.join("missing/Cargo.toml")),
+            ..ValidationOptions::default()
+        };
+        let current = std::fs::read_to_string(&lib_rs_path).unwrap();
+        let result = apply_patch_with_options(
+            &lib_rs_path,
+            0,
+            current.trim_end().len(),
+            "pub fn answer() -> i32 {\n    42\n}",
+            &src_dir,
+            Language::Rust,
+            AnalyzerMode::Off,
+            &missing,
+        );
+        match &result {
+            Err(splice::SpliceError::Other(msg)) => assert!(msg.contains("Manifest not found")),
+            other => panic!("missing manifest should be reported: {:?}", other),
+        }
+        assert_eq!(std::fs::read_to_string(&lib_rs_path).unwrap(), current);
+    }
+
+    #[test]
+    fn test_patch_build_script_is_validated() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        std::fs::create_dir_all(workspace_path.join("src")).expect("Failed to create src dir");
+        std::fs::write(workspace_path.join("src/lib.rs"), "").expect("Failed to write lib.rs");
+        let build_rs_path = workspace_path.join("build.rs");
+        let source = "fn stamp() -> u32 {\n    1\n}\n\nfn main() {\n    let _ = stamp();\n}\n";
+        std::fs::write(&build_rs_path, source).expect("Failed to write build.rs");
+
+        // Build scripts are extracted like any other Rust file
+        let symbols = extract_rust_symbols(&build_rs_path, source.as_bytes()).unwrap();
+        let stamp = symbols
+            .iter()
+            .find(|s| s.name == "stamp")
+            .expect("build.rs function should be extracted");
+        let (start, end) = (stamp.byte_start, stamp.byte_end);
+
+        // `cargo check` compiles the build script, so its type errors are caught,
+        // including under the narrowed check (build scripts check the whole package)
+        for options in [
+            ValidationOptions::default(),
+            ValidationOptions {
+                fast_check: true,
+                ..ValidationOptions::default()
+            },
+        ] {
+            let result = apply_patch_with_options(
+                &build_rs_path,
+                start,
+                end,
+                "fn stamp() -> u32 {\n    \"one\"\n}",
+                workspace_path,
+                Language::Rust,
+                AnalyzerMode::Off,
+                &options,
+            );
+            assert!(
+                matches!(result, Err(splice::SpliceError::CargoCheckFailed { .. })),
+                "build script type error should be rejected: {:?}",
+                result
+            );
+            assert_eq!(std::fs::read_to_string(&build_rs_path).unwrap(), source);
+        }
+
+        let result = apply_patch_with_validation(
+            &build_rs_path,
+            start,
+            end,
+            "fn stamp() -> u32 {\n    2\n}",
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+        );
+        assert!(
+            result.is_ok(),
+            "valid build script patch should pass: {:?}",
+            result
+        );
+        assert!(std::fs::read_to_string(&build_rs_path)
+            .unwrap()
+            .contains("    2\n"));
+    }
+
+    #[test]
+    fn test_concurrent_patches_use_distinct_temp_files() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path().to_path_buf();
+        let file_path = workspace_path.join("counter.py");
+        std::fs::write(&file_path, "value = 0\n").expect("Failed to write counter.py");
+
+        let options = ValidationOptions {
+            gates: GateSet {
+                tree_sitter: true,
+                compiler: false,
+                analyzer: false,
+            },
+            ..ValidationOptions::default()
+        };
+
+        let handles: Vec<_> = ["1", "2"]
+            .into_iter()
+            .map(|digit| {
+                let file_path = file_path.clone();
+                let workspace_path = workspace_path.clone();
+                let options = options.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        // Same-length replacement keeps the span valid for both writers
+                        apply_patch_with_options(
+                            &file_path,
+                            8,
+                            9,
+                            digit,
+                            &workspace_path,
+                            Language::Python,
+                            AnalyzerMode::Off,
+                            &options,
+                        )?;
+                    }
+                    Ok::<(), splice::SpliceError>(())
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            let result = handle.join().expect("Writer thread panicked");
+            assert!(result.is_ok(), "Concurrent patch failed: {:?}", result);
+        }
+
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        assert!(content == "value = 1\n" || content == "value = 2\n");
+        let leftovers: Vec<_> = std::fs::read_dir(&workspace_path)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty(), "Temp files left behind: {:?}", leftovers);
+    }
+
+    #[test]
+    fn test_insert_before_goes_above_attributes_and_keeps_indentation() {
+        let source = "impl Foo {\n    /// Docs.\n    #[inline]\n    fn bar(&self) {}\n}\n";
+        let start = source.find("fn bar").unwrap();
+        let end = source.find("{}\n}").unwrap() + 2;
+
+        let (offset, text) = insertion_edit(
+            source,
+            start,
+            end,
+            "\n    fn baz(&self) {}\n",
+            InsertPosition::Before,
+            Language::Rust,
+        );
+        let mut patched = source.to_string();
+        patched.insert_str(offset, &text);
+        assert_eq!(
+            patched,
+            "impl Foo {\n    fn baz(&self) {}\n\n    /// Docs.\n    #[inline]\n    fn bar(&self) {}\n}\n"
+        );
+
+        let (offset, text) = insertion_edit(
+            source,
+            start,
+            end,
+            "    fn baz(&self) {}",
+            InsertPosition::After,
+            Language::Rust,
+        );
+        assert_eq!(offset, end);
+        assert_eq!(text, "\n\n    fn baz(&self) {}");
+    }
+
+    #[test]
+    fn test_insert_before_goes_above_multi_line_attribute() {
+        let source = "/// Docs.\n#[cfg_attr(\n  feature = \"x\",\n  derive(Debug)\n)]\npub struct Foo;\n";
+        let start = source.find("pub struct").unwrap();
+        let end = source.len() - 1;
+
+        let (offset, text) = insertion_edit(
+            source,
+            start,
+            end,
+            "pub struct Bar;",
+            InsertPosition::Before,
+            Language::Rust,
+        );
+        assert_eq!(offset, 0);
+        let mut patched = source.to_string();
+        patched.insert_str(offset, &text);
+        assert_eq!(
+            patched,
+            "pub struct Bar;\n\n/// Docs.\n#[cfg_attr(\n  feature = \"x\",\n  derive(Debug)\n)]\npub struct Foo;\n"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_symbol_detected_only_in_same_scope() {
+        let path = std::path::Path::new("lib.rs");
+        let source = "pub fn helper() {}\n\nstruct A;\n\nimpl A {\n    fn new() -> Self {\n        A\n    }\n}\n";
+        let end = source.len();
+
+        let err = ensure_no_duplicate_symbol(
+            path,
+            source.as_bytes(),
+            end,
+            end,
+            "\nfn helper() {}\n",
+            Language::Rust,
+        )
+        .expect_err("a second top-level helper should be refused");
+        match err {
+            splice::SpliceError::DuplicateSymbol { name, kind, line, .. } => {
+                assert_eq!((name.as_str(), kind.as_str(), line), ("helper", "function", 1));
+            }
+            other => panic!("expected DuplicateSymbol, got {:?}", other),
+        }
+
+        // `new` in another impl, or a helper nested in a function, lives in a different scope
+        let extra = "\nstruct B;\n\nimpl B {\n    fn new() -> Self {\n        B\n    }\n}\n\nfn outer() {\n    fn helper() {}\n}\n";
+        ensure_no_duplicate_symbol(path, source.as_bytes(), end, end, extra, Language::Rust)
+            .expect("symbols in other scopes are not duplicates");
+
+        // Replacing the existing definition is not a duplicate of itself
+        let helper_end = source.find('\n').unwrap();
+        ensure_no_duplicate_symbol(
+            path,
+            source.as_bytes(),
+            0,
+            helper_end,
+            "pub fn helper() { }",
+            Language::Rust,
+        )
+        .expect("patching a symbol in place is allowed");
+    }
+
+    #[test]
+    fn test_previews_share_persistent_cargo_target_dir() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\npath = \"src/lib.rs\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        let source = "pub fn answer() -> i32 {\n    42\n}\n";
+        std::fs::write(&lib_rs_path, source).expect("Failed to write lib.rs");
+        let end = source.trim_end().len();
+
+        let cache_dir = TempDir::new().expect("Failed to create cache dir");
+        let options = ValidationOptions {
+            cargo_target_dir: Some(cache_dir.path().to_path_buf()),
+            ..ValidationOptions::default()
+        };
+        let preview = |body: &str| {
+            preview_patch_with_options(
+                &lib_rs_path,
+                0,
+                end,
+                body,
+                workspace_path,
+                Language::Rust,
+                AnalyzerMode::Off,
+                &options,
+            )
+        };
+
+        preview("pub fn answer() -> i32 {\n    41 + 1\n}").expect("First preview should pass");
+        assert!(
+            cache_dir.path().join("debug").is_dir(),
+            "cargo check should build into the shared cache"
+        );
+        preview("pub fn answer() -> i32 {\n    40 + 2\n}").expect("Second preview should pass");
+
+        assert!(cache_dir.path().join("debug").is_dir());
+        assert!(!workspace_path.join("target").exists(), "real target must stay untouched");
+        assert_eq!(std::fs::read_to_string(&lib_rs_path).unwrap(), source);
+
+        // The workspace's own target directory is refused as a preview cache
+        let options = ValidationOptions {
+            cargo_target_dir: Some(workspace_path.join("target")),
+            ..ValidationOptions::default()
+        };
+        let result = preview_patch_with_options(
+            &lib_rs_path,
+            0,
+            end,
+            source.trim_end(),
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+            &options,
+        );
+        assert!(result.is_err());
+
+        // A relative --preview-target-dir is taken from the caller's cwd, not the workspace
+        let cwd = TempDir::new().expect("Failed to create cwd");
+        let replacement = cwd.path().join("answer.rs");
+        std::fs::write(&replacement, "pub fn answer() -> i32 {\n    43 - 1\n}\n")
+            .expect("Failed to write replacement");
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_splice"))
+            .current_dir(cwd.path())
+            .arg("patch")
+            .arg("--file")
+            .arg(&lib_rs_path)
+            .args(["--symbol", "answer", "--with"])
+            .arg(&replacement)
+            .args(["--preview", "--preview-target-dir", "relative-cache"])
+            .output()
+            .expect("Failed to run splice CLI");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(cwd.path().join("relative-cache").join("debug").is_dir());
+        assert!(!workspace_path.join("relative-cache").exists());
+        assert_eq!(std::fs::read_to_string(&lib_rs_path).unwrap(), source);
+    }
+
+    /// A batch can create a module file together with the `mod` declaration
+    /// that uses it, and rolling back removes the new file and its directory.
+    #[test]
+    fn test_batch_creates_new_module_file_and_rolls_back_with_edits() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\npath = \"src/lib.rs\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        let source = "pub fn helper() -> i32 {\n    1\n}\n";
+        std::fs::write(&lib_rs_path, source).expect("Failed to write lib.rs");
+
+        let module_batch = |name: &str, body: &str| {
+            let mut batch = SpanBatch::new(vec![SpanReplacement::new(
+                lib_rs_path.clone(),
+                0,
+                0,
+                format!("pub mod {};\n", name),
+            )]);
+            batch.push_new_file(NewFile::new(
+                src_dir.join(name).join("mod.rs"),
+                format!("pub fn value() -> i32 {{\n    {}\n}}\n", body),
+            ));
+            vec![batch]
+        };
+
+        // A type error in the new file fails the batch and undoes everything
+        let result = apply_batch_with_validation(
+            &module_batch("broken", "\"oops\""),
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+        );
+        assert!(
+            matches!(result, Err(splice::SpliceError::CargoCheckFailed { .. })),
+            "Expected CargoCheckFailed, got {:?}",
+            result
+        );
+        assert_eq!(std::fs::read_to_string(&lib_rs_path).unwrap(), source);
+        assert!(!src_dir.join("broken").exists(), "new module dir should be removed");
+
+        // A valid module is written alongside the declaration
+        let summaries = apply_batch_with_validation(
+            &module_batch("shapes", "crate::helper() + 1"),
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+        )
+        .expect("Batch with a new module should succeed");
+        assert_eq!(summaries.len(), 2);
+        assert!(std::fs::read_to_string(&lib_rs_path)
+            .unwrap()
+            .starts_with("pub mod shapes;\n"));
+        assert!(src_dir.join("shapes/mod.rs").is_file());
+
+        // Creating a file that already exists is refused up front
+        let result = apply_batch_with_validation(
+            &module_batch("shapes", "2"),
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+        );
+        assert!(result.is_err());
+        assert!(std::fs::read_to_string(&lib_rs_path)
+            .unwrap()
+            .starts_with("pub mod shapes;\npub fn helper"));
+    }
+
+    #[test]
+    fn test_move_into_declared_module_skips_duplicate_mod() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\npath = \"src/lib.rs\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+        let lib_rs_path = src_dir.join("lib.rs");
+        let shapes_path = src_dir.join("shapes.rs");
+        let helper = "pub fn helper() -> i32 {\n    1\n}\n";
+        let source = format!("//! Geometry.\n\npub mod shapes;\n\n{}", helper);
+        std::fs::write(&lib_rs_path, &source).expect("Failed to write lib.rs");
+        std::fs::write(&shapes_path, "pub fn area() -> i32 {\n    2\n}\n")
+            .expect("Failed to write shapes.rs");
+
+        // lib.rs already declares `shapes`, so moving `helper` there adds no `mod`
+        let declaration = mod_declaration(&lib_rs_path, source.as_bytes(), "shapes", "pub")
+            .expect("lib.rs should parse");
+        assert!(declaration.is_none());
+
+        let helper_start = source.find(helper).unwrap();
+        let shapes_len = std::fs::metadata(&shapes_path).unwrap().len() as usize;
+        let mut replacements = vec![
+            SpanReplacement::new(
+                lib_rs_path.clone(),
+                helper_start,
+                source.len(),
+                String::new(),
+            ),
+            SpanReplacement::new(
+                shapes_path.clone(),
+                shapes_len,
+                shapes_len,
+                format!("\n{}", helper),
+            ),
+        ];
+        replacements.extend(declaration);
+        apply_batch_with_validation(
+            &[SpanBatch::new(replacements)],
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+        )
+        .expect("Move into an existing module should pass cargo check");
+
+        let lib_rs = std::fs::read_to_string(&lib_rs_path).unwrap();
+        assert_eq!(lib_rs.matches("mod shapes").count(), 1);
+        assert!(std::fs::read_to_string(&shapes_path)
+            .unwrap()
+            .contains("pub fn helper()"));
+
+        // A module that is not declared yet goes after the existing ones
+        let declaration = mod_declaration(&lib_rs_path, lib_rs.as_bytes(), "circles", "")
+            .expect("lib.rs should parse")
+            .expect("circles is not declared yet");
+        let end = lib_rs.find("pub mod shapes;").unwrap() + "pub mod shapes;".len();
+        assert_eq!(declaration.start, end);
+        assert_eq!(declaration.content, "\nmod circles;");
+    }
+
+    #[test]
+    fn test_batch_rollback_restores_before_hashes() {
+        use sha2::{Digest, Sha256};
+
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\npath = \"src/lib.rs\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+
+        // CRLF endings and a missing final newline must survive byte-for-byte
+        let files = [
+            ("lib.rs", "pub mod a;\r\npub mod b;\r\n"),
+            ("a.rs", "pub fn a() -> i32 {\r\n    1\r\n}"),
+            ("b.rs", "pub fn b() -> i32 {\n    2\n}\n"),
+        ];
+        let mut before_hashes = Vec::new();
+        for (name, content) in files {
+            let path = src_dir.join(name);
+            std::fs::write(&path, content).expect("Failed to write source file");
+            before_hashes.push((path, format!("{:x}", Sha256::digest(content.as_bytes()))));
+        }
+
+        // a.rs gets a valid edit, b.rs a type error, so cargo check fails after both are written
+        let batch = SpanBatch::new(vec![
+            SpanReplacement::new(src_dir.join("a.rs"), 25, 26, "10".to_string()),
+            SpanReplacement::new(src_dir.join("b.rs"), 24, 25, "\"two\"".to_string()),
+        ]);
+        let result = apply_batch_with_validation(
+            &[batch],
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+        );
+        assert!(
+            matches!(result, Err(splice::SpliceError::CargoCheckFailed { .. })),
+            "Expected CargoCheckFailed, got {:?}",
+            result
+        );
+
+        for (path, before_hash) in before_hashes {
+            let restored = std::fs::read(&path).expect("Failed to read restored file");
+            assert_eq!(
+                format!("{:x}", Sha256::digest(&restored)),
+                before_hash,
+                "{} does not match its before-hash after rollback",
+                path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn test_batch_parse_failure_names_the_broken_file() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\npath = \"src/lib.rs\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let src_dir = workspace_path.join("src");
+        std::fs::create_dir(&src_dir).expect("Failed to create src directory");
+
+        let names = ["a", "b", "c", "d", "e"];
+        let lib_source: String = names.iter().map(|n| format!("pub mod {};\n", n)).collect();
+        std::fs::write(src_dir.join("lib.rs"), lib_source).expect("Failed to write lib.rs");
+        let module_source = "pub fn value() -> i32 {\n    1\n}\n";
+        let body = module_source.find('1').unwrap();
+
+        let replacements = names
+            .iter()
+            .map(|name| {
+                let path = src_dir.join(format!("{}.rs", name));
+                std::fs::write(&path, module_source).expect("Failed to write module");
+                // Only c.rs loses its closing brace
+                let content = if *name == "c" { "2\n" } else { "2" };
+                let end = if *name == "c" { module_source.len() } else { body + 1 };
+                SpanReplacement::new(path, body, end, content.to_string())
+            })
+            .collect();
+
+        let result = apply_batch_with_validation(
+            &[SpanBatch::new(replacements)],
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+        );
+        match result {
+            Err(splice::SpliceError::ParseValidationFailed { file, message }) => {
+                assert_eq!(file, src_dir.join("c.rs"));
+                assert!(!message.contains("other file"), "message: {}", message);
+            }
+            other => panic!("Expected ParseValidationFailed, got {:?}", other),
+        }
+        for name in names {
+            let content = std::fs::read_to_string(src_dir.join(format!("{}.rs", name))).unwrap();
+            assert_eq!(content, module_source, "{}.rs was not rolled back", name);
+        }
+    }
+
+    #[test]
+    fn test_batch_parallel_validate_matches_serial_results() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        let module_source = "pub fn value() -> i32 {\n    1\n}\n";
+        let body = module_source.find('1').unwrap();
+        let paths: Vec<_> = (0..40)
+            .map(|i| {
+                let path = workspace_path.join(format!("m{:02}.rs", i));
+                std::fs::write(&path, module_source).expect("Failed to write module");
+                path
+            })
+            .collect();
+        let batch = |broken: &[usize]| {
+            let replacements = paths
+                .iter()
+                .enumerate()
+                .map(|(i, path)| {
+                    if broken.contains(&i) {
+                        SpanReplacement::new(path.clone(), body, module_source.len(), "2\n".into())
+                    } else {
+                        SpanReplacement::new(path.clone(), body, body + 1, "2".into())
+                    }
+                })
+                .collect();
+            SpanBatch::new(replacements)
+        };
+        let run = |broken: &[usize], parallel_validate: Option<usize>| {
+            let options = ValidationOptions {
+                gates: GateSet {
+                    tree_sitter: true,
+                    compiler: false,
+                    analyzer: false,
+                },
+                parallel_validate,
+                ..ValidationOptions::default()
+            };
+            let result = apply_batch_with_options(
+                &[batch(broken)],
+                workspace_path,
+                Language::Rust,
+                AnalyzerMode::Off,
+                &options,
+            );
+            format!("{:?}", result)
+        };
+
+        // Several broken files: same error, naming the same files, and a full rollback
+        let broken = [7, 19, 33];
+        let serial = run(&broken, None);
+        let parallel = run(&broken, Some(4));
+        assert!(serial.contains("ParseValidationFailed"), "{}", serial);
+        assert!(serial.contains("m07.rs") && serial.contains("m33.rs"));
+        assert_eq!(parallel, serial);
+        for path in &paths {
+            assert_eq!(std::fs::read_to_string(path).unwrap(), module_source);
+        }
+
+        // A clean batch succeeds either way, given the same starting bytes
+        let parallel = run(&[], Some(0));
+        assert!(parallel.starts_with("Ok("), "{}", parallel);
+        for path in &paths {
+            std::fs::write(path, module_source).expect("Failed to reset module");
+        }
+        assert_eq!(run(&[], None), parallel);
+    }
+
+    #[test]
+    fn test_whitespace_only_replacement_is_a_noop_when_normalized() {
+        // No Cargo.toml, so the compiler gate fails whenever it actually runs
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        let lib_path = workspace_path.join("lib.rs");
+        let source = "fn answer() -> i32 {\n    let x = \"a  b\";\n    42\n}\n";
+        std::fs::write(&lib_path, source).expect("Failed to write lib.rs");
+        let end = source.len() - 1;
+        let reindented = "fn answer() -> i32 {\n        let x = \"a  b\";\n        42\n}";
+        let normalized = ValidationOptions {
+            normalize_whitespace: true,
+            ..ValidationOptions::default()
+        };
+        let patch = |content: &str, options: &ValidationOptions| {
+            apply_patch_with_options(
+                &lib_path,
+                0,
+                end,
+                content,
+                workspace_path,
+                Language::Rust,
+                AnalyzerMode::Off,
+                options,
+            )
+        };
+
+        let (before_hash, after_hash) =
+            patch(reindented, &normalized).expect("Re-indenting should be a no-op");
+        assert_eq!(before_hash, after_hash);
+        assert_eq!(std::fs::read_to_string(&lib_path).unwrap(), source);
+
+        // Whitespace inside a string literal is content, so this is a real edit
+        let respaced = reindented.replace("a  b", "a b");
+        assert!(patch(&respaced, &normalized).is_err());
+
+        // Byte-exact comparison stays the default
+        assert!(patch(reindented, &ValidationOptions::default()).is_err());
+        assert_eq!(std::fs::read_to_string(&lib_path).unwrap(), source);
+    }
+
+    #[test]
+    fn test_batch_same_offset_and_abutting_edits_apply_in_input_order() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        let lib_path = workspace_path.join("lib.rs");
+        let source = "fn a() {}\nfn b() {}\n";
+        std::fs::write(&lib_path, source).expect("Failed to write lib.rs");
+        let options = ValidationOptions {
+            gates: GateSet {
+                tree_sitter: true,
+                compiler: false,
+                analyzer: false,
+            },
+            ..ValidationOptions::default()
+        };
+        let edit = |start: usize, end: usize, content: &str| {
+            SpanReplacement::new(lib_path.clone(), start, end, content.to_string())
+        };
+        let split = source.find("fn b").unwrap();
+
+        // Two insertions at one offset land in input order, and around the
+        // abutting replacements of `a` and `b` rather than inside them
+        let batch = SpanBatch::new(vec![
+            edit(split, source.len(), "fn b2() {}\n"),
+            edit(split, split, "// first\n"),
+            edit(0, split, "fn a2() {}\n"),
+            edit(split, split, "// second\n"),
+        ]);
+        apply_batch_with_options(
+            &[batch],
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+            &options,
+        )
+        .expect("Same-offset and abutting edits should apply");
+        assert_eq!(
+            std::fs::read_to_string(&lib_path).unwrap(),
+            "fn a2() {}\n// first\n// second\nfn b2() {}\n"
+        );
+
+        // Edits that share bytes are still refused, whatever their order
+        let batch = SpanBatch::new(vec![edit(4, 8, "x"), edit(0, 5, "y")]);
+        let result = apply_batch_with_options(
+            &[batch],
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+            &options,
+        );
+        assert!(result.is_err(), "overlap should be rejected: {:?}", result);
+    }
+
+    #[test]
+    fn test_format_after_leaves_insertion_rustfmt_clean() {
+        if std::process::Command::new("rustfmt").arg("--version").output().is_err() {
+            eprintln!("rustfmt not installed; skipping");
+            return;
+        }
+
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[package]\nname = \"temp-test\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write Cargo.toml");
+        let lib_path = workspace_path.join("lib.rs");
+        let source = "pub fn existing() -> i32 {\n    1\n}\n";
+        std::fs::write(&lib_path, source).expect("Failed to write lib.rs");
+
+        let (offset, text) = insertion_edit(
+            source,
+            0,
+            source.len() - 1,
+            "pub fn   added( x:i32 )->i32{x+1}",
+            InsertPosition::After,
+            Language::Rust,
+        );
+        let options = ValidationOptions {
+            format_after: true,
+            gates: GateSet {
+                tree_sitter: true,
+                compiler: false,
+                analyzer: false,
+            },
+            ..Default::default()
+        };
+        let (_, after_hash) = apply_patch_with_options(
+            &lib_path,
+            offset,
+            offset,
+            &text,
+            workspace_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+            &options,
+        )
+        .expect("Insertion should succeed");
+
+        let patched = std::fs::read_to_string(&lib_path).unwrap();
+        assert!(
+            patched.contains("pub fn added(x: i32) -> i32 {\n    x + 1\n}"),
+            "not formatted: {:?}",
+            patched
+        );
+        let check = std::process::Command::new("rustfmt")
+            .args(["--check", "--edition", "2021"])
+            .arg(&lib_path)
+            .output()
+            .expect("Failed to run rustfmt");
+        assert!(check.status.success(), "rustfmt --check failed: {:?}", check);
+
+        // The reported hash is of the formatted file
+        use sha2::{Digest, Sha256};
+        assert_eq!(after_hash, format!("{:x}", Sha256::digest(patched.as_bytes())));
+    }
+
+    /// Blank lines inside literals and comments are kept, and trimmed runs
+    /// keep the file's line endings.
+    #[test]
+    fn test_blank_line_replacements_skips_literals_and_keeps_crlf() {
+        let path = std::path::Path::new("lib.rs");
+        let source = "pub const TEXT: &str = \"a\n\n\n\nb\";\n\
+                      pub const RAW: &str = r#\"a\n\n\n\nb\"#;\n\
+                      /* a\n\n\n\nb */\n\
+                      pub fn kept() {}\n";
+        let file = SourceFile::from_bytes(path, Language::Rust, source.as_bytes().to_vec());
+        let replacements = blank_line_replacements(&file, 1).unwrap();
+        assert!(replacements.is_empty(), "{:?}", replacements);
+
+        let source = "pub fn first() {}\r\n\r\n\r\n\r\npub fn last() {}\r\n";
+        let file = SourceFile::from_bytes(path, Language::Rust, source.as_bytes().to_vec());
+        let replacements = blank_line_replacements(&file, 1).unwrap();
+        assert_eq!(replacements.len(), 1);
+        let patched = format!(
+            "{}{}{}",
+            &source[..replacements[0].start],
+            replacements[0].content,
+            &source[replacements[0].end..]
+        );
+        assert_eq!(patched, "pub fn first() {}\r\n\r\npub fn last() {}\r\n");
+    }
+
+    /// `--format-after` follows an edition inherited from the workspace root.
+    #[test]
+    fn test_format_after_reads_workspace_edition() {
+        if std::process::Command::new("rustfmt")
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            eprintln!("rustfmt not installed; skipping");
+            return;
+        }
+
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+        std::fs::write(
+            workspace_path.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"member\"]\n\n[workspace.package]\nedition = \"2021\"\n",
+        )
+        .expect("Failed to write workspace Cargo.toml");
+        let member_path = workspace_path.join("member");
+        std::fs::create_dir_all(member_path.join("src")).expect("Failed to create member");
+        std::fs::write(
+            member_path.join("Cargo.toml"),
+            "[package]\nname = \"member\"\nversion = \"0.1.0\"\nedition = { workspace = true }\n",
+        )
+        .expect("Failed to write member Cargo.toml");
+        let lib_path = member_path.join("src/lib.rs");
+        let source = "pub fn existing() -> i32 {\n    1\n}\n";
+        std::fs::write(&lib_path, source).expect("Failed to write lib.rs");
+
+        let options = ValidationOptions {
+            format_after: true,
+            gates: GateSet {
+                tree_sitter: true,
+                compiler: false,
+                analyzer: false,
+            },
+            ..Default::default()
+        };
+        apply_patch_with_options(
+            &lib_path,
+            source.len(),
+            source.len(),
+            "pub   async fn added( )->i32{1}\n",
+            &member_path,
+            Language::Rust,
+            AnalyzerMode::Off,
+            &options,
+        )
+        .expect("Insertion should succeed");
+
+        let patched = std::fs::read_to_string(&lib_path).unwrap();
+        assert!(
+            patched.contains("pub async fn added() -> i32 {\n    1\n}"),
+            "not formatted: {:?}",
+            patched
+        );
+    }
 }
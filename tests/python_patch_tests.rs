@@ -289,4 +289,118 @@ x = undefined_variable  # This will cause NameError
             );
         }
     }
+
+    /// Deleting the first function of a script keeps its shebang and header.
+    #[test]
+    fn test_python_shebang_spans_and_delete_first_function() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        let py_path = workspace_path.join("script.py");
+        let header = "#!/usr/bin/env python3\n# header\n\n";
+        let source = format!(
+            "{}def first():\n    return 1\n\ndef second():\n    return 2\n",
+            header
+        );
+        std::fs::write(&py_path, &source).expect("Failed to write script.py");
+
+        let symbols =
+            extract_python_symbols(&py_path, source.as_bytes()).expect("Failed to parse script.py");
+        let first = symbols.iter().find(|s| s.name == "first").unwrap();
+        assert_eq!(first.line_start, 4);
+        assert_eq!(first.byte_start, header.len());
+        assert!(source[first.byte_start..first.byte_end].starts_with("def first():"));
+
+        apply_patch_with_validation(
+            &py_path,
+            first.byte_start,
+            first.byte_end,
+            "",
+            workspace_path,
+            Language::Python,
+            AnalyzerMode::Off,
+        )
+        .expect("Delete should succeed");
+
+        let patched = std::fs::read_to_string(&py_path).unwrap();
+        assert!(patched.starts_with(header), "header disturbed: {:?}", patched);
+        assert!(!patched.contains("def first"));
+        assert!(patched.contains("def second():"));
+    }
+
+    #[test]
+    fn test_python_delete_decorated_function_removes_decorators() {
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let workspace_path = workspace_dir.path();
+
+        let py_path = workspace_path.join("app.py");
+        let source = "import functools\n\n\n@functools.lru_cache(maxsize=None)\n@staticmethod\ndef handler(path):\n    return path\n\n\nclass Service:\n    @property\n    def name(self):\n        return \"svc\"\n";
+        std::fs::write(&py_path, source).expect("Failed to write app.py");
+
+        let symbols =
+            extract_python_symbols(&py_path, source.as_bytes()).expect("Failed to parse app.py");
+        let handler = symbols.iter().find(|s| s.name == "handler").unwrap();
+        assert_eq!(handler.line_start, 4);
+        assert!(source[handler.byte_start..].starts_with("@functools.lru_cache"));
+
+        // Methods pick up their decorators too
+        let name = symbols.iter().find(|s| s.name == "name").unwrap();
+        assert!(source[name.byte_start..name.byte_end].starts_with("@property\n"));
+
+        apply_patch_with_validation(
+            &py_path,
+            handler.byte_start,
+            handler.byte_end,
+            "",
+            workspace_path,
+            Language::Python,
+            AnalyzerMode::Off,
+        )
+        .expect("Delete should succeed");
+
+        let patched = std::fs::read_to_string(&py_path).unwrap();
+        assert!(!patched.contains("lru_cache"));
+        assert!(!patched.contains("@staticmethod"));
+        assert!(!patched.contains("def handler"));
+        assert!(patched.contains("@property"));
+        let reparsed = extract_python_symbols(&py_path, patched.as_bytes())
+            .expect("Patched file should still parse");
+        assert!(reparsed.iter().any(|s| s.name == "Service"));
+    }
+
+    /// Patching an executable script keeps its mode, executable bit included.
+    #[cfg(unix)]
+    #[test]
+    fn test_python_patch_preserves_executable_bit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let workspace_dir = TempDir::new().expect("Failed to create temp workspace");
+        let script_path = workspace_dir.path().join("tool.py");
+        let source = "#!/usr/bin/env python3\n\ndef main():\n    print(\"old\")\n";
+        std::fs::write(&script_path, source).expect("Failed to write tool.py");
+        std::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o750))
+            .expect("Failed to make tool.py executable");
+
+        let symbols = extract_python_symbols(&script_path, source.as_bytes())
+            .expect("Failed to parse tool.py");
+        let main = &symbols[0];
+        let result = apply_patch_with_validation(
+            &script_path,
+            main.byte_start,
+            main.byte_end,
+            "def main():\n    print(\"new\")",
+            workspace_dir.path(),
+            Language::Python,
+            AnalyzerMode::Off,
+        );
+        assert!(result.is_ok(), "Patch should succeed: {:?}", result);
+
+        let patched = std::fs::read_to_string(&script_path).expect("Failed to read tool.py");
+        assert!(patched.contains("print(\"new\")"));
+        let mode = std::fs::metadata(&script_path)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o750);
+    }
 }
@@ -1,15 +1,26 @@
 //! Graph persistence and symbol resolution tests.
 
-use splice::graph::CodeGraph;
+use splice::graph::{CodeGraph, SymbolRecord};
 use splice::ingest::rust::{extract_rust_symbols, RustSymbolKind};
+use splice::ingest::ParseOptions;
+use splice::offset::PositionEncoding;
+use splice::resolve::hover::hover;
+use splice::resolve::references::{
+    find_references, find_references_with_options, ReferenceContext, ReferenceScanOptions,
+};
+use splice::resolve::{
+    ensure_resolved_in_file, resolve_symbol, resolve_symbol_in_scope, resolve_symbol_with_signature,
+};
+use splice::symbol::{Language, Symbol};
 use std::io::Write;
-use tempfile::NamedTempFile;
+use tempfile::{Builder, NamedTempFile};
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
+    #[allow(deprecated)]
     fn test_store_and_retrieve_symbol_spans() {
         // Create a temporary Rust file
         let source = r#"
@@ -94,4 +105,620 @@ fn goodbye() {
         // Should return an error
         assert!(result.is_err(), "Expected error for nonexistent symbol");
     }
+
+    #[test]
+    fn test_resolve_reports_symbol_visibility() {
+        let source = "pub(crate) fn internal() {}\n\npub fn exported() {}\n\nfn hidden() {}\n";
+        let file_path = std::path::Path::new("visibility.rs");
+
+        let graph_db = NamedTempFile::new().expect("Failed to create temp db");
+        let mut code_graph = CodeGraph::open(graph_db.path()).expect("Failed to open graph");
+
+        let symbols =
+            extract_rust_symbols(file_path, source.as_bytes()).expect("Failed to parse Rust file");
+        for symbol in &symbols {
+            code_graph
+                .store_symbol_record(file_path, &SymbolRecord::of(symbol))
+                .expect("Failed to store symbol");
+        }
+
+        let visibility_of = |name: &str| {
+            resolve_symbol(&code_graph, Some(file_path), Some("function"), name)
+                .expect("Failed to resolve symbol")
+                .visibility
+        };
+
+        assert_eq!(visibility_of("internal").as_deref(), Some("pub(crate)"));
+        assert_eq!(visibility_of("exported").as_deref(), Some("pub"));
+        assert_eq!(visibility_of("hidden").as_deref(), Some("private"));
+    }
+
+    #[test]
+    fn test_delete_single_reexport_reports_dependent_references() {
+        // Workspace scanning skips hidden directories, so avoid the default `.tmp` prefix
+        let workspace = Builder::new()
+            .prefix("reexport")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"reexports\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/lib.rs"), "pub mod api;\npub mod consumer;\npub mod utils;\n")
+            .unwrap();
+        std::fs::write(root.join("src/utils.rs"), "pub struct Bar;\n").unwrap();
+        let api_source = "pub use crate::utils::Bar as Baz;\npub use crate::utils::Bar;\n";
+        let api_path = root.join("src/api.rs");
+        std::fs::write(&api_path, api_source).unwrap();
+        std::fs::write(
+            root.join("src/consumer.rs"),
+            "use crate::api::Baz;\n\npub fn make() -> Baz {\n    Baz\n}\n",
+        )
+        .unwrap();
+
+        let symbols = extract_rust_symbols(&api_path, api_source.as_bytes()).unwrap();
+        let reexport = symbols
+            .iter()
+            .find(|s| s.name == "Baz")
+            .expect("Re-export should be addressable");
+        assert_eq!(reexport.kind, RustSymbolKind::Reexport);
+        assert_eq!(reexport.kind.as_str(), "reexport");
+        assert_eq!(
+            &api_source[reexport.byte_start..reexport.byte_end],
+            "pub use crate::utils::Bar as Baz;"
+        );
+
+        let graph_db = NamedTempFile::new().expect("Failed to create temp db");
+        let code_graph = CodeGraph::open(graph_db.path()).expect("Failed to open graph database");
+        let ref_set = find_references(&code_graph, &api_path, "Baz", Some(RustSymbolKind::Reexport))
+            .expect("Failed to find references");
+
+        // The import, the return type and the value expression in consumer.rs
+        let mut lines: Vec<usize> = ref_set.references.iter().map(|r| r.line).collect();
+        lines.sort();
+        assert_eq!(lines, vec![1, 3, 4]);
+        assert!(ref_set
+            .references
+            .iter()
+            .all(|r| r.file_path.ends_with("consumer.rs")));
+
+        // Deleting the definition span removes only that re-export line
+        let mut remaining = api_source.to_string();
+        remaining.replace_range(
+            ref_set.definition.byte_start..ref_set.definition.byte_end,
+            "",
+        );
+        assert_eq!(remaining.trim(), "pub use crate::utils::Bar;");
+    }
+
+    #[test]
+    fn test_references_follow_two_hop_reexport_chain() {
+        let workspace = Builder::new()
+            .prefix("reexport_chain")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace.path();
+        std::fs::create_dir_all(root.join("src/inner")).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"chain\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "pub mod app;\npub mod facade;\npub mod inner;\n",
+        )
+        .unwrap();
+        let defs_source = "pub fn helper() -> i32 {\n    1\n}\n";
+        let defs_path = root.join("src/inner/defs.rs");
+        std::fs::write(&defs_path, defs_source).unwrap();
+        // defs -> inner -> facade: two hops before the use at the far end
+        std::fs::write(
+            root.join("src/inner/mod.rs"),
+            "pub mod defs;\npub use defs::helper;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/facade.rs"),
+            "pub use crate::inner::helper;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/app.rs"),
+            "use super::facade::helper;\n\npub fn run() -> i32 {\n    helper()\n}\n",
+        )
+        .unwrap();
+
+        let graph_db = NamedTempFile::new().expect("Failed to create temp db");
+        let code_graph = CodeGraph::open(graph_db.path()).expect("Failed to open graph database");
+        let ref_set = find_references(
+            &code_graph,
+            &defs_path,
+            "helper",
+            Some(RustSymbolKind::Function),
+        )
+        .expect("Failed to find references");
+
+        let mut app_lines: Vec<usize> = ref_set
+            .references
+            .iter()
+            .filter(|r| r.file_path.ends_with("app.rs"))
+            .map(|r| r.line)
+            .collect();
+        app_lines.sort();
+        assert_eq!(app_lines, vec![1, 4], "got: {:?}", ref_set.references);
+    }
+
+    #[test]
+    fn test_rename_through_aliased_import_only_touches_use_path() {
+        let workspace = Builder::new()
+            .prefix("alias")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"aliases\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/lib.rs"), "pub mod foo;\npub mod user;\n").unwrap();
+        let foo_path = root.join("src/foo.rs");
+        std::fs::write(&foo_path, "pub fn bar() {}\n").unwrap();
+        let user_path = root.join("src/user.rs");
+        // The local `bar` binding is unrelated to `foo::bar`
+        let user_source = "use crate::foo::bar as baz;\n\npub fn run() {\n    baz();\n    let bar = 1;\n    let _ = bar + 1;\n}\n";
+        std::fs::write(&user_path, user_source).unwrap();
+
+        let graph_db = NamedTempFile::new().expect("Failed to create temp db");
+        let code_graph = CodeGraph::open(graph_db.path()).expect("Failed to open graph database");
+        let ref_set = find_references(&code_graph, &foo_path, "bar", None)
+            .expect("Failed to find references");
+
+        let user_refs: Vec<_> = ref_set
+            .references
+            .iter()
+            .filter(|r| r.file_path.ends_with("user.rs"))
+            .collect();
+        assert_eq!(user_refs.len(), 2, "the use path and the `baz()` call");
+        let (alias_refs, user_refs): (Vec<_>, Vec<_>) =
+            user_refs.into_iter().partition(|r| r.alias.is_some());
+        assert_eq!(alias_refs.len(), 1);
+        assert_eq!(alias_refs[0].alias.as_deref(), Some("baz"));
+        assert_eq!(alias_refs[0].line, 4);
+
+        // Apply the rename back-to-front, as a rename would, leaving alias usages alone
+        let mut renamed = user_source.to_string();
+        for r in &user_refs {
+            renamed.replace_range(r.byte_start..r.byte_end, "renamed");
+        }
+        assert_eq!(
+            renamed,
+            "use crate::foo::renamed as baz;\n\npub fn run() {\n    baz();\n    let bar = 1;\n    let _ = bar + 1;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_references_found_through_use_as_alias() {
+        let workspace = Builder::new()
+            .prefix("alias")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"aliases\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/lib.rs"), "pub mod utils;\npub mod app;\n").unwrap();
+        let utils_path = root.join("src/utils.rs");
+        std::fs::write(&utils_path, "pub fn helper() -> i32 {\n    1\n}\n").unwrap();
+        let app_source = "use crate::utils::helper as h;\n\npub fn run() -> i32 {\n    h() + h()\n}\n\npub fn pick() -> fn() -> i32 {\n    h\n}\n";
+        std::fs::write(root.join("src/app.rs"), app_source).unwrap();
+
+        let graph_db = NamedTempFile::new().expect("Failed to create temp db");
+        let code_graph = CodeGraph::open(graph_db.path()).expect("Failed to open graph database");
+        let ref_set = find_references(&code_graph, &utils_path, "helper", None)
+            .expect("Failed to find references");
+
+        let mut alias_lines: Vec<usize> = ref_set
+            .references
+            .iter()
+            .filter(|r| r.file_path.ends_with("app.rs") && r.alias.as_deref() == Some("h"))
+            .map(|r| r.line)
+            .collect();
+        alias_lines.sort_unstable();
+        assert_eq!(alias_lines, vec![4, 4, 8], "both calls and the fn item use `h`");
+        for r in ref_set.references.iter().filter(|r| r.alias.is_some()) {
+            assert_eq!(&app_source[r.byte_start..r.byte_end], "h");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_workspace_file_is_reported_as_skipped() {
+        let workspace = Builder::new()
+            .prefix("skipped")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let root = workspace.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"skipped\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        let lib_path = root.join("src/lib.rs");
+        std::fs::write(&lib_path, "pub mod broken;\npub fn helper() {}\n").unwrap();
+        // A dangling symlink can't be read, even when running as root
+        let broken = root.join("src/broken.rs");
+        std::os::unix::fs::symlink(root.join("missing.rs"), &broken).unwrap();
+
+        let graph_db = NamedTempFile::new().expect("Failed to create temp db");
+        let code_graph = CodeGraph::open(graph_db.path()).expect("Failed to open graph database");
+        let ref_set =
+            find_references(&code_graph, &lib_path, "helper", None).expect("Failed to find references");
+
+        assert_eq!(ref_set.skipped_files.len(), 1);
+        let (path, reason) = &ref_set.skipped_files[0];
+        assert_eq!(path, &broken);
+        assert!(reason.starts_with("unreadable"), "unexpected reason: {}", reason);
+    }
+
+    #[test]
+    fn test_java_overloads_resolved_by_signature() {
+        use splice::ingest::java::extract_java_symbols;
+
+        let source = "public class Calc {\n    int add(int x) { return x; }\n    String add(String s, int... rest) { return s; }\n}\n";
+        let path = std::path::Path::new("/tmp/Calc.java");
+        let symbols = extract_java_symbols(path, source.as_bytes()).unwrap();
+        let adds: Vec<_> = symbols.iter().filter(|s| s.name == "add").collect();
+        assert_eq!(adds[0].parameter_types, vec!["int"]);
+        assert_eq!(adds[1].parameter_types, vec!["String", "int..."]);
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut graph = CodeGraph::open(temp.path()).unwrap();
+        for symbol in &symbols {
+            let signature = symbol.signature();
+            let record = SymbolRecord {
+                signature: signature.as_deref(),
+                ..SymbolRecord::of(symbol)
+            };
+            graph.store_symbol_record(path, &record).unwrap();
+        }
+
+        // Without a signature the overloads are listed in the error
+        match resolve_symbol(&graph, Some(path), Some("method"), "add") {
+            Err(splice::SpliceError::AmbiguousSymbol { signatures, .. }) => {
+                assert_eq!(signatures, vec!["add(int)", "add(String, int...)"]);
+            }
+            other => panic!("expected AmbiguousSymbol, got {:?}", other),
+        }
+
+        // Whitespace in the filter does not matter
+        let resolved = resolve_symbol_with_signature(
+            &graph,
+            Some(path),
+            None,
+            "add",
+            Some("( String,int... )"),
+        )
+        .unwrap();
+        assert_eq!(resolved.byte_start, adds[1].byte_start);
+        assert_eq!(resolved.signature.as_deref(), Some("(String, int...)"));
+
+        let resolved =
+            resolve_symbol_with_signature(&graph, Some(path), None, "add", Some("(int)")).unwrap();
+        assert_eq!(resolved.byte_start, adds[0].byte_start);
+
+        let missing = resolve_symbol_with_signature(&graph, Some(path), None, "add", Some("(long)"));
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_resolved_file_mismatch_is_refused() {
+        let dir = Builder::new()
+            .prefix("mismatch")
+            .tempdir()
+            .expect("Failed to create temp workspace");
+        let lib_path = dir.path().join("lib.rs");
+        let other_path = dir.path().join("other.rs");
+        let source = "pub fn target() {}\n";
+        std::fs::write(&lib_path, source).unwrap();
+        std::fs::write(&other_path, source).unwrap();
+
+        let graph_db = NamedTempFile::new().expect("Failed to create temp db");
+        let mut code_graph = CodeGraph::open(graph_db.path()).expect("Failed to open graph");
+        code_graph
+            .store_symbol_with_file_and_language(
+                &lib_path,
+                "target",
+                "function",
+                Language::Rust,
+                0,
+                source.len() - 1,
+            )
+            .expect("Failed to store symbol");
+
+        let mut resolved = resolve_symbol(&code_graph, Some(&lib_path), None, "target")
+            .expect("Failed to resolve symbol");
+
+        // The same file spelled differently still matches
+        let dotted = dir.path().join(".").join("lib.rs");
+        assert!(ensure_resolved_in_file(&resolved, &dotted).is_ok());
+
+        // A node whose stored path points elsewhere must not be patched
+        resolved.file_path = other_path.to_string_lossy().to_string();
+        let err = ensure_resolved_in_file(&resolved, &lib_path).unwrap_err();
+        assert_eq!(err.kind(), "Other");
+        let message = err.to_string();
+        assert!(message.contains("other.rs"), "message: {}", message);
+        assert!(message.contains("lib.rs"), "message: {}", message);
+    }
+
+    #[test]
+    fn test_test_module_helper_resolves_separately_from_top_level_helper() {
+        let source = "#[cfg(test)]\nmod tests {\n    fn helper() -> u8 { 2 }\n\n    #[test]\n    fn checks() { assert_eq!(helper(), 2); }\n}\n\nfn helper() -> u8 { 1 }\n";
+        let path = std::path::Path::new("/tmp/helpers.rs");
+        let symbols = extract_rust_symbols(path, source.as_bytes()).unwrap();
+        let flags: Vec<(&str, bool)> = symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.in_test))
+            .collect();
+        assert_eq!(
+            flags,
+            vec![
+                ("tests", true),
+                ("helper", true),
+                ("checks", true),
+                ("helper", false)
+            ]
+        );
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut graph = CodeGraph::open(temp.path()).unwrap();
+        graph.store_file_symbols(path, &symbols).unwrap();
+
+        let top_level = source.rfind("fn helper").unwrap();
+        let in_tests = source.find("fn helper").unwrap();
+
+        // Without a selector the regular symbol wins, even though the test module comes first
+        let resolved = resolve_symbol(&graph, Some(path), None, "helper").unwrap();
+        assert_eq!(resolved.byte_start, top_level);
+        assert!(!resolved.in_test);
+
+        let resolved =
+            resolve_symbol_in_scope(&graph, Some(path), None, "helper", None, Some(true)).unwrap();
+        assert_eq!(resolved.byte_start, in_tests);
+        assert!(resolved.in_test);
+
+        let resolved =
+            resolve_symbol_in_scope(&graph, Some(path), None, "helper", None, Some(false)).unwrap();
+        assert_eq!(resolved.byte_start, top_level);
+
+        // Name-only resolution is no longer ambiguous between the two
+        let resolved = resolve_symbol(&graph, None, None, "helper").unwrap();
+        assert_eq!(resolved.byte_start, top_level);
+        let resolved =
+            resolve_symbol_in_scope(&graph, None, None, "helper", None, Some(true)).unwrap();
+        assert_eq!(resolved.byte_start, in_tests);
+    }
+
+    #[test]
+    fn test_lazy_static_symbol_reports_macro_generated_error() {
+        let source = "use std::collections::HashMap;\n\nlazy_static! {\n    static ref REGISTRY: HashMap<u32, String> = HashMap::new();\n}\n\nfn lookup(id: u32) -> Option<&'static String> {\n    REGISTRY.get(&id)\n}\n";
+        let mut file = Builder::new().suffix(".rs").tempfile().unwrap();
+        file.write_all(source.as_bytes()).unwrap();
+        let path = file.path();
+        let symbols = extract_rust_symbols(path, source.as_bytes()).unwrap();
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut graph = CodeGraph::open(temp.path()).unwrap();
+        graph.store_file_symbols(path, &symbols).unwrap();
+
+        match resolve_symbol(&graph, Some(path), None, "REGISTRY") {
+            Err(err @ splice::SpliceError::MacroGeneratedSymbol { .. }) => {
+                assert_eq!(err.exit_code(), 3);
+                assert!(err.to_string().contains("`lazy_static!`"), "{}", err);
+            }
+            other => panic!("expected MacroGeneratedSymbol, got {:?}", other),
+        }
+
+        // Only used, never defined, inside a macro call: still plainly not found
+        match resolve_symbol(&graph, Some(path), None, "HashMap") {
+            Err(splice::SpliceError::SymbolNotFound { .. }) => {}
+            other => panic!("expected SymbolNotFound, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_name_shared_by_two_kinds_lists_each_kind() {
+        let source = "pub struct Parser {\n    depth: usize,\n}\n\nimpl Parser {\n    pub fn new() -> Self {\n        Parser { depth: 0 }\n    }\n}\n\nmod parser {}\n\n#[allow(non_snake_case)]\nfn Parser() {}\n";
+        let mut file = Builder::new().suffix(".rs").tempfile().unwrap();
+        file.write_all(source.as_bytes()).unwrap();
+        let path = file.path();
+        let symbols = extract_rust_symbols(path, source.as_bytes()).unwrap();
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut graph = CodeGraph::open(temp.path()).unwrap();
+        graph.store_file_symbols(path, &symbols).unwrap();
+
+        let file_str = path.to_str().unwrap();
+        let err = resolve_symbol(&graph, Some(path), None, "Parser").unwrap_err();
+        assert!(err.to_string().contains("function at"), "{}", err);
+        assert_eq!(err.hint(), Some("Pass --kind with one of the listed kinds"));
+        match err {
+            splice::SpliceError::AmbiguousSymbol { kinds, .. } => {
+                // The impl block belongs to the struct and is not a candidate
+                assert_eq!(
+                    kinds,
+                    vec![
+                        format!("struct at {}:1", file_str),
+                        format!("function at {}:14", file_str)
+                    ]
+                );
+            }
+            other => panic!("expected AmbiguousSymbol, got {:?}", other),
+        }
+
+        // A kind picks one, and a name with a single kind is unaffected
+        let resolved = resolve_symbol(&graph, Some(path), Some("function"), "Parser").unwrap();
+        assert_eq!(resolved.kind, "function");
+        let resolved = resolve_symbol(&graph, Some(path), None, "parser").unwrap();
+        assert_eq!(resolved.kind, "module");
+    }
+
+    #[test]
+    fn test_tsx_references_include_jsx_elements_and_expressions() {
+        let source = "function Foo() {\n  return <div />;\n}\n\nfunction helper() {\n  return 1;\n}\n\nexport function App() {\n  const shadow = (helper: number) => helper;\n  return <main><Foo title={helper()} />{helper()}<Foo></Foo></main>;\n}\n";
+        let mut file = Builder::new().suffix(".tsx").tempfile().unwrap();
+        file.write_all(source.as_bytes()).unwrap();
+        let temp = NamedTempFile::new().unwrap();
+        let graph = CodeGraph::open(temp.path()).unwrap();
+
+        let found = |name: &str| -> Vec<(usize, usize, ReferenceContext)> {
+            let mut refs = find_references(&graph, file.path(), name, None)
+                .unwrap()
+                .references;
+            refs.sort_by_key(|r| r.byte_start);
+            refs.into_iter()
+                .map(|r| (r.line, r.column_byte, r.context))
+                .collect()
+        };
+
+        // Opening, closing and self-closing tags all name the component
+        assert_eq!(
+            found("Foo"),
+            vec![
+                (11, 16, ReferenceContext::JsxElement),
+                (11, 50, ReferenceContext::JsxElement),
+                (11, 56, ReferenceContext::JsxElement),
+            ]
+        );
+        // Calls inside JSX expressions count, the shadowing parameter does not
+        let call = ReferenceContext::FunctionCall {
+            is_qualified: false,
+        };
+        assert_eq!(
+            found("helper"),
+            vec![(11, 27, call.clone()), (11, 40, call)]
+        );
+    }
+
+    #[test]
+    fn test_since_mtime_scans_only_recently_modified_files() {
+        use std::time::{Duration, SystemTime};
+
+        let workspace = Builder::new().prefix("mtime").tempdir().unwrap();
+        let root = workspace.path();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"mtime\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        let lib_path = root.join("src/lib.rs");
+        std::fs::write(&lib_path, "pub mod a;\npub mod b;\n\npub fn helper() {}\n").unwrap();
+        let user = "use crate::helper;\n\npub fn call() {\n    helper();\n}\n";
+        std::fs::write(root.join("src/a.rs"), user).unwrap();
+        std::fs::write(root.join("src/b.rs"), user).unwrap();
+
+        let since = SystemTime::now();
+        let set_mtime = |name: &str, time: SystemTime| {
+            let file = std::fs::File::options()
+                .write(true)
+                .open(root.join("src").join(name))
+                .unwrap();
+            file.set_modified(time).unwrap();
+        };
+        set_mtime("a.rs", since + Duration::from_secs(3600));
+        set_mtime("b.rs", since - Duration::from_secs(3600));
+
+        let temp = NamedTempFile::new().unwrap();
+        let graph = CodeGraph::open(temp.path()).unwrap();
+        let files = |since| {
+            let options = ReferenceScanOptions {
+                max_file_size: u64::MAX,
+                since,
+                ..ReferenceScanOptions::default()
+            };
+            let ref_set =
+                find_references_with_options(&graph, &lib_path, "helper", None, &options).unwrap();
+            let mut files: Vec<String> = ref_set
+                .references
+                .iter()
+                .map(|r| r.file_path.rsplit('/').next().unwrap().to_string())
+                .collect();
+            files.sort();
+            files.dedup();
+            files
+        };
+
+        assert_eq!(files(None), vec!["a.rs", "b.rs"]);
+        assert_eq!(files(Some(since)), vec!["a.rs"]);
+    }
+
+    #[test]
+    fn test_removed_symbols_and_files_no_longer_resolve() {
+        let source = "fn keep() {}\n\nfn drop_me() {}\n";
+        let path = std::path::Path::new("/tmp/removal.rs");
+        let symbols = extract_rust_symbols(path, source.as_bytes()).unwrap();
+
+        let temp = NamedTempFile::new().unwrap();
+        let mut graph = CodeGraph::open(temp.path()).unwrap();
+        graph.store_file_symbols(path, &symbols).unwrap();
+        assert_eq!(graph.node_ids().unwrap().len(), 3);
+
+        let dropped = resolve_symbol(&graph, Some(path), None, "drop_me").unwrap();
+        graph.remove_symbol(dropped.node_id).unwrap();
+        match resolve_symbol(&graph, Some(path), None, "drop_me") {
+            Err(splice::SpliceError::SymbolNotFound { .. }) => {}
+            other => panic!("expected SymbolNotFound, got {:?}", other),
+        }
+        assert!(graph.inner().get_node(dropped.node_id.as_i64()).is_err());
+        assert!(resolve_symbol(&graph, Some(path), None, "keep").is_ok());
+
+        // A reopened graph finds the remaining nodes without the in-memory cache
+        let mut graph = CodeGraph::open(temp.path()).unwrap();
+        assert_eq!(graph.remove_file(path).unwrap(), 1);
+        assert!(graph.node_ids().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_hover_inside_method_returns_method_without_graph() {
+        let dir = Builder::new().prefix("hover").tempdir().unwrap();
+        let path = dir.path().join("shapes.py");
+        let source = "class Circle:\n    def area(self):\n        return 3.14 * self.r * self.r\n\n    def scale(self, k):\n        self.r *= k\n";
+        std::fs::write(&path, source).unwrap();
+
+        // Cursor on `self.r` in the body of `area`
+        let options = ParseOptions::default();
+        let found = hover(&path, 3, 22, None, PositionEncoding::Utf8, None, &options).unwrap();
+        assert_eq!(found.name, "area");
+        assert_eq!(found.kind, "function");
+        assert_eq!(found.qualified_name, "module::Circle::area");
+        assert_eq!(found.language, "python");
+        assert_eq!(found.byte_start, source.find("def area").unwrap());
+        assert_eq!(found.byte_end, source.find("\n\n").unwrap());
+        assert_eq!((found.line_start, found.line_end), (2, 3));
+
+        let class = hover(
+            &path,
+            3,
+            22,
+            None,
+            PositionEncoding::Utf8,
+            Some("class"),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(class.name, "Circle");
+
+        // Nothing is written next to the file
+        assert!(!dir.path().join(".splice_graph.db").exists());
+    }
 }
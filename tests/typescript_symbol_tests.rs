@@ -217,4 +217,46 @@ mod tests {
         assert_eq!(symbols[0].name, "greet");
         assert_eq!(symbols[0].kind, TypeScriptSymbolKind::Variable);
     }
+
+    #[test]
+    fn test_resolve_interface_and_type_alias_by_name() {
+        use splice::graph::CodeGraph;
+        use splice::resolve::resolve_symbol;
+        use splice::symbol::Language;
+
+        let source = "export interface Shape<T> {\n  area(): T;\n}\n\nexport type Area = number;\n\nclass Square implements Shape<Area> {\n  area(): Area { return 1; }\n}\n";
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let ts_path = dir.path().join("shapes.ts");
+        std::fs::write(&ts_path, source).expect("Failed to write shapes.ts");
+
+        let symbols =
+            extract_typescript_symbols(&ts_path, source.as_bytes()).expect("Failed to parse");
+        let mut code_graph =
+            CodeGraph::open(&dir.path().join("graph.db")).expect("Failed to open graph database");
+        for symbol in &symbols {
+            code_graph
+                .store_symbol_with_file_and_language(
+                    &ts_path,
+                    &symbol.name,
+                    symbol.kind.as_str(),
+                    Language::TypeScript,
+                    symbol.byte_start,
+                    symbol.byte_end,
+                )
+                .expect("Failed to store symbol");
+        }
+
+        let shape = resolve_symbol(&code_graph, Some(&ts_path), Some("interface"), "Shape")
+            .expect("Failed to resolve interface");
+        assert_eq!(shape.kind, "interface");
+        assert_eq!(
+            &source[shape.byte_start..shape.byte_end],
+            "interface Shape<T> {\n  area(): T;\n}"
+        );
+
+        let area = resolve_symbol(&code_graph, Some(&ts_path), None, "Area")
+            .expect("Failed to resolve type alias");
+        assert_eq!(area.kind, "type_alias");
+        assert_eq!(&source[area.byte_start..area.byte_end], "type Area = number;");
+    }
 }
@@ -78,4 +78,28 @@ error: aborting due to previous error
         assert_eq!(errors[0].line, 15);
         assert_eq!(errors[0].column, 10);
     }
+
+    #[test]
+    fn test_parse_denied_warning_is_error() {
+        let output = r#"warning: unused variable: `x`
+ --> src/lib.rs:3:9
+  |
+3 |     let x = 1;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`
+  |
+note: the lint level is defined here
+ --> src/lib.rs:1:9
+  |
+1 | #![deny(warnings)]
+  |         ^^^^^^^^
+  = note: `#[deny(unused_variables)]` implied by `#[deny(warnings)]`
+warning: unused import: `std::fmt`
+ --> src/lib.rs:2:5"#;
+
+        let errors = parse_cargo_output(output);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].level, ErrorLevel::Error);
+        assert!(errors[0].message.contains("unused variable"));
+        assert_eq!(errors[1].level, ErrorLevel::Warning);
+    }
 }